@@ -0,0 +1,102 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use hyprspaces::hyprctl::{
+    BatchCache, SwitchStyle, paired_switch_batch_with_focus, rebalance_batch,
+};
+use hyprspaces::layout;
+
+const ITERATIONS: u32 = 100_000;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Prints the timing and returns the allocations per iteration.
+fn bench(name: &str, mut run: impl FnMut(u32)) -> f64 {
+    run(0);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for iteration in 0..ITERATIONS {
+        run(iteration);
+    }
+    let elapsed = start.elapsed();
+    let allocs_per_iter =
+        (ALLOCATIONS.load(Ordering::Relaxed) - allocations) as f64 / f64::from(ITERATIONS);
+    println!(
+        "{name:<24} {:>8.1} ns/iter {allocs_per_iter:>8.2} allocs/iter",
+        elapsed.as_nanos() as f64 / f64::from(ITERATIONS),
+    );
+    allocs_per_iter
+}
+
+/// Cached batches are rendered into a reused buffer and must not allocate.
+fn bench_cached(name: &str, run: impl FnMut(u32)) {
+    let allocs_per_iter = bench(name, run);
+    assert!(
+        allocs_per_iter < 1.0,
+        "{name} allocated {allocs_per_iter:.2} times per iteration"
+    );
+}
+
+fn main() {
+    bench("paired_switch/uncached", |iteration| {
+        black_box(paired_switch_batch_with_focus(
+            "DP-1",
+            "HDMI-A-1",
+            iteration % 10 + 1,
+            10,
+            "DP-1",
+        ));
+    });
+
+    let mut cache = BatchCache::default();
+    bench_cached("paired_switch/cached", |iteration| {
+        black_box(cache.paired_switch(
+            &layout::Layout::paired("DP-1", "HDMI-A-1", 10),
+            iteration % 10 + 1,
+            "DP-1",
+            SwitchStyle::Full,
+        ));
+    });
+
+    let mut cache = BatchCache::default();
+    bench_cached("paired_switch/fast", |iteration| {
+        black_box(cache.paired_switch(
+            &layout::Layout::paired("DP-1", "HDMI-A-1", 10),
+            iteration % 10 + 1,
+            "DP-1",
+            SwitchStyle::Fast,
+        ));
+    });
+
+    bench("rebalance/uncached", |_| {
+        black_box(rebalance_batch("DP-1", "HDMI-A-1", 10));
+    });
+
+    let mut cache = BatchCache::default();
+    bench_cached("rebalance/cached", |_| {
+        black_box(cache.rebalance(&layout::Layout::paired("DP-1", "HDMI-A-1", 10)));
+    });
+}
@@ -2,7 +2,9 @@
 set -euo pipefail
 
 features=()
-if [[ -n "${CARGO_TEST_FEATURES:-}" ]]; then
+if [[ "${CARGO_TEST_FEATURES:-}" == "no-default" ]]; then
+  features=(--no-default-features)
+elif [[ -n "${CARGO_TEST_FEATURES:-}" ]]; then
   features=(--features "${CARGO_TEST_FEATURES}")
 fi
 cargo test "${features[@]}"
@@ -0,0 +1,142 @@
+use crate::config::CONFIG_SCHEMA_VERSION;
+use crate::control::{CONTROL_PROTOCOL_VERSION, ControlCommand};
+use crate::version;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapabilityManifest {
+    pub version: &'static str,
+    pub features: Vec<&'static str>,
+    pub commands: Vec<String>,
+    pub control_commands: Vec<&'static str>,
+    pub config_schema_version: u32,
+    pub control_protocol_version: u32,
+}
+
+impl CapabilityManifest {
+    pub fn new(cli: &clap::Command) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            features: version::enabled_features(),
+            commands: command_paths(cli),
+            control_commands: ControlCommand::ALL
+                .iter()
+                .map(ControlCommand::as_str)
+                .collect(),
+            config_schema_version: CONFIG_SCHEMA_VERSION,
+            control_protocol_version: CONTROL_PROTOCOL_VERSION,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "version": self.version,
+            "features": self.features,
+            "commands": self.commands,
+            "control_commands": self.control_commands,
+            "config_schema_version": self.config_schema_version,
+            "control_protocol_version": self.control_protocol_version,
+        })
+        .to_string()
+    }
+
+    pub fn summary(&self) -> String {
+        let features = if self.features.is_empty() {
+            "none".to_string()
+        } else {
+            self.features.join(", ")
+        };
+        let mut lines = vec![
+            format!("Version: {}", self.version),
+            format!("Features: {features}"),
+            format!("Config schema: {}", self.config_schema_version),
+            format!(
+                "Control protocol: {} ({})",
+                self.control_protocol_version,
+                self.control_commands.join(", ")
+            ),
+            "Commands:".to_string(),
+        ];
+        lines.extend(self.commands.iter().map(|command| format!("  {command}")));
+        lines.join("\n")
+    }
+}
+
+pub fn command_paths(cli: &clap::Command) -> Vec<String> {
+    let mut paths = Vec::new();
+    collect_paths(cli, "", &mut paths);
+    paths
+}
+
+fn collect_paths(command: &clap::Command, prefix: &str, paths: &mut Vec<String>) {
+    for subcommand in command.get_subcommands() {
+        let name = subcommand.get_name();
+        if name == "help" {
+            continue;
+        }
+        let path = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{prefix} {name}")
+        };
+        if subcommand.has_subcommands() && !subcommand.is_args_conflicts_with_subcommands_set() {
+            collect_paths(subcommand, &path, paths);
+        } else {
+            paths.push(path.clone());
+            collect_paths(subcommand, &path, paths);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CapabilityManifest, command_paths};
+
+    fn cli() -> clap::Command {
+        clap::Command::new("hyprspaces")
+            .subcommand(
+                clap::Command::new("paired")
+                    .subcommand(clap::Command::new("switch"))
+                    .subcommand(clap::Command::new("cycle")),
+            )
+            .subcommand(
+                clap::Command::new("daemon")
+                    .args_conflicts_with_subcommands(true)
+                    .subcommand(clap::Command::new("stop")),
+            )
+            .subcommand(clap::Command::new("status"))
+    }
+
+    #[test]
+    fn lists_leaf_and_runnable_command_paths() {
+        assert_eq!(
+            command_paths(&cli()),
+            vec![
+                "paired switch",
+                "paired cycle",
+                "daemon",
+                "daemon stop",
+                "status"
+            ]
+        );
+    }
+
+    #[test]
+    fn serializes_manifest_as_json() {
+        let manifest = CapabilityManifest::new(&cli());
+
+        let json: serde_json::Value = serde_json::from_str(&manifest.to_json()).expect("json");
+
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["config_schema_version"], 1);
+        assert_eq!(json["control_protocol_version"], 5);
+        assert!(json["features"].is_array());
+        assert_eq!(json["commands"][0], "paired switch");
+        assert!(
+            json["control_commands"]
+                .as_array()
+                .expect("control commands")
+                .contains(&serde_json::json!("watch"))
+        );
+        assert!(manifest.summary().contains("\n  daemon stop"));
+    }
+}
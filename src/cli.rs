@@ -1,44 +1,89 @@
-use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
-use clap_complete::{generate, Shell};
+#[cfg(feature = "cli-full")]
+use clap::Args;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::{Shell, generate};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
 use std::env;
 use std::fs;
-use std::io::{self, BufRead, Write};
+use std::io::{self, Write};
 use std::os::unix::fs::FileTypeExt;
 use std::os::unix::net::UnixStream;
 use std::path::{Path, PathBuf};
 use std::process::{Command as ProcessCommand, Stdio};
 use std::time::Duration;
 
+use crate::capabilities;
 use crate::commands;
-use crate::config::{Config, ConfigError};
+#[cfg(feature = "sway-compat")]
+use crate::compat::SwayIpc;
+#[cfg(not(feature = "cli-full"))]
+use crate::config::WaybarMode;
+use crate::config::{
+    Config, ConfigError, DebounceConfig, DebounceMode, MAX_DEBOUNCE_MS, OutOfRangePolicy,
+    RebalanceMode, ReconcilePolicy, RetryConfig,
+};
+use crate::control;
 use crate::daemon;
-use crate::hyprctl::{HyprlandIpc, Hyprctl, HyprctlError, SystemHyprctlRunner};
+use crate::events;
+use crate::hold;
 #[cfg(feature = "native-ipc")]
 use crate::hyprctl::NativeIpc;
-use crate::paired::{CycleDirection, normalize_workspace};
+use crate::hyprctl::{
+    self, BatchCache, Compositor, Dispatcher, Hyprctl, HyprctlBatch, HyprctlError, HyprlandIpc,
+    HyprlandOptions, SocketRunner, SystemHyprctlRunner, WorkspaceTarget,
+};
+use crate::inhibit;
+use crate::instance;
+use crate::journal;
+#[cfg(feature = "metrics")]
+use crate::metrics;
+use crate::paired::{CycleDirection, CycleOrder, PairedSide, WorkspaceArg};
 use crate::paths;
+use crate::reconcile;
+use crate::replay;
+use crate::resolved;
+use crate::retry;
+#[cfg(feature = "async-daemon")]
+use crate::runtime;
+use crate::schedule::{self, ScheduleError};
+#[cfg(feature = "cli-full")]
 use crate::session;
-use crate::setup::{self, SetupError};
-use crate::waybar::{self, WaybarError};
+use crate::shutdown;
+use crate::sticky;
+use crate::tui;
+use crate::validate;
+use crate::version;
+
+#[cfg(feature = "cli-full")]
+mod full;
+#[cfg(feature = "cli-full")]
+use full::ensure_setup;
 
 #[derive(Parser, Debug)]
 #[command(
     name = "hyprspaces",
-    version,
+    disable_version_flag = true,
+    arg_required_else_help = true,
     about = "Paired workspaces for Hyprland."
 )]
 pub struct Cli {
     #[arg(long, value_enum, default_value_t = IpcBackend::Hyprctl)]
     pub ipc: IpcBackend,
+    #[arg(short = 'V', long, help = "Print version")]
+    pub version: bool,
+    #[arg(long, requires = "version", help = "Print version metadata as JSON")]
+    pub json: bool,
     #[command(subcommand)]
-    pub command: Command,
+    pub command: Option<Command>,
 }
 
-
 #[derive(ValueEnum, Debug, Clone, Copy)]
 pub enum IpcBackend {
     Hyprctl,
+    Socket,
     Native,
+    Sway,
 }
 
 #[derive(Subcommand, Debug)]
@@ -47,53 +92,221 @@ pub enum Command {
         #[command(subcommand)]
         command: PairedCommand,
     },
-    Daemon,
+    Send {
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    #[command(args_conflicts_with_subcommands = true)]
+    Daemon {
+        #[arg(long)]
+        replace: bool,
+        #[arg(long)]
+        no_initial_rebalance: bool,
+        #[arg(long)]
+        trace_decisions: bool,
+        #[arg(long)]
+        restore_on_start: bool,
+        #[arg(
+            long,
+            value_name = "MS",
+            value_parser = clap::value_parser!(u64).range(1..=MAX_DEBOUNCE_MS)
+        )]
+        rebalance_debounce: Option<u64>,
+        #[arg(
+            long,
+            value_name = "MS",
+            value_parser = clap::value_parser!(u64).range(..=MAX_DEBOUNCE_MS)
+        )]
+        focus_debounce: Option<u64>,
+        #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+        record: Option<PathBuf>,
+        #[arg(long, value_name = "FILE", conflicts_with = "replace")]
+        replay: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 1.0,
+            requires = "replay",
+            value_parser = replay::parse_speed
+        )]
+        speed: f64,
+        #[command(subcommand)]
+        command: Option<DaemonCommand>,
+    },
+    #[cfg(feature = "cli-full")]
     Session {
         #[command(subcommand)]
         command: SessionCommand,
     },
+    #[cfg(feature = "cli-full")]
     Setup {
         #[command(subcommand)]
         command: SetupCommand,
     },
+    #[cfg(feature = "cli-full")]
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand,
+    },
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    Log {
+        #[command(subcommand)]
+        command: LogCommand,
+    },
+    #[cfg(feature = "cli-full")]
+    Query {
+        #[command(subcommand)]
+        command: QueryCommand,
+    },
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommand,
+    },
+    Rebalance {
+        #[arg(long, conflicts_with = "preserve")]
+        strict: bool,
+        #[arg(long)]
+        preserve: bool,
+    },
+    Reconcile {
+        #[arg(long, conflicts_with = "dry_run")]
+        fix: bool,
+        #[arg(long)]
+        dry_run: bool,
+    },
+    #[cfg(feature = "cli-full")]
+    Doctor {
+        #[arg(long)]
+        fix: bool,
+        #[arg(long, requires = "fix")]
+        yes: bool,
+    },
+    #[cfg(feature = "cli-full")]
+    Tui,
+    Keyword {
+        name: String,
+        value: String,
+        #[arg(long)]
+        dry_run: bool,
+    },
+    #[cfg(feature = "cli-full")]
     Waybar(WaybarArgs),
+    #[cfg(feature = "cli-full")]
+    Bar(BarArgs),
     Completions {
         #[arg(value_enum)]
         shell: Shell,
     },
     Status,
+    Capabilities {
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
 pub enum PairedCommand {
     Switch {
-        workspace: u32,
+        workspace: WorkspaceArg,
+        #[arg(long)]
+        raw: bool,
+        #[arg(long, value_enum)]
+        cursor: Option<CursorArg>,
     },
     Cycle {
         direction: CycleDirectionArg,
+        #[arg(long)]
+        occupied_only: bool,
+        #[arg(long, value_enum, default_value_t = CycleOrderArg::Numeric)]
+        order: CycleOrderArg,
+        #[arg(long, value_enum)]
+        cursor: Option<CursorArg>,
     },
     #[command(name = "move-window")]
     MoveWindow {
-        workspace: u32,
+        workspace: WorkspaceArg,
     },
     #[command(name = "grab-rogue")]
     GrabRogue,
+    Clone {
+        from: WorkspaceArg,
+        to: WorkspaceArg,
+    },
+    Stick {
+        #[arg(long)]
+        address: Option<String>,
+    },
+    #[command(name = "send-to")]
+    SendTo {
+        target: SendTargetArg,
+    },
+    Urgent,
+    Hold {
+        #[arg(value_enum, default_value_t = HoldActionArg::Toggle)]
+        action: HoldActionArg,
+        #[arg(long, value_enum, default_value_t = PairedSideArg::Secondary)]
+        monitor: PairedSideArg,
+    },
 }
 
+/// The arguments of `hyprspaces send`.
+#[derive(Parser, Debug)]
+#[command(name = "send", no_binary_name = true)]
+struct SendLine {
+    #[command(subcommand)]
+    command: PairedCommand,
+}
+
+fn parse_send_args(args: &[String]) -> Result<PairedCommand, clap::Error> {
+    SendLine::try_parse_from(args).map(|line| line.command)
+}
+
+#[cfg(feature = "cli-full")]
 #[derive(Subcommand, Debug)]
 pub enum SessionCommand {
     Save {
         #[arg(long, value_name = "PATH")]
         path: Option<PathBuf>,
+        #[arg(long, conflicts_with = "path")]
+        name: Option<String>,
     },
     Restore {
         #[arg(long, value_name = "PATH")]
         path: Option<PathBuf>,
+        #[arg(long, conflicts_with = "path")]
+        name: Option<String>,
+        #[arg(long, value_enum, default_value_t = SessionRestoreMode::Auto)]
+        mode: SessionRestoreMode,
+        #[arg(long)]
+        allow_degraded: bool,
+    },
+    List,
+    Delete {
+        #[arg(long)]
+        name: String,
+    },
+    Diff {
+        #[arg(long, value_name = "PATH")]
+        path: Option<PathBuf>,
+        #[arg(long, conflicts_with = "path")]
+        name: Option<String>,
+        #[arg(long, value_name = "NAME", default_value = "current")]
+        against: String,
         #[arg(long, value_enum, default_value_t = SessionRestoreMode::Auto)]
         mode: SessionRestoreMode,
     },
 }
 
+#[cfg(feature = "cli-full")]
+#[derive(Subcommand, Debug)]
+pub enum RulesCommand {
+    Sync,
+}
+
+#[cfg(feature = "cli-full")]
 #[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SessionRestoreMode {
     Auto,
@@ -116,26 +329,239 @@ impl From<CycleDirectionArg> for CycleDirection {
     }
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendTargetArg {
+    Primary,
+    Secondary,
+    Other,
+}
+
+impl From<SendTargetArg> for commands::SendTarget {
+    fn from(value: SendTargetArg) -> Self {
+        match value {
+            SendTargetArg::Primary => commands::SendTarget::Primary,
+            SendTargetArg::Secondary => commands::SendTarget::Secondary,
+            SendTargetArg::Other => commands::SendTarget::Other,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldActionArg {
+    On,
+    Off,
+    Toggle,
+}
+
+impl From<HoldActionArg> for hold::HoldAction {
+    fn from(value: HoldActionArg) -> Self {
+        match value {
+            HoldActionArg::On => hold::HoldAction::On,
+            HoldActionArg::Off => hold::HoldAction::Off,
+            HoldActionArg::Toggle => hold::HoldAction::Toggle,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairedSideArg {
+    Primary,
+    Secondary,
+}
+
+impl From<PairedSideArg> for PairedSide {
+    fn from(value: PairedSideArg) -> Self {
+        match value {
+            PairedSideArg::Primary => PairedSide::Primary,
+            PairedSideArg::Secondary => PairedSide::Secondary,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleOrderArg {
+    Mru,
+    Numeric,
+    Occupied,
+}
+
+impl From<CycleOrderArg> for CycleOrder {
+    fn from(value: CycleOrderArg) -> Self {
+        match value {
+            CycleOrderArg::Mru => CycleOrder::Mru,
+            CycleOrderArg::Numeric => CycleOrder::Numeric,
+            CycleOrderArg::Occupied => CycleOrder::Occupied,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorArg {
+    Follow,
+    Stay,
+}
+
+/// `config` with a `--cursor` override applied.
+fn with_cursor(config: &Config, cursor: Option<CursorArg>) -> Cow<'_, Config> {
+    match cursor.map(|cursor| cursor == CursorArg::Follow) {
+        Some(follows) if follows != config.cursor_follows_focus => Cow::Owned(Config {
+            cursor_follows_focus: follows,
+            ..config.clone()
+        }),
+        _ => Cow::Borrowed(config),
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DaemonCommand {
+    Stop,
+    Restart,
+    Reload,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    Validate,
+    #[cfg(feature = "cli-full")]
+    Init {
+        #[arg(long)]
+        force: bool,
+    },
+    #[cfg(feature = "cli-full")]
+    Set {
+        key: String,
+        value: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum LogCommand {
+    Show {
+        #[arg(long, value_name = "DURATION", value_parser = journal::parse_since)]
+        since: Option<Duration>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ScheduleCommand {
+    List,
+    Enable { name: String },
+    Disable { name: String },
+}
+
+#[cfg(feature = "cli-full")]
+#[derive(Subcommand, Debug)]
+pub enum QueryCommand {
+    Windows {
+        #[arg(long, value_enum)]
+        format: QueryFormat,
+    },
+}
+
+#[cfg(feature = "cli-full")]
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryFormat {
+    Alfred,
+    RofiScript,
+}
+
+#[cfg(feature = "cli-full")]
 #[derive(Subcommand, Debug)]
 pub enum SetupCommand {
     Install(InstallArgs),
     Uninstall,
     #[command(name = "migrate-windows")]
-    MigrateWindows,
+    MigrateWindows {
+        #[arg(long)]
+        preview: bool,
+        #[arg(long, conflicts_with = "preview")]
+        yes: bool,
+        #[arg(long, value_name = "CLASS")]
+        only: Vec<String>,
+        #[arg(long, value_name = "CLASS")]
+        except: Vec<String>,
+    },
+    Rollback,
 }
 
-#[derive(Args, Debug)]
+#[cfg(feature = "cli-full")]
+#[derive(Args, Debug, Default)]
 pub struct InstallArgs {
     #[arg(long)]
     pub waybar: bool,
+    #[arg(long)]
+    pub autostart_xdg: bool,
+    #[arg(long, value_name = "PATH", requires = "waybar")]
+    pub waybar_config: Option<PathBuf>,
+    #[arg(long, value_enum, value_name = "POSITION", requires = "waybar")]
+    pub waybar_bar: Option<crate::setup::BarPosition>,
+    #[arg(long, value_name = "OUTPUT", requires = "waybar")]
+    pub waybar_output: Option<String>,
+    #[arg(long, value_enum, value_name = "SECTION", requires = "waybar")]
+    pub waybar_modules: Option<crate::setup::ModulesSection>,
+    #[arg(long, value_name = "INDEX", requires = "waybar")]
+    pub waybar_index: Option<usize>,
+    #[arg(long, requires = "waybar", conflicts_with = "waybar_output")]
+    pub waybar_per_output: bool,
 }
 
+#[cfg(feature = "cli-full")]
 #[derive(Args, Debug)]
 pub struct WaybarArgs {
     #[arg(long)]
     pub enable_waybar: bool,
     #[arg(long, value_name = "PATH")]
     pub theme_css: Option<PathBuf>,
+    #[arg(long, value_name = "HEX")]
+    pub color: Option<String>,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "30s",
+        value_parser = journal::parse_since
+    )]
+    pub daemon_timeout: Duration,
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub compat: Option<WaybarCompat>,
+    #[arg(long, conflicts_with = "compat")]
+    pub follow_file: bool,
+    #[arg(long, value_name = "MONITOR", conflicts_with = "follow_file")]
+    pub monitor: Option<String>,
+}
+
+#[cfg(feature = "cli-full")]
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaybarCompat {
+    HyprlandWorkspaces,
+}
+
+#[cfg(feature = "cli-full")]
+#[derive(Args, Debug)]
+pub struct BarArgs {
+    #[arg(long, value_enum, value_name = "FORMAT", default_value = "waybar-json")]
+    pub format: BarFormat,
+    #[arg(long, value_name = "PATH")]
+    pub theme_css: Option<PathBuf>,
+    #[arg(long, value_name = "HEX")]
+    pub color: Option<String>,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "30s",
+        value_parser = journal::parse_since
+    )]
+    pub daemon_timeout: Duration,
+    #[arg(long, value_name = "MONITOR")]
+    pub monitor: Option<String>,
+}
+
+#[cfg(feature = "cli-full")]
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarFormat {
+    WaybarJson,
+    Eww,
+    Polybar,
+    Plain,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -148,48 +574,88 @@ pub enum CliError {
     WaybarDisabled,
     #[error("native ipc requires --features native-ipc")]
     NativeIpcUnavailable,
+    #[error("sway ipc requires --features sway-compat")]
+    SwayIpcUnavailable,
+    #[error("the daemon requires Hyprland events ({0:?} is not supported)")]
+    DaemonUnsupported(Compositor),
     #[error("io error")]
     Io(#[from] io::Error),
     #[error("config error")]
     Config(#[from] ConfigError),
+    #[cfg(feature = "cli-full")]
     #[error("setup error")]
-    Setup(#[from] SetupError),
+    Setup(#[from] crate::setup::SetupError),
     #[error("hyprctl error")]
     Hyprctl(#[from] HyprctlError),
-    #[error("session error")]
-    Session(#[from] session::SessionError),
+    #[error("{0}")]
+    Command(#[from] commands::CommandError),
+    #[cfg(feature = "cli-full")]
+    #[error("session error: {0}")]
+    Session(#[from] crate::session::SessionError),
+    #[cfg(feature = "cli-full")]
     #[error("waybar error")]
-    Waybar(#[from] WaybarError),
+    Waybar(#[from] crate::waybar::WaybarError),
+    #[error("{0}")]
+    Instance(#[from] instance::InstanceError),
+    #[error("daemon is not running (no control socket at {0})")]
+    DaemonNotRunning(PathBuf),
+    #[error("daemon rejected {command}: {response}")]
+    DaemonControl {
+        command: &'static str,
+        response: String,
+    },
+    #[error("{0}")]
+    Send(String),
+    #[error("doctor found {0} failing check(s)")]
+    DoctorFailed(usize),
+    #[error("{failed} of {total} window(s) failed to migrate")]
+    MigrationFailed { failed: usize, total: usize },
+    #[error("config already exists: {0} (use --force to overwrite)")]
+    ConfigExists(PathBuf),
+    #[error("config validation failed: {errors} error(s), {warnings} warning(s)")]
+    Validation { errors: usize, warnings: usize },
+    #[error("{0}")]
+    Schedule(#[from] ScheduleError),
+    #[error("{0}")]
+    Sticky(#[from] sticky::StickyError),
+    #[error("{0}")]
+    Hold(#[from] hold::HoldError),
+    #[error("lost the Hyprland event socket after {0} reconnect attempts")]
+    EventSourceLost(u32),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Validation { errors: 0, .. } => 2,
+            _ => 1,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 struct EnvPaths {
     base_dir: PathBuf,
+    state_dir: PathBuf,
+    runtime_dir: PathBuf,
+    #[cfg_attr(not(feature = "cli-full"), allow(dead_code))]
+    sessions_dir: PathBuf,
     config_path: PathBuf,
     hypr_config_dir: PathBuf,
+    #[cfg_attr(not(feature = "cli-full"), allow(dead_code))]
     waybar_css: PathBuf,
+    #[cfg_attr(not(feature = "cli-full"), allow(dead_code))]
+    autostart_dir: PathBuf,
 }
 
-trait DaemonLauncher {
-    fn launch(&self, bin_path: &str, base_dir: &Path) -> Result<(), CliError>;
-}
-
-struct SystemDaemonLauncher;
-
-impl DaemonLauncher for SystemDaemonLauncher {
-    fn launch(&self, bin_path: &str, base_dir: &Path) -> Result<(), CliError> {
-        spawn_daemon(bin_path, base_dir)
-    }
-}
-
-fn spawn_daemon(bin_path: &str, base_dir: &Path) -> Result<(), CliError> {
+fn spawn_daemon(bin_path: &str, state_dir: &Path) -> Result<(), CliError> {
     let child = ProcessCommand::new(bin_path)
         .arg("daemon")
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()?;
-    write_daemon_pid(base_dir, child.id())?;
+    write_daemon_pid(state_dir, child.id())?;
     Ok(())
 }
 
@@ -209,25 +675,29 @@ trait DaemonPidSource {
     fn pids(&self) -> Result<Vec<u32>, CliError>;
 }
 
-struct SystemDaemonPidSource;
+/// Daemons started under the given Hyprland instance.
+struct SystemDaemonPidSource {
+    signature: Option<String>,
+}
 
 impl DaemonPidSource for SystemDaemonPidSource {
     fn pids(&self) -> Result<Vec<u32>, CliError> {
-        system_daemon_pids()
+        system_daemon_pids(self.signature.as_deref())
     }
 }
 
-fn daemon_pid_path(base_dir: &Path) -> PathBuf {
-    base_dir.join("daemon.pid")
+fn daemon_pid_path(state_dir: &Path) -> PathBuf {
+    state_dir.join("daemon.pid")
 }
 
-fn write_daemon_pid(base_dir: &Path, pid: u32) -> Result<(), CliError> {
-    fs::write(daemon_pid_path(base_dir), format!("{pid}\n"))?;
+fn write_daemon_pid(state_dir: &Path, pid: u32) -> Result<(), CliError> {
+    fs::create_dir_all(state_dir)?;
+    fs::write(daemon_pid_path(state_dir), format!("{pid}\n"))?;
     Ok(())
 }
 
-fn read_daemon_pid(base_dir: &Path) -> Result<Option<u32>, CliError> {
-    let path = daemon_pid_path(base_dir);
+fn read_daemon_pid(state_dir: &Path) -> Result<Option<u32>, CliError> {
+    let path = daemon_pid_path(state_dir);
     if !path.exists() {
         return Ok(None);
     }
@@ -241,6 +711,13 @@ fn read_daemon_pid(base_dir: &Path) -> Result<Option<u32>, CliError> {
     }
 }
 
+/// Removes the pidfile if it names this process.
+fn release_daemon_pid(state_dir: &Path) {
+    if read_daemon_pid(state_dir).ok().flatten() == Some(std::process::id()) {
+        let _ = fs::remove_file(daemon_pid_path(state_dir));
+    }
+}
+
 fn kill_pid(pid: u32) -> Result<(), CliError> {
     match ProcessCommand::new("kill")
         .arg("-TERM")
@@ -254,19 +731,21 @@ fn kill_pid(pid: u32) -> Result<(), CliError> {
     }
 }
 
-fn stop_daemon(base_dir: &Path) -> Result<(), CliError> {
+fn stop_daemon(state_dir: &Path) -> Result<(), CliError> {
     let killer = SystemDaemonKiller;
-    let pid_source = SystemDaemonPidSource;
-    stop_daemon_with_killer(base_dir, &killer, &pid_source)
+    let pid_source = SystemDaemonPidSource {
+        signature: env::var("HYPRLAND_INSTANCE_SIGNATURE").ok(),
+    };
+    stop_daemon_with_killer(state_dir, &killer, &pid_source)
 }
 
 fn stop_daemon_with_killer<L: DaemonKiller, P: DaemonPidSource>(
-    base_dir: &Path,
+    state_dir: &Path,
     killer: &L,
     pid_source: &P,
 ) -> Result<(), CliError> {
     let mut pids = Vec::new();
-    if let Some(pid) = read_daemon_pid(base_dir)? {
+    if let Some(pid) = read_daemon_pid(state_dir)? {
         pids.push(pid);
     }
     if let Ok(mut extra) = pid_source.pids() {
@@ -277,14 +756,14 @@ fn stop_daemon_with_killer<L: DaemonKiller, P: DaemonPidSource>(
     for pid in pids {
         killer.kill(pid)?;
     }
-    let path = daemon_pid_path(base_dir);
+    let path = daemon_pid_path(state_dir);
     if path.exists() {
         fs::remove_file(path)?;
     }
     Ok(())
 }
 
-fn system_daemon_pids() -> Result<Vec<u32>, CliError> {
+fn system_daemon_pids(signature: Option<&str>) -> Result<Vec<u32>, CliError> {
     let mut pids = Vec::new();
     let entries = match fs::read_dir("/proc") {
         Ok(entries) => entries,
@@ -297,8 +776,8 @@ fn system_daemon_pids() -> Result<Vec<u32>, CliError> {
         };
         let file_name = entry.file_name();
         let pid = match file_name.to_string_lossy().parse::<u32>() {
-            Ok(pid) => pid,
-            Err(_) => continue,
+            Ok(pid) if pid != std::process::id() => pid,
+            _ => continue,
         };
         let cmdline_path = entry.path().join("cmdline");
         let cmdline = match fs::read(&cmdline_path) {
@@ -306,7 +785,11 @@ fn system_daemon_pids() -> Result<Vec<u32>, CliError> {
             Err(_) => continue,
         };
         let args = parse_cmdline(&cmdline);
-        if cmdline_is_daemon(&args) {
+        if !cmdline_is_daemon(&args) {
+            continue;
+        }
+        let environ = fs::read(entry.path().join("environ")).unwrap_or_default();
+        if environ_signature(&environ).as_deref() == signature {
             pids.push(pid);
         }
     }
@@ -321,6 +804,13 @@ fn parse_cmdline(bytes: &[u8]) -> Vec<String> {
         .collect()
 }
 
+fn environ_signature(bytes: &[u8]) -> Option<String> {
+    parse_cmdline(bytes).into_iter().find_map(|var| {
+        var.strip_prefix("HYPRLAND_INSTANCE_SIGNATURE=")
+            .map(str::to_string)
+    })
+}
+
 fn cmdline_is_daemon(args: &[String]) -> bool {
     let has_daemon = args.iter().any(|arg| arg == "daemon");
     let has_binary = args
@@ -330,37 +820,41 @@ fn cmdline_is_daemon(args: &[String]) -> bool {
     has_daemon && has_binary
 }
 
-impl WaybarArgs {
-    fn ensure_enabled(&self) -> Result<(), CliError> {
-        if self.enable_waybar {
-            Ok(())
-        } else {
-            Err(CliError::WaybarDisabled)
-        }
-    }
-}
-
 fn build_ipc(backend: IpcBackend) -> Result<Box<dyn HyprlandIpc>, CliError> {
-    match backend {
-        IpcBackend::Hyprctl => Ok(Box::new(Hyprctl::new(SystemHyprctlRunner::new(
-            "hyprctl",
-        )))),
+    let ipc: Box<dyn HyprlandIpc> = match backend {
+        IpcBackend::Hyprctl => Box::new(Hyprctl::new(SystemHyprctlRunner::new("hyprctl"))),
+        IpcBackend::Socket => Box::new(Hyprctl::new(SocketRunner::new(request_socket_path()?))),
         IpcBackend::Native => {
             #[cfg(feature = "native-ipc")]
             {
-                Ok(Box::new(NativeIpc::new()))
+                Box::new(NativeIpc::new())
             }
             #[cfg(not(feature = "native-ipc"))]
             {
-                Err(CliError::NativeIpcUnavailable)
+                return Err(CliError::NativeIpcUnavailable);
             }
         }
-    }
+        IpcBackend::Sway => {
+            #[cfg(feature = "sway-compat")]
+            {
+                Box::new(SwayIpc::new(SystemHyprctlRunner::new("swaymsg")))
+            }
+            #[cfg(not(feature = "sway-compat"))]
+            {
+                return Err(CliError::SwayIpcUnavailable);
+            }
+        }
+    };
+    // Until the config is loaded and names its own limit.
+    ipc.set_timeout(RetryConfig::default().ipc_timeout());
+    Ok(ipc)
 }
 
 fn event_source_kind(backend: IpcBackend) -> daemon::EventSourceKind {
     match backend {
-        IpcBackend::Hyprctl => daemon::EventSourceKind::Socket2,
+        IpcBackend::Hyprctl | IpcBackend::Socket | IpcBackend::Sway => {
+            daemon::EventSourceKind::Socket2
+        }
         IpcBackend::Native => {
             #[cfg(feature = "native-ipc")]
             {
@@ -374,15 +868,20 @@ fn event_source_kind(backend: IpcBackend) -> daemon::EventSourceKind {
     }
 }
 
+#[cfg(not(feature = "async-daemon"))]
 fn build_event_source(
     backend: IpcBackend,
     socket_path: &Path,
     timeout: Duration,
+    record: Option<&Path>,
 ) -> Result<Box<dyn daemon::EventSource>, CliError> {
     match event_source_kind(backend) {
         daemon::EventSourceKind::Socket2 => {
             let stream = UnixStream::connect(socket_path)?;
-            let source = daemon::Socket2EventSource::new(stream, timeout)?;
+            let mut source = daemon::Socket2EventSource::new(stream, timeout)?;
+            if let Some(path) = record {
+                source = source.with_recorder(replay::Recorder::open(path)?);
+            }
             Ok(Box::new(source))
         }
         #[cfg(feature = "native-ipc")]
@@ -393,244 +892,1974 @@ fn build_event_source(
     }
 }
 
+/// How often a reconnecting loop still checks for shutdown and control requests.
+#[cfg(not(feature = "async-daemon"))]
+const RECONNECT_POLL: Duration = Duration::from_millis(20);
+
+enum Reconnect<S, T> {
+    Connected(S),
+    /// A shutdown or control request ended the loop while it waited.
+    Stopped(T),
+    GaveUp,
+}
+
+/// Retries the event source; `stop` runs between attempts and ends the wait with `Some`.
+#[cfg(not(feature = "async-daemon"))]
+fn reconnect_event_source<T>(
+    backend: IpcBackend,
+    socket_path: &Path,
+    timeout: Duration,
+    record: Option<&Path>,
+    mut stop: impl FnMut() -> Result<Option<T>, CliError>,
+) -> Result<Reconnect<Box<dyn daemon::EventSource>, T>, CliError> {
+    for _ in 0..daemon::RECONNECT_ATTEMPTS {
+        let retry_at = std::time::Instant::now() + daemon::RECONNECT_INTERVAL;
+        while std::time::Instant::now() < retry_at {
+            if let Some(stopped) = stop()? {
+                return Ok(Reconnect::Stopped(stopped));
+            }
+            std::thread::sleep(RECONNECT_POLL);
+        }
+        if let Ok(source) = build_event_source(backend, socket_path, timeout, record) {
+            return Ok(Reconnect::Connected(source));
+        }
+    }
+    Ok(Reconnect::GaveUp)
+}
+
 pub fn run() -> Result<(), CliError> {
-    let Cli { ipc, command } = Cli::parse();
+    let Cli {
+        ipc,
+        version,
+        json,
+        command,
+    } = Cli::parse();
+
+    if version {
+        let report = version::VersionReport::current();
+        write_stdout(&if json {
+            report.to_json()
+        } else {
+            report.summary()
+        })?;
+        return Ok(());
+    }
+    let Some(command) = command else {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::MissingSubcommand,
+                "a subcommand is required",
+            )
+            .exit();
+    };
 
     if let Command::Completions { shell } = &command {
-        let mut cmd = Cli::command();
-        let mut output = Vec::new();
-        generate(*shell, &mut cmd, "hyprspaces", &mut output);
-        write_stdout_bytes(&output)?;
+        write_stdout_bytes(&completions_output(*shell))?;
         return Ok(());
     }
 
+    if let Command::Capabilities { json } = &command {
+        let manifest = capabilities::CapabilityManifest::new(&Cli::command());
+        write_stdout(&if *json {
+            manifest.to_json()
+        } else {
+            manifest.summary()
+        })?;
+        return Ok(());
+    }
+
+    let command = match command {
+        Command::Send { args } => match send_exec(&env_paths()?.runtime_dir, &args) {
+            Err(CliError::DaemonNotRunning(_)) => Command::Paired {
+                command: parse_send_args(&args).unwrap_or_else(|err| err.exit()),
+            },
+            result => return result,
+        },
+        command => command,
+    };
+
     let hyprctl = build_ipc(ipc)?;
     let hyprctl = hyprctl.as_ref();
     let paths = env_paths()?;
     let bin_path = bin_path();
 
     match command {
+        Command::Send { .. } => unreachable!("send is forwarded to the daemon above"),
         Command::Paired { command } => {
             ensure_setup(hyprctl, &paths, &bin_path)?;
-            let config = load_config(&paths)?;
+            let config = load_config(hyprctl, &paths)?;
             match command {
-                PairedCommand::Switch { workspace } => {
-                    commands::paired_switch(hyprctl, &config, workspace)?;
+                PairedCommand::Switch {
+                    workspace,
+                    raw,
+                    cursor,
+                } => {
+                    let policy = if raw {
+                        OutOfRangePolicy::Raw
+                    } else {
+                        config.out_of_range
+                    };
+                    let workspace = resolve_workspace_arg(&workspace, &config)?;
+                    let held = hold::load(&hold::state_path(&paths.state_dir))?;
+                    let config = with_cursor(&config, cursor);
+                    commands::paired_switch_held(hyprctl, &config, workspace, policy, held)?;
                 }
-                PairedCommand::Cycle { direction } => {
-                    commands::paired_cycle(hyprctl, &config, direction.into())?;
+                PairedCommand::Cycle {
+                    direction,
+                    occupied_only,
+                    order,
+                    cursor,
+                } => {
+                    let history = if order == CycleOrderArg::Mru {
+                        slot_history(&paths.runtime_dir)
+                    } else {
+                        Vec::new()
+                    };
+                    let options = commands::CycleOptions {
+                        occupied_only: occupied_only || config.cycle_skip_empty,
+                        order: order.into(),
+                        history,
+                        held: hold::load(&hold::state_path(&paths.state_dir))?,
+                    };
+                    commands::paired_cycle_with_options(
+                        hyprctl,
+                        &with_cursor(&config, cursor),
+                        direction.into(),
+                        &options,
+                    )?;
                 }
                 PairedCommand::MoveWindow { workspace } => {
+                    let workspace = resolve_workspace_arg(&workspace, &config)?;
                     commands::paired_move_window(hyprctl, &config, workspace)?;
                 }
-                PairedCommand::GrabRogue => {
-                    commands::grab_rogue_windows(hyprctl, &config)?;
+                PairedCommand::GrabRogue => match commands::grab_rogue_windows(hyprctl, &config)? {
+                    0 => println!("no rogue windows"),
+                    moved => println!("moved {moved} window(s) back to the primary range"),
+                },
+                PairedCommand::Clone { from, to } => {
+                    let from = resolve_workspace_arg(&from, &config)?;
+                    let to = resolve_workspace_arg(&to, &config)?;
+                    let report = commands::paired_clone(hyprctl, &config, from, to)?;
+                    for class in &report.skipped {
+                        eprintln!("skipped {class}: no launch command configured");
+                    }
+                    println!("launched {} window(s) into slot {to}", report.launched);
                 }
-            }
-        }
-        Command::Daemon => {
-            ensure_setup(hyprctl, &paths, &bin_path)?;
-            let config = load_config(&paths)?;
-            let socket_path = socket2_path()?;
-            ensure_socket(&socket_path)?;
-            daemon::rebalance_all(hyprctl, &config)?;
-            let mut source = build_event_source(
-                ipc,
-                &socket_path,
-                daemon::DEFAULT_REBALANCE_DEBOUNCE,
-            )?;
-            let mut rebalance_debounce =
-                daemon::RebalanceDebounce::new(daemon::DEFAULT_REBALANCE_DEBOUNCE);
-            let mut focus_debounce =
-                daemon::FocusSwitchDebounce::new(daemon::DEFAULT_FOCUS_SWITCH_DEBOUNCE);
-            loop {
-                let event = daemon::EventSource::next_event(&mut *source)?;
-                match event {
-                    daemon::DaemonEvent::Disconnected => break,
-                    event => {
-                        let _ = daemon::process_event(
-                            hyprctl,
-                            &config,
-                            &mut rebalance_debounce,
-                            &mut focus_debounce,
-                            event,
-                        )?;
+                PairedCommand::Stick { address } => {
+                    let clients = hyprctl.clients()?;
+                    let address = match address {
+                        Some(address) => address,
+                        None => sticky::active_window(&clients)
+                            .map(|client| client.address.clone())
+                            .ok_or(sticky::StickyError::NoActiveWindow)?,
+                    };
+                    let state_path = sticky::state_path(&paths.state_dir);
+                    let stuck = sticky::toggle(&state_path, &address, &clients)?;
+                    println!(
+                        "{address} is {}",
+                        if stuck { "sticky" } else { "no longer sticky" }
+                    );
+                    if control::daemon_reachable(&control::control_socket_path(&paths.runtime_dir))
+                    {
+                        send_control(&paths.runtime_dir, control::ControlCommand::Reload)?;
                     }
                 }
-            }
-        }
-        Command::Session { command } => {
-            ensure_setup(hyprctl, &paths, &bin_path)?;
-            let config = load_config(&paths)?;
-            match command {
-                SessionCommand::Save { path } => {
-                    let _ = session::save_session(
-                        hyprctl,
-                        &config,
-                        &paths.base_dir,
-                        path.as_deref(),
-                    )?;
+                PairedCommand::SendTo { target } => {
+                    commands::paired_send_to(hyprctl, &config, target.into())?;
                 }
-                SessionCommand::Restore { path, mode } => {
-                    let restore_mode = match mode {
-                        SessionRestoreMode::Auto => session::RestoreMode::Auto,
-                        SessionRestoreMode::Same => session::RestoreMode::Same,
-                        SessionRestoreMode::Cold => session::RestoreMode::Cold,
-                    };
-                    session::restore_session(
-                        hyprctl,
-                        &config,
-                        &paths.base_dir,
-                        path.as_deref(),
-                        restore_mode,
-                    )?;
+                PairedCommand::Urgent => {
+                    let address = urgent_window(&paths.runtime_dir)
+                        .ok_or(commands::CommandError::NoUrgentWindow)?;
+                    commands::paired_focus_window(hyprctl, &config, &address)?;
+                }
+                PairedCommand::Hold { action, monitor } => {
+                    let state_path = hold::state_path(&paths.state_dir);
+                    match hold::update(&state_path, action.into(), monitor.into())? {
+                        Some(PairedSide::Primary) => {
+                            println!("holding {}", config.primary_monitor);
+                        }
+                        Some(PairedSide::Secondary) => {
+                            println!("holding {}", config.secondary_monitor);
+                        }
+                        None => println!("hold released"),
+                    }
+                    if control::daemon_reachable(&control::control_socket_path(&paths.runtime_dir))
+                    {
+                        send_control(&paths.runtime_dir, control::ControlCommand::Reload)?;
+                    }
                 }
             }
         }
-        Command::Setup { command } => match command {
-            SetupCommand::Install(args) => {
-                handle_setup_install(hyprctl, &paths, &bin_path, args.waybar)?;
+        Command::Daemon {
+            replace,
+            no_initial_rebalance,
+            trace_decisions,
+            restore_on_start,
+            rebalance_debounce,
+            focus_debounce,
+            record,
+            replay,
+            speed,
+            command: None,
+        } => {
+            let options = DaemonOptions {
+                initial_rebalance: !no_initial_rebalance,
+                trace_decisions,
+                restore_on_start,
+                debounce: DebounceOverrides {
+                    rebalance_ms: rebalance_debounce,
+                    focus_ms: focus_debounce,
+                },
+            };
+            match replay {
+                Some(recording) => replay_daemon(hyprctl, &paths, &recording, speed, options)?,
+                None => run_daemon(
+                    ipc,
+                    hyprctl,
+                    &paths,
+                    &bin_path,
+                    replace,
+                    record.as_deref(),
+                    options,
+                )?,
             }
-            SetupCommand::Uninstall => {
-                if let Ok(config) = load_config(&paths) {
-                    let _ = commands::migrate_windows(hyprctl, &config);
+        }
+        Command::Daemon {
+            command: Some(DaemonCommand::Stop),
+            ..
+        } => {
+            stop_running_daemon(&paths.state_dir, &paths.runtime_dir)?;
+        }
+        Command::Daemon {
+            command: Some(DaemonCommand::Restart),
+            ..
+        } => {
+            stop_running_daemon(&paths.state_dir, &paths.runtime_dir)?;
+            spawn_daemon(&bin_path, &paths.state_dir)?;
+        }
+        Command::Daemon {
+            command: Some(DaemonCommand::Reload),
+            ..
+        } => {
+            send_control(&paths.runtime_dir, control::ControlCommand::Reload)?;
+        }
+        #[cfg(feature = "cli-full")]
+        Command::Session { command } => full::session(hyprctl, &paths, &bin_path, command)?,
+        #[cfg(feature = "cli-full")]
+        Command::Setup { command } => full::setup(hyprctl, &paths, &bin_path, command)?,
+        #[cfg(feature = "cli-full")]
+        Command::Rules {
+            command: RulesCommand::Sync,
+        } => full::sync_rules(hyprctl, &paths, &bin_path)?,
+        #[cfg(feature = "cli-full")]
+        Command::Waybar(args) => full::waybar(hyprctl, &paths, &bin_path, args)?,
+        #[cfg(feature = "cli-full")]
+        Command::Bar(args) => full::bar(hyprctl, &paths, &bin_path, args)?,
+        Command::Config { command } => match command {
+            ConfigCommand::Validate => {
+                let diagnostics = config_diagnostics(hyprctl, &paths)?;
+                write_stdout(&validate_report(&paths.config_path, &diagnostics))?;
+                let errors = validate::count_severity(&diagnostics, validate::Severity::Error);
+                let warnings = validate::count_severity(&diagnostics, validate::Severity::Warning);
+                if errors > 0 || warnings > 0 {
+                    return Err(CliError::Validation { errors, warnings });
                 }
-                stop_daemon(&paths.base_dir)?;
-                setup::uninstall(&paths.base_dir, &paths.hypr_config_dir)?;
-                let _ = hyprctl.reload();
             }
-            SetupCommand::MigrateWindows => {
-                let config = load_config(&paths)?;
-                commands::migrate_windows(hyprctl, &config)?;
+            #[cfg(feature = "cli-full")]
+            ConfigCommand::Init { force } => full::config_init(hyprctl, &paths, force)?,
+            #[cfg(feature = "cli-full")]
+            ConfigCommand::Set { key, value } => {
+                full::config_set(hyprctl, &paths, &bin_path, &key, &value)?
+            }
+        },
+        Command::Log { command } => match command {
+            LogCommand::Show { since } => {
+                let journal = journal::Journal::new(
+                    journal::journal_path(&paths.base_dir),
+                    journal::DEFAULT_JOURNAL_MAX_BYTES,
+                );
+                let cutoff = since
+                    .map(|since| journal::epoch_seconds().saturating_sub(since.as_secs()))
+                    .unwrap_or(0);
+                for entry in journal.entries_since(cutoff)? {
+                    write_stdout(&journal::format_entry(&entry))?;
+                }
             }
         },
-        Command::Waybar(args) => {
-            args.ensure_enabled()?;
+        #[cfg(feature = "cli-full")]
+        Command::Doctor { fix, yes } => full::doctor(hyprctl, &paths, &bin_path, fix, yes)?,
+        #[cfg(feature = "cli-full")]
+        Command::Query { command } => full::query(hyprctl, &paths, command)?,
+        Command::Rebalance { strict, preserve } => {
             ensure_setup(hyprctl, &paths, &bin_path)?;
-            let config = load_config(&paths)?;
-            let theme_path = args.theme_css.unwrap_or(paths.waybar_css);
-            let colors = waybar::load_theme_colors(&theme_path)?;
-            let socket_path = socket2_path()?;
-            ensure_socket(&socket_path)?;
-            write_stdout(&waybar::state_from_hyprctl(
-                hyprctl,
-                config.paired_offset,
-                &colors,
-            )?)?;
-            let stream = std::os::unix::net::UnixStream::connect(&socket_path)?;
-            let reader = io::BufReader::new(stream);
-            for line in reader.lines() {
-                let line = line?;
-                if waybar::should_update(&line) {
-                    let state =
-                        waybar::state_from_hyprctl(hyprctl, config.paired_offset, &colors)?;
-                    write_stdout(&state)?;
+            let mut config = load_config(hyprctl, &paths)?;
+            if strict {
+                config.rebalance_mode = RebalanceMode::Strict;
+            } else if preserve {
+                config.rebalance_mode = RebalanceMode::Preserve;
+            }
+            if let Some(config) = daemon::select_config(hyprctl, &config)? {
+                daemon::rebalance_all(hyprctl, &config)?;
+            }
+        }
+        Command::Reconcile { fix, dry_run } => {
+            let config = load_config(hyprctl, &paths)?;
+            match daemon::select_config(hyprctl, &config)? {
+                Some(config) => {
+                    let fix = fix || (!dry_run && config.reconcile.policy == ReconcilePolicy::Fix);
+                    let drift = reconcile::reconcile(hyprctl, &config, fix)?;
+                    write_stdout(&reconcile::report(&drift, fix))?;
+                }
+                None => write_stdout("paired monitors are not connected")?,
+            }
+        }
+        Command::Schedule { command } => {
+            let config = load_config(hyprctl, &paths)?;
+            let state_path = schedule::state_path(&paths.base_dir);
+            let (name, enabled) = match command {
+                ScheduleCommand::List => {
+                    let disabled = schedule::load_disabled(&state_path)?;
+                    for entry in &config.schedules {
+                        write_stdout(&schedule::format_schedule(entry, &disabled))?;
+                    }
+                    return Ok(());
                 }
+                ScheduleCommand::Enable { name } => (name, true),
+                ScheduleCommand::Disable { name } => (name, false),
+            };
+            schedule::set_enabled(&state_path, &config.schedules, &name, enabled)?;
+            if control::daemon_reachable(&control::control_socket_path(&paths.runtime_dir)) {
+                send_control(&paths.runtime_dir, control::ControlCommand::Reload)?;
+            }
+        }
+        #[cfg(feature = "cli-full")]
+        Command::Tui => full::tui(hyprctl, &paths, &bin_path)?,
+        Command::Keyword {
+            name,
+            value,
+            dry_run,
+        } => {
+            if let Some(output) = run_keyword(hyprctl, &paths, &name, &value, dry_run)? {
+                write_stdout(&output)?;
             }
         }
         Command::Status => {
-            let config = load_config(&paths)?;
-            let pid_source = SystemDaemonPidSource;
+            let config = load_config(hyprctl, &paths).inspect_err(|_| {
+                let control_path = control::read_only_socket_path(&paths.runtime_dir);
+                if let Some(error) = control::safe_mode_error(&control_path) {
+                    let _ = write_stdout(&format!("Daemon: safe mode\nConfig error: {error}"));
+                }
+            })?;
+            let pid_source = SystemDaemonPidSource {
+                signature: env::var("HYPRLAND_INSTANCE_SIGNATURE").ok(),
+            };
             let output = status_output(hyprctl, &config, &paths, &pid_source)?;
             write_stdout(&output)?;
         }
-        Command::Completions { .. } => {}
+        Command::Completions { .. } | Command::Capabilities { .. } => {}
     }
 
     Ok(())
 }
 
-fn load_config(paths: &EnvPaths) -> Result<Config, CliError> {
-    Ok(Config::from_path(&paths.config_path)?)
-}
-
-fn status_output(
+fn run_daemon(
+    ipc: IpcBackend,
     hyprctl: &dyn HyprlandIpc,
-    config: &Config,
     paths: &EnvPaths,
-    pid_source: &dyn DaemonPidSource,
-) -> Result<String, CliError> {
-    let daemon = match read_daemon_pid(&paths.base_dir)? {
-        Some(pid) => {
-            let pids = pid_source.pids()?;
-            if pids.contains(&pid) {
-                format!("Daemon: running (PID {pid})")
-            } else {
-                "Daemon: stopped".to_string()
+    bin_path: &str,
+    replace: bool,
+    record: Option<&Path>,
+    options: DaemonOptions,
+) -> Result<(), CliError> {
+    if hyprctl.compositor() != Compositor::Hyprland {
+        return Err(CliError::DaemonUnsupported(hyprctl.compositor()));
+    }
+    #[cfg(feature = "native-ipc")]
+    if record.is_some() && matches!(event_source_kind(ipc), daemon::EventSourceKind::Native) {
+        eprintln!(
+            "warning: --record needs the socket2 event stream and is ignored with --ipc native"
+        );
+    }
+    let _instance = acquire_instance_lock(&paths.state_dir, &paths.runtime_dir, replace)?;
+    ensure_setup(hyprctl, paths, bin_path)?;
+    let socket_path = socket2_path()?;
+    ensure_socket(&socket_path)?;
+    let control = control::ControlListener::bind_runtime_dir(&paths.runtime_dir)?;
+    let signals = shutdown::install()?;
+    let base_config = match load_config(hyprctl, paths) {
+        Ok(config) => config,
+        Err(CliError::Config(err)) => {
+            let safe_mode = SafeMode::enter(paths, &err);
+            match drive_safe_mode(ipc, &socket_path, paths, &control, &signals, safe_mode)? {
+                SafeModeExit::Recovered(config) => *config,
+                SafeModeExit::Stop => {
+                    release_daemon_pid(&paths.state_dir);
+                    return Ok(());
+                }
             }
         }
-        None => "Daemon: stopped".to_string(),
+        Err(err) => return Err(err),
     };
-    let active = hyprctl.active_workspace_id()?;
-    let primary_workspace = normalize_workspace(active, config.paired_offset);
-    let secondary_workspace = primary_workspace + config.paired_offset;
-    let config_path = paths.config_path.display();
-
-    Ok(format!(
-        "{daemon}\nConfig: {config_path}\n\nPaired Monitors:\n  Primary:   {primary}\n  Secondary: {secondary}\n  Offset:    {offset}\n\nActive workspace pair: {primary_workspace} / {secondary_workspace}",
-        primary = config.primary_monitor,
-        secondary = config.secondary_monitor,
-        offset = config.paired_offset,
-    ))
-}
-
-fn handle_setup_install(
-    hyprctl: &dyn HyprlandIpc,
-    paths: &EnvPaths,
-    bin_path: &str,
-    waybar: bool,
-) -> Result<(), CliError> {
-    let launcher = SystemDaemonLauncher;
-    handle_setup_install_with_launcher(hyprctl, paths, bin_path, waybar, &launcher)
-}
-
-fn handle_setup_install_with_launcher<L: DaemonLauncher>(
-    hyprctl: &dyn HyprlandIpc,
-    paths: &EnvPaths,
-    bin_path: &str,
-    waybar: bool,
-    launcher: &L,
-) -> Result<(), CliError> {
-    let monitors = hyprctl.monitors().ok();
-    setup::install(
-        &paths.base_dir,
-        bin_path,
-        &paths.hypr_config_dir,
-        &paths.config_path,
-        monitors.as_deref(),
-    )?;
-    if waybar {
-        setup::install_waybar(&paths.base_dir, bin_path)?;
-    }
-    let _ = hyprctl.reload();
-    launcher.launch(bin_path, &paths.base_dir)?;
-    Ok(())
+    let journal = journal::Journal::new(
+        journal::journal_path(&paths.base_dir),
+        journal::DEFAULT_JOURNAL_MAX_BYTES,
+    );
+    let hyprctl = journal::JournalingIpc::new(hyprctl, journal);
+    let state = DaemonLoop::start(hyprctl, paths, base_config, options)?;
+    drive_daemon(ipc, &socket_path, record, paths, &control, &signals, state)
 }
 
-fn ensure_setup(
+/// Feeds a `--record` file through the daemon loop against scratch data dirs.
+fn replay_daemon(
     hyprctl: &dyn HyprlandIpc,
     paths: &EnvPaths,
-    bin_path: &str,
+    recording: &Path,
+    speed: f64,
+    options: DaemonOptions,
 ) -> Result<(), CliError> {
-    if paths.base_dir.join("bindings.conf").exists() {
-        return Ok(());
+    let base_config = load_config(hyprctl, paths)?;
+    let timeout = options.debounce.apply(&base_config.debounce).rebalance();
+    let mut source = replay::ReplayEventSource::open(recording, speed, timeout)?;
+    let scratch = paths.runtime_dir.join("replay");
+    let _ = fs::remove_dir_all(&scratch);
+    let paths = replay_paths(paths, &scratch);
+    for dir in [&paths.base_dir, &paths.state_dir, &paths.sessions_dir] {
+        fs::create_dir_all(dir)?;
     }
-    let monitors = hyprctl.monitors().ok();
-    setup::install(
-        &paths.base_dir,
-        bin_path,
-        &paths.hypr_config_dir,
-        &paths.config_path,
-        monitors.as_deref(),
-    )?;
-    let _ = hyprctl.reload();
+    let dry_run = replay::DryRunIpc::new(hyprctl, io::stdout());
+    let journal = journal::Journal::new(
+        journal::journal_path(&paths.base_dir),
+        journal::DEFAULT_JOURNAL_MAX_BYTES,
+    );
+    let hyprctl = journal::JournalingIpc::new(&dry_run, journal);
+    let mut state = DaemonLoop::start(hyprctl, &paths, base_config, options)?;
+    let mut bus = daemon_event_bus();
+    while bus.poll(&mut source, &mut state)? == events::Poll::Dispatched {}
     Ok(())
 }
 
-fn bin_path() -> String {
-    env::args()
-        .next()
-        .unwrap_or_else(|| "hyprspaces".to_string())
+fn replay_paths(paths: &EnvPaths, scratch: &Path) -> EnvPaths {
+    EnvPaths {
+        base_dir: scratch.join("data"),
+        state_dir: scratch.join("state"),
+        runtime_dir: scratch.to_path_buf(),
+        sessions_dir: scratch.join("sessions"),
+        waybar_css: scratch.join("waybar.css"),
+        autostart_dir: scratch.join("autostart"),
+        ..paths.clone()
+    }
+}
+
+#[cfg(not(feature = "async-daemon"))]
+fn drive_daemon(
+    ipc: IpcBackend,
+    socket_path: &Path,
+    record: Option<&Path>,
+    _paths: &EnvPaths,
+    control: &control::ControlListener,
+    _signals: &UnixStream,
+    mut state: DaemonLoop<'_>,
+) -> Result<(), CliError> {
+    let timeout = state.debounce().rebalance();
+    let mut source = build_event_source(ipc, socket_path, timeout, record)?;
+    let mut bus = daemon_event_bus();
+    loop {
+        if shutdown::requested() {
+            state.shutdown();
+            return Ok(());
+        }
+        if let Some(request) = control.poll()?
+            && state.handle_control(request)
+        {
+            return Ok(());
+        }
+        if bus.poll(&mut *source, &mut state)? == events::Poll::Disconnected {
+            state.disconnect();
+            let reconnect = reconnect_event_source(ipc, socket_path, timeout, record, || {
+                if shutdown::requested() {
+                    state.shutdown();
+                    return Ok(Some(()));
+                }
+                Ok(match control.poll()? {
+                    Some(request) => state.handle_control(request).then_some(()),
+                    None => None,
+                })
+            })?;
+            match reconnect {
+                Reconnect::Connected(reconnected) => source = reconnected,
+                Reconnect::Stopped(()) => return Ok(()),
+                Reconnect::GaveUp => {
+                    state.shutdown();
+                    return Err(CliError::EventSourceLost(daemon::RECONNECT_ATTEMPTS));
+                }
+            }
+            if let Err(err) = state.resync("reconnect resync") {
+                eprintln!("error: resync after reconnect failed: {err}");
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async-daemon")]
+fn drive_daemon(
+    ipc: IpcBackend,
+    socket_path: &Path,
+    record: Option<&Path>,
+    paths: &EnvPaths,
+    control: &control::ControlListener,
+    signals: &UnixStream,
+    mut state: DaemonLoop<'_>,
+) -> Result<(), CliError> {
+    let tokio_runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    tokio_runtime.block_on(async {
+        let control = runtime::AsyncControl::new(control)?;
+        let signals = runtime::ShutdownWatch::new(signals.try_clone()?)?;
+        let mut watcher =
+            runtime::ConfigWatcher::new(&paths.config_path, runtime::CONFIG_POLL_INTERVAL);
+        let mut source = build_async_event_source(ipc, socket_path, record).await?;
+        let mut bus = daemon_event_bus();
+        loop {
+            let event = runtime::next_loop_event(
+                &mut source,
+                &control,
+                &signals,
+                &mut watcher,
+                state.flush_deadline(),
+            )
+            .await?;
+            match event {
+                runtime::LoopEvent::Shutdown => {
+                    state.shutdown();
+                    return Ok(());
+                }
+                runtime::LoopEvent::Control(request) => {
+                    if state.handle_control(request) {
+                        return Ok(());
+                    }
+                }
+                runtime::LoopEvent::ConfigChanged => {
+                    if let Err(err) = state.reload("config change") {
+                        eprintln!("error: config reload failed: {err}");
+                    }
+                }
+                runtime::LoopEvent::Daemon(daemon::DaemonEvent::Disconnected) => {
+                    state.disconnect();
+                    let reconnect = reconnect_async_event_source(
+                        ipc,
+                        socket_path,
+                        record,
+                        &control,
+                        &signals,
+                        |event| match event {
+                            runtime::LoopEvent::Control(request) => {
+                                state.handle_control(request).then_some(())
+                            }
+                            _ => {
+                                state.shutdown();
+                                Some(())
+                            }
+                        },
+                    )
+                    .await?;
+                    match reconnect {
+                        Reconnect::Connected(reconnected) => source = reconnected,
+                        Reconnect::Stopped(()) => return Ok(()),
+                        Reconnect::GaveUp => {
+                            state.shutdown();
+                            return Err(CliError::EventSourceLost(daemon::RECONNECT_ATTEMPTS));
+                        }
+                    }
+                    if let Err(err) = state.resync("reconnect resync") {
+                        eprintln!("error: resync after reconnect failed: {err}");
+                    }
+                }
+                runtime::LoopEvent::Daemon(event) => bus.dispatch(&mut state, &event)?,
+            }
+        }
+    })
+}
+
+#[cfg(not(feature = "async-daemon"))]
+fn drive_safe_mode(
+    ipc: IpcBackend,
+    socket_path: &Path,
+    _paths: &EnvPaths,
+    control: &control::ControlListener,
+    _signals: &UnixStream,
+    mut safe_mode: SafeMode<'_>,
+) -> Result<SafeModeExit, CliError> {
+    let mut source =
+        build_event_source(ipc, socket_path, daemon::DEFAULT_REBALANCE_DEBOUNCE, None)?;
+    loop {
+        if shutdown::requested() {
+            return Ok(SafeModeExit::Stop);
+        }
+        if let Some(request) = control.poll()?
+            && let Some(exit) = safe_mode.handle_control(request)
+        {
+            return Ok(exit);
+        }
+        match source.next_event()? {
+            daemon::DaemonEvent::Disconnected => {
+                let reconnect = reconnect_event_source(
+                    ipc,
+                    socket_path,
+                    daemon::DEFAULT_REBALANCE_DEBOUNCE,
+                    None,
+                    || {
+                        if shutdown::requested() {
+                            return Ok(Some(SafeModeExit::Stop));
+                        }
+                        Ok(control
+                            .poll()?
+                            .and_then(|request| safe_mode.handle_control(request)))
+                    },
+                )?;
+                match reconnect {
+                    Reconnect::Connected(reconnected) => source = reconnected,
+                    Reconnect::Stopped(exit) => return Ok(exit),
+                    Reconnect::GaveUp => {
+                        return Err(CliError::EventSourceLost(daemon::RECONNECT_ATTEMPTS));
+                    }
+                }
+            }
+            event => safe_mode.log_event(&event),
+        }
+    }
+}
+
+#[cfg(feature = "async-daemon")]
+fn drive_safe_mode(
+    ipc: IpcBackend,
+    socket_path: &Path,
+    paths: &EnvPaths,
+    control: &control::ControlListener,
+    signals: &UnixStream,
+    mut safe_mode: SafeMode<'_>,
+) -> Result<SafeModeExit, CliError> {
+    let tokio_runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    tokio_runtime.block_on(async {
+        let control = runtime::AsyncControl::new(control)?;
+        let signals = runtime::ShutdownWatch::new(signals.try_clone()?)?;
+        let mut watcher =
+            runtime::ConfigWatcher::new(&paths.config_path, runtime::CONFIG_POLL_INTERVAL);
+        let mut source = build_async_event_source(ipc, socket_path, None).await?;
+        loop {
+            match runtime::next_loop_event(&mut source, &control, &signals, &mut watcher, None)
+                .await?
+            {
+                runtime::LoopEvent::Shutdown => return Ok(SafeModeExit::Stop),
+                runtime::LoopEvent::Control(request) => {
+                    if let Some(exit) = safe_mode.handle_control(request) {
+                        return Ok(exit);
+                    }
+                }
+                runtime::LoopEvent::ConfigChanged => {
+                    if let Some(config) = safe_mode.try_reload() {
+                        return Ok(SafeModeExit::Recovered(Box::new(config)));
+                    }
+                }
+                runtime::LoopEvent::Daemon(daemon::DaemonEvent::Disconnected) => {
+                    let reconnect = reconnect_async_event_source(
+                        ipc,
+                        socket_path,
+                        None,
+                        &control,
+                        &signals,
+                        |event| match event {
+                            runtime::LoopEvent::Control(request) => {
+                                safe_mode.handle_control(request)
+                            }
+                            _ => Some(SafeModeExit::Stop),
+                        },
+                    )
+                    .await?;
+                    match reconnect {
+                        Reconnect::Connected(reconnected) => source = reconnected,
+                        Reconnect::Stopped(exit) => return Ok(exit),
+                        Reconnect::GaveUp => {
+                            return Err(CliError::EventSourceLost(daemon::RECONNECT_ATTEMPTS));
+                        }
+                    }
+                }
+                runtime::LoopEvent::Daemon(event) => safe_mode.log_event(&event),
+            }
+        }
+    })
+}
+
+#[cfg(feature = "async-daemon")]
+async fn build_async_event_source(
+    backend: IpcBackend,
+    socket_path: &Path,
+    record: Option<&Path>,
+) -> Result<runtime::AsyncEventSource, CliError> {
+    match event_source_kind(backend) {
+        daemon::EventSourceKind::Socket2 => {
+            let mut source = runtime::AsyncEventSource::socket2(socket_path).await?;
+            if let Some(path) = record {
+                source = source.with_recorder(replay::Recorder::open(path)?);
+            }
+            Ok(source)
+        }
+        #[cfg(feature = "native-ipc")]
+        daemon::EventSourceKind::Native => {
+            let source = daemon::NativeEventSource::new(daemon::DEFAULT_REBALANCE_DEBOUNCE)?;
+            Ok(runtime::AsyncEventSource::bridged(Box::new(source)))
+        }
+    }
+}
+
+#[cfg(feature = "async-daemon")]
+/// Retries the event source; `stop` sees shutdowns and control requests between attempts.
+async fn reconnect_async_event_source<T>(
+    backend: IpcBackend,
+    socket_path: &Path,
+    record: Option<&Path>,
+    control: &runtime::AsyncControl<'_>,
+    signals: &runtime::ShutdownWatch,
+    mut stop: impl FnMut(runtime::LoopEvent) -> Option<T>,
+) -> Result<Reconnect<runtime::AsyncEventSource, T>, CliError> {
+    for _ in 0..daemon::RECONNECT_ATTEMPTS {
+        let retry = tokio::time::sleep(daemon::RECONNECT_INTERVAL);
+        tokio::pin!(retry);
+        loop {
+            let event = tokio::select! {
+                biased;
+                result = signals.requested() => result.map(|()| runtime::LoopEvent::Shutdown)?,
+                request = control.accept() => runtime::LoopEvent::Control(request?),
+                () = &mut retry => break,
+            };
+            if let Some(stopped) = stop(event) {
+                return Ok(Reconnect::Stopped(stopped));
+            }
+        }
+        if let Ok(source) = build_async_event_source(backend, socket_path, record).await {
+            return Ok(Reconnect::Connected(source));
+        }
+    }
+    Ok(Reconnect::GaveUp)
+}
+
+fn daemon_event_bus<'a>() -> events::EventBus<'static, DaemonLoop<'a>, CliError> {
+    let mut bus = events::EventBus::new();
+    bus.on_event(Duration::ZERO, DaemonLoop::handle_event);
+    bus
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DaemonOptions {
+    initial_rebalance: bool,
+    trace_decisions: bool,
+    restore_on_start: bool,
+    debounce: DebounceOverrides,
+}
+
+/// Debounce flags, which win over the config's `debounce` keys.
+#[derive(Debug, Clone, Copy, Default)]
+struct DebounceOverrides {
+    rebalance_ms: Option<u64>,
+    focus_ms: Option<u64>,
+}
+
+impl DebounceOverrides {
+    fn apply(&self, config: &DebounceConfig) -> DebounceConfig {
+        DebounceConfig {
+            rebalance_ms: self.rebalance_ms.unwrap_or(config.rebalance_ms),
+            focus_ms: self.focus_ms.unwrap_or(config.focus_ms),
+            ..*config
+        }
+    }
+}
+
+struct DaemonLoop<'a> {
+    hyprctl: journal::JournalingIpc<'a>,
+    paths: &'a EnvPaths,
+    base_config: Config,
+    active: Option<Config>,
+    options: HyprlandOptions,
+    rebalance_debounce: Box<dyn daemon::DebounceStrategy>,
+    focus_debounce: daemon::FocusSwitchDebounce,
+    debounce_overrides: DebounceOverrides,
+    hotplug_settle: daemon::HotplugSettle,
+    reload_quiesce: daemon::ReloadQuiesce,
+    drag_guard: daemon::DragGuard,
+    launches: daemon::LaunchPlacements,
+    cache: daemon::StateCache,
+    batches: BatchCache,
+    breaker: retry::CircuitBreaker,
+    subscribers: Vec<control::Subscriber>,
+    scheduler: schedule::Scheduler,
+    clock: schedule::LocalClock,
+    idle_inhibitor: inhibit::IdleInhibitor,
+    history: daemon::SlotHistory,
+    sticky: BTreeSet<String>,
+    held: Option<PairedSide>,
+    reconcile_timer: reconcile::ReconcileTimer,
+    drift: Vec<reconcile::Drift>,
+    mode: daemon::DaemonMode,
+    trace_decisions: bool,
+    #[cfg(feature = "cli-full")]
+    waybar_push: Option<full::WaybarPush>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<std::sync::Arc<metrics::Metrics>>,
+}
+
+#[cfg(feature = "metrics")]
+fn start_metrics(config: &Config) -> Option<std::sync::Arc<metrics::Metrics>> {
+    let address = config.metrics_address.as_deref()?;
+    let metrics = std::sync::Arc::new(metrics::Metrics::default());
+    match metrics::serve(address, std::sync::Arc::clone(&metrics)) {
+        Ok(()) => Some(metrics),
+        Err(err) => {
+            eprintln!("error: metrics exporter on {address}: {err}");
+            None
+        }
+    }
+}
+
+#[cfg(feature = "cli-full")]
+fn restore_on_start(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    config: &Config,
+    launches: &mut daemon::LaunchPlacements,
+) {
+    match session::restore_on_start(
+        hyprctl,
+        config,
+        &paths::sessions_dir(&paths.base_dir, None),
+        &session::restore_marker_path(&paths.state_dir),
+    ) {
+        Ok(Some((path, launched))) => {
+            eprintln!("restored session from {}", path.display());
+            let now = std::time::Instant::now();
+            for launch in launched {
+                launches.expect(launch.class, launch.workspace, now);
+            }
+        }
+        Ok(None) => {}
+        Err(err) => eprintln!("error: restore on start: {err}"),
+    }
+}
+
+#[cfg(not(feature = "cli-full"))]
+fn restore_on_start(
+    _hyprctl: &dyn HyprlandIpc,
+    _paths: &EnvPaths,
+    _config: &Config,
+    _launches: &mut daemon::LaunchPlacements,
+) {
+    eprintln!("warning: --restore-on-start is ignored without --features cli-full");
+}
+
+#[cfg(not(feature = "cli-full"))]
+fn warn_waybar_push_unavailable(config: &Config) {
+    if config.waybar.mode == WaybarMode::DaemonPush {
+        eprintln!("warning: waybar daemon_push mode is ignored without --features cli-full");
+    }
+}
+
+impl<'a> DaemonLoop<'a> {
+    fn start(
+        hyprctl: journal::JournalingIpc<'a>,
+        paths: &'a EnvPaths,
+        base_config: Config,
+        launch: DaemonOptions,
+    ) -> Result<Self, CliError> {
+        hyprctl.set_trigger("startup");
+        let options = HyprlandOptions::query(&hyprctl);
+        let active = daemon::select_config(&hyprctl, &base_config)?;
+        if let Some(config) = active
+            .as_ref()
+            .filter(|config| launch.initial_rebalance && config.rebalance_on_start)
+        {
+            daemon::rebalance_all(&hyprctl, config)?;
+        }
+        let mut launches = daemon::LaunchPlacements::new(daemon::LAUNCH_PLACEMENT_WINDOW);
+        if launch.restore_on_start {
+            hyprctl.set_trigger("restore on start");
+            restore_on_start(
+                &hyprctl,
+                paths,
+                active.as_ref().unwrap_or(&base_config),
+                &mut launches,
+            );
+        }
+        let hotplug_settle =
+            daemon::HotplugSettle::new(Duration::from_millis(base_config.hotplug_settle_ms));
+        let drag_guard =
+            daemon::DragGuard::new(Duration::from_millis(base_config.drag_focus_delay_ms));
+        let mut cache = daemon::StateCache::default();
+        cache.seed(&hyprctl)?;
+        hyprctl.capture(launch.trace_decisions);
+        let scheduler = schedule::Scheduler::new(
+            base_config.schedules.clone(),
+            schedule::load_disabled(&schedule::state_path(&paths.base_dir))?,
+        );
+        let breaker = retry::CircuitBreaker::from_config(&base_config.retry);
+        let reconcile_timer = reconcile::ReconcileTimer::from_config(
+            &base_config.reconcile,
+            std::time::Instant::now(),
+        );
+        let debounce = launch.debounce.apply(&base_config.debounce);
+        let mut daemon = Self {
+            hyprctl,
+            paths,
+            base_config,
+            active,
+            options,
+            rebalance_debounce: daemon::rebalance_debounce(&debounce),
+            focus_debounce: daemon::FocusSwitchDebounce::new(debounce.focus()),
+            debounce_overrides: launch.debounce,
+            hotplug_settle,
+            reload_quiesce: daemon::ReloadQuiesce::new(daemon::DEFAULT_RELOAD_QUIET),
+            drag_guard,
+            launches,
+            cache,
+            batches: BatchCache::default(),
+            breaker,
+            subscribers: Vec::new(),
+            scheduler,
+            clock: schedule::LocalClock::default(),
+            idle_inhibitor: inhibit::IdleInhibitor::new(),
+            history: daemon::SlotHistory::default(),
+            sticky: sticky::load(&sticky::state_path(&paths.state_dir))?,
+            held: hold::load(&hold::state_path(&paths.state_dir))?,
+            reconcile_timer,
+            drift: Vec::new(),
+            mode: daemon::DaemonMode::Starting,
+            trace_decisions: launch.trace_decisions,
+            #[cfg(feature = "cli-full")]
+            waybar_push: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        };
+        #[cfg(feature = "cli-full")]
+        {
+            daemon.waybar_push = full::WaybarPush::for_config(&daemon.base_config, paths);
+        }
+        #[cfg(not(feature = "cli-full"))]
+        warn_waybar_push_unavailable(&daemon.base_config);
+        #[cfg(feature = "metrics")]
+        {
+            daemon.metrics = start_metrics(&daemon.base_config);
+        }
+        #[cfg(not(feature = "metrics"))]
+        if daemon.base_config.metrics_address.is_some() {
+            eprintln!("warning: metrics_address is ignored without --features metrics");
+        }
+        daemon.track_active();
+        daemon.remember_resolved();
+        daemon.transition(daemon::ModeTrigger::Selected {
+            paired: daemon.active.is_some(),
+        });
+        Ok(daemon)
+    }
+
+    #[cfg(feature = "async-daemon")]
+    fn flush_deadline(&self) -> Option<std::time::Instant> {
+        let rebalance = self
+            .active
+            .as_ref()
+            .and_then(|_| self.rebalance_debounce.flush_deadline());
+        let schedule = self
+            .scheduler
+            .is_active()
+            .then(|| schedule::next_minute(std::time::Instant::now()));
+        [
+            rebalance,
+            self.hotplug_settle.deadline(),
+            self.reload_quiesce.deadline(),
+            self.drag_guard.deadline(),
+            self.reconcile_timer.deadline(),
+            schedule,
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    /// Flushes a pending rebalance, snapshots the session, and releases the pidfile.
+    fn shutdown(&mut self) {
+        self.transition(daemon::ModeTrigger::Stop);
+        self.hyprctl.set_trigger("shutdown");
+        if let Some(config) = &self.active
+            && let Err(err) = daemon::flush_rebalance_now(
+                &self.hyprctl,
+                config,
+                self.rebalance_debounce.as_mut(),
+                &mut self.batches,
+            )
+        {
+            eprintln!("error: shutdown rebalance failed: {err}");
+        }
+        #[cfg(feature = "cli-full")]
+        {
+            let config = self.active.as_ref().unwrap_or(&self.base_config);
+            let path = session::shutdown_path(&self.paths.sessions_dir);
+            if let Err(err) =
+                session::save_session(&self.hyprctl, config, &self.paths.sessions_dir, Some(&path))
+            {
+                eprintln!("error: shutdown snapshot failed: {err}");
+            }
+        }
+        release_daemon_pid(&self.paths.state_dir);
+    }
+
+    fn handle_control(&mut self, request: control::ControlRequest) -> bool {
+        let response = match request.command {
+            control::ControlCommand::Stop => {
+                self.shutdown();
+                let _ = request.respond("ok");
+                return true;
+            }
+            control::ControlCommand::Reload => match self.reload("control reload") {
+                Ok(()) => "ok".to_string(),
+                Err(err) => format!("error: {err}"),
+            },
+            control::ControlCommand::State => {
+                let config = self.active.as_ref().unwrap_or(&self.base_config);
+                match tui::overview(&self.hyprctl, config) {
+                    Ok(overview) => serde_json::to_string(&overview)
+                        .unwrap_or_else(|err| format!("error: {err}")),
+                    Err(err) => format!("error: {err}"),
+                }
+            }
+            control::ControlCommand::Watch => {
+                self.subscribe(request);
+                return false;
+            }
+            control::ControlCommand::Status => self.mode.as_str().to_string(),
+            control::ControlCommand::History => serde_json::to_string(self.history.slots())
+                .unwrap_or_else(|err| format!("error: {err}")),
+            control::ControlCommand::Urgent => serde_json::to_string(&self.cache.urgent())
+                .unwrap_or_else(|err| format!("error: {err}")),
+            control::ControlCommand::Debounce => serde_json::to_string(&self.debounce())
+                .unwrap_or_else(|err| format!("error: {err}")),
+            control::ControlCommand::Exec => match self.exec(&request.args) {
+                Ok(()) => "ok".to_string(),
+                Err(err) => format!("error: {err}"),
+            },
+        };
+        let _ = request.respond(&response);
+        false
+    }
+
+    /// Runs a `hyprspaces send` command with the daemon's own IPC and state.
+    fn exec(&self, args: &[String]) -> Result<(), CliError> {
+        let command = parse_send_args(args).map_err(|err| {
+            let message = err.to_string();
+            let line = message.lines().next().unwrap_or_default();
+            CliError::Send(line.trim_start_matches("error: ").to_string())
+        })?;
+        let config = self
+            .active
+            .as_ref()
+            .ok_or_else(|| CliError::Send("no paired monitors are connected".to_string()))?;
+        self.hyprctl.set_trigger(format!("send {}", args.join(" ")));
+        match command {
+            PairedCommand::Switch {
+                workspace,
+                raw,
+                cursor,
+            } => {
+                let policy = if raw {
+                    OutOfRangePolicy::Raw
+                } else {
+                    config.out_of_range
+                };
+                let workspace = resolve_workspace_arg(&workspace, config)?;
+                let config = with_cursor(config, cursor);
+                commands::paired_switch_held(&self.hyprctl, &config, workspace, policy, self.held)?;
+            }
+            PairedCommand::Cycle {
+                direction,
+                occupied_only,
+                order,
+                cursor,
+            } => {
+                let options = commands::CycleOptions {
+                    occupied_only: occupied_only || config.cycle_skip_empty,
+                    order: order.into(),
+                    history: if order == CycleOrderArg::Mru {
+                        self.history.slots().to_vec()
+                    } else {
+                        Vec::new()
+                    },
+                    held: self.held,
+                };
+                commands::paired_cycle_with_options(
+                    &self.hyprctl,
+                    &with_cursor(config, cursor),
+                    direction.into(),
+                    &options,
+                )?;
+            }
+            PairedCommand::MoveWindow { workspace } => {
+                let workspace = resolve_workspace_arg(&workspace, config)?;
+                commands::paired_move_window(&self.hyprctl, config, workspace)?;
+            }
+            PairedCommand::SendTo { target } => {
+                commands::paired_send_to(&self.hyprctl, config, target.into())?;
+            }
+            PairedCommand::Urgent => {
+                let address = self
+                    .cache
+                    .urgent()
+                    .ok_or(commands::CommandError::NoUrgentWindow)?;
+                commands::paired_focus_window(&self.hyprctl, config, address)?;
+            }
+            PairedCommand::GrabRogue
+            | PairedCommand::Clone { .. }
+            | PairedCommand::Stick { .. }
+            | PairedCommand::Hold { .. } => {
+                return Err(CliError::Send(
+                    "only switch, cycle, move-window, send-to, and urgent run through send"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn subscribe(&mut self, request: control::ControlRequest) {
+        if let Ok(mut subscriber) = request.subscribe()
+            && self
+                .bar_line()
+                .is_none_or(|line| subscriber.send(&line).is_ok())
+        {
+            self.subscribers.push(subscriber);
+        }
+    }
+
+    fn bar_line(&self) -> Option<String> {
+        let mut state = self.cache.bar_state()?;
+        state.mode = self.bar_mode();
+        serde_json::to_string(&state).ok()
+    }
+
+    fn bar_mode(&self) -> daemon::BarMode {
+        match self.mode {
+            daemon::DaemonMode::Resyncing if self.active.is_none() => daemon::BarMode::Passthrough,
+            mode => mode.bar_mode(),
+        }
+    }
+
+    fn transition(&mut self, trigger: daemon::ModeTrigger) {
+        self.mode = self.mode.next(trigger);
+    }
+
+    fn disconnect(&mut self) {
+        self.transition(daemon::ModeTrigger::SocketLost);
+        self.publish();
+    }
+
+    fn publish(&mut self) {
+        #[cfg(feature = "cli-full")]
+        self.push_waybar();
+        let Some(line) = self.bar_line() else {
+            return;
+        };
+        self.subscribers
+            .retain_mut(|subscriber| subscriber.send(&line).is_ok());
+    }
+
+    #[cfg(feature = "cli-full")]
+    fn push_waybar(&self) {
+        let (Some(push), Some(mut state)) = (&self.waybar_push, self.cache.bar_state()) else {
+            return;
+        };
+        state.mode = self.bar_mode();
+        let config = &self.base_config;
+        let clients = config
+            .waybar
+            .tooltip_windows
+            .then(|| self.hyprctl.clients().ok())
+            .flatten();
+        push.write(&state, config, clients.as_deref());
+    }
+
+    /// Persists the resolved monitor matches and refreshes the workspace rules.
+    fn remember_resolved(&self) {
+        let Some(config) = &self.active else {
+            return;
+        };
+        let path = resolved::state_path(&self.paths.state_dir);
+        match resolved::store(&path, &resolved::ResolvedMonitors::of(config)) {
+            Ok(true) => eprintln!(
+                "monitors: resolved to {} / {}",
+                config.primary_monitor, config.secondary_monitor
+            ),
+            Ok(false) => {}
+            Err(err) => eprintln!("error: failed to store resolved monitors: {err}"),
+        }
+        #[cfg(feature = "cli-full")]
+        full::refresh_workspace_rules(&self.paths.base_dir, config);
+    }
+
+    fn track_active(&mut self) {
+        let config = self.active.as_ref().unwrap_or(&self.base_config);
+        let active_workspace = self.cache.active_workspace();
+        if let Some(workspace) = active_workspace {
+            self.history.record(config.slot_of(workspace));
+        }
+        let slot = inhibit::inhibiting_slot(
+            &config.idle_inhibit_slots,
+            active_workspace,
+            config.paired_offset,
+            config.workspace_base,
+        );
+        if let Err(err) = self.idle_inhibitor.update(slot) {
+            eprintln!("error: idle inhibit failed: {err}");
+        }
+    }
+
+    /// The config's debounce windows with any daemon flags on top.
+    fn debounce(&self) -> DebounceConfig {
+        self.debounce_overrides.apply(&self.base_config.debounce)
+    }
+
+    fn reload(&mut self, trigger: &str) -> Result<(), String> {
+        self.base_config = load_config(&self.hyprctl, self.paths).map_err(|err| err.to_string())?;
+        self.hyprctl.set_trigger(trigger);
+        self.options = HyprlandOptions::query(&self.hyprctl);
+        self.focus_debounce.reset();
+        let debounce = self.debounce();
+        if self.rebalance_debounce.mode() == debounce.mode {
+            self.rebalance_debounce.reset();
+            self.rebalance_debounce.configure(&debounce);
+        } else {
+            self.rebalance_debounce = daemon::rebalance_debounce(&debounce);
+        }
+        self.focus_debounce.set_interval(debounce.focus());
+        self.hotplug_settle =
+            daemon::HotplugSettle::new(Duration::from_millis(self.base_config.hotplug_settle_ms));
+        self.drag_guard =
+            daemon::DragGuard::new(Duration::from_millis(self.base_config.drag_focus_delay_ms));
+        self.breaker = retry::CircuitBreaker::from_config(&self.base_config.retry);
+        self.reconcile_timer = reconcile::ReconcileTimer::from_config(
+            &self.base_config.reconcile,
+            std::time::Instant::now(),
+        );
+        #[cfg(feature = "cli-full")]
+        {
+            self.waybar_push = full::WaybarPush::for_config(&self.base_config, self.paths);
+        }
+        #[cfg(not(feature = "cli-full"))]
+        warn_waybar_push_unavailable(&self.base_config);
+        let disabled = schedule::load_disabled(&schedule::state_path(&self.paths.base_dir))
+            .map_err(|err| err.to_string())?;
+        self.scheduler
+            .update(self.base_config.schedules.clone(), disabled);
+        self.sticky = sticky::load(&sticky::state_path(&self.paths.state_dir))
+            .map_err(|err| err.to_string())?;
+        self.held =
+            hold::load(&hold::state_path(&self.paths.state_dir)).map_err(|err| err.to_string())?;
+        self.cache
+            .seed(&self.hyprctl)
+            .map_err(|err| err.to_string())?;
+        self.track_active();
+        self.active = daemon::select_config(&self.hyprctl, &self.base_config)
+            .map_err(|err| err.to_string())?;
+        self.remember_resolved();
+        self.transition(daemon::ModeTrigger::Selected {
+            paired: self.active.is_some(),
+        });
+        self.publish();
+        match &self.active {
+            Some(config) => {
+                daemon::rebalance_all(&self.hyprctl, config).map_err(|err| err.to_string())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn resync(&mut self, trigger: &str) -> Result<(), CliError> {
+        self.hyprctl.set_trigger(trigger);
+        self.reload_quiesce.reset();
+        self.options = HyprlandOptions::query(&self.hyprctl);
+        self.cache.seed(&self.hyprctl)?;
+        self.track_active();
+        self.active = daemon::select_config(&self.hyprctl, &self.base_config)?;
+        self.remember_resolved();
+        self.transition(daemon::ModeTrigger::Selected {
+            paired: self.active.is_some(),
+        });
+        self.drag_guard.reset();
+        self.publish();
+        if let Some(config) = &self.active {
+            daemon::resync(
+                &self.hyprctl,
+                config,
+                self.rebalance_debounce.as_mut(),
+                &mut self.focus_debounce,
+                &mut self.batches,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn reselect(&mut self) -> Result<bool, CliError> {
+        let selected =
+            daemon::select_config_cached(&self.hyprctl, &self.base_config, &mut self.cache)?;
+        if selected == self.active && !self.hotplug_settle.is_enabled() {
+            return Ok(false);
+        }
+        self.active = selected;
+        self.remember_resolved();
+        self.transition(daemon::ModeTrigger::Selected {
+            paired: self.active.is_some(),
+        });
+        self.publish();
+        self.rebalance_debounce.reset();
+        self.focus_debounce.reset();
+        self.drag_guard.reset();
+        if let Some(config) = &self.active {
+            daemon::rebalance_all_cached(&self.hyprctl, config, &mut self.batches)?;
+        }
+        Ok(true)
+    }
+
+    fn run_schedules(&mut self) -> Result<(), CliError> {
+        if !self.scheduler.is_active() || !self.mode.policy().schedules {
+            return Ok(());
+        }
+        let due = self.scheduler.due(self.clock.now());
+        let Some(config) = &self.active else {
+            return Ok(());
+        };
+        for entry in due {
+            self.hyprctl.set_trigger(format!("schedule {}", entry.name));
+            commands::paired_switch(&self.hyprctl, config, config.slot_workspace(entry.slot))?;
+        }
+        Ok(())
+    }
+
+    fn run_reconcile(&mut self) -> Result<(), CliError> {
+        if !self.mode.policy().reconcile || !self.reconcile_timer.due(std::time::Instant::now()) {
+            return Ok(());
+        }
+        let Some(config) = &self.active else {
+            return Ok(());
+        };
+        let fix = config.reconcile.policy == ReconcilePolicy::Fix;
+        self.hyprctl.set_trigger("reconcile");
+        let drift = reconcile::reconcile(&self.hyprctl, config, fix)?;
+        if drift != self.drift && !drift.is_empty() {
+            eprintln!(
+                "reconcile: {}",
+                reconcile::report(&drift, fix).replace('\n', "; ")
+            );
+        }
+        self.drift = drift;
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &daemon::DaemonEvent) -> Result<(), CliError> {
+        let now = std::time::Instant::now();
+        self.hyprctl.take_captured();
+        if !self.breaker.allow(now) {
+            self.record_decision(event, daemon::Decision::Unresponsive);
+            return Ok(());
+        }
+        let commands = self.hyprctl.stats().commands;
+        let suppressed = self.suppressed();
+        let result = self.dispatch_event(event).map(|decision| {
+            decision.settle(
+                self.hyprctl.stats().commands > commands,
+                self.suppressed() > suppressed,
+            )
+        });
+        if let Ok(decision) = result {
+            self.record_decision(event, decision);
+        }
+        #[cfg(feature = "metrics")]
+        self.record_metrics(event, commands);
+        self.recover(result.map(|_| ()), now)
+    }
+
+    fn suppressed(&self) -> u64 {
+        self.focus_debounce.suppressed() + self.rebalance_debounce.suppressed()
+    }
+
+    /// Counts the decision and, with `--trace-decisions`, logs it.
+    fn record_decision(&self, event: &daemon::DaemonEvent, decision: daemon::Decision) {
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.update(|registry| registry.record_decision(decision.as_str()));
+        }
+        if !self.trace_decisions {
+            return;
+        }
+        let commands = self.hyprctl.take_captured();
+        if matches!(event, daemon::DaemonEvent::Timeout { .. }) && commands.is_empty() {
+            return;
+        }
+        let debounce = if decision == daemon::Decision::Debounced {
+            "suppressed"
+        } else {
+            "pass"
+        };
+        let outcome = if commands.is_empty() {
+            format!("skip: {}", decision.as_str())
+        } else {
+            commands
+                .iter()
+                .map(|command| format!("{command:?}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        eprintln!(
+            "trace: {} ({}) mode={} debounce={debounce} -> {outcome}",
+            event.kind(),
+            event.trigger(),
+            self.mode.as_str()
+        );
+    }
+
+    fn recover(
+        &mut self,
+        result: Result<(), CliError>,
+        now: std::time::Instant,
+    ) -> Result<(), CliError> {
+        match result {
+            Ok(()) => {
+                if self.breaker.record_success() {
+                    eprintln!("hyprland is responding again; resuming dispatches");
+                    if let Err(err) = self.resync("reconnect resync") {
+                        eprintln!("error: resync after recovery failed: {err}");
+                    }
+                }
+                Ok(())
+            }
+            Err(CliError::Hyprctl(err)) => {
+                eprintln!("error: event dispatch failed: {err}");
+                if self.breaker.record_failure(now) {
+                    eprintln!(
+                        "warning: hyprland is not responding; pausing dispatches for {}ms",
+                        self.breaker.cooldown().as_millis()
+                    );
+                    self.transition(daemon::ModeTrigger::Unresponsive);
+                    self.publish();
+                }
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn record_metrics(&self, event: &daemon::DaemonEvent, commands_before: u64) {
+        let Some(metrics) = &self.metrics else {
+            return;
+        };
+        let stats = self.hyprctl.stats();
+        let received = !matches!(event, daemon::DaemonEvent::Timeout { .. });
+        metrics.update(|registry| {
+            if received {
+                registry.record_event(event.kind());
+            }
+            registry.set_ipc_stats(stats);
+            registry.set_suppressed("focus", self.focus_debounce.suppressed());
+            registry.set_suppressed("rebalance", self.rebalance_debounce.suppressed());
+            if received
+                && stats.commands > commands_before
+                && let Some(at) = event.at()
+            {
+                registry.observe_latency(at.elapsed());
+            }
+        });
+    }
+
+    fn dispatch_event(
+        &mut self,
+        event: &daemon::DaemonEvent,
+    ) -> Result<daemon::Decision, CliError> {
+        if let daemon::DaemonEvent::ConfigReloaded { at } = event {
+            self.reload_quiesce.record(*at);
+            self.transition(daemon::ModeTrigger::Settling);
+            return Ok(daemon::Decision::ReloadQuiet);
+        }
+        let now = std::time::Instant::now();
+        if self.reload_quiesce.is_quiet(now) {
+            return Ok(daemon::Decision::ReloadQuiet);
+        }
+        if self.reload_quiesce.due(now) {
+            self.resync("config reloaded")?;
+        }
+        self.run_schedules()?;
+        self.run_reconcile()?;
+        let event = self.cache.resolve(&self.hyprctl, event.clone())?;
+        if self.cache.apply(&event) {
+            self.publish();
+            self.track_active();
+        }
+        if let daemon::DaemonEvent::Window(daemon::WindowEvent::Opened {
+            address,
+            workspace_id,
+            class: Some(class),
+        }) = &event
+            && let Some(workspace) = self.launches.claim(class, now)
+        {
+            let target = WorkspaceTarget::from_name(&workspace);
+            if workspace_id.map(WorkspaceTarget::Id) != Some(target) {
+                self.hyprctl.set_trigger(event.trigger());
+                let mut batch = HyprctlBatch::new();
+                batch.push(Dispatcher::MoveToWorkspaceSilent { target, address });
+                self.hyprctl.batch(&batch.into_argument())?;
+            }
+            return Ok(daemon::Decision::Dispatched);
+        }
+        if let daemon::DaemonEvent::Monitor { at, .. } = event
+            && self.hotplug_settle.is_enabled()
+        {
+            self.hotplug_settle.record(at);
+            self.transition(daemon::ModeTrigger::Settling);
+            return Ok(daemon::Decision::HotplugSettling);
+        }
+        if self.hotplug_settle.due(std::time::Instant::now()) {
+            self.hyprctl.set_trigger("hotplug settled");
+            self.reselect()?;
+        }
+        if matches!(event, daemon::DaemonEvent::Monitor { .. }) {
+            self.hyprctl.set_trigger(event.trigger());
+            if self.reselect()? {
+                return Ok(daemon::Decision::Dispatched);
+            }
+        }
+        let policy = self.mode.policy();
+        let reacts = match &event {
+            daemon::DaemonEvent::Focus(_) => policy.follow_focus,
+            _ => policy.rebalance,
+        };
+        let Some(config) = self.active.as_ref() else {
+            return Ok(daemon::Decision::Unpaired);
+        };
+        if !reacts {
+            return Ok(daemon::Decision::ModePolicy);
+        }
+        let retrying = retry::RetryingIpc::new(
+            &self.hyprctl,
+            retry::RetryPolicy::from_config(&self.base_config.retry),
+        );
+        if config.enforce_range
+            && let daemon::DaemonEvent::Window(
+                daemon::WindowEvent::Opened {
+                    address,
+                    workspace_id: Some(workspace_id),
+                    ..
+                }
+                | daemon::WindowEvent::Moved {
+                    address,
+                    workspace_id: Some(workspace_id),
+                },
+            ) = &event
+            && let Some(target) = commands::range_target(config, *workspace_id)
+        {
+            self.hyprctl.set_trigger(event.trigger());
+            let mut batch = HyprctlBatch::new();
+            batch.push(Dispatcher::MoveToWorkspaceSilent {
+                target: WorkspaceTarget::Id(target),
+                address,
+            });
+            retrying.batch(&batch.into_argument())?;
+            return Ok(daemon::Decision::Dispatched);
+        }
+        if config.jump_to_urgent
+            && let daemon::DaemonEvent::Urgent { address } = &event
+        {
+            self.hyprctl.set_trigger(event.trigger());
+            commands::paired_focus_window(&retrying, config, address)?;
+            return Ok(daemon::Decision::Dispatched);
+        }
+        if let Some(focus) = self.drag_guard.due(std::time::Instant::now())
+            && self.held.is_none()
+        {
+            let workspace_id = focus.workspace_id;
+            let focus = daemon::DaemonEvent::Focus(focus);
+            self.hyprctl
+                .set_trigger(format!("drag settled, {}", focus.trigger()));
+            let switched = daemon::process_event(
+                &retrying,
+                config,
+                self.rebalance_debounce.as_mut(),
+                &mut self.focus_debounce,
+                &mut self.batches,
+                &self.options,
+                focus,
+            )?;
+            if switched && let Some(workspace_id) = workspace_id {
+                sticky::follow(&retrying, config, &self.sticky, workspace_id)?;
+            }
+        }
+        match &event {
+            daemon::DaemonEvent::Focus(focus) if self.drag_guard.hold(focus) => {
+                return Ok(daemon::Decision::DragGuard);
+            }
+            daemon::DaemonEvent::Window(daemon::WindowEvent::Moved { .. }) => {
+                self.drag_guard.record_move(std::time::Instant::now());
+            }
+            _ => {}
+        }
+        if config.fast_switch
+            && let daemon::DaemonEvent::Focus(daemon::FocusEvent {
+                workspace_id: Some(workspace_id),
+                ..
+            }) = &event
+            && self.cache.shows_pair(config, *workspace_id)
+        {
+            return Ok(daemon::Decision::PairShown);
+        }
+        if self.held.is_some() && matches!(event, daemon::DaemonEvent::Focus(_)) {
+            return Ok(daemon::Decision::Held);
+        }
+        let workspace_id = match &event {
+            daemon::DaemonEvent::Focus(focus) => focus.workspace_id,
+            _ => None,
+        };
+        self.hyprctl.set_trigger(event.trigger());
+        let switched = daemon::process_event(
+            &retrying,
+            config,
+            self.rebalance_debounce.as_mut(),
+            &mut self.focus_debounce,
+            &mut self.batches,
+            &self.options,
+            event,
+        )?;
+        if switched && let Some(workspace_id) = workspace_id {
+            sticky::follow(&retrying, config, &self.sticky, workspace_id)?;
+        }
+        Ok(daemon::Decision::Dispatched)
+    }
+}
+
+enum SafeModeExit {
+    Stop,
+    Recovered(Box<Config>),
+}
+
+struct SafeMode<'a> {
+    paths: &'a EnvPaths,
+    error: String,
+}
+
+impl<'a> SafeMode<'a> {
+    fn enter(paths: &'a EnvPaths, err: &ConfigError) -> Self {
+        let error = err.to_string().replace('\n', " ");
+        eprintln!("error: {error}; entering safe mode until the config loads");
+        Self { paths, error }
+    }
+
+    fn try_reload(&mut self) -> Option<Config> {
+        match Config::from_path(&self.paths.config_path) {
+            Ok(config) => Some(config),
+            Err(err) => {
+                self.error = err.to_string().replace('\n', " ");
+                None
+            }
+        }
+    }
+
+    fn handle_command(
+        &mut self,
+        command: control::ControlCommand,
+    ) -> (String, Option<SafeModeExit>) {
+        let status = format!("{}{}", control::SAFE_MODE_PREFIX, self.error);
+        match command {
+            control::ControlCommand::Stop => ("ok".to_string(), Some(SafeModeExit::Stop)),
+            control::ControlCommand::Reload => match self.try_reload() {
+                Some(config) => (
+                    "ok".to_string(),
+                    Some(SafeModeExit::Recovered(Box::new(config))),
+                ),
+                None => (format!("error: {}", self.error), None),
+            },
+            control::ControlCommand::Status => (status, None),
+            control::ControlCommand::State
+            | control::ControlCommand::Watch
+            | control::ControlCommand::History
+            | control::ControlCommand::Urgent
+            | control::ControlCommand::Debounce
+            | control::ControlCommand::Exec => (format!("error: {status}"), None),
+        }
+    }
+
+    fn handle_control(&mut self, request: control::ControlRequest) -> Option<SafeModeExit> {
+        let (response, exit) = self.handle_command(request.command);
+        let _ = request.respond(&response);
+        exit
+    }
+
+    fn log_event(&self, event: &daemon::DaemonEvent) {
+        if !matches!(event, daemon::DaemonEvent::Timeout { .. }) {
+            eprintln!("safe mode: {}", event.trigger());
+        }
+    }
+}
+
+fn acquire_instance_lock(
+    state_dir: &Path,
+    runtime_dir: &Path,
+    replace: bool,
+) -> Result<instance::InstanceLock, CliError> {
+    let path = instance::lock_path(state_dir);
+    match instance::InstanceLock::try_acquire(&path) {
+        Ok(lock) => Ok(lock),
+        Err(instance::InstanceError::AlreadyRunning { .. }) if replace => {
+            stop_running_daemon(state_dir, runtime_dir)?;
+            Ok(instance::InstanceLock::acquire_within(
+                &path,
+                control::CONTROL_TIMEOUT,
+            )?)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn send_control(runtime_dir: &Path, command: control::ControlCommand) -> Result<(), CliError> {
+    let path = control::control_socket_path(runtime_dir);
+    check_control_response(&path, command, control::send_command(&path, command))
+}
+
+fn send_exec(runtime_dir: &Path, args: &[String]) -> Result<(), CliError> {
+    let path = control::control_socket_path(runtime_dir);
+    check_control_response(
+        &path,
+        control::ControlCommand::Exec,
+        control::send_exec(&path, args),
+    )
+}
+
+fn check_control_response(
+    path: &Path,
+    command: control::ControlCommand,
+    result: io::Result<String>,
+) -> Result<(), CliError> {
+    let response = match result {
+        Ok(response) => response,
+        Err(err)
+            if matches!(
+                err.kind(),
+                io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused
+            ) =>
+        {
+            return Err(CliError::DaemonNotRunning(path.to_path_buf()));
+        }
+        Err(err) => return Err(err.into()),
+    };
+    if response != "ok" {
+        return Err(CliError::DaemonControl {
+            command: command.as_str(),
+            response,
+        });
+    }
+    Ok(())
+}
+
+fn stop_running_daemon(state_dir: &Path, runtime_dir: &Path) -> Result<(), CliError> {
+    match send_control(runtime_dir, control::ControlCommand::Stop) {
+        Ok(()) => {
+            wait_for_control_socket_removal(runtime_dir);
+            let path = daemon_pid_path(state_dir);
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            Ok(())
+        }
+        Err(CliError::DaemonNotRunning(_)) => stop_daemon(state_dir),
+        Err(err) => Err(err),
+    }
+}
+
+fn wait_for_control_socket_removal(runtime_dir: &Path) {
+    let path = control::control_socket_path(runtime_dir);
+    let deadline = std::time::Instant::now() + control::CONTROL_TIMEOUT;
+    while path.exists() && std::time::Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn slot_history(runtime_dir: &Path) -> Vec<u32> {
+    let path = control::read_only_socket_path(runtime_dir);
+    control::send_command(&path, control::ControlCommand::History)
+        .ok()
+        .and_then(|response| serde_json::from_str(&response).ok())
+        .unwrap_or_default()
+}
+
+fn urgent_window(runtime_dir: &Path) -> Option<String> {
+    let path = control::read_only_socket_path(runtime_dir);
+    control::send_command(&path, control::ControlCommand::Urgent)
+        .ok()
+        .and_then(|response| serde_json::from_str(&response).ok())
+        .flatten()
+}
+
+fn run_keyword(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    name: &str,
+    value: &str,
+    dry_run: bool,
+) -> Result<Option<String>, CliError> {
+    if dry_run {
+        return Ok(Some(format!("keyword {name} {value}")));
+    }
+    let journal = journal::Journal::new(
+        journal::journal_path(&paths.base_dir),
+        journal::DEFAULT_JOURNAL_MAX_BYTES,
+    );
+    let hyprctl = journal::JournalingIpc::new(hyprctl, journal);
+    hyprctl.set_trigger("cli keyword");
+    let output = hyprctl.keyword(name, value)?;
+    Ok((output.trim() != "ok").then_some(output))
+}
+
+fn resolve_workspace_arg(workspace: &WorkspaceArg, config: &Config) -> Result<u32, CliError> {
+    workspace
+        .resolve(config)
+        .map_err(|err| commands::CommandError::Workspace(err).into())
+}
+
+/// Reads the config with monitor matches resolved.
+fn load_config(hyprctl: &dyn HyprlandIpc, paths: &EnvPaths) -> Result<Config, CliError> {
+    let mut config = Config::from_path(&paths.config_path)?;
+    hyprctl.set_timeout(config.retry.ipc_timeout());
+    if config.has_monitor_matches() {
+        if let Ok(stored) = resolved::load(&resolved::state_path(&paths.state_dir)) {
+            stored.apply(&mut config);
+        }
+        if !config.monitors_resolved()
+            && let Ok(monitors) = hyprctl.monitors()
+        {
+            config.resolve_monitors(&hyprctl::logical_monitors(monitors));
+        }
+    }
+    Ok(config)
+}
+
+fn config_diagnostics(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+) -> Result<Vec<validate::Diagnostic>, CliError> {
+    let contents = fs::read_to_string(&paths.config_path)?;
+    let monitors = hyprctl.monitors();
+    let pinned = validate::pinned_workspaces_in_dir(&paths.hypr_config_dir);
+    let mut diagnostics = validate::validate_config(
+        &contents,
+        monitors.as_deref().map_err(ToString::to_string),
+        &pinned,
+    );
+    diagnostics.extend(validate::option_diagnostics(&HyprlandOptions::query(
+        hyprctl,
+    )));
+    Ok(diagnostics)
+}
+
+fn validate_report(config_path: &Path, diagnostics: &[validate::Diagnostic]) -> String {
+    if diagnostics.is_empty() {
+        return format!("{}: ok", config_path.display());
+    }
+    diagnostics
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn completions_output(shell: Shell) -> Vec<u8> {
+    let mut cmd = Cli::command();
+    let mut output = Vec::new();
+    generate(shell, &mut cmd, "hyprspaces", &mut output);
+    output
+}
+
+fn status_output(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    paths: &EnvPaths,
+    pid_source: &dyn DaemonPidSource,
+) -> Result<String, CliError> {
+    let running_pid = match read_daemon_pid(&paths.state_dir)? {
+        Some(pid) if pid_source.pids()?.contains(&pid) => Some(pid),
+        _ => None,
+    };
+    let daemon = match running_pid {
+        Some(pid) => format!("Daemon: running (PID {pid})"),
+        None if control::daemon_reachable(&control::read_only_socket_path(&paths.runtime_dir)) => {
+            "Daemon: running".to_string()
+        }
+        None => "Daemon: stopped".to_string(),
+    };
+    let read_only = control::read_only_socket_path(&paths.runtime_dir);
+    let daemon = match control::daemon_mode(&read_only) {
+        Some(mode) => format!("{daemon}\nMode: {mode}"),
+        None => daemon,
+    };
+    let debounce = control::daemon_debounce(&read_only).unwrap_or(config.debounce);
+    let daemon = format!(
+        "{daemon}\nDebounce: rebalance {}ms, focus {}ms",
+        debounce.rebalance_ms, debounce.focus_ms
+    );
+    let daemon = match debounce.mode {
+        DebounceMode::Fixed => daemon,
+        DebounceMode::Adaptive => {
+            format!("{daemon} (adaptive, up to {}ms)", debounce.max_rebalance_ms)
+        }
+    };
+    let pair = match hyprctl.active_workspace_id() {
+        Ok(active) => {
+            let slot = config.slot_of(active);
+            let primary_workspace = config.slot_workspace(slot);
+            let secondary_workspace = primary_workspace + config.paired_offset;
+            let name = config
+                .slot_name(slot)
+                .map(|name| format!(" ({name})"))
+                .unwrap_or_default();
+            format!("{primary_workspace} / {secondary_workspace}{name}")
+        }
+        Err(err) => format!("unavailable ({err})"),
+    };
+    let held = match hold::load(&hold::state_path(&paths.state_dir))? {
+        Some(PairedSide::Primary) => format!("\n  Held:      {}", config.primary_monitor),
+        Some(PairedSide::Secondary) => format!("\n  Held:      {}", config.secondary_monitor),
+        None => String::new(),
+    };
+    let monitors = hyprctl.monitors().unwrap_or_default();
+    let describe = |name: &str| match monitors.iter().find(|monitor| monitor.name == name) {
+        Some(monitor) if monitor.focused => format!("{name} ({}, focused)", monitor.geometry()),
+        Some(monitor) => format!("{name} ({})", monitor.geometry()),
+        None => name.to_string(),
+    };
+    let config_path = paths.config_path.display();
+
+    Ok(format!(
+        "{daemon}\nConfig: {config_path}\n\nPaired Monitors:\n  Primary:   {primary}\n  Secondary: {secondary}\n  Offset:    {offset}{held}\n\nActive workspace pair: {pair}",
+        primary = describe(&config.primary_monitor),
+        secondary = describe(&config.secondary_monitor),
+        offset = config.paired_offset,
+    ))
+}
+
+#[cfg(not(feature = "cli-full"))]
+fn ensure_setup(
+    _hyprctl: &dyn HyprlandIpc,
+    _paths: &EnvPaths,
+    _bin_path: &str,
+) -> Result<(), CliError> {
+    Ok(())
+}
+
+fn bin_path() -> String {
+    env::args()
+        .next()
+        .unwrap_or_else(|| "hyprspaces".to_string())
 }
 
 fn env_paths() -> Result<EnvPaths, CliError> {
@@ -640,15 +2869,29 @@ fn env_paths() -> Result<EnvPaths, CliError> {
     let xdg_path = xdg_config.as_deref().map(Path::new);
     let config_dir = paths::config_dir(home_path, xdg_path);
     let base_dir = config_dir.join("hyprspaces");
+    let signature = env::var("HYPRLAND_INSTANCE_SIGNATURE").ok();
+    let state_dir = paths::state_dir(&base_dir, signature.as_deref());
+    let sessions_dir = paths::sessions_dir(&base_dir, signature.as_deref());
+    let xdg_runtime = env::var("XDG_RUNTIME_DIR").ok();
+    let runtime_dir = paths::runtime_dir(
+        xdg_runtime.as_deref().map(Path::new),
+        &state_dir,
+        signature.as_deref(),
+    );
     let config_path = paths::config_path(home_path, xdg_path);
     let hypr_config_dir = paths::hypr_config_dir(home_path, xdg_path);
     let waybar_css = config_dir.join("waybar").join("style.css");
+    let autostart_dir = paths::autostart_dir(home_path, xdg_path);
 
     Ok(EnvPaths {
         base_dir,
+        state_dir,
+        runtime_dir,
+        sessions_dir,
         config_path,
         hypr_config_dir,
         waybar_css,
+        autostart_dir,
     })
 }
 
@@ -660,6 +2903,17 @@ fn socket2_path() -> Result<PathBuf, CliError> {
     Ok(PathBuf::from(daemon::socket2_path(&runtime_dir, &instance)))
 }
 
+fn request_socket_path() -> Result<PathBuf, CliError> {
+    let runtime_dir =
+        env::var("XDG_RUNTIME_DIR").map_err(|_| CliError::MissingEnv("XDG_RUNTIME_DIR"))?;
+    let instance = env::var("HYPRLAND_INSTANCE_SIGNATURE")
+        .map_err(|_| CliError::MissingEnv("HYPRLAND_INSTANCE_SIGNATURE"))?;
+    Ok(PathBuf::from(hyprctl::request_socket_path(
+        &runtime_dir,
+        &instance,
+    )))
+}
+
 fn ensure_socket(path: &Path) -> Result<(), CliError> {
     let metadata = std::fs::metadata(path).map_err(|_| CliError::MissingSocket(path.into()))?;
     if metadata.file_type().is_socket() {
@@ -693,37 +2947,20 @@ fn write_stdout_bytes(bytes: &[u8]) -> Result<(), CliError> {
 
 #[cfg(test)]
 mod tests {
-    use clap::Parser;
-    use super::{
-        Cli, CliError, Command, EnvPaths, SetupCommand, WaybarArgs,
-        handle_setup_install_with_launcher,
-    };
-    use crate::config::Config;
+    use super::{Cli, CliError, EnvPaths, SafeMode, SafeModeExit};
+    use crate::config::{Config, OutOfRangePolicy, RebalanceMode};
+    use crate::control::ControlCommand;
     use crate::daemon;
     use crate::hyprctl::{
-        ClientInfo, Hyprctl, HyprctlError, HyprctlRunner, HyprlandIpc, MonitorInfo, WorkspaceInfo,
+        ClientInfo, HyprctlError, HyprlandIpc, MonitorInfo, OptionInfo, WorkspaceInfo,
     };
+    use clap::Parser;
     use std::cell::RefCell;
-    use std::collections::VecDeque;
+    use std::collections::BTreeMap;
     use std::fs;
     use std::path::Path;
-    use std::path::PathBuf;
     use std::rc::Rc;
 
-    #[derive(Default)]
-    struct RecordingLauncher {
-        calls: Rc<RefCell<Vec<String>>>,
-    }
-
-    impl super::DaemonLauncher for RecordingLauncher {
-        fn launch(&self, bin_path: &str, base_dir: &Path) -> Result<(), CliError> {
-            self.calls
-                .borrow_mut()
-                .push(format!("{}:{}", bin_path, base_dir.display()));
-            Ok(())
-        }
-    }
-
     #[derive(Default)]
     struct RecordingKiller {
         calls: Rc<RefCell<Vec<u32>>>,
@@ -760,176 +2997,854 @@ mod tests {
             Ok(self.active_id)
         }
 
-        fn dispatch(&self, _dispatcher: &str, _argument: &str) -> Result<String, HyprctlError> {
-            Ok("ok".to_string())
-        }
+        fn dispatch(&self, _dispatcher: &str, _argument: &str) -> Result<String, HyprctlError> {
+            Ok("ok".to_string())
+        }
+
+        fn reload(&self) -> Result<String, HyprctlError> {
+            Ok("ok".to_string())
+        }
+
+        fn monitors(&self) -> Result<Vec<MonitorInfo>, HyprctlError> {
+            Ok(Vec::new())
+        }
+
+        fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, HyprctlError> {
+            Ok(Vec::new())
+        }
+
+        fn clients(&self) -> Result<Vec<ClientInfo>, HyprctlError> {
+            Ok(Vec::new())
+        }
+
+        fn get_option(&self, name: &str) -> Result<OptionInfo, HyprctlError> {
+            Ok(OptionInfo {
+                option: name.to_string(),
+                int: Some(0),
+                float: None,
+                string: None,
+                set: false,
+            })
+        }
+
+        fn keyword(&self, _name: &str, _value: &str) -> Result<String, HyprctlError> {
+            Ok("ok".to_string())
+        }
+    }
+
+    #[test]
+    fn writes_and_reads_daemon_pid() {
+        let dir = tempfile::tempdir().expect("tempdir");
+
+        super::write_daemon_pid(dir.path(), 4242).expect("write pid");
+
+        let pid = super::read_daemon_pid(dir.path()).expect("read pid");
+        assert_eq!(pid, Some(4242));
+    }
+
+    #[test]
+    fn instance_lock_blocks_second_daemon_without_replace() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let _held =
+            super::acquire_instance_lock(dir.path(), dir.path(), false).expect("first lock");
+
+        let err =
+            super::acquire_instance_lock(dir.path(), dir.path(), false).expect_err("second lock");
+
+        assert!(matches!(
+            err,
+            CliError::Instance(crate::instance::InstanceError::AlreadyRunning { .. })
+        ));
+    }
+
+    #[test]
+    fn send_control_reports_missing_daemon() {
+        let dir = tempfile::tempdir().expect("tempdir");
+
+        let err = super::send_control(dir.path(), crate::control::ControlCommand::Reload)
+            .expect_err("no daemon");
+
+        assert!(matches!(err, CliError::DaemonNotRunning(_)));
+    }
+
+    #[test]
+    fn send_control_surfaces_daemon_errors() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let listener =
+            crate::control::ControlListener::bind(&crate::control::control_socket_path(dir.path()))
+                .expect("bind");
+        let server = std::thread::spawn(move || {
+            loop {
+                if let Some(request) = listener.poll().expect("poll") {
+                    request.respond("error: config error").expect("respond");
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+        });
+
+        let err = super::send_control(dir.path(), crate::control::ControlCommand::Reload)
+            .expect_err("rejected");
+        server.join().expect("join");
+
+        assert_eq!(
+            err.to_string(),
+            "daemon rejected reload: error: config error"
+        );
+    }
+
+    #[test]
+    fn shutdown_flushes_rebalance_then_snapshots_and_releases_pidfile() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        super::write_daemon_pid(&paths.state_dir, std::process::id()).expect("write pid");
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .build()
+            .expect("config");
+        let ipc = crate::testing::ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","id":0,"x":0},{"name":"HDMI-A-1","id":1,"x":1920}]"#,
+            )
+            .with_json("workspaces", r#"[{"id":1,"windows":0,"monitor":"DP-1"}]"#)
+            .with_json("clients", "[]")
+            .with_json("activeworkspace", r#"{"id":1}"#);
+        let journal = crate::journal::Journal::new(
+            dir.path().join("journal.jsonl"),
+            crate::journal::DEFAULT_JOURNAL_MAX_BYTES,
+        );
+        let mut state = super::DaemonLoop::start(
+            crate::journal::JournalingIpc::new(&ipc, journal),
+            &paths,
+            config,
+            super::DaemonOptions {
+                initial_rebalance: false,
+                trace_decisions: false,
+                restore_on_start: false,
+                debounce: super::DebounceOverrides::default(),
+            },
+        )
+        .expect("start");
+        let start = std::time::Instant::now();
+        for at in [start, start + std::time::Duration::from_millis(10)] {
+            state
+                .handle_event(&daemon::DaemonEvent::Monitor {
+                    kind: daemon::MonitorEventKind::Added,
+                    at,
+                    monitor: None,
+                })
+                .expect("monitor event");
+        }
+        ipc.clear_calls();
+
+        state.shutdown();
 
-        fn reload(&self) -> Result<String, HyprctlError> {
-            Ok("ok".to_string())
+        let calls = ipc.calls();
+        let rebalance = calls
+            .iter()
+            .position(|call| call[0] == "--batch")
+            .expect("pending rebalance flushed");
+        #[cfg(feature = "cli-full")]
+        {
+            let snapshot = calls
+                .iter()
+                .position(|call| call[..] == ["-j", "clients"])
+                .expect("session captured");
+            assert!(rebalance < snapshot, "calls: {calls:?}");
+            assert!(paths.sessions_dir.join("shutdown.json").exists());
         }
+        assert!(calls[rebalance][1].contains("moveworkspacetomonitor"));
+        assert!(!super::daemon_pid_path(&paths.state_dir).exists());
+    }
 
-        fn monitors(&self) -> Result<Vec<MonitorInfo>, HyprctlError> {
-            Ok(Vec::new())
-        }
+    #[test]
+    fn replay_feeds_recorded_events_through_a_dry_run() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().join("data"),
+            runtime_dir: dir.path().join("run"),
+            state_dir: dir.path().join("state"),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        fs::write(
+            &paths.config_path,
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#,
+        )
+        .expect("config");
+        let recording = dir.path().join("events.log");
+        fs::write(
+            &recording,
+            "1000\tmonitoradded>>HDMI-A-1\n1010\tmonitoradded>>DP-1\n",
+        )
+        .expect("recording");
+        let ipc = crate::testing::ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","id":0,"x":0},{"name":"HDMI-A-1","id":1,"x":1920}]"#,
+            )
+            .with_json("workspaces", r#"[{"id":1,"windows":0,"monitor":"DP-1"}]"#)
+            .with_json("clients", "[]")
+            .with_json("activeworkspace", r#"{"id":1}"#);
 
-        fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, HyprctlError> {
-            Ok(Vec::new())
-        }
+        super::replay_daemon(
+            &ipc,
+            &paths,
+            &recording,
+            100.0,
+            super::DaemonOptions {
+                initial_rebalance: false,
+                trace_decisions: false,
+                restore_on_start: false,
+                debounce: super::DebounceOverrides::default(),
+            },
+        )
+        .expect("replay");
 
-        fn clients(&self) -> Result<Vec<ClientInfo>, HyprctlError> {
-            Ok(Vec::new())
-        }
+        assert!(!ipc.calls().iter().any(|call| call[0] == "--batch"));
+        let scratch = super::replay_paths(&paths, &paths.runtime_dir.join("replay"));
+        let journal = fs::read_to_string(crate::journal::journal_path(&scratch.base_dir))
+            .expect("dry-run journal");
+        assert!(journal.contains("moveworkspacetomonitor"), "{journal}");
+        assert!(!crate::journal::journal_path(&paths.base_dir).exists());
     }
 
     #[test]
-    fn waybar_requires_enable_flag() {
-        let args = WaybarArgs {
-            theme_css: None,
-            enable_waybar: false,
+    fn exec_runs_paired_commands_through_the_daemon_connection() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
         };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .build()
+            .expect("config");
+        let ipc = crate::testing::ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","id":0,"x":0,"focused":true},{"name":"HDMI-A-1","id":1,"x":1920}]"#,
+            )
+            .with_json("workspaces", r#"[{"id":1,"windows":0,"monitor":"DP-1"}]"#)
+            .with_json("clients", "[]")
+            .with_json("activeworkspace", r#"{"id":1}"#);
+        let journal = crate::journal::Journal::new(
+            dir.path().join("journal.jsonl"),
+            crate::journal::DEFAULT_JOURNAL_MAX_BYTES,
+        );
+        let state = super::DaemonLoop::start(
+            crate::journal::JournalingIpc::new(&ipc, journal),
+            &paths,
+            config,
+            super::DaemonOptions {
+                initial_rebalance: false,
+                trace_decisions: false,
+                restore_on_start: false,
+                debounce: super::DebounceOverrides::default(),
+            },
+        )
+        .expect("start");
+        ipc.clear_calls();
 
-        let err = args.ensure_enabled().expect_err("expected disabled error");
+        state
+            .exec(&["switch".to_string(), "2".to_string()])
+            .expect("switch");
 
-        assert!(matches!(err, CliError::WaybarDisabled));
+        let calls = ipc.calls();
+        assert!(
+            calls
+                .iter()
+                .any(|call| call.join(" ").contains("workspace 2")),
+            "calls: {calls:?}"
+        );
+        let err = state
+            .exec(&["grab-rogue".to_string()])
+            .expect_err("grab-rogue is not forwarded");
+        assert_eq!(
+            err.to_string(),
+            "only switch, cycle, move-window, send-to, and urgent run through send"
+        );
+        let err = state
+            .exec(&["switch".to_string()])
+            .expect_err("missing workspace");
+        assert!(
+            err.to_string()
+                .starts_with("the following required arguments")
+        );
     }
 
     #[test]
-    fn waybar_allows_enabled_flag() {
-        let args = WaybarArgs {
-            theme_css: None,
-            enable_waybar: true,
+    #[cfg(feature = "cli-full")]
+    fn restores_the_previous_instance_once_on_start() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().join("run/new"),
+            state_dir: dir.path().join("run/new"),
+            sessions_dir: dir.path().join("sessions/new"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        let previous = crate::session::shutdown_path(&dir.path().join("sessions/old"));
+        fs::create_dir_all(previous.parent().expect("parent")).expect("dir");
+        fs::write(
+            &previous,
+            r#"{"version":1,"created_at":0,"paired_offset":10,"workspace_count":10,
+                "focus":{"workspace_id":1},"monitors":[],"workspaces":[],
+                "clients":[{"address":"0x1","class":"firefox","initial_class":"firefox",
+                    "workspace_id":3,"paired_slot":3}]}"#,
+        )
+        .expect("snapshot");
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .build()
+            .expect("config");
+        let ipc = crate::testing::ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","id":0,"x":0},{"name":"HDMI-A-1","id":1,"x":1920}]"#,
+            )
+            .with_json("workspaces", r#"[{"id":1,"windows":1,"monitor":"DP-1"}]"#)
+            .with_json(
+                "clients",
+                r#"[{"address":"0x9","class":"firefox","initialClass":"firefox","workspace":{"id":1,"name":"1"}}]"#,
+            )
+            .with_json("activeworkspace", r#"{"id":1}"#);
+        let options = || super::DaemonOptions {
+            initial_rebalance: false,
+            trace_decisions: false,
+            restore_on_start: true,
+            debounce: super::DebounceOverrides::default(),
+        };
+        let journal = || {
+            crate::journal::Journal::new(
+                dir.path().join("journal.jsonl"),
+                crate::journal::DEFAULT_JOURNAL_MAX_BYTES,
+            )
+        };
+
+        let start = || {
+            super::DaemonLoop::start(
+                crate::journal::JournalingIpc::new(&ipc, journal()),
+                &paths,
+                config.clone(),
+                options(),
+            )
+            .expect("start");
         };
+        start();
+        let restores = |calls: Vec<Vec<String>>| {
+            calls
+                .iter()
+                .filter(|call| {
+                    call.join(" ")
+                        .contains("movetoworkspacesilent 3,address:0x9")
+                })
+                .count()
+        };
+        assert_eq!(restores(ipc.calls()), 1, "calls: {:?}", ipc.calls());
+        ipc.clear_calls();
 
-        args.ensure_enabled().expect("enabled");
+        start();
+        assert_eq!(restores(ipc.calls()), 0);
     }
 
     #[test]
-    fn parses_waybar_install_flag() {
-        let cli =
-            Cli::try_parse_from(["hyprspaces", "setup", "install", "--waybar"]).expect("parse");
+    #[cfg(feature = "cli-full")]
+    fn daemon_start_refreshes_installed_workspace_rules() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().join("run"),
+            state_dir: dir.path().join("run"),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        let rules_path = crate::setup::workspace_rules_path(dir.path());
+        fs::write(&rules_path, "workspace = 1, monitor:DP-9").expect("stale rules");
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
+        let ipc = crate::testing::ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","id":0,"x":0},{"name":"HDMI-A-1","id":1,"x":1920}]"#,
+            )
+            .with_json("workspaces", r#"[{"id":1,"windows":0,"monitor":"DP-1"}]"#)
+            .with_json("clients", "[]")
+            .with_json("activeworkspace", r#"{"id":1}"#);
+        let journal = crate::journal::Journal::new(dir.path().join("journal.jsonl"), 1024 * 1024);
 
-        match cli.command {
-            Command::Setup {
-                command: SetupCommand::Install(args),
-            } => assert!(args.waybar),
-            _ => panic!("expected setup install"),
-        }
+        super::DaemonLoop::start(
+            crate::journal::JournalingIpc::new(&ipc, journal),
+            &paths,
+            config,
+            super::DaemonOptions {
+                initial_rebalance: false,
+                trace_decisions: false,
+                restore_on_start: false,
+                debounce: super::DebounceOverrides::default(),
+            },
+        )
+        .expect("start");
+
+        assert_eq!(
+            fs::read_to_string(&rules_path).expect("rules"),
+            crate::setup::render_workspace_rules("DP-1", "HDMI-A-1", 2, 1)
+        );
     }
 
-    #[derive(Clone)]
-    struct SequenceRunner {
-        responses: Rc<RefCell<VecDeque<String>>>,
+    #[test]
+    #[cfg(feature = "cli-full")]
+    fn restore_refuses_missing_monitors_unless_degraded() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("desk.json");
+        fs::write(
+            &path,
+            r#"{"version":2,"created_at":0,"paired_offset":10,"workspace_count":10,
+                "focus":{"workspace_id":1},
+                "monitors":[{"id":0,"name":"DP-1","x":0,"y":0,"width":2560,"height":1440},
+                    {"id":1,"name":"HDMI-A-1","x":2560,"y":0,"width":1920,"height":1080}],
+                "workspaces":[],
+                "clients":[{"address":"0x1","workspace_id":13,"paired_slot":3}]}"#,
+        )
+        .expect("snapshot");
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .build()
+            .expect("config");
+        let ipc = crate::testing::ScriptedIpc::new()
+            .with_json("monitors", r#"[{"name":"eDP-1","id":0,"x":0}]"#)
+            .with_json(
+                "clients",
+                r#"[{"address":"0x1","workspace":{"id":1,"name":"1"}}]"#,
+            );
+        let restore = |allow_degraded| {
+            crate::session::restore_session(
+                &ipc,
+                &config,
+                dir.path(),
+                Some(&path),
+                crate::session::RestoreMode::Same,
+                allow_degraded,
+            )
+        };
+
+        let err = restore(false).expect_err("mismatch");
+        assert!(matches!(
+            &err,
+            crate::session::SessionError::MonitorMismatch(missing)
+                if missing == &["DP-1".to_string(), "HDMI-A-1".to_string()]
+        ));
+        assert!(ipc.calls().iter().all(|call| call[0] != "--batch"));
+
+        restore(true).expect("degraded restore");
+        let calls = ipc.calls();
+        let collapsed = "movetoworkspacesilent 3,address:0x1";
+        assert!(
+            calls.iter().any(|call| call.join(" ").contains(collapsed)),
+            "calls: {calls:?}"
+        );
     }
 
-    impl SequenceRunner {
-        fn new(responses: Vec<String>) -> Self {
-            Self {
-                responses: Rc::new(RefCell::new(responses.into())),
-            }
-        }
+    #[test]
+    #[cfg(feature = "cli-full")]
+    fn launches_missing_apps_on_start_and_places_their_windows() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().join("run/new"),
+            state_dir: dir.path().join("run/new"),
+            sessions_dir: dir.path().join("sessions/new"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        let previous = crate::session::shutdown_path(&dir.path().join("sessions/old"));
+        fs::create_dir_all(previous.parent().expect("parent")).expect("dir");
+        fs::write(
+            &previous,
+            r#"{"version":1,"created_at":0,"paired_offset":10,"workspace_count":10,
+                "focus":{"workspace_id":1},"monitors":[],"workspaces":[],
+                "clients":[
+                    {"address":"0x1","class":"kitty","workspace_id":4,"paired_slot":4},
+                    {"address":"0x2","class":"spotify","workspace_id":5,"paired_slot":5}]}"#,
+        )
+        .expect("snapshot");
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .restore(crate::config::RestoreConfig {
+                launch_missing: true,
+                launch_command: [("kitty".to_string(), "kitty -1".to_string())].into(),
+                ..Default::default()
+            })
+            .build()
+            .expect("config");
+        let ipc = crate::testing::ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","id":0,"x":0},{"name":"HDMI-A-1","id":1,"x":1920}]"#,
+            )
+            .with_json("workspaces", r#"[{"id":1,"windows":0,"monitor":"DP-1"}]"#)
+            .with_json("clients", "[]")
+            .with_json("activeworkspace", r#"{"id":1}"#);
+        let journal = crate::journal::Journal::new(
+            dir.path().join("journal.jsonl"),
+            crate::journal::DEFAULT_JOURNAL_MAX_BYTES,
+        );
+        let mut state = super::DaemonLoop::start(
+            crate::journal::JournalingIpc::new(&ipc, journal),
+            &paths,
+            config,
+            super::DaemonOptions {
+                initial_rebalance: false,
+                trace_decisions: false,
+                restore_on_start: true,
+                debounce: super::DebounceOverrides::default(),
+            },
+        )
+        .expect("start");
+        let launches = ipc
+            .calls()
+            .into_iter()
+            .filter(|call| call.join(" ").contains("exec"))
+            .collect::<Vec<_>>();
+        assert_eq!(launches.len(), 1, "calls: {launches:?}");
+        assert!(
+            launches[0]
+                .join(" ")
+                .contains("[workspace 4 silent] kitty -1")
+        );
+        ipc.clear_calls();
+
+        state
+            .handle_event(&daemon::DaemonEvent::Window(daemon::WindowEvent::Opened {
+                address: "0x9".to_string(),
+                workspace_id: Some(1),
+                class: Some("kitty".to_string()),
+            }))
+            .expect("window opened");
+
+        assert!(
+            ipc.calls().iter().any(|call| call
+                .join(" ")
+                .contains("movetoworkspacesilent 4,address:0x9")),
+            "calls: {:?}",
+            ipc.calls()
+        );
     }
 
-    impl HyprctlRunner for SequenceRunner {
-        fn run(&self, args: &[String]) -> Result<String, crate::hyprctl::HyprctlError> {
-            let response = self
-                .responses
-                .borrow_mut()
-                .pop_front()
-                .unwrap_or_default();
-            if args == ["-j".to_string(), "monitors".to_string()]
-                || args == ["reload".to_string()]
-            {
-                return Ok(response);
-            }
-            Ok(response)
+    #[test]
+    fn enforce_range_pulls_windows_back_into_the_paired_range() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .enforce_range(true)
+            .build()
+            .expect("config");
+        let ipc = crate::testing::ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","id":0,"x":0},{"name":"HDMI-A-1","id":1,"x":1920}]"#,
+            )
+            .with_json("workspaces", r#"[{"id":1,"windows":0,"monitor":"DP-1"}]"#)
+            .with_json("clients", "[]")
+            .with_json("activeworkspace", r#"{"id":1}"#);
+        let journal = crate::journal::Journal::new(
+            dir.path().join("journal.jsonl"),
+            crate::journal::DEFAULT_JOURNAL_MAX_BYTES,
+        );
+        let mut state = super::DaemonLoop::start(
+            crate::journal::JournalingIpc::new(&ipc, journal),
+            &paths,
+            config,
+            super::DaemonOptions {
+                initial_rebalance: false,
+                trace_decisions: false,
+                restore_on_start: false,
+                debounce: super::DebounceOverrides::default(),
+            },
+        )
+        .expect("start");
+        let rogue = |ipc: &crate::testing::ScriptedIpc| {
+            ipc.calls()
+                .into_iter()
+                .filter(|call| call.join(" ").contains("movetoworkspacesilent"))
+                .map(|call| call.join(" "))
+                .collect::<Vec<_>>()
+        };
+
+        for event in [
+            daemon::WindowEvent::Opened {
+                address: "0x1".to_string(),
+                workspace_id: Some(15),
+                class: None,
+            },
+            daemon::WindowEvent::Moved {
+                address: "0x1".to_string(),
+                workspace_id: Some(5),
+            },
+        ] {
+            state
+                .handle_event(&daemon::DaemonEvent::Window(event))
+                .expect("in range");
         }
+        assert!(rogue(&ipc).is_empty(), "calls: {:?}", ipc.calls());
+
+        state
+            .handle_event(&daemon::DaemonEvent::Window(daemon::WindowEvent::Opened {
+                address: "0x2".to_string(),
+                workspace_id: Some(24),
+                class: None,
+            }))
+            .expect("opened");
+        state
+            .handle_event(&daemon::DaemonEvent::Window(daemon::WindowEvent::Moved {
+                address: "0x1".to_string(),
+                workspace_id: Some(37),
+            }))
+            .expect("moved");
+
+        assert_eq!(
+            rogue(&ipc),
+            [
+                "--batch dispatch movetoworkspacesilent 4,address:0x2",
+                "--batch dispatch movetoworkspacesilent 7,address:0x1",
+            ]
+        );
     }
 
     #[test]
-    fn setup_install_writes_waybar_files_when_enabled() {
+    fn debounce_flags_override_the_config_across_reloads() {
         let dir = tempfile::tempdir().expect("tempdir");
-        let base_dir = dir.path().join("hyprspaces");
-        let config_path = dir.path().join("paired.json");
-        let hypr_dir = dir.path().join("hypr");
-        fs::create_dir_all(&hypr_dir).expect("hypr dir");
-        fs::write(hypr_dir.join("bindings.conf"), "base\n").expect("bindings");
-        fs::write(hypr_dir.join("autostart.conf"), "base\n").expect("autostart");
-        fs::write(hypr_dir.join("hyprland.conf"), "base\n").expect("hyprland");
-
-        let monitors = r#"[{"name":"DP-1","x":0,"id":1},{"name":"HDMI-A-1","x":1920,"id":2}]"#;
-        let runner = SequenceRunner::new(vec![monitors.to_string(), "ok".to_string()]);
-        let hyprctl = Hyprctl::new(runner);
         let paths = EnvPaths {
-            base_dir: base_dir.clone(),
-            config_path,
-            hypr_config_dir: hypr_dir,
-            waybar_css: PathBuf::from("unused"),
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        let write_config = |focus_ms: u64| {
+            fs::write(
+                &paths.config_path,
+                format!(
+                    r#"{{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","debounce":{{"rebalance_ms":500,"focus_ms":{focus_ms}}}}}"#
+                ),
+            )
+            .expect("config");
         };
+        write_config(50);
+        let ipc = crate::testing::ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","id":0,"x":0},{"name":"HDMI-A-1","id":1,"x":1920}]"#,
+            )
+            .with_json("workspaces", r#"[{"id":1,"windows":0,"monitor":"DP-1"}]"#)
+            .with_json("clients", "[]")
+            .with_json("activeworkspace", r#"{"id":1}"#);
+        let journal = crate::journal::Journal::new(
+            dir.path().join("journal.jsonl"),
+            crate::journal::DEFAULT_JOURNAL_MAX_BYTES,
+        );
+        let mut state = super::DaemonLoop::start(
+            crate::journal::JournalingIpc::new(&ipc, journal),
+            &paths,
+            Config::from_path(&paths.config_path).expect("config"),
+            super::DaemonOptions {
+                initial_rebalance: false,
+                trace_decisions: false,
+                restore_on_start: false,
+                debounce: super::DebounceOverrides {
+                    rebalance_ms: Some(300),
+                    focus_ms: None,
+                },
+            },
+        )
+        .expect("start");
 
-        let launcher = RecordingLauncher::default();
-        handle_setup_install_with_launcher(&hyprctl, &paths, "hyprspaces", true, &launcher)
-            .expect("install waybar");
-
-        let waybar_dir = base_dir.join("waybar");
-        let config = fs::read_to_string(waybar_dir.join("workspaces.json")).expect("config");
-        let json: serde_json::Value = serde_json::from_str(&config).expect("json");
-        let exec = json["custom/workspaces"]["exec"]
-            .as_str()
-            .expect("exec");
-        let theme_path = waybar_dir.join("theme.css");
-        assert!(waybar_dir.join("workspaces.json").exists());
-        assert!(waybar_dir.join("workspaces.css").exists());
-        assert!(theme_path.exists());
-        assert!(waybar_dir.join("installed.flag").exists());
         assert_eq!(
-            exec,
-            format!(
-                "hyprspaces waybar --enable-waybar --theme-css {}",
-                theme_path.display()
-            )
+            state.debounce(),
+            crate::config::DebounceConfig {
+                rebalance_ms: 300,
+                focus_ms: 50,
+                ..Default::default()
+            }
+        );
+
+        write_config(80);
+        state.reload("config change").expect("reload");
+
+        assert_eq!(
+            state.debounce(),
+            crate::config::DebounceConfig {
+                rebalance_ms: 300,
+                focus_ms: 80,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            state.rebalance_debounce.mode(),
+            crate::config::DebounceMode::Fixed
+        );
+
+        fs::write(
+            &paths.config_path,
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","debounce":{"mode":"adaptive"}}"#,
+        )
+        .expect("config");
+        state.reload("config change").expect("reload");
+
+        assert_eq!(
+            state.rebalance_debounce.mode(),
+            crate::config::DebounceMode::Adaptive
         );
+        assert_eq!(state.debounce().rebalance_ms, 300);
     }
 
     #[test]
-    fn setup_install_launches_daemon() {
+    fn jumps_to_urgent_windows_when_enabled() {
         let dir = tempfile::tempdir().expect("tempdir");
-        let base_dir = dir.path().join("hyprspaces");
-        let config_path = dir.path().join("paired.json");
-        let hypr_dir = dir.path().join("hypr");
-        fs::create_dir_all(&hypr_dir).expect("hypr dir");
-        fs::write(hypr_dir.join("bindings.conf"), "base\n").expect("bindings");
-        fs::write(hypr_dir.join("autostart.conf"), "base\n").expect("autostart");
-        fs::write(hypr_dir.join("hyprland.conf"), "base\n").expect("hyprland");
-
-        let monitors = r#"[{"name":"DP-1","x":0,"id":1},{"name":"HDMI-A-1","x":1920,"id":2}]"#;
-        let runner = SequenceRunner::new(vec![monitors.to_string(), "ok".to_string()]);
-        let hyprctl = Hyprctl::new(runner);
         let paths = EnvPaths {
-            base_dir: base_dir.clone(),
-            config_path,
-            hypr_config_dir: hypr_dir,
-            waybar_css: PathBuf::from("unused"),
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        let ipc = crate::testing::ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","id":0,"x":0},{"name":"HDMI-A-1","id":1,"x":1920}]"#,
+            )
+            .with_json("workspaces", r#"[{"id":1,"windows":0,"monitor":"DP-1"}]"#)
+            .with_json(
+                "clients",
+                r#"[{"address":"0x1","workspace":{"id":1}},{"address":"0x7","workspace":{"id":14}}]"#,
+            )
+            .with_json("activeworkspace", r#"{"id":1}"#);
+        let start = |jump: bool| {
+            let config = Config::builder()
+                .primary_monitor("DP-1")
+                .secondary_monitor("HDMI-A-1")
+                .jump_to_urgent(jump)
+                .build()
+                .expect("config");
+            let journal = crate::journal::Journal::new(
+                dir.path().join("journal.jsonl"),
+                crate::journal::DEFAULT_JOURNAL_MAX_BYTES,
+            );
+            super::DaemonLoop::start(
+                crate::journal::JournalingIpc::new(&ipc, journal),
+                &paths,
+                config,
+                super::DaemonOptions {
+                    initial_rebalance: false,
+                    trace_decisions: false,
+                    restore_on_start: false,
+                    debounce: super::DebounceOverrides::default(),
+                },
+            )
+            .expect("start")
+        };
+        let urgent = daemon::DaemonEvent::Urgent {
+            address: "0x7".to_string(),
+        };
+        let focused = |ipc: &crate::testing::ScriptedIpc| {
+            ipc.calls()
+                .iter()
+                .any(|call| call.join(" ") == "dispatch focuswindow address:0x7")
         };
 
-        let launcher = RecordingLauncher::default();
-        handle_setup_install_with_launcher(&hyprctl, &paths, "hyprspaces", false, &launcher)
-            .expect("install");
+        let mut state = start(false);
+        ipc.clear_calls();
+        state.handle_event(&urgent).expect("urgent");
+        assert!(!focused(&ipc), "calls: {:?}", ipc.calls());
+        state
+            .exec(&["urgent".to_string()])
+            .expect("urgent through send");
+        assert!(focused(&ipc), "calls: {:?}", ipc.calls());
+        state.shutdown();
 
-        let calls = launcher.calls.borrow();
-        assert_eq!(
-            calls.as_slice(),
-            &[format!("hyprspaces:{}", base_dir.display())]
+        let mut state = start(true);
+        ipc.clear_calls();
+        assert!(matches!(
+            state.exec(&["urgent".to_string()]),
+            Err(CliError::Command(
+                crate::commands::CommandError::NoUrgentWindow
+            ))
+        ));
+        state.handle_event(&urgent).expect("urgent");
+        let calls = ipc.calls();
+        assert!(
+            calls
+                .iter()
+                .any(|call| call[0] == "--batch" && call[1].contains("workspace 4")),
+            "calls: {calls:?}"
         );
+        assert!(focused(&ipc), "calls: {calls:?}");
     }
 
     #[test]
-    fn writes_and_reads_daemon_pid() {
+    fn shutdown_keeps_a_pidfile_owned_by_another_daemon() {
         let dir = tempfile::tempdir().expect("tempdir");
+        super::write_daemon_pid(dir.path(), 1).expect("write pid");
 
-        super::write_daemon_pid(dir.path(), 4242).expect("write pid");
+        super::release_daemon_pid(dir.path());
 
-        let pid = super::read_daemon_pid(dir.path()).expect("read pid");
-        assert_eq!(pid, Some(4242));
+        assert_eq!(
+            super::read_daemon_pid(dir.path()).expect("read pid"),
+            Some(1)
+        );
     }
 
     #[test]
@@ -946,12 +3861,21 @@ mod tests {
         assert!(!super::daemon_pid_path(dir.path()).exists());
     }
 
+    #[test]
+    fn environ_reads_instance_signature() {
+        let environ =
+            b"HOME=/home/jtaw\0HYPRLAND_INSTANCE_SIGNATURE=abc_123\0XDG_RUNTIME_DIR=/run\0";
+
+        assert_eq!(
+            super::environ_signature(environ).as_deref(),
+            Some("abc_123")
+        );
+        assert_eq!(super::environ_signature(b"HOME=/home/jtaw\0"), None);
+    }
+
     #[test]
     fn cmdline_detects_daemon() {
-        let args = vec![
-            "/usr/bin/hyprspaces".to_string(),
-            "daemon".to_string(),
-        ];
+        let args = vec!["/usr/bin/hyprspaces".to_string(), "daemon".to_string()];
 
         assert!(super::cmdline_is_daemon(&args));
     }
@@ -967,7 +3891,6 @@ mod tests {
         assert!(!super::cmdline_is_daemon(&args));
     }
 
-
     #[test]
     fn ipc_defaults_to_hyprctl() {
         let cli = Cli::try_parse_from(["hyprspaces", "paired", "switch", "1"]).expect("parse");
@@ -977,15 +3900,8 @@ mod tests {
 
     #[test]
     fn ipc_parses_explicit_hyprctl() {
-        let cli = Cli::try_parse_from([
-            "hyprspaces",
-            "--ipc",
-            "hyprctl",
-            "paired",
-            "switch",
-            "1",
-        ])
-        .expect("parse");
+        let cli = Cli::try_parse_from(["hyprspaces", "--ipc", "hyprctl", "paired", "switch", "1"])
+            .expect("parse");
 
         assert!(matches!(cli.ipc, super::IpcBackend::Hyprctl));
     }
@@ -993,15 +3909,8 @@ mod tests {
     #[cfg(not(feature = "native-ipc"))]
     #[test]
     fn ipc_native_requires_feature() {
-        let cli = Cli::try_parse_from([
-            "hyprspaces",
-            "--ipc",
-            "native",
-            "paired",
-            "switch",
-            "1",
-        ])
-        .expect("parse");
+        let cli = Cli::try_parse_from(["hyprspaces", "--ipc", "native", "paired", "switch", "1"])
+            .expect("parse");
 
         let err = match super::build_ipc(cli.ipc) {
             Ok(_) => panic!("expected native ipc error"),
@@ -1014,15 +3923,8 @@ mod tests {
     #[cfg(feature = "native-ipc")]
     #[test]
     fn ipc_native_available_with_feature() {
-        let cli = Cli::try_parse_from([
-            "hyprspaces",
-            "--ipc",
-            "native",
-            "paired",
-            "switch",
-            "1",
-        ])
-        .expect("parse");
+        let cli = Cli::try_parse_from(["hyprspaces", "--ipc", "native", "paired", "switch", "1"])
+            .expect("parse");
 
         let _ = super::build_ipc(cli.ipc).expect("native ipc");
     }
@@ -1042,15 +3944,68 @@ mod tests {
         assert!(matches!(kind, daemon::EventSourceKind::Native));
     }
 
+    #[test]
+    fn keyword_dry_run_skips_ipc_and_journal() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        let ipc = StatusIpc { active_id: 1 };
+
+        let output =
+            super::run_keyword(&ipc, &paths, "general:gaps_out", "12", true).expect("dry run");
+
+        assert_eq!(output.as_deref(), Some("keyword general:gaps_out 12"));
+        assert!(!crate::journal::journal_path(dir.path()).exists());
+    }
+
+    #[test]
+    fn keyword_is_journaled() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        let ipc = StatusIpc { active_id: 1 };
+
+        let output =
+            super::run_keyword(&ipc, &paths, "general:gaps_out", "12", false).expect("keyword");
+
+        assert_eq!(output, None);
+        let journal = crate::journal::Journal::new(
+            crate::journal::journal_path(dir.path()),
+            crate::journal::DEFAULT_JOURNAL_MAX_BYTES,
+        );
+        let entries = journal.entries_since(0).expect("entries");
+        assert_eq!(entries[0].command, "keyword general:gaps_out 12");
+    }
+
     #[test]
     fn status_reports_daemon_and_pair() {
         let dir = tempfile::tempdir().expect("tempdir");
         super::write_daemon_pid(dir.path(), 4242).expect("write pid");
         let paths = EnvPaths {
             base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
             config_path: dir.path().join("paired.json"),
             hypr_config_dir: dir.path().join("hypr"),
             waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
         };
         let config = Config {
             primary_monitor: "DP-1".to_string(),
@@ -1058,6 +4013,35 @@ mod tests {
             paired_offset: 10,
             workspace_count: 10,
             wrap_cycling: true,
+            cycle_skip_empty: false,
+            out_of_range: OutOfRangePolicy::Normalize,
+            profiles: Vec::new(),
+            rebalance_on_start: true,
+            hotplug_settle_ms: 0,
+            schedules: Vec::new(),
+            rebalance_mode: RebalanceMode::Strict,
+            idle_inhibit_slots: Vec::new(),
+            launch: BTreeMap::new(),
+            names: BTreeMap::from([(2, "web".to_string())]),
+            theme_source: Default::default(),
+            waybar: Default::default(),
+            drag_focus_delay_ms: 0,
+            metrics_address: None,
+            retry: Default::default(),
+            fast_switch: false,
+            enforce_range: false,
+            jump_to_urgent: false,
+            cursor_follows_focus: false,
+            instant_rebalance: false,
+            debounce: Default::default(),
+            layout: Vec::new(),
+            sticky_classes: Vec::new(),
+            reconcile: Default::default(),
+            primary_match: None,
+            secondary_match: None,
+            auto_primary: Default::default(),
+            workspace_base: 1,
+            restore: Default::default(),
         };
         let ipc = StatusIpc { active_id: 12 };
         let pid_source = RecordingPidSource { pids: vec![4242] };
@@ -1065,14 +4049,62 @@ mod tests {
         let output = super::status_output(&ipc, &config, &paths, &pid_source).expect("status");
 
         assert!(output.contains("Daemon: running (PID 4242)"));
-        assert!(output.contains(&format!(
-            "Config: {}",
-            paths.config_path.display()
-        )));
+        assert!(output.contains("Debounce: rebalance 200ms, focus 100ms"));
+        assert!(output.contains(&format!("Config: {}", paths.config_path.display())));
         assert!(output.contains("Primary:   DP-1"));
         assert!(output.contains("Secondary: HDMI-A-1"));
         assert!(output.contains("Offset:    10"));
-        assert!(output.contains("Active workspace pair: 2 / 12"));
+        assert!(output.contains("Active workspace pair: 2 / 12 (web)"));
+    }
+
+    #[test]
+    fn validation_exit_code_distinguishes_warnings() {
+        let warnings = CliError::Validation {
+            errors: 0,
+            warnings: 2,
+        };
+        let errors = CliError::Validation {
+            errors: 1,
+            warnings: 2,
+        };
+
+        assert_eq!(warnings.exit_code(), 2);
+        assert_eq!(errors.exit_code(), 1);
+        assert_eq!(CliError::WaybarDisabled.exit_code(), 1);
+    }
+
+    #[test]
+    fn config_diagnostics_cross_checks_monitors() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        fs::write(
+            &paths.config_path,
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#,
+        )
+        .expect("write config");
+        let ipc = StatusIpc { active_id: 1 };
+
+        let diagnostics = super::config_diagnostics(&ipc, &paths).expect("diagnostics");
+        let report = super::validate_report(&paths.config_path, &diagnostics);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(report.contains("error: primary_monitor 'DP-1' is not connected"));
+    }
+
+    #[test]
+    fn validate_report_is_ok_without_diagnostics() {
+        let report = super::validate_report(Path::new("/tmp/paired.json"), &[]);
+
+        assert_eq!(report, "/tmp/paired.json: ok");
     }
 
     #[test]
@@ -1081,9 +4113,13 @@ mod tests {
         super::write_daemon_pid(dir.path(), 4242).expect("write pid");
         let paths = EnvPaths {
             base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
             config_path: dir.path().join("paired.json"),
             hypr_config_dir: dir.path().join("hypr"),
             waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
         };
         let config = Config {
             primary_monitor: "DP-1".to_string(),
@@ -1091,6 +4127,35 @@ mod tests {
             paired_offset: 10,
             workspace_count: 10,
             wrap_cycling: true,
+            cycle_skip_empty: false,
+            out_of_range: OutOfRangePolicy::Normalize,
+            profiles: Vec::new(),
+            rebalance_on_start: true,
+            hotplug_settle_ms: 0,
+            schedules: Vec::new(),
+            rebalance_mode: RebalanceMode::Strict,
+            idle_inhibit_slots: Vec::new(),
+            launch: BTreeMap::new(),
+            names: BTreeMap::new(),
+            theme_source: Default::default(),
+            waybar: Default::default(),
+            drag_focus_delay_ms: 0,
+            metrics_address: None,
+            retry: Default::default(),
+            fast_switch: false,
+            enforce_range: false,
+            jump_to_urgent: false,
+            cursor_follows_focus: false,
+            instant_rebalance: false,
+            debounce: Default::default(),
+            layout: Vec::new(),
+            sticky_classes: Vec::new(),
+            reconcile: Default::default(),
+            primary_match: None,
+            secondary_match: None,
+            auto_primary: Default::default(),
+            workspace_base: 1,
+            restore: Default::default(),
         };
         let ipc = StatusIpc { active_id: 12 };
         let pid_source = RecordingPidSource { pids: Vec::new() };
@@ -1099,4 +4164,140 @@ mod tests {
 
         assert!(output.contains("Daemon: stopped"));
     }
+
+    #[test]
+    fn status_shows_hold_and_survives_hyprland_errors() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        crate::hold::update(
+            &crate::hold::state_path(&paths.state_dir),
+            crate::hold::HoldAction::On,
+            crate::paired::PairedSide::Secondary,
+        )
+        .expect("hold");
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .build()
+            .expect("config");
+        let ipc = crate::testing::ScriptedIpc::new();
+        let pid_source = RecordingPidSource { pids: Vec::new() };
+
+        let output = super::status_output(&ipc, &config, &paths, &pid_source).expect("status");
+
+        assert!(output.contains("Daemon: stopped"));
+        assert!(output.contains("Held:      HDMI-A-1"));
+        assert!(output.contains("Active workspace pair: unavailable ("));
+    }
+
+    #[test]
+    fn status_shows_monitor_geometry() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .build()
+            .expect("config");
+        let ipc = crate::testing::ScriptedIpc::new().with_json(
+            "monitors",
+            r#"[{"name":"DP-1","id":0,"x":0,"y":0,"width":2560,"height":1440,"scale":1.25,"focused":true},
+                {"name":"HDMI-A-1","id":1,"x":2048,"y":0,"width":1920,"height":1080,"transform":1}]"#,
+        );
+        let pid_source = RecordingPidSource { pids: Vec::new() };
+
+        let output = super::status_output(&ipc, &config, &paths, &pid_source).expect("status");
+
+        assert!(output.contains("Primary:   DP-1 (2560x1440+0+0 @1.25x, focused)"));
+        assert!(output.contains("Secondary: HDMI-A-1 (1920x1080+2048+0 transform 1)"));
+    }
+
+    #[test]
+    fn completions_cover_paired_subcommands() {
+        let output = super::completions_output(clap_complete::Shell::Bash);
+        let script = String::from_utf8(output).expect("utf8");
+
+        assert!(script.contains("hyprspaces"));
+        assert!(script.contains("grab-rogue"));
+        assert!(script.contains("send-to"));
+    }
+
+    #[test]
+    fn safe_mode_reports_error_until_config_loads() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = EnvPaths {
+            base_dir: dir.path().to_path_buf(),
+            runtime_dir: dir.path().to_path_buf(),
+            state_dir: dir.path().to_path_buf(),
+            sessions_dir: dir.path().join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: dir.path().join("waybar.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        fs::write(&paths.config_path, "{").expect("config");
+        let err = Config::from_path(&paths.config_path).expect_err("invalid");
+        let mut safe_mode = SafeMode::enter(&paths, &err);
+
+        let (status, exit) = safe_mode.handle_command(ControlCommand::Status);
+        assert!(status.starts_with("safe mode: invalid config json"));
+        assert!(exit.is_none());
+        let (reload, exit) = safe_mode.handle_command(ControlCommand::Reload);
+        assert!(reload.starts_with("error: invalid config json"));
+        assert!(exit.is_none());
+
+        fs::write(
+            &paths.config_path,
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#,
+        )
+        .expect("config");
+        let (reload, exit) = safe_mode.handle_command(ControlCommand::Reload);
+        assert_eq!(reload, "ok");
+        assert!(matches!(
+            exit,
+            Some(SafeModeExit::Recovered(config)) if config.primary_monitor == "DP-1"
+        ));
+    }
+
+    #[cfg(not(feature = "async-daemon"))]
+    #[test]
+    fn reconnect_checks_for_stop_requests_between_attempts() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let socket = dir.path().join("missing.sock");
+        let mut checks = 0;
+
+        let started = std::time::Instant::now();
+        let reconnect = super::reconnect_event_source(
+            super::IpcBackend::Hyprctl,
+            &socket,
+            std::time::Duration::from_millis(10),
+            None,
+            || {
+                checks += 1;
+                Ok((checks == 3).then_some("stopped"))
+            },
+        )
+        .expect("reconnect");
+
+        assert!(matches!(reconnect, super::Reconnect::Stopped("stopped")));
+        assert!(started.elapsed() < daemon::RECONNECT_INTERVAL);
+    }
 }
@@ -0,0 +1,1302 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command as ProcessCommand, Stdio};
+use std::time::Duration;
+
+use super::{
+    BarArgs, BarFormat, CliError, EnvPaths, InstallArgs, QueryCommand, QueryFormat, SessionCommand,
+    SessionRestoreMode, SetupCommand, WaybarArgs, WaybarCompat, daemon_pid_path, ensure_socket,
+    load_config, send_control, socket2_path, spawn_daemon, stop_daemon, write_stdout,
+};
+use crate::commands;
+use crate::config::{self, Config, WaybarMode};
+use crate::control;
+use crate::daemon;
+use crate::doctor;
+use crate::hyprctl::{ClientInfo, Compositor, HyprlandIpc};
+use crate::init;
+use crate::layout::Layout;
+use crate::paths;
+use crate::query;
+use crate::session;
+use crate::session_index;
+use crate::setup::{self, WaybarPlacement};
+use crate::tui;
+use crate::version;
+use crate::waybar;
+
+trait DaemonLauncher {
+    fn launch(&self, bin_path: &str, state_dir: &Path) -> Result<(), CliError>;
+}
+
+struct SystemDaemonLauncher;
+
+impl DaemonLauncher for SystemDaemonLauncher {
+    fn launch(&self, bin_path: &str, state_dir: &Path) -> Result<(), CliError> {
+        spawn_daemon(bin_path, state_dir)
+    }
+}
+
+impl InstallArgs {
+    fn waybar_placement(&self, stored: Option<WaybarPlacement>) -> Option<WaybarPlacement> {
+        if self.waybar_config.is_none()
+            && self.waybar_bar.is_none()
+            && self.waybar_output.is_none()
+            && self.waybar_modules.is_none()
+            && self.waybar_index.is_none()
+            && !self.waybar_per_output
+        {
+            return None;
+        }
+        let stored = stored.unwrap_or_default();
+        Some(WaybarPlacement {
+            config: self.waybar_config.clone().or(stored.config),
+            bar: self.waybar_bar.unwrap_or(stored.bar),
+            output: self.waybar_output.clone().or(stored.output),
+            modules: self.waybar_modules.unwrap_or(stored.modules),
+            index: self.waybar_index.or(stored.index),
+            per_output: self.waybar_per_output || stored.per_output,
+        })
+    }
+}
+
+impl WaybarArgs {
+    fn ensure_enabled(&self) -> Result<(), CliError> {
+        if self.enable_waybar {
+            Ok(())
+        } else {
+            Err(CliError::WaybarDisabled)
+        }
+    }
+}
+
+pub(super) fn session(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+    command: SessionCommand,
+) -> Result<(), CliError> {
+    match command {
+        SessionCommand::List => {
+            let entries = session_index::list(&paths.base_dir)?;
+            println!("{}", session_index::render_list(&entries));
+            return Ok(());
+        }
+        SessionCommand::Delete { name } => {
+            session_index::delete(&paths.base_dir, &name)?;
+            return Ok(());
+        }
+        _ => {}
+    }
+    ensure_setup(hyprctl, paths, bin_path)?;
+    let config = load_config(hyprctl, paths)?;
+    match command {
+        SessionCommand::Save { path, name } => {
+            let path = match name {
+                Some(name) => Some(session_index::named_path(&paths.base_dir, &name)?),
+                None => path,
+            };
+            let _ = session::save_session(hyprctl, &config, &paths.sessions_dir, path.as_deref())?;
+        }
+        SessionCommand::Restore {
+            path,
+            name,
+            mode,
+            allow_degraded,
+        } => {
+            let path = match name {
+                Some(name) => session_index::existing_path(&paths.base_dir, &name)?,
+                None => path.unwrap_or_else(|| default_snapshot(paths)),
+            };
+            let launched = session::restore_session(
+                hyprctl,
+                &config,
+                &paths.sessions_dir,
+                Some(&path),
+                restore_mode(mode),
+                allow_degraded || config.restore.allow_degraded,
+            )?;
+            if !launched.is_empty() {
+                println!("launched {} missing application(s)", launched.len());
+            }
+        }
+        SessionCommand::Diff {
+            path,
+            name,
+            against,
+            mode,
+        } => {
+            let path = match name {
+                Some(name) => session_index::existing_path(&paths.base_dir, &name)?,
+                None => path.unwrap_or_else(|| default_snapshot(paths)),
+            };
+            let snapshot = session::read_snapshot(&path)?;
+            let target = if against == "current" {
+                session::capture_snapshot(hyprctl, &config)?
+            } else {
+                session::read_snapshot(&session_index::existing_path(&paths.base_dir, &against)?)?
+            };
+            let diff = session::diff_snapshots(&snapshot, &target, restore_mode(mode), &config);
+            println!("{}", session::render_diff(&diff));
+        }
+        SessionCommand::List | SessionCommand::Delete { .. } => unreachable!(),
+    }
+    Ok(())
+}
+
+/// This instance's `latest.json`, else the newest snapshot of any instance.
+fn default_snapshot(paths: &EnvPaths) -> PathBuf {
+    let own = session::session_path(&paths.sessions_dir, None);
+    if own.exists() {
+        return own;
+    }
+    session::newest_snapshot(&paths::sessions_dir(&paths.base_dir, None)).unwrap_or(own)
+}
+
+pub(super) fn sync_rules(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+) -> Result<(), CliError> {
+    ensure_setup(hyprctl, paths, bin_path)?;
+    let config = load_config(hyprctl, paths)?;
+    let path = setup::workspace_rules_path(&paths.base_dir);
+    if setup::sync_workspace_rules(&paths.base_dir, &config)? {
+        println!("updated {}", path.display());
+    } else {
+        println!("{} is up to date", path.display());
+    }
+    Ok(())
+}
+
+/// Rewrites the rules fragment only when `setup install` put one in place.
+pub(super) fn refresh_workspace_rules(base_dir: &Path, config: &Config) {
+    if !setup::workspace_rules_path(base_dir).exists() {
+        return;
+    }
+    match setup::sync_workspace_rules(base_dir, config) {
+        Ok(true) => eprintln!(
+            "workspace rules: updated for {} / {}",
+            config.primary_monitor, config.secondary_monitor
+        ),
+        Ok(false) => {}
+        Err(err) => eprintln!("error: failed to update workspace rules: {err}"),
+    }
+}
+
+fn restore_mode(mode: SessionRestoreMode) -> session::RestoreMode {
+    match mode {
+        SessionRestoreMode::Auto => session::RestoreMode::Auto,
+        SessionRestoreMode::Same => session::RestoreMode::Same,
+        SessionRestoreMode::Cold => session::RestoreMode::Cold,
+    }
+}
+
+/// Failed moves don't stop the rest but make the command fail at the end.
+fn migrate_windows<R: BufRead, W: Write>(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    filter: &commands::MigrationFilter,
+    assume_yes: bool,
+    input: &mut R,
+    output: &mut W,
+) -> Result<(), CliError> {
+    let targets = commands::preview_migration(hyprctl, config, filter)?;
+    writeln!(output, "{}", setup::render_migration_summary(&targets))?;
+    if targets.is_empty() {
+        return Ok(());
+    }
+    if !assume_yes {
+        write!(output, "Migrate them? [y/N]: ")?;
+        output.flush()?;
+        let mut answer = String::new();
+        input.read_line(&mut answer)?;
+        if !matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+            writeln!(output, "aborted")?;
+            return Ok(());
+        }
+    }
+    let total = targets.len();
+    let mut done = 0;
+    let moved = commands::migrate_targets(hyprctl, &targets, |target, result| {
+        done += 1;
+        let class = target.class.as_deref().unwrap_or("-");
+        let _ = match result {
+            Ok(()) => writeln!(
+                output,
+                "[{done}/{total}] moved {} ({class}) {} → {}",
+                target.address, target.from, target.to
+            ),
+            Err(err) => writeln!(
+                output,
+                "[{done}/{total}] failed {} ({class}) {} → {}: {err}",
+                target.address, target.from, target.to
+            ),
+        };
+    });
+    writeln!(output, "migrated {moved} window(s)")?;
+    match total - moved {
+        0 => Ok(()),
+        failed => Err(CliError::MigrationFailed { failed, total }),
+    }
+}
+
+pub(super) fn setup(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+    command: SetupCommand,
+) -> Result<(), CliError> {
+    match command {
+        SetupCommand::Install(args) => {
+            handle_setup_install(hyprctl, paths, bin_path, &args)?;
+        }
+        SetupCommand::Uninstall => {
+            if let Ok(config) = load_config(hyprctl, paths) {
+                let _ = commands::migrate_windows(hyprctl, &config);
+            }
+            stop_daemon(&paths.state_dir)?;
+            setup::unplace_waybar_module(&paths.base_dir, &waybar_dir(paths), &paths.config_path)?;
+            setup::uninstall(&paths.base_dir, &paths.hypr_config_dir)?;
+            setup::uninstall_xdg_autostart(&paths.autostart_dir)?;
+            let _ = hyprctl.reload();
+        }
+        SetupCommand::MigrateWindows {
+            preview,
+            yes,
+            only,
+            except,
+        } => {
+            let config = load_config(hyprctl, paths)?;
+            let filter = commands::MigrationFilter { only, except };
+            if preview {
+                let targets = commands::preview_migration(hyprctl, &config, &filter)?;
+                write_stdout(&setup::render_migration_preview(&targets))?;
+            } else {
+                let stdin = io::stdin();
+                migrate_windows(
+                    hyprctl,
+                    &config,
+                    &filter,
+                    yes,
+                    &mut stdin.lock(),
+                    &mut io::stdout(),
+                )?;
+            }
+        }
+        SetupCommand::Rollback => {
+            for path in setup::rollback(&paths.base_dir)? {
+                write_stdout(&format!("restored {}", path.display()))?;
+            }
+            let _ = hyprctl.reload();
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn waybar(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+    args: WaybarArgs,
+) -> Result<(), CliError> {
+    args.ensure_enabled()?;
+    if args.follow_file {
+        let mut follower = waybar::StateFollower::new(waybar::state_path(&paths.state_dir));
+        loop {
+            if let Some(line) = follower.poll() {
+                write_stdout(&line)?;
+            }
+            std::thread::sleep(WAYBAR_FOLLOW_INTERVAL);
+        }
+    }
+    ensure_setup(hyprctl, paths, bin_path)?;
+    let control_path = control::read_only_socket_path(&paths.runtime_dir);
+    let config = load_config(hyprctl, paths).inspect_err(|_| {
+        if args.compat.is_none()
+            && let Some(error) = control::safe_mode_error(&control_path)
+        {
+            let _ = write_stdout(&waybar::render_safe_mode(&error));
+        }
+    })?;
+    let config = &config;
+    let render: Box<dyn Fn(&waybar::BarState) -> String> = match args.compat {
+        Some(WaybarCompat::HyprlandWorkspaces) => {
+            Box::new(|state| waybar::render_hyprland_workspaces(state, config))
+        }
+        None => {
+            let colors = theme_colors(
+                config,
+                args.theme_css.unwrap_or_else(|| paths.waybar_css.clone()),
+                args.color.as_deref(),
+            )?;
+            Box::new(move |state| {
+                let clients = config
+                    .waybar
+                    .tooltip_windows
+                    .then(|| hyprctl.clients().ok())
+                    .flatten();
+                waybar::render_bar(state, config, &colors, clients.as_deref())
+            })
+        }
+    };
+    let starting = args.compat.is_none().then(waybar::render_starting);
+    stream_bar(
+        hyprctl,
+        paths,
+        config,
+        args.monitor.as_deref(),
+        args.daemon_timeout,
+        starting,
+        &*render,
+    )
+}
+
+pub(super) fn bar(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+    args: BarArgs,
+) -> Result<(), CliError> {
+    let backend: Box<dyn waybar::RenderBackend> = match args.format {
+        BarFormat::WaybarJson => Box::new(waybar::WaybarJson),
+        BarFormat::Eww => Box::new(waybar::Eww),
+        BarFormat::Polybar => Box::new(waybar::Polybar),
+        BarFormat::Plain => Box::new(waybar::Plain),
+    };
+    ensure_setup(hyprctl, paths, bin_path)?;
+    let control_path = control::read_only_socket_path(&paths.runtime_dir);
+    let config = load_config(hyprctl, paths).inspect_err(|_| {
+        if let Some(error) = control::safe_mode_error(&control_path)
+            && let Some(line) = backend.safe_mode(&error)
+        {
+            let _ = write_stdout(&line);
+        }
+    })?;
+    let colors = theme_colors(
+        &config,
+        args.theme_css.unwrap_or_else(|| paths.waybar_css.clone()),
+        args.color.as_deref(),
+    )?;
+    let render = |state: &waybar::BarState| match args.format {
+        BarFormat::WaybarJson => {
+            let clients = config
+                .waybar
+                .tooltip_windows
+                .then(|| hyprctl.clients().ok())
+                .flatten();
+            waybar::render_bar(state, &config, &colors, clients.as_deref())
+        }
+        _ => waybar::render_with(backend.as_ref(), state, &config, &colors),
+    };
+    stream_bar(
+        hyprctl,
+        paths,
+        &config,
+        args.monitor.as_deref(),
+        args.daemon_timeout,
+        backend.starting(),
+        &render,
+    )
+}
+
+/// Prints a line per state change, from the daemon or else socket2 events.
+fn stream_bar(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    config: &Config,
+    monitor: Option<&str>,
+    daemon_timeout: Duration,
+    starting: Option<String>,
+    render: &dyn Fn(&waybar::BarState) -> String,
+) -> Result<(), CliError> {
+    let control_path = control::read_only_socket_path(&paths.runtime_dir);
+    let layout = Layout::for_config(config);
+    let perspective = |state: &waybar::BarState| match monitor {
+        Some(monitor) => state.for_monitor(&layout, monitor),
+        None => state.clone(),
+    };
+    let query_state = || -> Result<waybar::BarState, CliError> {
+        let mut state = waybar::bar_state_from_hyprctl(hyprctl)?;
+        if monitor.is_some() {
+            state.visible = waybar::visible_workspaces(&hyprctl.monitors()?);
+        }
+        Ok(state)
+    };
+    if !control::daemon_reachable(&control_path) && !daemon_timeout.is_zero() {
+        if let Some(starting) = starting {
+            write_stdout(&starting)?;
+        }
+        control::wait_for_daemon(&control_path, daemon_timeout);
+    }
+    if let Ok(states) = control::watch(&control_path) {
+        for line in states.map_while(Result::ok) {
+            if let Ok(state) = serde_json::from_str::<waybar::BarState>(&line) {
+                write_stdout(&render(&perspective(&state)))?;
+            }
+        }
+    }
+    let socket_path = socket2_path()?;
+    ensure_socket(&socket_path)?;
+    write_stdout(&render(&perspective(&query_state()?)))?;
+    let stream = std::os::unix::net::UnixStream::connect(&socket_path)?;
+    let reader = io::BufReader::new(stream);
+    for line in reader.lines() {
+        let line = line?;
+        if waybar::should_update(&line) {
+            write_stdout(&render(&perspective(&query_state()?)))?;
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn config_init(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    force: bool,
+) -> Result<(), CliError> {
+    if paths.config_path.exists() && !force {
+        return Err(CliError::ConfigExists(paths.config_path.clone()));
+    }
+    let monitors = hyprctl.monitors().unwrap_or_default();
+    let stdin = io::stdin();
+    let answers = init::prompt_config(&mut stdin.lock(), &mut io::stdout(), &monitors)?;
+    answers.to_config()?;
+    if let Some(parent) = paths.config_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&paths.config_path, init::render_commented_config(&answers))?;
+    write_stdout(&format!("wrote {}", paths.config_path.display()))?;
+    Ok(())
+}
+
+/// Writes one config key, then regenerates bindings and rules and reloads the daemon.
+pub(super) fn config_set(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+    key: &str,
+    value: &str,
+) -> Result<(), CliError> {
+    config::set_value(&paths.config_path, key, value)?;
+    write_stdout(&format!("set {key} in {}", paths.config_path.display()))?;
+    let config = load_config(hyprctl, paths)?;
+    let mut hyprland_reload = false;
+    if setup::sync_bindings(&paths.base_dir, bin_path, &paths.config_path)? {
+        write_stdout(&format!(
+            "updated {}",
+            paths.base_dir.join("bindings.conf").display()
+        ))?;
+        hyprland_reload = true;
+    }
+    let rules = setup::workspace_rules_path(&paths.base_dir);
+    if rules.exists() && setup::sync_workspace_rules(&paths.base_dir, &config)? {
+        write_stdout(&format!("updated {}", rules.display()))?;
+        hyprland_reload = true;
+    }
+    if hyprland_reload {
+        let _ = hyprctl.reload();
+    }
+    match send_control(&paths.runtime_dir, control::ControlCommand::Reload) {
+        Ok(()) => write_stdout("daemon reloaded")?,
+        Err(CliError::DaemonNotRunning(_)) => {
+            if let Some(config) = daemon::select_config(hyprctl, &config)? {
+                daemon::rebalance_all(hyprctl, &config)?;
+            }
+        }
+        Err(err) => return Err(err),
+    }
+    Ok(())
+}
+
+pub(super) fn doctor(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+    fix: bool,
+    yes: bool,
+) -> Result<(), CliError> {
+    let ctx = doctor_context(hyprctl, paths, bin_path);
+    let mut checks = doctor::run_checks(&ctx);
+    write_stdout(&version::VersionReport::current().summary())?;
+    for check in &checks {
+        write_stdout(&doctor::format_check(check))?;
+    }
+    if fix {
+        let stdin = io::stdin();
+        doctor::apply_fixes(&checks, yes, &mut stdin.lock(), &mut io::stdout())?;
+        checks = doctor::run_checks(&ctx);
+    }
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == doctor::CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        return Err(CliError::DoctorFailed(failed));
+    }
+    Ok(())
+}
+
+pub(super) fn query(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    command: QueryCommand,
+) -> Result<(), CliError> {
+    match command {
+        QueryCommand::Windows { format } => {
+            let config = load_config(hyprctl, paths)?;
+            let entries = query::query_windows(hyprctl, &config)?;
+            let output = match format {
+                QueryFormat::Alfred => query::render_alfred(&entries),
+                QueryFormat::RofiScript => query::render_rofi_script(&entries),
+            };
+            write_stdout(&output)?;
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn tui(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+) -> Result<(), CliError> {
+    ensure_setup(hyprctl, paths, bin_path)?;
+    let config = load_config(hyprctl, paths)?;
+    run_tui(hyprctl, &config, paths)
+}
+
+pub(super) struct WaybarPush {
+    path: PathBuf,
+    colors: waybar::ThemeColors,
+}
+
+impl WaybarPush {
+    pub(super) fn for_config(config: &Config, paths: &EnvPaths) -> Option<Self> {
+        if config.waybar.mode != WaybarMode::DaemonPush {
+            return None;
+        }
+        let colors = theme_colors(config, paths.waybar_css.clone(), None)
+            .inspect_err(|err| eprintln!("error: waybar theme: {err}; using default colors"))
+            .ok()
+            .or_else(|| waybar::ThemeColors::from_foreground(waybar::DEFAULT_FOREGROUND))?;
+        Some(Self {
+            path: waybar::state_path(&paths.state_dir),
+            colors,
+        })
+    }
+
+    pub(super) fn write(
+        &self,
+        state: &waybar::BarState,
+        config: &Config,
+        clients: Option<&[ClientInfo]>,
+    ) {
+        let line = waybar::render_bar(state, config, &self.colors, clients);
+        if let Err(err) = waybar::write_state(&self.path, &line) {
+            eprintln!("error: waybar state write failed: {err}");
+            return;
+        }
+        if let Some(signal) = config.waybar.signal
+            && let Err(err) = waybar::signal_waybar(signal)
+        {
+            eprintln!("error: waybar signal failed: {err}");
+        }
+    }
+}
+
+const TUI_REFRESH: Duration = Duration::from_millis(500);
+
+struct RawTerminal {
+    saved: Option<String>,
+}
+
+impl RawTerminal {
+    fn enable() -> Result<Self, CliError> {
+        let output = ProcessCommand::new("stty")
+            .arg("-g")
+            .stdin(Stdio::inherit())
+            .output()?;
+        let saved = output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string());
+        ProcessCommand::new("stty")
+            .args(["raw", "-echo"])
+            .stdin(Stdio::inherit())
+            .status()?;
+        write_terminal("\x1b[?1049h\x1b[?25l")?;
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = write_terminal("\x1b[?25h\x1b[?1049l");
+        let mut stty = ProcessCommand::new("stty");
+        match &self.saved {
+            Some(saved) => stty.arg(saved),
+            None => stty.arg("sane"),
+        };
+        let _ = stty.stdin(Stdio::inherit()).status();
+    }
+}
+
+fn write_terminal(output: &str) -> Result<(), CliError> {
+    let mut stdout = io::stdout();
+    stdout.write_all(output.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+fn tui_overview(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    runtime_dir: &Path,
+) -> Result<tui::Overview, CliError> {
+    let path = control::read_only_socket_path(runtime_dir);
+    if let Ok(response) = control::send_command(&path, control::ControlCommand::State)
+        && let Ok(overview) = serde_json::from_str(&response)
+    {
+        return Ok(overview);
+    }
+    Ok(tui::overview(hyprctl, config)?)
+}
+
+fn run_tui(hyprctl: &dyn HyprlandIpc, config: &Config, paths: &EnvPaths) -> Result<(), CliError> {
+    let _terminal = RawTerminal::enable()?;
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut buffer = [0u8; 32];
+        loop {
+            match io::Read::read(&mut stdin, &mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => {
+                    if sender.send(buffer[..read].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+    let mut selected = None;
+    let mut status = String::new();
+    loop {
+        let overview = tui_overview(hyprctl, config, &paths.runtime_dir)?;
+        let current = *selected.get_or_insert(overview.active_slot.unwrap_or(1));
+        write_terminal(&format!(
+            "\x1b[H\x1b[2J{}",
+            tui::render_frame(&overview, current, &status)
+        ))?;
+        let keys = match receiver.recv_timeout(TUI_REFRESH) {
+            Ok(keys) => keys,
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+        };
+        let mut current = current;
+        for action in tui::parse_keys(&keys) {
+            let workspace = config.slot_workspace(current);
+            status = match action {
+                tui::TuiAction::Quit => return Ok(()),
+                tui::TuiAction::Switch => match commands::paired_switch(hyprctl, config, workspace)
+                {
+                    Ok(()) => format!("switched to pair {current}"),
+                    Err(err) => format!("error: {err}"),
+                },
+                tui::TuiAction::MoveWindow => {
+                    match commands::paired_move_window(hyprctl, config, workspace) {
+                        Ok(()) => format!("moved window to pair {current}"),
+                        Err(err) => format!("error: {err}"),
+                    }
+                }
+                tui::TuiAction::SaveSession => {
+                    match session::save_session(hyprctl, config, &paths.sessions_dir, None) {
+                        Ok(path) => format!("saved session to {}", path.display()),
+                        Err(err) => format!("error: {err}"),
+                    }
+                }
+                navigation => {
+                    current = tui::move_selection(current, navigation, config.paired_offset);
+                    continue;
+                }
+            };
+        }
+        selected = Some(current);
+    }
+}
+
+fn doctor_context(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+) -> doctor::DoctorContext {
+    let monitors = hyprctl
+        .monitors()
+        .map(|monitors| monitors.into_iter().map(|monitor| monitor.name).collect())
+        .map_err(|err| err.to_string());
+    doctor::DoctorContext {
+        base_dir: paths.base_dir.clone(),
+        config_path: paths.config_path.clone(),
+        hypr_config_dir: paths.hypr_config_dir.clone(),
+        pid_path: daemon_pid_path(&paths.state_dir),
+        bin_path: bin_path.to_string(),
+        pid_alive: doctor::pid_alive,
+        safe_mode_error: control::safe_mode_error(&control::read_only_socket_path(
+            &paths.runtime_dir,
+        )),
+        runtime_dir: env::var("XDG_RUNTIME_DIR").ok(),
+        instance_signature: env::var("HYPRLAND_INSTANCE_SIGNATURE").ok(),
+        search_path: env::var_os("PATH"),
+        monitors,
+    }
+}
+
+fn handle_setup_install(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+    args: &InstallArgs,
+) -> Result<(), CliError> {
+    let launcher = SystemDaemonLauncher;
+    handle_setup_install_with_launcher(hyprctl, paths, bin_path, args, &launcher)
+}
+
+fn handle_setup_install_with_launcher<L: DaemonLauncher>(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+    args: &InstallArgs,
+    launcher: &L,
+) -> Result<(), CliError> {
+    let monitors = hyprctl.monitors().ok();
+    setup::install(
+        &paths.base_dir,
+        bin_path,
+        &paths.hypr_config_dir,
+        &paths.config_path,
+        monitors.as_deref(),
+    )?;
+    if args.waybar {
+        if let Some(placement) = args.waybar_placement(setup::waybar_placement(&paths.config_path)?)
+        {
+            setup::store_waybar_placement(&paths.base_dir, &paths.config_path, &placement)?;
+        }
+        let outputs = match setup::waybar_placement(&paths.config_path)? {
+            Some(placement) if placement.per_output => {
+                waybar_outputs(&load_config(hyprctl, paths)?)
+            }
+            _ => Vec::new(),
+        };
+        setup::install_waybar(&paths.base_dir, bin_path, &outputs)?;
+        setup::place_waybar_module(&paths.base_dir, &waybar_dir(paths), &paths.config_path)?;
+    }
+    if args.autostart_xdg {
+        setup::install_xdg_autostart(
+            &paths.autostart_dir,
+            bin_path,
+            setup::restore_on_start(&paths.config_path)?,
+        )?;
+    }
+    let _ = hyprctl.reload();
+    launcher.launch(bin_path, &paths.state_dir)?;
+    Ok(())
+}
+
+fn waybar_outputs(config: &Config) -> Vec<String> {
+    let mut outputs: Vec<String> = Vec::new();
+    for monitor in Layout::for_config(config).monitors() {
+        if !outputs.iter().any(|output| output == monitor) {
+            outputs.push(monitor.to_string());
+        }
+    }
+    outputs
+}
+
+fn waybar_dir(paths: &EnvPaths) -> PathBuf {
+    paths
+        .waybar_css
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+pub(super) fn ensure_setup(
+    hyprctl: &dyn HyprlandIpc,
+    paths: &EnvPaths,
+    bin_path: &str,
+) -> Result<(), CliError> {
+    if hyprctl.compositor() != Compositor::Hyprland || paths.base_dir.join("bindings.conf").exists()
+    {
+        return Ok(());
+    }
+    let monitors = hyprctl.monitors().ok();
+    setup::install(
+        &paths.base_dir,
+        bin_path,
+        &paths.hypr_config_dir,
+        &paths.config_path,
+        monitors.as_deref(),
+    )?;
+    let _ = hyprctl.reload();
+    Ok(())
+}
+
+const WAYBAR_FOLLOW_INTERVAL: Duration = Duration::from_millis(100);
+
+fn theme_colors(
+    config: &Config,
+    theme_css: PathBuf,
+    color: Option<&str>,
+) -> Result<waybar::ThemeColors, CliError> {
+    let theme_paths = waybar::ThemePaths::new(theme_css, &cache_dir()?);
+    Ok(waybar::resolve_theme_colors(
+        config.theme_source,
+        color,
+        &theme_paths,
+    )?)
+}
+
+fn cache_dir() -> Result<PathBuf, CliError> {
+    let home = env::var("HOME").map_err(|_| CliError::MissingEnv("HOME"))?;
+    let xdg_cache = env::var("XDG_CACHE_HOME").ok();
+    Ok(paths::cache_dir(
+        Path::new(&home),
+        xdg_cache.as_deref().map(Path::new),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{handle_setup_install_with_launcher, migrate_windows};
+    use crate::cli::{
+        Cli, CliError, Command, EnvPaths, InstallArgs, SessionCommand, SessionRestoreMode,
+        SetupCommand, WaybarArgs,
+    };
+    use crate::commands::MigrationFilter;
+    use crate::config::Config;
+    use crate::hyprctl::HyprctlError;
+    use crate::setup::{self, BarPosition, ModulesSection};
+    use crate::testing::ScriptedIpc;
+    use clap::Parser;
+    use std::cell::RefCell;
+    use std::fs;
+    use std::io::Cursor;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+
+    #[derive(Default)]
+    struct RecordingLauncher {
+        calls: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl super::DaemonLauncher for RecordingLauncher {
+        fn launch(&self, bin_path: &str, base_dir: &Path) -> Result<(), CliError> {
+            self.calls
+                .borrow_mut()
+                .push(format!("{}:{}", bin_path, base_dir.display()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn waybar_requires_enable_flag() {
+        let args = WaybarArgs {
+            theme_css: None,
+            color: None,
+            follow_file: false,
+            enable_waybar: false,
+            daemon_timeout: std::time::Duration::ZERO,
+            compat: None,
+            monitor: None,
+        };
+
+        let err = args.ensure_enabled().expect_err("expected disabled error");
+
+        assert!(matches!(err, CliError::WaybarDisabled));
+    }
+
+    #[test]
+    fn waybar_allows_enabled_flag() {
+        let args = WaybarArgs {
+            theme_css: None,
+            color: None,
+            follow_file: false,
+            enable_waybar: true,
+            daemon_timeout: std::time::Duration::ZERO,
+            compat: None,
+            monitor: None,
+        };
+
+        args.ensure_enabled().expect("enabled");
+    }
+
+    #[test]
+    fn parses_waybar_install_flag() {
+        let cli =
+            Cli::try_parse_from(["hyprspaces", "setup", "install", "--waybar"]).expect("parse");
+
+        match cli.command.expect("command") {
+            Command::Setup {
+                command: SetupCommand::Install(args),
+            } => assert!(args.waybar),
+            _ => panic!("expected setup install"),
+        }
+    }
+
+    #[test]
+    fn setup_install_writes_waybar_files_when_enabled() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = dir.path().join("hyprspaces");
+        let config_path = dir.path().join("paired.json");
+        let hypr_dir = dir.path().join("hypr");
+        fs::create_dir_all(&hypr_dir).expect("hypr dir");
+        fs::write(hypr_dir.join("bindings.conf"), "base\n").expect("bindings");
+        fs::write(hypr_dir.join("autostart.conf"), "base\n").expect("autostart");
+        fs::write(hypr_dir.join("hyprland.conf"), "base\n").expect("hyprland");
+
+        let monitors = r#"[{"name":"DP-1","x":0,"id":1},{"name":"HDMI-A-1","x":1920,"id":2}]"#;
+        let hyprctl = ScriptedIpc::new().with_json("monitors", monitors);
+        let paths = EnvPaths {
+            base_dir: base_dir.clone(),
+            runtime_dir: base_dir.clone(),
+            state_dir: base_dir.clone(),
+            sessions_dir: base_dir.join("sessions"),
+            config_path,
+            hypr_config_dir: hypr_dir,
+            waybar_css: dir.path().join("waybar").join("style.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+
+        let launcher = RecordingLauncher::default();
+        let args = InstallArgs {
+            waybar: true,
+            ..InstallArgs::default()
+        };
+        handle_setup_install_with_launcher(&hyprctl, &paths, "hyprspaces", &args, &launcher)
+            .expect("install waybar");
+
+        let waybar_dir = base_dir.join("waybar");
+        let config = fs::read_to_string(waybar_dir.join("workspaces.json")).expect("config");
+        let json: serde_json::Value = serde_json::from_str(&config).expect("json");
+        let exec = json["custom/workspaces"]["exec"].as_str().expect("exec");
+        let theme_path = waybar_dir.join("theme.css");
+        assert!(waybar_dir.join("workspaces.json").exists());
+        assert!(waybar_dir.join("workspaces.css").exists());
+        assert!(theme_path.exists());
+        assert!(waybar_dir.join("installed.flag").exists());
+        assert_eq!(
+            exec,
+            format!(
+                "hyprspaces waybar --enable-waybar --theme-css {}",
+                theme_path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn setup_install_places_waybar_module_and_stores_placement() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = dir.path().join("hyprspaces");
+        let config_path = dir.path().join("paired.json");
+        let hypr_dir = dir.path().join("hypr");
+        let waybar_dir = dir.path().join("waybar");
+        fs::create_dir_all(&hypr_dir).expect("hypr dir");
+        fs::create_dir_all(&waybar_dir).expect("waybar dir");
+        fs::write(
+            waybar_dir.join("config.jsonc"),
+            r#"[
+  // main bar
+  {"position": "top", "modules-left": ["clock"]},
+  {"position": "bottom", "output": "DP-1", "modules-right": ["tray", "clock"]}
+]"#,
+        )
+        .expect("waybar config");
+
+        let hyprctl = ScriptedIpc::new().with_json("monitors", "[]");
+        let paths = EnvPaths {
+            base_dir: base_dir.clone(),
+            runtime_dir: base_dir.clone(),
+            state_dir: base_dir.clone(),
+            sessions_dir: base_dir.join("sessions"),
+            config_path: config_path.clone(),
+            hypr_config_dir: hypr_dir,
+            waybar_css: waybar_dir.join("style.css"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        let args = InstallArgs {
+            waybar: true,
+            waybar_bar: Some(BarPosition::Bottom),
+            waybar_output: Some("DP-1".to_string()),
+            waybar_modules: Some(ModulesSection::Right),
+            waybar_index: Some(1),
+            ..InstallArgs::default()
+        };
+        handle_setup_install_with_launcher(
+            &hyprctl,
+            &paths,
+            "hyprspaces",
+            &args,
+            &RecordingLauncher::default(),
+        )
+        .expect("install");
+
+        let bars: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(waybar_dir.join("config.jsonc")).expect("read"),
+        )
+        .expect("json");
+        assert_eq!(bars[0]["modules-left"], serde_json::json!(["clock"]));
+        assert_eq!(
+            bars[1]["modules-right"],
+            serde_json::json!(["tray", "custom/workspaces", "clock"])
+        );
+        assert_eq!(
+            bars[1]["include"],
+            serde_json::json!([base_dir
+                .join("waybar/workspaces.json")
+                .display()
+                .to_string()])
+        );
+        let stored = setup::waybar_placement(&config_path)
+            .expect("placement")
+            .expect("stored");
+        assert_eq!(stored.bar, BarPosition::Bottom);
+        assert_eq!(stored.output.as_deref(), Some("DP-1"));
+        assert_eq!(stored.index, Some(1));
+    }
+
+    #[test]
+    fn setup_install_launches_daemon() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = dir.path().join("hyprspaces");
+        let config_path = dir.path().join("paired.json");
+        let hypr_dir = dir.path().join("hypr");
+        fs::create_dir_all(&hypr_dir).expect("hypr dir");
+        fs::write(hypr_dir.join("bindings.conf"), "base\n").expect("bindings");
+        fs::write(hypr_dir.join("autostart.conf"), "base\n").expect("autostart");
+        fs::write(hypr_dir.join("hyprland.conf"), "base\n").expect("hyprland");
+
+        let monitors = r#"[{"name":"DP-1","x":0,"id":1},{"name":"HDMI-A-1","x":1920,"id":2}]"#;
+        let hyprctl = ScriptedIpc::new().with_json("monitors", monitors);
+        let paths = EnvPaths {
+            base_dir: base_dir.clone(),
+            runtime_dir: base_dir.clone(),
+            state_dir: base_dir.clone(),
+            sessions_dir: base_dir.join("sessions"),
+            config_path,
+            hypr_config_dir: hypr_dir,
+            waybar_css: PathBuf::from("unused"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+
+        let launcher = RecordingLauncher::default();
+        let args = InstallArgs {
+            autostart_xdg: true,
+            ..InstallArgs::default()
+        };
+        handle_setup_install_with_launcher(&hyprctl, &paths, "hyprspaces", &args, &launcher)
+            .expect("install");
+
+        let calls = launcher.calls.borrow();
+        assert_eq!(
+            calls.as_slice(),
+            &[format!("hyprspaces:{}", base_dir.display())]
+        );
+        let desktop = fs::read_to_string(dir.path().join("autostart/hyprspaces-daemon.desktop"))
+            .expect("desktop entry");
+        assert!(desktop.contains("Exec=hyprspaces daemon\n"));
+    }
+
+    #[test]
+    fn config_set_regenerates_bindings_and_rebalances() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = dir.path().join("hyprspaces");
+        let paths = EnvPaths {
+            base_dir: base_dir.clone(),
+            runtime_dir: base_dir.clone(),
+            state_dir: base_dir.clone(),
+            sessions_dir: base_dir.join("sessions"),
+            config_path: dir.path().join("paired.json"),
+            hypr_config_dir: dir.path().join("hypr"),
+            waybar_css: PathBuf::from("unused"),
+            autostart_dir: dir.path().join("autostart"),
+        };
+        fs::write(
+            &paths.config_path,
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","workspace_count":2,"bindings":{"commands":["switch"]}}"#,
+        )
+        .expect("config");
+        setup::install(
+            &base_dir,
+            "hyprspaces",
+            &paths.hypr_config_dir,
+            &paths.config_path,
+            None,
+        )
+        .expect("install");
+        let ipc = ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","id":0,"x":0},{"name":"HDMI-A-1","id":1,"x":1920}]"#,
+            )
+            .with_json("activeworkspace", r#"{"id":1}"#);
+
+        super::config_set(&ipc, &paths, "hyprspaces", "workspace_count", "3").expect("set");
+
+        let bindings = fs::read_to_string(base_dir.join("bindings.conf")).expect("bindings");
+        assert!(bindings.contains("Paired workspace 3"));
+        let rules = fs::read_to_string(setup::workspace_rules_path(&base_dir)).expect("rules");
+        assert!(rules.contains("workspace = 6, monitor:HDMI-A-1"));
+        assert!(ipc.called(&["reload"]));
+        assert!(ipc.calls().iter().any(|call| {
+            call.iter()
+                .any(|arg| arg.contains("moveworkspacetomonitor 6 HDMI-A-1"))
+        }));
+    }
+
+    #[test]
+    fn session_restore_finds_snapshots_from_a_previous_instance() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = dir.path().to_path_buf();
+        let instance_paths = |signature: &str| EnvPaths {
+            base_dir: base_dir.clone(),
+            runtime_dir: base_dir.join("run").join(signature),
+            state_dir: base_dir.join("run").join(signature),
+            sessions_dir: crate::paths::sessions_dir(&base_dir, Some(signature)),
+            config_path: base_dir.join("paired.json"),
+            hypr_config_dir: base_dir.join("hypr"),
+            waybar_css: PathBuf::from("unused"),
+            autostart_dir: base_dir.join("autostart"),
+        };
+        fs::write(
+            base_dir.join("paired.json"),
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#,
+        )
+        .expect("config");
+        fs::write(base_dir.join("bindings.conf"), "").expect("bindings");
+        let ipc = ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","id":0,"x":0},{"name":"HDMI-A-1","id":1,"x":1920}]"#,
+            )
+            .with_json(
+                "clients",
+                r#"[{"address":"0x1","workspace":{"id":3},"class":"kitty"}]"#,
+            )
+            .with_json("workspaces", r#"[{"id":3,"windows":1,"monitor":"DP-1"}]"#)
+            .with_json("activeworkspace", r#"{"id":3}"#);
+
+        super::session(
+            &ipc,
+            &instance_paths("first"),
+            "hyprspaces",
+            SessionCommand::Save {
+                path: None,
+                name: None,
+            },
+        )
+        .expect("save");
+        ipc.set_json(
+            "clients",
+            r#"[{"address":"0x1","workspace":{"id":1},"class":"kitty"}]"#,
+        );
+
+        super::session(
+            &ipc,
+            &instance_paths("second"),
+            "hyprspaces",
+            SessionCommand::Restore {
+                path: None,
+                name: None,
+                mode: SessionRestoreMode::Same,
+                allow_degraded: false,
+            },
+        )
+        .expect("restore");
+
+        assert!(ipc.calls().iter().any(|call| {
+            call.iter()
+                .any(|arg| arg.contains("movetoworkspacesilent 3,address:0x1"))
+        }));
+    }
+
+    fn migration_ipc() -> ScriptedIpc {
+        ScriptedIpc::new().with_json(
+            "clients",
+            r#"[{"address":"0x1","workspace":{"id":12},"class":"kitty"},
+                {"address":"0x2","workspace":{"id":13},"class":"firefox"}]"#,
+        )
+    }
+
+    fn migration_config() -> Config {
+        Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .build()
+            .expect("config")
+    }
+
+    #[test]
+    fn migrate_windows_asks_before_moving() {
+        let ipc = migration_ipc();
+        let mut output = Vec::new();
+
+        migrate_windows(
+            &ipc,
+            &migration_config(),
+            &MigrationFilter::default(),
+            false,
+            &mut Cursor::new("n\n"),
+            &mut output,
+        )
+        .expect("declined");
+
+        assert_eq!(
+            String::from_utf8(output).expect("utf8"),
+            "2 window(s) to migrate:\n  12 → 2: 1 window(s) (kitty)\n  13 → 3: 1 window(s) (firefox)\nMigrate them? [y/N]: aborted\n"
+        );
+        assert!(
+            ipc.calls()
+                .iter()
+                .all(|call| call.first().map(String::as_str) == Some("-j"))
+        );
+    }
+
+    #[test]
+    fn migrate_windows_reports_each_failure_and_keeps_going() {
+        let ipc = migration_ipc();
+        ipc.fail(
+            "dispatch",
+            HyprctlError::CommandFailed {
+                command: "hyprctl dispatch".to_string(),
+                status: 1,
+                stderr: "no such window".to_string(),
+            },
+        );
+        let mut output = Vec::new();
+
+        let err = migrate_windows(
+            &ipc,
+            &migration_config(),
+            &MigrationFilter::default(),
+            true,
+            &mut Cursor::new(""),
+            &mut output,
+        )
+        .expect_err("one move failed");
+
+        let output = String::from_utf8(output).expect("utf8");
+        assert!(output.contains("[1/2] failed 0x1 (kitty) 12 → 2: hyprctl command failed"));
+        assert!(output.contains("[2/2] moved 0x2 (firefox) 13 → 3\nmigrated 1 window(s)"));
+        assert!(!output.contains("[y/N]"));
+        assert!(matches!(
+            err,
+            CliError::MigrationFailed {
+                failed: 1,
+                total: 2
+            }
+        ));
+    }
+}
@@ -1,116 +1,553 @@
-use crate::config::Config;
-use crate::hyprctl::{HyprlandIpc, paired_switch_batch};
-use crate::paired::{CycleDirection, cycle_target, normalize_workspace};
-use crate::setup::migration_targets;
+use crate::config::{Config, OutOfRangePolicy};
+use crate::hyprctl::{
+    ClientInfo, Dispatcher, HyprctlBatch, HyprctlError, HyprlandIpc, MonitorInfo,
+    PairedSwitchTemplate, SwitchStyle, WorkspaceTarget, logical_monitors,
+    single_monitor_switch_batch,
+};
+use crate::layout::Layout;
+use crate::paired::{
+    CycleDirection, CycleOrder, PairedSide, WorkspaceRangeError, cycle_order, cycle_target,
+    occupied_cycle_target, ordered_cycle_target, resolve_workspace,
+};
+use crate::sticky::active_window;
+use std::collections::BTreeMap;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("{0}")]
+    Hyprctl(#[from] HyprctlError),
+    #[error("{0}")]
+    Workspace(#[from] WorkspaceRangeError),
+    #[error("neither {primary} nor {secondary} is connected")]
+    MonitorsDisconnected { primary: String, secondary: String },
+    #[error("no focused window")]
+    NoActiveWindow,
+    #[error("focused window is on workspace {0}, outside the paired range")]
+    WindowOutsideLayout(u32),
+    #[error("no window is asking for attention")]
+    NoUrgentWindow,
+    #[error("no window with address {0}")]
+    UnknownWindow(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendTarget {
+    Primary,
+    Secondary,
+    Other,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CycleOptions {
+    pub occupied_only: bool,
+    pub order: CycleOrder,
+    pub history: Vec<u32>,
+    pub held: Option<PairedSide>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CloneReport {
+    pub launched: usize,
+    pub skipped: Vec<String>,
+}
 
 pub fn paired_switch(
     hyprctl: &dyn HyprlandIpc,
     config: &Config,
     workspace: u32,
-) -> Result<(), crate::hyprctl::HyprctlError> {
-    let batch = paired_switch_batch(
-        &config.primary_monitor,
-        &config.secondary_monitor,
+) -> Result<(), CommandError> {
+    paired_switch_with_policy(hyprctl, config, workspace, config.out_of_range)
+}
+
+pub fn paired_switch_with_policy(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    workspace: u32,
+    policy: OutOfRangePolicy,
+) -> Result<(), CommandError> {
+    paired_switch_held(hyprctl, config, workspace, policy, None)
+}
+
+/// Like [`paired_switch_with_policy`], but a `held` monitor keeps its workspace.
+pub fn paired_switch_held(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    workspace: u32,
+    policy: OutOfRangePolicy,
+    held: Option<PairedSide>,
+) -> Result<(), CommandError> {
+    let target = resolve_workspace(
         workspace,
         config.paired_offset,
-    );
-    hyprctl.batch(&batch)?;
+        config.workspace_base,
+        policy,
+    )?;
+    let primary_workspace = config.slot_workspace(target.slot);
+    let secondary_workspace = primary_workspace + config.paired_offset;
+    let monitors = hyprctl.monitors().map(logical_monitors).ok();
+    let (primary_connected, secondary_connected) = connected_monitors(monitors.as_deref(), config);
+    if primary_connected && secondary_connected {
+        let unlocked = match held {
+            Some(PairedSide::Primary) => Some((&config.secondary_monitor, secondary_workspace)),
+            Some(PairedSide::Secondary) => Some((&config.primary_monitor, primary_workspace)),
+            None => None,
+        };
+        if let Some((monitor, workspace)) = unlocked {
+            return single_monitor_switch(hyprctl, config, monitors.as_deref(), monitor, workspace);
+        }
+    }
+    if !primary_connected || !secondary_connected {
+        let (monitor, workspace, missing) = match (primary_connected, secondary_connected) {
+            (true, false) => (
+                &config.primary_monitor,
+                primary_workspace,
+                &config.secondary_monitor,
+            ),
+            (false, true) => (
+                &config.secondary_monitor,
+                secondary_workspace,
+                &config.primary_monitor,
+            ),
+            _ => {
+                return Err(CommandError::MonitorsDisconnected {
+                    primary: config.primary_monitor.clone(),
+                    secondary: config.secondary_monitor.clone(),
+                });
+            }
+        };
+        eprintln!("warning: {missing} is not connected; switching {monitor} only");
+        return single_monitor_switch(hyprctl, config, monitors.as_deref(), monitor, workspace);
+    }
+    // Focus stays put unless a raw secondary target was asked for.
+    let layout = Layout::for_config(config);
+    let current = monitors.as_deref().and_then(focused_monitor);
+    let in_layout = current.filter(|current| layout.monitors().any(|monitor| monitor == *current));
+    let focus_monitor = match target.side {
+        PairedSide::Primary => in_layout.unwrap_or(&config.primary_monitor),
+        PairedSide::Secondary => &config.secondary_monitor,
+    };
+    let template = PairedSwitchTemplate::new(&layout);
+    if monitors
+        .as_deref()
+        .is_some_and(|monitors| template.is_current(monitors, primary_workspace, focus_monitor))
+    {
+        return Ok(());
+    }
+    let mut batch = HyprctlBatch::from_argument(template.to_batch(
+        primary_workspace,
+        focus_monitor,
+        SwitchStyle::Full,
+    ));
+    let mut focused = focus_monitor;
+    if let Some(current) = current.filter(|_| in_layout.is_none()) {
+        batch.push(Dispatcher::FocusMonitor(current));
+        focused = current;
+    }
+    warp_cursor(&mut batch, config, monitors.as_deref(), focused);
+    hyprctl.batch(batch.as_str())?;
     Ok(())
 }
 
+/// Centers the pointer on `monitor` when `cursor_follows_focus` is set.
+fn warp_cursor(
+    batch: &mut HyprctlBatch,
+    config: &Config,
+    monitors: Option<&[MonitorInfo]>,
+    monitor: &str,
+) {
+    if !config.cursor_follows_focus {
+        return;
+    }
+    if let Some(info) = monitors
+        .unwrap_or_default()
+        .iter()
+        .find(|info| info.name == monitor)
+    {
+        let (x, y) = info.center();
+        batch.push(Dispatcher::MoveCursor { x, y });
+    }
+}
+
+fn focused_monitor(monitors: &[MonitorInfo]) -> Option<&str> {
+    monitors
+        .iter()
+        .find(|monitor| monitor.focused)
+        .map(|monitor| monitor.name.as_str())
+}
+
+/// Switches `monitor` to `workspace` and hands focus back.
+fn single_monitor_switch(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    monitors: Option<&[MonitorInfo]>,
+    monitor: &str,
+    workspace: u32,
+) -> Result<(), CommandError> {
+    let monitors = monitors.unwrap_or_default();
+    if monitors
+        .iter()
+        .any(|info| info.name == monitor && info.active_workspace == Some(workspace))
+    {
+        return Ok(());
+    }
+    let mut batch = HyprctlBatch::from_argument(single_monitor_switch_batch(monitor, workspace));
+    let mut focused = monitor;
+    if let Some(current) = focused_monitor(monitors).filter(|current| *current != monitor) {
+        batch.push(Dispatcher::FocusMonitor(current));
+        focused = current;
+    }
+    warp_cursor(&mut batch, config, Some(monitors), focused);
+    hyprctl.batch(batch.as_str())?;
+    Ok(())
+}
+
+fn connected_monitors(monitors: Option<&[MonitorInfo]>, config: &Config) -> (bool, bool) {
+    match monitors {
+        Some(monitors) => {
+            let connected = |name: &str| monitors.iter().any(|monitor| monitor.name == name);
+            (
+                connected(&config.primary_monitor),
+                connected(&config.secondary_monitor),
+            )
+        }
+        None => (true, true),
+    }
+}
+
 pub fn paired_cycle(
     hyprctl: &dyn HyprlandIpc,
     config: &Config,
     direction: CycleDirection,
-) -> Result<(), crate::hyprctl::HyprctlError> {
+) -> Result<(), CommandError> {
+    let options = CycleOptions {
+        occupied_only: config.cycle_skip_empty,
+        ..CycleOptions::default()
+    };
+    paired_cycle_with_options(hyprctl, config, direction, &options)
+}
+
+pub fn paired_cycle_with_options(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    direction: CycleDirection,
+    options: &CycleOptions,
+) -> Result<(), CommandError> {
     let active_workspace = hyprctl.active_workspace_id()?;
-    let base = normalize_workspace(active_workspace, config.paired_offset);
-    let target = cycle_target(base, config.paired_offset, direction, config.wrap_cycling);
-    paired_switch(hyprctl, config, target)
+    let offset = config.paired_offset;
+    let base = config.slot_of(active_workspace);
+    if !options.occupied_only && options.order == CycleOrder::Numeric {
+        let target = cycle_target(base, offset, direction, config.wrap_cycling);
+        return paired_switch_held(
+            hyprctl,
+            config,
+            config.slot_workspace(target),
+            config.out_of_range,
+            options.held,
+        );
+    }
+    let mut counts = BTreeMap::new();
+    if options.occupied_only || options.order == CycleOrder::Occupied {
+        let layout = Layout::for_config(config);
+        for workspace in hyprctl.workspaces()? {
+            if workspace.windows > 0 && layout.member_of(workspace.id).is_some() {
+                *counts.entry(layout.slot(workspace.id)).or_insert(0) += workspace.windows;
+            }
+        }
+    }
+    let target = match options.order {
+        CycleOrder::Numeric => {
+            let occupied: Vec<u32> = counts.keys().copied().collect();
+            occupied_cycle_target(base, offset, direction, config.wrap_cycling, &occupied)
+        }
+        order => {
+            let mut slots = cycle_order(offset, order, &options.history, &counts);
+            if options.occupied_only {
+                slots.retain(|slot| *slot == base || counts.contains_key(slot));
+            }
+            ordered_cycle_target(&slots, base, direction, config.wrap_cycling)
+        }
+    };
+    match target {
+        Some(target) => paired_switch_held(
+            hyprctl,
+            config,
+            config.slot_workspace(target),
+            config.out_of_range,
+            options.held,
+        ),
+        None => Ok(()),
+    }
 }
 
 pub fn paired_move_window(
     hyprctl: &dyn HyprlandIpc,
     config: &Config,
     workspace: u32,
-) -> Result<(), crate::hyprctl::HyprctlError> {
-    let normalized = normalize_workspace(workspace, config.paired_offset);
+) -> Result<(), CommandError> {
+    let layout = Layout::for_config(config);
+    let normalized = layout.slot(workspace);
     let active_workspace = hyprctl.active_workspace_id()?;
-    let mut target = normalized;
-    if active_workspace > config.paired_offset {
-        target += config.paired_offset;
-    }
+    let member = match layout.member_of(active_workspace) {
+        Some(member) => member,
+        None if active_workspace > layout.max_workspace() => layout.member_count() - 1,
+        None => 0,
+    };
+    let target = layout.workspace(normalized, member);
     hyprctl.dispatch("movetoworkspacesilent", &target.to_string())?;
-    paired_switch(hyprctl, config, normalized)
+    paired_switch(hyprctl, config, layout.workspace(normalized, 0))
+}
+
+/// Returns the workspace the window was sent to, or `None` if already there.
+pub fn paired_send_to(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    target: SendTarget,
+) -> Result<Option<u32>, CommandError> {
+    let clients = hyprctl.clients()?;
+    let window = active_window(&clients).ok_or(CommandError::NoActiveWindow)?;
+    let layout = Layout::for_config(config);
+    let workspace = window.workspace.id;
+    let member = layout
+        .member_of(workspace)
+        .ok_or(CommandError::WindowOutsideLayout(workspace))?;
+    let monitor = match target {
+        SendTarget::Primary => config.primary_monitor.as_str(),
+        SendTarget::Secondary => config.secondary_monitor.as_str(),
+        SendTarget::Other => layout.monitor((member + 1) % layout.member_count()),
+    };
+    let Some(destination) = layout.shown_workspace(workspace, monitor) else {
+        return Ok(None);
+    };
+    if destination == workspace {
+        return Ok(None);
+    }
+    let mut batch = HyprctlBatch::new();
+    batch.push(Dispatcher::MoveToWorkspaceSilent {
+        target: WorkspaceTarget::Id(destination),
+        address: &window.address,
+    });
+    hyprctl.batch(&batch.into_argument())?;
+    Ok(Some(destination))
+}
+
+pub fn paired_clone(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    from: u32,
+    to: u32,
+) -> Result<CloneReport, CommandError> {
+    let (offset, base) = (config.paired_offset, config.workspace_base);
+    let from = resolve_workspace(from, offset, base, OutOfRangePolicy::Error)?.slot;
+    let to = resolve_workspace(to, offset, base, OutOfRangePolicy::Error)?.slot;
+    let layout =
+        Layout::paired(&config.primary_monitor, &config.secondary_monitor, offset).with_base(base);
+    let mut report = CloneReport::default();
+    for client in hyprctl.clients()? {
+        let Some(member) = layout.member_of(client.workspace.id) else {
+            continue;
+        };
+        if layout.slot(client.workspace.id) != from {
+            continue;
+        }
+        let Some(class) = client
+            .class
+            .as_deref()
+            .or(client.initial_class.as_deref())
+            .filter(|class| !class.is_empty())
+        else {
+            continue;
+        };
+        match config.launch.get(class) {
+            Some(command) => {
+                let workspace = layout.workspace(to, member);
+                hyprctl.dispatch("exec", &format!("[workspace {workspace} silent] {command}"))?;
+                report.launched += 1;
+            }
+            None if !report.skipped.iter().any(|skipped| skipped == class) => {
+                report.skipped.push(class.to_string());
+            }
+            None => {}
+        }
+    }
+    Ok(report)
+}
+
+/// A window in the secondary range and the primary workspace it moves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationTarget {
+    pub address: String,
+    pub class: Option<String>,
+    pub title: Option<String>,
+    pub from: u32,
+    pub to: u32,
+}
+
+/// Restricts a migration by window class; `only` is applied before `except`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationFilter {
+    pub only: Vec<String>,
+    pub except: Vec<String>,
+}
+
+impl MigrationFilter {
+    pub fn matches(&self, target: &MigrationTarget) -> bool {
+        let class = target.class.as_deref().unwrap_or_default();
+        (self.only.is_empty() || self.only.iter().any(|only| only == class))
+            && !self.except.iter().any(|except| except == class)
+    }
+}
+
+pub fn migration_targets(clients: &[ClientInfo], offset: u32, base: u32) -> Vec<MigrationTarget> {
+    let secondary = base + offset..base + offset * 2;
+    clients
+        .iter()
+        .filter_map(|client| {
+            let workspace_id = client.workspace.id;
+            if secondary.contains(&workspace_id) {
+                Some(MigrationTarget {
+                    address: client.address.clone(),
+                    class: client
+                        .class
+                        .clone()
+                        .or_else(|| client.initial_class.clone()),
+                    title: client.title.clone(),
+                    from: workspace_id,
+                    to: workspace_id - offset,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// The windows `migrate_windows_filtered` would move, without moving them.
+pub fn preview_migration(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    filter: &MigrationFilter,
+) -> Result<Vec<MigrationTarget>, crate::hyprctl::HyprctlError> {
+    let mut targets = migration_targets(
+        &hyprctl.clients()?,
+        config.paired_offset,
+        config.workspace_base,
+    );
+    targets.retain(|target| filter.matches(target));
+    Ok(targets)
 }
 
 pub fn migrate_windows(
     hyprctl: &dyn HyprlandIpc,
     config: &Config,
 ) -> Result<usize, crate::hyprctl::HyprctlError> {
-    let clients = hyprctl.clients()?;
-    let targets = migration_targets(&clients, config.paired_offset);
-    for (address, target) in &targets {
+    migrate_windows_filtered(hyprctl, config, &MigrationFilter::default())
+}
+
+pub fn migrate_windows_filtered(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    filter: &MigrationFilter,
+) -> Result<usize, crate::hyprctl::HyprctlError> {
+    let targets = preview_migration(hyprctl, config, filter)?;
+    for target in &targets {
         hyprctl.dispatch(
             "movetoworkspacesilent",
-            &format!("{target},address:{address}"),
+            &format!("{},address:{}", target.to, target.address),
         )?;
     }
     Ok(targets.len())
 }
 
+/// Moves each target, reporting failures to `progress`; returns how many moved.
+pub fn migrate_targets(
+    hyprctl: &dyn HyprlandIpc,
+    targets: &[MigrationTarget],
+    mut progress: impl FnMut(&MigrationTarget, Result<(), &crate::hyprctl::HyprctlError>),
+) -> usize {
+    let mut moved = 0;
+    for target in targets {
+        match hyprctl.dispatch(
+            "movetoworkspacesilent",
+            &format!("{},address:{}", target.to, target.address),
+        ) {
+            Ok(_) => {
+                moved += 1;
+                progress(target, Ok(()));
+            }
+            Err(err) => progress(target, Err(&err)),
+        }
+    }
+    moved
+}
+
+/// Shows the pair holding window `address` and focuses it; returns its workspace.
+pub fn paired_focus_window(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    address: &str,
+) -> Result<u32, CommandError> {
+    let clients = hyprctl.clients()?;
+    let window = clients
+        .iter()
+        .find(|client| client.address == address)
+        .ok_or_else(|| CommandError::UnknownWindow(address.to_string()))?;
+    let workspace = window.workspace.id;
+    if Layout::for_config(config).member_of(workspace).is_some() {
+        paired_switch_with_policy(hyprctl, config, workspace, OutOfRangePolicy::Normalize)?;
+    }
+    hyprctl.dispatch("focuswindow", &format!("address:{address}"))?;
+    Ok(workspace)
+}
+
 pub fn grab_rogue_windows(
     hyprctl: &dyn HyprlandIpc,
     config: &Config,
 ) -> Result<usize, crate::hyprctl::HyprctlError> {
     let clients = hyprctl.clients()?;
-    let targets = migration_targets(&clients, config.workspace_count);
-    for (address, target) in &targets {
+    let targets = migration_targets(&clients, config.workspace_count, config.workspace_base);
+    for target in &targets {
         hyprctl.dispatch(
             "movetoworkspacesilent",
-            &format!("{target},address:{address}"),
+            &format!("{},address:{}", target.to, target.address),
         )?;
     }
     Ok(targets.len())
 }
 
+/// Where `enforce_range` sends a window on `workspace`, if it is out of range.
+pub fn range_target(config: &Config, workspace: u32) -> Option<u32> {
+    let layout = Layout::for_config(config);
+    layout
+        .member_of(workspace)
+        .is_none()
+        .then(|| layout.workspace(layout.slot(workspace), 0))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{grab_rogue_windows, migrate_windows, paired_cycle, paired_move_window};
-    use crate::config::Config;
-    use crate::hyprctl::{Hyprctl, HyprctlRunner};
-    use crate::paired::CycleDirection;
-    use std::cell::RefCell;
-    use std::rc::Rc;
-
-    #[derive(Clone)]
-    struct ScriptedRunner {
-        active_id: u32,
-        clients_json: String,
-        calls: Rc<RefCell<Vec<Vec<String>>>>,
-    }
-
-    impl ScriptedRunner {
-        fn new(active_id: u32, clients_json: &str) -> Self {
-            Self {
-                active_id,
-                clients_json: clients_json.to_string(),
-                calls: Rc::new(RefCell::new(Vec::new())),
-            }
-        }
-    }
+    use super::{
+        CloneReport, CommandError, CycleOptions, MigrationFilter, MigrationTarget, SendTarget,
+        grab_rogue_windows, migrate_targets, migrate_windows, migrate_windows_filtered,
+        migration_targets, paired_clone, paired_cycle, paired_cycle_with_options,
+        paired_focus_window, paired_move_window, paired_send_to, paired_switch, paired_switch_held,
+        paired_switch_with_policy, preview_migration, range_target,
+    };
+    use crate::config::{Config, OutOfRangePolicy, RebalanceMode};
+    use crate::hyprctl::{ClientInfo, HyprctlError, WorkspaceRef};
+    use crate::layout::LayoutMonitor;
+    use crate::paired::{CycleDirection, CycleOrder, PairedSide};
+    use crate::testing::ScriptedIpc;
+    use std::collections::BTreeMap;
 
-    impl HyprctlRunner for ScriptedRunner {
-        fn run(&self, args: &[String]) -> Result<String, crate::hyprctl::HyprctlError> {
-            self.calls.borrow_mut().push(args.to_vec());
-            if args == ["-j".to_string(), "activeworkspace".to_string()] {
-                return Ok(format!(r#"{{"id":{}}}"#, self.active_id));
-            }
-            if args == ["-j".to_string(), "clients".to_string()] {
-                return Ok(self.clients_json.clone());
-            }
-            Ok("ok".to_string())
-        }
+    fn scripted(active_id: u32, clients_json: &str) -> ScriptedIpc {
+        ScriptedIpc::new()
+            .with_json("activeworkspace", &format!(r#"{{"id":{active_id}}}"#))
+            .with_json("clients", clients_json)
+            .with_json("workspaces", "[]")
+            .with_json(
+                "monitors",
+                r#"[{"id":0,"name":"DP-1","x":0},{"id":1,"name":"HDMI-A-1","x":1920}]"#,
+            )
     }
 
     fn config() -> Config {
@@ -120,17 +557,45 @@ mod tests {
             paired_offset: 10,
             workspace_count: 10,
             wrap_cycling: true,
+            cycle_skip_empty: false,
+            out_of_range: OutOfRangePolicy::Normalize,
+            profiles: Vec::new(),
+            rebalance_on_start: true,
+            hotplug_settle_ms: 0,
+            schedules: Vec::new(),
+            rebalance_mode: RebalanceMode::Strict,
+            idle_inhibit_slots: Vec::new(),
+            launch: BTreeMap::new(),
+            names: BTreeMap::new(),
+            theme_source: Default::default(),
+            waybar: Default::default(),
+            drag_focus_delay_ms: 0,
+            metrics_address: None,
+            retry: Default::default(),
+            fast_switch: false,
+            enforce_range: false,
+            jump_to_urgent: false,
+            cursor_follows_focus: false,
+            instant_rebalance: false,
+            debounce: Default::default(),
+            layout: Vec::new(),
+            sticky_classes: Vec::new(),
+            reconcile: Default::default(),
+            primary_match: None,
+            secondary_match: None,
+            auto_primary: Default::default(),
+            workspace_base: 1,
+            restore: Default::default(),
         }
     }
 
     #[test]
     fn cycles_to_next_workspace() {
-        let runner = ScriptedRunner::new(12, "[]");
-        let hyprctl = Hyprctl::new(runner.clone());
+        let hyprctl = scripted(12, "[]");
 
         paired_cycle(&hyprctl, &config(), CycleDirection::Next).expect("cycle");
 
-        let calls = runner.calls.borrow();
+        let calls = hyprctl.calls();
         assert!(calls.iter().any(|call| {
             call == &vec![
                 "--batch".to_string(),
@@ -139,14 +604,357 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn switches_and_cycles_from_workspace_base_zero() {
+        let config = Config {
+            workspace_base: 0,
+            ..config()
+        };
+        let hyprctl = scripted(19, "[]");
+
+        paired_switch(&hyprctl, &config, 0).expect("switch");
+        paired_cycle(&hyprctl, &config, CycleDirection::Next).expect("cycle");
+
+        let calls = hyprctl.calls();
+        let batches = calls
+            .iter()
+            .filter(|call| call[0] == "--batch")
+            .map(|call| call[1].as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            batches,
+            vec![
+                "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 10 ; dispatch focusmonitor DP-1 ; dispatch workspace 0",
+                "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 10 ; dispatch focusmonitor DP-1 ; dispatch workspace 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles_to_next_occupied_pair() {
+        let hyprctl = scripted(12, "[]");
+        hyprctl.set_json(
+            "workspaces",
+            r#"[{"id":2,"windows":1},{"id":3,"windows":0},{"id":16,"windows":2}]"#,
+        );
+
+        let options = CycleOptions {
+            occupied_only: true,
+            ..CycleOptions::default()
+        };
+
+        paired_cycle_with_options(&hyprctl, &config(), CycleDirection::Next, &options)
+            .expect("cycle");
+
+        let calls = hyprctl.calls();
+        assert_eq!(
+            calls.last().expect("batch"),
+            &vec![
+                "--batch".to_string(),
+                "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 16 ; dispatch focusmonitor DP-1 ; dispatch workspace 6".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn occupied_cycle_stays_put_without_other_pairs() {
+        let hyprctl = scripted(2, "[]");
+        hyprctl.set_json("workspaces", r#"[{"id":2,"windows":1}]"#);
+
+        let options = CycleOptions {
+            occupied_only: true,
+            ..CycleOptions::default()
+        };
+
+        paired_cycle_with_options(&hyprctl, &config(), CycleDirection::Prev, &options)
+            .expect("cycle");
+
+        let calls = hyprctl.calls();
+        assert!(!calls.iter().any(|call| call[0] == "--batch"));
+    }
+
+    #[test]
+    fn cycles_in_mru_and_occupancy_order() {
+        let hyprctl = scripted(3, "[]");
+        hyprctl.set_json(
+            "workspaces",
+            r#"[{"id":2,"windows":1},{"id":7,"windows":2},{"id":17,"windows":2}]"#,
+        );
+        let mru = CycleOptions {
+            order: CycleOrder::Mru,
+            history: vec![3, 8, 1],
+            ..CycleOptions::default()
+        };
+        let occupied = CycleOptions {
+            occupied_only: true,
+            order: CycleOrder::Occupied,
+            ..CycleOptions::default()
+        };
+
+        paired_cycle_with_options(&hyprctl, &config(), CycleDirection::Next, &mru).expect("mru");
+        paired_cycle_with_options(&hyprctl, &config(), CycleDirection::Prev, &occupied)
+            .expect("occupied");
+
+        let calls = hyprctl.calls();
+        let batches: Vec<&String> = calls
+            .iter()
+            .filter(|call| call[0] == "--batch")
+            .map(|call| &call[1])
+            .collect();
+        assert!(batches[0].ends_with("dispatch workspace 8"));
+        assert!(batches[1].ends_with("dispatch workspace 2"));
+    }
+
+    #[test]
+    fn switch_normalizes_out_of_range_by_default() {
+        let hyprctl = scripted(1, "[]");
+
+        paired_switch(&hyprctl, &config(), 13).expect("switch");
+
+        let calls = hyprctl.calls();
+        assert_eq!(
+            calls[1],
+            vec![
+                "--batch".to_string(),
+                "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 13 ; dispatch focusmonitor DP-1 ; dispatch workspace 3".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_switch_focuses_secondary_workspace() {
+        let hyprctl = scripted(1, "[]");
+
+        paired_switch_with_policy(&hyprctl, &config(), 13, OutOfRangePolicy::Raw).expect("switch");
+
+        let calls = hyprctl.calls();
+        assert_eq!(
+            calls[1],
+            vec![
+                "--batch".to_string(),
+                "dispatch focusmonitor DP-1 ; dispatch workspace 3 ; dispatch focusmonitor HDMI-A-1 ; dispatch workspace 13".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn held_monitor_keeps_its_workspace() {
+        let hyprctl = scripted(5, "[]");
+
+        paired_switch_held(
+            &hyprctl,
+            &config(),
+            4,
+            OutOfRangePolicy::Normalize,
+            Some(PairedSide::Secondary),
+        )
+        .expect("switch");
+        paired_switch_held(
+            &hyprctl,
+            &config(),
+            4,
+            OutOfRangePolicy::Normalize,
+            Some(PairedSide::Primary),
+        )
+        .expect("switch");
+        let options = CycleOptions {
+            held: Some(PairedSide::Secondary),
+            ..CycleOptions::default()
+        };
+        paired_cycle_with_options(&hyprctl, &config(), CycleDirection::Next, &options)
+            .expect("cycle");
+
+        let batches: Vec<String> = hyprctl
+            .calls()
+            .iter()
+            .filter(|call| call[0] == "--batch")
+            .map(|call| call[1].clone())
+            .collect();
+        assert_eq!(
+            batches,
+            vec![
+                "dispatch focusmonitor DP-1 ; dispatch workspace 4",
+                "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 14",
+                "dispatch focusmonitor DP-1 ; dispatch workspace 6",
+            ]
+        );
+    }
+
+    #[test]
+    fn switch_falls_back_to_connected_monitor() {
+        let hyprctl = scripted(1, "[]");
+        hyprctl.set_json("monitors", r#"[{"id":0,"name":"DP-1","x":0}]"#);
+
+        paired_switch(&hyprctl, &config(), 3).expect("switch");
+
+        hyprctl.set_json("monitors", r#"[{"id":1,"name":"HDMI-A-1","x":0}]"#);
+        paired_switch(&hyprctl, &config(), 4).expect("switch");
+
+        hyprctl.set_json(
+            "monitors",
+            r#"[{"id":0,"name":"DP-1","x":0},{"id":1,"name":"HDMI-A-1","x":0,"mirrorOf":"0"}]"#,
+        );
+        paired_switch(&hyprctl, &config(), 5).expect("switch");
+
+        let batches: Vec<String> = hyprctl
+            .calls()
+            .iter()
+            .filter(|call| call[0] == "--batch")
+            .map(|call| call[1].clone())
+            .collect();
+        assert_eq!(
+            batches,
+            vec![
+                "dispatch focusmonitor DP-1 ; dispatch workspace 3",
+                "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 14",
+                "dispatch focusmonitor DP-1 ; dispatch workspace 5",
+            ]
+        );
+    }
+
+    #[test]
+    fn switch_skips_dispatch_when_the_pair_is_already_shown() {
+        let hyprctl = scripted(3, "[]");
+        hyprctl.set_json(
+            "monitors",
+            r#"[{"id":0,"name":"DP-1","x":0,"focused":true,"activeWorkspace":{"id":3,"name":"3"}},
+                {"id":1,"name":"HDMI-A-1","x":1920,"activeWorkspace":{"id":13,"name":"13"}}]"#,
+        );
+
+        paired_switch(&hyprctl, &config(), 3).expect("switch");
+        paired_switch_with_policy(&hyprctl, &config(), 13, OutOfRangePolicy::Raw).expect("switch");
+        paired_switch(&hyprctl, &config(), 4).expect("switch");
+        paired_switch_held(
+            &hyprctl,
+            &config(),
+            3,
+            OutOfRangePolicy::Normalize,
+            Some(PairedSide::Secondary),
+        )
+        .expect("switch");
+
+        let batches: Vec<String> = hyprctl
+            .calls()
+            .iter()
+            .filter(|call| call[0] == "--batch")
+            .map(|call| call[1].clone())
+            .collect();
+        assert_eq!(
+            batches,
+            vec![
+                "dispatch focusmonitor DP-1 ; dispatch workspace 3 ; dispatch focusmonitor HDMI-A-1 ; dispatch workspace 13",
+                "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 14 ; dispatch focusmonitor DP-1 ; dispatch workspace 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn switch_returns_focus_to_the_monitor_the_user_was_on() {
+        let hyprctl = scripted(13, "[]");
+        hyprctl.set_json(
+            "monitors",
+            r#"[{"id":0,"name":"DP-1","x":0},{"id":1,"name":"HDMI-A-1","x":1920,"focused":true},
+                {"id":2,"name":"eDP-1","x":3840}]"#,
+        );
+
+        paired_switch(&hyprctl, &config(), 4).expect("switch");
+        paired_switch_held(
+            &hyprctl,
+            &config(),
+            5,
+            OutOfRangePolicy::Normalize,
+            Some(PairedSide::Secondary),
+        )
+        .expect("switch");
+        hyprctl.set_json(
+            "monitors",
+            r#"[{"id":0,"name":"DP-1","x":0},{"id":1,"name":"HDMI-A-1","x":1920},
+                {"id":2,"name":"eDP-1","x":3840,"focused":true}]"#,
+        );
+        paired_switch(&hyprctl, &config(), 6).expect("switch");
+
+        let batches: Vec<String> = hyprctl
+            .calls()
+            .iter()
+            .filter(|call| call[0] == "--batch")
+            .map(|call| call[1].clone())
+            .collect();
+        assert_eq!(
+            batches,
+            vec![
+                "dispatch focusmonitor DP-1 ; dispatch workspace 4 ; dispatch focusmonitor HDMI-A-1 ; dispatch workspace 14",
+                "dispatch focusmonitor DP-1 ; dispatch workspace 5 ; dispatch focusmonitor HDMI-A-1",
+                "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 16 ; dispatch focusmonitor DP-1 ; dispatch workspace 6 ; dispatch focusmonitor eDP-1",
+            ]
+        );
+    }
+
+    #[test]
+    fn switch_warps_the_cursor_to_the_focused_monitor_when_asked() {
+        let hyprctl = scripted(1, "[]");
+        hyprctl.set_json(
+            "monitors",
+            r#"[{"id":0,"name":"DP-1","x":0,"width":2560,"height":1440,"focused":true},
+                {"id":1,"name":"HDMI-A-1","x":2560,"width":3840,"height":2160,"scale":2.0}]"#,
+        );
+        let follow = Config {
+            cursor_follows_focus: true,
+            ..config()
+        };
+
+        paired_switch(&hyprctl, &follow, 2).expect("switch");
+        paired_switch_with_policy(&hyprctl, &follow, 13, OutOfRangePolicy::Raw).expect("switch");
+        paired_switch(&hyprctl, &config(), 4).expect("switch");
+
+        let batches: Vec<String> = hyprctl
+            .calls()
+            .iter()
+            .filter(|call| call[0] == "--batch")
+            .map(|call| call[1].clone())
+            .collect();
+        assert_eq!(
+            batches,
+            vec![
+                "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 12 ; dispatch focusmonitor DP-1 ; dispatch workspace 2 ; dispatch movecursor 1280 720",
+                "dispatch focusmonitor DP-1 ; dispatch workspace 3 ; dispatch focusmonitor HDMI-A-1 ; dispatch workspace 13 ; dispatch movecursor 3520 540",
+                "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 14 ; dispatch focusmonitor DP-1 ; dispatch workspace 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn switch_errors_when_no_configured_monitor_is_connected() {
+        let hyprctl = scripted(1, "[]");
+        hyprctl.set_json("monitors", r#"[{"id":0,"name":"eDP-1","x":0}]"#);
+
+        let err = paired_switch(&hyprctl, &config(), 3).expect_err("disconnected");
+
+        assert_eq!(err.to_string(), "neither DP-1 nor HDMI-A-1 is connected");
+        assert!(!hyprctl.calls().iter().any(|call| call[0] == "--batch"));
+    }
+
+    #[test]
+    fn switch_rejects_out_of_range_with_error_policy() {
+        let hyprctl = scripted(1, "[]");
+
+        let err = paired_switch_with_policy(&hyprctl, &config(), 13, OutOfRangePolicy::Error)
+            .expect_err("out of range");
+
+        assert!(matches!(err, CommandError::Workspace(_)));
+        assert_eq!(
+            err.to_string(),
+            "workspace 13 is outside the paired range 1-10"
+        );
+        assert!(hyprctl.calls().is_empty());
+    }
+
     #[test]
     fn moves_window_and_switches_pair() {
-        let runner = ScriptedRunner::new(12, "[]");
-        let hyprctl = Hyprctl::new(runner.clone());
+        let hyprctl = scripted(12, "[]");
 
         paired_move_window(&hyprctl, &config(), 2).expect("move");
 
-        let calls = runner.calls.borrow();
+        let calls = hyprctl.calls();
         assert!(calls.iter().any(|call| {
             call == &vec![
                 "dispatch".to_string(),
@@ -156,16 +964,79 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn sends_focused_window_to_the_companion_workspace() {
+        let clients_json = r#"[{"address":"0x1","workspace":{"id":3},"focusHistoryID":1},{"address":"0x2","workspace":{"id":3},"focusHistoryID":0}]"#;
+        let hyprctl = scripted(3, clients_json);
+
+        let sent = paired_send_to(&hyprctl, &config(), SendTarget::Other).expect("send");
+
+        assert_eq!(sent, Some(13));
+        assert_eq!(
+            hyprctl.calls().last(),
+            Some(&vec![
+                "--batch".to_string(),
+                "dispatch movetoworkspacesilent 13,address:0x2".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn focuses_a_window_after_switching_to_its_pair() {
+        let clients_json =
+            r#"[{"address":"0x1","workspace":{"id":2}},{"address":"0x2","workspace":{"id":13}}]"#;
+        let hyprctl = scripted(2, clients_json);
+
+        let workspace = paired_focus_window(&hyprctl, &config(), "0x2").expect("focus");
+
+        assert_eq!(workspace, 13);
+        let calls = hyprctl.calls();
+        let switched = calls
+            .iter()
+            .position(|call| call[0] == "--batch" && call[1].contains("workspace 3"))
+            .expect("pair switched");
+        assert_eq!(
+            calls[switched + 1..],
+            [vec![
+                "dispatch".to_string(),
+                "focuswindow".to_string(),
+                "address:0x2".to_string(),
+            ]]
+        );
+        assert!(matches!(
+            paired_focus_window(&hyprctl, &config(), "0x9"),
+            Err(CommandError::UnknownWindow(address)) if address == "0x9"
+        ));
+    }
+
+    #[test]
+    fn send_to_own_monitor_is_a_no_op() {
+        let clients_json = r#"[{"address":"0x2","workspace":{"id":13},"focusHistoryID":0}]"#;
+        let hyprctl = scripted(13, clients_json);
+
+        let sent = paired_send_to(&hyprctl, &config(), SendTarget::Secondary).expect("send");
+
+        assert_eq!(sent, None);
+        assert_eq!(
+            paired_send_to(&hyprctl, &config(), SendTarget::Primary).expect("send"),
+            Some(3)
+        );
+        let empty = scripted(1, "[]");
+        assert!(matches!(
+            paired_send_to(&empty, &config(), SendTarget::Other),
+            Err(CommandError::NoActiveWindow)
+        ));
+    }
+
     #[test]
     fn migrates_windows_from_secondary() {
         let clients_json = r#"[{"address":"0x123","workspace":{"id":12}},{"address":"0x456","workspace":{"id":1}}]"#;
-        let runner = ScriptedRunner::new(1, clients_json);
-        let hyprctl = Hyprctl::new(runner.clone());
+        let hyprctl = scripted(1, clients_json);
 
         let migrated = migrate_windows(&hyprctl, &config()).expect("migrate");
 
         assert_eq!(migrated, 1);
-        let calls = runner.calls.borrow();
+        let calls = hyprctl.calls();
         assert!(calls.iter().any(|call| {
             call == &vec![
                 "dispatch".to_string(),
@@ -175,16 +1046,85 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn filters_migration_by_class() {
+        let clients_json = r#"[
+            {"address":"0x1","workspace":{"id":12},"class":"kitty","title":"shell"},
+            {"address":"0x2","workspace":{"id":13},"class":"firefox"},
+            {"address":"0x3","workspace":{"id":14},"initialClass":"mpv"}
+        ]"#;
+        let hyprctl = scripted(1, clients_json);
+        let filter = MigrationFilter {
+            only: vec!["kitty".to_string(), "mpv".to_string()],
+            except: vec!["mpv".to_string()],
+        };
+
+        let preview = preview_migration(&hyprctl, &config(), &filter).expect("preview");
+
+        assert_eq!(
+            preview,
+            vec![MigrationTarget {
+                address: "0x1".to_string(),
+                class: Some("kitty".to_string()),
+                title: Some("shell".to_string()),
+                from: 12,
+                to: 2,
+            }]
+        );
+        assert!(
+            hyprctl
+                .calls()
+                .iter()
+                .all(|call| call.first().map(String::as_str) == Some("-j"))
+        );
+
+        let migrated = migrate_windows_filtered(&hyprctl, &config(), &filter).expect("migrate");
+
+        assert_eq!(migrated, 1);
+        assert!(hyprctl.called(&["dispatch", "movetoworkspacesilent", "2,address:0x1"]));
+        assert!(!hyprctl.called(&["dispatch", "movetoworkspacesilent", "4,address:0x3"]));
+    }
+
+    #[test]
+    fn keeps_migrating_after_a_failed_move() {
+        let clients_json = r#"[
+            {"address":"0x1","workspace":{"id":12}},
+            {"address":"0x2","workspace":{"id":13}}
+        ]"#;
+        let hyprctl = scripted(1, clients_json);
+        let targets =
+            preview_migration(&hyprctl, &config(), &MigrationFilter::default()).expect("preview");
+        hyprctl.fail(
+            "dispatch",
+            HyprctlError::CommandFailed {
+                command: "hyprctl dispatch".to_string(),
+                status: 1,
+                stderr: "no such window".to_string(),
+            },
+        );
+        let mut outcomes = Vec::new();
+
+        let moved = migrate_targets(&hyprctl, &targets, |target, result| {
+            outcomes.push((target.address.clone(), result.is_ok()));
+        });
+
+        assert_eq!(moved, 1);
+        assert_eq!(
+            outcomes,
+            vec![("0x1".to_string(), false), ("0x2".to_string(), true)]
+        );
+        assert!(hyprctl.called(&["dispatch", "movetoworkspacesilent", "3,address:0x2"]));
+    }
+
     #[test]
     fn grabs_rogue_windows_from_secondary_range() {
         let clients_json = r#"[{"address":"0x123","workspace":{"id":12}},{"address":"0x456","workspace":{"id":1}}]"#;
-        let runner = ScriptedRunner::new(1, clients_json);
-        let hyprctl = Hyprctl::new(runner.clone());
+        let hyprctl = scripted(1, clients_json);
 
         let migrated = grab_rogue_windows(&hyprctl, &config()).expect("grab");
 
         assert_eq!(migrated, 1);
-        let calls = runner.calls.borrow();
+        let calls = hyprctl.calls();
         assert!(calls.iter().any(|call| {
             call == &vec![
                 "dispatch".to_string(),
@@ -193,4 +1133,124 @@ mod tests {
             ]
         }));
     }
+
+    #[test]
+    fn range_target_leaves_paired_workspaces_alone() {
+        let config = config();
+        assert_eq!(range_target(&config, 1), None);
+        assert_eq!(range_target(&config, 20), None);
+        assert_eq!(range_target(&config, 23), Some(3));
+        assert_eq!(range_target(&config, 40), Some(10));
+
+        let mut trio = config;
+        trio.layout = vec![
+            LayoutMonitor::Primary,
+            LayoutMonitor::Secondary,
+            LayoutMonitor::Secondary,
+        ];
+        assert_eq!(range_target(&trio, 23), None);
+        assert_eq!(range_target(&trio, 33), Some(3));
+    }
+
+    #[test]
+    fn clones_slot_classes_from_launch_table() {
+        let clients_json = r#"[{"address":"0x1","workspace":{"id":2},"class":"kitty"},
+            {"address":"0x2","workspace":{"id":12},"class":"firefox"},
+            {"address":"0x3","workspace":{"id":2},"class":"obsidian"},
+            {"address":"0x4","workspace":{"id":3},"class":"kitty"}]"#;
+        let hyprctl = scripted(1, clients_json);
+        let mut config = config();
+        config
+            .launch
+            .insert("kitty".to_string(), "kitty".to_string());
+        config
+            .launch
+            .insert("firefox".to_string(), "firefox --new-window".to_string());
+
+        let report = paired_clone(&hyprctl, &config, 2, 5).expect("clone");
+
+        assert_eq!(
+            report,
+            CloneReport {
+                launched: 2,
+                skipped: vec!["obsidian".to_string()],
+            }
+        );
+        let calls = hyprctl.calls();
+        let execs: Vec<&str> = calls
+            .iter()
+            .filter(|call| call.get(1).map(String::as_str) == Some("exec"))
+            .map(|call| call[2].as_str())
+            .collect();
+        assert_eq!(
+            execs,
+            vec![
+                "[workspace 5 silent] kitty",
+                "[workspace 15 silent] firefox --new-window"
+            ]
+        );
+    }
+
+    #[test]
+    fn clone_rejects_slots_outside_range() {
+        let hyprctl = scripted(1, "[]");
+
+        let err = paired_clone(&hyprctl, &config(), 2, 11).expect_err("range");
+
+        assert!(matches!(err, CommandError::Workspace(_)));
+        assert!(hyprctl.calls().is_empty());
+    }
+
+    #[test]
+    fn derives_migration_targets_for_secondary_workspaces() {
+        let clients = vec![
+            ClientInfo {
+                address: "0x123".to_string(),
+                workspace: WorkspaceRef { id: 12, name: None },
+                class: None,
+                title: None,
+                initial_class: None,
+                initial_title: None,
+                app_id: None,
+                pid: None,
+                focus_history_id: None,
+                floating: false,
+                at: None,
+                size: None,
+                fullscreen: 0,
+                fullscreen_client: 0,
+                pseudo: false,
+            },
+            ClientInfo {
+                address: "0x456".to_string(),
+                workspace: WorkspaceRef { id: 1, name: None },
+                class: None,
+                title: None,
+                initial_class: None,
+                initial_title: None,
+                app_id: None,
+                pid: None,
+                focus_history_id: None,
+                floating: false,
+                at: None,
+                size: None,
+                fullscreen: 0,
+                fullscreen_client: 0,
+                pseudo: false,
+            },
+        ];
+
+        let targets = migration_targets(&clients, 10, 1);
+
+        assert_eq!(
+            targets,
+            vec![MigrationTarget {
+                address: "0x123".to_string(),
+                class: None,
+                title: None,
+                from: 12,
+                to: 2,
+            }]
+        );
+    }
 }
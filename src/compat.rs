@@ -0,0 +1,446 @@
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+use crate::hyprctl::{
+    ClientInfo, Compositor, HyprctlError, HyprctlRunner, HyprlandIpc, MonitorInfo, OptionInfo,
+    WorkspaceInfo, WorkspaceRef,
+};
+
+pub struct SwayIpc<R> {
+    runner: R,
+}
+
+impl<R> SwayIpc<R> {
+    pub fn new(runner: R) -> Self {
+        Self { runner }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SwayWorkspace {
+    num: i32,
+    #[serde(default)]
+    focused: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct SwayOutput {
+    name: String,
+    #[serde(default = "default_active")]
+    active: bool,
+    rect: SwayRect,
+}
+
+#[derive(Debug, Deserialize)]
+struct SwayRect {
+    x: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct SwayNode {
+    id: u64,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    num: Option<i32>,
+    #[serde(default)]
+    app_id: Option<String>,
+    #[serde(default)]
+    pid: Option<i32>,
+    #[serde(default)]
+    window_properties: Option<SwayWindowProperties>,
+    #[serde(default)]
+    nodes: Vec<SwayNode>,
+    #[serde(default)]
+    floating_nodes: Vec<SwayNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SwayWindowProperties {
+    #[serde(default)]
+    class: Option<String>,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+#[derive(Debug, Default)]
+struct SwayTree {
+    workspaces: Vec<WorkspaceInfo>,
+    clients: Vec<ClientInfo>,
+}
+
+impl SwayTree {
+    fn collect(node: SwayNode) -> Self {
+        let mut tree = Self::default();
+        tree.visit(node, None, None);
+        tree
+    }
+
+    fn visit(&mut self, node: SwayNode, output: Option<&str>, workspace: Option<usize>) {
+        let SwayNode {
+            id,
+            kind,
+            name,
+            num,
+            app_id,
+            pid,
+            window_properties,
+            nodes,
+            floating_nodes,
+        } = node;
+        let output_name = (kind == "output").then(|| name.clone()).flatten();
+        let output = output_name.as_deref().or(output);
+        let mut workspace = workspace;
+        match kind.as_str() {
+            "workspace" => {
+                let Some(id) = num.and_then(|num| u32::try_from(num).ok()) else {
+                    return;
+                };
+                self.workspaces.push(WorkspaceInfo {
+                    id,
+                    windows: 0,
+                    name,
+                    monitor: output.map(str::to_string),
+                });
+                workspace = Some(self.workspaces.len() - 1);
+            }
+            "con" | "floating_con" if nodes.is_empty() && floating_nodes.is_empty() => {
+                let Some(index) = workspace else {
+                    return;
+                };
+                if app_id.is_none() && window_properties.is_none() {
+                    return;
+                }
+                let info = &mut self.workspaces[index];
+                info.windows += 1;
+                let class = app_id
+                    .clone()
+                    .or_else(|| window_properties.and_then(|properties| properties.class));
+                self.clients.push(ClientInfo {
+                    address: format!("0x{id:x}"),
+                    workspace: WorkspaceRef {
+                        id: info.id,
+                        name: info.name.clone(),
+                    },
+                    initial_class: class.clone(),
+                    class,
+                    initial_title: name.clone(),
+                    title: name,
+                    app_id,
+                    pid,
+                    focus_history_id: None,
+                    floating: false,
+                    at: None,
+                    size: None,
+                    fullscreen: 0,
+                    fullscreen_client: 0,
+                    pseudo: false,
+                });
+                return;
+            }
+            _ => {}
+        }
+        for child in nodes.into_iter().chain(floating_nodes) {
+            self.visit(child, output, workspace);
+        }
+    }
+}
+
+pub fn translate_batch(batch: &str) -> Result<Vec<String>, HyprctlError> {
+    let mut commands = Vec::new();
+    for command in batch.split(';') {
+        let command = command.trim();
+        if command.is_empty() {
+            continue;
+        }
+        let mut parts = command.splitn(3, ' ');
+        if parts.next() != Some("dispatch") {
+            return Err(HyprctlError::Unsupported(command.to_string()));
+        }
+        let dispatcher = parts
+            .next()
+            .ok_or_else(|| HyprctlError::Unsupported(command.to_string()))?;
+        commands.extend(translate_dispatch(dispatcher, parts.next().unwrap_or(""))?);
+    }
+    Ok(commands)
+}
+
+pub fn translate_dispatch(dispatcher: &str, argument: &str) -> Result<Vec<String>, HyprctlError> {
+    let argument = argument.trim();
+    let unsupported = || HyprctlError::Unsupported(format!("dispatch {dispatcher} {argument}"));
+    let workspace = |value: &str| {
+        value
+            .parse::<u32>()
+            .map_err(|_| unsupported())
+            .map(|id| format!("workspace number {id}"))
+    };
+    Ok(match dispatcher {
+        "focusmonitor" => vec![format!("focus output {argument}")],
+        "workspace" => vec![workspace(argument)?],
+        "moveworkspacetomonitor" => {
+            let (id, output) = argument.split_once(' ').ok_or_else(unsupported)?;
+            vec![workspace(id)?, format!("move workspace to output {output}")]
+        }
+        "movetoworkspace" | "movetoworkspacesilent" => {
+            let (id, window) = match argument.split_once(',') {
+                Some((id, window)) => (id, Some(window)),
+                None => (argument, None),
+            };
+            let criteria = match window {
+                Some(window) => format!("{} ", con_criteria(window).ok_or_else(unsupported)?),
+                None => String::new(),
+            };
+            let target = workspace(id)?;
+            let mut commands = vec![format!("{criteria}move container to {target}")];
+            if dispatcher == "movetoworkspace" {
+                commands.push(target);
+            }
+            commands
+        }
+        "focuswindow" => vec![format!(
+            "{} focus",
+            con_criteria(argument).ok_or_else(unsupported)?
+        )],
+        "exec" => {
+            let command = match argument.strip_prefix('[') {
+                Some(rest) => rest
+                    .split_once(']')
+                    .map_or(argument, |(_, command)| command),
+                None => argument,
+            };
+            vec![format!("exec {}", command.trim())]
+        }
+        _ => return Err(unsupported()),
+    })
+}
+
+fn con_criteria(window: &str) -> Option<String> {
+    let address = window.strip_prefix("address:")?;
+    let id = u64::from_str_radix(address.trim_start_matches("0x"), 16).ok()?;
+    Some(format!("[con_id={id}]"))
+}
+
+impl<R: HyprctlRunner> SwayIpc<R> {
+    fn query<T: DeserializeOwned>(&self, kind: &str) -> Result<T, HyprctlError> {
+        let args = vec!["-t".to_string(), kind.to_string(), "-r".to_string()];
+        let output = self.runner.run(&args)?;
+        serde_json::from_str(&output).map_err(|source| HyprctlError::Json {
+            command: kind.to_string(),
+            source,
+        })
+    }
+
+    fn tree(&self) -> Result<SwayTree, HyprctlError> {
+        Ok(SwayTree::collect(self.query("get_tree")?))
+    }
+
+    fn run_commands(&self, commands: &[String]) -> Result<String, HyprctlError> {
+        if commands.is_empty() {
+            return Ok("ok".to_string());
+        }
+        self.runner.run(&[commands.join("; ")])
+    }
+}
+
+impl<R: HyprctlRunner> HyprlandIpc for SwayIpc<R> {
+    fn batch(&self, batch: &str) -> Result<String, HyprctlError> {
+        let mut commands = translate_batch(batch)?;
+        if batch.contains("moveworkspacetomonitor") {
+            let active = self.active_workspace_id()?;
+            commands.push(format!("workspace number {active}"));
+        }
+        self.run_commands(&commands)
+    }
+
+    fn active_workspace_id(&self) -> Result<u32, HyprctlError> {
+        let workspaces: Vec<SwayWorkspace> = self.query("get_workspaces")?;
+        workspaces
+            .into_iter()
+            .find(|workspace| workspace.focused)
+            .and_then(|workspace| u32::try_from(workspace.num).ok())
+            .ok_or_else(|| HyprctlError::Unsupported("focused named workspace".to_string()))
+    }
+
+    fn dispatch(&self, dispatcher: &str, argument: &str) -> Result<String, HyprctlError> {
+        self.run_commands(&translate_dispatch(dispatcher, argument)?)
+    }
+
+    fn reload(&self) -> Result<String, HyprctlError> {
+        self.run_commands(&["reload".to_string()])
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, HyprctlError> {
+        let outputs: Vec<SwayOutput> = self.query("get_outputs")?;
+        Ok(outputs
+            .into_iter()
+            .filter(|output| output.active)
+            .zip(0..)
+            .map(|(output, id)| MonitorInfo {
+                name: output.name,
+                x: output.rect.x,
+                id,
+                mirror_of: None,
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, HyprctlError> {
+        Ok(self.tree()?.workspaces)
+    }
+
+    fn clients(&self) -> Result<Vec<ClientInfo>, HyprctlError> {
+        Ok(self.tree()?.clients)
+    }
+
+    fn get_option(&self, name: &str) -> Result<OptionInfo, HyprctlError> {
+        Err(HyprctlError::Unsupported(format!("getoption {name}")))
+    }
+
+    fn keyword(&self, name: &str, value: &str) -> Result<String, HyprctlError> {
+        Err(HyprctlError::Unsupported(format!("keyword {name} {value}")))
+    }
+
+    fn compositor(&self) -> Compositor {
+        Compositor::Sway
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        self.runner.set_timeout(timeout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SwayIpc, translate_batch, translate_dispatch};
+    use crate::hyprctl::{HyprlandIpc, paired_switch_batch, rebalance_batch};
+    use crate::testing::ScriptedIpc;
+
+    const TREE: &str = r#"{"id":1,"type":"root","nodes":[
+        {"id":2,"type":"output","name":"__i3","nodes":[
+            {"id":3,"type":"workspace","name":"__i3_scratch","num":-1,"nodes":[]}]},
+        {"id":4,"type":"output","name":"DP-1","nodes":[
+            {"id":5,"type":"workspace","name":"1","num":1,"nodes":[
+                {"id":10,"type":"con","name":"","nodes":[
+                    {"id":26,"type":"con","name":"vim","app_id":"foot","pid":42,"nodes":[]},
+                    {"id":27,"type":"con","name":"Firefox","app_id":null,
+                     "window_properties":{"class":"firefox"},"nodes":[]}]}],
+             "floating_nodes":[]}]},
+        {"id":6,"type":"output","name":"HDMI-A-1","nodes":[
+            {"id":7,"type":"workspace","name":"11: chat","num":11,"nodes":[],
+             "floating_nodes":[
+                {"id":30,"type":"floating_con","name":"Signal","app_id":"signal","nodes":[]}]}]}]}"#;
+
+    const WORKSPACES: &str = r#"[{"num":1,"focused":false},{"num":11,"focused":true}]"#;
+
+    const OUTPUTS: &str = r#"[
+        {"name":"HDMI-A-1","active":true,"rect":{"x":1920}},
+        {"name":"DP-2","active":false,"rect":{"x":0}},
+        {"name":"DP-1","active":true,"rect":{"x":0}}]"#;
+
+    fn sway() -> ScriptedIpc {
+        ScriptedIpc::new()
+            .with_json("get_tree", TREE)
+            .with_json("get_workspaces", WORKSPACES)
+            .with_json("get_outputs", OUTPUTS)
+    }
+
+    #[test]
+    fn translates_paired_switch_and_rebalance() {
+        assert_eq!(
+            translate_batch(&paired_switch_batch("DP-1", "HDMI-A-1", 3, 10)).expect("switch"),
+            vec![
+                "focus output HDMI-A-1",
+                "workspace number 13",
+                "focus output DP-1",
+                "workspace number 3",
+            ]
+        );
+        assert_eq!(
+            translate_batch(&rebalance_batch("DP-1", "HDMI-A-1", 1)).expect("rebalance"),
+            vec![
+                "workspace number 1",
+                "move workspace to output DP-1",
+                "workspace number 2",
+                "move workspace to output HDMI-A-1",
+            ]
+        );
+    }
+
+    #[test]
+    fn translates_window_moves_and_exec() {
+        assert_eq!(
+            translate_dispatch("movetoworkspacesilent", "12,address:0x1a").expect("move"),
+            vec!["[con_id=26] move container to workspace number 12"]
+        );
+        assert_eq!(
+            translate_dispatch("movetoworkspace", "4").expect("move"),
+            vec!["move container to workspace number 4", "workspace number 4"]
+        );
+        assert_eq!(
+            translate_dispatch("exec", "[workspace 3 silent] foot").expect("exec"),
+            vec!["exec foot"]
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_commands() {
+        let err = translate_batch("dispatch togglespecialworkspace").expect_err("unsupported");
+        assert_eq!(
+            err.to_string(),
+            "unsupported by this compositor: dispatch togglespecialworkspace "
+        );
+        assert!(translate_batch("keyword animations:enabled 0").is_err());
+        assert!(translate_dispatch("workspace", "e+1").is_err());
+    }
+
+    #[test]
+    fn reads_workspaces_clients_and_outputs_from_sway() {
+        let ipc = SwayIpc::new(sway());
+
+        let workspaces = ipc.workspaces().expect("workspaces");
+        assert_eq!(workspaces.len(), 2);
+        assert_eq!(workspaces[0].id, 1);
+        assert_eq!(workspaces[0].windows, 2);
+        assert_eq!(workspaces[0].monitor.as_deref(), Some("DP-1"));
+        assert_eq!(workspaces[1].id, 11);
+        assert_eq!(workspaces[1].name.as_deref(), Some("11: chat"));
+
+        let clients = ipc.clients().expect("clients");
+        assert_eq!(clients.len(), 3);
+        assert_eq!(clients[0].address, "0x1a");
+        assert_eq!(clients[0].class.as_deref(), Some("foot"));
+        assert_eq!(clients[1].class.as_deref(), Some("firefox"));
+        assert_eq!(clients[2].workspace.id, 11);
+
+        let monitors = ipc.monitors().expect("monitors");
+        let names = monitors
+            .iter()
+            .map(|monitor| monitor.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["HDMI-A-1", "DP-1"]);
+        assert_eq!(ipc.active_workspace_id().expect("active"), 11);
+    }
+
+    #[test]
+    fn rebalance_restores_the_focused_workspace() {
+        let runner = sway();
+        let ipc = SwayIpc::new(runner.clone());
+
+        ipc.batch(&rebalance_batch("DP-1", "HDMI-A-1", 1))
+            .expect("rebalance");
+
+        assert_eq!(
+            runner.calls().last().expect("command"),
+            &vec![
+                "workspace number 1; move workspace to output DP-1; workspace number 2; move workspace to output HDMI-A-1; workspace number 11"
+                    .to_string()
+            ]
+        );
+    }
+}
@@ -1,29 +1,394 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
+use std::time::Duration;
 
+use crate::hyprctl::MonitorInfo;
+use crate::layout::LayoutMonitor;
+use crate::paired::normalize_workspace;
+use crate::schedule::Schedule;
+
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
 pub const DEFAULT_PAIRED_OFFSET: u32 = 10;
 pub const DEFAULT_WORKSPACE_COUNT: u32 = DEFAULT_PAIRED_OFFSET;
+pub const DEFAULT_WORKSPACE_BASE: u32 = 1;
 pub const DEFAULT_WRAP_CYCLING: bool = true;
+pub const DEFAULT_REBALANCE_ON_START: bool = true;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutOfRangePolicy {
+    #[default]
+    Normalize,
+    Raw,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RebalanceMode {
+    #[default]
+    Strict,
+    Preserve,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeSource {
+    #[default]
+    Css,
+    Wal,
+    Matugen,
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WaybarMode {
+    #[default]
+    Stream,
+    DaemonPush,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WaybarConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occupied_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub empty_format: Option<String>,
+    pub icons: BTreeMap<u32, String>,
+    pub tooltip_windows: bool,
+    pub mode: WaybarMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signal: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    pub attempts: u32,
+    pub backoff_ms: u64,
+    pub failure_threshold: u32,
+    pub cooldown_ms: u64,
+    /// Per-request limit for Hyprland IPC; `0` waits indefinitely.
+    pub timeout_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff_ms: 50,
+            failure_threshold: 5,
+            cooldown_ms: 5_000,
+            timeout_ms: 2_000,
+        }
+    }
+}
+
+impl RetryConfig {
+    pub fn ipc_timeout(&self) -> Option<Duration> {
+        (self.timeout_ms > 0).then(|| Duration::from_millis(self.timeout_ms))
+    }
+}
+
+/// Upper bound for either debounce window, in milliseconds.
+pub const MAX_DEBOUNCE_MS: u64 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DebounceMode {
+    #[default]
+    Fixed,
+    Adaptive,
+}
+
+/// Quiet windows for rebalancing after monitor events and for repeat focus switches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DebounceConfig {
+    pub rebalance_ms: u64,
+    pub focus_ms: u64,
+    pub mode: DebounceMode,
+    pub max_rebalance_ms: u64,
+}
+
+impl Default for DebounceConfig {
+    fn default() -> Self {
+        Self {
+            rebalance_ms: crate::daemon::DEFAULT_REBALANCE_DEBOUNCE.as_millis() as u64,
+            focus_ms: crate::daemon::DEFAULT_FOCUS_SWITCH_DEBOUNCE.as_millis() as u64,
+            mode: DebounceMode::default(),
+            max_rebalance_ms: crate::daemon::DEFAULT_ADAPTIVE_DEBOUNCE_CAP.as_millis() as u64,
+        }
+    }
+}
+
+impl DebounceConfig {
+    pub fn rebalance(&self) -> Duration {
+        Duration::from_millis(self.rebalance_ms)
+    }
+
+    pub fn focus(&self) -> Duration {
+        Duration::from_millis(self.focus_ms)
+    }
+
+    pub fn max_rebalance(&self) -> Duration {
+        Duration::from_millis(self.max_rebalance_ms)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconcilePolicy {
+    #[default]
+    Off,
+    Report,
+    Fix,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ReconcileConfig {
+    pub policy: ReconcilePolicy,
+    pub interval_ms: u64,
+}
+
+impl Default for ReconcileConfig {
+    fn default() -> Self {
+        Self {
+            policy: ReconcilePolicy::Off,
+            interval_ms: 30_000,
+        }
+    }
+}
+
+/// Cold-restore options; `launch_command` maps a window class to its command.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RestoreConfig {
+    pub launch_missing: bool,
+    pub launch_command: BTreeMap<String, String>,
+    pub allow_degraded: bool,
+}
+
+/// How to pick a monitor the config leaves out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoPrimary {
+    #[default]
+    Leftmost,
+    /// Most pixels; ties go to the leftmost.
+    Largest,
+    /// The focused monitor, else the leftmost.
+    Focused,
+}
+
+impl AutoPrimary {
+    /// The best monitor other than `taken`.
+    pub fn pick<'a>(self, monitors: &'a [MonitorInfo], taken: &str) -> Option<&'a MonitorInfo> {
+        let candidates = || monitors.iter().filter(|monitor| monitor.name != taken);
+        let leftmost = || candidates().min_by_key(|monitor| (monitor.x, monitor.y));
+        match self {
+            AutoPrimary::Leftmost => leftmost(),
+            AutoPrimary::Largest => candidates().min_by_key(|monitor| {
+                let pixels = u64::from(monitor.width) * u64::from(monitor.height);
+                (std::cmp::Reverse(pixels), monitor.x, monitor.y)
+            }),
+            AutoPrimary::Focused => candidates()
+                .find(|monitor| monitor.focused)
+                .or_else(leftmost),
+        }
+    }
+}
+
+/// Picks a monitor by something steadier than its connector name.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorMatch {
+    /// Substring of the monitor's description, e.g. `LG UltraGear`.
+    Description(String),
+    /// The monitor's EDID serial.
+    Serial(String),
+    /// Left out of the config; chosen by `auto_primary`.
+    Auto,
+}
+
+impl MonitorMatch {
+    pub fn matches(&self, monitor: &MonitorInfo) -> bool {
+        match self {
+            MonitorMatch::Description(needle) => monitor
+                .description
+                .as_deref()
+                .is_some_and(|description| description.contains(needle.as_str())),
+            MonitorMatch::Serial(serial) => monitor.serial.as_deref() == Some(serial.as_str()),
+            MonitorMatch::Auto => false,
+        }
+    }
+}
+
+impl std::fmt::Display for MonitorMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MonitorMatch::Description(description) => write!(f, "description '{description}'"),
+            MonitorMatch::Serial(serial) => write!(f, "serial '{serial}'"),
+            MonitorMatch::Auto => f.write_str("auto"),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MonitorSpec {
+    Name(String),
+    Match(MonitorMatch),
+}
+
+impl MonitorSpec {
+    fn split(spec: Option<Self>) -> (Option<String>, Option<MonitorMatch>) {
+        match spec {
+            Some(MonitorSpec::Name(name)) => (Some(name), None),
+            Some(MonitorSpec::Match(selector)) => (Some(String::new()), Some(selector)),
+            None => (Some(String::new()), Some(MonitorMatch::Auto)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MonitorProfile {
+    pub monitors: Vec<String>,
+    pub primary_monitor: String,
+    pub secondary_monitor: String,
+}
+
+impl MonitorProfile {
+    pub fn matches(&self, connected: &[String]) -> bool {
+        let mut expected = self.monitors.clone();
+        let mut actual = connected.to_vec();
+        expected.sort();
+        expected.dedup();
+        actual.sort();
+        actual.dedup();
+        expected == actual
+    }
+}
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Config {
+    /// Connector name; empty while `primary_match` is unresolved.
     pub primary_monitor: String,
     pub secondary_monitor: String,
+    /// Set unless the config names the monitor's connector.
+    #[serde(skip)]
+    pub primary_match: Option<MonitorMatch>,
+    #[serde(skip)]
+    pub secondary_match: Option<MonitorMatch>,
+    pub auto_primary: AutoPrimary,
     pub paired_offset: u32,
     pub workspace_count: u32,
+    /// Workspace id of the primary monitor's first slot.
+    pub workspace_base: u32,
     pub wrap_cycling: bool,
+    pub cycle_skip_empty: bool,
+    pub out_of_range: OutOfRangePolicy,
+    pub profiles: Vec<MonitorProfile>,
+    pub rebalance_on_start: bool,
+    pub hotplug_settle_ms: u64,
+    pub drag_focus_delay_ms: u64,
+    pub schedules: Vec<Schedule>,
+    pub rebalance_mode: RebalanceMode,
+    pub idle_inhibit_slots: Vec<u32>,
+    pub launch: BTreeMap<String, String>,
+    pub names: BTreeMap<u32, String>,
+    pub theme_source: ThemeSource,
+    pub waybar: WaybarConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics_address: Option<String>,
+    pub retry: RetryConfig,
+    pub fast_switch: bool,
+    /// Pull windows that land outside the paired range back onto their slot.
+    pub enforce_range: bool,
+    /// Switch to the pair of any window that asks for attention.
+    pub jump_to_urgent: bool,
+    /// Warp the pointer to the monitor a paired switch leaves focused.
+    pub cursor_follows_focus: bool,
+    /// Turn animations off while a rebalance batch runs.
+    pub instant_rebalance: bool,
+    pub debounce: DebounceConfig,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub layout: Vec<LayoutMonitor>,
+    pub sticky_classes: Vec<String>,
+    pub reconcile: ReconcileConfig,
+    pub restore: RestoreConfig,
 }
 
 #[derive(Debug, Deserialize)]
 struct RawConfig {
-    primary_monitor: Option<String>,
-    secondary_monitor: Option<String>,
+    primary_monitor: Option<MonitorSpec>,
+    secondary_monitor: Option<MonitorSpec>,
     #[serde(default = "default_offset")]
     paired_offset: u32,
     #[serde(default)]
     workspace_count: Option<u32>,
+    #[serde(default = "default_workspace_base")]
+    workspace_base: u32,
     #[serde(default = "default_wrap_cycling")]
     wrap_cycling: bool,
+    #[serde(default)]
+    cycle_skip_empty: bool,
+    #[serde(default)]
+    out_of_range: OutOfRangePolicy,
+    #[serde(default)]
+    profiles: Vec<MonitorProfile>,
+    #[serde(default = "default_rebalance_on_start")]
+    rebalance_on_start: bool,
+    #[serde(default)]
+    hotplug_settle_ms: u64,
+    #[serde(default)]
+    drag_focus_delay_ms: u64,
+    #[serde(default)]
+    schedules: Vec<Schedule>,
+    #[serde(default)]
+    rebalance_mode: RebalanceMode,
+    #[serde(default)]
+    idle_inhibit_slots: Vec<u32>,
+    #[serde(default)]
+    launch: BTreeMap<String, String>,
+    #[serde(default)]
+    names: BTreeMap<u32, String>,
+    #[serde(default)]
+    theme_source: ThemeSource,
+    #[serde(default)]
+    waybar: WaybarConfig,
+    #[serde(default)]
+    metrics_address: Option<String>,
+    #[serde(default)]
+    retry: RetryConfig,
+    #[serde(default)]
+    fast_switch: bool,
+    #[serde(default)]
+    enforce_range: bool,
+    #[serde(default)]
+    jump_to_urgent: bool,
+    #[serde(default)]
+    cursor_follows_focus: bool,
+    #[serde(default)]
+    instant_rebalance: bool,
+    #[serde(default)]
+    debounce: DebounceConfig,
+    #[serde(default)]
+    layout: Vec<LayoutMonitor>,
+    #[serde(default)]
+    sticky_classes: Vec<String>,
+    #[serde(default)]
+    reconcile: ReconcileConfig,
+    #[serde(default)]
+    restore: RestoreConfig,
+    #[serde(default)]
+    auto_primary: AutoPrimary,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -34,34 +399,809 @@ pub enum ConfigError {
     Io(#[from] std::io::Error),
     #[error("missing required field: {0}")]
     MissingField(&'static str),
+    #[error("invalid config: {}", .0.join("; "))]
+    Invalid(Vec<String>),
+    #[error("unknown config key: {0}")]
+    UnknownKey(String),
+}
+
+pub fn strip_json_comments(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+    let mut escaped = false;
+    while let Some(ch) = chars.next() {
+        if in_string {
+            output.push(ch);
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        if ch == '/' && chars.peek() == Some(&'/') {
+            for next in chars.by_ref() {
+                if next == '\n' {
+                    output.push('\n');
+                    break;
+                }
+            }
+            continue;
+        }
+        if ch == '"' {
+            in_string = true;
+        }
+        output.push(ch);
+    }
+    output
 }
 
 impl Config {
     pub fn from_json(input: &str) -> Result<Self, ConfigError> {
-        let raw: RawConfig = serde_json::from_str(input)?;
-        let primary_monitor = raw
-            .primary_monitor
-            .filter(|value| !value.is_empty())
+        let raw: RawConfig = serde_json::from_str(&strip_json_comments(input))?;
+        let (primary_monitor, primary_match) = MonitorSpec::split(raw.primary_monitor);
+        let primary_monitor = primary_monitor
+            .filter(|value| !value.is_empty() || primary_match.is_some())
             .ok_or(ConfigError::MissingField("primary_monitor"))?;
-        let secondary_monitor = raw
-            .secondary_monitor
-            .filter(|value| !value.is_empty())
+        let (secondary_monitor, secondary_match) = MonitorSpec::split(raw.secondary_monitor);
+        let secondary_monitor = secondary_monitor
+            .filter(|value| !value.is_empty() || secondary_match.is_some())
             .ok_or(ConfigError::MissingField("secondary_monitor"))?;
         let workspace_count = raw.workspace_count.unwrap_or(raw.paired_offset);
 
-        Ok(Self {
+        let config = Self {
             primary_monitor,
             secondary_monitor,
+            primary_match,
+            secondary_match,
+            auto_primary: raw.auto_primary,
             paired_offset: workspace_count,
             workspace_count,
+            workspace_base: raw.workspace_base,
             wrap_cycling: raw.wrap_cycling,
-        })
+            cycle_skip_empty: raw.cycle_skip_empty,
+            out_of_range: raw.out_of_range,
+            profiles: raw.profiles,
+            rebalance_on_start: raw.rebalance_on_start,
+            hotplug_settle_ms: raw.hotplug_settle_ms,
+            drag_focus_delay_ms: raw.drag_focus_delay_ms,
+            schedules: raw.schedules,
+            rebalance_mode: raw.rebalance_mode,
+            idle_inhibit_slots: raw.idle_inhibit_slots,
+            launch: raw.launch,
+            names: raw.names,
+            theme_source: raw.theme_source,
+            waybar: raw.waybar,
+            metrics_address: raw.metrics_address,
+            retry: raw.retry,
+            fast_switch: raw.fast_switch,
+            enforce_range: raw.enforce_range,
+            jump_to_urgent: raw.jump_to_urgent,
+            cursor_follows_focus: raw.cursor_follows_focus,
+            instant_rebalance: raw.instant_rebalance,
+            debounce: raw.debounce,
+            layout: raw.layout,
+            sticky_classes: raw.sticky_classes,
+            reconcile: raw.reconcile,
+            restore: raw.restore,
+        };
+        let errors = crate::validate::monitor_name_errors(&config);
+        if !errors.is_empty() {
+            return Err(ConfigError::Invalid(errors));
+        }
+        Ok(config)
+    }
+
+    pub fn for_monitors(&self, connected: &[String]) -> Option<Config> {
+        if connected.len() <= 1 {
+            return None;
+        }
+        let mut config = self.clone();
+        if let Some(profile) = self
+            .profiles
+            .iter()
+            .find(|profile| profile.matches(connected))
+        {
+            config.primary_monitor = profile.primary_monitor.clone();
+            config.secondary_monitor = profile.secondary_monitor.clone();
+            config.primary_match = None;
+            config.secondary_match = None;
+        }
+        Some(config)
+    }
+
+    /// Resolves both monitor matches; returns whether either name changed.
+    pub fn resolve_monitors(&mut self, monitors: &[MonitorInfo]) -> bool {
+        let before = (self.primary_monitor.clone(), self.secondary_monitor.clone());
+        for (selector, name) in [
+            (&self.primary_match, &mut self.primary_monitor),
+            (&self.secondary_match, &mut self.secondary_monitor),
+        ] {
+            if let Some(selector) = selector {
+                *name = monitors
+                    .iter()
+                    .find(|monitor| selector.matches(monitor))
+                    .map(|monitor| monitor.name.clone())
+                    .unwrap_or_default();
+            }
+        }
+        if self.primary_match == Some(MonitorMatch::Auto) {
+            self.primary_monitor = self.pick_auto(monitors, &self.secondary_monitor);
+        }
+        if self.secondary_match == Some(MonitorMatch::Auto) {
+            self.secondary_monitor = self.pick_auto(monitors, &self.primary_monitor);
+        }
+        before != (self.primary_monitor.clone(), self.secondary_monitor.clone())
+    }
+
+    fn pick_auto(&self, monitors: &[MonitorInfo], taken: &str) -> String {
+        self.auto_primary
+            .pick(monitors, taken)
+            .map(|monitor| monitor.name.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn has_monitor_matches(&self) -> bool {
+        self.primary_match.is_some() || self.secondary_match.is_some()
+    }
+
+    pub fn monitors_resolved(&self) -> bool {
+        !self.primary_monitor.is_empty() && !self.secondary_monitor.is_empty()
+    }
+
+    /// The 1-based slot a workspace id belongs to.
+    pub fn slot_of(&self, workspace: u32) -> u32 {
+        normalize_workspace(workspace, self.paired_offset, self.workspace_base)
+    }
+
+    /// The primary monitor's workspace id for `slot`.
+    pub fn slot_workspace(&self, slot: u32) -> u32 {
+        self.workspace_base + slot - 1
+    }
+
+    pub fn slot_name(&self, slot: u32) -> Option<&str> {
+        self.names.get(&slot).map(String::as_str)
+    }
+
+    pub fn slot_for_name(&self, name: &str) -> Option<u32> {
+        self.names
+            .iter()
+            .find(|(_, slot_name)| slot_name.as_str() == name)
+            .map(|(slot, _)| *slot)
     }
 
     pub fn from_path(path: &Path) -> Result<Self, ConfigError> {
         let contents = std::fs::read_to_string(path)?;
         Self::from_json(&contents)
     }
+
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.document().unwrap_or_default()).unwrap_or_default()
+    }
+
+    /// The serialized config, with matches in place of resolved names.
+    fn document(&self) -> Result<serde_json::Value, serde_json::Error> {
+        let mut document = serde_json::to_value(self)?;
+        for (field, selector) in [
+            ("primary_monitor", &self.primary_match),
+            ("secondary_monitor", &self.secondary_match),
+        ] {
+            match (selector, document.as_object_mut()) {
+                (Some(MonitorMatch::Auto), Some(object)) => {
+                    object.remove(field);
+                }
+                (Some(selector), _) => document[field] = serde_json::to_value(selector)?,
+                (None, _) => {}
+            }
+        }
+        Ok(document)
+    }
+
+    /// Writes back only the changed values, so comments and unknown keys survive.
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        let mut contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let contents = serde_json::to_string_pretty(&self.document()?)? + "\n";
+                return write_config(path, &contents);
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let current: serde_json::Value = serde_json::from_str(&strip_json_comments(&contents))?;
+        // Keys the file leaves out compare against what it loads as, so defaults stay implicit.
+        let mut effective = match Config::from_json(&contents) {
+            Ok(loaded) => loaded.document()?,
+            Err(_) => ConfigBuilder::default().config.document()?,
+        };
+        let mut update = self.document()?;
+        if current.get("workspace_count").is_some()
+            && let Some(update) = update.as_object_mut()
+        {
+            // Ignored next to workspace_count; leave whatever the file says.
+            update.remove("paired_offset");
+        }
+        merge_json(&mut effective, current);
+        let mut changes = Vec::new();
+        changed_values(Some(&effective), &update, &mut Vec::new(), &mut changes);
+        for (key, value) in changes {
+            let key: Vec<&str> = key.iter().map(String::as_str).collect();
+            contents = set_in_text(&contents, &key, &value)
+                .ok_or_else(|| ConfigError::UnknownKey(key.join(".")))?;
+        }
+        write_config(path, &contents)
+    }
+}
+
+/// Rewrites the value of dotted `key` in place, refusing unknown keys and invalid results.
+pub fn set_value(path: &Path, key: &str, value: &str) -> Result<Config, ConfigError> {
+    let contents = std::fs::read_to_string(path)?;
+    let document: serde_json::Value = serde_json::from_str(&strip_json_comments(&contents))?;
+    let pins_workspace_count = document.get("workspace_count").is_some();
+    let mut known = ConfigBuilder::default().config.document()?;
+    merge_json(&mut known, document);
+    let pointer = format!("/{}", key.replace('.', "/"));
+    if key.is_empty() || known.pointer(&pointer).is_none() {
+        return Err(ConfigError::UnknownKey(key.to_string()));
+    }
+    if key == "paired_offset" && pins_workspace_count {
+        return Err(ConfigError::Invalid(vec![
+            "paired_offset is ignored because workspace_count is set; set workspace_count instead"
+                .to_string(),
+        ]));
+    }
+
+    let value = serde_json::from_str(value)
+        .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+    let segments: Vec<&str> = key.split('.').collect();
+    let edited = set_in_text(&contents, &segments, &value)
+        .ok_or_else(|| ConfigError::UnknownKey(key.to_string()))?;
+
+    let config = Config::from_json(&edited)?;
+    let errors = crate::validate::config_errors(&config);
+    if !errors.is_empty() {
+        return Err(ConfigError::Invalid(errors));
+    }
+    write_config(path, &edited)?;
+    Ok(config)
+}
+
+fn write_config(path: &Path, contents: &str) -> Result<(), ConfigError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temp = path.with_extension("json.tmp");
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, path)?;
+    Ok(())
+}
+
+/// The leaf values in `update` that differ from `current`, keyed by path.
+fn changed_values(
+    current: Option<&serde_json::Value>,
+    update: &serde_json::Value,
+    key: &mut Vec<String>,
+    changes: &mut Vec<(Vec<String>, serde_json::Value)>,
+) {
+    match (current, update) {
+        (Some(serde_json::Value::Object(current)), serde_json::Value::Object(update)) => {
+            for (field, value) in update {
+                key.push(field.clone());
+                changed_values(current.get(field), value, key, changes);
+                key.pop();
+            }
+        }
+        (current, update) if current != Some(update) => {
+            changes.push((key.clone(), update.clone()));
+        }
+        _ => {}
+    }
+}
+
+/// Replaces or inserts the value at `key`, leaving the rest of the text as it was.
+pub(crate) fn set_in_text(
+    contents: &str,
+    key: &[&str],
+    value: &serde_json::Value,
+) -> Option<String> {
+    // Blanking comments keeps byte offsets in step with `contents`.
+    let blanked = blank_json_comments(contents);
+    let bytes = blanked.as_bytes();
+    let mut open = skip_json_space(bytes, 0);
+    if bytes.get(open) != Some(&b'{') {
+        return None;
+    }
+    let mut depth = 0;
+    'objects: loop {
+        let mut at = skip_json_space(bytes, open + 1);
+        let mut last = None;
+        while bytes.get(at) != Some(&b'}') {
+            let field_end = json_string_end(bytes, at)?;
+            let field: String = serde_json::from_str(&blanked[at..field_end]).ok()?;
+            let colon = skip_json_space(bytes, field_end);
+            if bytes.get(colon) != Some(&b':') {
+                return None;
+            }
+            let start = skip_json_space(bytes, colon + 1);
+            let end = json_value_end(bytes, start)?;
+            if field == key[depth] {
+                if depth + 1 == key.len() {
+                    let mut edited = contents.to_string();
+                    edited.replace_range(start..end, &value.to_string());
+                    return Some(edited);
+                }
+                if bytes[start] != b'{' {
+                    return None;
+                }
+                open = start;
+                depth += 1;
+                continue 'objects;
+            }
+            last = Some(end);
+            at = skip_json_space(bytes, end);
+            if bytes.get(at) == Some(&b',') {
+                at = skip_json_space(bytes, at + 1);
+            }
+        }
+
+        let nested = key[depth + 1..]
+            .iter()
+            .rev()
+            .fold(value.clone(), |value, field| {
+                serde_json::Value::Object(serde_json::Map::from_iter([(field.to_string(), value)]))
+            });
+        let member = format!("{}: {nested}", serde_json::Value::from(key[depth]));
+        let mut edited = contents.to_string();
+        match last {
+            None => edited.insert_str(at, &member),
+            Some(last) => match blanked[last..at].find('\n') {
+                // Keep the new member on its own line, after any trailing comment.
+                Some(newline) => {
+                    let line_start = blanked[..last].rfind('\n').map_or(0, |index| index + 1);
+                    let indent: String = blanked[line_start..]
+                        .chars()
+                        .take_while(|ch| *ch == ' ' || *ch == '\t')
+                        .collect();
+                    edited.insert_str(last + newline, &format!("\n{indent}{member}"));
+                    edited.insert(last, ',');
+                }
+                None => edited.insert_str(last, &format!(", {member}")),
+            },
+        }
+        return Some(edited);
+    }
+}
+
+/// Drops the member at `key`, leaving the rest of the text as it was.
+#[cfg(feature = "cli-full")]
+pub(crate) fn remove_in_text(contents: &str, key: &[&str]) -> Option<String> {
+    let blanked = blank_json_comments(contents);
+    let bytes = blanked.as_bytes();
+    let mut open = skip_json_space(bytes, 0);
+    if bytes.get(open) != Some(&b'{') {
+        return None;
+    }
+    let mut depth = 0;
+    'objects: loop {
+        let mut at = skip_json_space(bytes, open + 1);
+        let mut previous = None;
+        while bytes.get(at) != Some(&b'}') {
+            let field_end = json_string_end(bytes, at)?;
+            let field: String = serde_json::from_str(&blanked[at..field_end]).ok()?;
+            let colon = skip_json_space(bytes, field_end);
+            if bytes.get(colon) != Some(&b':') {
+                return None;
+            }
+            let start = skip_json_space(bytes, colon + 1);
+            let end = json_value_end(bytes, start)?;
+            let next = skip_json_space(bytes, end);
+            if field != key[depth] {
+                previous = Some(end);
+                at = next;
+                if bytes.get(at) == Some(&b',') {
+                    at = skip_json_space(bytes, at + 1);
+                }
+                continue;
+            }
+            if depth + 1 < key.len() {
+                if bytes[start] != b'{' {
+                    return Some(contents.to_string());
+                }
+                open = start;
+                depth += 1;
+                continue 'objects;
+            }
+
+            // Take the trailing comma with the member, or the leading one when it is last.
+            let (member_end, previous) = match (bytes.get(next), previous) {
+                (Some(b','), _) => (next + 1, None),
+                (_, previous) => (end, previous),
+            };
+            let line_start = blanked[..at].rfind('\n').map_or(0, |index| index + 1);
+            let line_end = blanked[member_end..]
+                .find('\n')
+                .map(|index| member_end + index);
+            let own_line = line_end.filter(|line_end| {
+                blanked[line_start..at].trim().is_empty()
+                    && blanked[member_end..*line_end].trim().is_empty()
+            });
+            let mut edited = contents.to_string();
+            match (own_line, previous) {
+                (Some(line_end), previous) => {
+                    edited.replace_range(line_start..line_end + 1, "");
+                    if let Some(previous) = previous {
+                        edited.remove(skip_json_space(bytes, previous));
+                    }
+                }
+                (None, Some(previous)) => edited.replace_range(previous..member_end, ""),
+                (None, None) => {
+                    let trailing = blanked[member_end..].len()
+                        - blanked[member_end..].trim_start_matches([' ', '\t']).len();
+                    edited.replace_range(at..member_end + trailing, "");
+                }
+            }
+            return Some(edited);
+        }
+        return Some(contents.to_string());
+    }
+}
+
+/// `input` with each `//` comment replaced by spaces of the same length.
+fn blank_json_comments(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut in_comment = false;
+    while let Some(ch) = chars.next() {
+        if in_comment {
+            if ch == '\n' {
+                in_comment = false;
+                output.push(ch);
+            } else {
+                output.extend(std::iter::repeat_n(' ', ch.len_utf8()));
+            }
+            continue;
+        }
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+        } else if ch == '/' && chars.peek() == Some(&'/') {
+            in_comment = true;
+            output.push(' ');
+            continue;
+        } else if ch == '"' {
+            in_string = true;
+        }
+        output.push(ch);
+    }
+    output
+}
+
+fn skip_json_space(bytes: &[u8], mut at: usize) -> usize {
+    while bytes.get(at).is_some_and(u8::is_ascii_whitespace) {
+        at += 1;
+    }
+    at
+}
+
+fn json_string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    if bytes.get(start) != Some(&b'"') {
+        return None;
+    }
+    let mut at = start + 1;
+    loop {
+        match bytes.get(at)? {
+            b'\\' => at += 2,
+            b'"' => return Some(at + 1),
+            _ => at += 1,
+        }
+    }
+}
+
+fn json_value_end(bytes: &[u8], start: usize) -> Option<usize> {
+    match bytes.get(start)? {
+        b'"' => json_string_end(bytes, start),
+        b'{' | b'[' => {
+            let mut depth = 0usize;
+            let mut at = start;
+            loop {
+                match bytes.get(at)? {
+                    b'"' => at = json_string_end(bytes, at)?,
+                    b'{' | b'[' => {
+                        depth += 1;
+                        at += 1;
+                    }
+                    b'}' | b']' => {
+                        depth -= 1;
+                        at += 1;
+                        if depth == 0 {
+                            return Some(at);
+                        }
+                    }
+                    _ => at += 1,
+                }
+            }
+        }
+        _ => {
+            let mut at = start;
+            while bytes.get(at).is_some_and(|byte| {
+                !matches!(byte, b',' | b'}' | b']') && !byte.is_ascii_whitespace()
+            }) {
+                at += 1;
+            }
+            (at > start).then_some(at)
+        }
+    }
+}
+
+fn merge_json(target: &mut serde_json::Value, update: serde_json::Value) {
+    match (target, update) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(update)) => {
+            for (key, value) in update {
+                match target.get_mut(&key) {
+                    Some(existing) if existing.is_object() && value.is_object() => {
+                        merge_json(existing, value)
+                    }
+                    _ => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, update) => *target = update,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self {
+            config: Config {
+                primary_monitor: String::new(),
+                secondary_monitor: String::new(),
+                paired_offset: DEFAULT_PAIRED_OFFSET,
+                workspace_count: DEFAULT_WORKSPACE_COUNT,
+                wrap_cycling: DEFAULT_WRAP_CYCLING,
+                cycle_skip_empty: false,
+                out_of_range: OutOfRangePolicy::default(),
+                profiles: Vec::new(),
+                rebalance_on_start: DEFAULT_REBALANCE_ON_START,
+                hotplug_settle_ms: 0,
+                drag_focus_delay_ms: 0,
+                schedules: Vec::new(),
+                rebalance_mode: RebalanceMode::default(),
+                idle_inhibit_slots: Vec::new(),
+                launch: BTreeMap::new(),
+                names: BTreeMap::new(),
+                theme_source: ThemeSource::default(),
+                waybar: WaybarConfig::default(),
+                metrics_address: None,
+                retry: Default::default(),
+                fast_switch: false,
+                enforce_range: false,
+                jump_to_urgent: false,
+                cursor_follows_focus: false,
+                instant_rebalance: false,
+                debounce: Default::default(),
+                layout: Vec::new(),
+                sticky_classes: Vec::new(),
+                reconcile: Default::default(),
+                primary_match: None,
+                secondary_match: None,
+                auto_primary: Default::default(),
+                workspace_base: DEFAULT_WORKSPACE_BASE,
+                restore: Default::default(),
+            },
+        }
+    }
+}
+
+impl ConfigBuilder {
+    pub fn from_config(config: Config) -> Self {
+        Self { config }
+    }
+
+    pub fn primary_monitor(mut self, name: impl Into<String>) -> Self {
+        self.config.primary_monitor = name.into();
+        self
+    }
+
+    pub fn secondary_monitor(mut self, name: impl Into<String>) -> Self {
+        self.config.secondary_monitor = name.into();
+        self
+    }
+
+    pub fn primary_match(mut self, selector: MonitorMatch) -> Self {
+        self.config.primary_match = Some(selector);
+        self
+    }
+
+    pub fn secondary_match(mut self, selector: MonitorMatch) -> Self {
+        self.config.secondary_match = Some(selector);
+        self
+    }
+
+    pub fn workspace_count(mut self, count: u32) -> Self {
+        self.config.workspace_count = count;
+        self.config.paired_offset = count;
+        self
+    }
+
+    pub fn workspace_base(mut self, base: u32) -> Self {
+        self.config.workspace_base = base;
+        self
+    }
+
+    pub fn wrap_cycling(mut self, wrap: bool) -> Self {
+        self.config.wrap_cycling = wrap;
+        self
+    }
+
+    pub fn cycle_skip_empty(mut self, skip: bool) -> Self {
+        self.config.cycle_skip_empty = skip;
+        self
+    }
+
+    pub fn out_of_range(mut self, policy: OutOfRangePolicy) -> Self {
+        self.config.out_of_range = policy;
+        self
+    }
+
+    pub fn profile(mut self, profile: MonitorProfile) -> Self {
+        self.config.profiles.push(profile);
+        self
+    }
+
+    pub fn rebalance_on_start(mut self, rebalance: bool) -> Self {
+        self.config.rebalance_on_start = rebalance;
+        self
+    }
+
+    pub fn hotplug_settle_ms(mut self, millis: u64) -> Self {
+        self.config.hotplug_settle_ms = millis;
+        self
+    }
+
+    pub fn drag_focus_delay_ms(mut self, millis: u64) -> Self {
+        self.config.drag_focus_delay_ms = millis;
+        self
+    }
+
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.config.schedules.push(schedule);
+        self
+    }
+
+    pub fn rebalance_mode(mut self, mode: RebalanceMode) -> Self {
+        self.config.rebalance_mode = mode;
+        self
+    }
+
+    pub fn idle_inhibit_slot(mut self, slot: u32) -> Self {
+        self.config.idle_inhibit_slots.push(slot);
+        self
+    }
+
+    pub fn launch(mut self, class: impl Into<String>, command: impl Into<String>) -> Self {
+        self.config.launch.insert(class.into(), command.into());
+        self
+    }
+
+    pub fn name(mut self, slot: u32, name: impl Into<String>) -> Self {
+        self.config.names.insert(slot, name.into());
+        self
+    }
+
+    pub fn theme_source(mut self, source: ThemeSource) -> Self {
+        self.config.theme_source = source;
+        self
+    }
+
+    pub fn waybar(mut self, waybar: WaybarConfig) -> Self {
+        self.config.waybar = waybar;
+        self
+    }
+
+    pub fn metrics_address(mut self, address: impl Into<String>) -> Self {
+        self.config.metrics_address = Some(address.into());
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.config.retry = retry;
+        self
+    }
+
+    pub fn fast_switch(mut self, fast: bool) -> Self {
+        self.config.fast_switch = fast;
+        self
+    }
+
+    pub fn enforce_range(mut self, enforce: bool) -> Self {
+        self.config.enforce_range = enforce;
+        self
+    }
+
+    pub fn jump_to_urgent(mut self, jump: bool) -> Self {
+        self.config.jump_to_urgent = jump;
+        self
+    }
+
+    pub fn cursor_follows_focus(mut self, follows: bool) -> Self {
+        self.config.cursor_follows_focus = follows;
+        self
+    }
+
+    pub fn instant_rebalance(mut self, instant: bool) -> Self {
+        self.config.instant_rebalance = instant;
+        self
+    }
+
+    pub fn debounce(mut self, debounce: DebounceConfig) -> Self {
+        self.config.debounce = debounce;
+        self
+    }
+
+    pub fn layout(mut self, layout: Vec<LayoutMonitor>) -> Self {
+        self.config.layout = layout;
+        self
+    }
+
+    pub fn sticky_class(mut self, class: impl Into<String>) -> Self {
+        self.config.sticky_classes.push(class.into());
+        self
+    }
+
+    pub fn reconcile(mut self, reconcile: ReconcileConfig) -> Self {
+        self.config.reconcile = reconcile;
+        self
+    }
+
+    pub fn restore(mut self, restore: RestoreConfig) -> Self {
+        self.config.restore = restore;
+        self
+    }
+
+    pub fn auto_primary(mut self, auto: AutoPrimary) -> Self {
+        self.config.auto_primary = auto;
+        self
+    }
+
+    /// Monitors left unset (no name and no match) are picked automatically.
+    pub fn build(mut self) -> Result<Config, ConfigError> {
+        for (name, selector) in [
+            (&self.config.primary_monitor, &mut self.config.primary_match),
+            (
+                &self.config.secondary_monitor,
+                &mut self.config.secondary_match,
+            ),
+        ] {
+            if name.is_empty() && selector.is_none() {
+                *selector = Some(MonitorMatch::Auto);
+            }
+        }
+        let errors = crate::validate::config_errors(&self.config);
+        if !errors.is_empty() {
+            return Err(ConfigError::Invalid(errors));
+        }
+        Ok(self.config)
+    }
 }
 
 impl std::str::FromStr for Config {
@@ -76,14 +1216,27 @@ fn default_offset() -> u32 {
     DEFAULT_PAIRED_OFFSET
 }
 
+fn default_workspace_base() -> u32 {
+    DEFAULT_WORKSPACE_BASE
+}
+
 fn default_wrap_cycling() -> bool {
     DEFAULT_WRAP_CYCLING
 }
 
+fn default_rebalance_on_start() -> bool {
+    DEFAULT_REBALANCE_ON_START
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Config;
+    use super::{
+        Config, DebounceMode, MonitorMatch, MonitorProfile, OutOfRangePolicy, RebalanceMode,
+        ReconcilePolicy, ThemeSource, WaybarMode, strip_json_comments,
+    };
+    use crate::hyprctl::MonitorInfo;
     use std::fs;
+    use std::time::Duration;
 
     #[test]
     fn parses_config_with_explicit_offset() {
@@ -119,6 +1272,23 @@ mod tests {
         assert_eq!(config.workspace_count, 6);
     }
 
+    #[test]
+    fn parses_workspace_base() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","workspace_count":5,"workspace_base":11}"#;
+
+        let config = Config::from_json(input).expect("config should parse");
+
+        assert_eq!(config.workspace_base, 11);
+        assert_eq!(config.slot_workspace(1), 11);
+        assert_eq!(config.slot_of(17), 2);
+        let saved = Config::from_json(&config.to_json()).expect("round trip");
+        assert_eq!(saved.workspace_base, 11);
+        let default =
+            Config::from_json(r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#)
+                .expect("config");
+        assert_eq!(default.workspace_base, 1);
+    }
+
     #[test]
     fn parses_config_via_from_str_trait() {
         let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
@@ -161,6 +1331,386 @@ mod tests {
         assert!(config.wrap_cycling);
     }
 
+    #[test]
+    fn parses_rebalance_on_start() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        assert!(Config::from_json(input).expect("config").rebalance_on_start);
+
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","rebalance_on_start":false}"#;
+        assert!(!Config::from_json(input).expect("config").rebalance_on_start);
+    }
+
+    #[test]
+    fn parses_reconcile_policy() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        let config = Config::from_json(input).expect("config");
+        assert_eq!(config.reconcile.policy, ReconcilePolicy::Off);
+        assert_eq!(config.reconcile.interval_ms, 30_000);
+
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","reconcile":{"policy":"fix"}}"#;
+        let config = Config::from_json(input).expect("config");
+        assert_eq!(config.reconcile.policy, ReconcilePolicy::Fix);
+        assert_eq!(config.reconcile.interval_ms, 30_000);
+    }
+
+    #[test]
+    fn parses_restore_launch_commands() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        let restore = Config::from_json(input).expect("config").restore;
+        assert!(!restore.launch_missing);
+        assert!(!restore.allow_degraded);
+
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1",
+            "restore":{"launch_missing":true,"launch_command":{"org.gnome.Nautilus":"nautilus"}}}"#;
+        let restore = Config::from_json(input).expect("config").restore;
+        assert!(restore.launch_missing);
+        assert_eq!(
+            restore
+                .launch_command
+                .get("org.gnome.Nautilus")
+                .map(String::as_str),
+            Some("nautilus")
+        );
+    }
+
+    #[test]
+    fn parses_fast_switch() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        assert!(!Config::from_json(input).expect("config").fast_switch);
+
+        let input =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","fast_switch":true}"#;
+        assert!(Config::from_json(input).expect("config").fast_switch);
+    }
+
+    #[test]
+    fn parses_enforce_range() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        assert!(!Config::from_json(input).expect("config").enforce_range);
+
+        let input =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","enforce_range":true}"#;
+        assert!(Config::from_json(input).expect("config").enforce_range);
+    }
+
+    #[test]
+    fn parses_jump_to_urgent() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        assert!(!Config::from_json(input).expect("config").jump_to_urgent);
+
+        let input =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","jump_to_urgent":true}"#;
+        assert!(Config::from_json(input).expect("config").jump_to_urgent);
+    }
+
+    #[test]
+    fn parses_cursor_follows_focus() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        let config = Config::from_json(input).expect("config");
+        assert!(!config.cursor_follows_focus);
+
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","cursor_follows_focus":true}"#;
+        let config = Config::from_json(input).expect("config");
+        assert!(config.cursor_follows_focus);
+    }
+
+    #[test]
+    fn parses_instant_rebalance() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        assert!(!Config::from_json(input).expect("config").instant_rebalance);
+
+        let input =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","instant_rebalance":true}"#;
+        assert!(Config::from_json(input).expect("config").instant_rebalance);
+    }
+
+    #[test]
+    fn parses_debounce_windows() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        let config = Config::from_json(input).expect("config");
+        assert_eq!(config.debounce.rebalance(), Duration::from_millis(200));
+        assert_eq!(config.debounce.focus(), Duration::from_millis(100));
+
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","debounce":{"rebalance_ms":500}}"#;
+        let config = Config::from_json(input).expect("config");
+        assert_eq!(config.debounce.rebalance_ms, 500);
+        assert_eq!(config.debounce.focus_ms, 100);
+        assert_eq!(config.debounce.mode, DebounceMode::Fixed);
+
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","debounce":{"mode":"adaptive","max_rebalance_ms":3000}}"#;
+        let config = Config::from_json(input).expect("config");
+        assert_eq!(config.debounce.mode, DebounceMode::Adaptive);
+        assert_eq!(config.debounce.max_rebalance(), Duration::from_secs(3));
+        assert_eq!(config.debounce.rebalance_ms, 200);
+    }
+
+    #[test]
+    fn parses_ipc_timeout() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        let config = Config::from_json(input).expect("config");
+        assert_eq!(config.retry.ipc_timeout(), Some(Duration::from_secs(2)));
+
+        let input =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","retry":{"timeout_ms":0}}"#;
+        let config = Config::from_json(input).expect("config");
+        assert_eq!(config.retry.attempts, 3);
+        assert_eq!(config.retry.ipc_timeout(), None);
+    }
+
+    #[test]
+    fn parses_hotplug_settle_window() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        assert_eq!(
+            Config::from_json(input).expect("config").hotplug_settle_ms,
+            0
+        );
+
+        let input =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","hotplug_settle_ms":2500}"#;
+        assert_eq!(
+            Config::from_json(input).expect("config").hotplug_settle_ms,
+            2500
+        );
+    }
+
+    #[test]
+    fn parses_drag_focus_delay() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        assert_eq!(
+            Config::from_json(input)
+                .expect("config")
+                .drag_focus_delay_ms,
+            0
+        );
+
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","drag_focus_delay_ms":300}"#;
+        assert_eq!(
+            Config::from_json(input)
+                .expect("config")
+                .drag_focus_delay_ms,
+            300
+        );
+    }
+
+    #[test]
+    fn parses_waybar_daemon_push_mode() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","waybar":{"mode":"daemon-push","signal":8,"bar":"top"}}"#;
+
+        let config = Config::from_json(input).expect("config should parse");
+
+        assert_eq!(config.waybar.mode, WaybarMode::DaemonPush);
+        assert_eq!(config.waybar.signal, Some(8));
+    }
+
+    #[test]
+    fn builder_round_trips_through_json() {
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(4)
+            .wrap_cycling(false)
+            .out_of_range(OutOfRangePolicy::Error)
+            .name(2, "web")
+            .idle_inhibit_slot(3)
+            .launch("kitty", "kitty --single-instance")
+            .schedule(
+                serde_json::from_str(
+                    r#"{"name":"focus","at":"09:30","slot":1,"days":["mon","wed"]}"#,
+                )
+                .expect("schedule"),
+            )
+            .build()
+            .expect("valid config");
+
+        assert_eq!(config.paired_offset, 4);
+        assert_eq!(Config::from_json(&config.to_json()).expect("parse"), config);
+    }
+
+    #[test]
+    fn builder_rejects_invalid_configs() {
+        let auto = Config::builder()
+            .primary_monitor("DP-1")
+            .build()
+            .expect("auto secondary");
+        assert_eq!(auto.secondary_match, Some(MonitorMatch::Auto));
+
+        let err = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .name(3, "media")
+            .idle_inhibit_slot(0)
+            .build()
+            .expect_err("invalid");
+        assert_eq!(
+            err.to_string(),
+            "invalid config: idle_inhibit_slots contains slot 0, outside 1-2; name 'media' is set for slot 3, outside 1-2"
+        );
+    }
+
+    #[test]
+    fn save_preserves_unknown_keys_and_waybar_placement() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("paired.json");
+        fs::write(
+            &path,
+            r#"{
+                // comment
+                "primary_monitor": "DP-1",
+                "secondary_monitor": "HDMI-A-1",
+                "bindings": {"modifier": "ALT"},
+                "waybar": {"bar": "bottom", "tooltip_windows": false}
+            }"#,
+        )
+        .expect("write");
+
+        let config = Config::from_path(&path).expect("config");
+        let mut waybar = config.waybar.clone();
+        waybar.tooltip_windows = true;
+        super::ConfigBuilder::from_config(config)
+            .workspace_count(6)
+            .waybar(waybar)
+            .build()
+            .expect("valid")
+            .save(&path)
+            .expect("save");
+
+        let contents = fs::read_to_string(&path).expect("read");
+        assert!(contents.contains("// comment"));
+        let saved: serde_json::Value =
+            serde_json::from_str(&strip_json_comments(&contents)).expect("json");
+        assert_eq!(saved["workspace_count"], 6);
+        assert_eq!(saved["bindings"]["modifier"], "ALT");
+        assert_eq!(saved["waybar"]["bar"], "bottom");
+        assert_eq!(saved["waybar"]["tooltip_windows"], true);
+        assert_eq!(Config::from_path(&path).expect("reload").workspace_count, 6);
+    }
+
+    #[test]
+    fn save_leaves_an_unchanged_config_byte_identical() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("paired.json");
+        let original = "{\n  // Monitors.\n  \"primary_monitor\": \"DP-1\",\n  \"secondary_monitor\": \"HDMI-A-1\"\n}\n";
+        fs::write(&path, original).expect("write");
+
+        Config::from_path(&path)
+            .expect("config")
+            .save(&path)
+            .expect("save");
+
+        assert_eq!(fs::read_to_string(&path).expect("read"), original);
+    }
+
+    #[test]
+    fn set_value_rejects_paired_offset_next_to_workspace_count() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("paired.json");
+        let original =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","workspace_count":6}"#;
+        fs::write(&path, original).expect("write");
+
+        let err = super::set_value(&path, "paired_offset", "8").expect_err("rejected");
+
+        assert!(matches!(err, super::ConfigError::Invalid(_)));
+        assert_eq!(fs::read_to_string(&path).expect("read"), original);
+    }
+
+    #[cfg(feature = "cli-full")]
+    #[test]
+    fn remove_in_text_drops_members_and_their_commas() {
+        let contents = "{\n  // Bar.\n  \"waybar\": {\n    \"bar\": \"top\",\n    \"output\": \"DP-1\"\n  },\n  \"wrap_cycling\": true\n}\n";
+
+        assert_eq!(
+            super::remove_in_text(contents, &["waybar", "output"]).expect("remove"),
+            "{\n  // Bar.\n  \"waybar\": {\n    \"bar\": \"top\"\n  },\n  \"wrap_cycling\": true\n}\n"
+        );
+        assert_eq!(
+            super::remove_in_text(contents, &["waybar"]).expect("remove"),
+            "{\n  // Bar.\n  \"wrap_cycling\": true\n}\n"
+        );
+        assert_eq!(
+            super::remove_in_text(r#"{"a": 1, "b": 2}"#, &["a"]).expect("remove"),
+            r#"{"b": 2}"#
+        );
+        assert_eq!(
+            super::remove_in_text(r#"{"a": 1, "b": 2}"#, &["b"]).expect("remove"),
+            r#"{"a": 1}"#
+        );
+        assert_eq!(
+            super::remove_in_text(contents, &["waybar", "index"]).expect("absent"),
+            contents
+        );
+    }
+
+    #[test]
+    fn set_value_edits_keys_in_place() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("paired.json");
+        fs::write(
+            &path,
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","paired_offset":10,"bindings":{"modifier":"ALT"}}"#,
+        )
+        .expect("write");
+
+        let config = super::set_value(&path, "workspace_count", "12").expect("set");
+        assert_eq!(config.workspace_count, 12);
+        super::set_value(&path, "debounce.mode", "adaptive").expect("set nested");
+        super::set_value(&path, "bindings.modifier", "SUPER").expect("set unparsed key");
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).expect("read")).expect("json");
+        assert_eq!(saved["workspace_count"], 12);
+        assert_eq!(saved["debounce"], serde_json::json!({"mode": "adaptive"}));
+        assert_eq!(saved["bindings"]["modifier"], "SUPER");
+        assert_eq!(saved["paired_offset"], 10);
+        let config = Config::from_path(&path).expect("reload");
+        assert_eq!(config.workspace_count, 12);
+        assert_eq!(config.debounce.mode, DebounceMode::Adaptive);
+    }
+
+    #[test]
+    fn set_value_keeps_comments_and_repairs_unparseable_keys() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("paired.json");
+        fs::write(
+            &path,
+            "{\n  // Monitors.\n  \"primary_monitor\": \"DP-1\",\n  \"secondary_monitor\": \"HDMI-A-1\", // right\n  \"rebalance_mode\": \"bogus\"\n}\n",
+        )
+        .expect("write");
+
+        super::set_value(&path, "rebalance_mode", "preserve").expect("repair");
+        super::set_value(&path, "workspace_count", "4").expect("insert");
+        super::set_value(&path, "debounce.mode", "adaptive").expect("insert nested");
+
+        assert_eq!(
+            fs::read_to_string(&path).expect("read"),
+            "{\n  // Monitors.\n  \"primary_monitor\": \"DP-1\",\n  \"secondary_monitor\": \"HDMI-A-1\", // right\n  \"rebalance_mode\": \"preserve\",\n  \"workspace_count\": 4,\n  \"debounce\": {\"mode\":\"adaptive\"}\n}\n"
+        );
+        let config = Config::from_path(&path).expect("reload");
+        assert_eq!(config.rebalance_mode, RebalanceMode::Preserve);
+        assert_eq!(config.debounce.mode, DebounceMode::Adaptive);
+    }
+
+    #[test]
+    fn set_value_refuses_unknown_keys_and_invalid_values() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("paired.json");
+        let contents = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+        fs::write(&path, contents).expect("write");
+
+        let err = super::set_value(&path, "workspace_cuont", "12").expect_err("unknown");
+        assert_eq!(err.to_string(), "unknown config key: workspace_cuont");
+        assert!(super::set_value(&path, "debounce.burst", "1").is_err());
+        let err = super::set_value(&path, "workspace_count", "0").expect_err("invalid");
+        assert_eq!(
+            err.to_string(),
+            "invalid config: workspace_count must be at least 1"
+        );
+        assert!(super::set_value(&path, "workspace_count", "many").is_err());
+
+        assert_eq!(fs::read_to_string(&path).expect("read"), contents);
+    }
+
     #[test]
     fn parses_wrap_cycling_false() {
         let input =
@@ -172,8 +1722,194 @@ mod tests {
     }
 
     #[test]
-    fn errors_when_primary_missing() {
-        let input = r#"{"secondary_monitor":"HDMI-A-1","paired_offset":10}"#;
+    fn defaults_out_of_range_to_normalize() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+
+        let config = Config::from_json(input).expect("config should parse");
+
+        assert_eq!(config.out_of_range, OutOfRangePolicy::Normalize);
+    }
+
+    #[test]
+    fn parses_out_of_range_policy() {
+        let input =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","out_of_range":"raw"}"#;
+
+        let config = Config::from_json(input).expect("config should parse");
+
+        assert_eq!(config.out_of_range, OutOfRangePolicy::Raw);
+    }
+
+    #[test]
+    fn rejects_unknown_out_of_range_policy() {
+        let input =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","out_of_range":"wrap"}"#;
+
+        let error = Config::from_json(input).expect_err("config should fail");
+
+        assert!(matches!(error, super::ConfigError::InvalidJson(_)));
+    }
+
+    #[test]
+    fn parses_theme_source() {
+        let input =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","theme_source":"wal"}"#;
+
+        let config = Config::from_json(input).expect("config should parse");
+        let default =
+            Config::from_json(r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#)
+                .expect("config should parse");
+
+        assert_eq!(config.theme_source, ThemeSource::Wal);
+        assert_eq!(default.theme_source, ThemeSource::Css);
+    }
+
+    #[test]
+    fn resolves_monitor_matches() {
+        let input = r#"{"primary_monitor":{"description":"LG UltraGear"},"secondary_monitor":{"serial":"ABC123"}}"#;
+        let mut config = Config::from_json(input).expect("config");
+        let monitor = |name: &str, description: &str, serial: &str| MonitorInfo {
+            name: name.to_string(),
+            x: 0,
+            id: 0,
+            mirror_of: None,
+            active_workspace: None,
+            description: Some(description.to_string()),
+            serial: Some(serial.to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.primary_match,
+            Some(MonitorMatch::Description("LG UltraGear".to_string()))
+        );
+        assert!(!config.monitors_resolved());
+        assert!(config.resolve_monitors(&[
+            monitor("DP-3", "Dell Inc. DELL U2720Q", "ABC123"),
+            monitor("DP-2", "LG Electronics LG UltraGear 0x01", "XYZ"),
+        ]));
+        assert_eq!(config.primary_monitor, "DP-2");
+        assert_eq!(config.secondary_monitor, "DP-3");
+        assert!(!config.resolve_monitors(&[
+            monitor("DP-2", "LG Electronics LG UltraGear 0x01", "XYZ"),
+            monitor("DP-3", "Dell Inc. DELL U2720Q", "ABC123"),
+        ]));
+
+        let saved: serde_json::Value = serde_json::from_str(&config.to_json()).expect("json");
+        assert_eq!(
+            saved["primary_monitor"],
+            serde_json::json!({"description": "LG UltraGear"})
+        );
+        assert_eq!(
+            saved["secondary_monitor"],
+            serde_json::json!({"serial": "ABC123"})
+        );
+
+        config.resolve_monitors(&[monitor("DP-3", "Dell Inc. DELL U2720Q", "ABC123")]);
+        assert_eq!(config.primary_monitor, "");
+    }
+
+    #[test]
+    fn picks_omitted_monitors_automatically() {
+        let monitor = |name: &str, x: i32, width: u32, focused: bool| MonitorInfo {
+            name: name.to_string(),
+            x,
+            width,
+            height: width * 9 / 16,
+            focused,
+            ..Default::default()
+        };
+        let monitors = [
+            monitor("HDMI-A-1", 1920, 3840, false),
+            monitor("DP-1", 0, 1920, false),
+            monitor("eDP-1", -1920, 1920, true),
+        ];
+        let resolve = |input: &str| {
+            let mut config = Config::from_json(input).expect("config");
+            config.resolve_monitors(&monitors);
+            (config.primary_monitor, config.secondary_monitor)
+        };
+
+        assert_eq!(resolve("{}"), ("eDP-1".to_string(), "DP-1".to_string()));
+        assert_eq!(
+            resolve(r#"{"auto_primary":"largest"}"#),
+            ("HDMI-A-1".to_string(), "eDP-1".to_string())
+        );
+        assert_eq!(
+            resolve(r#"{"auto_primary":"focused","secondary_monitor":"eDP-1"}"#),
+            ("DP-1".to_string(), "eDP-1".to_string())
+        );
+        assert_eq!(
+            resolve(r#"{"primary_monitor":"DP-1"}"#),
+            ("DP-1".to_string(), "eDP-1".to_string())
+        );
+
+        let config = Config::from_json(r#"{"auto_primary":"focused"}"#).expect("config");
+        let saved: serde_json::Value = serde_json::from_str(&config.to_json()).expect("json");
+        assert!(saved.get("primary_monitor").is_none());
+        assert_eq!(saved["auto_primary"], "focused");
+    }
+
+    #[test]
+    fn selects_profile_for_connected_monitors() {
+        let input = r#"{
+            "primary_monitor": "DP-1",
+            "secondary_monitor": "HDMI-A-1",
+            "profiles": [
+                {"monitors": ["eDP-1", "DP-2"], "primary_monitor": "eDP-1", "secondary_monitor": "DP-2"}
+            ]
+        }"#;
+        let config = Config::from_json(input).expect("config should parse");
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            config.profiles,
+            vec![MonitorProfile {
+                monitors: names(&["eDP-1", "DP-2"]),
+                primary_monitor: "eDP-1".to_string(),
+                secondary_monitor: "DP-2".to_string(),
+            }]
+        );
+        let docked = config
+            .for_monitors(&names(&["DP-2", "eDP-1"]))
+            .expect("paired");
+        assert_eq!(docked.primary_monitor, "eDP-1");
+        assert_eq!(docked.secondary_monitor, "DP-2");
+        let fallback = config
+            .for_monitors(&names(&["DP-1", "HDMI-A-1"]))
+            .expect("paired");
+        assert_eq!(fallback.primary_monitor, "DP-1");
+        assert_eq!(config.for_monitors(&names(&["eDP-1"])), None);
+    }
+
+    #[test]
+    fn parses_config_with_line_comments() {
+        let input = "{\n  // primary output\n  \"primary_monitor\": \"DP-1\",\n  \"secondary_monitor\": \"HDMI-A-1\" // right\n}\n";
+
+        let config = Config::from_json(input).expect("config should parse");
+
+        assert_eq!(config.primary_monitor, "DP-1");
+        assert_eq!(config.secondary_monitor, "HDMI-A-1");
+    }
+
+    #[test]
+    fn keeps_comment_markers_inside_strings() {
+        let input = r#"{"primary_monitor":"desc://a\"b//c"} // trailing"#;
+
+        assert_eq!(
+            strip_json_comments(input),
+            r#"{"primary_monitor":"desc://a\"b//c"} "#
+        );
+    }
+
+    #[test]
+    fn errors_when_primary_empty() {
+        let input = r#"{"primary_monitor":"","secondary_monitor":"HDMI-A-1","paired_offset":10}"#;
 
         let error = Config::from_json(input).expect_err("config should fail");
 
@@ -184,8 +1920,8 @@ mod tests {
     }
 
     #[test]
-    fn errors_when_secondary_missing() {
-        let input = r#"{"primary_monitor":"DP-1","paired_offset":10}"#;
+    fn errors_when_secondary_empty() {
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"","paired_offset":10}"#;
 
         let error = Config::from_json(input).expect_err("config should fail");
 
@@ -195,6 +1931,29 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn rejects_monitor_names_that_would_break_batches() {
+        let input = r#"{"primary_monitor":"DP-1;dispatch exit","secondary_monitor":"HDMI A 1",
+            "layout":["primary","secondary","DP-3,x"]}"#;
+
+        let error = Config::from_json(input).expect_err("config should fail");
+
+        let super::ConfigError::Invalid(errors) = error else {
+            panic!("unexpected error: {error}");
+        };
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].starts_with("primary_monitor 'DP-1;dispatch exit'"));
+        assert!(errors[1].starts_with("secondary_monitor 'HDMI A 1'"));
+        assert!(errors[2].starts_with("layout monitor 'DP-3,x'"));
+        assert!(
+            Config::builder()
+                .primary_monitor("DP-1\n")
+                .secondary_monitor("HDMI-A-1")
+                .build()
+                .is_err()
+        );
+    }
+
     #[test]
     fn loads_config_from_path() {
         let dir = tempfile::tempdir().expect("tempdir");
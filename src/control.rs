@@ -0,0 +1,761 @@
+use std::cell::RefCell;
+use std::fs::{self, DirBuilder};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::ops::BitOr;
+use std::os::fd::{AsRawFd, RawFd};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::DebounceConfig;
+
+pub const CONTROL_PROTOCOL_VERSION: u32 = 5;
+pub const CONTROL_TIMEOUT: Duration = Duration::from_secs(2);
+const WAIT_INTERVAL: Duration = Duration::from_millis(200);
+pub const SAFE_MODE_PREFIX: &str = "safe mode: ";
+const SOCKET_DIR_MODE: u32 = 0o700;
+const SOCKET_MODE: u32 = 0o600;
+const MAX_REQUEST_LEN: usize = 4096;
+
+/// `READ` allows state queries and subscriptions; `CONTROL` allows commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities(u8);
+
+impl Capabilities {
+    pub const READ: Capabilities = Capabilities(1);
+    pub const CONTROL: Capabilities = Capabilities(1 << 1);
+    pub const ALL: Capabilities = Capabilities(Self::READ.0 | Self::CONTROL.0);
+
+    pub fn contains(self, other: Capabilities) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Capabilities;
+
+    fn bitor(self, other: Capabilities) -> Capabilities {
+        Capabilities(self.0 | other.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    Stop,
+    Reload,
+    State,
+    Watch,
+    Status,
+    History,
+    Urgent,
+    Debounce,
+    Exec,
+}
+
+impl ControlCommand {
+    pub const ALL: [ControlCommand; 9] = [
+        ControlCommand::Stop,
+        ControlCommand::Reload,
+        ControlCommand::State,
+        ControlCommand::Watch,
+        ControlCommand::Status,
+        ControlCommand::History,
+        ControlCommand::Urgent,
+        ControlCommand::Debounce,
+        ControlCommand::Exec,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ControlCommand::Stop => "stop",
+            ControlCommand::Reload => "reload",
+            ControlCommand::State => "state",
+            ControlCommand::Watch => "watch",
+            ControlCommand::Status => "status",
+            ControlCommand::History => "history",
+            ControlCommand::Urgent => "urgent",
+            ControlCommand::Debounce => "debounce",
+            ControlCommand::Exec => "exec",
+        }
+    }
+
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim() {
+            "stop" => Some(ControlCommand::Stop),
+            "reload" => Some(ControlCommand::Reload),
+            "state" => Some(ControlCommand::State),
+            "watch" => Some(ControlCommand::Watch),
+            "status" => Some(ControlCommand::Status),
+            "history" => Some(ControlCommand::History),
+            "urgent" => Some(ControlCommand::Urgent),
+            "debounce" => Some(ControlCommand::Debounce),
+            "exec" => Some(ControlCommand::Exec),
+            _ => None,
+        }
+    }
+
+    /// Splits a request line into its command and arguments.
+    pub fn parse_line(input: &str) -> Option<(Self, Vec<String>)> {
+        let mut words = input.split_whitespace();
+        let command = Self::parse(words.next()?)?;
+        let args = words.map(str::to_string).collect::<Vec<_>>();
+        (command == ControlCommand::Exec || args.is_empty()).then_some((command, args))
+    }
+
+    pub fn required(&self) -> Capabilities {
+        match self {
+            ControlCommand::Stop | ControlCommand::Reload | ControlCommand::Exec => {
+                Capabilities::CONTROL
+            }
+            ControlCommand::State
+            | ControlCommand::Watch
+            | ControlCommand::Status
+            | ControlCommand::History
+            | ControlCommand::Urgent
+            | ControlCommand::Debounce => Capabilities::READ,
+        }
+    }
+}
+
+pub fn control_socket_path(runtime_dir: &Path) -> PathBuf {
+    runtime_dir.join("daemon.sock")
+}
+
+/// Socket for bars and other readers; it refuses commands that change the daemon.
+pub fn read_only_socket_path(runtime_dir: &Path) -> PathBuf {
+    runtime_dir.join("daemon-ro.sock")
+}
+
+pub fn peer_uid(stream: &UnixStream) -> io::Result<u32> {
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = size_of::<libc::ucred>() as libc::socklen_t;
+    // SAFETY: `cred` and `len` are valid for writes and `len` is its size.
+    let result = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            (&raw mut cred).cast(),
+            &mut len,
+        )
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(cred.uid)
+}
+
+pub fn current_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail.
+    unsafe { libc::geteuid() }
+}
+
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    pub args: Vec<String>,
+    stream: UnixStream,
+}
+
+impl ControlRequest {
+    pub fn respond(mut self, response: &str) -> io::Result<()> {
+        writeln!(self.stream, "{response}")
+    }
+
+    pub fn subscribe(self) -> io::Result<Subscriber> {
+        self.stream.set_nonblocking(true)?;
+        Ok(Subscriber {
+            stream: self.stream,
+        })
+    }
+}
+
+pub struct Subscriber {
+    stream: UnixStream,
+}
+
+impl Subscriber {
+    pub fn send(&mut self, line: &str) -> io::Result<()> {
+        self.stream.write_all(format!("{line}\n").as_bytes())
+    }
+}
+
+struct ControlSocket {
+    listener: UnixListener,
+    path: PathBuf,
+    capabilities: Capabilities,
+    pending: RefCell<Vec<PendingRequest>>,
+}
+
+/// An accepted connection whose request line hasn't fully arrived yet.
+struct PendingRequest {
+    stream: UnixStream,
+    line: Vec<u8>,
+    accepted: Instant,
+}
+
+impl PendingRequest {
+    /// Reads what the client has sent so far; `true` once the line is complete.
+    fn read(&mut self) -> io::Result<bool> {
+        let mut buffer = [0; 256];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(read) => {
+                    self.line.extend_from_slice(&buffer[..read]);
+                    if self.line.contains(&b'\n') {
+                        return Ok(true);
+                    }
+                    if self.line.len() > MAX_REQUEST_LEN {
+                        return Err(io::ErrorKind::InvalidData.into());
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl ControlSocket {
+    fn bind(path: &Path, capabilities: Capabilities) -> io::Result<Self> {
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("control socket already in use: {}", path.display()),
+                ));
+            }
+            fs::remove_file(path)?;
+        }
+        if let Some(parent) = path.parent() {
+            DirBuilder::new()
+                .recursive(true)
+                .mode(SOCKET_DIR_MODE)
+                .create(parent)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        fs::set_permissions(path, fs::Permissions::from_mode(SOCKET_MODE))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+            capabilities,
+            pending: RefCell::new(Vec::new()),
+        })
+    }
+
+    fn poll(&self) -> io::Result<Option<ControlRequest>> {
+        self.accept()?;
+        let mut pending = self.pending.borrow_mut();
+        let mut index = 0;
+        while index < pending.len() {
+            match pending[index].read() {
+                Ok(true) => {
+                    if let Some(request) = self.request(pending.remove(index))? {
+                        return Ok(Some(request));
+                    }
+                }
+                Ok(false) if pending[index].accepted.elapsed() < CONTROL_TIMEOUT => index += 1,
+                _ => {
+                    pending.remove(index);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn accept(&self) -> io::Result<()> {
+        loop {
+            let mut stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(err) => return Err(err),
+            };
+            if peer_uid(&stream).ok() != Some(current_uid()) {
+                let _ = writeln!(stream, "error: permission denied");
+                continue;
+            }
+            stream.set_nonblocking(true)?;
+            self.pending.borrow_mut().push(PendingRequest {
+                stream,
+                line: Vec::new(),
+                accepted: Instant::now(),
+            });
+        }
+    }
+
+    fn request(&self, pending: PendingRequest) -> io::Result<Option<ControlRequest>> {
+        let mut stream = pending.stream;
+        let line = String::from_utf8_lossy(&pending.line);
+        let line = line.split('\n').next().unwrap_or_default();
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(CONTROL_TIMEOUT))?;
+        match ControlCommand::parse_line(line) {
+            Some((command, args)) if self.capabilities.contains(command.required()) => {
+                return Ok(Some(ControlRequest {
+                    command,
+                    args,
+                    stream,
+                }));
+            }
+            Some((command, _)) => {
+                let _ = writeln!(
+                    stream,
+                    "error: '{}' is not allowed on {}",
+                    command.as_str(),
+                    self.path.display()
+                );
+            }
+            None => {
+                let _ = writeln!(stream, "error: unknown command '{}'", line.trim());
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The daemon's control sockets, restricted to its user via `SO_PEERCRED`.
+pub struct ControlListener {
+    sockets: Vec<ControlSocket>,
+}
+
+impl ControlListener {
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            sockets: vec![ControlSocket::bind(path, Capabilities::ALL)?],
+        })
+    }
+
+    pub fn bind_runtime_dir(runtime_dir: &Path) -> io::Result<Self> {
+        Self::bind(&control_socket_path(runtime_dir))?
+            .with_socket(&read_only_socket_path(runtime_dir), Capabilities::READ)
+    }
+
+    pub fn with_socket(mut self, path: &Path, capabilities: Capabilities) -> io::Result<Self> {
+        self.sockets.push(ControlSocket::bind(path, capabilities)?);
+        Ok(self)
+    }
+
+    pub fn poll(&self) -> io::Result<Option<ControlRequest>> {
+        for socket in &self.sockets {
+            if let Some(request) = socket.poll()? {
+                return Ok(Some(request));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn raw_fds(&self) -> impl Iterator<Item = RawFd> + '_ {
+        self.sockets
+            .iter()
+            .map(|socket| socket.listener.as_raw_fd())
+    }
+
+    /// Connections still waiting for their request line.
+    pub fn pending_fds(&self) -> Vec<RawFd> {
+        self.sockets
+            .iter()
+            .flat_map(|socket| {
+                socket
+                    .pending
+                    .borrow()
+                    .iter()
+                    .map(|pending| pending.stream.as_raw_fd())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+pub fn daemon_reachable(path: &Path) -> bool {
+    UnixStream::connect(path).is_ok()
+}
+
+pub fn wait_for_daemon(path: &Path, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if daemon_reachable(path) {
+            return true;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        std::thread::sleep(WAIT_INTERVAL.min(deadline - now));
+    }
+}
+
+pub fn send_command(path: &Path, command: ControlCommand) -> io::Result<String> {
+    send_line(path, command.as_str())
+}
+
+/// Asks the daemon to run a paired command, e.g. `["switch", "3"]`.
+pub fn send_exec(path: &Path, args: &[String]) -> io::Result<String> {
+    if args
+        .iter()
+        .any(|arg| arg.is_empty() || arg.contains(char::is_whitespace))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "exec arguments must be non-empty and contain no whitespace",
+        ));
+    }
+    send_line(
+        path,
+        &format!("{} {}", ControlCommand::Exec.as_str(), args.join(" ")),
+    )
+}
+
+fn send_line(path: &Path, line: &str) -> io::Result<String> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(CONTROL_TIMEOUT))?;
+    writeln!(stream, "{line}")?;
+    let mut response = String::new();
+    BufReader::new(&stream).read_line(&mut response)?;
+    Ok(response.trim().to_string())
+}
+
+pub fn safe_mode_error(path: &Path) -> Option<String> {
+    let response = send_command(path, ControlCommand::Status).ok()?;
+    response
+        .strip_prefix(SAFE_MODE_PREFIX)
+        .map(|error| error.to_string())
+}
+
+/// The running daemon's mode, or `None` when unreachable or in safe mode.
+pub fn daemon_mode(path: &Path) -> Option<String> {
+    let response = send_command(path, ControlCommand::Status).ok()?;
+    (!response.starts_with(SAFE_MODE_PREFIX) && !response.is_empty() && response != "ok")
+        .then_some(response)
+}
+
+/// The running daemon's debounce windows, or `None` when unreachable.
+pub fn daemon_debounce(path: &Path) -> Option<DebounceConfig> {
+    let response = send_command(path, ControlCommand::Debounce).ok()?;
+    serde_json::from_str(&response).ok()
+}
+
+pub fn watch(path: &Path) -> io::Result<io::Lines<BufReader<UnixStream>>> {
+    let mut stream = UnixStream::connect(path)?;
+    writeln!(stream, "{}", ControlCommand::Watch.as_str())?;
+    Ok(BufReader::new(stream).lines())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CONTROL_TIMEOUT, Capabilities, ControlCommand, ControlListener, control_socket_path,
+        current_uid, daemon_debounce, daemon_mode, daemon_reachable, peer_uid,
+        read_only_socket_path, send_command, send_exec, wait_for_daemon, watch,
+    };
+    use crate::config::DebounceConfig;
+    use std::fs;
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixStream;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn round_trips_commands_over_socket() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = control_socket_path(dir.path());
+        let listener = ControlListener::bind(&path).expect("bind");
+
+        let client_path = path.clone();
+        let client = thread::spawn(move || {
+            send_command(&client_path, ControlCommand::Reload).expect("send")
+        });
+        let request = loop {
+            if let Some(request) = listener.poll().expect("poll") {
+                break request;
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(request.command, ControlCommand::Reload);
+        request.respond("ok").expect("respond");
+
+        assert_eq!(client.join().expect("join"), "ok");
+    }
+
+    #[test]
+    fn streams_lines_to_watchers() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = control_socket_path(dir.path());
+        let listener = ControlListener::bind(&path).expect("bind");
+
+        let mut lines = watch(&path).expect("watch");
+        let request = loop {
+            if let Some(request) = listener.poll().expect("poll") {
+                break request;
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(request.command, ControlCommand::Watch);
+        let mut subscriber = request.subscribe().expect("subscribe");
+        subscriber.send("first").expect("send");
+        subscriber.send("second").expect("send");
+        drop(subscriber);
+
+        let received = lines
+            .by_ref()
+            .map(|line| line.expect("line"))
+            .collect::<Vec<_>>();
+        assert_eq!(received, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn poll_returns_none_without_clients() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let listener = ControlListener::bind(&control_socket_path(dir.path())).expect("bind");
+
+        assert!(listener.poll().expect("poll").is_none());
+    }
+
+    #[test]
+    fn poll_does_not_wait_for_a_slow_client() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = control_socket_path(dir.path());
+        let listener = ControlListener::bind(&path).expect("bind");
+
+        let mut slow = UnixStream::connect(&path).expect("connect");
+        let started = Instant::now();
+        assert!(listener.poll().expect("poll").is_none());
+        assert!(started.elapsed() < CONTROL_TIMEOUT);
+        assert_eq!(listener.pending_fds().len(), 1);
+
+        slow.write_all(b"sta").expect("write");
+        assert!(listener.poll().expect("poll").is_none());
+        slow.write_all(b"tus\n").expect("write");
+        let request = listener.poll().expect("poll").expect("request");
+        assert_eq!(request.command, ControlCommand::Status);
+        assert!(listener.pending_fds().is_empty());
+    }
+
+    #[test]
+    fn replaces_stale_socket_and_removes_on_drop() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = control_socket_path(dir.path());
+        drop(std::os::unix::net::UnixListener::bind(&path).expect("stale"));
+        assert!(path.exists());
+
+        let listener = ControlListener::bind(&path).expect("bind");
+        assert!(ControlListener::bind(&path).is_err());
+        drop(listener);
+
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn waits_for_daemon_until_timeout() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = control_socket_path(dir.path());
+
+        assert!(!daemon_reachable(&path));
+        assert!(!wait_for_daemon(&path, Duration::from_millis(50)));
+
+        let _listener = ControlListener::bind(&path).expect("bind");
+        assert!(wait_for_daemon(&path, Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(ControlCommand::parse("stop\n"), Some(ControlCommand::Stop));
+        assert_eq!(
+            ControlCommand::parse("reload"),
+            Some(ControlCommand::Reload)
+        );
+        assert_eq!(ControlCommand::parse("state"), Some(ControlCommand::State));
+        assert_eq!(ControlCommand::parse("watch"), Some(ControlCommand::Watch));
+        assert_eq!(
+            ControlCommand::parse("status"),
+            Some(ControlCommand::Status)
+        );
+        assert_eq!(
+            ControlCommand::parse("history"),
+            Some(ControlCommand::History)
+        );
+        assert_eq!(
+            ControlCommand::parse("urgent"),
+            Some(ControlCommand::Urgent)
+        );
+        assert_eq!(
+            ControlCommand::parse("debounce"),
+            Some(ControlCommand::Debounce)
+        );
+        assert_eq!(ControlCommand::parse("exec"), Some(ControlCommand::Exec));
+        assert_eq!(ControlCommand::parse("restart"), None);
+    }
+
+    #[test]
+    fn parses_exec_arguments() {
+        assert_eq!(
+            ControlCommand::parse_line("exec switch 3\n"),
+            Some((
+                ControlCommand::Exec,
+                vec!["switch".to_string(), "3".to_string()]
+            ))
+        );
+        assert_eq!(
+            ControlCommand::parse_line("status\n"),
+            Some((ControlCommand::Status, Vec::new()))
+        );
+        assert_eq!(ControlCommand::parse_line("stop now"), None);
+        assert_eq!(ControlCommand::parse_line(""), None);
+    }
+
+    #[test]
+    fn round_trips_exec_requests() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = control_socket_path(dir.path());
+        let listener = ControlListener::bind(&path).expect("bind");
+
+        let client_path = path.clone();
+        let client = thread::spawn(move || {
+            send_exec(&client_path, &["cycle".to_string(), "next".to_string()]).expect("send")
+        });
+        let request = loop {
+            if let Some(request) = listener.poll().expect("poll") {
+                break request;
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(request.command, ControlCommand::Exec);
+        assert_eq!(request.args, vec!["cycle", "next"]);
+        request.respond("ok").expect("respond");
+
+        assert_eq!(client.join().expect("join"), "ok");
+        assert!(send_exec(&path, &["name:my web".to_string()]).is_err());
+    }
+
+    #[test]
+    fn creates_private_sockets() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let runtime_dir = dir.path().join("hyprspaces");
+        let _listener = ControlListener::bind_runtime_dir(&runtime_dir).expect("bind");
+
+        let mode = |path: &std::path::Path| {
+            fs::metadata(path).expect("metadata").permissions().mode() & 0o777
+        };
+        assert_eq!(mode(&runtime_dir), 0o700);
+        assert_eq!(mode(&control_socket_path(&runtime_dir)), 0o600);
+        assert_eq!(mode(&read_only_socket_path(&runtime_dir)), 0o600);
+    }
+
+    #[test]
+    fn reads_peer_uid() {
+        let (left, _right) = UnixStream::pair().expect("pair");
+
+        assert_eq!(peer_uid(&left).expect("peer uid"), current_uid());
+    }
+
+    #[test]
+    fn read_only_socket_refuses_control_commands() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let listener = ControlListener::bind_runtime_dir(dir.path()).expect("bind");
+        let path = read_only_socket_path(dir.path());
+
+        let client_path = path.clone();
+        let client = thread::spawn(move || {
+            let refused = send_command(&client_path, ControlCommand::Stop).expect("send");
+            let allowed = send_command(&client_path, ControlCommand::Status).expect("send");
+            (refused, allowed)
+        });
+        let request = loop {
+            if let Some(request) = listener.poll().expect("poll") {
+                break request;
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(request.command, ControlCommand::Status);
+        request.respond("running").expect("respond");
+
+        let (refused, allowed) = client.join().expect("join");
+        assert_eq!(
+            refused,
+            format!("error: 'stop' is not allowed on {}", path.display())
+        );
+        assert_eq!(allowed, "running");
+    }
+
+    #[test]
+    fn reads_daemon_mode_from_status() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let listener = ControlListener::bind_runtime_dir(dir.path()).expect("bind");
+        let path = read_only_socket_path(dir.path());
+
+        let client_path = path.clone();
+        let client = thread::spawn(move || {
+            let mode = daemon_mode(&client_path);
+            let safe_mode = daemon_mode(&client_path);
+            (mode, safe_mode)
+        });
+        for response in ["degraded", "safe mode: invalid config json"] {
+            let request = loop {
+                if let Some(request) = listener.poll().expect("poll") {
+                    break request;
+                }
+                thread::sleep(Duration::from_millis(5));
+            };
+            request.respond(response).expect("respond");
+        }
+
+        let (mode, safe_mode) = client.join().expect("join");
+        assert_eq!(mode.as_deref(), Some("degraded"));
+        assert_eq!(safe_mode, None);
+        assert_eq!(daemon_mode(&dir.path().join("missing.sock")), None);
+    }
+
+    #[test]
+    fn reads_daemon_debounce_windows() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let listener = ControlListener::bind_runtime_dir(dir.path()).expect("bind");
+        let path = read_only_socket_path(dir.path());
+
+        let client = thread::spawn(move || daemon_debounce(&path));
+        let request = loop {
+            if let Some(request) = listener.poll().expect("poll") {
+                break request;
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(request.command, ControlCommand::Debounce);
+        request
+            .respond(r#"{"rebalance_ms":350,"focus_ms":0}"#)
+            .expect("respond");
+
+        assert_eq!(
+            client.join().expect("join"),
+            Some(DebounceConfig {
+                rebalance_ms: 350,
+                focus_ms: 0,
+                ..Default::default()
+            })
+        );
+        assert_eq!(daemon_debounce(&dir.path().join("missing.sock")), None);
+    }
+
+    #[test]
+    fn maps_commands_to_capabilities() {
+        assert!(Capabilities::ALL.contains(ControlCommand::Reload.required()));
+        assert!(!Capabilities::READ.contains(ControlCommand::Stop.required()));
+        assert!(!Capabilities::READ.contains(ControlCommand::Exec.required()));
+        assert!(Capabilities::READ.contains(ControlCommand::Watch.required()));
+        assert_eq!(
+            Capabilities::READ | Capabilities::CONTROL,
+            Capabilities::ALL
+        );
+    }
+}
@@ -1,5 +1,12 @@
-use crate::config::Config;
-use crate::hyprctl::{HyprlandIpc, HyprctlError};
+use crate::config::{Config, DebounceConfig, DebounceMode, RebalanceMode};
+use crate::hyprctl::{
+    ANIMATIONS_ENABLED, BatchCache, HyprctlError, HyprlandIpc, HyprlandOptions, MonitorInfo,
+    SwitchStyle, WorkspaceInfo, logical_monitors, without_animations,
+};
+use crate::layout::Layout;
+use crate::replay::{Recorder, record_line};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::io::{self, BufRead, BufReader};
 use std::os::unix::net::UnixStream;
 use std::time::{Duration, Instant};
@@ -16,6 +23,10 @@ pub fn event_name(line: &str) -> &str {
 
 pub const DEFAULT_REBALANCE_DEBOUNCE: Duration = Duration::from_millis(200);
 pub const DEFAULT_FOCUS_SWITCH_DEBOUNCE: Duration = Duration::from_millis(100);
+pub const DEFAULT_ADAPTIVE_DEBOUNCE_CAP: Duration = Duration::from_millis(2000);
+pub const DEFAULT_RELOAD_QUIET: Duration = Duration::from_millis(500);
+pub const RECONNECT_ATTEMPTS: u32 = 50;
+pub const RECONNECT_INTERVAL: Duration = Duration::from_millis(200);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MonitorEventKind {
@@ -23,6 +34,7 @@ pub enum MonitorEventKind {
     Removed,
 }
 
+#[derive(Debug, Clone)]
 pub struct FocusEvent {
     pub at: Instant,
     pub workspace_id: Option<u32>,
@@ -30,13 +42,133 @@ pub struct FocusEvent {
     pub monitor_name: Option<String>,
 }
 
+#[derive(Debug, Clone)]
+pub enum WindowEvent {
+    Opened {
+        address: String,
+        workspace_id: Option<u32>,
+        class: Option<String>,
+    },
+    Closed {
+        address: String,
+    },
+    Moved {
+        address: String,
+        workspace_id: Option<u32>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorEventInfo {
+    pub id: Option<u32>,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl MonitorEventInfo {
+    pub fn parse(payload: &str, v2: bool) -> Option<Self> {
+        if !v2 {
+            let name = payload.trim();
+            return (!name.is_empty()).then(|| Self {
+                id: None,
+                name: name.to_string(),
+                description: None,
+            });
+        }
+        let mut fields = payload.splitn(3, ',');
+        let id = fields.next()?.parse().ok()?;
+        let name = fields.next().filter(|name| !name.is_empty())?;
+        Some(Self {
+            id: Some(id),
+            name: name.to_string(),
+            description: fields
+                .next()
+                .filter(|description| !description.is_empty())
+                .map(str::to_string),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum DaemonEvent {
     Focus(FocusEvent),
-    Monitor { kind: MonitorEventKind, at: Instant },
-    Timeout { at: Instant },
+    Monitor {
+        kind: MonitorEventKind,
+        at: Instant,
+        monitor: Option<MonitorEventInfo>,
+    },
+    Window(WindowEvent),
+    Urgent {
+        address: String,
+    },
+    ConfigReloaded {
+        at: Instant,
+    },
+    Timeout {
+        at: Instant,
+    },
     Disconnected,
 }
 
+impl DaemonEvent {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DaemonEvent::Focus(_) => "focus",
+            DaemonEvent::Monitor { .. } => "monitor",
+            DaemonEvent::Window(_) => "window",
+            DaemonEvent::Urgent { .. } => "urgent",
+            DaemonEvent::ConfigReloaded { .. } => "config_reloaded",
+            DaemonEvent::Timeout { .. } => "timeout",
+            DaemonEvent::Disconnected => "disconnected",
+        }
+    }
+
+    pub fn at(&self) -> Option<Instant> {
+        match self {
+            DaemonEvent::Focus(focus) => Some(focus.at),
+            DaemonEvent::Monitor { at, .. }
+            | DaemonEvent::ConfigReloaded { at }
+            | DaemonEvent::Timeout { at } => Some(*at),
+            DaemonEvent::Window(_) | DaemonEvent::Urgent { .. } | DaemonEvent::Disconnected => None,
+        }
+    }
+
+    pub fn trigger(&self) -> String {
+        match self {
+            DaemonEvent::Focus(focus) => {
+                match (focus.workspace_id, focus.window_address.as_deref()) {
+                    (Some(workspace_id), _) => format!("focus workspace {workspace_id}"),
+                    (None, Some(address)) => format!("focus window {address}"),
+                    (None, None) => "focus".to_string(),
+                }
+            }
+            DaemonEvent::Monitor { kind, monitor, .. } => {
+                let action = match kind {
+                    MonitorEventKind::Added => "monitor added",
+                    MonitorEventKind::Removed => "monitor removed",
+                };
+                match monitor {
+                    Some(monitor) => format!("{action} {}", monitor.name),
+                    None => action.to_string(),
+                }
+            }
+            DaemonEvent::Window(WindowEvent::Opened { address, .. }) => {
+                format!("window opened {address}")
+            }
+            DaemonEvent::Window(WindowEvent::Closed { address }) => {
+                format!("window closed {address}")
+            }
+            DaemonEvent::Window(WindowEvent::Moved { address, .. }) => {
+                format!("window moved {address}")
+            }
+            DaemonEvent::Urgent { address } => format!("window urgent {address}"),
+            DaemonEvent::ConfigReloaded { .. } => "config reloaded".to_string(),
+            DaemonEvent::Timeout { .. } => "debounce flush".to_string(),
+            DaemonEvent::Disconnected => "disconnected".to_string(),
+        }
+    }
+}
+
 pub enum EventSourceKind {
     Socket2,
     #[cfg(feature = "native-ipc")]
@@ -50,6 +182,7 @@ pub trait EventSource {
 pub struct Socket2EventSource {
     reader: BufReader<UnixStream>,
     line: String,
+    recorder: Option<Recorder>,
 }
 
 impl Socket2EventSource {
@@ -58,8 +191,14 @@ impl Socket2EventSource {
         Ok(Self {
             reader: BufReader::new(stream),
             line: String::new(),
+            recorder: None,
         })
     }
+
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
 }
 
 impl EventSource for Socket2EventSource {
@@ -73,6 +212,7 @@ impl EventSource for Socket2EventSource {
                     if trimmed.is_empty() {
                         continue;
                     }
+                    record_line(&mut self.recorder, trimmed);
                     if let Some(event) = parse_socket2_event(trimmed, Instant::now()) {
                         return Ok(event);
                     }
@@ -110,6 +250,7 @@ impl NativeEventSource {
                 let _ = added_sender.send(DaemonEvent::Monitor {
                     kind: MonitorEventKind::Added,
                     at: Instant::now(),
+                    monitor: None,
                 });
             });
             let removed_sender = sender.clone();
@@ -117,6 +258,7 @@ impl NativeEventSource {
                 let _ = removed_sender.send(DaemonEvent::Monitor {
                     kind: MonitorEventKind::Removed,
                     at: Instant::now(),
+                    monitor: None,
                 });
             });
             let workspace_sender = sender.clone();
@@ -159,6 +301,37 @@ impl NativeEventSource {
                     }));
                 }
             });
+            let opened_sender = sender.clone();
+            listener.add_window_opened_handler(move |window| {
+                let _ = opened_sender.send(DaemonEvent::Window(WindowEvent::Opened {
+                    address: window.window_address.to_string(),
+                    workspace_id: parse_workspace_id_from_name(&window.workspace_name),
+                    class: Some(window.window_class).filter(|class| !class.is_empty()),
+                }));
+            });
+            let closed_sender = sender.clone();
+            listener.add_window_closed_handler(move |address| {
+                let _ = closed_sender.send(DaemonEvent::Window(WindowEvent::Closed {
+                    address: address.to_string(),
+                }));
+            });
+            let moved_sender = sender.clone();
+            listener.add_window_moved_handler(move |window| {
+                let _ = moved_sender.send(DaemonEvent::Window(WindowEvent::Moved {
+                    address: window.window_address.to_string(),
+                    workspace_id: workspace_id_from_native(window.workspace_id),
+                }));
+            });
+            let urgent_sender = sender.clone();
+            listener.add_urgent_state_changed_handler(move |address| {
+                let _ = urgent_sender.send(DaemonEvent::Urgent {
+                    address: address.to_string(),
+                });
+            });
+            let reloaded_sender = sender.clone();
+            listener.add_config_reloaded_handler(move || {
+                let _ = reloaded_sender.send(DaemonEvent::ConfigReloaded { at: Instant::now() });
+            });
             let _ = listener.instance_start_listener(&instance);
             let _ = sender.send(DaemonEvent::Disconnected);
         });
@@ -175,18 +348,41 @@ impl EventSource for NativeEventSource {
             Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
                 Ok(DaemonEvent::Timeout { at: Instant::now() })
             }
-            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
-                Ok(DaemonEvent::Disconnected)
-            }
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => Ok(DaemonEvent::Disconnected),
         }
     }
 }
 
+/// Decides when bursts of monitor events turn into a rebalance.
+pub trait DebounceStrategy {
+    /// Records a monitor event; true means rebalance right away.
+    fn record_event(&mut self, now: Instant) -> bool;
+    /// True when a held-back rebalance is due.
+    fn flush(&mut self, now: Instant) -> bool;
+    fn take_pending(&mut self) -> bool;
+    fn flush_deadline(&self) -> Option<Instant>;
+    fn reset(&mut self);
+    fn suppressed(&self) -> u64;
+    fn mode(&self) -> DebounceMode;
+    fn configure(&mut self, debounce: &DebounceConfig);
+}
+
+pub fn rebalance_debounce(debounce: &DebounceConfig) -> Box<dyn DebounceStrategy> {
+    match debounce.mode {
+        DebounceMode::Fixed => Box::new(RebalanceDebounce::new(debounce.rebalance())),
+        DebounceMode::Adaptive => Box::new(AdaptiveDebounce::new(
+            debounce.rebalance(),
+            debounce.max_rebalance(),
+        )),
+    }
+}
+
 pub struct RebalanceDebounce {
     min_interval: Duration,
     last_rebalance: Option<Instant>,
     last_event: Option<Instant>,
     pending: bool,
+    suppressed: u64,
 }
 
 impl RebalanceDebounce {
@@ -196,9 +392,23 @@ impl RebalanceDebounce {
             last_rebalance: None,
             last_event: None,
             pending: false,
+            suppressed: 0,
+        }
+    }
+
+    pub fn set_interval(&mut self, min_interval: Duration) {
+        self.min_interval = min_interval;
+    }
+
+    fn should_run_now(&self, now: Instant) -> bool {
+        match self.last_rebalance {
+            None => true,
+            Some(last) => now.duration_since(last) >= self.min_interval,
         }
     }
+}
 
+impl DebounceStrategy for RebalanceDebounce {
     fn record_event(&mut self, now: Instant) -> bool {
         self.last_event = Some(now);
         if self.should_run_now(now) {
@@ -207,6 +417,7 @@ impl RebalanceDebounce {
             true
         } else {
             self.pending = true;
+            self.suppressed += 1;
             false
         }
     }
@@ -230,18 +441,311 @@ impl RebalanceDebounce {
         true
     }
 
-    fn should_run_now(&self, now: Instant) -> bool {
-        match self.last_rebalance {
-            None => true,
-            Some(last) => now.duration_since(last) >= self.min_interval,
+    fn take_pending(&mut self) -> bool {
+        std::mem::take(&mut self.pending)
+    }
+
+    fn flush_deadline(&self) -> Option<Instant> {
+        if !self.pending {
+            return None;
+        }
+        let after_event = self.last_event? + self.min_interval;
+        Some(match self.last_rebalance {
+            Some(last) => after_event.max(last + self.min_interval),
+            None => after_event,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.last_rebalance = None;
+        self.last_event = None;
+        self.pending = false;
+    }
+
+    fn suppressed(&self) -> u64 {
+        self.suppressed
+    }
+
+    fn mode(&self) -> DebounceMode {
+        DebounceMode::Fixed
+    }
+
+    fn configure(&mut self, debounce: &DebounceConfig) {
+        self.set_interval(debounce.rebalance());
+    }
+}
+
+/// Doubles the quiet window, up to `cap`, while monitor events keep arriving.
+pub struct AdaptiveDebounce {
+    base: Duration,
+    cap: Duration,
+    window: Duration,
+    last_event: Option<Instant>,
+    pending: bool,
+    suppressed: u64,
+}
+
+impl AdaptiveDebounce {
+    pub fn new(base: Duration, cap: Duration) -> Self {
+        Self {
+            base,
+            cap: cap.max(base),
+            window: base,
+            last_event: None,
+            pending: false,
+            suppressed: 0,
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+}
+
+impl DebounceStrategy for AdaptiveDebounce {
+    fn record_event(&mut self, now: Instant) -> bool {
+        if self.pending {
+            self.window = (self.window * 2).min(self.cap);
+            self.suppressed += 1;
+        } else {
+            self.window = self.base;
+        }
+        self.pending = true;
+        self.last_event = Some(now);
+        false
+    }
+
+    fn flush(&mut self, now: Instant) -> bool {
+        match self.flush_deadline() {
+            Some(deadline) if now >= deadline => self.take_pending(),
+            _ => false,
+        }
+    }
+
+    fn take_pending(&mut self) -> bool {
+        self.window = self.base;
+        std::mem::take(&mut self.pending)
+    }
+
+    fn flush_deadline(&self) -> Option<Instant> {
+        if !self.pending {
+            return None;
+        }
+        Some(self.last_event? + self.window)
+    }
+
+    fn reset(&mut self) {
+        self.window = self.base;
+        self.last_event = None;
+        self.pending = false;
+    }
+
+    fn suppressed(&self) -> u64 {
+        self.suppressed
+    }
+
+    fn mode(&self) -> DebounceMode {
+        DebounceMode::Adaptive
+    }
+
+    fn configure(&mut self, debounce: &DebounceConfig) {
+        self.base = debounce.rebalance();
+        self.cap = debounce.max_rebalance().max(self.base);
+        self.window = self.window.clamp(self.base, self.cap);
+    }
+}
+
+pub const SLOT_HISTORY_LEN: usize = 32;
+
+#[derive(Debug, Default)]
+pub struct SlotHistory {
+    slots: Vec<u32>,
+}
+
+impl SlotHistory {
+    pub fn record(&mut self, slot: u32) {
+        if self.slots.first() == Some(&slot) {
+            return;
+        }
+        self.slots.retain(|recent| *recent != slot);
+        self.slots.insert(0, slot);
+        self.slots.truncate(SLOT_HISTORY_LEN);
+    }
+
+    pub fn slots(&self) -> &[u32] {
+        &self.slots
+    }
+}
+
+pub struct HotplugSettle {
+    window: Duration,
+    deadline: Option<Instant>,
+}
+
+impl HotplugSettle {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            deadline: None,
         }
     }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.window.is_zero()
+    }
+
+    pub fn record(&mut self, at: Instant) {
+        self.deadline = Some(at + self.window);
+    }
+
+    pub fn due(&mut self, now: Instant) -> bool {
+        match self.deadline {
+            Some(deadline) if now >= deadline => {
+                self.deadline = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+}
+
+/// Holds off events during a config reload burst, then resyncs once.
+pub struct ReloadQuiesce {
+    window: Duration,
+    deadline: Option<Instant>,
+}
+
+impl ReloadQuiesce {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            deadline: None,
+        }
+    }
+
+    pub fn record(&mut self, at: Instant) {
+        self.deadline = Some(at + self.window);
+    }
+
+    pub fn is_quiet(&self, now: Instant) -> bool {
+        self.deadline.is_some_and(|deadline| now < deadline)
+    }
+
+    pub fn due(&mut self, now: Instant) -> bool {
+        match self.deadline {
+            Some(deadline) if now >= deadline => {
+                self.deadline = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn reset(&mut self) {
+        self.deadline = None;
+    }
+}
+
+/// How long a restore-launched application's window is waited for.
+pub const LAUNCH_PLACEMENT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Windows a cold restore launched, oldest first per class, with their workspace.
+#[derive(Debug)]
+pub struct LaunchPlacements {
+    window: Duration,
+    pending: Vec<(String, String, Instant)>,
+}
+
+impl LaunchPlacements {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn expect(&mut self, class: impl Into<String>, workspace: impl Into<String>, now: Instant) {
+        self.pending.push((class.into(), workspace.into(), now));
+    }
+
+    pub fn claim(&mut self, class: &str, now: Instant) -> Option<String> {
+        self.pending
+            .retain(|(_, _, at)| now.saturating_duration_since(*at) < self.window);
+        let index = self
+            .pending
+            .iter()
+            .position(|(expected, _, _)| expected.eq_ignore_ascii_case(class))?;
+        Some(self.pending.remove(index).1)
+    }
+}
+
+pub struct DragGuard {
+    delay: Duration,
+    deadline: Option<Instant>,
+    pending: Option<FocusEvent>,
+}
+
+impl DragGuard {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            deadline: None,
+            pending: None,
+        }
+    }
+
+    pub fn hold(&mut self, focus: &FocusEvent) -> bool {
+        if self.delay.is_zero() {
+            return false;
+        }
+        if focus.monitor_name.is_some() {
+            self.deadline = Some(focus.at + self.delay);
+        } else if self.deadline.is_none() {
+            return false;
+        }
+        self.pending = Some(focus.clone());
+        true
+    }
+
+    pub fn record_move(&mut self, at: Instant) {
+        if self.deadline.is_some() {
+            self.deadline = Some(at + self.delay);
+        }
+    }
+
+    pub fn due(&mut self, now: Instant) -> Option<FocusEvent> {
+        match self.deadline {
+            Some(deadline) if now >= deadline => {
+                self.deadline = None;
+                self.pending.take()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn reset(&mut self) {
+        self.deadline = None;
+        self.pending = None;
+    }
 }
 
 pub struct FocusSwitchDebounce {
     min_interval: Duration,
     last_switch: Option<Instant>,
     last_workspace: Option<u32>,
+    suppressed: u64,
 }
 
 impl FocusSwitchDebounce {
@@ -250,9 +754,18 @@ impl FocusSwitchDebounce {
             min_interval,
             last_switch: None,
             last_workspace: None,
+            suppressed: 0,
         }
     }
 
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed
+    }
+
+    pub fn set_interval(&mut self, min_interval: Duration) {
+        self.min_interval = min_interval;
+    }
+
     fn should_switch(&mut self, now: Instant, workspace: u32) -> bool {
         let recent_same_workspace = match (self.last_switch, self.last_workspace) {
             (Some(last_switch), Some(last_workspace)) if last_workspace == workspace => {
@@ -261,12 +774,18 @@ impl FocusSwitchDebounce {
             _ => false,
         };
         if recent_same_workspace {
+            self.suppressed += 1;
             return false;
         }
         self.last_switch = Some(now);
         self.last_workspace = Some(workspace);
         true
     }
+
+    pub fn reset(&mut self) {
+        self.last_switch = None;
+        self.last_workspace = None;
+    }
 }
 
 pub fn socket2_path(runtime_dir: &str, instance_signature: &str) -> String {
@@ -295,16 +814,18 @@ fn parse_second_field(payload: &str) -> Option<u32> {
         .and_then(|(_, second)| second.parse().ok())
 }
 
-fn parse_socket2_event(line: &str, at: Instant) -> Option<DaemonEvent> {
+pub fn parse_socket2_event(line: &str, at: Instant) -> Option<DaemonEvent> {
     let (name, payload) = line.split_once(">>")?;
     match name {
         "monitoradded" | "monitoraddedv2" => Some(DaemonEvent::Monitor {
             kind: MonitorEventKind::Added,
             at,
+            monitor: MonitorEventInfo::parse(payload, name.ends_with("v2")),
         }),
         "monitorremoved" | "monitorremovedv2" => Some(DaemonEvent::Monitor {
             kind: MonitorEventKind::Removed,
             at,
+            monitor: MonitorEventInfo::parse(payload, name.ends_with("v2")),
         }),
         "workspacev2" => parse_first_field(payload).map(|workspace_id| {
             DaemonEvent::Focus(FocusEvent {
@@ -341,75 +862,545 @@ fn parse_socket2_event(line: &str, at: Instant) -> Option<DaemonEvent> {
                 Some(DaemonEvent::Focus(FocusEvent {
                     at,
                     workspace_id: None,
-                    window_address: Some(address.to_string()),
+                    window_address: Some(normalize_address(address)),
                     monitor_name: None,
                 }))
             }
         }
+        "openwindow" => {
+            let mut fields = payload.splitn(4, ',');
+            let address = fields.next().filter(|address| !address.is_empty())?;
+            Some(DaemonEvent::Window(WindowEvent::Opened {
+                address: normalize_address(address),
+                workspace_id: fields.next().and_then(parse_workspace_id_from_name),
+                class: fields
+                    .next()
+                    .filter(|class| !class.is_empty())
+                    .map(str::to_string),
+            }))
+        }
+        "closewindow" => {
+            let address = payload.trim();
+            (!address.is_empty()).then(|| {
+                DaemonEvent::Window(WindowEvent::Closed {
+                    address: normalize_address(address),
+                })
+            })
+        }
+        "movewindowv2" => {
+            let mut fields = payload.splitn(3, ',');
+            let address = fields.next().filter(|address| !address.is_empty())?;
+            Some(DaemonEvent::Window(WindowEvent::Moved {
+                address: normalize_address(address),
+                workspace_id: fields.next().and_then(|id| id.parse().ok()),
+            }))
+        }
+        "urgent" => {
+            let address = payload.trim();
+            (!address.is_empty()).then(|| DaemonEvent::Urgent {
+                address: normalize_address(address),
+            })
+        }
+        "configreloaded" => Some(DaemonEvent::ConfigReloaded { at }),
         _ => None,
     }
 }
 
-fn monitor_name_for_workspace(
-    hyprctl: &dyn HyprlandIpc,
-    workspace_id: u32,
-) -> Result<Option<String>, HyprctlError> {
-    let workspaces = hyprctl.workspaces()?;
-    Ok(workspaces
-        .iter()
-        .find(|workspace| workspace.id == workspace_id)
-        .and_then(|workspace| workspace.monitor.clone()))
+fn normalize_address(address: &str) -> String {
+    if address.starts_with("0x") {
+        address.to_string()
+    } else {
+        format!("0x{address}")
+    }
 }
 
-#[cfg(feature = "native-ipc")]
-fn workspace_id_from_native(id: hyprland::shared::WorkspaceId) -> Option<u32> {
-    if id <= 0 {
-        return None;
-    }
-    u32::try_from(id).ok()
+/// What the daemon is doing as a whole; see [`DaemonMode::policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonMode {
+    Starting,
+    /// Both paired monitors are connected.
+    Active,
+    /// Hyprland is unreachable.
+    Paused,
+    /// No configured pair is connected; workspaces pass through untouched.
+    Degraded,
+    /// Waiting out a config-reload or hotplug window before resyncing once.
+    Resyncing,
+    ShuttingDown,
 }
 
-#[cfg(feature = "native-ipc")]
-fn workspace_id_from_workspace_type(
-    workspace: &hyprland::shared::WorkspaceType,
-) -> Option<u32> {
-    match workspace {
-        hyprland::shared::WorkspaceType::Regular(name) => parse_workspace_id_from_name(name),
-        hyprland::shared::WorkspaceType::Special(_) => None,
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeTrigger {
+    /// A config was (re)selected for the connected monitors.
+    Selected {
+        paired: bool,
+    },
+    SocketLost,
+    Unresponsive,
+    /// A reload or hotplug settle window started.
+    Settling,
+    Stop,
 }
 
-pub fn should_rebalance(line: &str) -> bool {
-    matches!(
-        parse_socket2_event(line, Instant::now()),
-        Some(DaemonEvent::Monitor { .. })
-    )
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModePolicy {
+    pub follow_focus: bool,
+    pub rebalance: bool,
+    pub schedules: bool,
+    pub reconcile: bool,
 }
 
-pub fn rebalance_batch_for_event(
-    primary: &str,
-    secondary: &str,
-    offset: u32,
-    line: &str,
-) -> Option<String> {
-    match parse_socket2_event(line, Instant::now()) {
-        Some(DaemonEvent::Monitor { .. }) => {
-            Some(crate::hyprctl::rebalance_batch(primary, secondary, offset))
-        }
-        _ => None,
-    }
+impl ModePolicy {
+    const ALL: Self = Self {
+        follow_focus: true,
+        rebalance: true,
+        schedules: true,
+        reconcile: true,
+    };
+    const NONE: Self = Self {
+        follow_focus: false,
+        rebalance: false,
+        schedules: false,
+        reconcile: false,
+    };
 }
 
-pub fn rebalance_all(
-    hyprctl: &dyn HyprlandIpc,
+impl DaemonMode {
+    pub fn next(self, trigger: ModeTrigger) -> Self {
+        match (self, trigger) {
+            (DaemonMode::ShuttingDown, _) | (_, ModeTrigger::Stop) => DaemonMode::ShuttingDown,
+            (_, ModeTrigger::Selected { paired: true }) => DaemonMode::Active,
+            (_, ModeTrigger::Selected { paired: false }) => DaemonMode::Degraded,
+            (_, ModeTrigger::SocketLost | ModeTrigger::Unresponsive) => DaemonMode::Paused,
+            (DaemonMode::Active | DaemonMode::Degraded, ModeTrigger::Settling) => {
+                DaemonMode::Resyncing
+            }
+            (mode, ModeTrigger::Settling) => mode,
+        }
+    }
+
+    pub fn policy(self) -> ModePolicy {
+        match self {
+            DaemonMode::Active => ModePolicy::ALL,
+            DaemonMode::Starting
+            | DaemonMode::Paused
+            | DaemonMode::Degraded
+            | DaemonMode::Resyncing
+            | DaemonMode::ShuttingDown => ModePolicy::NONE,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DaemonMode::Starting => "starting",
+            DaemonMode::Active => "active",
+            DaemonMode::Paused => "paused",
+            DaemonMode::Degraded => "degraded",
+            DaemonMode::Resyncing => "resyncing",
+            DaemonMode::ShuttingDown => "shutting_down",
+        }
+    }
+
+    pub fn bar_mode(self) -> BarMode {
+        match self {
+            DaemonMode::Paused => BarMode::Disconnected,
+            DaemonMode::Degraded => BarMode::Passthrough,
+            DaemonMode::Starting
+            | DaemonMode::Active
+            | DaemonMode::Resyncing
+            | DaemonMode::ShuttingDown => BarMode::Active,
+        }
+    }
+}
+
+/// What the daemon did with one event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Dispatched,
+    ReloadQuiet,
+    HotplugSettling,
+    ModePolicy,
+    Unpaired,
+    DragGuard,
+    PairShown,
+    Held,
+    Debounced,
+    NoAction,
+    Unresponsive,
+}
+
+impl Decision {
+    /// Refines a `Dispatched` verdict once the outcome is known.
+    pub fn settle(self, sent: bool, debounced: bool) -> Self {
+        match self {
+            Decision::Dispatched if sent => Decision::Dispatched,
+            Decision::Dispatched if debounced => Decision::Debounced,
+            Decision::Dispatched => Decision::NoAction,
+            decision => decision,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Decision::Dispatched => "dispatched",
+            Decision::ReloadQuiet => "reload_quiet",
+            Decision::HotplugSettling => "hotplug_settling",
+            Decision::ModePolicy => "mode_policy",
+            Decision::Unpaired => "unpaired",
+            Decision::DragGuard => "drag_guard",
+            Decision::PairShown => "pair_shown",
+            Decision::Held => "held",
+            Decision::Debounced => "debounced",
+            Decision::NoAction => "no_action",
+            Decision::Unresponsive => "unresponsive",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BarMode {
+    #[default]
+    Active,
+    Passthrough,
+    Disconnected,
+}
+
+impl BarMode {
+    pub fn class(self) -> &'static str {
+        match self {
+            BarMode::Active => "workspaces",
+            BarMode::Passthrough => "passthrough",
+            BarMode::Disconnected => "disconnected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BarState {
+    pub active_workspace: u32,
+    pub workspaces: Vec<WorkspaceInfo>,
+    #[serde(default)]
+    pub mode: BarMode,
+    /// Workspace shown on each monitor, when known.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub visible: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Default)]
+pub struct StateCache {
+    active_workspace: Option<u32>,
+    windows: HashMap<String, u32>,
+    monitors: Option<Vec<MonitorEventInfo>>,
+    focused_monitor: Option<String>,
+    visible: HashMap<String, u32>,
+    urgent: Option<String>,
+}
+
+impl StateCache {
+    pub fn seed(&mut self, hyprctl: &dyn HyprlandIpc) -> Result<(), HyprctlError> {
+        self.refresh_windows(hyprctl)?;
+        self.active_workspace = hyprctl.active_workspace_id().ok();
+        self.monitors = None;
+        self.focused_monitor = None;
+        self.visible.clear();
+        Ok(())
+    }
+
+    pub fn connected_monitors(
+        &mut self,
+        hyprctl: &dyn HyprlandIpc,
+    ) -> Result<Vec<String>, HyprctlError> {
+        if self.monitors.is_none() {
+            self.monitors = Some(
+                logical_monitors(hyprctl.monitors()?)
+                    .into_iter()
+                    .map(|monitor| MonitorEventInfo {
+                        id: u32::try_from(monitor.id).ok(),
+                        name: monitor.name,
+                        description: None,
+                    })
+                    .collect(),
+            );
+        }
+        Ok(self
+            .monitors
+            .iter()
+            .flatten()
+            .map(|monitor| monitor.name.clone())
+            .collect())
+    }
+
+    pub fn monitor_name(&self, id: u32) -> Option<&str> {
+        self.monitors
+            .iter()
+            .flatten()
+            .find(|monitor| monitor.id == Some(id))
+            .map(|monitor| monitor.name.as_str())
+    }
+
+    fn apply_monitor(&mut self, kind: MonitorEventKind, info: Option<&MonitorEventInfo>) {
+        match (self.monitors.as_mut(), info, kind) {
+            (Some(monitors), Some(info), MonitorEventKind::Removed) => {
+                monitors.retain(|monitor| monitor.name != info.name);
+            }
+            _ => self.monitors = None,
+        }
+    }
+
+    fn refresh_windows(&mut self, hyprctl: &dyn HyprlandIpc) -> Result<(), HyprctlError> {
+        self.windows = hyprctl
+            .clients()?
+            .into_iter()
+            .map(|client| (client.address, client.workspace.id))
+            .collect();
+        Ok(())
+    }
+
+    pub fn active_workspace(&self) -> Option<u32> {
+        self.active_workspace
+    }
+
+    pub fn window_workspace(&self, address: &str) -> Option<u32> {
+        self.windows.get(address).copied()
+    }
+
+    /// The most recent window still asking for attention.
+    pub fn urgent(&self) -> Option<&str> {
+        self.urgent.as_deref()
+    }
+
+    pub fn visible_workspace(&self, monitor: &str) -> Option<u32> {
+        self.visible.get(monitor).copied()
+    }
+
+    pub fn shows_pair(&self, config: &Config, workspace_id: u32) -> bool {
+        let layout = Layout::for_config(config);
+        let slot = layout.slot(workspace_id);
+        layout.monitors().all(|monitor| {
+            self.visible_workspace(monitor).is_some_and(|visible| {
+                layout.slot(visible) == slot && layout.monitor_for(visible) == Some(monitor)
+            })
+        })
+    }
+
+    pub fn resolve(
+        &mut self,
+        hyprctl: &dyn HyprlandIpc,
+        event: DaemonEvent,
+    ) -> Result<DaemonEvent, HyprctlError> {
+        let DaemonEvent::Focus(mut focus) = event else {
+            return Ok(event);
+        };
+        if focus.workspace_id.is_none()
+            && let Some(address) = focus.window_address.as_deref()
+        {
+            if self.window_workspace(address).is_none() {
+                self.refresh_windows(hyprctl)?;
+            }
+            focus.workspace_id = self.window_workspace(address);
+        }
+        Ok(DaemonEvent::Focus(focus))
+    }
+
+    pub fn apply(&mut self, event: &DaemonEvent) -> bool {
+        let before = self.bar_state();
+        match event {
+            DaemonEvent::Focus(focus) => {
+                let workspace_id = focus.workspace_id.or_else(|| {
+                    focus
+                        .window_address
+                        .as_deref()
+                        .and_then(|address| self.window_workspace(address))
+                });
+                if workspace_id.is_some() {
+                    self.active_workspace = workspace_id;
+                }
+                if focus.window_address.is_some() && focus.window_address == self.urgent {
+                    self.urgent = None;
+                }
+                if let Some(monitor) = &focus.monitor_name {
+                    self.focused_monitor = Some(monitor.clone());
+                }
+                if let (Some(monitor), Some(workspace_id)) = (&self.focused_monitor, workspace_id)
+                    && workspace_id > 0
+                {
+                    self.visible.insert(monitor.clone(), workspace_id);
+                }
+            }
+            DaemonEvent::Window(WindowEvent::Opened {
+                address,
+                workspace_id,
+                ..
+            })
+            | DaemonEvent::Window(WindowEvent::Moved {
+                address,
+                workspace_id,
+            }) => match workspace_id {
+                Some(workspace_id) => {
+                    self.windows.insert(address.clone(), *workspace_id);
+                }
+                None => {
+                    self.windows.remove(address);
+                }
+            },
+            DaemonEvent::Window(WindowEvent::Closed { address }) => {
+                self.windows.remove(address);
+                if self.urgent.as_ref() == Some(address) {
+                    self.urgent = None;
+                }
+            }
+            DaemonEvent::Urgent { address } => {
+                self.urgent = Some(address.clone());
+            }
+            DaemonEvent::Monitor { kind, monitor, .. } => {
+                self.apply_monitor(*kind, monitor.as_ref());
+                self.focused_monitor = None;
+                self.visible.clear();
+            }
+            DaemonEvent::ConfigReloaded { .. }
+            | DaemonEvent::Timeout { .. }
+            | DaemonEvent::Disconnected => {}
+        }
+        self.bar_state() != before
+    }
+
+    pub fn bar_state(&self) -> Option<BarState> {
+        let active_workspace = self.active_workspace?;
+        let mut counts = BTreeMap::new();
+        for workspace_id in self.windows.values() {
+            *counts.entry(*workspace_id).or_insert(0) += 1;
+        }
+        Some(BarState {
+            active_workspace,
+            workspaces: counts
+                .into_iter()
+                .map(|(id, windows)| WorkspaceInfo {
+                    id,
+                    windows,
+                    name: None,
+                    monitor: None,
+                })
+                .collect(),
+            mode: BarMode::Active,
+            visible: self
+                .visible
+                .iter()
+                .map(|(monitor, workspace)| (monitor.clone(), *workspace))
+                .collect(),
+        })
+    }
+}
+
+fn monitor_name_for_workspace(
+    hyprctl: &dyn HyprlandIpc,
+    workspace_id: u32,
+) -> Result<Option<String>, HyprctlError> {
+    let workspaces = hyprctl.workspaces()?;
+    Ok(workspaces
+        .iter()
+        .find(|workspace| workspace.id == workspace_id)
+        .and_then(|workspace| workspace.monitor.clone()))
+}
+
+#[cfg(feature = "native-ipc")]
+fn workspace_id_from_native(id: hyprland::shared::WorkspaceId) -> Option<u32> {
+    if id <= 0 {
+        return None;
+    }
+    u32::try_from(id).ok()
+}
+
+#[cfg(feature = "native-ipc")]
+fn workspace_id_from_workspace_type(workspace: &hyprland::shared::WorkspaceType) -> Option<u32> {
+    match workspace {
+        hyprland::shared::WorkspaceType::Regular(name) => parse_workspace_id_from_name(name),
+        hyprland::shared::WorkspaceType::Special(_) => None,
+    }
+}
+
+pub fn should_rebalance(line: &str) -> bool {
+    matches!(
+        parse_socket2_event(line, Instant::now()),
+        Some(DaemonEvent::Monitor { .. })
+    )
+}
+
+pub fn rebalance_batch_for_event(
+    primary: &str,
+    secondary: &str,
+    offset: u32,
+    line: &str,
+) -> Option<String> {
+    match parse_socket2_event(line, Instant::now()) {
+        Some(DaemonEvent::Monitor { .. }) => {
+            Some(crate::hyprctl::rebalance_batch(primary, secondary, offset))
+        }
+        _ => None,
+    }
+}
+
+pub fn rebalance_all(hyprctl: &dyn HyprlandIpc, config: &Config) -> Result<(), HyprctlError> {
+    rebalance_all_cached(hyprctl, config, &mut BatchCache::default())
+}
+
+pub fn rebalance_all_cached(
+    hyprctl: &dyn HyprlandIpc,
     config: &Config,
+    batches: &mut BatchCache,
 ) -> Result<(), HyprctlError> {
-    let batch = crate::hyprctl::rebalance_batch(
-        &config.primary_monitor,
-        &config.secondary_monitor,
-        config.paired_offset,
-    );
-    hyprctl.batch(&batch).map(|_| ())
+    let layout = Layout::for_config(config);
+    let preserved;
+    let batch = match config.rebalance_mode {
+        RebalanceMode::Strict => batches.rebalance(&layout),
+        RebalanceMode::Preserve => {
+            preserved = layout.preserving_rebalance_batch(&hyprctl.workspaces()?);
+            preserved.as_str()
+        }
+    };
+    if batch.is_empty() {
+        return Ok(());
+    }
+    // Never turn on animations the user had off.
+    if config.instant_rebalance
+        && hyprctl
+            .get_option(ANIMATIONS_ENABLED)
+            .is_ok_and(|option| option.as_bool() == Some(true))
+    {
+        return hyprctl.batch(&without_animations(batch)).map(|_| ());
+    }
+    hyprctl.batch(batch).map(|_| ())
+}
+
+/// Resolves monitor matches, then picks the profile for the connected monitors.
+pub fn select_config(
+    hyprctl: &dyn HyprlandIpc,
+    base: &Config,
+) -> Result<Option<Config>, HyprctlError> {
+    let monitors = logical_monitors(hyprctl.monitors()?);
+    let mut base = base.clone();
+    base.resolve_monitors(&monitors);
+    if !base.monitors_resolved() {
+        return Ok(None);
+    }
+    let connected = monitors
+        .into_iter()
+        .map(|monitor| monitor.name)
+        .collect::<Vec<_>>();
+    Ok(base.for_monitors(&connected))
+}
+
+/// Like [`select_config`], but reuses cached names when the config allows.
+pub fn select_config_cached(
+    hyprctl: &dyn HyprlandIpc,
+    base: &Config,
+    cache: &mut StateCache,
+) -> Result<Option<Config>, HyprctlError> {
+    if base.has_monitor_matches() {
+        return select_config(hyprctl, base);
+    }
+    Ok(base.for_monitors(&cache.connected_monitors(hyprctl)?))
 }
 
 pub fn rebalance_for_event(
@@ -423,12 +1414,7 @@ pub fn rebalance_for_event(
     ) {
         return Ok(false);
     }
-    let batch = crate::hyprctl::rebalance_batch(
-        &config.primary_monitor,
-        &config.secondary_monitor,
-        config.paired_offset,
-    );
-    hyprctl.batch(&batch)?;
+    rebalance_all(hyprctl, config)?;
     Ok(true)
 }
 
@@ -452,7 +1438,14 @@ pub fn focus_switch_for_event_at(
         Some(DaemonEvent::Focus(focus)) => focus,
         _ => return Ok(false),
     };
-    focus_switch_for_focus_event_at(hyprctl, config, &focus, debounce)
+    focus_switch_for_focus_event_at(
+        hyprctl,
+        config,
+        &focus,
+        debounce,
+        &mut BatchCache::default(),
+        &HyprlandOptions::default(),
+    )
 }
 
 fn focus_switch_for_focus_event_at(
@@ -460,6 +1453,8 @@ fn focus_switch_for_focus_event_at(
     config: &Config,
     focus: &FocusEvent,
     debounce: &mut FocusSwitchDebounce,
+    batches: &mut BatchCache,
+    options: &HyprlandOptions,
 ) -> Result<bool, HyprctlError> {
     let workspace_id = if let Some(workspace_id) = focus.workspace_id {
         Some(workspace_id)
@@ -473,11 +1468,10 @@ fn focus_switch_for_focus_event_at(
         None
     };
     let workspace_id = match workspace_id {
-        Some(workspace_id) if workspace_id > 0 => workspace_id,
+        Some(workspace_id) if workspace_id >= config.workspace_base => workspace_id,
         _ => return Ok(false),
     };
-    let base_workspace =
-        crate::paired::normalize_workspace(workspace_id, config.paired_offset);
+    let base_workspace = config.slot_of(workspace_id);
     if !debounce.should_switch(focus.at, base_workspace) {
         return Ok(false);
     }
@@ -485,17 +1479,14 @@ fn focus_switch_for_focus_event_at(
     if focus_monitor.is_none() {
         focus_monitor = monitor_name_for_workspace(hyprctl, workspace_id)?;
     }
-    let focus_monitor = focus_monitor
-        .as_deref()
-        .unwrap_or(&config.primary_monitor);
-    let batch = crate::hyprctl::paired_switch_batch_with_focus(
-        &config.primary_monitor,
-        &config.secondary_monitor,
+    let focus_monitor = focus_monitor.as_deref().unwrap_or(&config.primary_monitor);
+    let batch = batches.paired_switch(
+        &Layout::for_config(config),
         workspace_id,
-        config.paired_offset,
         focus_monitor,
+        SwitchStyle::new(options.workspace_back_and_forth, config.fast_switch),
     );
-    hyprctl.batch(&batch)?;
+    hyprctl.batch(batch)?;
     Ok(true)
 }
 
@@ -503,70 +1494,120 @@ pub fn rebalance_for_event_debounced(
     hyprctl: &dyn HyprlandIpc,
     config: &Config,
     line: &str,
-    debounce: &mut RebalanceDebounce,
+    debounce: &mut dyn DebounceStrategy,
 ) -> Result<bool, HyprctlError> {
     let event = match parse_socket2_event(line, Instant::now()) {
-        Some(DaemonEvent::Monitor { kind, at }) => (kind, at),
+        Some(DaemonEvent::Monitor { kind, at, .. }) => (kind, at),
         _ => return Ok(false),
     };
-    rebalance_for_event_at(hyprctl, config, event.0, debounce, event.1)
+    rebalance_for_event_at(
+        hyprctl,
+        config,
+        event.0,
+        debounce,
+        &mut BatchCache::default(),
+        event.1,
+    )
 }
 
 pub fn flush_pending_rebalance(
     hyprctl: &dyn HyprlandIpc,
     config: &Config,
-    debounce: &mut RebalanceDebounce,
+    debounce: &mut dyn DebounceStrategy,
 ) -> Result<bool, HyprctlError> {
-    flush_pending_rebalance_at(hyprctl, config, debounce, Instant::now())
+    flush_pending_rebalance_at(
+        hyprctl,
+        config,
+        debounce,
+        &mut BatchCache::default(),
+        Instant::now(),
+    )
 }
 
 pub fn process_event(
     hyprctl: &dyn HyprlandIpc,
     config: &Config,
-    rebalance_debounce: &mut RebalanceDebounce,
+    rebalance_debounce: &mut dyn DebounceStrategy,
     focus_debounce: &mut FocusSwitchDebounce,
+    batches: &mut BatchCache,
+    options: &HyprlandOptions,
     event: DaemonEvent,
 ) -> Result<bool, HyprctlError> {
     match event {
         DaemonEvent::Focus(focus) => {
             let mut did_work = false;
-            if focus_switch_for_focus_event_at(hyprctl, config, &focus, focus_debounce)? {
+            if focus_switch_for_focus_event_at(
+                hyprctl,
+                config,
+                &focus,
+                focus_debounce,
+                batches,
+                options,
+            )? {
                 did_work = true;
             }
             Ok(did_work)
         }
-        DaemonEvent::Monitor { kind, at } => {
+        DaemonEvent::Monitor { kind, at, .. } => {
             let mut did_work = false;
-            if rebalance_for_event_at(hyprctl, config, kind, rebalance_debounce, at)? {
+            if rebalance_for_event_at(hyprctl, config, kind, rebalance_debounce, batches, at)? {
                 did_work = true;
             }
             Ok(did_work)
         }
         DaemonEvent::Timeout { at } => {
-            flush_pending_rebalance_at(hyprctl, config, rebalance_debounce, at)
+            flush_pending_rebalance_at(hyprctl, config, rebalance_debounce, batches, at)
         }
-        DaemonEvent::Disconnected => Ok(false),
+        DaemonEvent::Window(_)
+        | DaemonEvent::Urgent { .. }
+        | DaemonEvent::ConfigReloaded { .. }
+        | DaemonEvent::Disconnected => Ok(false),
+    }
+}
+
+pub fn workspaces_drifted(
+    config: &Config,
+    monitors: &[MonitorInfo],
+    workspaces: &[WorkspaceInfo],
+) -> bool {
+    let has_monitor = |name: &str| monitors.iter().any(|monitor| monitor.name == name);
+    let layout = Layout::for_config(config);
+    workspaces.iter().any(|workspace| {
+        let Some(expected) = layout.monitor_for(workspace.id) else {
+            return false;
+        };
+        has_monitor(expected) && workspace.monitor.as_deref() != Some(expected)
+    })
+}
+
+pub fn resync(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    rebalance_debounce: &mut dyn DebounceStrategy,
+    focus_debounce: &mut FocusSwitchDebounce,
+    batches: &mut BatchCache,
+) -> Result<bool, HyprctlError> {
+    rebalance_debounce.reset();
+    focus_debounce.reset();
+    let monitors = logical_monitors(hyprctl.monitors()?);
+    let workspaces = hyprctl.workspaces()?;
+    if !workspaces_drifted(config, &monitors, &workspaces) {
+        return Ok(false);
     }
+    rebalance_all_cached(hyprctl, config, batches)?;
+    Ok(true)
 }
 
 fn rebalance_for_event_at(
     hyprctl: &dyn HyprlandIpc,
     config: &Config,
-    kind: MonitorEventKind,
-    debounce: &mut RebalanceDebounce,
+    _kind: MonitorEventKind,
+    debounce: &mut dyn DebounceStrategy,
+    batches: &mut BatchCache,
     now: Instant,
 ) -> Result<bool, HyprctlError> {
-    let batch = match kind {
-        MonitorEventKind::Added | MonitorEventKind::Removed => {
-            crate::hyprctl::rebalance_batch(
-                &config.primary_monitor,
-                &config.secondary_monitor,
-                config.paired_offset,
-            )
-        }
-    };
     if debounce.record_event(now) {
-        hyprctl.batch(&batch)?;
+        rebalance_all_cached(hyprctl, config, batches)?;
         Ok(true)
     } else {
         Ok(false)
@@ -576,16 +1617,27 @@ fn rebalance_for_event_at(
 fn flush_pending_rebalance_at(
     hyprctl: &dyn HyprlandIpc,
     config: &Config,
-    debounce: &mut RebalanceDebounce,
+    debounce: &mut dyn DebounceStrategy,
+    batches: &mut BatchCache,
     now: Instant,
 ) -> Result<bool, HyprctlError> {
     if debounce.flush(now) {
-        let batch = crate::hyprctl::rebalance_batch(
-            &config.primary_monitor,
-            &config.secondary_monitor,
-            config.paired_offset,
-        );
-        hyprctl.batch(&batch)?;
+        rebalance_all_cached(hyprctl, config, batches)?;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// Runs a rebalance the debounce is still holding back, without waiting.
+pub fn flush_rebalance_now(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    debounce: &mut dyn DebounceStrategy,
+    batches: &mut BatchCache,
+) -> Result<bool, HyprctlError> {
+    if debounce.take_pending() {
+        rebalance_all_cached(hyprctl, config, batches)?;
         Ok(true)
     } else {
         Ok(false)
@@ -595,17 +1647,23 @@ fn flush_pending_rebalance_at(
 #[cfg(test)]
 mod tests {
     use super::{
-        event_name, flush_pending_rebalance_at, focus_switch_for_event_at, process_event,
-        rebalance_all, rebalance_batch_for_event, rebalance_for_event, rebalance_for_event_at,
-        should_rebalance, socket2_path, DaemonEvent, EventSource, FocusSwitchDebounce,
-        MonitorEventKind, RebalanceDebounce, Socket2EventSource,
+        AdaptiveDebounce, BarMode, DaemonEvent, DaemonMode, DebounceStrategy, Decision, DragGuard,
+        EventSource, FocusEvent, FocusSwitchDebounce, HotplugSettle, LaunchPlacements, ModePolicy,
+        ModeTrigger, MonitorEventInfo, MonitorEventKind, RebalanceDebounce, ReloadQuiesce,
+        SlotHistory, Socket2EventSource, StateCache, WindowEvent, event_name,
+        flush_pending_rebalance_at, flush_rebalance_now, focus_switch_for_event_at,
+        parse_socket2_event, process_event, rebalance_all, rebalance_batch_for_event,
+        rebalance_for_event, rebalance_for_event_at, resync, select_config, select_config_cached,
+        should_rebalance, socket2_path,
+    };
+    use crate::config::{Config, MonitorProfile, RebalanceMode};
+    use crate::hyprctl::{
+        BatchCache, Hyprctl, HyprctlRunner, HyprlandOptions, paired_switch_batch, rebalance_batch,
     };
-    use crate::config::Config;
-    use crate::hyprctl::{Hyprctl, HyprctlRunner, paired_switch_batch, rebalance_batch};
     use std::cell::RefCell;
-    use std::rc::Rc;
     use std::io::Write;
     use std::os::unix::net::UnixStream;
+    use std::rc::Rc;
     use std::time::{Duration, Instant};
 
     #[test]
@@ -618,6 +1676,38 @@ mod tests {
         assert_eq!(event_name("focusedmon>>DP-1,1"), "focusedmon");
     }
 
+    #[test]
+    fn describes_event_triggers() {
+        let now = Instant::now();
+
+        assert_eq!(
+            DaemonEvent::Monitor {
+                kind: MonitorEventKind::Added,
+                at: now,
+                monitor: None,
+            }
+            .trigger(),
+            "monitor added"
+        );
+        assert_eq!(
+            parse_socket2_event("monitorremovedv2>>1,DP-1,Dell U2720Q", now)
+                .expect("event")
+                .trigger(),
+            "monitor removed DP-1"
+        );
+        assert_eq!(
+            DaemonEvent::Focus(super::FocusEvent {
+                at: now,
+                workspace_id: None,
+                window_address: Some("0x123".to_string()),
+                monitor_name: None,
+            })
+            .trigger(),
+            "focus window 0x123"
+        );
+        assert_eq!(DaemonEvent::Timeout { at: now }.trigger(), "debounce flush");
+    }
+
     #[test]
     fn leaves_event_name_when_separator_missing() {
         assert_eq!(event_name("monitoradded"), "monitoradded");
@@ -634,23 +1724,24 @@ mod tests {
     fn switches_pair_on_focusedmonv2_event() {
         let runner = RecordingRunner::default();
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
-        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
         let mut debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
 
-        assert!(focus_switch_for_event_at(
-            &hyprctl,
-            &config,
-            "focusedmonv2>>DP-1,3",
-            &mut debounce,
-            Instant::now(),
-        )
-        .expect("switch"));
+        assert!(
+            focus_switch_for_event_at(
+                &hyprctl,
+                &config,
+                "focusedmonv2>>DP-1,3",
+                &mut debounce,
+                Instant::now(),
+            )
+            .expect("switch")
+        );
 
         let calls = runner.calls.borrow();
         assert_eq!(calls.len(), 1);
@@ -667,23 +1758,24 @@ mod tests {
     fn keeps_focus_on_secondary_monitor_for_focusedmon_event() {
         let runner = RecordingRunner::default();
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
-        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
         let mut debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
 
-        assert!(focus_switch_for_event_at(
-            &hyprctl,
-            &config,
-            "focusedmonv2>>HDMI-A-1,4",
-            &mut debounce,
-            Instant::now(),
-        )
-        .expect("switch"));
+        assert!(
+            focus_switch_for_event_at(
+                &hyprctl,
+                &config,
+                "focusedmonv2>>HDMI-A-1,4",
+                &mut debounce,
+                Instant::now(),
+            )
+            .expect("switch")
+        );
 
         let calls = runner.calls.borrow();
         assert_eq!(calls.len(), 1);
@@ -703,23 +1795,24 @@ mod tests {
             r#"[{"id":4,"windows":1,"monitor":"DP-1"}]"#,
         );
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
-        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
         let mut debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
 
-        assert!(focus_switch_for_event_at(
-            &hyprctl,
-            &config,
-            "activewindowv2>>0x123",
-            &mut debounce,
-            Instant::now(),
-        )
-        .expect("switch"));
+        assert!(
+            focus_switch_for_event_at(
+                &hyprctl,
+                &config,
+                "activewindowv2>>0x123",
+                &mut debounce,
+                Instant::now(),
+            )
+            .expect("switch")
+        );
 
         let calls = runner.calls.borrow();
         assert_eq!(calls.len(), 3);
@@ -739,23 +1832,24 @@ mod tests {
             r#"[{"id":4,"windows":1,"monitor":"HDMI-A-1"}]"#,
         );
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
-        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
         let mut debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
 
-        assert!(focus_switch_for_event_at(
-            &hyprctl,
-            &config,
-            "activewindowv2>>0x123",
-            &mut debounce,
-            Instant::now(),
-        )
-        .expect("switch"));
+        assert!(
+            focus_switch_for_event_at(
+                &hyprctl,
+                &config,
+                "activewindowv2>>0x123",
+                &mut debounce,
+                Instant::now(),
+            )
+            .expect("switch")
+        );
 
         let calls = runner.calls.borrow();
         assert_eq!(calls.len(), 3);
@@ -769,70 +1863,121 @@ mod tests {
     }
 
     #[test]
-    fn debounces_repeated_focus_events() {
+    fn back_and_forth_skips_redispatching_focused_workspace() {
         let runner = RecordingRunner::default();
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
+        let mut debounce = RebalanceDebounce::new(Duration::from_millis(200));
+        let mut focus_debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
+        let options = HyprlandOptions {
+            workspace_back_and_forth: true,
         };
+
+        assert!(
+            process_event(
+                &hyprctl,
+                &config,
+                &mut debounce,
+                &mut focus_debounce,
+                &mut BatchCache::default(),
+                &options,
+                DaemonEvent::Focus(super::FocusEvent {
+                    at: Instant::now(),
+                    workspace_id: Some(4),
+                    window_address: None,
+                    monitor_name: Some("HDMI-A-1".to_string()),
+                }),
+            )
+            .expect("switch")
+        );
+
+        let calls = runner.calls.borrow();
+        assert_eq!(
+            calls[0],
+            vec![
+                "--batch".to_string(),
+                "dispatch focusmonitor DP-1 ; dispatch workspace 2 ; dispatch focusmonitor HDMI-A-1".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn debounces_repeated_focus_events() {
+        let runner = RecordingRunner::default();
+        let hyprctl = Hyprctl::new(runner.clone());
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
         let mut debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
         let start = Instant::now();
 
-        assert!(focus_switch_for_event_at(
-            &hyprctl,
-            &config,
-            "focusedmonv2>>DP-1,3",
-            &mut debounce,
-            start,
-        )
-        .expect("switch"));
-        assert!(!focus_switch_for_event_at(
-            &hyprctl,
-            &config,
-            "focusedmonv2>>DP-1,3",
-            &mut debounce,
-            start + Duration::from_millis(10),
-        )
-        .expect("debounced"));
+        assert!(
+            focus_switch_for_event_at(
+                &hyprctl,
+                &config,
+                "focusedmonv2>>DP-1,3",
+                &mut debounce,
+                start,
+            )
+            .expect("switch")
+        );
+        assert!(
+            !focus_switch_for_event_at(
+                &hyprctl,
+                &config,
+                "focusedmonv2>>DP-1,3",
+                &mut debounce,
+                start + Duration::from_millis(10),
+            )
+            .expect("debounced")
+        );
 
         let calls = runner.calls.borrow();
         assert_eq!(calls.len(), 1);
+        assert_eq!(debounce.suppressed(), 1);
     }
 
     #[test]
     fn debounces_paired_focus_events() {
         let runner = RecordingRunner::default();
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
-        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
         let mut debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
         let start = Instant::now();
 
-        assert!(focus_switch_for_event_at(
-            &hyprctl,
-            &config,
-            "focusedmonv2>>DP-1,3",
-            &mut debounce,
-            start,
-        )
-        .expect("switch"));
-        assert!(!focus_switch_for_event_at(
-            &hyprctl,
-            &config,
-            "focusedmonv2>>DP-1,1",
-            &mut debounce,
-            start + Duration::from_millis(10),
-        )
-        .expect("debounced"));
+        assert!(
+            focus_switch_for_event_at(
+                &hyprctl,
+                &config,
+                "focusedmonv2>>DP-1,3",
+                &mut debounce,
+                start,
+            )
+            .expect("switch")
+        );
+        assert!(
+            !focus_switch_for_event_at(
+                &hyprctl,
+                &config,
+                "focusedmonv2>>DP-1,1",
+                &mut debounce,
+                start + Duration::from_millis(10),
+            )
+            .expect("debounced")
+        );
 
         let calls = runner.calls.borrow();
         assert_eq!(calls.len(), 1);
@@ -864,6 +2009,7 @@ mod tests {
         calls: Rc<RefCell<Vec<Vec<String>>>>,
         clients_json: Option<String>,
         workspaces_json: Option<String>,
+        monitors_json: Option<String>,
     }
 
     impl HyprctlRunner for RecordingRunner {
@@ -881,6 +2027,12 @@ mod tests {
                     None => Ok("ok".to_string()),
                 };
             }
+            if args == ["-j".to_string(), "monitors".to_string()] {
+                return match self.monitors_json.as_ref() {
+                    Some(payload) => Ok(payload.clone()),
+                    None => Ok("ok".to_string()),
+                };
+            }
             Ok("ok".to_string())
         }
     }
@@ -891,21 +2043,187 @@ mod tests {
                 calls: Rc::new(RefCell::new(Vec::new())),
                 clients_json: Some(clients_json.to_string()),
                 workspaces_json: Some(workspaces_json.to_string()),
+                monitors_json: None,
             }
         }
+
+        fn with_monitors_and_workspaces(monitors_json: &str, workspaces_json: &str) -> Self {
+            Self {
+                calls: Rc::new(RefCell::new(Vec::new())),
+                clients_json: None,
+                workspaces_json: Some(workspaces_json.to_string()),
+                monitors_json: Some(monitors_json.to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn resync_rebalances_when_workspaces_drifted() {
+        let runner = RecordingRunner::with_monitors_and_workspaces(
+            r#"[{"name":"DP-1","x":0,"id":1},{"name":"HDMI-A-1","x":1920,"id":2}]"#,
+            r#"[{"id":1,"windows":1,"monitor":"DP-1"},{"id":3,"windows":1,"monitor":"DP-1"}]"#,
+        );
+        let hyprctl = Hyprctl::new(runner.clone());
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
+        let mut debounce = RebalanceDebounce::new(Duration::from_millis(200));
+        let mut focus_debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
+
+        assert!(
+            resync(
+                &hyprctl,
+                &config,
+                &mut debounce,
+                &mut focus_debounce,
+                &mut BatchCache::default(),
+            )
+            .expect("resync")
+        );
+
+        let calls = runner.calls.borrow();
+        assert_eq!(
+            calls.last().expect("batch"),
+            &vec![
+                "--batch".to_string(),
+                rebalance_batch("DP-1", "HDMI-A-1", 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn resync_skips_rebalance_when_layout_matches() {
+        let runner = RecordingRunner::with_monitors_and_workspaces(
+            r#"[{"name":"DP-1","x":0,"id":1},{"name":"HDMI-A-1","x":1920,"id":2}]"#,
+            r#"[{"id":1,"windows":1,"monitor":"DP-1"},{"id":3,"windows":1,"monitor":"HDMI-A-1"}]"#,
+        );
+        let hyprctl = Hyprctl::new(runner.clone());
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
+        let mut debounce = RebalanceDebounce::new(Duration::from_millis(200));
+        let mut focus_debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
+
+        assert!(
+            !resync(
+                &hyprctl,
+                &config,
+                &mut debounce,
+                &mut focus_debounce,
+                &mut BatchCache::default(),
+            )
+            .expect("resync")
+        );
+
+        let calls = runner.calls.borrow();
+        assert_eq!(calls.len(), 2);
+    }
+
+    #[test]
+    fn resync_ignores_workspaces_for_missing_monitor() {
+        let runner = RecordingRunner::with_monitors_and_workspaces(
+            r#"[{"name":"DP-1","x":0,"id":1}]"#,
+            r#"[{"id":3,"windows":1,"monitor":"DP-1"}]"#,
+        );
+        let hyprctl = Hyprctl::new(runner.clone());
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
+        let mut debounce = RebalanceDebounce::new(Duration::from_millis(200));
+        let mut focus_debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
+
+        assert!(
+            !resync(
+                &hyprctl,
+                &config,
+                &mut debounce,
+                &mut focus_debounce,
+                &mut BatchCache::default(),
+            )
+            .expect("resync")
+        );
+    }
+
+    #[test]
+    fn select_config_follows_connected_monitors() {
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .profile(MonitorProfile {
+                monitors: vec!["eDP-1".to_string(), "DP-2".to_string()],
+                primary_monitor: "eDP-1".to_string(),
+                secondary_monitor: "DP-2".to_string(),
+            })
+            .build()
+            .expect("config");
+        let docked = Hyprctl::new(RecordingRunner::with_monitors_and_workspaces(
+            r#"[{"name":"eDP-1","x":0,"id":1},{"name":"DP-2","x":1920,"id":2}]"#,
+            "[]",
+        ));
+        let undocked = Hyprctl::new(RecordingRunner::with_monitors_and_workspaces(
+            r#"[{"name":"eDP-1","x":0,"id":1}]"#,
+            "[]",
+        ));
+
+        let selected = select_config(&docked, &config)
+            .expect("select")
+            .expect("paired");
+        assert_eq!(selected.primary_monitor, "eDP-1");
+        assert_eq!(selected.secondary_monitor, "DP-2");
+        assert_eq!(select_config(&undocked, &config).expect("select"), None);
+    }
+
+    #[test]
+    fn select_config_resolves_monitor_matches_on_hotplug() {
+        let config = Config::from_json(
+            r#"{"primary_monitor":{"description":"LG UltraGear"},"secondary_monitor":"HDMI-A-1"}"#,
+        )
+        .expect("config");
+        let before = Hyprctl::new(RecordingRunner::with_monitors_and_workspaces(
+            r#"[{"name":"DP-1","x":0,"id":1,"description":"LG Electronics LG UltraGear"},{"name":"HDMI-A-1","x":1920,"id":2}]"#,
+            "[]",
+        ));
+        let swapped = Hyprctl::new(RecordingRunner::with_monitors_and_workspaces(
+            r#"[{"name":"DP-2","x":0,"id":3,"description":"LG Electronics LG UltraGear"},{"name":"HDMI-A-1","x":1920,"id":2}]"#,
+            "[]",
+        ));
+        let missing = Hyprctl::new(RecordingRunner::with_monitors_and_workspaces(
+            r#"[{"name":"DP-3","x":0,"id":4,"description":"Dell"},{"name":"HDMI-A-1","x":1920,"id":2}]"#,
+            "[]",
+        ));
+        let mut cache = StateCache::default();
+
+        let selected = select_config_cached(&before, &config, &mut cache)
+            .expect("select")
+            .expect("paired");
+        assert_eq!(selected.primary_monitor, "DP-1");
+        let selected = select_config_cached(&swapped, &config, &mut cache)
+            .expect("select")
+            .expect("paired");
+        assert_eq!(selected.primary_monitor, "DP-2");
+        assert_eq!(select_config(&missing, &config).expect("select"), None);
     }
 
     #[test]
     fn rebalance_all_runs_batch() {
         let runner = RecordingRunner::default();
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
-        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
 
         rebalance_all(&hyprctl, &config).expect("rebalance");
 
@@ -920,17 +2238,76 @@ mod tests {
         );
     }
 
+    #[test]
+    fn instant_rebalance_turns_animations_off_around_the_batch() {
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .instant_rebalance(true)
+            .build()
+            .expect("config");
+        let ipc = crate::testing::ScriptedIpc::new()
+            .with_json("getoption", r#"{"option":"animations:enabled","int":1}"#);
+
+        rebalance_all(&ipc, &config).expect("rebalance");
+        ipc.set_json("getoption", r#"{"option":"animations:enabled","int":0}"#);
+        rebalance_all(&ipc, &config).expect("rebalance");
+
+        let batches = ipc
+            .calls()
+            .into_iter()
+            .filter(|call| call[0] == "--batch")
+            .map(|call| call[1].clone())
+            .collect::<Vec<_>>();
+        let batch = rebalance_batch("DP-1", "HDMI-A-1", 2);
+        assert_eq!(
+            batches,
+            vec![
+                format!("keyword animations:enabled 0 ; {batch} ; keyword animations:enabled 1"),
+                batch,
+            ]
+        );
+    }
+
+    #[test]
+    fn preserve_mode_rebalances_only_misplaced_workspaces() {
+        let runner = RecordingRunner::with_clients_and_workspaces(
+            "[]",
+            r#"[{"id":1,"windows":1,"monitor":"DP-1"},{"id":3,"windows":2,"monitor":"DP-1"}]"#,
+        );
+        let hyprctl = Hyprctl::new(runner.clone());
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .rebalance_mode(RebalanceMode::Preserve)
+            .build()
+            .expect("config");
+
+        rebalance_all(&hyprctl, &config).expect("rebalance");
+
+        let calls = runner.calls.borrow();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[1],
+            vec![
+                "--batch".to_string(),
+                "dispatch moveworkspacetomonitor 3 HDMI-A-1".to_string()
+            ]
+        );
+    }
+
     #[test]
     fn rebalance_for_event_runs_only_on_monitor_events() {
         let runner = RecordingRunner::default();
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
-        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
 
         assert!(rebalance_for_event(&hyprctl, &config, "monitoradded>>DP-1").expect("rebalance"));
         assert!(!rebalance_for_event(&hyprctl, &config, "focusedmon>>DP-1,1").expect("skip"));
@@ -943,32 +2320,37 @@ mod tests {
     fn debounces_rebalance_events_within_window() {
         let runner = RecordingRunner::default();
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
-        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
         let mut debounce = RebalanceDebounce::new(Duration::from_millis(200));
         let start = Instant::now();
 
-        assert!(rebalance_for_event_at(
-            &hyprctl,
-            &config,
-            MonitorEventKind::Added,
-            &mut debounce,
-            start,
-        )
-        .expect("rebalance"));
-        assert!(!rebalance_for_event_at(
-            &hyprctl,
-            &config,
-            MonitorEventKind::Removed,
-            &mut debounce,
-            start + Duration::from_millis(50),
-        )
-        .expect("debounced"));
+        assert!(
+            rebalance_for_event_at(
+                &hyprctl,
+                &config,
+                MonitorEventKind::Added,
+                &mut debounce,
+                &mut BatchCache::default(),
+                start,
+            )
+            .expect("rebalance")
+        );
+        assert!(
+            !rebalance_for_event_at(
+                &hyprctl,
+                &config,
+                MonitorEventKind::Removed,
+                &mut debounce,
+                &mut BatchCache::default(),
+                start + Duration::from_millis(50),
+            )
+            .expect("debounced")
+        );
 
         let calls = runner.calls.borrow();
         assert_eq!(calls.len(), 1);
@@ -978,40 +2360,48 @@ mod tests {
     fn flushes_pending_rebalance_after_burst() {
         let runner = RecordingRunner::default();
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
-        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
         let mut debounce = RebalanceDebounce::new(Duration::from_millis(200));
         let start = Instant::now();
 
-        assert!(rebalance_for_event_at(
-            &hyprctl,
-            &config,
-            MonitorEventKind::Added,
-            &mut debounce,
-            start,
-        )
-        .expect("rebalance"));
-        assert!(!rebalance_for_event_at(
-            &hyprctl,
-            &config,
-            MonitorEventKind::Removed,
-            &mut debounce,
-            start + Duration::from_millis(50),
-        )
-        .expect("debounced"));
+        assert!(
+            rebalance_for_event_at(
+                &hyprctl,
+                &config,
+                MonitorEventKind::Added,
+                &mut debounce,
+                &mut BatchCache::default(),
+                start,
+            )
+            .expect("rebalance")
+        );
+        assert!(
+            !rebalance_for_event_at(
+                &hyprctl,
+                &config,
+                MonitorEventKind::Removed,
+                &mut debounce,
+                &mut BatchCache::default(),
+                start + Duration::from_millis(50),
+            )
+            .expect("debounced")
+        );
 
-        assert!(flush_pending_rebalance_at(
-            &hyprctl,
-            &config,
-            &mut debounce,
-            start + Duration::from_millis(260),
-        )
-        .expect("flush"));
+        assert!(
+            flush_pending_rebalance_at(
+                &hyprctl,
+                &config,
+                &mut debounce,
+                &mut BatchCache::default(),
+                start + Duration::from_millis(260),
+            )
+            .expect("flush")
+        );
 
         let calls = runner.calls.borrow();
         assert_eq!(calls.len(), 2);
@@ -1021,59 +2411,508 @@ mod tests {
     fn process_event_flushes_pending_rebalance() {
         let runner = RecordingRunner::default();
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
-        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
         let mut debounce = RebalanceDebounce::new(Duration::from_millis(200));
         let mut focus_debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
         let start = Instant::now();
 
-        assert!(process_event(
-            &hyprctl,
-            &config,
-            &mut debounce,
-            &mut focus_debounce,
-            DaemonEvent::Monitor {
-                kind: MonitorEventKind::Added,
-                at: start,
-            },
-        )
-        .expect("rebalance"));
-        assert!(!process_event(
-            &hyprctl,
-            &config,
-            &mut debounce,
-            &mut focus_debounce,
-            DaemonEvent::Monitor {
-                kind: MonitorEventKind::Removed,
-                at: start + Duration::from_millis(50),
-            },
-        )
-        .expect("debounced"));
-        assert!(process_event(
-            &hyprctl,
-            &config,
-            &mut debounce,
-            &mut focus_debounce,
-            DaemonEvent::Timeout {
-                at: start + Duration::from_millis(260),
-            },
-        )
-        .expect("flush"));
+        assert!(
+            process_event(
+                &hyprctl,
+                &config,
+                &mut debounce,
+                &mut focus_debounce,
+                &mut BatchCache::default(),
+                &HyprlandOptions::default(),
+                DaemonEvent::Monitor {
+                    kind: MonitorEventKind::Added,
+                    at: start,
+                    monitor: None,
+                },
+            )
+            .expect("rebalance")
+        );
+        assert!(
+            !process_event(
+                &hyprctl,
+                &config,
+                &mut debounce,
+                &mut focus_debounce,
+                &mut BatchCache::default(),
+                &HyprlandOptions::default(),
+                DaemonEvent::Monitor {
+                    kind: MonitorEventKind::Removed,
+                    at: start + Duration::from_millis(50),
+                    monitor: None,
+                },
+            )
+            .expect("debounced")
+        );
+        assert!(
+            process_event(
+                &hyprctl,
+                &config,
+                &mut debounce,
+                &mut focus_debounce,
+                &mut BatchCache::default(),
+                &HyprlandOptions::default(),
+                DaemonEvent::Timeout {
+                    at: start + Duration::from_millis(260),
+                },
+            )
+            .expect("flush")
+        );
 
         let calls = runner.calls.borrow();
         assert_eq!(calls.len(), 2);
     }
 
+    #[test]
+    fn flushes_pending_rebalance_without_waiting() {
+        let runner = RecordingRunner::default();
+        let hyprctl = Hyprctl::new(runner.clone());
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .build()
+            .expect("config");
+        let mut debounce = RebalanceDebounce::new(Duration::from_millis(200));
+        let mut batches = BatchCache::default();
+        let start = Instant::now();
+        assert!(debounce.record_event(start));
+        assert!(!debounce.record_event(start + Duration::from_millis(10)));
+
+        assert!(
+            flush_rebalance_now(&hyprctl, &config, &mut debounce, &mut batches).expect("flush")
+        );
+        assert!(
+            !flush_rebalance_now(&hyprctl, &config, &mut debounce, &mut batches).expect("idle")
+        );
+
+        assert_eq!(runner.calls.borrow().len(), 1);
+        assert_eq!(debounce.flush_deadline(), None);
+    }
+
+    #[test]
+    fn rebalance_debounce_reports_flush_deadline() {
+        let mut debounce = RebalanceDebounce::new(Duration::from_millis(200));
+        let start = Instant::now();
+
+        assert_eq!(debounce.flush_deadline(), None);
+        assert!(debounce.record_event(start));
+        assert_eq!(debounce.flush_deadline(), None);
+        assert!(!debounce.record_event(start + Duration::from_millis(50)));
+        assert_eq!(
+            debounce.flush_deadline(),
+            Some(start + Duration::from_millis(250))
+        );
+        assert!(debounce.flush(start + Duration::from_millis(250)));
+        assert_eq!(debounce.flush_deadline(), None);
+    }
+
+    #[test]
+    fn adaptive_debounce_grows_during_bursts_and_fires_once() {
+        let mut debounce =
+            AdaptiveDebounce::new(Duration::from_millis(200), Duration::from_millis(1000));
+        let start = Instant::now();
+        let ms = |millis| start + Duration::from_millis(millis);
+
+        let mut rebalances = 0;
+        for at in [0, 150, 450, 1000, 1900] {
+            assert!(!debounce.flush(ms(at)));
+            if debounce.record_event(ms(at)) {
+                rebalances += 1;
+            }
+        }
+        assert_eq!(debounce.window(), Duration::from_millis(1000));
+        assert_eq!(debounce.flush_deadline(), Some(ms(2900)));
+        assert!(!debounce.flush(ms(2800)));
+        if debounce.flush(ms(2900)) {
+            rebalances += 1;
+        }
+
+        assert_eq!(rebalances, 1);
+        assert_eq!(debounce.suppressed(), 4);
+        assert_eq!(debounce.window(), Duration::from_millis(200));
+        assert_eq!(debounce.flush_deadline(), None);
+
+        debounce.record_event(ms(5000));
+        assert_eq!(debounce.flush_deadline(), Some(ms(5200)));
+        assert!(debounce.take_pending());
+    }
+
+    #[test]
+    fn builds_the_configured_debounce_strategy() {
+        let mut config = crate::config::DebounceConfig::default();
+        assert_eq!(
+            super::rebalance_debounce(&config).mode(),
+            crate::config::DebounceMode::Fixed
+        );
+
+        config.mode = crate::config::DebounceMode::Adaptive;
+        let mut debounce = super::rebalance_debounce(&config);
+        let start = Instant::now();
+        assert_eq!(debounce.mode(), crate::config::DebounceMode::Adaptive);
+        assert!(!debounce.record_event(start));
+
+        config.rebalance_ms = 400;
+        debounce.configure(&config);
+        debounce.reset();
+        debounce.record_event(start);
+        assert_eq!(
+            debounce.flush_deadline(),
+            Some(start + Duration::from_millis(400))
+        );
+    }
+
+    #[test]
+    fn hotplug_settle_extends_window_on_each_event() {
+        let mut settle = HotplugSettle::new(Duration::from_millis(1000));
+        let start = Instant::now();
+
+        assert!(settle.is_enabled());
+        assert!(!HotplugSettle::new(Duration::ZERO).is_enabled());
+        settle.record(start);
+        settle.record(start + Duration::from_millis(800));
+        assert!(!settle.due(start + Duration::from_millis(1200)));
+        assert_eq!(settle.deadline(), Some(start + Duration::from_millis(1800)));
+        assert!(settle.due(start + Duration::from_millis(1800)));
+        assert!(!settle.due(start + Duration::from_millis(1900)));
+        assert_eq!(settle.deadline(), None);
+    }
+
+    #[test]
+    fn reload_quiesce_restarts_on_each_reload_and_fires_once() {
+        let mut quiesce = ReloadQuiesce::new(Duration::from_millis(500));
+        let start = Instant::now();
+
+        assert!(!quiesce.is_quiet(start));
+        quiesce.record(start);
+        quiesce.record(start + Duration::from_millis(300));
+        assert!(quiesce.is_quiet(start + Duration::from_millis(600)));
+        assert!(!quiesce.due(start + Duration::from_millis(600)));
+        assert!(!quiesce.is_quiet(start + Duration::from_millis(800)));
+        assert!(quiesce.due(start + Duration::from_millis(800)));
+        assert!(!quiesce.due(start + Duration::from_millis(900)));
+
+        quiesce.record(start);
+        quiesce.reset();
+        assert_eq!(quiesce.deadline(), None);
+    }
+
+    #[test]
+    fn parses_config_reloaded_events() {
+        let now = Instant::now();
+        let event = parse_socket2_event("configreloaded>>", now).expect("event");
+
+        assert!(matches!(event, DaemonEvent::ConfigReloaded { at } if at == now));
+        assert_eq!(event.trigger(), "config reloaded");
+        assert_eq!(event.kind(), "config_reloaded");
+    }
+
+    #[test]
+    fn drag_guard_defers_monitor_focus_until_moves_settle() {
+        let start = Instant::now();
+        let focus = |at, workspace_id, monitor_name: Option<&str>| FocusEvent {
+            at,
+            workspace_id: Some(workspace_id),
+            window_address: None,
+            monitor_name: monitor_name.map(str::to_string),
+        };
+        let mut guard = DragGuard::new(Duration::from_millis(300));
+
+        assert!(!guard.hold(&focus(start, 2, None)));
+        assert!(guard.hold(&focus(start, 12, Some("HDMI-A-1"))));
+        assert!(guard.hold(&focus(start + Duration::from_millis(50), 3, None)));
+        guard.record_move(start + Duration::from_millis(200));
+        assert!(guard.due(start + Duration::from_millis(300)).is_none());
+        assert_eq!(guard.deadline(), Some(start + Duration::from_millis(500)));
+        let released = guard
+            .due(start + Duration::from_millis(500))
+            .expect("pending focus");
+        assert_eq!(released.workspace_id, Some(3));
+        assert_eq!(guard.deadline(), None);
+        assert!(!guard.hold(&focus(start, 4, None)));
+        assert!(!DragGuard::new(Duration::ZERO).hold(&focus(start, 12, Some("HDMI-A-1"))));
+    }
+
+    #[test]
+    fn launch_placements_claim_oldest_match_until_they_lapse() {
+        let start = Instant::now();
+        let mut launches = LaunchPlacements::new(Duration::from_secs(60));
+        launches.expect("kitty", "3", start);
+        launches.expect("kitty", "14", start);
+        launches.expect("firefox", "2", start);
+
+        assert_eq!(launches.claim("Kitty", start), Some("3".to_string()));
+        assert_eq!(launches.claim("kitty", start), Some("14".to_string()));
+        assert_eq!(launches.claim("kitty", start), None);
+        assert_eq!(
+            launches.claim("firefox", start + Duration::from_secs(60)),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_window_events_with_normalized_addresses() {
+        let now = Instant::now();
+
+        assert!(matches!(
+            parse_socket2_event("openwindow>>55aa,3,kitty,shell", now),
+            Some(DaemonEvent::Window(WindowEvent::Opened { address, workspace_id: Some(3), class }))
+                if address == "0x55aa" && class.as_deref() == Some("kitty")
+        ));
+        assert!(matches!(
+            parse_socket2_event("movewindowv2>>0x55aa,13,13", now),
+            Some(DaemonEvent::Window(WindowEvent::Moved { address, workspace_id: Some(13) }))
+                if address == "0x55aa"
+        ));
+        assert!(matches!(
+            parse_socket2_event("closewindow>>55aa", now),
+            Some(DaemonEvent::Window(WindowEvent::Closed { address })) if address == "0x55aa"
+        ));
+        assert!(matches!(
+            parse_socket2_event("activewindowv2>>55aa", now),
+            Some(DaemonEvent::Focus(focus)) if focus.window_address.as_deref() == Some("0x55aa")
+        ));
+        assert!(matches!(
+            parse_socket2_event("urgent>>55aa", now),
+            Some(DaemonEvent::Urgent { address }) if address == "0x55aa"
+        ));
+        assert!(parse_socket2_event("urgent>>", now).is_none());
+    }
+
+    #[test]
+    fn state_cache_remembers_urgent_window_until_focused_or_closed() {
+        let mut cache = StateCache::default();
+        let now = Instant::now();
+        let event = |line: &str| parse_socket2_event(line, now).expect("event");
+
+        cache.apply(&event("urgent>>a1"));
+        assert_eq!(cache.urgent(), Some("0xa1"));
+        cache.apply(&event("activewindowv2>>b2"));
+        assert_eq!(cache.urgent(), Some("0xa1"));
+        cache.apply(&event("activewindowv2>>a1"));
+        assert_eq!(cache.urgent(), None);
+
+        cache.apply(&event("urgent>>a1"));
+        cache.apply(&event("urgent>>b2"));
+        cache.apply(&event("closewindow>>a1"));
+        assert_eq!(cache.urgent(), Some("0xb2"));
+        cache.apply(&event("closewindow>>b2"));
+        assert_eq!(cache.urgent(), None);
+    }
+
+    #[test]
+    fn state_cache_tracks_occupancy_and_active_workspace() {
+        let mut cache = StateCache::default();
+        let now = Instant::now();
+        let event = |line: &str| parse_socket2_event(line, now).expect("event");
+        assert_eq!(cache.bar_state(), None);
+
+        assert!(cache.apply(&event("workspacev2>>1,1")));
+        assert!(cache.apply(&event("openwindow>>a1,1,kitty,shell")));
+        assert!(cache.apply(&event("openwindow>>b2,11,firefox,web")));
+        assert!(cache.apply(&event("movewindowv2>>a1,2,2")));
+        assert!(!cache.apply(&event("movewindowv2>>a1,2,2")));
+        assert!(cache.apply(&event("activewindowv2>>b2")));
+
+        let state = cache.bar_state().expect("state");
+        assert_eq!(state.active_workspace, 11);
+        assert_eq!(
+            state
+                .workspaces
+                .iter()
+                .map(|workspace| (workspace.id, workspace.windows))
+                .collect::<Vec<_>>(),
+            vec![(2, 1), (11, 1)]
+        );
+
+        assert!(cache.apply(&event("closewindow>>b2")));
+        assert_eq!(cache.window_workspace("0xb2"), None);
+        assert_eq!(cache.bar_state().expect("state").workspaces.len(), 1);
+    }
+
+    #[test]
+    fn state_cache_tracks_visible_workspace_per_monitor() {
+        let mut cache = StateCache::default();
+        let now = Instant::now();
+        let event = |line: &str| parse_socket2_event(line, now).expect("event");
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .build()
+            .expect("config");
+
+        cache.apply(&event("workspacev2>>3,3"));
+        assert_eq!(cache.visible_workspace("DP-1"), None);
+
+        cache.apply(&event("focusedmonv2>>HDMI-A-1,11"));
+        cache.apply(&event("workspacev2>>13,13"));
+        cache.apply(&event("focusedmonv2>>DP-1,3"));
+        assert_eq!(cache.visible_workspace("HDMI-A-1"), Some(13));
+        assert!(cache.shows_pair(&config, 3));
+        assert!(cache.shows_pair(&config, 13));
+        assert!(!cache.shows_pair(&config, 4));
+
+        cache.apply(&event("workspacev2>>4,4"));
+        assert!(!cache.shows_pair(&config, 4));
+
+        cache.apply(&event("monitorremovedv2>>2,HDMI-A-1,LG"));
+        assert_eq!(cache.visible_workspace("DP-1"), None);
+    }
+
+    #[test]
+    fn slot_history_keeps_most_recent_first() {
+        let mut history = SlotHistory::default();
+        for slot in [1, 3, 3, 2, 1] {
+            history.record(slot);
+        }
+
+        assert_eq!(history.slots(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn state_cache_resolves_focus_without_querying_clients() {
+        let runner = RecordingRunner::with_clients_and_workspaces(
+            r#"[{"address":"0x123","workspace":{"id":4}}]"#,
+            r#"[{"id":4,"windows":1,"monitor":"DP-1"}]"#,
+        );
+        let hyprctl = Hyprctl::new(runner.clone());
+        let mut cache = StateCache::default();
+        let now = Instant::now();
+        let event = |line: &str| parse_socket2_event(line, now).expect("event");
+
+        let resolved = cache
+            .resolve(&hyprctl, event("activewindowv2>>123"))
+            .expect("resolve");
+        assert!(matches!(resolved, DaemonEvent::Focus(focus) if focus.workspace_id == Some(4)));
+        assert_eq!(runner.calls.borrow().len(), 1);
+
+        cache.apply(&event("movewindowv2>>123,7,7"));
+        let resolved = cache
+            .resolve(&hyprctl, event("activewindowv2>>123"))
+            .expect("resolve");
+        assert!(matches!(resolved, DaemonEvent::Focus(focus) if focus.workspace_id == Some(7)));
+        assert_eq!(runner.calls.borrow().len(), 1);
+
+        cache.apply(&event("closewindow>>123"));
+        assert_eq!(cache.window_workspace("0x123"), None);
+    }
+
+    #[test]
+    fn parses_monitor_event_payloads() {
+        assert_eq!(
+            MonitorEventInfo::parse("2,DP-2,Dell Inc. U2720Q, rev 2", true),
+            Some(MonitorEventInfo {
+                id: Some(2),
+                name: "DP-2".to_string(),
+                description: Some("Dell Inc. U2720Q, rev 2".to_string()),
+            })
+        );
+        assert_eq!(
+            MonitorEventInfo::parse("DP-2", false),
+            Some(MonitorEventInfo {
+                id: None,
+                name: "DP-2".to_string(),
+                description: None,
+            })
+        );
+        assert_eq!(MonitorEventInfo::parse("DP-2", true), None);
+    }
+
+    #[test]
+    fn state_cache_tracks_monitors_from_events() {
+        let runner = RecordingRunner::with_monitors_and_workspaces(
+            r#"[{"name":"DP-1","x":0,"id":1,"mirrorOf":"none"},
+                {"name":"HDMI-A-1","x":1920,"id":2},
+                {"name":"DP-2","x":3840,"id":3},
+                {"name":"eDP-1","x":0,"id":4,"mirrorOf":"1"}]"#,
+            "[]",
+        );
+        let hyprctl = Hyprctl::new(runner.clone());
+        let mut cache = StateCache::default();
+        let now = Instant::now();
+        let event = |line: &str| parse_socket2_event(line, now).expect("event");
+
+        assert_eq!(
+            cache.connected_monitors(&hyprctl).expect("monitors"),
+            vec!["DP-1", "HDMI-A-1", "DP-2"]
+        );
+        cache.apply(&event("monitorremovedv2>>2,HDMI-A-1,LG"));
+        assert_eq!(
+            cache.connected_monitors(&hyprctl).expect("monitors"),
+            vec!["DP-1", "DP-2"]
+        );
+        assert_eq!(cache.monitor_name(3), Some("DP-2"));
+        assert_eq!(runner.calls.borrow().len(), 1);
+
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .profile(MonitorProfile {
+                monitors: vec!["DP-1".to_string(), "DP-2".to_string()],
+                primary_monitor: "DP-1".to_string(),
+                secondary_monitor: "DP-2".to_string(),
+            })
+            .build()
+            .expect("config");
+        let selected = select_config_cached(&hyprctl, &config, &mut cache)
+            .expect("select")
+            .expect("config");
+        assert_eq!(selected.secondary_monitor, "DP-2");
+        assert_eq!(runner.calls.borrow().len(), 1);
+
+        cache.apply(&event("monitoraddedv2>>5,DP-3,Dell U2720Q"));
+        cache.connected_monitors(&hyprctl).expect("monitors");
+        assert_eq!(runner.calls.borrow().len(), 2);
+    }
+
+    #[test]
+    fn mirrored_outputs_count_as_one_logical_monitor() {
+        let runner = RecordingRunner::with_monitors_and_workspaces(
+            r#"[{"name":"DP-1","x":0,"id":1},{"name":"HDMI-A-1","x":0,"id":2,"mirrorOf":"1"}]"#,
+            r#"[{"id":1,"windows":1,"monitor":"DP-1"},{"id":3,"windows":1,"monitor":"DP-1"}]"#,
+        );
+        let hyprctl = Hyprctl::new(runner.clone());
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
+        let mut debounce = RebalanceDebounce::new(Duration::from_millis(200));
+        let mut focus_debounce = FocusSwitchDebounce::new(Duration::from_millis(100));
+
+        assert_eq!(select_config(&hyprctl, &config).expect("select"), None);
+        assert!(
+            !resync(
+                &hyprctl,
+                &config,
+                &mut debounce,
+                &mut focus_debounce,
+                &mut BatchCache::default(),
+            )
+            .expect("resync")
+        );
+        assert!(
+            runner
+                .calls
+                .borrow()
+                .iter()
+                .all(|call| call.first().map(String::as_str) != Some("--batch"))
+        );
+    }
+
     #[test]
     fn socket2_event_source_reads_lines() {
         let (mut writer, reader) = UnixStream::pair().expect("pair");
-        let mut source =
-            Socket2EventSource::new(reader, Duration::from_secs(1)).expect("source");
+        let mut source = Socket2EventSource::new(reader, Duration::from_secs(1)).expect("source");
 
         writer
             .write_all(b"monitoradded>>DP-1\n")
@@ -1091,8 +2930,7 @@ mod tests {
     #[test]
     fn socket2_event_source_reports_disconnect() {
         let (writer, reader) = UnixStream::pair().expect("pair");
-        let mut source =
-            Socket2EventSource::new(reader, Duration::from_secs(1)).expect("source");
+        let mut source = Socket2EventSource::new(reader, Duration::from_secs(1)).expect("source");
 
         drop(writer);
 
@@ -1114,34 +2952,105 @@ mod tests {
     fn allows_rebalance_after_debounce_window() {
         let runner = RecordingRunner::default();
         let hyprctl = Hyprctl::new(runner.clone());
-        let config = Config {
-            primary_monitor: "DP-1".to_string(),
-            secondary_monitor: "HDMI-A-1".to_string(),
-            paired_offset: 2,
-            workspace_count: 2,
-            wrap_cycling: true,
-        };
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(2)
+            .build()
+            .expect("config");
         let mut debounce = RebalanceDebounce::new(Duration::from_millis(200));
         let start = Instant::now();
 
-        assert!(rebalance_for_event_at(
-            &hyprctl,
-            &config,
-            MonitorEventKind::Added,
-            &mut debounce,
-            start,
-        )
-        .expect("rebalance"));
-        assert!(rebalance_for_event_at(
-            &hyprctl,
-            &config,
-            MonitorEventKind::Removed,
-            &mut debounce,
-            start + Duration::from_millis(250),
-        )
-        .expect("rebalance again"));
+        assert!(
+            rebalance_for_event_at(
+                &hyprctl,
+                &config,
+                MonitorEventKind::Added,
+                &mut debounce,
+                &mut BatchCache::default(),
+                start,
+            )
+            .expect("rebalance")
+        );
+        assert!(
+            rebalance_for_event_at(
+                &hyprctl,
+                &config,
+                MonitorEventKind::Removed,
+                &mut debounce,
+                &mut BatchCache::default(),
+                start + Duration::from_millis(250),
+            )
+            .expect("rebalance again")
+        );
 
         let calls = runner.calls.borrow();
         assert_eq!(calls.len(), 2);
     }
+
+    #[test]
+    fn mode_transitions_follow_triggers() {
+        use DaemonMode::{Active, Degraded, Paused, Resyncing, ShuttingDown, Starting};
+        let selected = |paired| ModeTrigger::Selected { paired };
+        let cases = [
+            (Starting, selected(true), Active),
+            (Starting, selected(false), Degraded),
+            (Starting, ModeTrigger::Settling, Starting),
+            (Active, ModeTrigger::Settling, Resyncing),
+            (Degraded, ModeTrigger::Settling, Resyncing),
+            (Resyncing, selected(true), Active),
+            (Active, ModeTrigger::SocketLost, Paused),
+            (Resyncing, ModeTrigger::Unresponsive, Paused),
+            (Paused, ModeTrigger::Settling, Paused),
+            (Paused, selected(false), Degraded),
+            (Active, ModeTrigger::Stop, ShuttingDown),
+            (ShuttingDown, selected(true), ShuttingDown),
+        ];
+
+        for (mode, trigger, expected) in cases {
+            assert_eq!(mode.next(trigger), expected, "{mode:?} on {trigger:?}");
+        }
+    }
+
+    #[test]
+    fn only_active_mode_reacts_to_events() {
+        let table = [
+            (DaemonMode::Starting, false, BarMode::Active),
+            (DaemonMode::Active, true, BarMode::Active),
+            (DaemonMode::Paused, false, BarMode::Disconnected),
+            (DaemonMode::Degraded, false, BarMode::Passthrough),
+            (DaemonMode::Resyncing, false, BarMode::Active),
+            (DaemonMode::ShuttingDown, false, BarMode::Active),
+        ];
+
+        for (mode, reacts, bar) in table {
+            let policy = mode.policy();
+            let expected = if reacts {
+                ModePolicy::ALL
+            } else {
+                ModePolicy::NONE
+            };
+            assert_eq!(policy, expected, "{mode:?}");
+            assert_eq!(mode.bar_mode(), bar, "{mode:?}");
+        }
+        assert_eq!(DaemonMode::ShuttingDown.as_str(), "shutting_down");
+    }
+
+    #[test]
+    fn decisions_settle_on_what_was_sent() {
+        assert_eq!(
+            Decision::Dispatched.settle(true, true),
+            Decision::Dispatched
+        );
+        assert_eq!(
+            Decision::Dispatched.settle(false, true),
+            Decision::Debounced
+        );
+        assert_eq!(
+            Decision::Dispatched.settle(false, false),
+            Decision::NoAction
+        );
+        assert_eq!(Decision::Held.settle(false, true), Decision::Held);
+        assert_eq!(Decision::PairShown.as_str(), "pair_shown");
+    }
 }
@@ -0,0 +1,572 @@
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, ConfigError};
+use crate::daemon;
+use crate::setup::{self, SetupError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fix {
+    CreateDir(PathBuf),
+    WriteFile { path: PathBuf, contents: String },
+    RemoveFile(PathBuf),
+    AddSourceLine { path: PathBuf, line: String },
+}
+
+impl Fix {
+    pub fn describe(&self) -> String {
+        match self {
+            Fix::CreateDir(path) => format!("create {}", path.display()),
+            Fix::WriteFile { path, .. } => format!("regenerate {}", path.display()),
+            Fix::RemoveFile(path) => format!("remove {}", path.display()),
+            Fix::AddSourceLine { path, line } => format!("add '{line}' to {}", path.display()),
+        }
+    }
+
+    pub fn apply(&self) -> Result<(), SetupError> {
+        match self {
+            Fix::CreateDir(path) => fs::create_dir_all(path)?,
+            Fix::WriteFile { path, contents } => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(path, contents)?;
+            }
+            Fix::RemoveFile(path) => fs::remove_file(path)?,
+            Fix::AddSourceLine { path, line } => {
+                setup::update_source_block(path, line, None)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub fix: Option<Fix>,
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            fix: None,
+            hint: None,
+        }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>, fix: Option<Fix>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            fix,
+            hint: None,
+        }
+    }
+
+    fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+pub struct DoctorContext {
+    pub base_dir: PathBuf,
+    pub config_path: PathBuf,
+    pub hypr_config_dir: PathBuf,
+    pub pid_path: PathBuf,
+    pub bin_path: String,
+    pub pid_alive: fn(u32) -> bool,
+    pub safe_mode_error: Option<String>,
+    pub runtime_dir: Option<String>,
+    pub instance_signature: Option<String>,
+    pub search_path: Option<OsString>,
+    pub monitors: Result<Vec<String>, String>,
+}
+
+pub fn run_checks(ctx: &DoctorContext) -> Vec<Check> {
+    let mut checks = vec![base_dir_check(&ctx.base_dir)];
+    checks.extend(environment_checks(ctx));
+    checks.push(hyprctl_check(ctx.search_path.as_deref()));
+    let config = Config::from_path(&ctx.config_path);
+    checks.push(config_check(&ctx.config_path, &config));
+    if let Ok(config) = &config {
+        checks.push(monitor_check(config, &ctx.monitors));
+    }
+    checks.extend(artifact_checks(ctx));
+    checks.extend(source_line_checks(ctx));
+    checks.push(waybar_check(&ctx.base_dir));
+    checks.push(pidfile_check(ctx));
+    checks.extend(safe_mode_check(ctx));
+    checks
+}
+
+fn base_dir_check(base_dir: &Path) -> Check {
+    if base_dir.is_dir() {
+        Check::pass("base dir", base_dir.display().to_string())
+    } else {
+        Check::fail(
+            "base dir",
+            format!("{} is missing", base_dir.display()),
+            Some(Fix::CreateDir(base_dir.to_path_buf())),
+        )
+    }
+}
+
+fn environment_checks(ctx: &DoctorContext) -> Vec<Check> {
+    let (Some(runtime_dir), Some(instance)) = (&ctx.runtime_dir, &ctx.instance_signature) else {
+        let missing = [
+            ("XDG_RUNTIME_DIR", ctx.runtime_dir.is_none()),
+            (
+                "HYPRLAND_INSTANCE_SIGNATURE",
+                ctx.instance_signature.is_none(),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, missing)| missing.then_some(name))
+        .collect::<Vec<_>>();
+        return vec![
+            Check::fail(
+                "hyprland env",
+                format!("{} not set", missing.join(", ")),
+                None,
+            )
+            .with_hint("run hyprspaces from inside a Hyprland session"),
+        ];
+    };
+    let socket = PathBuf::from(daemon::socket2_path(runtime_dir, instance));
+    let socket_check = match fs::metadata(&socket) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            Check::pass("event socket", socket.display().to_string())
+        }
+        Ok(_) => Check::fail(
+            "event socket",
+            format!("{} is not a socket", socket.display()),
+            None,
+        )
+        .with_hint("restart Hyprland to recreate its sockets"),
+        Err(_) => Check::fail(
+            "event socket",
+            format!("{} is missing", socket.display()),
+            None,
+        )
+        .with_hint("check that HYPRLAND_INSTANCE_SIGNATURE matches the running Hyprland"),
+    };
+    vec![
+        Check::pass("hyprland env", format!("instance {instance}")),
+        socket_check,
+    ]
+}
+
+fn hyprctl_check(search_path: Option<&OsStr>) -> Check {
+    let found = search_path
+        .map(env::split_paths)
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join("hyprctl"))
+        .find(|candidate| candidate.is_file());
+    match found {
+        Some(path) => Check::pass("hyprctl", path.display().to_string()),
+        None => Check::fail("hyprctl", "not found on PATH", None)
+            .with_hint("install Hyprland or add hyprctl to PATH"),
+    }
+}
+
+fn config_check(path: &Path, config: &Result<Config, ConfigError>) -> Check {
+    match config {
+        Ok(_) => Check::pass("config", path.display().to_string()),
+        Err(_) if !path.exists() => {
+            Check::fail("config", format!("{} is missing", path.display()), None)
+                .with_hint("run `hyprspaces config init`")
+        }
+        Err(err) => Check::fail("config", err.to_string(), None)
+            .with_hint("fix the config, then check it with `hyprspaces config validate`"),
+    }
+}
+
+fn monitor_check(config: &Config, monitors: &Result<Vec<String>, String>) -> Check {
+    let monitors = match monitors {
+        Ok(monitors) => monitors,
+        Err(err) => {
+            return Check::fail("monitors", format!("could not query monitors: {err}"), None)
+                .with_hint("check that Hyprland is running");
+        }
+    };
+    let missing = [&config.primary_monitor, &config.secondary_monitor]
+        .into_iter()
+        .filter(|name| !monitors.contains(name))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        Check::pass(
+            "monitors",
+            format!("{}, {}", config.primary_monitor, config.secondary_monitor),
+        )
+    } else {
+        Check::fail(
+            "monitors",
+            format!("{} not connected", missing.join(", ")),
+            None,
+        )
+        .with_hint(format!(
+            "connected monitors: {}; update primary_monitor/secondary_monitor",
+            monitors.join(", ")
+        ))
+    }
+}
+
+fn artifact_checks(ctx: &DoctorContext) -> Vec<Check> {
+    if !ctx.config_path.exists() {
+        return Vec::new();
+    }
+    let artifacts = match setup::generated_artifacts(&ctx.base_dir, &ctx.bin_path, &ctx.config_path)
+    {
+        Ok(artifacts) => artifacts,
+        Err(err) => {
+            return vec![Check::fail(
+                "generated fragments",
+                format!("could not render fragments: {err}"),
+                None,
+            )];
+        }
+    };
+    artifacts
+        .into_iter()
+        .map(|artifact| {
+            let name = artifact
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let detail = match fs::read_to_string(&artifact.path) {
+                Ok(contents) if contents == artifact.contents => {
+                    return Check::pass(name, "up to date");
+                }
+                Ok(_) => "stale (differs from the current config)",
+                Err(_) => "missing",
+            };
+            Check::fail(
+                name,
+                detail,
+                Some(Fix::WriteFile {
+                    path: artifact.path,
+                    contents: artifact.contents,
+                }),
+            )
+        })
+        .collect()
+}
+
+fn source_line_checks(ctx: &DoctorContext) -> Vec<Check> {
+    setup::source_lines(&ctx.base_dir, &ctx.hypr_config_dir)
+        .into_iter()
+        .filter_map(|(path, line)| {
+            let contents = fs::read_to_string(&path).ok()?;
+            let name = format!("source line in {}", path.display());
+            Some(
+                if contents.lines().any(|existing| existing.trim() == line) {
+                    Check::pass(name, "present")
+                } else {
+                    Check::fail(
+                        name,
+                        format!("'{line}' is missing"),
+                        Some(Fix::AddSourceLine { path, line }),
+                    )
+                },
+            )
+        })
+        .collect()
+}
+
+fn waybar_check(base_dir: &Path) -> Check {
+    let flag = base_dir.join("waybar").join("installed.flag");
+    if flag.exists() {
+        Check::pass("waybar", "module installed")
+    } else {
+        Check::fail("waybar", "module not installed", None)
+            .with_hint("run `hyprspaces setup install --waybar`")
+    }
+}
+
+fn pidfile_check(ctx: &DoctorContext) -> Check {
+    let Ok(contents) = fs::read_to_string(&ctx.pid_path) else {
+        return Check::fail("daemon pidfile", "daemon is not running", None)
+            .with_hint("start it with `hyprspaces daemon`");
+    };
+    let fix = Some(Fix::RemoveFile(ctx.pid_path.clone()));
+    match contents.trim().parse::<u32>() {
+        Ok(pid) if (ctx.pid_alive)(pid) => Check::pass("daemon pidfile", format!("PID {pid}")),
+        Ok(pid) => Check::fail("daemon pidfile", format!("PID {pid} is not running"), fix),
+        Err(_) => Check::fail("daemon pidfile", "unreadable PID", fix),
+    }
+}
+
+fn safe_mode_check(ctx: &DoctorContext) -> Option<Check> {
+    let error = ctx.safe_mode_error.as_ref()?;
+    Some(Check::fail(
+        "daemon mode",
+        format!("safe mode, config failed to load: {error}"),
+        None,
+    ))
+}
+
+pub fn pid_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+pub fn format_check(check: &Check) -> String {
+    let label = match check.status {
+        CheckStatus::Pass => "ok",
+        CheckStatus::Fail => "fail",
+    };
+    match (&check.status, &check.fix, &check.hint) {
+        (CheckStatus::Fail, Some(fix), _) => format!(
+            "[{label}] {}: {} (fix: {})",
+            check.name,
+            check.detail,
+            fix.describe()
+        ),
+        (CheckStatus::Fail, None, Some(hint)) => {
+            format!("[{label}] {}: {} (hint: {hint})", check.name, check.detail)
+        }
+        _ => format!("[{label}] {}: {}", check.name, check.detail),
+    }
+}
+
+pub fn apply_fixes<R: BufRead, W: Write>(
+    checks: &[Check],
+    assume_yes: bool,
+    input: &mut R,
+    output: &mut W,
+) -> io::Result<usize> {
+    let mut applied = 0;
+    for check in checks {
+        let Some(fix) = check
+            .fix
+            .as_ref()
+            .filter(|_| check.status == CheckStatus::Fail)
+        else {
+            continue;
+        };
+        if !assume_yes {
+            write!(output, "{}? [y/N]: ", capitalize(&fix.describe()))?;
+            output.flush()?;
+            let mut answer = String::new();
+            input.read_line(&mut answer)?;
+            if !matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+                continue;
+            }
+        }
+        match fix.apply() {
+            Ok(()) => {
+                writeln!(output, "fixed: {}", fix.describe())?;
+                applied += 1;
+            }
+            Err(err) => writeln!(output, "failed to {}: {err}", fix.describe())?,
+        }
+    }
+    Ok(applied)
+}
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CheckStatus, DoctorContext, Fix, apply_fixes, format_check, run_checks};
+    use crate::setup;
+    use std::fs;
+    use std::io::Cursor;
+    use std::os::unix::net::UnixListener;
+    use std::path::Path;
+
+    const CONFIG: &str = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+
+    fn context(base_dir: &Path, pid_alive: fn(u32) -> bool) -> DoctorContext {
+        let root = base_dir.parent().expect("parent");
+        let socket_dir = root.join("run").join("hypr").join("sig");
+        fs::create_dir_all(&socket_dir).expect("socket dir");
+        let socket = socket_dir.join(".socket2.sock");
+        if !socket.exists() {
+            UnixListener::bind(&socket).expect("socket");
+        }
+        let bin_dir = root.join("bin");
+        fs::create_dir_all(&bin_dir).expect("bin dir");
+        fs::write(bin_dir.join("hyprctl"), "").expect("hyprctl");
+        DoctorContext {
+            base_dir: base_dir.to_path_buf(),
+            config_path: root.join("paired.json"),
+            hypr_config_dir: root.join("hypr"),
+            pid_path: base_dir.join("daemon.pid"),
+            bin_path: "hyprspaces".to_string(),
+            pid_alive,
+            safe_mode_error: None,
+            runtime_dir: Some(root.join("run").display().to_string()),
+            instance_signature: Some("sig".to_string()),
+            search_path: Some(bin_dir.into_os_string()),
+            monitors: Ok(vec!["DP-1".to_string(), "HDMI-A-1".to_string()]),
+        }
+    }
+
+    fn installed(root: &Path) -> std::path::PathBuf {
+        let base_dir = root.join("hyprspaces");
+        let hypr_dir = root.join("hypr");
+        fs::create_dir_all(&hypr_dir).expect("hypr dir");
+        for file in ["bindings.conf", "autostart.conf", "hyprland.conf"] {
+            fs::write(hypr_dir.join(file), "base\n").expect("hypr file");
+        }
+        fs::write(root.join("paired.json"), CONFIG).expect("config");
+        setup::install(
+            &base_dir,
+            "hyprspaces",
+            &hypr_dir,
+            &root.join("paired.json"),
+            None,
+        )
+        .expect("install");
+        setup::install_waybar(&base_dir, "hyprspaces", &[]).expect("waybar");
+        fs::write(base_dir.join("daemon.pid"), "1\n").expect("pid");
+        base_dir
+    }
+
+    #[test]
+    fn passes_after_install() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = installed(dir.path());
+        let checks = run_checks(&context(&base_dir, |_| true));
+
+        assert!(
+            checks.iter().all(|check| check.status == CheckStatus::Pass),
+            "{checks:?}"
+        );
+    }
+
+    #[test]
+    fn reports_daemon_safe_mode() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = installed(dir.path());
+        let mut ctx = context(&base_dir, |_| true);
+        ctx.safe_mode_error = Some("invalid config json: EOF".to_string());
+
+        let checks = run_checks(&ctx);
+        let check = checks.last().expect("check");
+
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert_eq!(
+            format_check(check),
+            "[fail] daemon mode: safe mode, config failed to load: invalid config json: EOF"
+        );
+    }
+
+    #[test]
+    fn reports_environment_problems_with_hints() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = installed(dir.path());
+        fs::remove_file(base_dir.join("daemon.pid")).expect("pid");
+        let mut ctx = context(&base_dir, |_| true);
+        ctx.instance_signature = None;
+        ctx.search_path = None;
+        ctx.monitors = Ok(vec!["DP-1".to_string()]);
+
+        let failing = run_checks(&ctx)
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .map(format_check)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            failing,
+            vec![
+                "[fail] hyprland env: HYPRLAND_INSTANCE_SIGNATURE not set (hint: run hyprspaces from inside a Hyprland session)",
+                "[fail] hyprctl: not found on PATH (hint: install Hyprland or add hyprctl to PATH)",
+                "[fail] monitors: HDMI-A-1 not connected (hint: connected monitors: DP-1; update primary_monitor/secondary_monitor)",
+                "[fail] daemon pidfile: daemon is not running (hint: start it with `hyprspaces daemon`)",
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_and_fixes_stale_artifacts_sources_and_pidfile() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = installed(dir.path());
+        fs::write(base_dir.join("bindings.conf"), "old\n").expect("stale");
+        fs::write(dir.path().join("hypr").join("autostart.conf"), "base\n").expect("reset");
+        fs::write(base_dir.join("daemon.pid"), "4242\n").expect("pid");
+        let ctx = context(&base_dir, |_| false);
+
+        let checks = run_checks(&ctx);
+        let failing = checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .collect::<Vec<_>>();
+        assert_eq!(failing.len(), 3, "{checks:?}");
+        assert!(matches!(failing[0].fix, Some(Fix::WriteFile { .. })));
+        assert!(matches!(failing[1].fix, Some(Fix::AddSourceLine { .. })));
+        assert!(matches!(failing[2].fix, Some(Fix::RemoveFile(_))));
+
+        let mut output = Vec::new();
+        let applied = apply_fixes(&checks, true, &mut Cursor::new(""), &mut output).expect("apply");
+
+        assert_eq!(applied, 3);
+        let remaining = run_checks(&ctx)
+            .into_iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .collect::<Vec<_>>();
+        assert_eq!(remaining.len(), 1, "{remaining:?}");
+        assert_eq!(remaining[0].detail, "daemon is not running");
+        assert!(remaining[0].fix.is_none());
+    }
+
+    #[test]
+    fn asks_before_applying_fixes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = dir.path().join("hyprspaces");
+        let checks = run_checks(&context(&base_dir, |_| true));
+        assert_eq!(
+            format_check(&checks[0]),
+            format!(
+                "[fail] base dir: {} is missing (fix: create {})",
+                base_dir.display(),
+                base_dir.display()
+            )
+        );
+
+        let mut output = Vec::new();
+        let applied =
+            apply_fixes(&checks, false, &mut Cursor::new("n\n"), &mut output).expect("apply");
+        assert_eq!(applied, 0);
+        assert!(!base_dir.exists());
+
+        let applied =
+            apply_fixes(&checks, false, &mut Cursor::new("y\n"), &mut output).expect("apply");
+        assert_eq!(applied, 1);
+        assert!(base_dir.is_dir());
+        assert!(
+            String::from_utf8(output)
+                .expect("utf8")
+                .contains(&format!("Create {}? [y/N]: ", base_dir.display()))
+        );
+    }
+}
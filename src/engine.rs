@@ -0,0 +1,271 @@
+//! Embeddable facade over the paired-workspace logic.
+//!
+//! `Engine` owns a [`Config`] and a [`HyprlandIpc`] backend and exposes the
+//! same operations as the CLI without requiring callers to build batch
+//! strings or talk to `hyprctl` directly.
+
+use std::env;
+
+use crate::commands::{self, CommandError, SendTarget};
+use crate::config::Config;
+use crate::daemon;
+use crate::hyprctl::{self, Hyprctl, HyprctlError, HyprlandIpc, SocketIpc, SocketRunner};
+use crate::paired::{CycleDirection, WorkspaceRangeError};
+#[cfg(feature = "cli-full")]
+use crate::session::{self, SessionSnapshot};
+
+#[derive(thiserror::Error, Debug)]
+pub enum EngineError {
+    #[error("missing environment variable: {0}")]
+    MissingEnv(&'static str),
+    #[error("{0}")]
+    Hyprctl(#[from] HyprctlError),
+    #[error("{0}")]
+    Workspace(#[from] WorkspaceRangeError),
+    #[error("neither {primary} nor {secondary} is connected")]
+    MonitorsDisconnected { primary: String, secondary: String },
+    #[error("no focused window")]
+    NoActiveWindow,
+    #[error("focused window is on workspace {0}, outside the paired range")]
+    WindowOutsideLayout(u32),
+    #[error("no window is asking for attention")]
+    NoUrgentWindow,
+    #[error("no window with address {0}")]
+    UnknownWindow(String),
+}
+
+impl From<CommandError> for EngineError {
+    fn from(err: CommandError) -> Self {
+        match err {
+            CommandError::Hyprctl(err) => EngineError::Hyprctl(err),
+            CommandError::Workspace(err) => EngineError::Workspace(err),
+            CommandError::MonitorsDisconnected { primary, secondary } => {
+                EngineError::MonitorsDisconnected { primary, secondary }
+            }
+            CommandError::NoActiveWindow => EngineError::NoActiveWindow,
+            CommandError::WindowOutsideLayout(workspace) => {
+                EngineError::WindowOutsideLayout(workspace)
+            }
+            CommandError::NoUrgentWindow => EngineError::NoUrgentWindow,
+            CommandError::UnknownWindow(address) => EngineError::UnknownWindow(address),
+        }
+    }
+}
+
+pub struct Engine<I> {
+    config: Config,
+    ipc: I,
+}
+
+impl Engine<SocketIpc> {
+    /// Connects to the running Hyprland instance over its request socket.
+    pub fn connect(config: Config) -> Result<Self, EngineError> {
+        let runtime_dir =
+            env::var("XDG_RUNTIME_DIR").map_err(|_| EngineError::MissingEnv("XDG_RUNTIME_DIR"))?;
+        let instance = env::var("HYPRLAND_INSTANCE_SIGNATURE")
+            .map_err(|_| EngineError::MissingEnv("HYPRLAND_INSTANCE_SIGNATURE"))?;
+        let path = hyprctl::request_socket_path(&runtime_dir, &instance);
+        let ipc = Hyprctl::new(SocketRunner::new(path));
+        ipc.set_timeout(config.retry.ipc_timeout());
+        Ok(Self::new(config, ipc))
+    }
+}
+
+impl<I: HyprlandIpc> Engine<I> {
+    pub fn new(config: Config, ipc: I) -> Self {
+        Self { config, ipc }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Replaces the config, e.g. after the user edits `paired.json`.
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    pub fn ipc(&self) -> &I {
+        &self.ipc
+    }
+
+    /// Shows the pair holding `workspace` on both monitors.
+    pub fn switch(&self, workspace: u32) -> Result<(), EngineError> {
+        Ok(commands::paired_switch(&self.ipc, &self.config, workspace)?)
+    }
+
+    /// Moves to the next or previous slot, honouring `wrap_cycling`.
+    pub fn cycle(&self, direction: CycleDirection) -> Result<(), EngineError> {
+        Ok(commands::paired_cycle(&self.ipc, &self.config, direction)?)
+    }
+
+    /// Sends the focused window to the pair holding `workspace` and follows it.
+    pub fn move_window(&self, workspace: u32) -> Result<(), EngineError> {
+        Ok(commands::paired_move_window(
+            &self.ipc,
+            &self.config,
+            workspace,
+        )?)
+    }
+
+    /// Moves the focused window to another monitor without switching pairs.
+    pub fn send_to(&self, target: SendTarget) -> Result<Option<u32>, EngineError> {
+        Ok(commands::paired_send_to(&self.ipc, &self.config, target)?)
+    }
+
+    /// Shows the pair holding window `address` and focuses it.
+    pub fn focus_window(&self, address: &str) -> Result<u32, EngineError> {
+        Ok(commands::paired_focus_window(
+            &self.ipc,
+            &self.config,
+            address,
+        )?)
+    }
+
+    /// Moves every paired workspace back to its configured monitor.
+    pub fn rebalance(&self) -> Result<(), EngineError> {
+        Ok(daemon::rebalance_all(&self.ipc, &self.config)?)
+    }
+
+    /// Returns the paired slot of the focused workspace.
+    pub fn active_slot(&self) -> Result<u32, EngineError> {
+        let active = self.ipc.active_workspace_id()?;
+        Ok(self.config.slot_of(active))
+    }
+
+    /// Captures monitors, workspaces, and windows in the session snapshot format.
+    #[cfg(feature = "cli-full")]
+    pub fn snapshot(&self) -> Result<SessionSnapshot, EngineError> {
+        Ok(session::capture_snapshot(&self.ipc, &self.config)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Engine, EngineError};
+    use crate::config::{Config, OutOfRangePolicy, RebalanceMode};
+    use crate::hyprctl::{paired_switch_batch, rebalance_batch};
+    use crate::paired::CycleDirection;
+    use crate::testing::ScriptedIpc;
+    use std::collections::BTreeMap;
+
+    fn scripted() -> ScriptedIpc {
+        ScriptedIpc::new()
+            .with_json("activeworkspace", r#"{"id":13}"#)
+            .with_json(
+                "monitors",
+                r#"[{"id":0,"name":"DP-1","x":0},{"id":1,"name":"HDMI-A-1","x":1920}]"#,
+            )
+            .with_json(
+                "workspaces",
+                r#"[{"id":13,"windows":1,"monitor":"HDMI-A-1"}]"#,
+            )
+            .with_json("clients", r#"[{"address":"0x1","workspace":{"id":13}}]"#)
+    }
+
+    fn engine(ipc: &ScriptedIpc, policy: OutOfRangePolicy) -> Engine<ScriptedIpc> {
+        let config = Config {
+            primary_monitor: "DP-1".to_string(),
+            secondary_monitor: "HDMI-A-1".to_string(),
+            paired_offset: 10,
+            workspace_count: 10,
+            wrap_cycling: true,
+            cycle_skip_empty: false,
+            out_of_range: policy,
+            profiles: Vec::new(),
+            rebalance_on_start: true,
+            hotplug_settle_ms: 0,
+            schedules: Vec::new(),
+            rebalance_mode: RebalanceMode::Strict,
+            idle_inhibit_slots: Vec::new(),
+            launch: BTreeMap::new(),
+            names: BTreeMap::new(),
+            theme_source: Default::default(),
+            waybar: Default::default(),
+            drag_focus_delay_ms: 0,
+            metrics_address: None,
+            retry: Default::default(),
+            fast_switch: false,
+            enforce_range: false,
+            jump_to_urgent: false,
+            cursor_follows_focus: false,
+            instant_rebalance: false,
+            debounce: Default::default(),
+            layout: Vec::new(),
+            sticky_classes: Vec::new(),
+            reconcile: Default::default(),
+            primary_match: None,
+            secondary_match: None,
+            auto_primary: Default::default(),
+            workspace_base: 1,
+            restore: Default::default(),
+        };
+        Engine::new(config, ipc.clone())
+    }
+
+    #[test]
+    fn switches_and_rebalances_without_exposing_batches() {
+        let ipc = scripted();
+        let engine = engine(&ipc, OutOfRangePolicy::Normalize);
+
+        engine.switch(4).expect("switch");
+        engine.rebalance().expect("rebalance");
+
+        let calls = ipc.calls();
+        assert_eq!(calls[0], vec!["-j".to_string(), "monitors".to_string()]);
+        assert_eq!(
+            calls[1],
+            vec![
+                "--batch".to_string(),
+                paired_switch_batch("DP-1", "HDMI-A-1", 4, 10)
+            ]
+        );
+        assert_eq!(
+            calls[2],
+            vec![
+                "--batch".to_string(),
+                rebalance_batch("DP-1", "HDMI-A-1", 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles_from_the_active_slot() {
+        let ipc = scripted();
+        let engine = engine(&ipc, OutOfRangePolicy::Normalize);
+
+        assert_eq!(engine.active_slot().expect("slot"), 3);
+        engine.cycle(CycleDirection::Next).expect("cycle");
+
+        let calls = ipc.calls();
+        assert_eq!(
+            calls.last().expect("batch"),
+            &vec![
+                "--batch".to_string(),
+                paired_switch_batch("DP-1", "HDMI-A-1", 4, 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn surfaces_out_of_range_errors() {
+        let ipc = scripted();
+        let engine = engine(&ipc, OutOfRangePolicy::Error);
+
+        assert!(matches!(engine.switch(15), Err(EngineError::Workspace(_))));
+        assert!(ipc.calls().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "cli-full")]
+    fn captures_snapshots() {
+        let ipc = scripted();
+        let engine = engine(&ipc, OutOfRangePolicy::Normalize);
+
+        let snapshot = engine.snapshot().expect("snapshot");
+
+        assert_eq!(snapshot.focus.workspace_id, 13);
+        assert_eq!(snapshot.focus.monitor.as_deref(), Some("HDMI-A-1"));
+        assert_eq!(snapshot.clients[0].paired_slot, 3);
+    }
+}
@@ -0,0 +1,275 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::daemon::{DaemonEvent, EventSource, FocusEvent, MonitorEventKind, WindowEvent};
+
+type Callback<'a, C, E> = Box<dyn FnMut(&mut C, &DaemonEvent) -> Result<bool, E> + 'a>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Poll {
+    Dispatched,
+    Disconnected,
+}
+
+struct Handler<'a, C, E> {
+    throttle: Duration,
+    last_fired: Option<Instant>,
+    callback: Callback<'a, C, E>,
+}
+
+impl<C, E> Handler<'_, C, E> {
+    fn ready(&self, at: Instant) -> bool {
+        match self.last_fired {
+            Some(last_fired) => at.saturating_duration_since(last_fired) >= self.throttle,
+            None => true,
+        }
+    }
+}
+
+pub struct EventBus<'a, C, E = io::Error> {
+    handlers: Vec<Handler<'a, C, E>>,
+}
+
+impl<C, E> Default for EventBus<'_, C, E> {
+    fn default() -> Self {
+        Self {
+            handlers: Vec::new(),
+        }
+    }
+}
+
+impl<'a, C, E> EventBus<'a, C, E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on_event(
+        &mut self,
+        throttle: Duration,
+        mut callback: impl FnMut(&mut C, &DaemonEvent) -> Result<(), E> + 'a,
+    ) -> &mut Self {
+        self.register(throttle, move |ctx, event| {
+            callback(ctx, event).map(|()| true)
+        })
+    }
+
+    pub fn on_focus(
+        &mut self,
+        throttle: Duration,
+        mut callback: impl FnMut(&mut C, &FocusEvent) -> Result<(), E> + 'a,
+    ) -> &mut Self {
+        self.register(throttle, move |ctx, event| match event {
+            DaemonEvent::Focus(focus) => callback(ctx, focus).map(|()| true),
+            _ => Ok(false),
+        })
+    }
+
+    pub fn on_monitor_change(
+        &mut self,
+        throttle: Duration,
+        mut callback: impl FnMut(&mut C, MonitorEventKind) -> Result<(), E> + 'a,
+    ) -> &mut Self {
+        self.register(throttle, move |ctx, event| match event {
+            DaemonEvent::Monitor { kind, .. } => callback(ctx, *kind).map(|()| true),
+            _ => Ok(false),
+        })
+    }
+
+    pub fn on_window_open(
+        &mut self,
+        throttle: Duration,
+        mut callback: impl FnMut(&mut C, &str, Option<u32>) -> Result<(), E> + 'a,
+    ) -> &mut Self {
+        self.register(throttle, move |ctx, event| match event {
+            DaemonEvent::Window(WindowEvent::Opened {
+                address,
+                workspace_id,
+                ..
+            }) => callback(ctx, address, *workspace_id).map(|()| true),
+            _ => Ok(false),
+        })
+    }
+
+    pub fn on_window_close(
+        &mut self,
+        throttle: Duration,
+        mut callback: impl FnMut(&mut C, &str) -> Result<(), E> + 'a,
+    ) -> &mut Self {
+        self.register(throttle, move |ctx, event| match event {
+            DaemonEvent::Window(WindowEvent::Closed { address }) => {
+                callback(ctx, address).map(|()| true)
+            }
+            _ => Ok(false),
+        })
+    }
+
+    pub fn on_window_move(
+        &mut self,
+        throttle: Duration,
+        mut callback: impl FnMut(&mut C, &str, Option<u32>) -> Result<(), E> + 'a,
+    ) -> &mut Self {
+        self.register(throttle, move |ctx, event| match event {
+            DaemonEvent::Window(WindowEvent::Moved {
+                address,
+                workspace_id,
+            }) => callback(ctx, address, *workspace_id).map(|()| true),
+            _ => Ok(false),
+        })
+    }
+
+    fn register(
+        &mut self,
+        throttle: Duration,
+        callback: impl FnMut(&mut C, &DaemonEvent) -> Result<bool, E> + 'a,
+    ) -> &mut Self {
+        self.handlers.push(Handler {
+            throttle,
+            last_fired: None,
+            callback: Box::new(callback),
+        });
+        self
+    }
+
+    pub fn dispatch(&mut self, ctx: &mut C, event: &DaemonEvent) -> Result<(), E> {
+        let at = event.at().unwrap_or_else(Instant::now);
+        for handler in &mut self.handlers {
+            if handler.ready(at) && (handler.callback)(ctx, event)? {
+                handler.last_fired = Some(at);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn poll(&mut self, source: &mut dyn EventSource, ctx: &mut C) -> Result<Poll, E>
+    where
+        E: From<io::Error>,
+    {
+        match source.next_event()? {
+            DaemonEvent::Disconnected => Ok(Poll::Disconnected),
+            event => {
+                self.dispatch(ctx, &event)?;
+                Ok(Poll::Dispatched)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventBus, Poll};
+    use crate::daemon::{DaemonEvent, EventSource, MonitorEventKind, parse_socket2_event};
+    use std::collections::VecDeque;
+    use std::io;
+    use std::time::{Duration, Instant};
+
+    struct ScriptedSource {
+        events: VecDeque<DaemonEvent>,
+    }
+
+    impl EventSource for ScriptedSource {
+        fn next_event(&mut self) -> io::Result<DaemonEvent> {
+            Ok(self.events.pop_front().unwrap_or(DaemonEvent::Disconnected))
+        }
+    }
+
+    #[derive(Default)]
+    struct Seen {
+        focus: Vec<Option<u32>>,
+        monitors: Vec<MonitorEventKind>,
+        opened: Vec<String>,
+        all: usize,
+    }
+
+    fn bus() -> EventBus<'static, Seen> {
+        let mut bus = EventBus::new();
+        bus.on_focus(Duration::from_millis(100), |seen: &mut Seen, focus| {
+            seen.focus.push(focus.workspace_id);
+            Ok(())
+        })
+        .on_monitor_change(Duration::ZERO, |seen, kind| {
+            seen.monitors.push(kind);
+            Ok(())
+        })
+        .on_window_open(Duration::ZERO, |seen, address, _| {
+            seen.opened.push(address.to_string());
+            Ok(())
+        })
+        .on_event(Duration::ZERO, |seen, _| {
+            seen.all += 1;
+            Ok(())
+        });
+        bus
+    }
+
+    #[test]
+    fn routes_events_to_typed_callbacks() {
+        let now = Instant::now();
+        let mut bus = bus();
+        let mut seen = Seen::default();
+
+        for line in [
+            "workspacev2>>3,3",
+            "monitoradded>>DP-2",
+            "openwindow>>abc,3,kitty,shell",
+        ] {
+            let event = parse_socket2_event(line, now).expect("event");
+            bus.dispatch(&mut seen, &event).expect("dispatch");
+        }
+
+        assert_eq!(seen.focus, vec![Some(3)]);
+        assert_eq!(seen.monitors, vec![MonitorEventKind::Added]);
+        assert_eq!(seen.opened, vec!["0xabc".to_string()]);
+        assert_eq!(seen.all, 3);
+    }
+
+    #[test]
+    fn throttles_each_handler_independently() {
+        let start = Instant::now();
+        let mut bus = bus();
+        let mut seen = Seen::default();
+
+        for (line, offset) in [
+            ("workspacev2>>1,1", 0),
+            ("workspacev2>>2,2", 50),
+            ("workspacev2>>3,3", 150),
+        ] {
+            let event =
+                parse_socket2_event(line, start + Duration::from_millis(offset)).expect("event");
+            bus.dispatch(&mut seen, &event).expect("dispatch");
+        }
+
+        assert_eq!(seen.focus, vec![Some(1), Some(3)]);
+        assert_eq!(seen.all, 3);
+    }
+
+    #[test]
+    fn polls_until_disconnected() {
+        let now = Instant::now();
+        let mut source = ScriptedSource {
+            events: VecDeque::from([
+                parse_socket2_event("monitorremoved>>DP-2", now).expect("event")
+            ]),
+        };
+        let mut bus = bus();
+        let mut seen = Seen::default();
+
+        assert_eq!(
+            bus.poll(&mut source, &mut seen).expect("poll"),
+            Poll::Dispatched
+        );
+        assert_eq!(
+            bus.poll(&mut source, &mut seen).expect("poll"),
+            Poll::Disconnected
+        );
+        assert_eq!(seen.monitors, vec![MonitorEventKind::Removed]);
+    }
+
+    #[test]
+    fn propagates_callback_errors() {
+        let mut bus: EventBus<'_, (), &str> = EventBus::new();
+        bus.on_event(Duration::ZERO, |_, _| Err("boom"));
+
+        let event = DaemonEvent::Timeout { at: Instant::now() };
+        assert_eq!(bus.dispatch(&mut (), &event), Err("boom"));
+    }
+}
@@ -0,0 +1,101 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paired::PairedSide;
+
+#[derive(thiserror::Error, Debug)]
+pub enum HoldError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid hold state: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldAction {
+    On,
+    Off,
+    Toggle,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct HoldState {
+    monitor: PairedSide,
+}
+
+pub fn state_path(base_dir: &Path) -> PathBuf {
+    base_dir.join("hold.json")
+}
+
+/// The held monitor, if any.
+pub fn load(path: &Path) -> Result<Option<PairedSide>, HoldError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(serde_json::from_str::<HoldState>(&contents)?.monitor)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Applies and persists `action` for `side`, returning the new hold.
+pub fn update(
+    path: &Path,
+    action: HoldAction,
+    side: PairedSide,
+) -> Result<Option<PairedSide>, HoldError> {
+    let held = match (action, load(path)?) {
+        (HoldAction::On, _) | (HoldAction::Toggle, None) => Some(side),
+        (HoldAction::Off, _) | (HoldAction::Toggle, Some(_)) => None,
+    };
+    match held {
+        Some(monitor) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, serde_json::to_string_pretty(&HoldState { monitor })?)?;
+        }
+        None => match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        },
+    }
+    Ok(held)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HoldAction, load, update};
+    use crate::paired::PairedSide;
+
+    #[test]
+    fn toggles_and_persists_the_held_monitor() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("hold.json");
+
+        assert_eq!(load(&path).expect("load"), None);
+        assert_eq!(
+            update(&path, HoldAction::Toggle, PairedSide::Secondary).expect("hold"),
+            Some(PairedSide::Secondary)
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("read"),
+            "{\n  \"monitor\": \"secondary\"\n}"
+        );
+        assert_eq!(
+            update(&path, HoldAction::On, PairedSide::Primary).expect("hold"),
+            Some(PairedSide::Primary)
+        );
+        assert_eq!(load(&path).expect("load"), Some(PairedSide::Primary));
+        assert_eq!(
+            update(&path, HoldAction::Toggle, PairedSide::Secondary).expect("release"),
+            None
+        );
+        assert!(!path.exists());
+        assert_eq!(
+            update(&path, HoldAction::Off, PairedSide::Secondary).expect("release"),
+            None
+        );
+    }
+}
@@ -1,14 +1,23 @@
+use crate::layout::Layout;
 use crate::paired::normalize_workspace;
-use serde::de::DeserializeOwned;
-use serde::Deserialize;
-use std::process::Command;
 #[cfg(feature = "native-ipc")]
 use hyprland::{
     ctl,
     data::{Clients, Monitors, Workspace, Workspaces},
     dispatch::{Dispatch, DispatchType},
+    keyword::{Keyword, OptionValue},
     shared::{HyprData, HyprDataActive, HyprDataVec},
 };
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fmt::{self, Write as _};
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, thiserror::Error)]
 pub enum HyprctlError {
@@ -28,16 +37,42 @@ pub enum HyprctlError {
     },
     #[error("native ipc error: {0}")]
     Native(String),
+    #[error("unsupported by this compositor: {0}")]
+    Unsupported(String),
+    #[error("hyprctl timed out after {}ms ({command})", timeout.as_millis())]
+    Timeout { command: String, timeout: Duration },
 }
 
+impl HyprctlError {
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            HyprctlError::Io(_)
+                | HyprctlError::CommandFailed { .. }
+                | HyprctlError::Native(_)
+                | HyprctlError::Timeout { .. }
+        )
+    }
+}
+
+const CHILD_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
 pub trait HyprctlRunner {
     fn run(&self, args: &[String]) -> Result<String, HyprctlError>;
+
+    /// Bounds every later `run`; `None` waits indefinitely.
+    fn set_timeout(&self, _timeout: Option<Duration>) {}
 }
 
 pub struct Hyprctl<R> {
     runner: R,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compositor {
+    Hyprland,
+    Sway,
+}
 
 pub trait HyprlandIpc {
     fn batch(&self, batch: &str) -> Result<String, HyprctlError>;
@@ -47,6 +82,15 @@ pub trait HyprlandIpc {
     fn monitors(&self) -> Result<Vec<MonitorInfo>, HyprctlError>;
     fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, HyprctlError>;
     fn clients(&self) -> Result<Vec<ClientInfo>, HyprctlError>;
+    fn get_option(&self, name: &str) -> Result<OptionInfo, HyprctlError>;
+    fn keyword(&self, name: &str, value: &str) -> Result<String, HyprctlError>;
+
+    fn compositor(&self) -> Compositor {
+        Compositor::Hyprland
+    }
+
+    /// Fails stalled requests with [`HyprctlError::Timeout`] where supported.
+    fn set_timeout(&self, _timeout: Option<Duration>) {}
 }
 
 #[cfg(feature = "native-ipc")]
@@ -128,7 +172,19 @@ impl HyprlandIpc for NativeIpc {
                 Ok(MonitorInfo {
                     name: monitor.name,
                     x: monitor.x,
+                    y: monitor.y,
+                    width: u32::from(monitor.width),
+                    height: u32::from(monitor.height),
+                    scale: f64::from(monitor.scale),
+                    transform: monitor.transform as u8,
+                    focused: monitor.focused,
                     id: Self::monitor_id(monitor.id)?,
+                    mirror_of: None,
+                    active_workspace: Self::workspace_id(monitor.active_workspace.id)
+                        .ok()
+                        .filter(|id| *id > 0),
+                    description: Some(monitor.description),
+                    serial: None,
                 })
             })
             .collect()
@@ -166,10 +222,39 @@ impl HyprlandIpc for NativeIpc {
                     initial_title: Some(client.initial_title),
                     app_id: None,
                     pid: Some(client.pid),
+                    focus_history_id: Some(client.focus_history_id.into()),
+                    floating: client.floating,
+                    at: Some([client.at.0.into(), client.at.1.into()]),
+                    size: Some([client.size.0.into(), client.size.1.into()]),
+                    fullscreen: client.fullscreen as u8,
+                    fullscreen_client: client.fullscreen_client as u8,
+                    pseudo: false,
                 })
             })
             .collect()
     }
+
+    fn get_option(&self, name: &str) -> Result<OptionInfo, HyprctlError> {
+        let keyword = Keyword::get(name).map_err(Self::map_error)?;
+        let mut option = OptionInfo {
+            option: keyword.option,
+            int: None,
+            float: None,
+            string: None,
+            set: keyword.set,
+        };
+        match keyword.value {
+            OptionValue::Int(value) => option.int = Some(value),
+            OptionValue::Float(value) => option.float = Some(value),
+            OptionValue::String(value) => option.string = Some(value),
+        }
+        Ok(option)
+    }
+
+    fn keyword(&self, name: &str, value: &str) -> Result<String, HyprctlError> {
+        Keyword::set(name, value).map_err(Self::map_error)?;
+        Ok("ok".to_string())
+    }
 }
 
 impl<R> Hyprctl<R> {
@@ -225,6 +310,18 @@ impl<R: HyprctlRunner> Hyprctl<R> {
         let clients: Vec<ClientInfo> = parse_json("clients", &output)?;
         Ok(clients)
     }
+
+    pub fn get_option(&self, name: &str) -> Result<OptionInfo, HyprctlError> {
+        let args = vec!["-j".to_string(), "getoption".to_string(), name.to_string()];
+        let output = self.runner.run(&args)?;
+        let option: OptionInfo = parse_json("getoption", &output)?;
+        Ok(option)
+    }
+
+    pub fn keyword(&self, name: &str, value: &str) -> Result<String, HyprctlError> {
+        let args = vec!["keyword".to_string(), name.to_string(), value.to_string()];
+        self.runner.run(&args)
+    }
 }
 
 impl<R: HyprctlRunner> HyprlandIpc for Hyprctl<R> {
@@ -255,38 +352,166 @@ impl<R: HyprctlRunner> HyprlandIpc for Hyprctl<R> {
     fn clients(&self) -> Result<Vec<ClientInfo>, HyprctlError> {
         Hyprctl::clients(self)
     }
+
+    fn get_option(&self, name: &str) -> Result<OptionInfo, HyprctlError> {
+        Hyprctl::get_option(self, name)
+    }
+
+    fn keyword(&self, name: &str, value: &str) -> Result<String, HyprctlError> {
+        Hyprctl::keyword(self, name, value)
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        self.runner.set_timeout(timeout);
+    }
 }
 
 pub struct SystemHyprctlRunner {
     program: String,
+    timeout: Cell<Option<Duration>>,
 }
 
 impl SystemHyprctlRunner {
     pub fn new(program: impl Into<String>) -> Self {
         Self {
             program: program.into(),
+            timeout: Cell::new(None),
         }
     }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.timeout.set(Some(timeout));
+        self
+    }
 }
 
 impl HyprctlRunner for SystemHyprctlRunner {
     fn run(&self, args: &[String]) -> Result<String, HyprctlError> {
-        let output = Command::new(&self.program)
+        let mut child = Command::new(&self.program)
             .args(args)
-            .output()
-            ?;
-        if !output.status.success() {
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+        let status = match self.timeout.get() {
+            Some(timeout) => match wait_until(&mut child, Instant::now() + timeout)? {
+                Some(status) => status,
+                None => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(HyprctlError::Timeout {
+                        command: format_command(&self.program, args),
+                        timeout,
+                    });
+                }
+            },
+            None => child.wait()?,
+        };
+        let stdout = stdout.join().unwrap_or_default();
+        if !status.success() {
             return Err(HyprctlError::CommandFailed {
                 command: format_command(&self.program, args),
-                status: output.status.code().unwrap_or(-1),
-                stderr: String::from_utf8_lossy(&output.stderr)
+                status: status.code().unwrap_or(-1),
+                stderr: String::from_utf8_lossy(&stderr.join().unwrap_or_default())
                     .trim_end()
                     .to_string(),
             });
         }
-        Ok(String::from_utf8_lossy(&output.stdout)
-            .trim_end()
-            .to_string())
+        Ok(String::from_utf8_lossy(&stdout).trim_end().to_string())
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        self.timeout.set(timeout);
+    }
+}
+
+/// Drains a child's pipe on its own thread so it can't fill up.
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        output
+    })
+}
+
+/// The child's exit status, or `None` if it is still running at `deadline`.
+fn wait_until(child: &mut Child, deadline: Instant) -> std::io::Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(CHILD_POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+pub type SocketIpc = Hyprctl<SocketRunner>;
+
+pub struct SocketRunner {
+    path: PathBuf,
+    timeout: Cell<Option<Duration>>,
+}
+
+impl SocketRunner {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            timeout: Cell::new(None),
+        }
+    }
+}
+
+impl HyprctlRunner for SocketRunner {
+    fn run(&self, args: &[String]) -> Result<String, HyprctlError> {
+        let request = socket_request(args);
+        let timeout = self.timeout.get();
+        let exchange = || -> std::io::Result<String> {
+            let mut stream = UnixStream::connect(&self.path)?;
+            stream.set_read_timeout(timeout)?;
+            stream.set_write_timeout(timeout)?;
+            stream.write_all(request.as_bytes())?;
+            let mut response = String::new();
+            stream.read_to_string(&mut response)?;
+            Ok(response)
+        };
+        match exchange() {
+            Ok(response) => Ok(response.trim_end().to_string()),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+                ) =>
+            {
+                Err(HyprctlError::Timeout {
+                    command: request,
+                    timeout: timeout.unwrap_or_default(),
+                })
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        self.timeout.set(timeout);
+    }
+}
+
+pub fn request_socket_path(runtime_dir: &str, instance_signature: &str) -> String {
+    format!("{}/hypr/{}/.socket.sock", runtime_dir, instance_signature)
+}
+
+pub fn socket_request(args: &[String]) -> String {
+    match args {
+        [flag, rest @ ..] if flag == "-j" => format!("j/{}", rest.join(" ")),
+        [flag, batch] if flag == "--batch" => format!("[[BATCH]]{batch}"),
+        _ => args.join(" "),
     }
 }
 
@@ -310,14 +535,140 @@ struct ActiveWorkspace {
     id: u32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct MonitorInfo {
     pub name: String,
     pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    /// Mode size in pixels, before scale and transform.
+    #[serde(default)]
+    pub width: u32,
+    #[serde(default)]
+    pub height: u32,
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    /// wl_output transform: 0-3 rotate by 90° steps, 4-7 flip then rotate.
+    #[serde(default)]
+    pub transform: u8,
+    #[serde(default)]
+    pub focused: bool,
     pub id: i32,
+    #[serde(default, rename = "mirrorOf")]
+    pub mirror_of: Option<String>,
+    #[serde(
+        default,
+        rename = "activeWorkspace",
+        deserialize_with = "deserialize_active_workspace"
+    )]
+    pub active_workspace: Option<u32>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub serial: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+impl Default for MonitorInfo {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+            scale: default_scale(),
+            transform: 0,
+            focused: false,
+            id: 0,
+            mirror_of: None,
+            active_workspace: None,
+            description: None,
+            serial: None,
+        }
+    }
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn deserialize_active_workspace<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Active {
+        id: i64,
+    }
+    Ok(Option::<Active>::deserialize(deserializer)?
+        .and_then(|active| u32::try_from(active.id).ok())
+        .filter(|id| *id > 0))
+}
+
+/// Hyprland before 0.42 reported `fullscreen` as a bool.
+fn deserialize_fullscreen<'de, D>(deserializer: D) -> Result<u8, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Fullscreen {
+        Flag(bool),
+        State(u8),
+    }
+    Ok(match Fullscreen::deserialize(deserializer)? {
+        Fullscreen::Flag(true) => 2,
+        Fullscreen::Flag(false) => 0,
+        Fullscreen::State(state) => state,
+    })
+}
+
+impl MonitorInfo {
+    pub fn is_mirror(&self) -> bool {
+        self.mirror_of
+            .as_deref()
+            .is_some_and(|source| !source.is_empty() && source != "none")
+    }
+
+    /// Size in layout coordinates, after scale and rotation.
+    pub fn logical_size(&self) -> (u32, u32) {
+        let scale = if self.scale > 0.0 { self.scale } else { 1.0 };
+        let width = (f64::from(self.width) / scale).round() as u32;
+        let height = (f64::from(self.height) / scale).round() as u32;
+        if self.transform % 2 == 1 {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// The middle of the monitor in layout coordinates.
+    pub fn center(&self) -> (i32, i32) {
+        let (width, height) = self.logical_size();
+        (self.x + (width / 2) as i32, self.y + (height / 2) as i32)
+    }
+
+    /// `2560x1440+0+0`, plus the scale when it isn't 1 and a rotation marker.
+    pub fn geometry(&self) -> String {
+        let mut geometry = format!("{}x{}+{}+{}", self.width, self.height, self.x, self.y);
+        if (self.scale - 1.0).abs() > f64::EPSILON {
+            geometry.push_str(&format!(" @{}x", self.scale));
+        }
+        if self.transform != 0 {
+            geometry.push_str(&format!(" transform {}", self.transform));
+        }
+        geometry
+    }
+}
+
+pub fn logical_monitors(monitors: Vec<MonitorInfo>) -> Vec<MonitorInfo> {
+    monitors
+        .into_iter()
+        .filter(|monitor| !monitor.is_mirror())
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WorkspaceInfo {
     pub id: u32,
     pub windows: u32,
@@ -343,6 +694,23 @@ pub struct ClientInfo {
     pub app_id: Option<String>,
     #[serde(default)]
     pub pid: Option<i32>,
+    #[serde(default, rename = "focusHistoryID")]
+    pub focus_history_id: Option<i32>,
+    #[serde(default)]
+    pub floating: bool,
+    /// Top-left corner in layout coordinates.
+    #[serde(default)]
+    pub at: Option<[i32; 2]>,
+    #[serde(default)]
+    pub size: Option<[i32; 2]>,
+    /// Internal fullscreen state: 0 none, 1 maximized, 2 fullscreen, 3 both.
+    #[serde(default, deserialize_with = "deserialize_fullscreen")]
+    pub fullscreen: u8,
+    /// The fullscreen state the client itself is told about.
+    #[serde(default, rename = "fullscreenClient")]
+    pub fullscreen_client: u8,
+    #[serde(default)]
+    pub pseudo: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -352,39 +720,458 @@ pub struct WorkspaceRef {
     pub name: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OptionInfo {
+    pub option: String,
+    #[serde(default)]
+    pub int: Option<i64>,
+    #[serde(default)]
+    pub float: Option<f64>,
+    #[serde(default, rename = "str")]
+    pub string: Option<String>,
+    #[serde(default)]
+    pub set: bool,
+}
+
+impl OptionInfo {
+    pub fn as_bool(&self) -> Option<bool> {
+        self.int.map(|value| value != 0)
+    }
+}
+
+pub const WORKSPACE_BACK_AND_FORTH: &str = "binds:workspace_back_and_forth";
+pub const ANIMATIONS_ENABLED: &str = "animations:enabled";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HyprlandOptions {
+    pub workspace_back_and_forth: bool,
+}
+
+impl HyprlandOptions {
+    pub fn query(ipc: &dyn HyprlandIpc) -> Self {
+        let flag = |name: &str| {
+            ipc.get_option(name)
+                .ok()
+                .and_then(|option| option.as_bool())
+                .unwrap_or(false)
+        };
+        Self {
+            workspace_back_and_forth: flag(WORKSPACE_BACK_AND_FORTH),
+        }
+    }
+}
+
+const BATCH_SEPARATOR: &str = " ; ";
+
+/// Hyprland can't escape `;` in batches or `,` in window arguments.
+fn is_batch_separator(ch: char) -> bool {
+    ch == ';' || ch == ',' || ch.is_control()
+}
+
+/// Whether `value` reaches Hyprland unchanged inside a batch argument.
+pub fn is_batch_safe(value: &str) -> bool {
+    !value.chars().any(is_batch_separator)
+}
+
+/// A batch argument with separators replaced by `_`.
+struct BatchArg<'a>(&'a str);
+
+impl fmt::Display for BatchArg<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_batch_safe(self.0) {
+            return f.write_str(self.0);
+        }
+        for ch in self.0.chars() {
+            f.write_char(if is_batch_separator(ch) { '_' } else { ch })?;
+        }
+        Ok(())
+    }
+}
+
+/// A numeric workspace, or a special one by its full `special:<name>` name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceTarget<'a> {
+    Id(u32),
+    Special(&'a str),
+}
+
+impl<'a> WorkspaceTarget<'a> {
+    /// Reads a workspace as `hyprctl` names it: numeric names are ids.
+    pub fn from_name(name: &'a str) -> Self {
+        match name.parse() {
+            Ok(id) => WorkspaceTarget::Id(id),
+            Err(_) => WorkspaceTarget::Special(name),
+        }
+    }
+}
+
+impl fmt::Display for WorkspaceTarget<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkspaceTarget::Id(id) => write!(f, "{id}"),
+            WorkspaceTarget::Special(name) => write!(f, "{}", BatchArg(name)),
+        }
+    }
+}
+
+/// A dispatcher with typed arguments, displayed the way `hyprctl dispatch` takes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dispatcher<'a> {
+    Workspace(u32),
+    /// `workspace name:<id>`, which Hyprland never toggles back and forth.
+    WorkspaceByName(u32),
+    FocusMonitor(&'a str),
+    FocusWindow {
+        address: &'a str,
+    },
+    MoveToWorkspaceSilent {
+        target: WorkspaceTarget<'a>,
+        address: &'a str,
+    },
+    MoveWorkspaceToMonitor {
+        workspace: u32,
+        monitor: &'a str,
+    },
+    SetFloating {
+        address: &'a str,
+    },
+    MoveWindowPixel {
+        x: i32,
+        y: i32,
+        address: &'a str,
+    },
+    ResizeWindowPixel {
+        width: i32,
+        height: i32,
+        address: &'a str,
+    },
+    Pseudo {
+        address: &'a str,
+    },
+    /// Applies to the focused window.
+    FullscreenState {
+        internal: u8,
+        client: u8,
+    },
+    MoveCursor {
+        x: i32,
+        y: i32,
+    },
+}
+
+impl Dispatcher<'_> {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dispatcher::Workspace(_) | Dispatcher::WorkspaceByName(_) => "workspace",
+            Dispatcher::FocusMonitor(_) => "focusmonitor",
+            Dispatcher::FocusWindow { .. } => "focuswindow",
+            Dispatcher::MoveToWorkspaceSilent { .. } => "movetoworkspacesilent",
+            Dispatcher::MoveWorkspaceToMonitor { .. } => "moveworkspacetomonitor",
+            Dispatcher::SetFloating { .. } => "setfloating",
+            Dispatcher::MoveWindowPixel { .. } => "movewindowpixel",
+            Dispatcher::ResizeWindowPixel { .. } => "resizewindowpixel",
+            Dispatcher::Pseudo { .. } => "pseudo",
+            Dispatcher::FullscreenState { .. } => "fullscreenstate",
+            Dispatcher::MoveCursor { .. } => "movecursor",
+        }
+    }
+}
+
+impl fmt::Display for Dispatcher<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.name())?;
+        match self {
+            Dispatcher::Workspace(workspace) => write!(f, "{workspace}"),
+            Dispatcher::WorkspaceByName(workspace) => write!(f, "name:{workspace}"),
+            Dispatcher::FocusMonitor(monitor) => write!(f, "{}", BatchArg(monitor)),
+            Dispatcher::FocusWindow { address }
+            | Dispatcher::SetFloating { address }
+            | Dispatcher::Pseudo { address } => write!(f, "address:{}", BatchArg(address)),
+            Dispatcher::MoveToWorkspaceSilent { target, address } => {
+                write!(f, "{target},address:{}", BatchArg(address))
+            }
+            Dispatcher::MoveWorkspaceToMonitor { workspace, monitor } => {
+                write!(f, "{workspace} {}", BatchArg(monitor))
+            }
+            Dispatcher::MoveWindowPixel { x, y, address } => {
+                write!(f, "exact {x} {y},address:{}", BatchArg(address))
+            }
+            Dispatcher::ResizeWindowPixel {
+                width,
+                height,
+                address,
+            } => write!(f, "exact {width} {height},address:{}", BatchArg(address)),
+            Dispatcher::FullscreenState { internal, client } => write!(f, "{internal} {client}"),
+            Dispatcher::MoveCursor { x, y } => write!(f, "{x} {y}"),
+        }
+    }
+}
+
+/// Appends `dispatcher` to a batch string being built in `out`.
+fn push_dispatch(out: &mut String, dispatcher: &Dispatcher<'_>) {
+    if !out.is_empty() {
+        out.push_str(BATCH_SEPARATOR);
+    }
+    let _ = write!(out, "dispatch {dispatcher}");
+}
+
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct HyprctlBatch {
-    commands: Vec<String>,
+    buffer: String,
 }
 
 impl HyprctlBatch {
     pub fn new() -> Self {
         Self {
-            commands: Vec::new(),
+            buffer: String::new(),
         }
     }
 
-    pub fn dispatch(&mut self, dispatcher: &str, argument: &str) {
-        self.commands
-            .push(format!("dispatch {} {}", dispatcher, argument));
+    pub fn keyword(&mut self, name: &str, value: &str) -> &mut Self {
+        if !self.buffer.is_empty() {
+            self.buffer.push_str(BATCH_SEPARATOR);
+        }
+        let (name, value) = (BatchArg(name), BatchArg(value));
+        let _ = write!(self.buffer, "keyword {name} {value}");
+        self
+    }
+
+    /// Continues a batch rendered elsewhere, e.g. by [`PairedSwitchTemplate`].
+    pub fn from_argument(argument: String) -> Self {
+        Self { buffer: argument }
+    }
+
+    pub fn push(&mut self, dispatcher: Dispatcher<'_>) -> &mut Self {
+        push_dispatch(&mut self.buffer, &dispatcher);
+        self
+    }
+
+    /// Queues a dispatcher that has no [`Dispatcher`] variant; `;` is replaced.
+    pub fn dispatch(&mut self, dispatcher: &str, argument: &str) -> &mut Self {
+        if !self.buffer.is_empty() {
+            self.buffer.push_str(BATCH_SEPARATOR);
+        }
+        let _ = write!(self.buffer, "dispatch {dispatcher} ");
+        for ch in argument.chars() {
+            self.buffer.push(if ch == ';' || ch.is_control() {
+                '_'
+            } else {
+                ch
+            });
+        }
+        self
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.buffer
     }
 
     pub fn to_argument(&self) -> String {
-        self.commands.join(" ; ")
+        self.buffer.clone()
+    }
+
+    /// Each queued command on its own, in order.
+    pub fn commands(&self) -> impl Iterator<Item = &str> {
+        self.buffer
+            .split(BATCH_SEPARATOR)
+            .filter(|command| !command.is_empty())
+    }
+
+    pub fn into_argument(self) -> String {
+        self.buffer
+    }
+}
+
+/// How the focused monitor is handled after its counterpart switches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitchStyle {
+    Full,
+    Follow,
+    Fast,
+}
+
+impl SwitchStyle {
+    pub fn new(back_and_forth: bool, fast: bool) -> Self {
+        match (fast, back_and_forth) {
+            (true, _) => SwitchStyle::Fast,
+            (false, true) => SwitchStyle::Follow,
+            (false, false) => SwitchStyle::Full,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairedSwitchTemplate {
+    offset: u32,
+    base: u32,
+    monitors: Vec<String>,
+}
+
+impl PairedSwitchTemplate {
+    pub fn new(layout: &Layout<'_>) -> Self {
+        Self {
+            offset: layout.offset(),
+            base: layout.base(),
+            monitors: layout.monitors().map(str::to_string).collect(),
+        }
+    }
+
+    pub fn matches(&self, layout: &Layout<'_>) -> bool {
+        self.offset == layout.offset()
+            && self.base == layout.base()
+            && self.monitors.len() == layout.member_count()
+            && self
+                .monitors
+                .iter()
+                .map(String::as_str)
+                .eq(layout.monitors())
+    }
+
+    fn focused_member(&self, workspace: u32, focus_monitor: &str) -> usize {
+        let member = (workspace.saturating_sub(self.base) / self.offset) as usize;
+        if self
+            .monitors
+            .get(member)
+            .is_some_and(|monitor| monitor == focus_monitor)
+        {
+            return member;
+        }
+        self.monitors
+            .iter()
+            .position(|monitor| monitor == focus_monitor)
+            .unwrap_or(0)
+    }
+
+    /// Whether `monitors` already show what [`render`](Self::render) would.
+    pub fn is_current(
+        &self,
+        monitors: &[MonitorInfo],
+        workspace: u32,
+        focus_monitor: &str,
+    ) -> bool {
+        let first = self.base + normalize_workspace(workspace, self.offset, self.base) - 1;
+        let focused = self.focused_member(workspace, focus_monitor);
+        let focused_monitor = &self.monitors[focused];
+        let shows = |name: &str, workspace: u32| {
+            monitors
+                .iter()
+                .any(|monitor| monitor.name == name && monitor.active_workspace == Some(workspace))
+        };
+        monitors
+            .iter()
+            .any(|monitor| &monitor.name == focused_monitor && monitor.focused)
+            && shows(focused_monitor, first + self.offset * focused as u32)
+            && self.monitors.iter().enumerate().all(|(member, monitor)| {
+                monitor == focused_monitor
+                    || self.monitors[..member].contains(monitor)
+                    || shows(monitor, first + self.offset * member as u32)
+            })
+    }
+
+    pub fn render(
+        &self,
+        out: &mut String,
+        workspace: u32,
+        focus_monitor: &str,
+        style: SwitchStyle,
+    ) {
+        let first = self.base + normalize_workspace(workspace, self.offset, self.base) - 1;
+        let focused = self.focused_member(workspace, focus_monitor);
+        let focused_monitor = &self.monitors[focused];
+        out.clear();
+        for (member, monitor) in self.monitors.iter().enumerate() {
+            if monitor == focused_monitor || self.monitors[..member].contains(monitor) {
+                continue;
+            }
+            let workspace = first + self.offset * member as u32;
+            push_dispatch(out, &Dispatcher::FocusMonitor(monitor));
+            push_dispatch(
+                out,
+                &if style == SwitchStyle::Fast {
+                    Dispatcher::WorkspaceByName(workspace)
+                } else {
+                    Dispatcher::Workspace(workspace)
+                },
+            );
+        }
+        push_dispatch(out, &Dispatcher::FocusMonitor(focused_monitor));
+        if style == SwitchStyle::Full {
+            push_dispatch(
+                out,
+                &Dispatcher::Workspace(first + self.offset * focused as u32),
+            );
+        }
+    }
+
+    pub fn to_batch(&self, workspace: u32, focus_monitor: &str, style: SwitchStyle) -> String {
+        let mut out = String::new();
+        self.render(&mut out, workspace, focus_monitor, style);
+        out
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BatchCache {
+    rebalance_key: Option<(Vec<String>, u32, u32)>,
+    rebalance: String,
+    switch: Option<PairedSwitchTemplate>,
+    buffer: String,
+}
+
+impl BatchCache {
+    pub fn rebalance(&mut self, layout: &Layout<'_>) -> &str {
+        let cached = self
+            .rebalance_key
+            .as_ref()
+            .is_some_and(|(monitors, offset, base)| {
+                *offset == layout.offset()
+                    && *base == layout.base()
+                    && monitors.len() == layout.member_count()
+                    && monitors.iter().map(String::as_str).eq(layout.monitors())
+            });
+        if !cached {
+            self.rebalance = layout.rebalance_batch();
+            self.rebalance_key = Some((
+                layout.monitors().map(str::to_string).collect(),
+                layout.offset(),
+                layout.base(),
+            ));
+        }
+        &self.rebalance
+    }
+
+    pub fn paired_switch(
+        &mut self,
+        layout: &Layout<'_>,
+        workspace: u32,
+        focus_monitor: &str,
+        style: SwitchStyle,
+    ) -> &str {
+        let template = match &mut self.switch {
+            Some(template) if template.matches(layout) => template,
+            slot => slot.insert(PairedSwitchTemplate::new(layout)),
+        };
+        template.render(&mut self.buffer, workspace, focus_monitor, style);
+        &self.buffer
     }
 }
 
 pub fn paired_switch_batch(primary: &str, secondary: &str, workspace: u32, offset: u32) -> String {
-    let normalized = normalize_workspace(workspace, offset);
-    let secondary_workspace = normalized + offset;
+    PairedSwitchTemplate::new(&Layout::paired(primary, secondary, offset)).to_batch(
+        workspace,
+        primary,
+        SwitchStyle::Full,
+    )
+}
+
+pub fn single_monitor_switch_batch(monitor: &str, workspace: u32) -> String {
     let mut batch = HyprctlBatch::new();
 
-    batch.dispatch("focusmonitor", secondary);
-    batch.dispatch("workspace", &secondary_workspace.to_string());
-    batch.dispatch("focusmonitor", primary);
-    batch.dispatch("workspace", &normalized.to_string());
+    batch
+        .push(Dispatcher::FocusMonitor(monitor))
+        .push(Dispatcher::Workspace(workspace));
 
-    batch.to_argument()
+    batch.into_argument()
 }
 
 pub fn paired_switch_batch_with_focus(
@@ -394,55 +1181,128 @@ pub fn paired_switch_batch_with_focus(
     offset: u32,
     focus_monitor: &str,
 ) -> String {
-    let normalized = normalize_workspace(workspace, offset);
-    let secondary_workspace = normalized + offset;
-    let mut batch = HyprctlBatch::new();
+    PairedSwitchTemplate::new(&Layout::paired(primary, secondary, offset)).to_batch(
+        workspace,
+        focus_monitor,
+        SwitchStyle::Full,
+    )
+}
 
-    if focus_monitor == secondary {
-        batch.dispatch("focusmonitor", primary);
-        batch.dispatch("workspace", &normalized.to_string());
-        batch.dispatch("focusmonitor", secondary);
-        batch.dispatch("workspace", &secondary_workspace.to_string());
-    } else {
-        batch.dispatch("focusmonitor", secondary);
-        batch.dispatch("workspace", &secondary_workspace.to_string());
-        batch.dispatch("focusmonitor", primary);
-        batch.dispatch("workspace", &normalized.to_string());
-    }
+pub fn paired_follow_batch(
+    primary: &str,
+    secondary: &str,
+    workspace: u32,
+    offset: u32,
+    focus_monitor: &str,
+) -> String {
+    PairedSwitchTemplate::new(&Layout::paired(primary, secondary, offset)).to_batch(
+        workspace,
+        focus_monitor,
+        SwitchStyle::Follow,
+    )
+}
 
-    batch.to_argument()
+/// Wraps `batch` so Hyprland runs it with animations off.
+pub fn without_animations(batch: &str) -> String {
+    let mut wrapped = HyprctlBatch::new();
+    wrapped.keyword(ANIMATIONS_ENABLED, "0");
+    wrapped.buffer.push_str(BATCH_SEPARATOR);
+    wrapped.buffer.push_str(batch);
+    wrapped.keyword(ANIMATIONS_ENABLED, "1");
+    wrapped.into_argument()
 }
 
 pub fn rebalance_batch(primary: &str, secondary: &str, offset: u32) -> String {
-    let mut batch = HyprctlBatch::new();
-
-    for workspace_id in 1..=offset {
-        batch.dispatch(
-            "moveworkspacetomonitor",
-            &format!("{workspace_id} {primary}"),
-        );
-    }
-
-    for workspace_id in (offset + 1)..=(offset * 2) {
-        batch.dispatch(
-            "moveworkspacetomonitor",
-            &format!("{workspace_id} {secondary}"),
-        );
-    }
+    Layout::paired(primary, secondary, offset).rebalance_batch()
+}
 
-    batch.to_argument()
+pub fn preserving_rebalance_batch(
+    primary: &str,
+    secondary: &str,
+    offset: u32,
+    workspaces: &[WorkspaceInfo],
+) -> String {
+    Layout::paired(primary, secondary, offset).preserving_rebalance_batch(workspaces)
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        Hyprctl, HyprctlBatch, HyprctlRunner, SystemHyprctlRunner, paired_switch_batch,
-        rebalance_batch,
+        BatchCache, Dispatcher, Hyprctl, HyprctlBatch, HyprctlError, HyprctlRunner, HyprlandIpc,
+        HyprlandOptions, MonitorInfo, SocketIpc, SocketRunner, SwitchStyle, SystemHyprctlRunner,
+        WorkspaceTarget, is_batch_safe, paired_follow_batch, paired_switch_batch,
+        preserving_rebalance_batch, rebalance_batch, request_socket_path, socket_request,
     };
+    use crate::layout::{Layout, LayoutMonitor};
     use std::cell::RefCell;
     use std::fs;
     use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
     use std::rc::Rc;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn builds_request_socket_messages() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(socket_request(&args(&["-j", "monitors"])), "j/monitors");
+        assert_eq!(
+            socket_request(&args(&["-j", "getoption", "general:gaps_in"])),
+            "j/getoption general:gaps_in"
+        );
+        assert_eq!(
+            socket_request(&args(&[
+                "--batch",
+                "dispatch workspace 1 ; dispatch workspace 11"
+            ])),
+            "[[BATCH]]dispatch workspace 1 ; dispatch workspace 11"
+        );
+        assert_eq!(
+            socket_request(&args(&["dispatch", "workspace", "3"])),
+            "dispatch workspace 3"
+        );
+        assert_eq!(
+            request_socket_path("/run/user/1000", "abc"),
+            "/run/user/1000/hypr/abc/.socket.sock"
+        );
+    }
+
+    #[test]
+    fn socket_ipc_speaks_request_socket_protocol() {
+        use std::io::{Read, Write};
+        use std::os::unix::net::UnixListener;
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(".socket.sock");
+        let listener = UnixListener::bind(&path).expect("bind");
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in [r#"[{"name":"DP-1","x":0,"id":1}]"#, "ok\n\nok\n"] {
+                let (mut stream, _) = listener.accept().expect("accept");
+                let mut buffer = [0; 256];
+                let read = stream.read(&mut buffer).expect("read");
+                requests.push(String::from_utf8_lossy(&buffer[..read]).to_string());
+                stream.write_all(response.as_bytes()).expect("write");
+            }
+            requests
+        });
+        let ipc: SocketIpc = Hyprctl::new(SocketRunner::new(&path));
+
+        let monitors = ipc.monitors().expect("monitors");
+        let batch = ipc
+            .batch("dispatch workspace 1 ; dispatch workspace 11")
+            .expect("batch");
+
+        assert_eq!(monitors[0].name, "DP-1");
+        assert_eq!(batch, "ok\n\nok");
+        assert_eq!(
+            server.join().expect("join"),
+            vec![
+                "j/monitors".to_string(),
+                "[[BATCH]]dispatch workspace 1 ; dispatch workspace 11".to_string(),
+            ]
+        );
+    }
 
     #[test]
     fn batch_builds_dispatch_commands() {
@@ -456,6 +1316,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dispatchers_serialize_to_hyprctl_arguments() {
+        let address = "0x1a";
+        let cases = [
+            (Dispatcher::Workspace(13), "workspace 13"),
+            (Dispatcher::WorkspaceByName(13), "workspace name:13"),
+            (Dispatcher::FocusMonitor("DP-1"), "focusmonitor DP-1"),
+            (
+                Dispatcher::FocusWindow { address },
+                "focuswindow address:0x1a",
+            ),
+            (
+                Dispatcher::MoveToWorkspaceSilent {
+                    target: WorkspaceTarget::Id(3),
+                    address,
+                },
+                "movetoworkspacesilent 3,address:0x1a",
+            ),
+            (
+                Dispatcher::MoveToWorkspaceSilent {
+                    target: WorkspaceTarget::Special("special:scratch"),
+                    address,
+                },
+                "movetoworkspacesilent special:scratch,address:0x1a",
+            ),
+            (
+                Dispatcher::MoveWorkspaceToMonitor {
+                    workspace: 12,
+                    monitor: "HDMI-A-1",
+                },
+                "moveworkspacetomonitor 12 HDMI-A-1",
+            ),
+            (
+                Dispatcher::SetFloating { address },
+                "setfloating address:0x1a",
+            ),
+            (
+                Dispatcher::MoveWindowPixel {
+                    x: -40,
+                    y: 20,
+                    address,
+                },
+                "movewindowpixel exact -40 20,address:0x1a",
+            ),
+            (
+                Dispatcher::ResizeWindowPixel {
+                    width: 800,
+                    height: 600,
+                    address,
+                },
+                "resizewindowpixel exact 800 600,address:0x1a",
+            ),
+            (Dispatcher::Pseudo { address }, "pseudo address:0x1a"),
+            (
+                Dispatcher::FullscreenState {
+                    internal: 1,
+                    client: 0,
+                },
+                "fullscreenstate 1 0",
+            ),
+            (
+                Dispatcher::MoveCursor { x: 2880, y: -540 },
+                "movecursor 2880 -540",
+            ),
+        ];
+
+        for (dispatcher, expected) in cases {
+            assert_eq!(dispatcher.to_string(), expected);
+            assert!(expected.starts_with(dispatcher.name()));
+        }
+    }
+
+    #[test]
+    fn batch_pushes_typed_dispatchers() {
+        let mut batch = HyprctlBatch::new();
+        batch
+            .push(Dispatcher::FocusMonitor("HDMI-A-1"))
+            .push(Dispatcher::Workspace(13))
+            .dispatch("exec", "foot");
+
+        assert_eq!(
+            batch.to_argument(),
+            "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 13 ; dispatch exec foot"
+        );
+    }
+
     #[test]
     fn paired_switch_batch_normalizes_workspace() {
         let batch = paired_switch_batch("DP-1", "HDMI-A-1", 12, 10);
@@ -466,6 +1412,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn batches_neutralize_separators_in_monitor_names() {
+        let hostile = "HDMI-A-1;dispatch exec rm -rf ~";
+
+        let batch = paired_switch_batch("DP-1", hostile, 12, 10);
+        assert_eq!(
+            batch,
+            "dispatch focusmonitor HDMI-A-1_dispatch exec rm -rf ~ ; dispatch workspace 12 ; dispatch focusmonitor DP-1 ; dispatch workspace 2"
+        );
+
+        let batch = rebalance_batch("DP-1\n", hostile, 2);
+        assert_eq!(
+            batch,
+            "dispatch moveworkspacetomonitor 1 DP-1_ ; dispatch moveworkspacetomonitor 2 DP-1_ ; dispatch moveworkspacetomonitor 3 HDMI-A-1_dispatch exec rm -rf ~ ; dispatch moveworkspacetomonitor 4 HDMI-A-1_dispatch exec rm -rf ~"
+        );
+    }
+
+    #[test]
+    fn workspace_targets_read_numeric_names_as_ids() {
+        assert_eq!(WorkspaceTarget::from_name("14"), WorkspaceTarget::Id(14));
+        assert_eq!(
+            WorkspaceTarget::from_name("special:scratch"),
+            WorkspaceTarget::Special("special:scratch")
+        );
+    }
+
+    #[test]
+    fn window_arguments_neutralize_commas_and_separators() {
+        assert_eq!(
+            Dispatcher::MoveToWorkspaceSilent {
+                target: WorkspaceTarget::Special("special:a,address:0x2"),
+                address: "0x1;dispatch exit",
+            }
+            .to_string(),
+            "movetoworkspacesilent special:a_address:0x2,address:0x1_dispatch exit"
+        );
+        assert!(is_batch_safe("special:my scratch"));
+        assert!(!is_batch_safe("DP-1;"));
+
+        let mut batch = HyprctlBatch::new();
+        batch.dispatch("exec", "foot; dispatch exit");
+        assert_eq!(batch.commands().count(), 1);
+    }
+
+    #[test]
+    fn paired_follow_batch_leaves_focused_workspace_alone() {
+        assert_eq!(
+            paired_follow_batch("DP-1", "HDMI-A-1", 12, 10, "HDMI-A-1"),
+            "dispatch focusmonitor DP-1 ; dispatch workspace 2 ; dispatch focusmonitor HDMI-A-1"
+        );
+        assert_eq!(
+            paired_follow_batch("DP-1", "HDMI-A-1", 2, 10, "DP-1"),
+            "dispatch focusmonitor HDMI-A-1 ; dispatch workspace 12 ; dispatch focusmonitor DP-1"
+        );
+    }
+
     #[test]
     fn rebalance_batch_moves_workspaces_by_offset() {
         let batch = rebalance_batch("DP-1", "HDMI-A-1", 2);
@@ -476,6 +1478,140 @@ mod tests {
         );
     }
 
+    #[test]
+    fn batch_cache_matches_uncached_batches() {
+        let mut cache = BatchCache::default();
+
+        assert_eq!(
+            cache.paired_switch(
+                &Layout::paired("DP-1", "HDMI-A-1", 10),
+                12,
+                "DP-1",
+                SwitchStyle::Full,
+            ),
+            paired_switch_batch("DP-1", "HDMI-A-1", 12, 10)
+        );
+        assert_eq!(
+            cache.paired_switch(
+                &Layout::paired("DP-1", "HDMI-A-1", 10),
+                12,
+                "HDMI-A-1",
+                SwitchStyle::Follow,
+            ),
+            paired_follow_batch("DP-1", "HDMI-A-1", 12, 10, "HDMI-A-1")
+        );
+        assert_eq!(
+            cache.paired_switch(
+                &Layout::paired("HDMI-A-1", "DP-1", 10),
+                3,
+                "HDMI-A-1",
+                SwitchStyle::Full,
+            ),
+            paired_switch_batch("HDMI-A-1", "DP-1", 3, 10)
+        );
+        assert_eq!(
+            cache.rebalance(&Layout::paired("DP-1", "HDMI-A-1", 2)),
+            rebalance_batch("DP-1", "HDMI-A-1", 2)
+        );
+        assert_eq!(
+            cache.rebalance(&Layout::paired("DP-1", "HDMI-A-1", 3)),
+            rebalance_batch("DP-1", "HDMI-A-1", 3)
+        );
+    }
+
+    #[test]
+    fn fast_switch_names_counterpart_and_skips_refocus_dispatch() {
+        let mut cache = BatchCache::default();
+
+        assert_eq!(
+            cache.paired_switch(
+                &Layout::paired("DP-1", "HDMI-A-1", 10),
+                3,
+                "DP-1",
+                SwitchStyle::Fast,
+            ),
+            "dispatch focusmonitor HDMI-A-1 ; dispatch workspace name:13 ; dispatch focusmonitor DP-1"
+        );
+        assert_eq!(
+            cache.paired_switch(
+                &Layout::paired("DP-1", "HDMI-A-1", 10),
+                13,
+                "HDMI-A-1",
+                SwitchStyle::Fast,
+            ),
+            "dispatch focusmonitor DP-1 ; dispatch workspace name:3 ; dispatch focusmonitor HDMI-A-1"
+        );
+    }
+
+    #[test]
+    fn switches_every_monitor_in_a_layout() {
+        let members = [
+            LayoutMonitor::Primary,
+            LayoutMonitor::Secondary,
+            LayoutMonitor::Secondary,
+        ];
+        let layout = Layout::new("DP-1", "HDMI-A-1", 10, &members);
+        let mut cache = BatchCache::default();
+
+        assert_eq!(
+            cache.paired_switch(&layout, 3, "DP-1", SwitchStyle::Full),
+            paired_switch_batch("DP-1", "HDMI-A-1", 3, 10)
+        );
+        assert_eq!(
+            cache.paired_switch(&layout, 23, "HDMI-A-1", SwitchStyle::Full),
+            "dispatch focusmonitor DP-1 ; dispatch workspace 3 ; dispatch focusmonitor HDMI-A-1 ; dispatch workspace 23"
+        );
+
+        let members = [
+            LayoutMonitor::Primary,
+            LayoutMonitor::Secondary,
+            LayoutMonitor::Named("DP-3".to_string()),
+        ];
+        let layout = Layout::new("DP-1", "HDMI-A-1", 10, &members);
+        assert_eq!(
+            cache.paired_switch(&layout, 13, "HDMI-A-1", SwitchStyle::Follow),
+            "dispatch focusmonitor DP-1 ; dispatch workspace 3 ; dispatch focusmonitor DP-3 ; dispatch workspace 23 ; dispatch focusmonitor HDMI-A-1"
+        );
+    }
+
+    #[test]
+    fn batch_cache_reuses_rebalance_batch_for_same_key() {
+        let mut cache = BatchCache::default();
+        let first = cache
+            .rebalance(&Layout::paired("DP-1", "HDMI-A-1", 2))
+            .as_ptr();
+        let second = cache
+            .rebalance(&Layout::paired("DP-1", "HDMI-A-1", 2))
+            .as_ptr();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn preserving_rebalance_only_moves_misplaced_workspaces() {
+        let workspace = |id: u32, monitor: &str| super::WorkspaceInfo {
+            id,
+            windows: 1,
+            name: None,
+            monitor: Some(monitor.to_string()),
+        };
+        let workspaces = vec![
+            workspace(1, "DP-1"),
+            workspace(2, "HDMI-A-1"),
+            workspace(3, "HDMI-A-1"),
+            workspace(4, "DP-1"),
+            workspace(25, "DP-1"),
+        ];
+
+        let batch = preserving_rebalance_batch("DP-1", "HDMI-A-1", 2, &workspaces);
+
+        assert_eq!(
+            batch,
+            "dispatch moveworkspacetomonitor 2 DP-1 ; dispatch moveworkspacetomonitor 4 HDMI-A-1"
+        );
+        assert!(preserving_rebalance_batch("DP-1", "HDMI-A-1", 2, &workspaces[..1]).is_empty());
+    }
+
     #[derive(Clone, Default)]
     struct RecordingRunner {
         calls: Rc<RefCell<Vec<Vec<String>>>>,
@@ -611,6 +1747,29 @@ mod tests {
         assert_eq!(calls[0], vec!["-j".to_string(), "monitors".to_string()]);
     }
 
+    #[test]
+    fn parses_monitor_geometry_from_json() {
+        let runner = StaticRunner::new(
+            r#"[{"name":"DP-1","id":1,"description":"LG Electronics","x":0,"y":-1440,"width":3840,"height":2160,"scale":1.5,"transform":3,"focused":true,"activeWorkspace":{"id":4,"name":"4"}}]"#,
+        );
+        let hyprctl = Hyprctl::new(runner);
+
+        let monitors = hyprctl.monitors().expect("monitors");
+
+        assert_eq!(
+            (monitors[0].y, monitors[0].width, monitors[0].height),
+            (-1440, 3840, 2160)
+        );
+        assert!(monitors[0].focused);
+        assert_eq!(monitors[0].active_workspace, Some(4));
+        assert_eq!(monitors[0].logical_size(), (1440, 2560));
+        assert_eq!(
+            monitors[0].geometry(),
+            "3840x2160+0+-1440 @1.5x transform 3"
+        );
+        assert_eq!(MonitorInfo::default().logical_size(), (0, 0));
+    }
+
     #[test]
     fn parses_workspaces_from_json() {
         let runner = StaticRunner::new(r#"[{"id":1,"windows":2},{"id":12,"windows":0}]"#);
@@ -643,6 +1802,91 @@ mod tests {
         assert_eq!(calls[0], vec!["-j".to_string(), "clients".to_string()]);
     }
 
+    #[test]
+    fn parses_floating_geometry_from_clients_json() {
+        let runner = StaticRunner::new(
+            r#"[{"address":"0x1","workspace":{"id":1},"floating":true,"at":[40,-20],"size":[640,480]}]"#,
+        );
+        let hyprctl = Hyprctl::new(runner);
+
+        let clients = hyprctl.clients().expect("clients");
+
+        assert!(clients[0].floating);
+        assert_eq!(clients[0].at, Some([40, -20]));
+        assert_eq!(clients[0].size, Some([640, 480]));
+    }
+
+    #[test]
+    fn parses_fullscreen_as_a_state_or_a_legacy_flag() {
+        let runner = StaticRunner::new(
+            r#"[{"address":"0x1","workspace":{"id":1},"fullscreen":1,"fullscreenClient":2,"pseudo":true},
+                {"address":"0x2","workspace":{"id":1},"fullscreen":true}]"#,
+        );
+        let hyprctl = Hyprctl::new(runner);
+
+        let clients = hyprctl.clients().expect("clients");
+
+        assert_eq!(
+            (clients[0].fullscreen, clients[0].fullscreen_client),
+            (1, 2)
+        );
+        assert!(clients[0].pseudo);
+        assert_eq!(clients[1].fullscreen, 2);
+    }
+
+    #[test]
+    fn parses_option_from_json() {
+        let runner =
+            StaticRunner::new(r#"{"option":"binds:workspace_back_and_forth","int":1,"set":true}"#);
+        let hyprctl = Hyprctl::new(runner.clone());
+
+        let option = hyprctl
+            .get_option("binds:workspace_back_and_forth")
+            .expect("option");
+
+        assert_eq!(option.as_bool(), Some(true));
+        assert!(option.set);
+        let calls = runner.calls.borrow();
+        assert_eq!(
+            calls[0],
+            vec![
+                "-j".to_string(),
+                "getoption".to_string(),
+                "binds:workspace_back_and_forth".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn keyword_runs_hyprctl_keyword() {
+        let runner = StaticRunner::new("ok");
+        let hyprctl = Hyprctl::new(runner.clone());
+
+        let output = hyprctl.keyword("general:gaps_out", "12").expect("keyword");
+
+        assert_eq!(output, "ok");
+        let calls = runner.calls.borrow();
+        assert_eq!(
+            calls[0],
+            vec![
+                "keyword".to_string(),
+                "general:gaps_out".to_string(),
+                "12".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn queries_hyprland_options_with_fallback() {
+        let enabled = Hyprctl::new(StaticRunner::new(
+            r#"{"option":"binds:workspace_back_and_forth","int":1,"set":true}"#,
+        ));
+        let missing = Hyprctl::new(StaticRunner::new("no such option"));
+
+        assert!(HyprlandOptions::query(&enabled).workspace_back_and_forth);
+        assert_eq!(HyprlandOptions::query(&missing), HyprlandOptions::default());
+    }
+
     #[test]
     fn monitors_parse_error_includes_command_context() {
         let runner = StaticRunner::new("not json");
@@ -682,4 +1926,48 @@ mod tests {
             _ => panic!("expected command failure"),
         }
     }
+
+    #[test]
+    fn system_runner_kills_commands_that_outlive_the_timeout() {
+        let runner = SystemHyprctlRunner::new("/bin/sh").with_timeout(Duration::from_millis(50));
+        let started = Instant::now();
+
+        let err = runner
+            .run(&["-c".to_string(), "sleep 5".to_string()])
+            .expect_err("timeout");
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(err.is_transient());
+        match err {
+            HyprctlError::Timeout { command, timeout } => {
+                assert!(command.contains("sleep 5"));
+                assert_eq!(timeout, Duration::from_millis(50));
+            }
+            err => panic!("expected timeout, got {err}"),
+        }
+
+        runner.set_timeout(None);
+        assert_eq!(
+            runner
+                .run(&["-c".to_string(), "printf ok".to_string()])
+                .expect("no timeout"),
+            "ok"
+        );
+    }
+
+    #[test]
+    fn socket_runner_times_out_on_a_silent_compositor() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(".socket.sock");
+        let _listener = UnixListener::bind(&path).expect("bind");
+        let hyprctl = Hyprctl::new(SocketRunner::new(&path));
+        hyprctl.set_timeout(Some(Duration::from_millis(50)));
+
+        let err = hyprctl.monitors().expect_err("timeout");
+
+        assert!(
+            matches!(&err, HyprctlError::Timeout { command, .. } if command == "j/monitors"),
+            "{err}"
+        );
+    }
 }
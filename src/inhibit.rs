@@ -0,0 +1,134 @@
+use std::io;
+use std::process::{Child, Command, Stdio};
+
+pub struct IdleInhibitor {
+    command: fn(u32) -> Command,
+    held: Option<(u32, Child)>,
+}
+
+impl Default for IdleInhibitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdleInhibitor {
+    pub fn new() -> Self {
+        Self::with_command(systemd_inhibit)
+    }
+
+    pub fn with_command(command: fn(u32) -> Command) -> Self {
+        Self {
+            command,
+            held: None,
+        }
+    }
+
+    pub fn held_slot(&self) -> Option<u32> {
+        self.held.as_ref().map(|(slot, _)| *slot)
+    }
+
+    pub fn update(&mut self, slot: Option<u32>) -> io::Result<()> {
+        if let Some((held, child)) = &mut self.held
+            && Some(*held) == slot
+            && child.try_wait()?.is_none()
+        {
+            return Ok(());
+        }
+        self.release();
+        if let Some(slot) = slot {
+            self.held = Some((slot, self.spawn(slot)?));
+        }
+        Ok(())
+    }
+
+    pub fn release(&mut self) {
+        if let Some((_, mut child)) = self.held.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    fn spawn(&self, slot: u32) -> io::Result<Child> {
+        (self.command)(slot)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    }
+}
+
+impl Drop for IdleInhibitor {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+fn systemd_inhibit(slot: u32) -> Command {
+    let mut command = Command::new("systemd-inhibit");
+    command
+        .args(["--what=idle", "--who=hyprspaces", "--mode=block"])
+        .arg(format!("--why=paired slot {slot} is visible"))
+        .args(["sleep", "infinity"]);
+    command
+}
+
+pub fn inhibiting_slot(
+    slots: &[u32],
+    active_workspace: Option<u32>,
+    offset: u32,
+    base: u32,
+) -> Option<u32> {
+    let slot = crate::paired::normalize_workspace(active_workspace?, offset, base);
+    slots.contains(&slot).then_some(slot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdleInhibitor, inhibiting_slot};
+    use std::process::Command;
+
+    fn sleeper() -> IdleInhibitor {
+        IdleInhibitor::with_command(|_| {
+            let mut command = Command::new("sleep");
+            command.arg("30");
+            command
+        })
+    }
+
+    #[test]
+    fn maps_active_workspace_to_inhibiting_slot() {
+        assert_eq!(inhibiting_slot(&[5], Some(15), 10, 1), Some(5));
+        assert_eq!(inhibiting_slot(&[5], Some(5), 10, 1), Some(5));
+        assert_eq!(inhibiting_slot(&[5], Some(4), 10, 1), None);
+        assert_eq!(inhibiting_slot(&[5], None, 10, 1), None);
+        assert_eq!(inhibiting_slot(&[5], Some(4), 10, 0), Some(5));
+    }
+
+    #[test]
+    fn holds_until_switching_away() {
+        let mut inhibitor = sleeper();
+
+        inhibitor.update(Some(5)).expect("hold");
+        let pid = inhibitor.held.as_ref().map(|(_, child)| child.id());
+        inhibitor.update(Some(5)).expect("keep");
+        assert_eq!(inhibitor.held.as_ref().map(|(_, child)| child.id()), pid);
+        assert_eq!(inhibitor.held_slot(), Some(5));
+
+        inhibitor.update(None).expect("release");
+        assert_eq!(inhibitor.held_slot(), None);
+    }
+
+    #[test]
+    fn restarts_exited_inhibitors() {
+        let mut inhibitor = IdleInhibitor::with_command(|_| Command::new("true"));
+
+        inhibitor.update(Some(5)).expect("hold");
+        if let Some((_, child)) = &mut inhibitor.held {
+            child.wait().expect("wait");
+        }
+        inhibitor.update(Some(5)).expect("respawn");
+
+        assert_eq!(inhibitor.held_slot(), Some(5));
+    }
+}
@@ -0,0 +1,267 @@
+use std::io::{self, BufRead, Write};
+
+use crate::config::{Config, ConfigError, DEFAULT_WORKSPACE_COUNT, DEFAULT_WRAP_CYCLING};
+use crate::hyprctl::MonitorInfo;
+use crate::setup::select_monitors;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitAnswers {
+    pub primary_monitor: String,
+    pub secondary_monitor: String,
+    pub workspace_count: u32,
+    pub wrap_cycling: bool,
+}
+
+impl InitAnswers {
+    pub fn to_config(&self) -> Result<Config, ConfigError> {
+        Config::builder()
+            .primary_monitor(&self.primary_monitor)
+            .secondary_monitor(&self.secondary_monitor)
+            .workspace_count(self.workspace_count)
+            .wrap_cycling(self.wrap_cycling)
+            .build()
+    }
+}
+
+pub fn prompt_config<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    monitors: &[MonitorInfo],
+) -> io::Result<InitAnswers> {
+    let mut sorted = monitors
+        .iter()
+        .filter(|monitor| !monitor.is_mirror())
+        .collect::<Vec<_>>();
+    sorted.sort_by_key(|monitor| (monitor.x, monitor.id));
+    let names = sorted
+        .iter()
+        .map(|monitor| monitor.name.clone())
+        .collect::<Vec<_>>();
+
+    if sorted.is_empty() {
+        writeln!(
+            output,
+            "No monitors reported by Hyprland; enter names manually."
+        )?;
+    } else {
+        writeln!(output, "Connected monitors:")?;
+        for (idx, monitor) in sorted.iter().enumerate() {
+            writeln!(output, "  {}) {} (x={})", idx + 1, monitor.name, monitor.x)?;
+        }
+    }
+
+    let (auto_primary, auto_secondary) = match select_monitors(monitors) {
+        Some((primary, secondary)) => (Some(primary), Some(secondary)),
+        None => (None, None),
+    };
+    let primary_monitor = ask(
+        input,
+        output,
+        "Primary monitor",
+        auto_primary.as_deref(),
+        |answer| parse_monitor(answer, &names),
+    )?;
+    let secondary_default = auto_secondary
+        .filter(|name| name != &primary_monitor)
+        .or_else(|| names.iter().find(|name| **name != primary_monitor).cloned());
+    let secondary_monitor = ask(
+        input,
+        output,
+        "Secondary monitor",
+        secondary_default.as_deref(),
+        |answer| parse_monitor(answer, &names),
+    )?;
+    let workspace_count = ask(
+        input,
+        output,
+        "Workspaces per monitor",
+        Some(&DEFAULT_WORKSPACE_COUNT.to_string()),
+        |answer| match answer.parse::<u32>() {
+            Ok(count) if count > 0 => Ok(count),
+            _ => Err(format!("'{answer}' is not a positive number")),
+        },
+    )?;
+    let wrap_default = if DEFAULT_WRAP_CYCLING { "yes" } else { "no" };
+    let wrap_cycling = ask(
+        input,
+        output,
+        "Wrap around when cycling",
+        Some(wrap_default),
+        |answer| match answer.to_ascii_lowercase().as_str() {
+            "y" | "yes" | "true" => Ok(true),
+            "n" | "no" | "false" => Ok(false),
+            _ => Err(format!("'{answer}' is not yes or no")),
+        },
+    )?;
+
+    Ok(InitAnswers {
+        primary_monitor,
+        secondary_monitor,
+        workspace_count,
+        wrap_cycling,
+    })
+}
+
+fn ask<R: BufRead, W: Write, T>(
+    input: &mut R,
+    output: &mut W,
+    label: &str,
+    default: Option<&str>,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> io::Result<T> {
+    loop {
+        match default {
+            Some(default) => write!(output, "{label} [{default}]: ")?,
+            None => write!(output, "{label}: ")?,
+        }
+        output.flush()?;
+        let mut line = String::new();
+        let read = input.read_line(&mut line)?;
+        let answer = line.trim();
+        let answer = match (answer.is_empty(), default) {
+            (false, _) => answer,
+            (true, Some(default)) => default,
+            (true, None) if read == 0 => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("no answer for {label}"),
+                ));
+            }
+            (true, None) => continue,
+        };
+        match parse(answer) {
+            Ok(value) => return Ok(value),
+            Err(_) if read == 0 => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("no answer for {label}"),
+                ));
+            }
+            Err(message) => writeln!(output, "{message}")?,
+        }
+    }
+}
+
+fn parse_monitor(answer: &str, names: &[String]) -> Result<String, String> {
+    if let Ok(index) = answer.parse::<usize>()
+        && index >= 1
+        && index <= names.len()
+    {
+        return Ok(names[index - 1].clone());
+    }
+    if names.is_empty() || names.iter().any(|name| name == answer) {
+        return Ok(answer.to_string());
+    }
+    Err(format!(
+        "'{answer}' is not a connected monitor (available: {})",
+        names.join(", ")
+    ))
+}
+
+pub fn render_commented_config(answers: &InitAnswers) -> String {
+    let quote = |value: &str| serde_json::Value::from(value).to_string();
+    format!(
+        "{{\n  // Monitor holding workspaces 1-{count}.\n  \"primary_monitor\": {primary},\n  // Monitor holding the paired workspaces {first}-{last}.\n  \"secondary_monitor\": {secondary},\n  // Workspaces per monitor; workspace N pairs with N+{count}.\n  \"workspace_count\": {count},\n  // Wrap from the last pair to the first when cycling.\n  \"wrap_cycling\": {wrap}\n}}\n",
+        primary = quote(&answers.primary_monitor),
+        secondary = quote(&answers.secondary_monitor),
+        count = answers.workspace_count,
+        first = answers.workspace_count + 1,
+        last = answers.workspace_count * 2,
+        wrap = answers.wrap_cycling,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InitAnswers, prompt_config, render_commented_config};
+    use crate::config::Config;
+    use crate::hyprctl::MonitorInfo;
+    use std::io::Cursor;
+
+    fn monitors() -> Vec<MonitorInfo> {
+        vec![
+            MonitorInfo {
+                name: "HDMI-A-1".to_string(),
+                x: 1920,
+                id: 2,
+                mirror_of: None,
+                ..Default::default()
+            },
+            MonitorInfo {
+                name: "DP-1".to_string(),
+                x: 0,
+                id: 1,
+                mirror_of: None,
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn accepts_auto_picked_defaults() {
+        let mut input = Cursor::new("\n\n\n\n");
+        let mut output = Vec::new();
+
+        let answers = prompt_config(&mut input, &mut output, &monitors()).expect("answers");
+
+        assert_eq!(
+            answers,
+            InitAnswers {
+                primary_monitor: "DP-1".to_string(),
+                secondary_monitor: "HDMI-A-1".to_string(),
+                workspace_count: 10,
+                wrap_cycling: true,
+            }
+        );
+        let output = String::from_utf8(output).expect("utf8");
+        assert!(output.contains("  1) DP-1 (x=0)"));
+        assert!(output.contains("Primary monitor [DP-1]: "));
+    }
+
+    #[test]
+    fn picks_monitors_by_index_and_reprompts_invalid_answers() {
+        let mut input = Cursor::new("2\nDP-9\nDP-1\n0\n6\nno\n");
+        let mut output = Vec::new();
+
+        let answers = prompt_config(&mut input, &mut output, &monitors()).expect("answers");
+
+        assert_eq!(answers.primary_monitor, "HDMI-A-1");
+        assert_eq!(answers.secondary_monitor, "DP-1");
+        assert_eq!(answers.workspace_count, 6);
+        assert!(!answers.wrap_cycling);
+        let output = String::from_utf8(output).expect("utf8");
+        assert!(output.contains("'DP-9' is not a connected monitor"));
+        assert!(output.contains("'0' is not a positive number"));
+    }
+
+    #[test]
+    fn errors_on_eof_without_monitors() {
+        let mut input = Cursor::new("");
+        let mut output = Vec::new();
+
+        let err = prompt_config(&mut input, &mut output, &[]).expect_err("eof");
+
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn renders_commented_config_that_parses() {
+        let answers = InitAnswers {
+            primary_monitor: "DP-1".to_string(),
+            secondary_monitor: "HDMI-A-1".to_string(),
+            workspace_count: 5,
+            wrap_cycling: false,
+        };
+
+        let rendered = render_commented_config(&answers);
+        let config = Config::from_json(&rendered).expect("config");
+
+        assert_eq!(answers.to_config().expect("built"), config);
+        assert!(rendered.contains("// Monitor holding the paired workspaces 6-10."));
+        assert_eq!(config.primary_monitor, "DP-1");
+        assert_eq!(config.secondary_monitor, "HDMI-A-1");
+        assert_eq!(config.workspace_count, 5);
+        assert_eq!(config.paired_offset, 5);
+        assert!(!config.wrap_cycling);
+    }
+}
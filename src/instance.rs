@@ -0,0 +1,115 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, thiserror::Error)]
+pub enum InstanceError {
+    #[error("another hyprspaces daemon is already running ({holder}); use --replace to take over")]
+    AlreadyRunning { holder: String },
+    #[error("instance lock io error: {0}")]
+    Io(#[from] io::Error),
+}
+
+pub fn lock_path(base_dir: &Path) -> PathBuf {
+    base_dir.join("daemon.lock")
+}
+
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    pub fn try_acquire(path: &Path) -> Result<Self, InstanceError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut contents = String::new();
+                let _ = file.read_to_string(&mut contents);
+                let holder = match contents.trim().parse::<u32>() {
+                    Ok(pid) => format!("PID {pid}"),
+                    Err(_) => "unknown PID".to_string(),
+                };
+                return Err(InstanceError::AlreadyRunning { holder });
+            }
+            Err(TryLockError::Error(err)) => return Err(err.into()),
+        }
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", std::process::id())?;
+        Ok(Self { _file: file })
+    }
+
+    pub fn acquire_within(path: &Path, timeout: Duration) -> Result<Self, InstanceError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match Self::try_acquire(path) {
+                Err(InstanceError::AlreadyRunning { .. }) if Instant::now() < deadline => {
+                    std::thread::sleep(LOCK_RETRY_INTERVAL);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InstanceError, InstanceLock, lock_path};
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn second_acquire_reports_holder_pid() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = lock_path(dir.path());
+
+        let _lock = InstanceLock::try_acquire(&path).expect("first lock");
+        let err = InstanceLock::try_acquire(&path).expect_err("second lock");
+
+        match err {
+            InstanceError::AlreadyRunning { holder } => {
+                assert_eq!(holder, format!("PID {}", std::process::id()));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn lock_is_released_on_drop() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = lock_path(dir.path());
+
+        drop(InstanceLock::try_acquire(&path).expect("first lock"));
+        let _lock = InstanceLock::try_acquire(&path).expect("relock");
+
+        assert_eq!(
+            fs::read_to_string(&path).expect("read").trim(),
+            std::process::id().to_string()
+        );
+    }
+
+    #[test]
+    fn acquire_within_times_out_while_held() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = lock_path(dir.path());
+        let _lock = InstanceLock::try_acquire(&path).expect("first lock");
+
+        let err =
+            InstanceLock::acquire_within(&path, Duration::from_millis(60)).expect_err("still held");
+
+        assert!(matches!(err, InstanceError::AlreadyRunning { .. }));
+    }
+}
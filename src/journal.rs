@@ -0,0 +1,408 @@
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::hyprctl::{
+    ClientInfo, Compositor, HyprctlError, HyprlandIpc, MonitorInfo, OptionInfo, WorkspaceInfo,
+};
+
+pub const DEFAULT_JOURNAL_MAX_BYTES: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub at: u64,
+    pub trigger: String,
+    pub command: String,
+}
+
+pub struct Journal {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl Journal {
+    pub fn new(path: PathBuf, max_bytes: u64) -> Self {
+        Self { path, max_bytes }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut name = self.path.as_os_str().to_os_string();
+        name.push(".1");
+        PathBuf::from(name)
+    }
+
+    pub fn append(&self, entry: &JournalEntry) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if fs::metadata(&self.path).is_ok_and(|metadata| metadata.len() >= self.max_bytes) {
+            fs::rename(&self.path, self.rotated_path())?;
+        }
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())
+    }
+
+    pub fn entries_since(&self, since: u64) -> io::Result<Vec<JournalEntry>> {
+        let mut entries = Vec::new();
+        for path in [self.rotated_path(), self.path.clone()] {
+            let contents = match fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+            entries.extend(
+                contents
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<JournalEntry>(line).ok())
+                    .filter(|entry| entry.at >= since),
+            );
+        }
+        Ok(entries)
+    }
+}
+
+pub fn journal_path(base_dir: &Path) -> PathBuf {
+    base_dir.join("journal.jsonl")
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IpcStats {
+    pub commands: u64,
+    pub errors: u64,
+}
+
+pub struct JournalingIpc<'a> {
+    inner: &'a dyn HyprlandIpc,
+    journal: Journal,
+    trigger: RefCell<String>,
+    stats: Cell<IpcStats>,
+    captured: RefCell<Option<Vec<String>>>,
+}
+
+impl<'a> JournalingIpc<'a> {
+    pub fn new(inner: &'a dyn HyprlandIpc, journal: Journal) -> Self {
+        Self {
+            inner,
+            journal,
+            trigger: RefCell::new(String::new()),
+            stats: Cell::new(IpcStats::default()),
+            captured: RefCell::new(None),
+        }
+    }
+
+    pub fn set_trigger(&self, trigger: impl Into<String>) {
+        *self.trigger.borrow_mut() = trigger.into();
+    }
+
+    pub fn stats(&self) -> IpcStats {
+        self.stats.get()
+    }
+
+    /// Keeps a copy of every recorded command until [`Self::take_captured`].
+    pub fn capture(&self, enabled: bool) {
+        *self.captured.borrow_mut() = enabled.then(Vec::new);
+    }
+
+    pub fn take_captured(&self) -> Vec<String> {
+        self.captured
+            .borrow_mut()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    fn track<T>(&self, result: Result<T, HyprctlError>) -> Result<T, HyprctlError> {
+        if result.is_err() {
+            let mut stats = self.stats.get();
+            stats.errors += 1;
+            self.stats.set(stats);
+        }
+        result
+    }
+
+    fn record(&self, command: String) {
+        let mut stats = self.stats.get();
+        stats.commands += 1;
+        self.stats.set(stats);
+        if let Some(captured) = self.captured.borrow_mut().as_mut() {
+            captured.push(command.clone());
+        }
+        let entry = JournalEntry {
+            at: epoch_seconds(),
+            trigger: self.trigger.borrow().clone(),
+            command,
+        };
+        let _ = self.journal.append(&entry);
+    }
+}
+
+impl HyprlandIpc for JournalingIpc<'_> {
+    fn batch(&self, batch: &str) -> Result<String, HyprctlError> {
+        let output = self.track(self.inner.batch(batch))?;
+        self.record(batch.to_string());
+        Ok(output)
+    }
+
+    fn active_workspace_id(&self) -> Result<u32, HyprctlError> {
+        self.track(self.inner.active_workspace_id())
+    }
+
+    fn dispatch(&self, dispatcher: &str, argument: &str) -> Result<String, HyprctlError> {
+        let output = self.track(self.inner.dispatch(dispatcher, argument))?;
+        self.record(format!("dispatch {dispatcher} {argument}"));
+        Ok(output)
+    }
+
+    fn reload(&self) -> Result<String, HyprctlError> {
+        let output = self.track(self.inner.reload())?;
+        self.record("reload".to_string());
+        Ok(output)
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, HyprctlError> {
+        self.track(self.inner.monitors())
+    }
+
+    fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, HyprctlError> {
+        self.track(self.inner.workspaces())
+    }
+
+    fn clients(&self) -> Result<Vec<ClientInfo>, HyprctlError> {
+        self.track(self.inner.clients())
+    }
+
+    fn get_option(&self, name: &str) -> Result<OptionInfo, HyprctlError> {
+        self.track(self.inner.get_option(name))
+    }
+
+    fn keyword(&self, name: &str, value: &str) -> Result<String, HyprctlError> {
+        let output = self.track(self.inner.keyword(name, value))?;
+        self.record(format!("keyword {name} {value}"));
+        Ok(output)
+    }
+
+    fn compositor(&self) -> Compositor {
+        self.inner.compositor()
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        self.inner.set_timeout(timeout);
+    }
+}
+
+pub fn parse_since(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (value, unit) = input.split_at(split);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("invalid duration: {input}"))?;
+    let scale: u64 = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => {
+            return Err(format!(
+                "invalid duration unit in {input} (use s, m, h, or d)"
+            ));
+        }
+    };
+    value
+        .checked_mul(scale)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("invalid duration: {input}"))
+}
+
+pub fn format_entry(entry: &JournalEntry) -> String {
+    format!(
+        "{} [{}] {}",
+        format_timestamp(entry.at),
+        entry.trigger,
+        entry.command
+    )
+}
+
+pub fn format_timestamp(epoch_seconds: u64) -> String {
+    let days = epoch_seconds / 86_400;
+    let remainder = epoch_seconds % 86_400;
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        remainder / 3600,
+        (remainder % 3600) / 60,
+        remainder % 60
+    )
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+pub fn epoch_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        IpcStats, Journal, JournalEntry, JournalingIpc, format_entry, format_timestamp,
+        journal_path, parse_since,
+    };
+    use crate::hyprctl::HyprlandIpc;
+    use crate::testing::ScriptedIpc;
+    use std::time::Duration;
+
+    fn entry(at: u64, command: &str) -> JournalEntry {
+        JournalEntry {
+            at,
+            trigger: "monitor added".to_string(),
+            command: command.to_string(),
+        }
+    }
+
+    #[test]
+    fn appends_and_filters_entries_by_time() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let journal = Journal::new(journal_path(dir.path()), 1024);
+
+        journal
+            .append(&entry(100, "dispatch workspace 1"))
+            .expect("append");
+        journal
+            .append(&entry(200, "dispatch workspace 2"))
+            .expect("append");
+
+        let entries = journal.entries_since(150).expect("entries");
+        assert_eq!(entries, vec![entry(200, "dispatch workspace 2")]);
+    }
+
+    #[test]
+    fn rotates_when_size_limit_reached() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let journal = Journal::new(journal_path(dir.path()), 10);
+
+        journal.append(&entry(1, "first")).expect("append");
+        journal.append(&entry(2, "second")).expect("append");
+        journal.append(&entry(3, "third")).expect("append");
+
+        assert!(journal.rotated_path().exists());
+        let entries = journal.entries_since(0).expect("entries");
+        assert_eq!(entries, vec![entry(2, "second"), entry(3, "third")]);
+    }
+
+    #[test]
+    fn journaling_ipc_records_batches_with_trigger() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let hyprctl = ScriptedIpc::new();
+        let ipc = JournalingIpc::new(&hyprctl, Journal::new(journal_path(dir.path()), 1024));
+
+        ipc.set_trigger("monitor added");
+        ipc.batch("dispatch moveworkspacetomonitor 1 DP-1")
+            .expect("batch");
+
+        let journal = Journal::new(journal_path(dir.path()), 1024);
+        let entries = journal.entries_since(0).expect("entries");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].trigger, "monitor added");
+        assert_eq!(entries[0].command, "dispatch moveworkspacetomonitor 1 DP-1");
+    }
+
+    #[test]
+    fn journaling_ipc_records_keywords() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let hyprctl = ScriptedIpc::new();
+        let ipc = JournalingIpc::new(&hyprctl, Journal::new(journal_path(dir.path()), 1024));
+
+        ipc.set_trigger("cli keyword");
+        ipc.keyword("general:gaps_out", "12").expect("keyword");
+
+        let journal = Journal::new(journal_path(dir.path()), 1024);
+        let entries = journal.entries_since(0).expect("entries");
+        assert_eq!(entries[0].trigger, "cli keyword");
+        assert_eq!(entries[0].command, "keyword general:gaps_out 12");
+    }
+
+    #[test]
+    fn journaling_ipc_counts_commands_and_errors() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let hyprctl = ScriptedIpc::new();
+        let ipc = JournalingIpc::new(&hyprctl, Journal::new(journal_path(dir.path()), 1024));
+
+        ipc.batch("dispatch workspace 1").expect("batch");
+        ipc.dispatch("workspace", "2").expect("dispatch");
+        assert!(ipc.monitors().is_err());
+
+        assert_eq!(
+            ipc.stats(),
+            IpcStats {
+                commands: 2,
+                errors: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn journaling_ipc_captures_commands_when_enabled() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let hyprctl = ScriptedIpc::new();
+        let ipc = JournalingIpc::new(&hyprctl, Journal::new(journal_path(dir.path()), 1024));
+
+        ipc.batch("dispatch workspace 1").expect("batch");
+        assert!(ipc.take_captured().is_empty());
+
+        ipc.capture(true);
+        ipc.dispatch("workspace", "2").expect("dispatch");
+        assert_eq!(ipc.take_captured(), vec!["dispatch workspace 2"]);
+        assert!(ipc.take_captured().is_empty());
+    }
+
+    #[test]
+    fn parses_since_durations() {
+        assert_eq!(parse_since("10m"), Ok(Duration::from_secs(600)));
+        assert_eq!(parse_since("45"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_since("2h"), Ok(Duration::from_secs(7200)));
+        assert!(parse_since("10w").is_err());
+        assert!(parse_since("m").is_err());
+        assert_eq!(
+            parse_since("999999999999999d"),
+            Err("invalid duration: 999999999999999d".to_string())
+        );
+    }
+
+    #[test]
+    fn formats_entries_with_utc_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(1_767_052_800), "2025-12-30T00:00:00Z");
+        assert_eq!(
+            format_entry(&entry(1_767_052_861, "dispatch workspace 1")),
+            "2025-12-30T00:01:01Z [monitor added] dispatch workspace 1"
+        );
+    }
+}
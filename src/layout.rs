@@ -0,0 +1,327 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::hyprctl::{Dispatcher, HyprctlBatch, WorkspaceInfo};
+use crate::paired::normalize_workspace;
+
+/// `"primary"`, `"secondary"`, or a literal monitor name.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum LayoutMonitor {
+    Primary,
+    Secondary,
+    Named(String),
+}
+
+impl From<String> for LayoutMonitor {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "primary" => LayoutMonitor::Primary,
+            "secondary" => LayoutMonitor::Secondary,
+            _ => LayoutMonitor::Named(value),
+        }
+    }
+}
+
+impl From<LayoutMonitor> for String {
+    fn from(value: LayoutMonitor) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for LayoutMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutMonitor::Primary => f.write_str("primary"),
+            LayoutMonitor::Secondary => f.write_str("secondary"),
+            LayoutMonitor::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+static PAIRED: [LayoutMonitor; 2] = [LayoutMonitor::Primary, LayoutMonitor::Secondary];
+
+/// Member `i` of slot `n` is workspace `base + n - 1 + i * offset`.
+#[derive(Debug, Clone, Copy)]
+pub struct Layout<'a> {
+    primary: &'a str,
+    secondary: &'a str,
+    offset: u32,
+    base: u32,
+    members: &'a [LayoutMonitor],
+}
+
+impl<'a> Layout<'a> {
+    pub fn new(
+        primary: &'a str,
+        secondary: &'a str,
+        offset: u32,
+        members: &'a [LayoutMonitor],
+    ) -> Self {
+        let members = if members.is_empty() {
+            &PAIRED[..]
+        } else {
+            members
+        };
+        Self {
+            primary,
+            secondary,
+            offset,
+            base: 1,
+            members,
+        }
+    }
+
+    /// Numbers slot 1 of the first member as workspace `base`.
+    pub fn with_base(mut self, base: u32) -> Self {
+        self.base = base;
+        self
+    }
+
+    pub fn paired(primary: &'a str, secondary: &'a str, offset: u32) -> Self {
+        Self::new(primary, secondary, offset, &PAIRED)
+    }
+
+    pub fn for_config(config: &'a Config) -> Self {
+        Self::new(
+            &config.primary_monitor,
+            &config.secondary_monitor,
+            config.paired_offset,
+            &config.layout,
+        )
+        .with_base(config.workspace_base)
+    }
+
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    pub fn base(&self) -> u32 {
+        self.base
+    }
+
+    pub fn member_count(&self) -> usize {
+        self.members.len()
+    }
+
+    /// The highest workspace id in the layout.
+    pub fn max_workspace(&self) -> u32 {
+        self.base + self.offset * self.members.len() as u32 - 1
+    }
+
+    pub fn monitor(&self, member: usize) -> &'a str {
+        match &self.members[member] {
+            LayoutMonitor::Primary => self.primary,
+            LayoutMonitor::Secondary => self.secondary,
+            LayoutMonitor::Named(name) => name,
+        }
+    }
+
+    pub fn monitors(&self) -> impl Iterator<Item = &'a str> + '_ {
+        (0..self.members.len()).map(|member| self.monitor(member))
+    }
+
+    pub fn workspace(&self, slot: u32, member: usize) -> u32 {
+        self.base + slot - 1 + self.offset * member as u32
+    }
+
+    pub fn member_of(&self, workspace: u32) -> Option<usize> {
+        if workspace < self.base || workspace > self.max_workspace() {
+            return None;
+        }
+        Some(((workspace - self.base) / self.offset) as usize)
+    }
+
+    pub fn monitor_for(&self, workspace: u32) -> Option<&'a str> {
+        self.member_of(workspace).map(|member| self.monitor(member))
+    }
+
+    /// The workspace `monitor` shows once `workspace` is switched to.
+    pub fn shown_workspace(&self, workspace: u32, monitor: &str) -> Option<u32> {
+        if self.monitor_for(workspace) == Some(monitor) {
+            return Some(workspace);
+        }
+        let slot = self.slot(workspace);
+        self.monitors()
+            .position(|candidate| candidate == monitor)
+            .map(|member| self.workspace(slot, member))
+    }
+
+    pub fn rebalance_batch(&self) -> String {
+        let mut batch = HyprctlBatch::new();
+
+        for (member, monitor) in self.monitors().enumerate() {
+            for slot in 1..=self.offset {
+                batch.push(Dispatcher::MoveWorkspaceToMonitor {
+                    workspace: self.workspace(slot, member),
+                    monitor,
+                });
+            }
+        }
+
+        batch.into_argument()
+    }
+
+    pub fn preserving_rebalance_batch(&self, workspaces: &[WorkspaceInfo]) -> String {
+        let mut batch = HyprctlBatch::new();
+
+        for workspace in workspaces {
+            let Some(expected) = self.monitor_for(workspace.id) else {
+                continue;
+            };
+            if workspace.monitor.as_deref() != Some(expected) {
+                batch.push(Dispatcher::MoveWorkspaceToMonitor {
+                    workspace: workspace.id,
+                    monitor: expected,
+                });
+            }
+        }
+
+        batch.into_argument()
+    }
+
+    pub fn slot(&self, workspace: u32) -> u32 {
+        normalize_workspace(workspace, self.offset, self.base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Layout, LayoutMonitor};
+    use crate::config::Config;
+    use crate::hyprctl::{WorkspaceInfo, preserving_rebalance_batch, rebalance_batch};
+
+    fn trio() -> Vec<LayoutMonitor> {
+        vec![
+            LayoutMonitor::Primary,
+            LayoutMonitor::Secondary,
+            LayoutMonitor::Secondary,
+        ]
+    }
+
+    #[test]
+    fn parses_roles_and_monitor_names() {
+        let members: Vec<LayoutMonitor> =
+            serde_json::from_str(r#"["primary","secondary","DP-3"]"#).expect("layout");
+
+        assert_eq!(
+            members,
+            vec![
+                LayoutMonitor::Primary,
+                LayoutMonitor::Secondary,
+                LayoutMonitor::Named("DP-3".to_string()),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&members).expect("json"),
+            r#"["primary","secondary","DP-3"]"#
+        );
+    }
+
+    #[test]
+    fn empty_layout_is_paired() {
+        let layout = Layout::new("DP-1", "HDMI-A-1", 10, &[]);
+
+        assert_eq!(layout.member_count(), 2);
+        assert_eq!(layout.monitor_for(3), Some("DP-1"));
+        assert_eq!(layout.monitor_for(13), Some("HDMI-A-1"));
+        assert_eq!(layout.monitor_for(21), None);
+        assert_eq!(
+            layout.rebalance_batch(),
+            rebalance_batch("DP-1", "HDMI-A-1", 10)
+        );
+    }
+
+    #[test]
+    fn maps_trio_members_to_monitors() {
+        let members = trio();
+        let layout = Layout::new("DP-1", "HDMI-A-1", 10, &members);
+
+        assert_eq!(layout.max_workspace(), 30);
+        assert_eq!(layout.member_of(23), Some(2));
+        assert_eq!(layout.monitor_for(23), Some("HDMI-A-1"));
+        assert_eq!(layout.slot(23), 3);
+        assert_eq!(layout.shown_workspace(23, "HDMI-A-1"), Some(23));
+        assert_eq!(layout.shown_workspace(23, "DP-1"), Some(3));
+        assert_eq!(layout.shown_workspace(3, "HDMI-A-1"), Some(13));
+        assert_eq!(layout.shown_workspace(3, "DP-2"), None);
+    }
+
+    #[test]
+    fn rebalances_every_member() {
+        let members = trio();
+        let layout = Layout::new("DP-1", "HDMI-A-1", 1, &members);
+
+        assert_eq!(
+            layout.rebalance_batch(),
+            "dispatch moveworkspacetomonitor 1 DP-1 ; dispatch moveworkspacetomonitor 2 HDMI-A-1 ; dispatch moveworkspacetomonitor 3 HDMI-A-1"
+        );
+
+        let workspaces = [
+            WorkspaceInfo {
+                id: 2,
+                windows: 1,
+                name: None,
+                monitor: Some("HDMI-A-1".to_string()),
+            },
+            WorkspaceInfo {
+                id: 3,
+                windows: 1,
+                name: None,
+                monitor: Some("DP-1".to_string()),
+            },
+        ];
+        assert_eq!(
+            layout.preserving_rebalance_batch(&workspaces),
+            "dispatch moveworkspacetomonitor 3 HDMI-A-1"
+        );
+        assert_eq!(
+            Layout::paired("DP-1", "HDMI-A-1", 1).preserving_rebalance_batch(&workspaces),
+            preserving_rebalance_batch("DP-1", "HDMI-A-1", 1, &workspaces)
+        );
+    }
+
+    #[test]
+    fn numbers_workspaces_from_the_base() {
+        let layout = Layout::paired("DP-1", "HDMI-A-1", 10).with_base(0);
+
+        assert_eq!(layout.workspace(1, 0), 0);
+        assert_eq!(layout.workspace(10, 1), 19);
+        assert_eq!(layout.max_workspace(), 19);
+        assert_eq!(layout.monitor_for(0), Some("DP-1"));
+        assert_eq!(layout.monitor_for(10), Some("HDMI-A-1"));
+        assert_eq!(layout.monitor_for(20), None);
+        assert_eq!(layout.slot(13), 4);
+
+        let layout = Layout::paired("DP-1", "HDMI-A-1", 2).with_base(11);
+        assert_eq!(layout.monitor_for(10), None);
+        assert_eq!(layout.shown_workspace(14, "DP-1"), Some(12));
+        assert_eq!(
+            layout.rebalance_batch(),
+            "dispatch moveworkspacetomonitor 11 DP-1 ; dispatch moveworkspacetomonitor 12 DP-1 ; dispatch moveworkspacetomonitor 13 HDMI-A-1 ; dispatch moveworkspacetomonitor 14 HDMI-A-1"
+        );
+    }
+
+    #[test]
+    fn resolves_roles_against_selected_monitors() {
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .layout(vec![
+                LayoutMonitor::Primary,
+                LayoutMonitor::Secondary,
+                LayoutMonitor::Named("DP-3".to_string()),
+            ])
+            .build()
+            .expect("config");
+
+        let layout = Layout::for_config(&config);
+
+        assert_eq!(
+            layout.monitors().collect::<Vec<_>>(),
+            vec!["DP-1", "HDMI-A-1", "DP-3"]
+        );
+    }
+}
@@ -1,10 +1,50 @@
+pub mod capabilities;
 pub mod cli;
 pub mod commands;
+#[cfg(feature = "sway-compat")]
+pub mod compat;
 pub mod config;
+pub mod control;
 pub mod daemon;
+#[cfg(feature = "cli-full")]
+pub mod doctor;
+pub mod engine;
+pub mod events;
+pub mod hold;
 pub mod hyprctl;
+pub mod inhibit;
+#[cfg(feature = "cli-full")]
+pub mod init;
+pub mod instance;
+pub mod journal;
+pub mod layout;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod paired;
 pub mod paths;
+#[cfg(feature = "cli-full")]
+pub mod query;
+pub mod reconcile;
+pub mod replay;
+pub mod resolved;
+pub mod retry;
+#[cfg(feature = "async-daemon")]
+pub mod runtime;
+pub mod schedule;
+#[cfg(feature = "cli-full")]
 pub mod session;
+#[cfg(feature = "cli-full")]
+pub mod session_index;
+#[cfg(feature = "cli-full")]
 pub mod setup;
+pub mod shutdown;
+pub mod sticky;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+pub mod tui;
+pub mod validate;
+pub mod version;
+#[cfg(feature = "cli-full")]
 pub mod waybar;
+
+pub use engine::{Engine, EngineError};
@@ -1,6 +1,6 @@
 fn main() {
     if let Err(err) = hyprspaces::cli::run() {
         eprintln!("error: {}", err);
-        std::process::exit(1);
+        std::process::exit(err.exit_code());
     }
 }
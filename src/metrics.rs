@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::journal::IpcStats;
+
+pub const LATENCY_BUCKETS: [f64; 10] =
+    [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Registry {
+    events: BTreeMap<&'static str, u64>,
+    decisions: BTreeMap<&'static str, u64>,
+    ipc: IpcStats,
+    suppressed: BTreeMap<&'static str, u64>,
+    latency: Histogram,
+}
+
+impl Registry {
+    pub fn record_event(&mut self, kind: &'static str) {
+        *self.events.entry(kind).or_insert(0) += 1;
+    }
+
+    pub fn record_decision(&mut self, outcome: &'static str) {
+        *self.decisions.entry(outcome).or_insert(0) += 1;
+    }
+
+    pub fn set_ipc_stats(&mut self, stats: IpcStats) {
+        self.ipc = stats;
+    }
+
+    pub fn set_suppressed(&mut self, debounce: &'static str, total: u64) {
+        self.suppressed.insert(debounce, total);
+    }
+
+    pub fn observe_latency(&mut self, latency: Duration) {
+        self.latency.observe(latency.as_secs_f64());
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "# HELP hyprspaces_events_total Hyprland events received by the daemon."
+        );
+        let _ = writeln!(out, "# TYPE hyprspaces_events_total counter");
+        for (kind, count) in &self.events {
+            let _ = writeln!(out, "hyprspaces_events_total{{kind=\"{kind}\"}} {count}");
+        }
+        let _ = writeln!(
+            out,
+            "# HELP hyprspaces_decisions_total What the daemon decided for each event."
+        );
+        let _ = writeln!(out, "# TYPE hyprspaces_decisions_total counter");
+        for (outcome, count) in &self.decisions {
+            let _ = writeln!(
+                out,
+                "hyprspaces_decisions_total{{outcome=\"{outcome}\"}} {count}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP hyprspaces_batches_dispatched_total Batches and dispatches sent to Hyprland."
+        );
+        let _ = writeln!(out, "# TYPE hyprspaces_batches_dispatched_total counter");
+        let _ = writeln!(
+            out,
+            "hyprspaces_batches_dispatched_total {}",
+            self.ipc.commands
+        );
+        let _ = writeln!(
+            out,
+            "# HELP hyprspaces_debounce_suppressed_total Events dropped by a debounce window."
+        );
+        let _ = writeln!(out, "# TYPE hyprspaces_debounce_suppressed_total counter");
+        for (debounce, count) in &self.suppressed {
+            let _ = writeln!(
+                out,
+                "hyprspaces_debounce_suppressed_total{{debounce=\"{debounce}\"}} {count}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP hyprspaces_ipc_errors_total Failed IPC requests."
+        );
+        let _ = writeln!(out, "# TYPE hyprspaces_ipc_errors_total counter");
+        let _ = writeln!(out, "hyprspaces_ipc_errors_total {}", self.ipc.errors);
+        let _ = writeln!(
+            out,
+            "# HELP hyprspaces_event_dispatch_seconds Time from receiving an event to dispatching its batch."
+        );
+        let _ = writeln!(out, "# TYPE hyprspaces_event_dispatch_seconds histogram");
+        for (count, bound) in self.latency.buckets.iter().zip(LATENCY_BUCKETS) {
+            let _ = writeln!(
+                out,
+                "hyprspaces_event_dispatch_seconds_bucket{{le=\"{bound}\"}} {count}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "hyprspaces_event_dispatch_seconds_bucket{{le=\"+Inf\"}} {}",
+            self.latency.count
+        );
+        let _ = writeln!(
+            out,
+            "hyprspaces_event_dispatch_seconds_sum {}",
+            self.latency.sum
+        );
+        let _ = writeln!(
+            out,
+            "hyprspaces_event_dispatch_seconds_count {}",
+            self.latency.count
+        );
+        out
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    registry: Mutex<Registry>,
+}
+
+impl Metrics {
+    pub fn update(&self, update: impl FnOnce(&mut Registry)) {
+        if let Ok(mut registry) = self.registry.lock() {
+            update(&mut registry);
+        }
+    }
+
+    pub fn render(&self) -> String {
+        self.registry
+            .lock()
+            .map(|registry| registry.render())
+            .unwrap_or_default()
+    }
+}
+
+pub fn serve(address: &str, metrics: Arc<Metrics>) -> io::Result<()> {
+    if address.starts_with('/') {
+        let path = Path::new(address);
+        remove_stale_socket(path)?;
+        let listener = UnixListener::bind(path)?;
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = stream.set_read_timeout(Some(REQUEST_TIMEOUT));
+                let _ = respond(stream, &metrics);
+            }
+        });
+    } else {
+        let listener = TcpListener::bind(address)?;
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = stream.set_read_timeout(Some(REQUEST_TIMEOUT));
+                let _ = respond(stream, &metrics);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Unlinks a socket nobody listens on; anything else at `path` is left alone.
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("metrics address is not a socket: {}", path.display()),
+        ));
+    }
+    if UnixStream::connect(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("metrics socket already in use: {}", path.display()),
+        ));
+    }
+    fs::remove_file(path)
+}
+
+fn respond<S: Read + Write>(stream: S, metrics: &Metrics) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && !line.trim_end().is_empty() {
+        line.clear();
+    }
+    let body = metrics.render();
+    write!(
+        reader.get_mut(),
+        "HTTP/1.0 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Metrics, serve};
+    use crate::journal::IpcStats;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn renders_prometheus_text() {
+        let metrics = Metrics::default();
+        metrics.update(|registry| {
+            registry.record_event("focus");
+            registry.record_event("focus");
+            registry.record_event("monitor");
+            registry.record_decision("debounced");
+            registry.set_ipc_stats(IpcStats {
+                commands: 3,
+                errors: 1,
+            });
+            registry.set_suppressed("focus", 4);
+            registry.observe_latency(Duration::from_millis(3));
+            registry.observe_latency(Duration::from_millis(40));
+        });
+
+        let text = metrics.render();
+
+        assert!(text.contains("hyprspaces_events_total{kind=\"focus\"} 2\n"));
+        assert!(text.contains("hyprspaces_events_total{kind=\"monitor\"} 1\n"));
+        assert!(text.contains("hyprspaces_decisions_total{outcome=\"debounced\"} 1\n"));
+        assert!(text.contains("hyprspaces_batches_dispatched_total 3\n"));
+        assert!(text.contains("hyprspaces_ipc_errors_total 1\n"));
+        assert!(text.contains("hyprspaces_debounce_suppressed_total{debounce=\"focus\"} 4\n"));
+        assert!(text.contains("hyprspaces_event_dispatch_seconds_bucket{le=\"0.0025\"} 0\n"));
+        assert!(text.contains("hyprspaces_event_dispatch_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("hyprspaces_event_dispatch_seconds_bucket{le=\"0.05\"} 2\n"));
+        assert!(text.contains("hyprspaces_event_dispatch_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("hyprspaces_event_dispatch_seconds_count 2\n"));
+    }
+
+    #[test]
+    fn serves_metrics_over_unix_socket() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("metrics.sock");
+        let metrics = Arc::new(Metrics::default());
+        metrics.update(|registry| registry.record_event("window"));
+
+        serve(path.to_str().expect("utf8"), Arc::clone(&metrics)).expect("serve");
+        let mut stream = UnixStream::connect(&path).expect("connect");
+        stream
+            .write_all(b"GET /metrics HTTP/1.0\r\n\r\n")
+            .expect("request");
+        let mut response = String::new();
+        stream.read_to_string(&mut response).expect("response");
+
+        assert!(response.starts_with("HTTP/1.0 200 OK\r\n"));
+        assert!(response.contains("hyprspaces_events_total{kind=\"window\"} 1\n"));
+    }
+
+    #[test]
+    fn replaces_stale_sockets_but_not_other_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let stale = dir.path().join("stale.sock");
+        drop(std::os::unix::net::UnixListener::bind(&stale).expect("stale"));
+        serve(stale.to_str().expect("utf8"), Arc::default()).expect("serve");
+        assert!(UnixStream::connect(&stale).is_ok());
+
+        let err = serve(stale.to_str().expect("utf8"), Arc::default()).expect_err("in use");
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "keep me").expect("write");
+        let err = serve(file.to_str().expect("utf8"), Arc::default()).expect_err("not a socket");
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(&file).expect("read"), "keep me");
+    }
+}
@@ -1,11 +1,120 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, OutOfRangePolicy};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CycleDirection {
     Next,
     Prev,
 }
 
-pub fn normalize_workspace(id: u32, offset: u32) -> u32 {
-    ((id - 1) % offset) + 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CycleOrder {
+    #[default]
+    Numeric,
+    Mru,
+    Occupied,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PairedSide {
+    Primary,
+    Secondary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PairedTarget {
+    pub slot: u32,
+    pub side: PairedSide,
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum WorkspaceRangeError {
+    #[error("workspace ids start at {base}")]
+    BelowBase { workspace: u32, base: u32 },
+    #[error("workspace {workspace} is outside the paired range {min}-{max}")]
+    OutOfRange { workspace: u32, min: u32, max: u32 },
+    #[error("no workspace pair is named '{0}'")]
+    UnknownName(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceArg {
+    Id(u32),
+    Name(String),
+}
+
+impl WorkspaceArg {
+    pub fn resolve(&self, config: &Config) -> Result<u32, WorkspaceRangeError> {
+        match self {
+            WorkspaceArg::Id(id) => Ok(*id),
+            WorkspaceArg::Name(name) => config
+                .slot_for_name(name)
+                .map(|slot| config.slot_workspace(slot))
+                .ok_or_else(|| WorkspaceRangeError::UnknownName(name.clone())),
+        }
+    }
+}
+
+impl FromStr for WorkspaceArg {
+    type Err = Infallible;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Ok(match input.parse() {
+            Ok(id) => WorkspaceArg::Id(id),
+            Err(_) => WorkspaceArg::Name(input.to_string()),
+        })
+    }
+}
+
+/// The 1-based slot of workspace `id` when slot 1 is workspace `base`.
+pub fn normalize_workspace(id: u32, offset: u32, base: u32) -> u32 {
+    let offset = i64::from(offset.max(1));
+    ((i64::from(id) - i64::from(base)).rem_euclid(offset) + 1) as u32
+}
+
+pub fn resolve_workspace(
+    workspace: u32,
+    offset: u32,
+    base: u32,
+    policy: OutOfRangePolicy,
+) -> Result<PairedTarget, WorkspaceRangeError> {
+    if workspace < base {
+        return Err(WorkspaceRangeError::BelowBase { workspace, base });
+    }
+    let index = workspace - base;
+    if index < offset {
+        return Ok(PairedTarget {
+            slot: index + 1,
+            side: PairedSide::Primary,
+        });
+    }
+    match policy {
+        OutOfRangePolicy::Normalize => Ok(PairedTarget {
+            slot: normalize_workspace(workspace, offset, base),
+            side: PairedSide::Primary,
+        }),
+        OutOfRangePolicy::Raw if index < offset * 2 => Ok(PairedTarget {
+            slot: index - offset + 1,
+            side: PairedSide::Secondary,
+        }),
+        OutOfRangePolicy::Raw => Err(WorkspaceRangeError::OutOfRange {
+            workspace,
+            min: base,
+            max: base + offset * 2 - 1,
+        }),
+        OutOfRangePolicy::Error => Err(WorkspaceRangeError::OutOfRange {
+            workspace,
+            min: base,
+            max: base + offset - 1,
+        }),
+    }
 }
 
 pub fn cycle_target(base: u32, offset: u32, direction: CycleDirection, wrap: bool) -> u32 {
@@ -31,14 +140,87 @@ pub fn cycle_target(base: u32, offset: u32, direction: CycleDirection, wrap: boo
     }
 }
 
+pub fn occupied_cycle_target(
+    base: u32,
+    offset: u32,
+    direction: CycleDirection,
+    wrap: bool,
+    occupied: &[u32],
+) -> Option<u32> {
+    let mut slot = base;
+    for _ in 1..offset {
+        let next = cycle_target(slot, offset, direction, wrap);
+        if next == slot || next == base {
+            return None;
+        }
+        if occupied.contains(&next) {
+            return Some(next);
+        }
+        slot = next;
+    }
+    None
+}
+
+pub fn cycle_order(
+    offset: u32,
+    order: CycleOrder,
+    history: &[u32],
+    counts: &BTreeMap<u32, u32>,
+) -> Vec<u32> {
+    let mut slots: Vec<u32> = (1..=offset).collect();
+    match order {
+        CycleOrder::Numeric => {}
+        CycleOrder::Mru => {
+            let rank = |slot: &u32| {
+                history
+                    .iter()
+                    .position(|recent| recent == slot)
+                    .unwrap_or(history.len())
+            };
+            slots.sort_by_key(rank);
+        }
+        CycleOrder::Occupied => {
+            slots.sort_by_key(|slot| Reverse(counts.get(slot).copied().unwrap_or(0)));
+        }
+    }
+    slots
+}
+
+pub fn ordered_cycle_target(
+    slots: &[u32],
+    base: u32,
+    direction: CycleDirection,
+    wrap: bool,
+) -> Option<u32> {
+    let idx = slots.iter().position(|slot| *slot == base)?;
+    let target = match direction {
+        CycleDirection::Next if idx + 1 < slots.len() => slots[idx + 1],
+        CycleDirection::Prev if idx > 0 => slots[idx - 1],
+        CycleDirection::Next if wrap => slots[0],
+        CycleDirection::Prev if wrap => slots[slots.len() - 1],
+        _ => return None,
+    };
+    (target != base).then_some(target)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{CycleDirection, cycle_target, normalize_workspace};
+    use super::{
+        CycleDirection, CycleOrder, PairedSide, PairedTarget, WorkspaceArg, WorkspaceRangeError,
+        cycle_order, cycle_target, normalize_workspace, occupied_cycle_target,
+        ordered_cycle_target, resolve_workspace,
+    };
+    use crate::config::{Config, OutOfRangePolicy};
+    use std::collections::BTreeMap;
 
     #[test]
     fn normalizes_workspace_ids_with_offset() {
-        assert_eq!(normalize_workspace(1, 10), 1);
-        assert_eq!(normalize_workspace(12, 10), 2);
+        assert_eq!(normalize_workspace(1, 10, 1), 1);
+        assert_eq!(normalize_workspace(12, 10, 1), 2);
+        assert_eq!(normalize_workspace(0, 10, 0), 1);
+        assert_eq!(normalize_workspace(19, 10, 0), 10);
+        assert_eq!(normalize_workspace(21, 10, 11), 1);
+        assert_eq!(normalize_workspace(5, 10, 11), 5);
     }
 
     #[test]
@@ -64,4 +246,184 @@ mod tests {
         assert_eq!(cycle_target(2, 10, CycleDirection::Prev, false), 1);
         assert_eq!(cycle_target(1, 10, CycleDirection::Prev, false), 1);
     }
+
+    #[test]
+    fn cycles_between_occupied_slots() {
+        let occupied = [2, 7];
+        assert_eq!(
+            occupied_cycle_target(2, 10, CycleDirection::Next, true, &occupied),
+            Some(7)
+        );
+        assert_eq!(
+            occupied_cycle_target(7, 10, CycleDirection::Next, true, &occupied),
+            Some(2)
+        );
+        assert_eq!(
+            occupied_cycle_target(7, 10, CycleDirection::Next, false, &occupied),
+            None
+        );
+        assert_eq!(
+            occupied_cycle_target(2, 10, CycleDirection::Prev, true, &[2]),
+            None
+        );
+    }
+
+    #[test]
+    fn orders_slots_by_recency_and_occupancy() {
+        let counts = BTreeMap::from([(2, 1), (4, 3)]);
+
+        assert_eq!(
+            cycle_order(5, CycleOrder::Mru, &[3, 1], &counts),
+            vec![3, 1, 2, 4, 5]
+        );
+        assert_eq!(
+            cycle_order(5, CycleOrder::Occupied, &[], &counts),
+            vec![4, 2, 1, 3, 5]
+        );
+        assert_eq!(
+            cycle_order(3, CycleOrder::Numeric, &[3], &counts),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn steps_through_ordered_slots() {
+        let slots = [3, 1, 2];
+
+        assert_eq!(
+            ordered_cycle_target(&slots, 3, CycleDirection::Next, true),
+            Some(1)
+        );
+        assert_eq!(
+            ordered_cycle_target(&slots, 2, CycleDirection::Next, true),
+            Some(3)
+        );
+        assert_eq!(
+            ordered_cycle_target(&slots, 2, CycleDirection::Next, false),
+            None
+        );
+        assert_eq!(
+            ordered_cycle_target(&slots, 3, CycleDirection::Prev, true),
+            Some(2)
+        );
+        assert_eq!(
+            ordered_cycle_target(&[3], 3, CycleDirection::Next, true),
+            None
+        );
+    }
+
+    #[test]
+    fn resolves_out_of_range_by_normalizing() {
+        assert_eq!(
+            resolve_workspace(13, 10, 1, OutOfRangePolicy::Normalize),
+            Ok(PairedTarget {
+                slot: 3,
+                side: PairedSide::Primary,
+            })
+        );
+    }
+
+    #[test]
+    fn resolves_raw_ids_to_secondary_side() {
+        assert_eq!(
+            resolve_workspace(13, 10, 1, OutOfRangePolicy::Raw),
+            Ok(PairedTarget {
+                slot: 3,
+                side: PairedSide::Secondary,
+            })
+        );
+        assert_eq!(
+            resolve_workspace(3, 10, 1, OutOfRangePolicy::Raw),
+            Ok(PairedTarget {
+                slot: 3,
+                side: PairedSide::Primary,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_raw_ids_beyond_secondary_range() {
+        assert_eq!(
+            resolve_workspace(21, 10, 1, OutOfRangePolicy::Raw),
+            Err(WorkspaceRangeError::OutOfRange {
+                workspace: 21,
+                min: 1,
+                max: 20,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_with_error_policy() {
+        assert_eq!(
+            resolve_workspace(13, 10, 1, OutOfRangePolicy::Error),
+            Err(WorkspaceRangeError::OutOfRange {
+                workspace: 13,
+                min: 1,
+                max: 10,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_workspace_zero() {
+        assert_eq!(
+            resolve_workspace(0, 10, 1, OutOfRangePolicy::Normalize),
+            Err(WorkspaceRangeError::BelowBase {
+                workspace: 0,
+                base: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn resolves_workspaces_from_a_custom_base() {
+        assert_eq!(
+            resolve_workspace(0, 10, 0, OutOfRangePolicy::Error),
+            Ok(PairedTarget {
+                slot: 1,
+                side: PairedSide::Primary,
+            })
+        );
+        assert_eq!(
+            resolve_workspace(23, 10, 11, OutOfRangePolicy::Raw),
+            Ok(PairedTarget {
+                slot: 3,
+                side: PairedSide::Secondary,
+            })
+        );
+        assert_eq!(
+            resolve_workspace(21, 10, 11, OutOfRangePolicy::Error),
+            Err(WorkspaceRangeError::OutOfRange {
+                workspace: 21,
+                min: 11,
+                max: 20,
+            })
+        );
+        assert_eq!(
+            WorkspaceRangeError::BelowBase {
+                workspace: 3,
+                base: 11,
+            }
+            .to_string(),
+            "workspace ids start at 11"
+        );
+    }
+
+    #[test]
+    fn resolves_workspace_names_and_ids() {
+        let config = Config::from_json(
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","names":{"1":"code","2":"web"}}"#,
+        )
+        .expect("config");
+        let arg = |input: &str| input.parse::<WorkspaceArg>().expect("arg");
+
+        assert_eq!(arg("13"), WorkspaceArg::Id(13));
+        assert_eq!(arg("web").resolve(&config), Ok(2));
+        assert_eq!(arg("13").resolve(&config), Ok(13));
+        assert_eq!(
+            arg("mail").resolve(&config),
+            Err(WorkspaceRangeError::UnknownName("mail".to_string()))
+        );
+    }
 }
@@ -16,9 +16,57 @@ pub fn hypr_config_dir(home: &Path, xdg_config: Option<&Path>) -> PathBuf {
     config_dir(home, xdg_config).join("hypr")
 }
 
+pub fn autostart_dir(home: &Path, xdg_config: Option<&Path>) -> PathBuf {
+    config_dir(home, xdg_config).join("autostart")
+}
+
+pub fn cache_dir(home: &Path, xdg_cache: Option<&Path>) -> PathBuf {
+    xdg_cache
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".cache"))
+}
+
+/// The Hyprland instance signature, when it can name a directory.
+fn instance(signature: Option<&str>) -> Option<&str> {
+    signature.filter(|sig| !sig.is_empty() && !sig.contains('/') && *sig != "." && *sig != "..")
+}
+
+/// `base_dir/run/<signature>`, or `base_dir` outside Hyprland.
+pub fn state_dir(base_dir: &Path, signature: Option<&str>) -> PathBuf {
+    match instance(signature) {
+        Some(sig) => base_dir.join("run").join(sig),
+        None => base_dir.to_path_buf(),
+    }
+}
+
+/// `base_dir/sessions/<signature>`, or `base_dir/sessions` outside Hyprland.
+pub fn sessions_dir(base_dir: &Path, signature: Option<&str>) -> PathBuf {
+    let dir = base_dir.join("sessions");
+    match instance(signature) {
+        Some(sig) => dir.join(sig),
+        None => dir,
+    }
+}
+
+/// `$XDG_RUNTIME_DIR/hyprspaces/<signature>`, or `state_dir` without one.
+pub fn runtime_dir(
+    xdg_runtime: Option<&Path>,
+    state_dir: &Path,
+    signature: Option<&str>,
+) -> PathBuf {
+    match (xdg_runtime, instance(signature)) {
+        (Some(dir), Some(sig)) => dir.join("hyprspaces").join(sig),
+        (Some(dir), None) => dir.join("hyprspaces"),
+        (None, _) => state_dir.to_path_buf(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{config_dir, config_path, hypr_config_dir};
+    use super::{
+        autostart_dir, cache_dir, config_dir, config_path, hypr_config_dir, runtime_dir,
+        sessions_dir, state_dir,
+    };
     use std::path::PathBuf;
 
     #[test]
@@ -55,4 +103,61 @@ mod tests {
             PathBuf::from("/home/jtaw/.config/hypr")
         );
     }
+
+    #[test]
+    fn builds_xdg_autostart_dir() {
+        let home = PathBuf::from("/home/jtaw");
+        let xdg = PathBuf::from("/tmp/config");
+
+        assert_eq!(
+            autostart_dir(&home, Some(&xdg)),
+            PathBuf::from("/tmp/config/autostart")
+        );
+    }
+
+    #[test]
+    fn builds_cache_dir() {
+        let home = PathBuf::from("/home/jtaw");
+        let xdg = PathBuf::from("/tmp/cache");
+
+        assert_eq!(cache_dir(&home, None), PathBuf::from("/home/jtaw/.cache"));
+        assert_eq!(cache_dir(&home, Some(&xdg)), PathBuf::from("/tmp/cache"));
+    }
+
+    #[test]
+    fn prefers_xdg_runtime_dir_for_sockets() {
+        let base = PathBuf::from("/home/jtaw/.config/hyprspaces");
+        let runtime = PathBuf::from("/run/user/1000");
+
+        assert_eq!(
+            runtime_dir(Some(&runtime), &base, None),
+            PathBuf::from("/run/user/1000/hyprspaces")
+        );
+        assert_eq!(
+            runtime_dir(Some(&runtime), &base, Some("abc_123")),
+            PathBuf::from("/run/user/1000/hyprspaces/abc_123")
+        );
+        assert_eq!(runtime_dir(None, &base, Some("abc_123")), base);
+    }
+
+    #[test]
+    fn namespaces_state_by_instance_signature() {
+        let base = PathBuf::from("/home/jtaw/.config/hyprspaces");
+
+        assert_eq!(
+            state_dir(&base, Some("abc_123")),
+            PathBuf::from("/home/jtaw/.config/hyprspaces/run/abc_123")
+        );
+        assert_eq!(state_dir(&base, None), base);
+        assert_eq!(state_dir(&base, Some("")), base);
+        assert_eq!(state_dir(&base, Some("../escape")), base);
+        assert_eq!(
+            sessions_dir(&base, Some("abc_123")),
+            PathBuf::from("/home/jtaw/.config/hyprspaces/sessions/abc_123")
+        );
+        assert_eq!(
+            sessions_dir(&base, None),
+            PathBuf::from("/home/jtaw/.config/hyprspaces/sessions")
+        );
+    }
 }
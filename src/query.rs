@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::hyprctl::{ClientInfo, HyprctlError, HyprlandIpc, WorkspaceInfo};
+use crate::paired::normalize_workspace;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WindowEntry {
+    pub address: String,
+    pub title: String,
+    pub class: String,
+    pub workspace_id: u32,
+    pub slot: Option<u32>,
+    pub monitor: Option<String>,
+}
+
+impl WindowEntry {
+    fn label(&self) -> String {
+        let slot = self
+            .slot
+            .map(|slot| slot.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let monitor = self.monitor.as_deref().unwrap_or("?");
+        format!("[{slot}] {} ({}) on {monitor}", self.title, self.class)
+    }
+}
+
+pub fn window_entries(
+    clients: &[ClientInfo],
+    workspaces: &[WorkspaceInfo],
+    offset: u32,
+    base: u32,
+) -> Vec<WindowEntry> {
+    let monitors = workspaces
+        .iter()
+        .filter_map(|workspace| Some((workspace.id, workspace.monitor.clone()?)))
+        .collect::<HashMap<_, _>>();
+    let mut entries = clients
+        .iter()
+        .map(|client| {
+            let workspace_id = client.workspace.id;
+            WindowEntry {
+                address: client.address.clone(),
+                title: client.title.clone().unwrap_or_default(),
+                class: client.class.clone().unwrap_or_default(),
+                workspace_id,
+                slot: (base..base + offset * 2)
+                    .contains(&workspace_id)
+                    .then(|| normalize_workspace(workspace_id, offset, base)),
+                monitor: monitors.get(&workspace_id).cloned(),
+            }
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|entry| (entry.slot.unwrap_or(u32::MAX), entry.workspace_id));
+    entries
+}
+
+pub fn query_windows(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+) -> Result<Vec<WindowEntry>, HyprctlError> {
+    let clients = hyprctl.clients()?;
+    let workspaces = hyprctl.workspaces()?;
+    Ok(window_entries(
+        &clients,
+        &workspaces,
+        config.paired_offset,
+        config.workspace_base,
+    ))
+}
+
+pub fn render_alfred(entries: &[WindowEntry]) -> String {
+    let items = entries
+        .iter()
+        .map(|entry| {
+            serde_json::json!({
+                "uid": entry.address,
+                "title": entry.title,
+                "subtitle": entry.label(),
+                "arg": entry.address,
+                "variables": {
+                    "address": entry.address,
+                    "class": entry.class,
+                    "workspace": entry.workspace_id,
+                    "slot": entry.slot,
+                    "monitor": entry.monitor,
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({ "items": items }).to_string()
+}
+
+pub fn render_rofi_script(entries: &[WindowEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| format!("{}\0info\x1f{}", entry.label(), entry.address))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_alfred, render_rofi_script, window_entries};
+    use crate::hyprctl::{ClientInfo, WorkspaceInfo, WorkspaceRef};
+
+    fn client(address: &str, workspace: u32, title: &str) -> ClientInfo {
+        ClientInfo {
+            address: address.to_string(),
+            workspace: WorkspaceRef {
+                id: workspace,
+                name: None,
+            },
+            class: Some("kitty".to_string()),
+            title: Some(title.to_string()),
+            initial_class: None,
+            initial_title: None,
+            app_id: None,
+            pid: None,
+            focus_history_id: None,
+            floating: false,
+            at: None,
+            size: None,
+            fullscreen: 0,
+            fullscreen_client: 0,
+            pseudo: false,
+        }
+    }
+
+    fn workspace(id: u32, monitor: &str) -> WorkspaceInfo {
+        WorkspaceInfo {
+            id,
+            windows: 1,
+            name: None,
+            monitor: Some(monitor.to_string()),
+        }
+    }
+
+    #[test]
+    fn builds_entries_sorted_by_slot() {
+        let clients = vec![
+            client("0x3", 25, "scratch"),
+            client("0x2", 12, "browser"),
+            client("0x1", 1, "editor"),
+        ];
+        let workspaces = vec![workspace(1, "DP-1"), workspace(12, "HDMI-A-1")];
+
+        let entries = window_entries(&clients, &workspaces, 10, 1);
+
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.address.as_str(), entry.slot))
+                .collect::<Vec<_>>(),
+            vec![("0x1", Some(1)), ("0x2", Some(2)), ("0x3", None)]
+        );
+        assert_eq!(entries[1].monitor.as_deref(), Some("HDMI-A-1"));
+        assert_eq!(entries[2].monitor, None);
+    }
+
+    #[test]
+    fn renders_alfred_items() {
+        let entries = window_entries(
+            &[client("0x1", 12, "browser")],
+            &[workspace(12, "HDMI-A-1")],
+            10,
+            1,
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&render_alfred(&entries)).expect("json");
+
+        let item = &json["items"][0];
+        assert_eq!(item["uid"], "0x1");
+        assert_eq!(item["arg"], "0x1");
+        assert_eq!(item["title"], "browser");
+        assert_eq!(item["subtitle"], "[2] browser (kitty) on HDMI-A-1");
+        assert_eq!(item["variables"]["slot"], 2);
+        assert_eq!(item["variables"]["monitor"], "HDMI-A-1");
+    }
+
+    #[test]
+    fn renders_rofi_script_rows() {
+        let entries = window_entries(
+            &[client("0x1", 1, "editor"), client("0x2", 30, "notes")],
+            &[workspace(1, "DP-1")],
+            10,
+            1,
+        );
+
+        assert_eq!(
+            render_rofi_script(&entries),
+            "[1] editor (kitty) on DP-1\0info\x1f0x1\n[-] notes (kitty) on ?\0info\x1f0x2"
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, ReconcileConfig, ReconcilePolicy};
+use crate::hyprctl::{
+    Dispatcher, HyprctlBatch, HyprctlError, HyprlandIpc, MonitorInfo, WorkspaceInfo,
+    logical_monitors,
+};
+use crate::layout::Layout;
+
+/// A workspace placed on the wrong monitor for the configured pairing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    /// A managed workspace on the wrong monitor.
+    Misplaced {
+        workspace: u32,
+        monitor: Option<String>,
+        expected: String,
+    },
+    /// A workspace beyond the managed range; reported, never moved.
+    Unmanaged {
+        workspace: u32,
+        monitor: Option<String>,
+    },
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Drift::Misplaced {
+                workspace,
+                monitor,
+                expected,
+            } => write!(
+                f,
+                "workspace {workspace} is on {}, expected {expected}",
+                monitor.as_deref().unwrap_or("no monitor")
+            ),
+            Drift::Unmanaged { workspace, monitor } => write!(
+                f,
+                "workspace {workspace} on {} is outside the managed range",
+                monitor.as_deref().unwrap_or("no monitor")
+            ),
+        }
+    }
+}
+
+/// Skips workspaces expected on a disconnected monitor, as resync does.
+pub fn detect(
+    layout: &Layout<'_>,
+    monitors: &[MonitorInfo],
+    workspaces: &[WorkspaceInfo],
+) -> Vec<Drift> {
+    let has_monitor = |name: &str| monitors.iter().any(|monitor| monitor.name == name);
+    let mut drift: Vec<Drift> = workspaces
+        .iter()
+        .filter(|workspace| workspace.id > 0)
+        .filter_map(|workspace| match layout.monitor_for(workspace.id) {
+            Some(expected)
+                if !has_monitor(expected) || workspace.monitor.as_deref() == Some(expected) =>
+            {
+                None
+            }
+            Some(expected) => Some(Drift::Misplaced {
+                workspace: workspace.id,
+                monitor: workspace.monitor.clone(),
+                expected: expected.to_string(),
+            }),
+            None => Some(Drift::Unmanaged {
+                workspace: workspace.id,
+                monitor: workspace.monitor.clone(),
+            }),
+        })
+        .collect();
+    drift.sort_by_key(|drift| match drift {
+        Drift::Misplaced { workspace, .. } | Drift::Unmanaged { workspace, .. } => *workspace,
+    });
+    drift
+}
+
+/// One line per drift entry, marking the workspaces that were moved back.
+pub fn report(drift: &[Drift], fixed: bool) -> String {
+    if drift.is_empty() {
+        return "no drift".to_string();
+    }
+    drift
+        .iter()
+        .map(|entry| match entry {
+            Drift::Misplaced { .. } if fixed => format!("{entry} (moved)"),
+            _ => entry.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Moves every misplaced workspace back to its expected monitor.
+pub fn fix_batch(drift: &[Drift]) -> String {
+    let mut batch = HyprctlBatch::new();
+
+    for entry in drift {
+        if let Drift::Misplaced {
+            workspace,
+            expected,
+            ..
+        } = entry
+        {
+            batch.push(Dispatcher::MoveWorkspaceToMonitor {
+                workspace: *workspace,
+                monitor: expected,
+            });
+        }
+    }
+
+    batch.into_argument()
+}
+
+/// Returns the drift found, adopting misplaced workspaces when `fix` is set.
+pub fn reconcile(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    fix: bool,
+) -> Result<Vec<Drift>, HyprctlError> {
+    let drift = detect(
+        &Layout::for_config(config),
+        &logical_monitors(hyprctl.monitors()?),
+        &hyprctl.workspaces()?,
+    );
+    if fix {
+        let batch = fix_batch(&drift);
+        if !batch.is_empty() {
+            hyprctl.batch(&batch)?;
+        }
+    }
+    Ok(drift)
+}
+
+/// Fires every `interval`; a zero interval never fires.
+#[derive(Debug, Clone)]
+pub struct ReconcileTimer {
+    interval: Duration,
+    deadline: Option<Instant>,
+}
+
+impl ReconcileTimer {
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Self {
+            interval,
+            deadline: (!interval.is_zero()).then(|| now + interval),
+        }
+    }
+
+    pub fn from_config(config: &ReconcileConfig, now: Instant) -> Self {
+        let interval = match config.policy {
+            ReconcilePolicy::Off => Duration::ZERO,
+            ReconcilePolicy::Report | ReconcilePolicy::Fix => {
+                Duration::from_millis(config.interval_ms)
+            }
+        };
+        Self::new(interval, now)
+    }
+
+    pub fn due(&mut self, now: Instant) -> bool {
+        match self.deadline {
+            Some(deadline) if now >= deadline => {
+                self.deadline = Some(now + self.interval);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Drift, ReconcileTimer, detect, fix_batch, reconcile, report};
+    use crate::config::{Config, ReconcileConfig, ReconcilePolicy};
+    use crate::hyprctl::{MonitorInfo, WorkspaceInfo};
+    use crate::layout::Layout;
+    use crate::testing::ScriptedIpc;
+    use std::time::{Duration, Instant};
+
+    fn workspace(id: u32, monitor: &str) -> WorkspaceInfo {
+        WorkspaceInfo {
+            id,
+            windows: 1,
+            name: None,
+            monitor: Some(monitor.to_string()),
+        }
+    }
+
+    fn monitors(names: &[&str]) -> Vec<MonitorInfo> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| MonitorInfo {
+                name: name.to_string(),
+                x: 0,
+                id: id as i32,
+                mirror_of: None,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_misplaced_and_unmanaged_workspaces() {
+        let layout = Layout::paired("DP-1", "HDMI-A-1", 10);
+        let workspaces = [
+            workspace(25, "DP-1"),
+            workspace(17, "DP-1"),
+            workspace(2, "DP-1"),
+            workspace(12, "HDMI-A-1"),
+        ];
+
+        let drift = detect(&layout, &monitors(&["DP-1", "HDMI-A-1"]), &workspaces);
+
+        assert_eq!(
+            drift,
+            vec![
+                Drift::Misplaced {
+                    workspace: 17,
+                    monitor: Some("DP-1".to_string()),
+                    expected: "HDMI-A-1".to_string(),
+                },
+                Drift::Unmanaged {
+                    workspace: 25,
+                    monitor: Some("DP-1".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            drift[0].to_string(),
+            "workspace 17 is on DP-1, expected HDMI-A-1"
+        );
+        assert_eq!(
+            drift[1].to_string(),
+            "workspace 25 on DP-1 is outside the managed range"
+        );
+        assert_eq!(
+            report(&drift, true),
+            "workspace 17 is on DP-1, expected HDMI-A-1 (moved)\nworkspace 25 on DP-1 is outside the managed range"
+        );
+        assert_eq!(report(&[], false), "no drift");
+        assert_eq!(
+            fix_batch(&drift),
+            "dispatch moveworkspacetomonitor 17 HDMI-A-1"
+        );
+    }
+
+    #[test]
+    fn ignores_workspaces_for_disconnected_monitors() {
+        let layout = Layout::paired("DP-1", "HDMI-A-1", 10);
+
+        assert!(detect(&layout, &monitors(&["DP-1"]), &[workspace(17, "DP-1")]).is_empty());
+    }
+
+    #[test]
+    fn fixes_only_when_asked() {
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .build()
+            .expect("config");
+        let ipc = ScriptedIpc::new()
+            .with_json(
+                "monitors",
+                r#"[{"name":"DP-1","x":0,"id":0},{"name":"HDMI-A-1","x":1920,"id":1}]"#,
+            )
+            .with_json(
+                "workspaces",
+                r#"[{"id":17,"windows":1,"monitor":"DP-1"},{"id":3,"windows":0,"monitor":"DP-1"}]"#,
+            );
+
+        assert_eq!(reconcile(&ipc, &config, false).expect("report").len(), 1);
+        assert!(!ipc.called(&["--batch", "dispatch moveworkspacetomonitor 17 HDMI-A-1"]));
+
+        reconcile(&ipc, &config, true).expect("fix");
+        assert!(ipc.called(&["--batch", "dispatch moveworkspacetomonitor 17 HDMI-A-1"]));
+    }
+
+    #[test]
+    fn timer_fires_each_interval() {
+        let start = Instant::now();
+        let mut timer = ReconcileTimer::new(Duration::from_secs(30), start);
+
+        assert!(!timer.due(start + Duration::from_secs(10)));
+        assert!(timer.due(start + Duration::from_secs(30)));
+        assert!(!timer.due(start + Duration::from_secs(31)));
+        assert_eq!(timer.deadline(), Some(start + Duration::from_secs(60)));
+        let off = ReconcileTimer::from_config(&ReconcileConfig::default(), start);
+        assert_eq!(off.deadline(), None);
+        let report = ReconcileTimer::from_config(
+            &ReconcileConfig {
+                policy: ReconcilePolicy::Report,
+                interval_ms: 1_000,
+            },
+            start,
+        );
+        assert_eq!(report.deadline(), Some(start + Duration::from_secs(1)));
+    }
+}
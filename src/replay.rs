@@ -0,0 +1,307 @@
+//! Recording the raw socket2 stream and feeding it back through the daemon.
+
+use std::cell::RefCell;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Lines, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::daemon::{DaemonEvent, EventSource, parse_socket2_event};
+use crate::hyprctl::{
+    ClientInfo, Compositor, HyprctlError, HyprlandIpc, MonitorInfo, OptionInfo, WorkspaceInfo,
+};
+
+pub struct Recorder {
+    file: File,
+}
+
+impl Recorder {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file })
+    }
+
+    pub fn record(&mut self, line: &str) -> io::Result<()> {
+        self.record_at(SystemTime::now(), line)
+    }
+
+    fn record_at(&mut self, at: SystemTime, line: &str) -> io::Result<()> {
+        let millis = at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        writeln!(self.file, "{millis}\t{line}")
+    }
+}
+
+/// Records `line` if recording is on; stops recording on the first write error.
+pub fn record_line(recorder: &mut Option<Recorder>, line: &str) {
+    if let Some(active) = recorder
+        && let Err(err) = active.record(line)
+    {
+        eprintln!("warning: event recording stopped: {err}");
+        *recorder = None;
+    }
+}
+
+pub fn parse_record(record: &str) -> Option<(u64, &str)> {
+    let (millis, line) = record.split_once('\t')?;
+    Some((millis.parse().ok()?, line))
+}
+
+pub fn replay_delay(previous: u64, next: u64, speed: f64) -> Duration {
+    Duration::from_secs_f64(next.saturating_sub(previous) as f64 / 1000.0 / speed)
+}
+
+pub fn parse_speed(input: &str) -> Result<f64, String> {
+    match input.trim().parse::<f64>() {
+        Ok(speed) if speed.is_finite() && speed > 0.0 => Ok(speed),
+        _ => Err(format!(
+            "invalid speed '{input}' (expected a positive number)"
+        )),
+    }
+}
+
+/// Replays a recording as socket2 events, with long gaps as timeouts.
+pub struct ReplayEventSource<R> {
+    records: Lines<R>,
+    speed: f64,
+    timeout: Duration,
+    last: Option<u64>,
+    pending: Option<(Instant, String)>,
+    drained: bool,
+}
+
+impl ReplayEventSource<BufReader<File>> {
+    pub fn open(path: &Path, speed: f64, timeout: Duration) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?), speed, timeout))
+    }
+}
+
+impl<R: BufRead> ReplayEventSource<R> {
+    pub fn new(reader: R, speed: f64, timeout: Duration) -> Self {
+        Self {
+            records: reader.lines(),
+            speed,
+            timeout,
+            last: None,
+            pending: None,
+            drained: false,
+        }
+    }
+
+    fn next_record(&mut self) -> io::Result<Option<(Instant, String)>> {
+        for record in self.records.by_ref() {
+            let record = record?;
+            let Some((millis, line)) = parse_record(record.trim_end()) else {
+                continue;
+            };
+            let delay = self.last.map_or(Duration::ZERO, |last| {
+                replay_delay(last, millis, self.speed)
+            });
+            self.last = Some(millis);
+            return Ok(Some((Instant::now() + delay, line.to_string())));
+        }
+        Ok(None)
+    }
+
+    fn idle(&self) -> DaemonEvent {
+        thread::sleep(self.timeout);
+        DaemonEvent::Timeout { at: Instant::now() }
+    }
+}
+
+impl<R: BufRead> EventSource for ReplayEventSource<R> {
+    fn next_event(&mut self) -> io::Result<DaemonEvent> {
+        loop {
+            if self.pending.is_none() {
+                self.pending = self.next_record()?;
+            }
+            let Some((due, line)) = self.pending.take() else {
+                if self.drained {
+                    return Ok(DaemonEvent::Disconnected);
+                }
+                self.drained = true;
+                return Ok(self.idle());
+            };
+            let now = Instant::now();
+            if due > now + self.timeout {
+                self.pending = Some((due, line));
+                return Ok(self.idle());
+            }
+            thread::sleep(due.saturating_duration_since(now));
+            if let Some(event) = parse_socket2_event(&line, Instant::now()) {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+/// Answers queries from `inner` but prints mutations to `out`.
+pub struct DryRunIpc<'a, W> {
+    inner: &'a dyn HyprlandIpc,
+    out: RefCell<W>,
+}
+
+impl<'a, W: Write> DryRunIpc<'a, W> {
+    pub fn new(inner: &'a dyn HyprlandIpc, out: W) -> Self {
+        Self {
+            inner,
+            out: RefCell::new(out),
+        }
+    }
+
+    pub fn into_output(self) -> W {
+        self.out.into_inner()
+    }
+
+    fn print(&self, command: String) -> Result<String, HyprctlError> {
+        writeln!(self.out.borrow_mut(), "dry-run: {command}")?;
+        Ok("ok".to_string())
+    }
+}
+
+impl<W: Write> HyprlandIpc for DryRunIpc<'_, W> {
+    fn batch(&self, batch: &str) -> Result<String, HyprctlError> {
+        self.print(format!("batch {batch}"))
+    }
+
+    fn active_workspace_id(&self) -> Result<u32, HyprctlError> {
+        self.inner.active_workspace_id()
+    }
+
+    fn dispatch(&self, dispatcher: &str, argument: &str) -> Result<String, HyprctlError> {
+        self.print(format!("dispatch {dispatcher} {argument}"))
+    }
+
+    fn reload(&self) -> Result<String, HyprctlError> {
+        self.print("reload".to_string())
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, HyprctlError> {
+        self.inner.monitors()
+    }
+
+    fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, HyprctlError> {
+        self.inner.workspaces()
+    }
+
+    fn clients(&self) -> Result<Vec<ClientInfo>, HyprctlError> {
+        self.inner.clients()
+    }
+
+    fn get_option(&self, name: &str) -> Result<OptionInfo, HyprctlError> {
+        self.inner.get_option(name)
+    }
+
+    fn keyword(&self, name: &str, value: &str) -> Result<String, HyprctlError> {
+        self.print(format!("keyword {name} {value}"))
+    }
+
+    fn compositor(&self) -> Compositor {
+        self.inner.compositor()
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        self.inner.set_timeout(timeout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ScriptedIpc;
+    use std::io::Cursor;
+
+    #[test]
+    fn records_lines_with_unix_millis() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("events.log");
+        let mut recorder = Recorder::open(&path).expect("open");
+        recorder
+            .record_at(UNIX_EPOCH + Duration::from_millis(1500), "workspace>>3")
+            .expect("record");
+        drop(recorder);
+        let mut recorder = Recorder::open(&path).expect("reopen");
+        recorder
+            .record_at(
+                UNIX_EPOCH + Duration::from_millis(2000),
+                "focusedmon>>DP-1,3",
+            )
+            .expect("record");
+
+        let contents = std::fs::read_to_string(&path).expect("read");
+        assert_eq!(contents, "1500\tworkspace>>3\n2000\tfocusedmon>>DP-1,3\n");
+        assert_eq!(
+            parse_record("1500\tworkspace>>3"),
+            Some((1500, "workspace>>3"))
+        );
+        assert_eq!(parse_record("workspace>>3"), None);
+    }
+
+    #[test]
+    fn scales_gaps_by_speed() {
+        assert_eq!(replay_delay(1000, 3000, 1.0), Duration::from_secs(2));
+        assert_eq!(replay_delay(1000, 3000, 4.0), Duration::from_millis(500));
+        assert_eq!(replay_delay(3000, 1000, 1.0), Duration::ZERO);
+        assert!(parse_speed("0").is_err());
+        assert!(parse_speed("-2").is_err());
+        assert_eq!(parse_speed("2.5"), Ok(2.5));
+    }
+
+    #[test]
+    fn replays_recorded_events_then_disconnects() {
+        let recording = "1000\tworkspace>>3\nnot a record\n1002\tbogus>>x\n1004\tworkspace>>4\n";
+        let mut source =
+            ReplayEventSource::new(Cursor::new(recording), 1.0, Duration::from_millis(50));
+
+        let mut kinds = Vec::new();
+        loop {
+            let event = source.next_event().expect("event");
+            let done = matches!(event, DaemonEvent::Disconnected);
+            kinds.push(match event {
+                DaemonEvent::Focus(focus) => format!("focus {:?}", focus.workspace_id),
+                other => other.kind().to_string(),
+            });
+            if done {
+                break;
+            }
+        }
+
+        assert_eq!(
+            kinds,
+            vec!["focus Some(3)", "focus Some(4)", "timeout", "disconnected"]
+        );
+    }
+
+    #[test]
+    fn long_gaps_surface_as_timeouts() {
+        let recording = "0\tworkspace>>3\n60000\tworkspace>>4\n";
+        let mut source =
+            ReplayEventSource::new(Cursor::new(recording), 1000.0, Duration::from_millis(20));
+
+        assert!(matches!(source.next_event(), Ok(DaemonEvent::Focus(_))));
+        assert!(matches!(
+            source.next_event(),
+            Ok(DaemonEvent::Timeout { .. })
+        ));
+    }
+
+    #[test]
+    fn dry_run_prints_mutations_and_forwards_queries() {
+        let inner = ScriptedIpc::new().with_json("activeworkspace", r#"{"id":7}"#);
+        let ipc = DryRunIpc::new(&inner, Vec::new());
+
+        assert_eq!(ipc.active_workspace_id().expect("query"), 7);
+        ipc.batch("dispatch workspace 3").expect("batch");
+        ipc.keyword("animations:enabled", "0").expect("keyword");
+
+        let output = String::from_utf8(ipc.into_output()).expect("utf8");
+        assert_eq!(
+            output,
+            "dry-run: batch dispatch workspace 3\ndry-run: keyword animations:enabled 0\n"
+        );
+        assert_eq!(inner.calls().len(), 1);
+    }
+}
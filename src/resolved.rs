@@ -0,0 +1,144 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, MonitorMatch};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ResolvedError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid resolved monitor state: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Resolved {
+    selector: MonitorMatch,
+    name: String,
+}
+
+/// Connector names the daemon last resolved monitor matches to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedMonitors {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    primary: Option<Resolved>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secondary: Option<Resolved>,
+}
+
+impl ResolvedMonitors {
+    /// The resolved matches of `config`.
+    pub fn of(config: &Config) -> Self {
+        let resolved = |selector: &Option<MonitorMatch>, name: &str| {
+            selector
+                .as_ref()
+                .filter(|_| !name.is_empty())
+                .map(|selector| Resolved {
+                    selector: selector.clone(),
+                    name: name.to_string(),
+                })
+        };
+        Self {
+            primary: resolved(&config.primary_match, &config.primary_monitor),
+            secondary: resolved(&config.secondary_match, &config.secondary_monitor),
+        }
+    }
+
+    /// Fills in names for matches that are still unresolved.
+    pub fn apply(&self, config: &mut Config) {
+        for (entry, selector, name) in [
+            (
+                &self.primary,
+                &config.primary_match,
+                &mut config.primary_monitor,
+            ),
+            (
+                &self.secondary,
+                &config.secondary_match,
+                &mut config.secondary_monitor,
+            ),
+        ] {
+            if let Some(entry) = entry
+                && name.is_empty()
+                && selector.as_ref() == Some(&entry.selector)
+            {
+                name.clone_from(&entry.name);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.primary.is_none() && self.secondary.is_none()
+    }
+}
+
+pub fn state_path(base_dir: &Path) -> PathBuf {
+    base_dir.join("resolved-monitors.json")
+}
+
+pub fn load(path: &Path) -> Result<ResolvedMonitors, ResolvedError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(serde_json::from_str(&contents)?),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(ResolvedMonitors::default()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Persists `resolved` if it changed; returns whether it did.
+pub fn store(path: &Path, resolved: &ResolvedMonitors) -> Result<bool, ResolvedError> {
+    if resolved.is_empty() || load(path).is_ok_and(|stored| stored == *resolved) {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(resolved)?)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResolvedMonitors, load, store};
+    use crate::config::{Config, MonitorMatch};
+
+    fn matched_config() -> Config {
+        Config::from_json(
+            r#"{"primary_monitor":{"description":"LG UltraGear"},"secondary_monitor":"HDMI-A-1"}"#,
+        )
+        .expect("config")
+    }
+
+    #[test]
+    fn stores_and_applies_resolved_names() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("resolved-monitors.json");
+        let mut resolved = matched_config();
+        resolved.primary_monitor = "DP-2".to_string();
+
+        assert!(store(&path, &ResolvedMonitors::of(&resolved)).expect("store"));
+        assert!(!store(&path, &ResolvedMonitors::of(&resolved)).expect("unchanged"));
+
+        let mut config = matched_config();
+        assert_eq!(config.primary_monitor, "");
+        load(&path).expect("load").apply(&mut config);
+        assert_eq!(config.primary_monitor, "DP-2");
+        assert_eq!(config.secondary_monitor, "HDMI-A-1");
+    }
+
+    #[test]
+    fn ignores_entries_for_other_matches() {
+        let mut resolved = matched_config();
+        resolved.primary_monitor = "DP-2".to_string();
+        let stored = ResolvedMonitors::of(&resolved);
+
+        let mut config = matched_config();
+        config.primary_match = Some(MonitorMatch::Serial("XYZ123".to_string()));
+        stored.apply(&mut config);
+
+        assert_eq!(config.primary_monitor, "");
+        assert!(ResolvedMonitors::of(&matched_config()).is_empty());
+    }
+}
@@ -0,0 +1,292 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::RetryConfig;
+use crate::hyprctl::{
+    ClientInfo, Compositor, HyprctlError, HyprlandIpc, MonitorInfo, OptionInfo, WorkspaceInfo,
+};
+
+const MAX_BACKOFF_SHIFT: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &RetryConfig) -> Self {
+        Self {
+            attempts: config.attempts.max(1),
+            backoff: Duration::from_millis(config.backoff_ms),
+        }
+    }
+
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff
+            .saturating_mul(1 << retry.saturating_sub(1).min(MAX_BACKOFF_SHIFT))
+    }
+
+    pub fn run<T>(&self, op: impl FnMut() -> Result<T, HyprctlError>) -> Result<T, HyprctlError> {
+        self.run_with(op, thread::sleep)
+    }
+
+    fn run_with<T>(
+        &self,
+        mut op: impl FnMut() -> Result<T, HyprctlError>,
+        mut sleep: impl FnMut(Duration),
+    ) -> Result<T, HyprctlError> {
+        let mut retry = 0;
+        loop {
+            match op() {
+                Err(err) if err.is_transient() && retry + 1 < self.attempts => {
+                    retry += 1;
+                    sleep(self.delay(retry));
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::from_config(&RetryConfig::default())
+    }
+}
+
+pub struct RetryingIpc<'a> {
+    inner: &'a dyn HyprlandIpc,
+    policy: RetryPolicy,
+}
+
+impl<'a> RetryingIpc<'a> {
+    pub fn new(inner: &'a dyn HyprlandIpc, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl HyprlandIpc for RetryingIpc<'_> {
+    fn batch(&self, batch: &str) -> Result<String, HyprctlError> {
+        self.policy.run(|| self.inner.batch(batch))
+    }
+
+    fn active_workspace_id(&self) -> Result<u32, HyprctlError> {
+        self.policy.run(|| self.inner.active_workspace_id())
+    }
+
+    fn dispatch(&self, dispatcher: &str, argument: &str) -> Result<String, HyprctlError> {
+        self.policy
+            .run(|| self.inner.dispatch(dispatcher, argument))
+    }
+
+    fn reload(&self) -> Result<String, HyprctlError> {
+        self.policy.run(|| self.inner.reload())
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, HyprctlError> {
+        self.policy.run(|| self.inner.monitors())
+    }
+
+    fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, HyprctlError> {
+        self.policy.run(|| self.inner.workspaces())
+    }
+
+    fn clients(&self) -> Result<Vec<ClientInfo>, HyprctlError> {
+        self.policy.run(|| self.inner.clients())
+    }
+
+    fn get_option(&self, name: &str) -> Result<OptionInfo, HyprctlError> {
+        self.policy.run(|| self.inner.get_option(name))
+    }
+
+    fn keyword(&self, name: &str, value: &str) -> Result<String, HyprctlError> {
+        self.policy.run(|| self.inner.keyword(name, value))
+    }
+
+    fn compositor(&self) -> Compositor {
+        self.inner.compositor()
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        self.inner.set_timeout(timeout);
+    }
+}
+
+/// Opens after `threshold` consecutive failures and probes again after `cooldown`.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            failures: 0,
+            open_until: None,
+        }
+    }
+
+    pub fn from_config(config: &RetryConfig) -> Self {
+        Self::new(
+            config.failure_threshold,
+            Duration::from_millis(config.cooldown_ms),
+        )
+    }
+
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    pub fn allow(&self, now: Instant) -> bool {
+        self.open_until.is_none_or(|until| now >= until)
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.threshold > 0 && self.failures >= self.threshold
+    }
+
+    /// Returns true when this success closes a tripped breaker.
+    pub fn record_success(&mut self) -> bool {
+        let recovered = self.is_tripped();
+        self.failures = 0;
+        self.open_until = None;
+        recovered
+    }
+
+    /// Returns true when this failure trips the breaker.
+    pub fn record_failure(&mut self, now: Instant) -> bool {
+        self.failures = self.failures.saturating_add(1);
+        if !self.is_tripped() {
+            return false;
+        }
+        self.open_until = Some(now + self.cooldown);
+        self.failures == self.threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CircuitBreaker, RetryPolicy, RetryingIpc};
+    use crate::config::RetryConfig;
+    use crate::hyprctl::{HyprctlError, HyprlandIpc};
+    use crate::testing::ScriptedIpc;
+    use std::io;
+    use std::time::{Duration, Instant};
+
+    fn transient() -> HyprctlError {
+        HyprctlError::Io(io::Error::new(io::ErrorKind::ConnectionRefused, "down"))
+    }
+
+    fn policy(attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            attempts,
+            backoff: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn retries_transient_failures() {
+        let ipc = ScriptedIpc::new();
+        ipc.fail("batch", transient());
+        ipc.fail("batch", transient());
+        let retrying = RetryingIpc::new(&ipc, policy(3));
+
+        assert_eq!(retrying.batch("dispatch workspace 1").expect("batch"), "ok");
+        assert_eq!(ipc.calls().len(), 3);
+    }
+
+    #[test]
+    fn gives_up_after_attempts() {
+        let ipc = ScriptedIpc::new();
+        for _ in 0..5 {
+            ipc.fail("monitors", transient());
+        }
+        let retrying = RetryingIpc::new(&ipc, policy(2));
+
+        assert!(matches!(retrying.monitors(), Err(HyprctlError::Io(_))));
+        assert_eq!(ipc.calls().len(), 2);
+    }
+
+    #[test]
+    fn does_not_retry_permanent_failures() {
+        let mut calls = 0;
+
+        let result: Result<(), _> = policy(3).run(|| {
+            calls += 1;
+            Err(HyprctlError::Unsupported("keyword".to_string()))
+        });
+
+        assert!(matches!(result, Err(HyprctlError::Unsupported(_))));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        let policy = RetryPolicy::from_config(&RetryConfig {
+            attempts: 4,
+            backoff_ms: 10,
+            ..RetryConfig::default()
+        });
+        let mut delays = Vec::new();
+
+        let result: Result<(), _> =
+            policy.run_with(|| Err(transient()), |delay| delays.push(delay));
+
+        assert!(result.is_err());
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(10),
+                Duration::from_millis(20),
+                Duration::from_millis(40)
+            ]
+        );
+    }
+
+    #[test]
+    fn treats_zero_attempts_as_one() {
+        let policy = RetryPolicy::from_config(&RetryConfig {
+            attempts: 0,
+            ..RetryConfig::default()
+        });
+
+        assert_eq!(policy.attempts, 1);
+    }
+
+    #[test]
+    fn breaker_trips_after_threshold_and_reopens_after_cooldown() {
+        let mut breaker = CircuitBreaker::new(2, Duration::from_secs(5));
+        let start = Instant::now();
+
+        assert!(!breaker.record_failure(start));
+        assert!(breaker.allow(start));
+        assert!(breaker.record_failure(start));
+        assert!(!breaker.allow(start + Duration::from_secs(1)));
+        assert!(breaker.allow(start + Duration::from_secs(5)));
+
+        let probe = start + Duration::from_secs(5);
+        assert!(!breaker.record_failure(probe));
+        assert!(!breaker.allow(probe + Duration::from_secs(1)));
+
+        assert!(breaker.record_success());
+        assert!(breaker.allow(probe));
+        assert!(!breaker.record_success());
+    }
+
+    #[test]
+    fn zero_threshold_disables_breaker() {
+        let mut breaker = CircuitBreaker::new(0, Duration::from_secs(5));
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            assert!(!breaker.record_failure(now));
+        }
+        assert!(breaker.allow(now));
+    }
+}
@@ -0,0 +1,373 @@
+use std::future;
+use std::io;
+use std::os::fd::RawFd;
+use std::path::{Path, PathBuf};
+use std::task::Poll;
+use std::time::{Duration, Instant, SystemTime};
+
+use tokio::io::unix::AsyncFd;
+use tokio::io::{AsyncBufReadExt, BufReader, Lines};
+use tokio::net::UnixStream;
+use tokio::sync::mpsc;
+use tokio::time::{self, Interval, MissedTickBehavior};
+
+use crate::control::{ControlListener, ControlRequest};
+use crate::daemon::{DaemonEvent, EventSource, parse_socket2_event};
+use crate::replay::{Recorder, record_line};
+
+pub const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub enum LoopEvent {
+    Daemon(DaemonEvent),
+    Control(ControlRequest),
+    ConfigChanged,
+    Shutdown,
+}
+
+pub enum AsyncEventSource {
+    Socket2 {
+        lines: Lines<BufReader<UnixStream>>,
+        recorder: Option<Recorder>,
+    },
+    Bridged(mpsc::UnboundedReceiver<DaemonEvent>),
+}
+
+impl AsyncEventSource {
+    pub async fn socket2(path: &Path) -> io::Result<Self> {
+        let stream = UnixStream::connect(path).await?;
+        Ok(Self::from_stream(stream))
+    }
+
+    pub fn from_stream(stream: UnixStream) -> Self {
+        Self::Socket2 {
+            lines: BufReader::new(stream).lines(),
+            recorder: None,
+        }
+    }
+
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        if let Self::Socket2 { recorder: slot, .. } = &mut self {
+            *slot = Some(recorder);
+        }
+        self
+    }
+
+    pub fn bridged(mut source: Box<dyn EventSource + Send>) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            loop {
+                match source.next_event() {
+                    Ok(DaemonEvent::Timeout { .. }) if sender.is_closed() => return,
+                    Ok(DaemonEvent::Timeout { .. }) => continue,
+                    Ok(DaemonEvent::Disconnected) | Err(_) => {
+                        let _ = sender.send(DaemonEvent::Disconnected);
+                        return;
+                    }
+                    Ok(event) => {
+                        if sender.send(event).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+        Self::Bridged(receiver)
+    }
+
+    pub async fn next_event(&mut self) -> io::Result<DaemonEvent> {
+        match self {
+            Self::Socket2 { lines, recorder } => loop {
+                let Some(line) = lines.next_line().await? else {
+                    return Ok(DaemonEvent::Disconnected);
+                };
+                let line = line.trim_end();
+                if !line.is_empty() {
+                    record_line(recorder, line);
+                }
+                if let Some(event) = parse_socket2_event(line, Instant::now()) {
+                    return Ok(event);
+                }
+            },
+            Self::Bridged(receiver) => {
+                Ok(receiver.recv().await.unwrap_or(DaemonEvent::Disconnected))
+            }
+        }
+    }
+}
+
+pub struct AsyncControl<'a> {
+    listener: &'a ControlListener,
+    fds: Vec<AsyncFd<RawFd>>,
+}
+
+impl<'a> AsyncControl<'a> {
+    pub fn new(listener: &'a ControlListener) -> io::Result<Self> {
+        Ok(Self {
+            listener,
+            fds: listener
+                .raw_fds()
+                .map(AsyncFd::new)
+                .collect::<io::Result<_>>()?,
+        })
+    }
+
+    pub async fn accept(&self) -> io::Result<ControlRequest> {
+        loop {
+            if let Some(request) = self.listener.poll()? {
+                return Ok(request);
+            }
+            let pending = self
+                .listener
+                .pending_fds()
+                .into_iter()
+                .map(AsyncFd::new)
+                .collect::<io::Result<Vec<_>>>()?;
+            future::poll_fn(|cx| {
+                for fd in self.fds.iter().chain(&pending) {
+                    if let Poll::Ready(guard) = fd.poll_read_ready(cx) {
+                        guard?.clear_ready();
+                        return Poll::Ready(Ok::<_, io::Error>(()));
+                    }
+                }
+                Poll::Pending
+            })
+            .await?;
+        }
+    }
+}
+
+/// Wakes once [`crate::shutdown`] sees SIGTERM or SIGINT.
+pub struct ShutdownWatch {
+    stream: UnixStream,
+}
+
+impl ShutdownWatch {
+    pub fn new(stream: std::os::unix::net::UnixStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        Ok(Self {
+            stream: UnixStream::from_std(stream)?,
+        })
+    }
+
+    pub async fn requested(&self) -> io::Result<()> {
+        loop {
+            self.stream.readable().await?;
+            match self.stream.try_read(&mut [0; 1]) {
+                Ok(_) => return Ok(()),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    interval: Interval,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: &Path, poll_interval: Duration) -> Self {
+        let mut interval = time::interval(poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            path: path.to_path_buf(),
+            modified: modified_time(path),
+            interval,
+        }
+    }
+
+    pub async fn changed(&mut self) {
+        loop {
+            self.interval.tick().await;
+            let modified = modified_time(&self.path);
+            if modified != self.modified {
+                self.modified = modified;
+                return;
+            }
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+pub async fn next_loop_event(
+    source: &mut AsyncEventSource,
+    control: &AsyncControl<'_>,
+    shutdown: &ShutdownWatch,
+    watcher: &mut ConfigWatcher,
+    flush_at: Option<Instant>,
+) -> io::Result<LoopEvent> {
+    let flush = async {
+        match flush_at {
+            Some(at) => time::sleep_until(time::Instant::from_std(at)).await,
+            None => future::pending().await,
+        }
+    };
+    tokio::select! {
+        biased;
+        result = shutdown.requested() => result.map(|()| LoopEvent::Shutdown),
+        request = control.accept() => Ok(LoopEvent::Control(request?)),
+        () = flush => Ok(LoopEvent::Daemon(DaemonEvent::Timeout { at: Instant::now() })),
+        event = source.next_event() => Ok(LoopEvent::Daemon(event?)),
+        () = watcher.changed() => Ok(LoopEvent::ConfigChanged),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AsyncControl, AsyncEventSource, ConfigWatcher, LoopEvent, ShutdownWatch, next_loop_event,
+    };
+    use crate::control::{ControlCommand, ControlListener, control_socket_path, send_command};
+    use crate::daemon::{DaemonEvent, MonitorEventKind};
+    use std::fs;
+    use std::time::{Duration, Instant};
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::UnixStream;
+
+    struct Fixture {
+        dir: tempfile::TempDir,
+        listener: ControlListener,
+        config_path: std::path::PathBuf,
+        wake: std::os::unix::net::UnixStream,
+        shutdown: ShutdownWatch,
+    }
+
+    fn fixture() -> Fixture {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let listener = ControlListener::bind(&control_socket_path(dir.path())).expect("bind");
+        let config_path = dir.path().join("paired.json");
+        fs::write(&config_path, "{}").expect("config");
+        let (wake, reader) = std::os::unix::net::UnixStream::pair().expect("wake pair");
+        Fixture {
+            dir,
+            listener,
+            config_path,
+            wake,
+            shutdown: ShutdownWatch::new(reader).expect("shutdown watch"),
+        }
+    }
+
+    #[tokio::test]
+    async fn yields_socket2_events_and_disconnect() {
+        let fixture = fixture();
+        let control = AsyncControl::new(&fixture.listener).expect("control");
+        let mut watcher = ConfigWatcher::new(&fixture.config_path, Duration::from_secs(60));
+        let (mut writer, reader) = UnixStream::pair().expect("pair");
+        let mut source = AsyncEventSource::from_stream(reader);
+        writer
+            .write_all(b"activelayout>>kbd,us\nmonitoradded>>DP-2\n")
+            .await
+            .expect("write");
+        drop(writer);
+
+        let event = next_loop_event(&mut source, &control, &fixture.shutdown, &mut watcher, None)
+            .await
+            .expect("event");
+        assert!(matches!(
+            event,
+            LoopEvent::Daemon(DaemonEvent::Monitor {
+                kind: MonitorEventKind::Added,
+                ..
+            })
+        ));
+        let event = next_loop_event(&mut source, &control, &fixture.shutdown, &mut watcher, None)
+            .await
+            .expect("event");
+        assert!(matches!(
+            event,
+            LoopEvent::Daemon(DaemonEvent::Disconnected)
+        ));
+    }
+
+    #[tokio::test]
+    async fn fires_flush_timer_at_deadline() {
+        let fixture = fixture();
+        let control = AsyncControl::new(&fixture.listener).expect("control");
+        let mut watcher = ConfigWatcher::new(&fixture.config_path, Duration::from_secs(60));
+        let (_writer, reader) = UnixStream::pair().expect("pair");
+        let mut source = AsyncEventSource::from_stream(reader);
+        let deadline = Instant::now() + Duration::from_millis(30);
+
+        let event = next_loop_event(
+            &mut source,
+            &control,
+            &fixture.shutdown,
+            &mut watcher,
+            Some(deadline),
+        )
+        .await
+        .expect("event");
+
+        assert!(matches!(
+            event,
+            LoopEvent::Daemon(DaemonEvent::Timeout { .. })
+        ));
+        assert!(Instant::now() >= deadline);
+    }
+
+    #[tokio::test]
+    async fn accepts_control_requests() {
+        let fixture = fixture();
+        let control = AsyncControl::new(&fixture.listener).expect("control");
+        let mut watcher = ConfigWatcher::new(&fixture.config_path, Duration::from_secs(60));
+        let (_writer, reader) = UnixStream::pair().expect("pair");
+        let mut source = AsyncEventSource::from_stream(reader);
+        let path = control_socket_path(fixture.dir.path());
+        let client =
+            std::thread::spawn(move || send_command(&path, ControlCommand::Reload).expect("send"));
+
+        let event = next_loop_event(&mut source, &control, &fixture.shutdown, &mut watcher, None)
+            .await
+            .expect("event");
+        let LoopEvent::Control(request) = event else {
+            panic!("expected control request");
+        };
+        assert_eq!(request.command, ControlCommand::Reload);
+        request.respond("ok").expect("respond");
+
+        assert_eq!(client.join().expect("join"), "ok");
+    }
+
+    #[tokio::test]
+    async fn reports_config_changes() {
+        let fixture = fixture();
+        let control = AsyncControl::new(&fixture.listener).expect("control");
+        let mut watcher = ConfigWatcher::new(&fixture.config_path, Duration::from_millis(10));
+        let (_writer, reader) = UnixStream::pair().expect("pair");
+        let mut source = AsyncEventSource::from_stream(reader);
+        fs::remove_file(&fixture.config_path).expect("remove");
+
+        let event = next_loop_event(&mut source, &control, &fixture.shutdown, &mut watcher, None)
+            .await
+            .expect("event");
+
+        assert!(matches!(event, LoopEvent::ConfigChanged));
+    }
+
+    #[tokio::test]
+    async fn wakes_on_shutdown_before_other_events() {
+        let mut fixture = fixture();
+        let control = AsyncControl::new(&fixture.listener).expect("control");
+        let mut watcher = ConfigWatcher::new(&fixture.config_path, Duration::from_secs(60));
+        let (mut writer, reader) = UnixStream::pair().expect("pair");
+        let mut source = AsyncEventSource::from_stream(reader);
+        writer
+            .write_all(b"monitoradded>>DP-2\n")
+            .await
+            .expect("write");
+        std::io::Write::write_all(&mut fixture.wake, &[1]).expect("wake");
+
+        let event = next_loop_event(&mut source, &control, &fixture.shutdown, &mut watcher, None)
+            .await
+            .expect("event");
+
+        assert!(matches!(event, LoopEvent::Shutdown));
+    }
+}
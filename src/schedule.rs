@@ -0,0 +1,439 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+const WEEKDAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+const WEEKDAYS: u8 = 0b0011111;
+const WEEKENDS: u8 = 0b1100000;
+const DAILY: u8 = WEEKDAYS | WEEKENDS;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ScheduleError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid schedule state: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("no schedule named '{0}'")]
+    Unknown(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl TimeOfDay {
+    fn minute_of_day(self) -> u16 {
+        u16::from(self.hour) * 60 + u16::from(self.minute)
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("invalid time '{value}' (expected HH:MM)");
+        let (hour, minute) = value.split_once(':').ok_or_else(invalid)?;
+        let hour = hour.parse::<u8>().map_err(|_| invalid())?;
+        let minute = minute.parse::<u8>().map_err(|_| invalid())?;
+        if hour > 23 || minute > 59 {
+            return Err(invalid());
+        }
+        Ok(Self { hour, minute })
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(time: TimeOfDay) -> Self {
+        time.to_string()
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "RawDays", into = "RawDays")]
+pub struct Days(u8);
+
+impl Days {
+    pub fn contains(self, weekday: u8) -> bool {
+        self.0 & (1 << weekday) != 0
+    }
+}
+
+impl Default for Days {
+    fn default() -> Self {
+        Days(DAILY)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum RawDays {
+    Preset(String),
+    List(Vec<String>),
+}
+
+impl TryFrom<RawDays> for Days {
+    type Error = String;
+
+    fn try_from(raw: RawDays) -> Result<Self, Self::Error> {
+        let names = match raw {
+            RawDays::Preset(preset) => match preset.as_str() {
+                "daily" => return Ok(Days(DAILY)),
+                "weekdays" => return Ok(Days(WEEKDAYS)),
+                "weekends" => return Ok(Days(WEEKENDS)),
+                _ => vec![preset],
+            },
+            RawDays::List(names) => names,
+        };
+        let mut mask = 0;
+        for name in names {
+            let index = WEEKDAY_NAMES
+                .iter()
+                .position(|day| name.eq_ignore_ascii_case(day))
+                .ok_or_else(|| format!("unknown day '{name}'"))?;
+            mask |= 1 << index;
+        }
+        Ok(Days(mask))
+    }
+}
+
+impl From<Days> for RawDays {
+    fn from(days: Days) -> Self {
+        match days.0 {
+            DAILY | WEEKDAYS | WEEKENDS => RawDays::Preset(days.to_string()),
+            mask => RawDays::List(
+                WEEKDAY_NAMES
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| mask & (1 << index) != 0)
+                    .map(|(_, name)| name.to_string())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Days {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            DAILY => write!(f, "daily"),
+            WEEKDAYS => write!(f, "weekdays"),
+            WEEKENDS => write!(f, "weekends"),
+            mask => {
+                let names = WEEKDAY_NAMES
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| mask & (1 << index) != 0)
+                    .map(|(_, name)| *name)
+                    .collect::<Vec<_>>();
+                write!(f, "{}", names.join(","))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Schedule {
+    pub name: String,
+    pub at: TimeOfDay,
+    pub slot: u32,
+    #[serde(default)]
+    pub days: Days,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    pub day: i64,
+    pub weekday: u8,
+    pub minute_of_day: u16,
+}
+
+impl LocalTime {
+    pub fn from_epoch(seconds: i64, utc_offset: i64) -> Self {
+        let local = seconds + utc_offset;
+        let day = local.div_euclid(SECONDS_PER_DAY);
+        Self {
+            day,
+            weekday: (day + 3).rem_euclid(7) as u8,
+            minute_of_day: (local.rem_euclid(SECONDS_PER_DAY) / 60) as u16,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LocalClock {
+    offset: Option<(i64, i64)>,
+}
+
+impl LocalClock {
+    pub fn now(&mut self) -> LocalTime {
+        let seconds = epoch_seconds();
+        let hour = seconds.div_euclid(3_600);
+        let offset = match self.offset {
+            Some((cached_hour, offset)) if cached_hour == hour => offset,
+            _ => {
+                let offset = local_utc_offset().unwrap_or(0);
+                self.offset = Some((hour, offset));
+                offset
+            }
+        };
+        LocalTime::from_epoch(seconds, offset)
+    }
+}
+
+fn epoch_seconds() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn local_utc_offset() -> Option<i64> {
+    let output = Command::new("date").arg("+%z").output().ok()?;
+    parse_utc_offset(String::from_utf8_lossy(&output.stdout).trim())
+}
+
+fn parse_utc_offset(value: &str) -> Option<i64> {
+    let (sign, digits) = match value.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+    if digits.len() != 4 {
+        return None;
+    }
+    let hours = digits[..2].parse::<i64>().ok()?;
+    let minutes = digits[2..].parse::<i64>().ok()?;
+    Some(sign * (hours * 3_600 + minutes * 60))
+}
+
+pub fn next_minute(now: Instant) -> Instant {
+    let millis_into_minute = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() % 60_000)
+        .unwrap_or(0) as u64;
+    now + Duration::from_millis(60_000 - millis_into_minute)
+}
+
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    schedules: Vec<Schedule>,
+    disabled: BTreeSet<String>,
+    fired: HashMap<String, i64>,
+}
+
+impl Scheduler {
+    pub fn new(schedules: Vec<Schedule>, disabled: BTreeSet<String>) -> Self {
+        Self {
+            schedules,
+            disabled,
+            fired: HashMap::new(),
+        }
+    }
+
+    pub fn update(&mut self, schedules: Vec<Schedule>, disabled: BTreeSet<String>) {
+        self.schedules = schedules;
+        self.disabled = disabled;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.schedules
+            .iter()
+            .any(|schedule| !self.disabled.contains(&schedule.name))
+    }
+
+    pub fn due(&mut self, now: LocalTime) -> Vec<Schedule> {
+        let mut due = Vec::new();
+        for schedule in &self.schedules {
+            if self.disabled.contains(&schedule.name)
+                || !schedule.days.contains(now.weekday)
+                || schedule.at.minute_of_day() != now.minute_of_day
+                || self.fired.get(&schedule.name) == Some(&now.day)
+            {
+                continue;
+            }
+            self.fired.insert(schedule.name.clone(), now.day);
+            due.push(schedule.clone());
+        }
+        due
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScheduleState {
+    #[serde(default)]
+    disabled: BTreeSet<String>,
+}
+
+pub fn state_path(base_dir: &Path) -> PathBuf {
+    base_dir.join("schedules.json")
+}
+
+pub fn load_disabled(path: &Path) -> Result<BTreeSet<String>, ScheduleError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(serde_json::from_str::<ScheduleState>(&contents)?.disabled),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(BTreeSet::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn set_enabled(
+    path: &Path,
+    schedules: &[Schedule],
+    name: &str,
+    enabled: bool,
+) -> Result<(), ScheduleError> {
+    if !schedules.iter().any(|schedule| schedule.name == name) {
+        return Err(ScheduleError::Unknown(name.to_string()));
+    }
+    let mut disabled = load_disabled(path)?;
+    if enabled {
+        disabled.remove(name);
+    } else {
+        disabled.insert(name.to_string());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(
+        path,
+        serde_json::to_string_pretty(&ScheduleState { disabled })?,
+    )?;
+    Ok(())
+}
+
+pub fn format_schedule(schedule: &Schedule, disabled: &BTreeSet<String>) -> String {
+    let status = if disabled.contains(&schedule.name) {
+        "disabled"
+    } else {
+        "enabled"
+    };
+    format!(
+        "{} {} {} -> slot {} ({status})",
+        schedule.name, schedule.at, schedule.days, schedule.slot
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Days, LocalTime, Schedule, ScheduleError, Scheduler, format_schedule, load_disabled,
+        parse_utc_offset, set_enabled, state_path,
+    };
+    use std::collections::BTreeSet;
+
+    fn schedules() -> Vec<Schedule> {
+        serde_json::from_str(
+            r#"[
+                {"name":"work","at":"09:00","slot":1,"days":"weekdays"},
+                {"name":"chill","at":"18:30","slot":5,"days":["sat","SUN"]},
+                {"name":"mail","at":"08:15","slot":3}
+            ]"#,
+        )
+        .expect("schedules")
+    }
+
+    #[test]
+    fn parses_schedule_entries() {
+        let schedules = schedules();
+
+        assert_eq!(schedules[0].at.to_string(), "09:00");
+        assert_eq!(schedules[0].days.to_string(), "weekdays");
+        assert_eq!(schedules[1].days.to_string(), "weekends");
+        assert_eq!(schedules[2].days, Days::default());
+        assert!(serde_json::from_str::<Schedule>(r#"{"name":"x","at":"25:00","slot":1}"#).is_err());
+        assert!(
+            serde_json::from_str::<Schedule>(
+                r#"{"name":"x","at":"09:00","slot":1,"days":"someday"}"#
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn converts_epoch_to_local_time() {
+        let monday_nine_utc = 1_791_795_600;
+        let time = LocalTime::from_epoch(monday_nine_utc, 0);
+        assert_eq!((time.weekday, time.minute_of_day), (0, 540));
+
+        let time = LocalTime::from_epoch(monday_nine_utc, -10 * 3_600);
+        assert_eq!((time.weekday, time.minute_of_day), (6, 1_380));
+
+        assert_eq!(parse_utc_offset("+0530"), Some(19_800));
+        assert_eq!(parse_utc_offset("-0100"), Some(-3_600));
+        assert_eq!(parse_utc_offset("UTC"), None);
+    }
+
+    #[test]
+    fn fires_each_schedule_once_per_day() {
+        let mut scheduler = Scheduler::new(schedules(), BTreeSet::new());
+        let monday_nine = LocalTime {
+            day: 100,
+            weekday: 0,
+            minute_of_day: 540,
+        };
+
+        let due = scheduler.due(monday_nine);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].slot, 1);
+        assert!(scheduler.due(monday_nine).is_empty());
+
+        let saturday_nine = LocalTime {
+            day: 105,
+            weekday: 5,
+            minute_of_day: 540,
+        };
+        assert!(scheduler.due(saturday_nine).is_empty());
+    }
+
+    #[test]
+    fn skips_disabled_schedules() {
+        let disabled = BTreeSet::from(["mail".to_string()]);
+        let mut scheduler = Scheduler::new(schedules(), disabled);
+
+        let due = scheduler.due(LocalTime {
+            day: 1,
+            weekday: 2,
+            minute_of_day: 495,
+        });
+
+        assert!(due.is_empty());
+        assert!(scheduler.is_active());
+    }
+
+    #[test]
+    fn persists_enabled_state() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = state_path(dir.path());
+        let schedules = schedules();
+
+        set_enabled(&path, &schedules, "work", false).expect("disable");
+        let disabled = load_disabled(&path).expect("load");
+        assert!(disabled.contains("work"));
+        assert_eq!(
+            format_schedule(&schedules[0], &disabled),
+            "work 09:00 weekdays -> slot 1 (disabled)"
+        );
+
+        set_enabled(&path, &schedules, "work", true).expect("enable");
+        assert!(load_disabled(&path).expect("load").is_empty());
+        assert!(matches!(
+            set_enabled(&path, &schedules, "nope", true),
+            Err(ScheduleError::Unknown(name)) if name == "nope"
+        ));
+    }
+}
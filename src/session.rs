@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
@@ -7,11 +8,15 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::config::Config;
 use crate::hyprctl::{
-    ClientInfo, HyprctlBatch, HyprctlError, HyprlandIpc, MonitorInfo, WorkspaceInfo,
+    ClientInfo, Dispatcher, HyprctlBatch, HyprctlError, HyprlandIpc, MonitorInfo, WorkspaceInfo,
+    WorkspaceRef, WorkspaceTarget,
 };
-use crate::paired::normalize_workspace;
+use crate::session_index;
 
-const SESSION_VERSION: u32 = 1;
+pub const SESSION_VERSION: u32 = 2;
+
+/// Upgrades a snapshot from version `index + 1` to `index + 2`.
+const MIGRATIONS: [fn(&mut Value); SESSION_VERSION as usize - 1] = [migrate_v1_to_v2];
 
 #[derive(thiserror::Error, Debug)]
 pub enum SessionError {
@@ -21,6 +26,19 @@ pub enum SessionError {
     Json(#[from] serde_json::Error),
     #[error("hyprctl error")]
     Hyprctl(#[from] HyprctlError),
+    #[error(
+        "unsupported session snapshot version {found} (this build reads 1 to {SESSION_VERSION})"
+    )]
+    UnsupportedVersion { found: u64 },
+    #[error(
+        "snapshot monitors not connected: {}; use --allow-degraded to collapse onto the connected monitors",
+        .0.join(", ")
+    )]
+    MonitorMismatch(Vec<String>),
+    #[error("invalid session name {0:?}: use letters, digits, '.', '_' and '-'")]
+    InvalidName(String),
+    #[error("no saved session named {0:?}")]
+    UnknownName(String),
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -48,6 +66,11 @@ pub struct SnapshotFocus {
 pub struct SnapshotMonitor {
     pub id: i32,
     pub name: String,
+    /// Layout position and mode size; all zero when migrated from v1.
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -79,6 +102,29 @@ pub struct SnapshotClient {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub workspace_name: Option<String>,
     pub paired_slot: u32,
+    /// Set for floating windows; tiled windows are left to the layout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub floating: Option<WindowGeometry>,
+    /// Set for fullscreen or maximized windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fullscreen: Option<FullscreenState>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pseudo: bool,
+}
+
+/// Hyprland's `fullscreenstate` pair: internal and client state.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FullscreenState {
+    pub internal: u8,
+    pub client: u8,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -107,6 +153,10 @@ impl SessionSnapshot {
             .map(|monitor| SnapshotMonitor {
                 id: monitor.id,
                 name: monitor.name,
+                x: monitor.x,
+                y: monitor.y,
+                width: monitor.width,
+                height: monitor.height,
             })
             .collect();
 
@@ -126,8 +176,15 @@ impl SessionSnapshot {
                 let paired_slot = if is_special_workspace_name(client.workspace.name.as_deref()) {
                     client.workspace.id
                 } else {
-                    normalize_workspace(client.workspace.id, config.paired_offset)
+                    config.slot_of(client.workspace.id)
+                };
+                let floating = floating_geometry(&client);
+                let fullscreen_state = FullscreenState {
+                    internal: client.fullscreen,
+                    client: client.fullscreen_client,
                 };
+                let fullscreen =
+                    (fullscreen_state != FullscreenState::default()).then_some(fullscreen_state);
                 SnapshotClient {
                     address: client.address,
                     class: client.class,
@@ -139,6 +196,9 @@ impl SessionSnapshot {
                     workspace_id: client.workspace.id,
                     workspace_name: client.workspace.name,
                     paired_slot,
+                    floating,
+                    fullscreen,
+                    pseudo: client.pseudo,
                 }
             })
             .collect();
@@ -160,27 +220,90 @@ impl SessionSnapshot {
     }
 }
 
-pub fn session_path(base_dir: &Path, override_path: Option<&Path>) -> PathBuf {
+pub fn session_path(sessions_dir: &Path, override_path: Option<&Path>) -> PathBuf {
     override_path
         .map(Path::to_path_buf)
-        .unwrap_or_else(|| base_dir.join("sessions").join("latest.json"))
+        .unwrap_or_else(|| sessions_dir.join("latest.json"))
 }
 
-pub fn save_session(
+/// The daemon's exit snapshot, kept apart from `latest.json`.
+pub fn shutdown_path(sessions_dir: &Path) -> PathBuf {
+    sessions_dir.join("shutdown.json")
+}
+
+/// Marks that `daemon --restore-on-start` already ran for this instance.
+pub fn restore_marker_path(state_dir: &Path) -> PathBuf {
+    state_dir.join("restored")
+}
+
+/// The newest `shutdown.json` or `latest.json` across all instance directories.
+pub fn newest_snapshot(sessions_root: &Path) -> Option<PathBuf> {
+    let mut dirs = vec![sessions_root.to_path_buf()];
+    if let Ok(entries) = fs::read_dir(sessions_root) {
+        dirs.extend(
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir() && !path.ends_with(session_index::NAMED_DIR)),
+        );
+    }
+    dirs.iter()
+        .flat_map(|dir| [shutdown_path(dir), session_path(dir, None)])
+        .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// Cold-restores the newest snapshot once per Hyprland instance.
+pub fn restore_on_start(
     ipc: &dyn HyprlandIpc,
     config: &Config,
-    base_dir: &Path,
-    override_path: Option<&Path>,
-) -> Result<PathBuf, SessionError> {
-    let snapshot = SessionSnapshot::from_state(
+    sessions_root: &Path,
+    marker: &Path,
+) -> Result<Option<(PathBuf, Vec<PendingLaunch>)>, SessionError> {
+    if marker.exists() {
+        return Ok(None);
+    }
+    if let Some(parent) = marker.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(marker, "")?;
+    let Some(path) = newest_snapshot(sessions_root) else {
+        return Ok(None);
+    };
+    let launched = restore_session(
+        ipc,
+        config,
+        sessions_root,
+        Some(&path),
+        RestoreMode::Cold,
+        config.restore.allow_degraded,
+    )?;
+    Ok(Some((path, launched)))
+}
+
+pub fn capture_snapshot(
+    ipc: &dyn HyprlandIpc,
+    config: &Config,
+) -> Result<SessionSnapshot, HyprctlError> {
+    Ok(SessionSnapshot::from_state(
         config,
         current_signature(),
         ipc.active_workspace_id()?,
         ipc.monitors()?,
         ipc.workspaces()?,
         ipc.clients()?,
-    );
-    let path = session_path(base_dir, override_path);
+    ))
+}
+
+pub fn save_session(
+    ipc: &dyn HyprlandIpc,
+    config: &Config,
+    sessions_dir: &Path,
+    override_path: Option<&Path>,
+) -> Result<PathBuf, SessionError> {
+    let snapshot = capture_snapshot(ipc, config)?;
+    let path = session_path(sessions_dir, override_path);
 
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
@@ -192,26 +315,144 @@ pub fn save_session(
     Ok(path)
 }
 
+pub fn read_snapshot(path: &Path) -> Result<SessionSnapshot, SessionError> {
+    parse_snapshot(&fs::read_to_string(path)?)
+}
+
+/// Parses a snapshot of any known version, upgrading older ones in memory.
+pub fn parse_snapshot(contents: &str) -> Result<SessionSnapshot, SessionError> {
+    let mut value: Value = serde_json::from_str(contents)?;
+    let found = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if found == 0 || found > u64::from(SESSION_VERSION) {
+        return Err(SessionError::UnsupportedVersion { found });
+    }
+    for migrate in &MIGRATIONS[found as usize - 1..] {
+        migrate(&mut value);
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// v2 records each monitor's position and size; v1 files get zeros.
+fn migrate_v1_to_v2(value: &mut Value) {
+    if let Some(monitors) = value.get_mut("monitors").and_then(Value::as_array_mut) {
+        for monitor in monitors.iter_mut().filter_map(Value::as_object_mut) {
+            for field in ["x", "y", "width", "height"] {
+                monitor.entry(field).or_insert(Value::from(0));
+            }
+        }
+    }
+    value["version"] = Value::from(2);
+}
+
+/// Returns the applications launched for unmatched clients so the caller can place them.
 pub fn restore_session(
     ipc: &dyn HyprlandIpc,
     config: &Config,
-    base_dir: &Path,
+    sessions_dir: &Path,
     override_path: Option<&Path>,
     mode: RestoreMode,
-) -> Result<(), SessionError> {
-    let path = session_path(base_dir, override_path);
-    let contents = fs::read_to_string(&path)?;
-    let snapshot: SessionSnapshot = serde_json::from_str(&contents)?;
+    allow_degraded: bool,
+) -> Result<Vec<PendingLaunch>, SessionError> {
+    let mut snapshot = read_snapshot(&session_path(sessions_dir, override_path))?;
+    if !snapshot.monitors.is_empty() {
+        let missing = missing_monitors(&snapshot, &ipc.monitors()?);
+        if !missing.is_empty() {
+            if !allow_degraded {
+                return Err(SessionError::MonitorMismatch(missing));
+            }
+            collapse_to_primary(&mut snapshot, config);
+        }
+    }
     let current_clients = ipc.clients()?;
     let signature = current_signature();
-    let batch = restore_batch(&snapshot, mode, signature.as_deref(), &current_clients, config);
+    let batch = restore_batch(
+        &snapshot,
+        mode,
+        signature.as_deref(),
+        &current_clients,
+        config,
+    );
 
     let argument = batch.to_argument();
     if !argument.is_empty() {
         ipc.batch(&argument)?;
     }
 
-    Ok(())
+    let cold = resolve_restore_mode(mode, snapshot.signature.as_deref(), signature.as_deref())
+        == RestoreMode::Cold;
+    if cold && config.restore.launch_missing {
+        return Ok(launch_missing(ipc, &snapshot, &current_clients, config)?);
+    }
+    Ok(Vec::new())
+}
+
+/// Snapshot monitors that are not connected now.
+pub fn missing_monitors(snapshot: &SessionSnapshot, monitors: &[MonitorInfo]) -> Vec<String> {
+    snapshot
+        .monitors
+        .iter()
+        .filter(|saved| !monitors.iter().any(|monitor| monitor.name == saved.name))
+        .map(|saved| saved.name.clone())
+        .collect()
+}
+
+/// Moves every non-special window to its pair's primary workspace.
+pub fn collapse_to_primary(snapshot: &mut SessionSnapshot, config: &Config) {
+    for client in &mut snapshot.clients {
+        if is_special_workspace_name(client.workspace_name.as_deref()) {
+            continue;
+        }
+        let slot = config.slot_of(client.workspace_id);
+        client.workspace_id = config.slot_workspace(slot);
+        client.workspace_name = None;
+        client.paired_slot = slot;
+    }
+}
+
+/// An application launched for an unmatched snapshot client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingLaunch {
+    pub class: String,
+    pub workspace: String,
+}
+
+/// Runs `restore.launch_command` for each unmatched client that has an entry.
+pub fn launch_missing(
+    ipc: &dyn HyprlandIpc,
+    snapshot: &SessionSnapshot,
+    current_clients: &[ClientInfo],
+    config: &Config,
+) -> Result<Vec<PendingLaunch>, HyprctlError> {
+    let mut launched = Vec::new();
+    for (launch, argument) in planned_launches(snapshot, current_clients, config) {
+        ipc.dispatch("exec", &argument)?;
+        launched.push(launch);
+    }
+    Ok(launched)
+}
+
+/// The launches `launch_missing` would run, each with its `exec` argument.
+fn planned_launches(
+    snapshot: &SessionSnapshot,
+    current_clients: &[ClientInfo],
+    config: &Config,
+) -> Vec<(PendingLaunch, String)> {
+    unmatched_clients(snapshot, current_clients)
+        .into_iter()
+        .filter_map(|client| {
+            let (class, command) = [&client.app_id, &client.class, &client.initial_class]
+                .into_iter()
+                .flatten()
+                .find_map(|id| config.restore.launch_command.get_key_value(id.as_str()))?;
+            let workspace = workspace_target(client);
+            let argument = format!("[workspace {workspace} silent] {command}");
+            let launch = PendingLaunch {
+                class: class.clone(),
+                workspace,
+            };
+            Some((launch, argument))
+        })
+        .collect()
 }
 
 pub fn restore_batch(
@@ -230,24 +471,37 @@ pub fn restore_batch(
     }
 }
 
-fn restore_same_session(snapshot: &SessionSnapshot, current_clients: &[ClientInfo]) -> HyprctlBatch {
+fn restore_same_session(
+    snapshot: &SessionSnapshot,
+    current_clients: &[ClientInfo],
+) -> HyprctlBatch {
     let mut batch = HyprctlBatch::new();
     let mut current_by_address = HashMap::new();
 
     for client in current_clients {
-        current_by_address.insert(
-            client.address.as_str(),
-            (client.workspace.id, client.workspace.name.as_deref()),
-        );
+        current_by_address.insert(client.address.as_str(), client);
     }
 
+    let mut refocus = false;
     for client in &snapshot.clients {
-        if let Some((current_id, current_name)) = current_by_address.get(client.address.as_str())
-            && !snapshot_matches_current(client, *current_id, *current_name)
-        {
-            let argument = format!("{},address:{}", workspace_target(client), client.address);
-            batch.dispatch("movetoworkspacesilent", &argument);
+        let Some(current) = current_by_address.get(client.address.as_str()) else {
+            continue;
+        };
+        if !snapshot_matches_current(
+            client,
+            current.workspace.id,
+            current.workspace.name.as_deref(),
+        ) {
+            batch.push(Dispatcher::MoveToWorkspaceSilent {
+                target: restore_target(client),
+                address: &client.address,
+            });
         }
+        restore_floating(&mut batch, client, current);
+        refocus |= restore_window_modes(&mut batch, client, current);
+    }
+    if refocus {
+        restore_focus(&mut batch, current_clients);
     }
 
     batch
@@ -259,8 +513,370 @@ fn restore_cold_session(
     config: &Config,
 ) -> HyprctlBatch {
     let mut batch = HyprctlBatch::new();
-    let mut used_snapshot = HashSet::new();
     let mut matched_addresses = HashSet::new();
+    let mut refocus = false;
+
+    for (client, idx) in cold_matches(snapshot, current_clients) {
+        let snapshot_client = &snapshot.clients[idx];
+        if !snapshot_matches_current(
+            snapshot_client,
+            client.workspace.id,
+            client.workspace.name.as_deref(),
+        ) {
+            batch.push(Dispatcher::MoveToWorkspaceSilent {
+                target: restore_target(snapshot_client),
+                address: &client.address,
+            });
+        }
+        restore_floating(&mut batch, snapshot_client, client);
+        refocus |= restore_window_modes(&mut batch, snapshot_client, client);
+        matched_addresses.insert(client.address.as_str());
+    }
+
+    for client in current_clients {
+        if matched_addresses.contains(client.address.as_str()) {
+            continue;
+        }
+        if is_special_workspace_name(client.workspace.name.as_deref()) {
+            continue;
+        }
+        let primary_workspace = config.slot_workspace(config.slot_of(client.workspace.id));
+        if primary_workspace != client.workspace.id {
+            batch.push(Dispatcher::MoveToWorkspaceSilent {
+                target: WorkspaceTarget::Id(primary_workspace),
+                address: &client.address,
+            });
+        }
+    }
+    if refocus {
+        restore_focus(&mut batch, current_clients);
+    }
+
+    batch
+}
+
+/// Re-floats a window saved as floating at its saved position and size.
+fn restore_floating(batch: &mut HyprctlBatch, saved: &SnapshotClient, current: &ClientInfo) {
+    let Some(geometry) = saved.floating else {
+        return;
+    };
+    let address = &current.address;
+    if !current.floating {
+        batch.push(Dispatcher::SetFloating { address });
+    }
+    if !current.floating || current.at != Some([geometry.x, geometry.y]) {
+        batch.push(Dispatcher::MoveWindowPixel {
+            x: geometry.x,
+            y: geometry.y,
+            address,
+        });
+    }
+    if !current.floating || current.size != Some([geometry.width, geometry.height]) {
+        batch.push(Dispatcher::ResizeWindowPixel {
+            width: geometry.width,
+            height: geometry.height,
+            address,
+        });
+    }
+}
+
+/// Returns whether it focused the window, which `fullscreenstate` requires.
+fn restore_window_modes(
+    batch: &mut HyprctlBatch,
+    saved: &SnapshotClient,
+    current: &ClientInfo,
+) -> bool {
+    let address = &current.address;
+    if saved.pseudo && !current.pseudo && saved.floating.is_none() {
+        batch.push(Dispatcher::Pseudo { address });
+    }
+    let Some(state) = saved.fullscreen else {
+        return false;
+    };
+    if (current.fullscreen, current.fullscreen_client) == (state.internal, state.client) {
+        return false;
+    }
+    batch
+        .push(Dispatcher::FocusWindow { address })
+        .push(Dispatcher::FullscreenState {
+            internal: state.internal,
+            client: state.client,
+        });
+    true
+}
+
+/// Gives focus back to the window that had it before the restore.
+fn restore_focus(batch: &mut HyprctlBatch, current_clients: &[ClientInfo]) {
+    if let Some(active) = current_clients
+        .iter()
+        .find(|client| client.focus_history_id == Some(0))
+    {
+        batch.push(Dispatcher::FocusWindow {
+            address: &active.address,
+        });
+    }
+}
+
+fn floating_geometry(client: &ClientInfo) -> Option<WindowGeometry> {
+    if !client.floating {
+        return None;
+    }
+    let ([x, y], [width, height]) = (client.at?, client.size?);
+    Some(WindowGeometry {
+        x,
+        y,
+        width,
+        height,
+    })
+}
+
+/// How a later state differs from a snapshot, and what a restore would run.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SessionDiff {
+    pub moved: Vec<MovedWindow>,
+    pub appeared: Vec<DiffWindow>,
+    pub vanished: Vec<DiffWindow>,
+    pub monitors: Vec<MonitorChange>,
+    pub restore: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffWindow {
+    pub address: String,
+    pub class: Option<String>,
+    pub title: Option<String>,
+    pub workspace: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovedWindow {
+    pub window: DiffWindow,
+    pub from: String,
+}
+
+/// A paired workspace that sits on a different monitor than in the snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorChange {
+    pub slot: u32,
+    pub workspace: u32,
+    pub from: String,
+    pub to: String,
+}
+
+impl SessionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.moved.is_empty()
+            && self.appeared.is_empty()
+            && self.vanished.is_empty()
+            && self.monitors.is_empty()
+            && self.restore.is_empty()
+    }
+}
+
+/// Compares `snapshot` to `target`, pairing windows the way `mode` restores do.
+pub fn diff_snapshots(
+    snapshot: &SessionSnapshot,
+    target: &SessionSnapshot,
+    mode: RestoreMode,
+    config: &Config,
+) -> SessionDiff {
+    let target_clients: Vec<ClientInfo> = target.clients.iter().map(client_info).collect();
+    let resolved = resolve_restore_mode(
+        mode,
+        snapshot.signature.as_deref(),
+        target.signature.as_deref(),
+    );
+    let matches: Vec<(&ClientInfo, usize)> = if resolved == RestoreMode::Same {
+        target_clients
+            .iter()
+            .filter_map(|client| {
+                let idx = snapshot
+                    .clients
+                    .iter()
+                    .position(|saved| saved.address == client.address)?;
+                Some((client, idx))
+            })
+            .collect()
+    } else {
+        cold_matches(snapshot, &target_clients)
+    };
+
+    let mut diff = SessionDiff::default();
+    let matched_target: HashSet<&str> = matches
+        .iter()
+        .map(|(client, _)| client.address.as_str())
+        .collect();
+    let matched_snapshot: HashSet<usize> = matches.iter().map(|(_, idx)| *idx).collect();
+    for (client, idx) in &matches {
+        let saved = &snapshot.clients[*idx];
+        if !snapshot_matches_current(saved, client.workspace.id, client.workspace.name.as_deref()) {
+            diff.moved.push(MovedWindow {
+                window: open_window(client),
+                from: workspace_target(saved),
+            });
+        }
+    }
+    diff.appeared = target_clients
+        .iter()
+        .filter(|client| !matched_target.contains(client.address.as_str()))
+        .map(open_window)
+        .collect();
+    diff.vanished = snapshot
+        .clients
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !matched_snapshot.contains(idx))
+        .map(|(_, client)| diff_window(client))
+        .collect();
+
+    for saved in &snapshot.workspaces {
+        if is_special_workspace_name(saved.name.as_deref()) {
+            continue;
+        }
+        let Some(from) = &saved.monitor else {
+            continue;
+        };
+        let Some(to) = target
+            .workspaces
+            .iter()
+            .find(|workspace| workspace.id == saved.id)
+            .and_then(|workspace| workspace.monitor.as_ref())
+        else {
+            continue;
+        };
+        if from != to {
+            diff.monitors.push(MonitorChange {
+                slot: config.slot_of(saved.id),
+                workspace: saved.id,
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
+    }
+
+    let batch = restore_batch(
+        snapshot,
+        mode,
+        target.signature.as_deref(),
+        &target_clients,
+        config,
+    );
+    diff.restore = batch.commands().map(str::to_string).collect();
+    if resolved == RestoreMode::Cold && config.restore.launch_missing {
+        diff.restore.extend(
+            planned_launches(snapshot, &target_clients, config)
+                .into_iter()
+                .map(|(_, argument)| format!("dispatch exec {argument}")),
+        );
+    }
+    diff
+}
+
+pub fn render_diff(diff: &SessionDiff) -> String {
+    if diff.is_empty() {
+        return "no differences".to_string();
+    }
+    let mut lines = Vec::new();
+    if !diff.moved.is_empty() {
+        lines.push("moved:".to_string());
+        lines.extend(diff.moved.iter().map(|moved| {
+            format!(
+                "  {}: {} → {}",
+                describe_window(&moved.window),
+                moved.from,
+                moved.window.workspace
+            )
+        }));
+    }
+    for (label, windows) in [("appeared", &diff.appeared), ("vanished", &diff.vanished)] {
+        if windows.is_empty() {
+            continue;
+        }
+        lines.push(format!("{label}:"));
+        lines.extend(
+            windows
+                .iter()
+                .map(|window| format!("  {} on {}", describe_window(window), window.workspace)),
+        );
+    }
+    if !diff.monitors.is_empty() {
+        lines.push("monitors:".to_string());
+        lines.extend(diff.monitors.iter().map(|change| {
+            format!(
+                "  pair {}: workspace {} {} → {}",
+                change.slot, change.workspace, change.from, change.to
+            )
+        }));
+    }
+    if !diff.restore.is_empty() {
+        lines.push("restore would run:".to_string());
+        lines.extend(diff.restore.iter().map(|command| format!("  {command}")));
+    }
+    lines.join("\n")
+}
+
+fn describe_window(window: &DiffWindow) -> String {
+    let class = window.class.as_deref().unwrap_or("-");
+    match window.title.as_deref() {
+        Some(title) if !title.is_empty() => format!("{class} \"{title}\" ({})", window.address),
+        _ => format!("{class} ({})", window.address),
+    }
+}
+
+fn diff_window(client: &SnapshotClient) -> DiffWindow {
+    DiffWindow {
+        address: client.address.clone(),
+        class: client.app_id.clone().or_else(|| client.class.clone()),
+        title: client.title.clone(),
+        workspace: workspace_target(client),
+    }
+}
+
+fn open_window(client: &ClientInfo) -> DiffWindow {
+    let name = client.workspace.name.as_deref();
+    DiffWindow {
+        address: client.address.clone(),
+        class: client.app_id.clone().or_else(|| client.class.clone()),
+        title: client.title.clone(),
+        workspace: match name {
+            Some(name) if is_special_workspace_name(Some(name)) => name.to_string(),
+            _ => client.workspace.id.to_string(),
+        },
+    }
+}
+
+fn client_info(client: &SnapshotClient) -> ClientInfo {
+    ClientInfo {
+        address: client.address.clone(),
+        workspace: WorkspaceRef {
+            id: client.workspace_id,
+            name: client.workspace_name.clone(),
+        },
+        class: client.class.clone(),
+        title: client.title.clone(),
+        initial_class: client.initial_class.clone(),
+        initial_title: client.initial_title.clone(),
+        app_id: client.app_id.clone(),
+        pid: client.pid,
+        focus_history_id: None,
+        floating: client.floating.is_some(),
+        at: client.floating.map(|geometry| [geometry.x, geometry.y]),
+        size: client
+            .floating
+            .map(|geometry| [geometry.width, geometry.height]),
+        fullscreen: client.fullscreen.map_or(0, |state| state.internal),
+        fullscreen_client: client.fullscreen.map_or(0, |state| state.client),
+        pseudo: client.pseudo,
+    }
+}
+
+/// Pairs each open window with the snapshot client it uniquely matches best.
+fn cold_matches<'a>(
+    snapshot: &SessionSnapshot,
+    current_clients: &'a [ClientInfo],
+) -> Vec<(&'a ClientInfo, usize)> {
+    let mut used_snapshot = HashSet::new();
+    let mut matches = Vec::new();
 
     for client in current_clients {
         let mut best = None;
@@ -293,36 +909,30 @@ fn restore_cold_session(
             && score >= 4
             && score > second_best
         {
-            let snapshot_client = &snapshot.clients[idx];
-            if !snapshot_matches_current(
-                snapshot_client,
-                client.workspace.id,
-                client.workspace.name.as_deref(),
-            ) {
-                let argument =
-                    format!("{},address:{}", workspace_target(snapshot_client), client.address);
-                batch.dispatch("movetoworkspacesilent", &argument);
-            }
             used_snapshot.insert(idx);
-            matched_addresses.insert(client.address.as_str());
+            matches.push((client, idx));
         }
     }
 
-    for client in current_clients {
-        if matched_addresses.contains(client.address.as_str()) {
-            continue;
-        }
-        if is_special_workspace_name(client.workspace.name.as_deref()) {
-            continue;
-        }
-        let paired_slot = normalize_workspace(client.workspace.id, config.paired_offset);
-        if paired_slot != client.workspace.id {
-            let argument = format!("{},address:{}", paired_slot, client.address);
-            batch.dispatch("movetoworkspacesilent", &argument);
-        }
-    }
+    matches
+}
 
-    batch
+/// Snapshot clients that no open window matched in a cold restore.
+pub fn unmatched_clients<'a>(
+    snapshot: &'a SessionSnapshot,
+    current_clients: &[ClientInfo],
+) -> Vec<&'a SnapshotClient> {
+    let matched = cold_matches(snapshot, current_clients)
+        .into_iter()
+        .map(|(_, idx)| idx)
+        .collect::<HashSet<_>>();
+    snapshot
+        .clients
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !matched.contains(idx))
+        .map(|(_, client)| client)
+        .collect()
 }
 
 fn resolve_restore_mode(
@@ -360,13 +970,13 @@ fn match_score(snapshot: &SnapshotClient, client: &ClientInfo) -> u8 {
 }
 
 fn workspace_target(snapshot: &SnapshotClient) -> String {
-    if is_special_workspace_name(snapshot.workspace_name.as_deref()) {
-        snapshot
-            .workspace_name
-            .clone()
-            .unwrap_or_else(|| snapshot.workspace_id.to_string())
-    } else {
-        snapshot.workspace_id.to_string()
+    restore_target(snapshot).to_string()
+}
+
+fn restore_target(snapshot: &SnapshotClient) -> WorkspaceTarget<'_> {
+    match snapshot.workspace_name.as_deref() {
+        Some(name) if is_special_workspace_name(Some(name)) => WorkspaceTarget::Special(name),
+        _ => WorkspaceTarget::Id(snapshot.workspace_id),
     }
 }
 
@@ -0,0 +1,205 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::journal::format_timestamp;
+use crate::session::{SessionError, read_snapshot};
+
+/// Directory under `sessions/` holding named slots, shared by all instances.
+pub const NAMED_DIR: &str = "named";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionEntry {
+    pub name: String,
+    pub created_at: u64,
+    pub monitors: Vec<String>,
+    pub windows: usize,
+}
+
+pub fn named_dir(base_dir: &Path) -> PathBuf {
+    base_dir.join("sessions").join(NAMED_DIR)
+}
+
+/// The snapshot file for `name`, which must be a plain file stem.
+pub fn named_path(base_dir: &Path, name: &str) -> Result<PathBuf, SessionError> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-'));
+    if !valid {
+        return Err(SessionError::InvalidName(name.to_string()));
+    }
+    Ok(named_dir(base_dir).join(format!("{name}.json")))
+}
+
+/// An existing named snapshot, or `UnknownName` when nothing was saved under it.
+pub fn existing_path(base_dir: &Path, name: &str) -> Result<PathBuf, SessionError> {
+    let path = named_path(base_dir, name)?;
+    if !path.is_file() {
+        return Err(SessionError::UnknownName(name.to_string()));
+    }
+    Ok(path)
+}
+
+/// Every named snapshot that parses, sorted by name.
+pub fn list(base_dir: &Path) -> Result<Vec<SessionEntry>, SessionError> {
+    let entries = match fs::read_dir(named_dir(base_dir)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut sessions: Vec<SessionEntry> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            let snapshot = read_snapshot(&path).ok()?;
+            Some(SessionEntry {
+                name,
+                created_at: snapshot.created_at,
+                monitors: snapshot
+                    .monitors
+                    .into_iter()
+                    .map(|monitor| monitor.name)
+                    .collect(),
+                windows: snapshot.clients.len(),
+            })
+        })
+        .collect();
+    sessions.sort_by(|left, right| left.name.cmp(&right.name));
+    Ok(sessions)
+}
+
+pub fn delete(base_dir: &Path, name: &str) -> Result<(), SessionError> {
+    fs::remove_file(existing_path(base_dir, name)?)?;
+    Ok(())
+}
+
+pub fn render_list(entries: &[SessionEntry]) -> String {
+    if entries.is_empty() {
+        return "no saved sessions".to_string();
+    }
+    let rows: Vec<[String; 4]> = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.name.clone(),
+                format_timestamp(entry.created_at),
+                if entry.monitors.is_empty() {
+                    "-".to_string()
+                } else {
+                    entry.monitors.join(",")
+                },
+                entry.windows.to_string(),
+            ]
+        })
+        .collect();
+    let header = ["NAME", "CREATED", "MONITORS", "WINDOWS"].map(str::to_string);
+    let widths: Vec<usize> = (0..3)
+        .map(|column| {
+            std::iter::once(&header)
+                .chain(&rows)
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            let mut line = String::new();
+            for (column, width) in widths.iter().enumerate() {
+                let cell = &row[column];
+                line.push_str(cell);
+                line.push_str(&" ".repeat(width - cell.chars().count() + 2));
+            }
+            line.push_str(&row[3]);
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SessionEntry, delete, list, named_path, render_list};
+    use crate::session::SessionError;
+    use std::fs;
+
+    const SNAPSHOT: &str = r#"{
+        "version": 1,
+        "created_at": 1700000000,
+        "paired_offset": 10,
+        "workspace_count": 10,
+        "focus": {"workspace_id": 1},
+        "monitors": [{"id": 0, "name": "DP-1"}, {"id": 1, "name": "HDMI-A-1"}],
+        "workspaces": [],
+        "clients": [
+            {"address": "0x1", "workspace_id": 1, "paired_slot": 1},
+            {"address": "0x2", "workspace_id": 11, "paired_slot": 1}
+        ]
+    }"#;
+
+    #[test]
+    fn rejects_names_that_are_not_plain_file_stems() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        for name in ["", ".hidden", "../escape", "a/b", "with space"] {
+            assert!(matches!(
+                named_path(dir.path(), name),
+                Err(SessionError::InvalidName(_))
+            ));
+        }
+        assert_eq!(
+            named_path(dir.path(), "work-2.v1").expect("valid"),
+            dir.path().join("sessions/named/work-2.v1.json")
+        );
+    }
+
+    #[test]
+    fn lists_saved_sessions_by_name_and_skips_unreadable_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        assert!(list(dir.path()).expect("list").is_empty());
+        let named = dir.path().join("sessions/named");
+        fs::create_dir_all(&named).expect("mkdir");
+        fs::write(named.join("work.json"), SNAPSHOT).expect("write");
+        fs::write(named.join("gaming.json"), SNAPSHOT).expect("write");
+        fs::write(named.join("broken.json"), "{").expect("write");
+
+        let entries = list(dir.path()).expect("list");
+        let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["gaming", "work"]);
+        assert_eq!(entries[0].monitors, ["DP-1", "HDMI-A-1"]);
+        assert_eq!(entries[0].windows, 2);
+    }
+
+    #[test]
+    fn deletes_only_existing_sessions() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        assert!(matches!(
+            delete(dir.path(), "work"),
+            Err(SessionError::UnknownName(_))
+        ));
+        let path = named_path(dir.path(), "work").expect("path");
+        fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+        fs::write(&path, SNAPSHOT).expect("write");
+        delete(dir.path(), "work").expect("delete");
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn renders_sessions_as_a_table() {
+        assert_eq!(render_list(&[]), "no saved sessions");
+        let entries = [SessionEntry {
+            name: "work".to_string(),
+            created_at: 1_700_000_000,
+            monitors: vec!["DP-1".to_string(), "HDMI-A-1".to_string()],
+            windows: 12,
+        }];
+        assert_eq!(
+            render_list(&entries),
+            "NAME  CREATED               MONITORS       WINDOWS\n\
+             work  2023-11-14T22:13:20Z  DP-1,HDMI-A-1  12"
+        );
+    }
+}
@@ -1,13 +1,21 @@
-use crate::config::{DEFAULT_PAIRED_OFFSET, DEFAULT_WRAP_CYCLING};
-use crate::hyprctl::{ClientInfo, MonitorInfo};
+use crate::commands::MigrationTarget;
+pub use crate::commands::migration_targets;
+use crate::config::{
+    Config, DEFAULT_PAIRED_OFFSET, DEFAULT_WORKSPACE_BASE, DEFAULT_WRAP_CYCLING, remove_in_text,
+    set_in_text, strip_json_comments,
+};
+use crate::hyprctl::MonitorInfo;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 pub fn select_monitors(monitors: &[MonitorInfo]) -> Option<(String, String)> {
-    if monitors.is_empty() {
+    let mut sorted: Vec<&MonitorInfo> = monitors
+        .iter()
+        .filter(|monitor| !monitor.is_mirror())
+        .collect();
+    if sorted.is_empty() {
         return None;
     }
-    let mut sorted: Vec<&MonitorInfo> = monitors.iter().collect();
     sorted.sort_by_key(|monitor| (monitor.x, monitor.id));
     let primary = sorted[0].name.clone();
     let secondary = sorted
@@ -32,60 +40,197 @@ pub fn render_config(primary: &str, secondary: &str, offset: u32, wrap_cycling:
     .to_string()
 }
 
-pub fn render_bindings(bin_path: &str, workspace_count: u32) -> String {
-    let mut lines = Vec::new();
-    lines.push("# hyprspaces keybindings".to_string());
-    for i in 1..=workspace_count {
-        let code = i + 9;
-        lines.push(format!(
-            "bindd = SUPER, code:{code}, Paired workspace {i}, exec, {bin_path} paired switch {i}"
-        ));
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingKeys {
+    #[default]
+    NumberRow,
+    Keypad,
+}
+
+impl BindingKeys {
+    fn key(self, slot: u32) -> Option<String> {
+        match self {
+            BindingKeys::NumberRow => Some(format!("code:{}", slot + 9)),
+            BindingKeys::Keypad => {
+                const KEYPAD_CODES: [u32; 10] = [87, 88, 89, 83, 84, 85, 79, 80, 81, 90];
+                let index = usize::try_from(slot.checked_sub(1)?).ok()?;
+                KEYPAD_CODES.get(index).map(|code| format!("code:{code}"))
+            }
+        }
     }
-    lines.push(format!(
-        "bindd = SUPER, mouse_up, Paired prev, exec, {bin_path} paired cycle prev"
-    ));
-    lines.push(format!(
-        "bindd = SUPER, mouse_down, Paired next, exec, {bin_path} paired cycle next"
-    ));
-    for i in 1..=workspace_count {
-        let code = i + 9;
-        lines.push(format!(
-            "bindd = SUPER SHIFT, code:{code}, Move to paired {i}, exec, {bin_path} paired move-window {i}"
-        ));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoundCommand {
+    Switch,
+    Cycle,
+    MoveWindow,
+    Swap,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(default)]
+pub struct BindingsConfig {
+    pub modifier: String,
+    pub keys: BindingKeys,
+    pub commands: Vec<BoundCommand>,
+    /// Bind `hyprspaces send ...` so a running daemon executes the command.
+    pub via_daemon: bool,
+}
+
+impl Default for BindingsConfig {
+    fn default() -> Self {
+        Self {
+            modifier: "SUPER".to_string(),
+            keys: BindingKeys::NumberRow,
+            commands: vec![
+                BoundCommand::Switch,
+                BoundCommand::Cycle,
+                BoundCommand::MoveWindow,
+                BoundCommand::Swap,
+            ],
+            via_daemon: false,
+        }
+    }
+}
+
+const SWITCH_TEMPLATE: &str =
+    "bindd = {mod}, {key}, Paired workspace {slot}, exec, {bin} {invoke} switch {slot}";
+const CYCLE_TEMPLATE: &str =
+    "bindd = {mod}, {key}, Paired {direction}, exec, {bin} {invoke} cycle {direction}";
+const MOVE_WINDOW_TEMPLATE: &str =
+    "bindd = {mod} SHIFT, {key}, Move to paired {slot}, exec, {bin} {invoke} move-window {slot}";
+const UNBIND_SWAP_TEMPLATE: &str = "unbind = {mod} SHIFT, {key}";
+const SWAP_TEMPLATE: &str =
+    "bindd = {mod} SHIFT, {key}, Move window {label}, movewindow, {direction}";
+
+pub fn render_template(template: &str, vars: &[(&str, &str)]) -> String {
+    vars.iter()
+        .fold(template.to_string(), |output, (name, value)| {
+            output.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+/// Binds slot keys to the slot's primary workspace id, counted from `base`.
+pub fn render_bindings(
+    bin_path: &str,
+    workspace_count: u32,
+    base: u32,
+    bindings: &BindingsConfig,
+) -> String {
+    let modifier = bindings.modifier.as_str();
+    let invoke = if bindings.via_daemon {
+        "send"
+    } else {
+        "paired"
+    };
+    let enabled = |command| bindings.commands.contains(&command);
+    let slot_lines = |template: &str| {
+        (1..=workspace_count)
+            .filter_map(|slot| {
+                let key = bindings.keys.key(slot)?;
+                let slot = (base + slot - 1).to_string();
+                Some(render_template(
+                    template,
+                    &[
+                        ("mod", modifier),
+                        ("key", &key),
+                        ("slot", &slot),
+                        ("bin", bin_path),
+                        ("invoke", invoke),
+                    ],
+                ))
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut lines = vec!["# hyprspaces keybindings".to_string()];
+    if enabled(BoundCommand::Switch) {
+        lines.extend(slot_lines(SWITCH_TEMPLATE));
+    }
+    if enabled(BoundCommand::Cycle) {
+        for (key, direction) in [("mouse_up", "prev"), ("mouse_down", "next")] {
+            lines.push(render_template(
+                CYCLE_TEMPLATE,
+                &[
+                    ("mod", modifier),
+                    ("key", key),
+                    ("direction", direction),
+                    ("bin", bin_path),
+                    ("invoke", invoke),
+                ],
+            ));
+        }
+    }
+    if enabled(BoundCommand::MoveWindow) {
+        lines.extend(slot_lines(MOVE_WINDOW_TEMPLATE));
+    }
+    if enabled(BoundCommand::Swap) {
+        lines.push(String::new());
+        lines.push("# Move window with arrow keys (override default swap behavior)".to_string());
+        let arrows = [
+            ("LEFT", "left", "l"),
+            ("RIGHT", "right", "r"),
+            ("UP", "up", "u"),
+            ("DOWN", "down", "d"),
+        ];
+        for template in [UNBIND_SWAP_TEMPLATE, SWAP_TEMPLATE] {
+            for (key, label, direction) in arrows {
+                lines.push(render_template(
+                    template,
+                    &[
+                        ("mod", modifier),
+                        ("key", key),
+                        ("label", label),
+                        ("direction", direction),
+                    ],
+                ));
+            }
+        }
     }
-    lines.push(String::new());
-    lines.push("# Move window with arrow keys (override default swap behavior)".to_string());
-    lines.push("unbind = SUPER SHIFT, LEFT".to_string());
-    lines.push("unbind = SUPER SHIFT, RIGHT".to_string());
-    lines.push("unbind = SUPER SHIFT, UP".to_string());
-    lines.push("unbind = SUPER SHIFT, DOWN".to_string());
-    lines.push("bindd = SUPER SHIFT, LEFT, Move window left, movewindow, l".to_string());
-    lines.push("bindd = SUPER SHIFT, RIGHT, Move window right, movewindow, r".to_string());
-    lines.push("bindd = SUPER SHIFT, UP, Move window up, movewindow, u".to_string());
-    lines.push("bindd = SUPER SHIFT, DOWN, Move window down, movewindow, d".to_string());
     lines.join("\n")
 }
 
-pub fn render_autostart(bin_path: &str) -> String {
-    format!("# hyprspaces autostart\nexec-once = {bin_path} daemon")
+fn daemon_command(bin_path: &str, restore_on_start: bool) -> String {
+    if restore_on_start {
+        format!("{bin_path} daemon --restore-on-start")
+    } else {
+        format!("{bin_path} daemon")
+    }
+}
+
+pub fn render_autostart(bin_path: &str, restore_on_start: bool) -> String {
+    format!(
+        "# hyprspaces autostart\nexec-once = {}",
+        daemon_command(bin_path, restore_on_start)
+    )
 }
 
-pub fn render_workspace_rules(primary: &str, secondary: &str, offset: u32) -> String {
+pub fn render_xdg_autostart(bin_path: &str, restore_on_start: bool) -> String {
+    format!(
+        "[Desktop Entry]\nType=Application\nName=hyprspaces daemon\nComment=Paired workspaces for Hyprland\nExec={}\nOnlyShowIn=Hyprland;\nNoDisplay=true\nX-GNOME-Autostart-enabled=true\n",
+        daemon_command(bin_path, restore_on_start)
+    )
+}
+
+pub fn render_workspace_rules(primary: &str, secondary: &str, offset: u32, base: u32) -> String {
     let mut lines = Vec::new();
     lines.push("# Generated by hyprspaces".to_string());
     for i in 1..=offset {
+        let workspace_id = base + i - 1;
         if i == 1 {
             lines.push(format!(
-                "workspace = 1, monitor:{primary}, persistent:true, default:true"
+                "workspace = {workspace_id}, monitor:{primary}, persistent:true, default:true"
             ));
         } else {
             lines.push(format!(
-                "workspace = {i}, monitor:{primary}, persistent:true"
+                "workspace = {workspace_id}, monitor:{primary}, persistent:true"
             ));
         }
     }
     for i in 1..=offset {
-        let workspace_id = i + offset;
+        let workspace_id = base + i - 1 + offset;
         if i == 1 {
             lines.push(format!(
                 "workspace = {workspace_id}, monitor:{secondary}, persistent:true, default:true"
@@ -99,20 +244,398 @@ pub fn render_workspace_rules(primary: &str, secondary: &str, offset: u32) -> St
     lines.join("\n")
 }
 
-pub fn render_waybar_config(bin_path: &str, theme_path: &Path) -> String {
-    serde_json::json!({
-        "custom/workspaces": {
-            "exec": format!(
-                "{bin_path} waybar --enable-waybar --theme-css {}",
-                theme_path.display()
-            ),
+pub fn workspace_rules_path(base_dir: &Path) -> PathBuf {
+    base_dir.join("workspace-rules.conf")
+}
+
+/// Rewrites `workspace-rules.conf`; returns `false` when it was already current.
+pub fn sync_workspace_rules(base_dir: &Path, config: &Config) -> Result<bool, SetupError> {
+    let path = workspace_rules_path(base_dir);
+    let contents = render_workspace_rules(
+        &config.primary_monitor,
+        &config.secondary_monitor,
+        config.paired_offset,
+        config.workspace_base,
+    );
+    if fs::read_to_string(&path).is_ok_and(|existing| existing == contents) {
+        return Ok(false);
+    }
+    fs::create_dir_all(base_dir)?;
+    write_atomic(&path, &contents)?;
+    Ok(true)
+}
+
+/// Rewrites an installed `bindings.conf`; returns `false` when it was already current.
+pub fn sync_bindings(
+    base_dir: &Path,
+    bin_path: &str,
+    config_path: &Path,
+) -> Result<bool, SetupError> {
+    let path = base_dir.join("bindings.conf");
+    let Ok(existing) = fs::read_to_string(&path) else {
+        return Ok(false);
+    };
+    let config_data = read_config_data(config_path)?;
+    let contents = render_bindings(
+        bin_path,
+        config_data.workspace_count,
+        config_data.workspace_base,
+        &config_data.bindings,
+    );
+    if existing == contents {
+        return Ok(false);
+    }
+    write_atomic(&path, &contents)?;
+    Ok(true)
+}
+
+/// The shared module plus one `custom/workspaces#<output>` per entry in `outputs`.
+pub fn render_waybar_config(bin_path: &str, theme_path: &Path, outputs: &[String]) -> String {
+    let module = |monitor: Option<&str>| {
+        let exec = format!(
+            "{bin_path} waybar --enable-waybar --theme-css {}",
+            theme_path.display()
+        );
+        serde_json::json!({
+            "exec": match monitor {
+                Some(monitor) => format!("{exec} --monitor {monitor}"),
+                None => exec,
+            },
             "return-type": "json",
             "format": "{}",
             "on-scroll-up": format!("{bin_path} paired cycle prev"),
             "on-scroll-down": format!("{bin_path} paired cycle next")
+        })
+    };
+    let mut modules = serde_json::Map::new();
+    modules.insert(WAYBAR_MODULE.to_string(), module(None));
+    for output in outputs {
+        modules.insert(waybar_module(Some(output)), module(Some(output)));
+    }
+    serde_json::Value::Object(modules).to_string()
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    clap::ValueEnum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum BarPosition {
+    #[default]
+    Top,
+    Bottom,
+}
+
+impl BarPosition {
+    fn as_str(self) -> &'static str {
+        match self {
+            BarPosition::Top => "top",
+            BarPosition::Bottom => "bottom",
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    clap::ValueEnum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ModulesSection {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl ModulesSection {
+    const ALL: [ModulesSection; 3] = [
+        ModulesSection::Left,
+        ModulesSection::Center,
+        ModulesSection::Right,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            ModulesSection::Left => "modules-left",
+            ModulesSection::Center => "modules-center",
+            ModulesSection::Right => "modules-right",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct WaybarPlacement {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<PathBuf>,
+    pub bar: BarPosition,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    pub modules: ModulesSection,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    /// Place a per-monitor module in every output-pinned bar.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub per_output: bool,
+}
+
+impl WaybarPlacement {
+    fn describe(&self) -> String {
+        match &self.output {
+            Some(output) => format!("{} bar on {output}", self.bar.as_str()),
+            None => format!("{} bar", self.bar.as_str()),
+        }
+    }
+
+    fn matches(&self, bar: &serde_json::Value) -> bool {
+        let position = bar
+            .get("position")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("top");
+        if position != self.bar.as_str() {
+            return false;
+        }
+        if self.per_output {
+            return bar_output(bar).is_some();
+        }
+        let Some(output) = &self.output else {
+            return true;
+        };
+        match bar.get("output") {
+            Some(serde_json::Value::String(name)) => name == output,
+            Some(serde_json::Value::Array(names)) => {
+                names.iter().any(|name| name.as_str() == Some(output))
+            }
+            _ => false,
         }
+    }
+}
+
+pub const WAYBAR_MODULE: &str = "custom/workspaces";
+
+pub fn waybar_module(output: Option<&str>) -> String {
+    match output {
+        Some(output) => format!("{WAYBAR_MODULE}#{output}"),
+        None => WAYBAR_MODULE.to_string(),
+    }
+}
+
+fn is_waybar_module(module: &serde_json::Value) -> bool {
+    module.as_str().is_some_and(|module| {
+        module == WAYBAR_MODULE
+            || module
+                .strip_prefix(WAYBAR_MODULE)
+                .is_some_and(|rest| rest.starts_with('#'))
     })
-    .to_string()
+}
+
+fn bar_output(bar: &serde_json::Value) -> Option<&str> {
+    bar.get("output").and_then(serde_json::Value::as_str)
+}
+
+pub fn waybar_config_path(waybar_dir: &Path, placement: &WaybarPlacement) -> PathBuf {
+    if let Some(path) = &placement.config {
+        return path.clone();
+    }
+    let jsonc = waybar_dir.join("config.jsonc");
+    if jsonc.exists() {
+        jsonc
+    } else {
+        waybar_dir.join("config")
+    }
+}
+
+fn placement_bars<'a>(
+    config: &'a mut serde_json::Value,
+    placement: &WaybarPlacement,
+) -> Vec<&'a mut serde_json::Map<String, serde_json::Value>> {
+    let bars: Vec<&mut serde_json::Value> = match config {
+        serde_json::Value::Array(bars) => {
+            let mut matching = bars.iter_mut().filter(|bar| placement.matches(bar));
+            if placement.per_output {
+                matching.collect()
+            } else {
+                matching.next().into_iter().collect()
+            }
+        }
+        bar if placement.matches(bar) => vec![bar],
+        _ => Vec::new(),
+    };
+    bars.into_iter()
+        .filter_map(serde_json::Value::as_object_mut)
+        .collect()
+}
+
+fn string_list<'a>(
+    bar: &'a mut serde_json::Map<String, serde_json::Value>,
+    key: &str,
+) -> &'a mut Vec<serde_json::Value> {
+    let entry = bar
+        .entry(key)
+        .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+    if !entry.is_array() {
+        let existing = entry.take();
+        *entry = serde_json::Value::Array(if existing.is_null() {
+            Vec::new()
+        } else {
+            vec![existing]
+        });
+    }
+    match entry {
+        serde_json::Value::Array(items) => items,
+        _ => unreachable!("entry was converted to an array"),
+    }
+}
+
+pub fn inject_waybar_module(
+    contents: &str,
+    placement: &WaybarPlacement,
+    include: &Path,
+) -> Result<String, SetupError> {
+    let mut config: serde_json::Value = serde_json::from_str(&strip_json_comments(contents))?;
+    let bars = placement_bars(&mut config, placement);
+    if bars.is_empty() {
+        return Err(SetupError::NoMatchingBar(placement.describe()));
+    }
+    let include = include.display().to_string();
+    for bar in bars {
+        let module = if placement.per_output {
+            waybar_module(bar.get("output").and_then(serde_json::Value::as_str))
+        } else {
+            waybar_module(None)
+        };
+        for section in ModulesSection::ALL {
+            if let Some(serde_json::Value::Array(modules)) = bar.get_mut(section.key()) {
+                modules.retain(|module| !is_waybar_module(module));
+            }
+        }
+        let modules = string_list(bar, placement.modules.key());
+        let index = placement.index.unwrap_or(modules.len()).min(modules.len());
+        modules.insert(index, module.into());
+        let includes = string_list(bar, "include");
+        if !includes.iter().any(|path| path.as_str() == Some(&include)) {
+            includes.push(include.clone().into());
+        }
+    }
+    Ok(serde_json::to_string_pretty(&config)? + "\n")
+}
+
+pub fn eject_waybar_module(
+    contents: &str,
+    placement: &WaybarPlacement,
+    include: &Path,
+) -> Option<String> {
+    let mut config: serde_json::Value =
+        serde_json::from_str(&strip_json_comments(contents)).ok()?;
+    let include = include.display().to_string();
+    let mut changed = false;
+    for bar in placement_bars(&mut config, placement) {
+        for section in ModulesSection::ALL {
+            if let Some(serde_json::Value::Array(modules)) = bar.get_mut(section.key()) {
+                let before = modules.len();
+                modules.retain(|module| !is_waybar_module(module));
+                changed |= modules.len() != before;
+            }
+        }
+        if let Some(serde_json::Value::Array(includes)) = bar.get_mut("include") {
+            let before = includes.len();
+            includes.retain(|item| item.as_str() != Some(include.as_str()));
+            changed |= includes.len() != before;
+            if includes.is_empty() {
+                bar.remove("include");
+            }
+        }
+    }
+    changed.then(|| serde_json::to_string_pretty(&config).unwrap_or_default() + "\n")
+}
+
+/// Rewrites the `waybar` placement keys in place, so comments elsewhere survive.
+pub fn store_waybar_placement(
+    base_dir: &Path,
+    config_path: &Path,
+    placement: &WaybarPlacement,
+) -> Result<(), SetupError> {
+    let original = fs::read_to_string(config_path)?;
+    let config: serde_json::Value = serde_json::from_str(&strip_json_comments(&original))?;
+    let Some(fields) = config.as_object() else {
+        return Err(SetupError::MissingConfigFile(config_path.to_path_buf()));
+    };
+    let placement = serde_json::to_value(placement)?;
+    let unparsed = || SetupError::MissingConfigFile(config_path.to_path_buf());
+    let mut contents = original.clone();
+    if fields
+        .get("waybar")
+        .is_some_and(|section| !section.is_object())
+    {
+        contents = set_in_text(&contents, &["waybar"], &placement).ok_or_else(unparsed)?;
+    } else {
+        for key in ["config", "bar", "output", "modules", "index", "per_output"] {
+            contents = match placement.get(key) {
+                Some(value) => set_in_text(&contents, &["waybar", key], value),
+                None => remove_in_text(&contents, &["waybar", key]),
+            }
+            .ok_or_else(unparsed)?;
+        }
+    }
+    BackupSet::begin(base_dir)?.write(config_path, &contents)
+}
+
+pub fn waybar_placement(config_path: &Path) -> Result<Option<WaybarPlacement>, SetupError> {
+    Ok(read_config_data(config_path)?.waybar)
+}
+
+/// Whether the config asks for `daemon --restore-on-start` in autostart.
+pub fn restore_on_start(config_path: &Path) -> Result<bool, SetupError> {
+    Ok(read_config_data(config_path)?.restore_on_start)
+}
+
+pub fn place_waybar_module(
+    base_dir: &Path,
+    waybar_dir: &Path,
+    config_path: &Path,
+) -> Result<Option<PathBuf>, SetupError> {
+    let Some(placement) = waybar_placement(config_path)? else {
+        return Ok(None);
+    };
+    let path = waybar_config_path(waybar_dir, &placement);
+    let include = base_dir.join("waybar").join("workspaces.json");
+    let updated = inject_waybar_module(&fs::read_to_string(&path)?, &placement, &include)?;
+    BackupSet::begin(base_dir)?.write(&path, &updated)?;
+    Ok(Some(path))
+}
+
+pub fn unplace_waybar_module(
+    base_dir: &Path,
+    waybar_dir: &Path,
+    config_path: &Path,
+) -> Result<bool, SetupError> {
+    let Ok(Some(placement)) = waybar_placement(config_path) else {
+        return Ok(false);
+    };
+    let path = waybar_config_path(waybar_dir, &placement);
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Ok(false);
+    };
+    let include = base_dir.join("waybar").join("workspaces.json");
+    let Some(updated) = eject_waybar_module(&contents, &placement, &include) else {
+        return Ok(false);
+    };
+    BackupSet::begin(base_dir)?.write(&path, &updated)?;
+    Ok(true)
 }
 
 pub fn render_waybar_style() -> String {
@@ -120,25 +643,34 @@ pub fn render_waybar_style() -> String {
 }
 
 pub fn render_waybar_theme() -> String {
-    "@define-color foreground #c0caf5;\n".to_string()
+    format!(
+        "@define-color foreground {};\n",
+        crate::waybar::DEFAULT_FOREGROUND
+    )
 }
 
 pub fn add_source_block(contents: &str, source_line: &str) -> String {
-    if contents.contains(source_line) {
-        return contents.to_string();
-    }
     let mut output = String::from(contents);
-    if !output.ends_with('\n') {
-        output.push('\n');
-    }
-    output.push('\n');
-    output.push_str("# BEGIN HYPRSPACES\n");
-    output.push_str(source_line);
-    output.push('\n');
-    output.push_str("# END HYPRSPACES\n");
+    output.push_str(&source_block_insertion(contents, source_line).unwrap_or_default());
     output
 }
 
+fn source_block_insertion(contents: &str, source_line: &str) -> Option<String> {
+    if contents.contains(source_line) {
+        return None;
+    }
+    let mut inserted = String::new();
+    if !contents.ends_with('\n') {
+        inserted.push('\n');
+    }
+    inserted.push('\n');
+    inserted.push_str("# BEGIN HYPRSPACES\n");
+    inserted.push_str(source_line);
+    inserted.push('\n');
+    inserted.push_str("# END HYPRSPACES\n");
+    Some(inserted)
+}
+
 pub fn remove_source_block(contents: &str) -> String {
     let mut output = String::new();
     let mut skipping = false;
@@ -159,26 +691,18 @@ pub fn remove_source_block(contents: &str) -> String {
     output
 }
 
-pub fn migration_targets(clients: &[ClientInfo], offset: u32) -> Vec<(String, u32)> {
-    clients
-        .iter()
-        .filter_map(|client| {
-            let workspace_id = client.workspace.id;
-            if workspace_id > offset && workspace_id <= offset * 2 {
-                Some((client.address.clone(), workspace_id - offset))
-            } else {
-                None
-            }
-        })
-        .collect()
-}
-
 #[derive(Debug, thiserror::Error)]
 pub enum SetupError {
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
     #[error("missing config file: {0}")]
     MissingConfigFile(PathBuf),
+    #[error("no setup backup set to restore")]
+    NoBackups,
+    #[error("invalid json: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("no waybar {0} found")]
+    NoMatchingBar(String),
 }
 
 pub fn ensure_config(
@@ -199,14 +723,18 @@ pub fn ensure_config(
     Ok(true)
 }
 
-pub fn install_waybar(base_dir: &Path, bin_path: &str) -> Result<(), SetupError> {
+pub fn install_waybar(
+    base_dir: &Path,
+    bin_path: &str,
+    outputs: &[String],
+) -> Result<(), SetupError> {
     let waybar_dir = base_dir.join("waybar");
     let theme_path = waybar_dir.join("theme.css");
     fs::create_dir_all(&waybar_dir)?;
     fs::write(&theme_path, render_waybar_theme())?;
     fs::write(
         waybar_dir.join("workspaces.json"),
-        render_waybar_config(bin_path, &theme_path),
+        render_waybar_config(bin_path, &theme_path, outputs),
     )?;
     fs::write(waybar_dir.join("workspaces.css"), render_waybar_style())?;
     fs::write(waybar_dir.join("installed.flag"), "1\n")?;
@@ -236,6 +764,30 @@ pub fn uninstall_waybar(base_dir: &Path) -> Result<bool, SetupError> {
     Ok(true)
 }
 
+pub fn xdg_autostart_path(autostart_dir: &Path) -> PathBuf {
+    autostart_dir.join("hyprspaces-daemon.desktop")
+}
+
+pub fn install_xdg_autostart(
+    autostart_dir: &Path,
+    bin_path: &str,
+    restore_on_start: bool,
+) -> Result<PathBuf, SetupError> {
+    fs::create_dir_all(autostart_dir)?;
+    let path = xdg_autostart_path(autostart_dir);
+    fs::write(&path, render_xdg_autostart(bin_path, restore_on_start))?;
+    Ok(path)
+}
+
+pub fn uninstall_xdg_autostart(autostart_dir: &Path) -> Result<bool, SetupError> {
+    let path = xdg_autostart_path(autostart_dir);
+    if !path.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(path)?;
+    Ok(true)
+}
+
 pub fn install(
     base_dir: &Path,
     bin_path: &str,
@@ -244,55 +796,156 @@ pub fn install(
     monitors: Option<&[MonitorInfo]>,
 ) -> Result<(), SetupError> {
     ensure_config(config_path, monitors)?;
-    let config_data = read_config_data(config_path)?;
     fs::create_dir_all(base_dir)?;
-    fs::write(
-        base_dir.join("bindings.conf"),
-        render_bindings(bin_path, config_data.workspace_count),
-    )?;
-    fs::write(base_dir.join("autostart.conf"), render_autostart(bin_path))?;
-    fs::write(
-        base_dir.join("workspace-rules.conf"),
-        render_workspace_rules(
-            &config_data.primary_monitor,
-            &config_data.secondary_monitor,
-            config_data.paired_offset,
-        ),
-    )?;
+    let mut manifest = InstallManifest::load(base_dir)?;
+    let mut backups = BackupSet::begin(base_dir)?;
+    for artifact in generated_artifacts(base_dir, bin_path, config_path)? {
+        backups.write(&artifact.path, &artifact.contents)?;
+        manifest.record_file(artifact.path);
+    }
+    for (path, source_line) in source_lines(base_dir, hypr_config_dir) {
+        if let Some(inserted) = update_source_block(&path, &source_line, Some(&mut backups))? {
+            manifest.record_edit(path, inserted);
+        }
+    }
+    manifest.save(base_dir)
+}
 
-    update_source_block(
-        &hypr_config_dir.join("bindings.conf"),
-        &format!("source = {}", base_dir.join("bindings.conf").display()),
-    )?;
-    update_source_block(
-        &hypr_config_dir.join("autostart.conf"),
-        &format!("source = {}", base_dir.join("autostart.conf").display()),
-    )?;
-    update_source_block(
-        &hypr_config_dir.join("hyprland.conf"),
-        &format!(
-            "source = {}",
-            base_dir.join("workspace-rules.conf").display()
-        ),
-    )?;
+#[derive(Debug, Clone, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub struct SourceEdit {
+    pub path: PathBuf,
+    pub inserted: String,
+}
 
-    Ok(())
+#[derive(Debug, Clone, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub struct InstallManifest {
+    #[serde(default)]
+    pub files: Vec<PathBuf>,
+    #[serde(default)]
+    pub edits: Vec<SourceEdit>,
+}
+
+impl InstallManifest {
+    pub fn load(base_dir: &Path) -> Result<Self, SetupError> {
+        match fs::read_to_string(manifest_path(base_dir)) {
+            Ok(contents) => Ok(serde_json::from_str(&contents).unwrap_or_default()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn save(&self, base_dir: &Path) -> Result<(), SetupError> {
+        let contents = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        fs::write(manifest_path(base_dir), contents)?;
+        Ok(())
+    }
+
+    fn record_file(&mut self, path: PathBuf) {
+        if !self.files.contains(&path) {
+            self.files.push(path);
+        }
+    }
+
+    fn record_edit(&mut self, path: PathBuf, inserted: String) {
+        self.edits.push(SourceEdit { path, inserted });
+    }
+}
+
+pub fn manifest_path(base_dir: &Path) -> PathBuf {
+    base_dir.join("install-manifest.json")
+}
+
+pub fn revert_source_edit(contents: &str, inserted: &str) -> Option<String> {
+    let start = contents.rfind(inserted)?;
+    let mut output = String::with_capacity(contents.len() - inserted.len());
+    output.push_str(&contents[..start]);
+    output.push_str(&contents[start + inserted.len()..]);
+    Some(output)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+pub fn generated_artifacts(
+    base_dir: &Path,
+    bin_path: &str,
+    config_path: &Path,
+) -> Result<Vec<Artifact>, SetupError> {
+    let config_data = read_config_data(config_path)?;
+    Ok(vec![
+        Artifact {
+            path: base_dir.join("bindings.conf"),
+            contents: render_bindings(
+                bin_path,
+                config_data.workspace_count,
+                config_data.workspace_base,
+                &config_data.bindings,
+            ),
+        },
+        Artifact {
+            path: base_dir.join("autostart.conf"),
+            contents: render_autostart(bin_path, config_data.restore_on_start),
+        },
+        Artifact {
+            path: workspace_rules_path(base_dir),
+            contents: render_workspace_rules(
+                &config_data.primary_monitor,
+                &config_data.secondary_monitor,
+                config_data.paired_offset,
+                config_data.workspace_base,
+            ),
+        },
+    ])
+}
+
+pub fn source_lines(base_dir: &Path, hypr_config_dir: &Path) -> Vec<(PathBuf, String)> {
+    [
+        ("bindings.conf", "bindings.conf"),
+        ("autostart.conf", "autostart.conf"),
+        ("hyprland.conf", "workspace-rules.conf"),
+    ]
+    .into_iter()
+    .map(|(hypr_file, fragment)| {
+        (
+            hypr_config_dir.join(hypr_file),
+            format!("source = {}", base_dir.join(fragment).display()),
+        )
+    })
+    .collect()
 }
 
 pub fn uninstall(base_dir: &Path, hypr_config_dir: &Path) -> Result<(), SetupError> {
     let _ = uninstall_waybar(base_dir)?;
+    let manifest = InstallManifest::load(base_dir)?;
+    let mut backups = BackupSet::begin(base_dir)?;
+    for edit in manifest.edits.iter().rev() {
+        if let Ok(contents) = fs::read_to_string(&edit.path)
+            && let Some(restored) = revert_source_edit(&contents, &edit.inserted)
+        {
+            backups.write(&edit.path, &restored)?;
+        }
+    }
     for file in ["bindings.conf", "autostart.conf", "hyprland.conf"] {
         let path = hypr_config_dir.join(file);
-        if path.exists() {
-            let updated = remove_source_block(&fs::read_to_string(&path)?);
-            fs::write(path, updated)?;
+        if path.exists() && !manifest.edits.iter().any(|edit| edit.path == path) {
+            let contents = fs::read_to_string(&path)?;
+            if contents.contains("# BEGIN HYPRSPACES") {
+                backups.write(&path, &remove_source_block(&contents))?;
+            }
         }
     }
-    for file in ["bindings.conf", "autostart.conf", "workspace-rules.conf"] {
-        let path = base_dir.join(file);
-        if path.exists() {
-            fs::remove_file(path)?;
-        }
+    let generated = ["bindings.conf", "autostart.conf", "workspace-rules.conf"]
+        .into_iter()
+        .map(|file| base_dir.join(file));
+    for path in manifest.files.iter().cloned().chain(generated) {
+        backups.remove(&path)?;
+    }
+    let manifest_path = manifest_path(base_dir);
+    if manifest_path.exists() {
+        fs::remove_file(manifest_path)?;
     }
     if base_dir.exists() {
         let _ = fs::remove_dir(base_dir);
@@ -306,6 +959,10 @@ struct ConfigData {
     secondary_monitor: String,
     paired_offset: u32,
     workspace_count: u32,
+    workspace_base: u32,
+    bindings: BindingsConfig,
+    waybar: Option<WaybarPlacement>,
+    restore_on_start: bool,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -318,19 +975,35 @@ struct RawConfigData {
     paired_offset: u32,
     #[serde(default)]
     workspace_count: Option<u32>,
+    #[serde(default = "default_workspace_base")]
+    workspace_base: u32,
+    #[serde(default)]
+    bindings: BindingsConfig,
+    #[serde(default)]
+    waybar: Option<WaybarPlacement>,
+    #[serde(default)]
+    restore_on_start: bool,
 }
 
 fn default_offset() -> u32 {
     DEFAULT_PAIRED_OFFSET
 }
 
+fn default_workspace_base() -> u32 {
+    DEFAULT_WORKSPACE_BASE
+}
+
 fn read_config_data(path: &Path) -> Result<ConfigData, SetupError> {
     let contents = fs::read_to_string(path)?;
-    let raw = serde_json::from_str(&contents).unwrap_or(RawConfigData {
+    let raw = serde_json::from_str(&strip_json_comments(&contents)).unwrap_or(RawConfigData {
         primary_monitor: None,
         secondary_monitor: None,
         paired_offset: DEFAULT_PAIRED_OFFSET,
         workspace_count: None,
+        workspace_base: DEFAULT_WORKSPACE_BASE,
+        bindings: BindingsConfig::default(),
+        waybar: None,
+        restore_on_start: false,
     });
     let workspace_count = raw.workspace_count.unwrap_or(raw.paired_offset);
     Ok(ConfigData {
@@ -338,29 +1011,286 @@ fn read_config_data(path: &Path) -> Result<ConfigData, SetupError> {
         secondary_monitor: raw.secondary_monitor.unwrap_or_default(),
         paired_offset: workspace_count,
         workspace_count,
+        workspace_base: raw.workspace_base,
+        bindings: raw.bindings,
+        waybar: raw.waybar,
+        restore_on_start: raw.restore_on_start,
     })
 }
 
-fn update_source_block(path: &Path, source_line: &str) -> Result<(), SetupError> {
+pub fn update_source_block(
+    path: &Path,
+    source_line: &str,
+    backups: Option<&mut BackupSet>,
+) -> Result<Option<String>, SetupError> {
     if !path.exists() {
-        return Ok(());
+        return Ok(None);
+    }
+    let mut contents = fs::read_to_string(path)?;
+    let Some(inserted) = source_block_insertion(&contents, source_line) else {
+        return Ok(None);
+    };
+    contents.push_str(&inserted);
+    match backups {
+        Some(backups) => backups.write(path, &contents)?,
+        None => write_atomic(path, &contents)?,
     }
-    let updated = add_source_block(&fs::read_to_string(path)?, source_line);
-    fs::write(path, updated)?;
+    Ok(Some(inserted))
+}
+
+pub fn write_atomic(path: &Path, contents: &str) -> Result<(), SetupError> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp = path.with_file_name(format!(".{file_name}.tmp"));
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path)?;
     Ok(())
 }
 
+pub fn backups_dir(base_dir: &Path) -> PathBuf {
+    base_dir.join("backups")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BackupEntry {
+    pub path: PathBuf,
+    pub backup: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct BackupSet {
+    dir: PathBuf,
+    entries: Vec<BackupEntry>,
+}
+
+impl BackupSet {
+    pub fn begin(base_dir: &Path) -> Result<Self, SetupError> {
+        let next = backup_set_ids(base_dir)?.last().map_or(1, |id| id + 1);
+        Ok(Self {
+            dir: backups_dir(base_dir).join(next.to_string()),
+            entries: Vec::new(),
+        })
+    }
+
+    pub fn write(&mut self, path: &Path, contents: &str) -> Result<(), SetupError> {
+        if fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+            return Ok(());
+        }
+        self.record(path)?;
+        write_atomic(path, contents)
+    }
+
+    pub fn remove(&mut self, path: &Path) -> Result<(), SetupError> {
+        if !path.exists() {
+            return Ok(());
+        }
+        self.record(path)?;
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    fn record(&mut self, path: &Path) -> Result<(), SetupError> {
+        if self.entries.iter().any(|entry| entry.path == path) {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        let backup = if path.exists() {
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let backup = self
+                .dir
+                .join(format!("{}-{file_name}.bak", self.entries.len()));
+            fs::copy(path, &backup)?;
+            Some(backup)
+        } else {
+            None
+        };
+        self.entries.push(BackupEntry {
+            path: path.to_path_buf(),
+            backup,
+        });
+        let index = serde_json::to_string_pretty(&self.entries).map_err(std::io::Error::from)?;
+        write_atomic(&self.dir.join("set.json"), &index)
+    }
+}
+
+fn backup_set_ids(base_dir: &Path) -> Result<Vec<u64>, SetupError> {
+    let entries = match fs::read_dir(backups_dir(base_dir)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut ids = entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .collect::<Vec<u64>>();
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+pub fn rollback(base_dir: &Path) -> Result<Vec<PathBuf>, SetupError> {
+    let id = *backup_set_ids(base_dir)?
+        .last()
+        .ok_or(SetupError::NoBackups)?;
+    let dir = backups_dir(base_dir).join(id.to_string());
+    let entries: Vec<BackupEntry> =
+        serde_json::from_str(&fs::read_to_string(dir.join("set.json"))?)
+            .map_err(std::io::Error::from)?;
+    for entry in entries.iter().rev() {
+        match &entry.backup {
+            Some(backup) => {
+                if let Some(parent) = entry.path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                write_atomic(&entry.path, &fs::read_to_string(backup)?)?;
+            }
+            None if entry.path.exists() => fs::remove_file(&entry.path)?,
+            None => {}
+        }
+    }
+    fs::remove_dir_all(dir)?;
+    Ok(entries.into_iter().map(|entry| entry.path).collect())
+}
+
+/// Renders `setup migrate-windows --preview` as an aligned table.
+pub fn render_migration_preview(targets: &[MigrationTarget]) -> String {
+    if targets.is_empty() {
+        return "no windows to migrate".to_string();
+    }
+    let rows: Vec<[String; 4]> = targets
+        .iter()
+        .map(|target| {
+            [
+                target.address.clone(),
+                target.class.clone().unwrap_or_else(|| "-".to_string()),
+                format!("{} → {}", target.from, target.to),
+                target.title.clone().unwrap_or_default(),
+            ]
+        })
+        .collect();
+    let header = ["ADDRESS", "CLASS", "WORKSPACE", "TITLE"].map(str::to_string);
+    let widths: Vec<usize> = (0..3)
+        .map(|column| {
+            std::iter::once(&header)
+                .chain(&rows)
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            let mut line = String::new();
+            for (column, width) in widths.iter().enumerate() {
+                let cell = &row[column];
+                line.push_str(cell);
+                line.push_str(&" ".repeat(width - cell.chars().count() + 2));
+            }
+            line.push_str(&row[3]);
+            line.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Summarizes a migration per workspace move.
+pub fn render_migration_summary(targets: &[MigrationTarget]) -> String {
+    if targets.is_empty() {
+        return "no windows to migrate".to_string();
+    }
+    let mut moves: std::collections::BTreeMap<(u32, u32), (usize, Vec<&str>)> =
+        std::collections::BTreeMap::new();
+    for target in targets {
+        let (count, classes) = moves.entry((target.from, target.to)).or_default();
+        *count += 1;
+        if let Some(class) = target.class.as_deref()
+            && !classes.contains(&class)
+        {
+            classes.push(class);
+        }
+    }
+    let mut lines = vec![format!("{} window(s) to migrate:", targets.len())];
+    for ((from, to), (count, classes)) in moves {
+        let mut line = format!("  {from} → {to}: {count} window(s)");
+        if !classes.is_empty() {
+            line.push_str(&format!(" ({})", classes.join(", ")));
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        add_source_block, ensure_config, install, migration_targets, remove_source_block,
+        BarPosition, BindingsConfig, InstallManifest, SetupError, WaybarPlacement,
+        add_source_block, eject_waybar_module, ensure_config, generated_artifacts,
+        inject_waybar_module, install, install_xdg_autostart, manifest_path, remove_source_block,
         render_autostart, render_bindings, render_config, render_default_config,
-        render_workspace_rules, select_monitors, uninstall,
+        render_migration_preview, render_migration_summary, render_waybar_config,
+        render_workspace_rules, render_xdg_autostart, restore_on_start, rollback, select_monitors,
+        store_waybar_placement, sync_bindings, sync_workspace_rules, uninstall,
+        uninstall_xdg_autostart, waybar_placement, workspace_rules_path, write_atomic,
     };
-    use crate::hyprctl::{ClientInfo, MonitorInfo, WorkspaceRef};
+    use crate::commands::MigrationTarget;
+    use crate::config::strip_json_comments;
+    use crate::hyprctl::MonitorInfo;
     use serde_json::Value;
     use std::fs;
 
+    #[test]
+    fn renders_migration_preview_table() {
+        let targets = [
+            MigrationTarget {
+                address: "0x55d1".to_string(),
+                class: Some("firefox".to_string()),
+                title: Some("Docs".to_string()),
+                from: 12,
+                to: 2,
+            },
+            MigrationTarget {
+                address: "0x7".to_string(),
+                class: None,
+                title: None,
+                from: 20,
+                to: 10,
+            },
+        ];
+
+        assert_eq!(
+            render_migration_preview(&targets),
+            "ADDRESS  CLASS    WORKSPACE  TITLE\n0x55d1   firefox  12 → 2     Docs\n0x7      -        20 → 10"
+        );
+        assert_eq!(render_migration_preview(&[]), "no windows to migrate");
+    }
+
+    #[test]
+    fn summarizes_migration_per_workspace() {
+        let target = |address: &str, class: Option<&str>, from: u32| MigrationTarget {
+            address: address.to_string(),
+            class: class.map(str::to_string),
+            title: None,
+            from,
+            to: from - 10,
+        };
+        let targets = [
+            target("0x1", Some("kitty"), 15),
+            target("0x2", Some("firefox"), 12),
+            target("0x3", Some("kitty"), 15),
+            target("0x4", None, 12),
+        ];
+
+        assert_eq!(
+            render_migration_summary(&targets),
+            "4 window(s) to migrate:\n  12 → 2: 2 window(s) (firefox)\n  15 → 5: 2 window(s) (kitty)"
+        );
+        assert_eq!(render_migration_summary(&[]), "no windows to migrate");
+    }
+
     #[test]
     fn selects_primary_secondary_by_position() {
         let monitors = vec![
@@ -368,11 +1298,15 @@ mod tests {
                 name: "HDMI-A-1".to_string(),
                 x: 1920,
                 id: 2,
+                mirror_of: None,
+                ..Default::default()
             },
             MonitorInfo {
                 name: "DP-1".to_string(),
                 x: 0,
                 id: 1,
+                mirror_of: None,
+                ..Default::default()
             },
         ];
 
@@ -388,6 +1322,8 @@ mod tests {
             name: "DP-1".to_string(),
             x: 0,
             id: 1,
+            mirror_of: None,
+            ..Default::default()
         }];
 
         let selection = select_monitors(&monitors).expect("selection");
@@ -396,6 +1332,30 @@ mod tests {
         assert_eq!(selection.1, "DP-1");
     }
 
+    #[test]
+    fn skips_mirrored_monitors_when_selecting() {
+        let monitors = vec![
+            MonitorInfo {
+                name: "eDP-1".to_string(),
+                x: 0,
+                id: 0,
+                mirror_of: Some("1".to_string()),
+                ..Default::default()
+            },
+            MonitorInfo {
+                name: "DP-1".to_string(),
+                x: 0,
+                id: 1,
+                mirror_of: Some("none".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        let selection = select_monitors(&monitors).expect("selection");
+
+        assert_eq!(selection, ("DP-1".to_string(), "DP-1".to_string()));
+    }
+
     #[test]
     fn renders_default_config_with_empty_monitors() {
         let value: Value = serde_json::from_str(&render_default_config()).expect("json");
@@ -421,29 +1381,312 @@ mod tests {
 
     #[test]
     fn renders_bindings_with_bin_path() {
-        let bindings = render_bindings("hyprspaces", 3);
+        let bindings = render_bindings("hyprspaces", 3, 1, &BindingsConfig::default());
 
         assert!(bindings.contains("hyprspaces paired switch 1"));
         assert!(bindings.contains("hyprspaces paired switch 3"));
         assert!(!bindings.contains("hyprspaces paired switch 4"));
         assert!(bindings.contains("hyprspaces paired cycle next"));
+        assert!(bindings.contains("bindd = SUPER, code:10, Paired workspace 1, exec"));
+        assert!(bindings.contains("unbind = SUPER SHIFT, LEFT\nunbind = SUPER SHIFT, RIGHT"));
+    }
+
+    #[test]
+    fn renders_customized_bindings() {
+        let config: BindingsConfig = serde_json::from_str(
+            r#"{"modifier":"ALT","keys":"keypad","commands":["switch","move_window"]}"#,
+        )
+        .expect("bindings");
+
+        let bindings = render_bindings("hyprspaces", 11, 1, &config);
+
+        assert!(bindings.contains("bindd = ALT, code:87, Paired workspace 1, exec"));
+        assert!(bindings.contains("bindd = ALT, code:90, Paired workspace 10, exec"));
+        assert!(!bindings.contains("paired switch 11"));
+        assert!(bindings.contains(
+            "bindd = ALT SHIFT, code:88, Move to paired 2, exec, hyprspaces paired move-window 2"
+        ));
+        assert!(!bindings.contains("paired cycle"));
+        assert!(!bindings.contains("movewindow"));
+    }
+
+    #[test]
+    fn routes_bindings_through_the_daemon() {
+        let config = BindingsConfig {
+            via_daemon: true,
+            ..BindingsConfig::default()
+        };
+
+        let bindings = render_bindings("hyprspaces", 2, 1, &config);
+
+        assert!(bindings.contains("Paired workspace 2, exec, hyprspaces send switch 2"));
+        assert!(bindings.contains("Paired next, exec, hyprspaces send cycle next"));
+        assert!(bindings.contains("Move to paired 1, exec, hyprspaces send move-window 1"));
+        assert!(!bindings.contains("hyprspaces paired"));
+    }
+
+    #[test]
+    fn injects_and_ejects_waybar_module_on_matching_bar() {
+        let original = r#"{"position": "top", "modules-left": ["clock"], "include": "extra.json"}"#;
+        let include = std::path::Path::new("/cfg/hyprspaces/waybar/workspaces.json");
+        let placement = WaybarPlacement {
+            index: Some(0),
+            ..WaybarPlacement::default()
+        };
+
+        let injected = inject_waybar_module(original, &placement, include).expect("inject");
+        let json: serde_json::Value = serde_json::from_str(&injected).expect("json");
+        assert_eq!(
+            json["modules-left"],
+            serde_json::json!(["custom/workspaces", "clock"])
+        );
+        assert_eq!(
+            json["include"],
+            serde_json::json!(["extra.json", include.display().to_string()])
+        );
+        assert_eq!(
+            inject_waybar_module(&injected, &placement, include).expect("reinject"),
+            injected
+        );
+
+        let ejected = eject_waybar_module(&injected, &placement, include).expect("eject");
+        let json: serde_json::Value = serde_json::from_str(&ejected).expect("json");
+        assert_eq!(json["modules-left"], serde_json::json!(["clock"]));
+        assert_eq!(json["include"], serde_json::json!(["extra.json"]));
+        assert!(eject_waybar_module(&ejected, &placement, include).is_none());
+
+        let bottom = WaybarPlacement {
+            bar: BarPosition::Bottom,
+            ..WaybarPlacement::default()
+        };
+        let err = inject_waybar_module(original, &bottom, include).expect_err("no bar");
+        assert_eq!(err.to_string(), "no waybar bottom bar found");
+    }
+
+    #[test]
+    fn stores_waybar_placement_without_dropping_comments() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let config_path = dir.path().join("paired.json");
+        fs::write(
+            &config_path,
+            "{\n  // Monitor holding workspaces 1-5.\n  \"primary_monitor\": \"DP-1\",\n  \"secondary_monitor\": \"HDMI-A-1\",\n  \"waybar\": {\n    // Shown in the tooltip.\n    \"tooltip_windows\": true,\n    \"output\": \"DP-1\"\n  }\n}\n",
+        )
+        .expect("write");
+        let placement = WaybarPlacement {
+            bar: BarPosition::Bottom,
+            index: Some(2),
+            ..WaybarPlacement::default()
+        };
+
+        store_waybar_placement(dir.path(), &config_path, &placement).expect("store");
+
+        let contents = fs::read_to_string(&config_path).expect("read");
+        assert!(contents.contains("// Monitor holding workspaces 1-5."));
+        assert!(contents.contains("// Shown in the tooltip."));
+        let saved: Value = serde_json::from_str(&strip_json_comments(&contents)).expect("json");
+        assert_eq!(saved["waybar"]["bar"], "bottom");
+        assert_eq!(saved["waybar"]["index"], 2);
+        assert_eq!(saved["waybar"]["tooltip_windows"], true);
+        assert!(saved["waybar"].get("output").is_none());
+        assert_eq!(
+            waybar_placement(&config_path).expect("placement"),
+            Some(placement)
+        );
+        let backup = dir
+            .path()
+            .join("backups")
+            .join("1")
+            .join("0-paired.json.bak");
+        assert!(
+            fs::read_to_string(backup)
+                .expect("backup")
+                .contains("\"output\": \"DP-1\"")
+        );
+    }
+
+    #[test]
+    fn injects_per_output_modules_into_each_output_bar() {
+        let original = r#"[
+            {"position": "top", "output": "DP-1", "modules-left": ["clock"]},
+            {"position": "top", "output": "HDMI-A-1", "modules-left": ["custom/workspaces"]},
+            {"position": "top", "modules-left": ["tray"]}
+        ]"#;
+        let include = std::path::Path::new("/cfg/hyprspaces/waybar/workspaces.json");
+        let placement = WaybarPlacement {
+            per_output: true,
+            ..WaybarPlacement::default()
+        };
+
+        let injected = inject_waybar_module(original, &placement, include).expect("inject");
+        let bars: serde_json::Value = serde_json::from_str(&injected).expect("json");
+        assert_eq!(
+            bars[0]["modules-left"],
+            serde_json::json!(["clock", "custom/workspaces#DP-1"])
+        );
+        assert_eq!(
+            bars[1]["modules-left"],
+            serde_json::json!(["custom/workspaces#HDMI-A-1"])
+        );
+        assert_eq!(bars[2]["modules-left"], serde_json::json!(["tray"]));
+        assert!(bars[2].get("include").is_none());
+
+        let ejected = eject_waybar_module(&injected, &placement, include).expect("eject");
+        let bars: serde_json::Value = serde_json::from_str(&ejected).expect("json");
+        assert_eq!(bars[0]["modules-left"], serde_json::json!(["clock"]));
+        assert!(bars[1].get("include").is_none());
+    }
+
+    #[test]
+    fn renders_one_waybar_module_per_output() {
+        let config = render_waybar_config(
+            "hyprspaces",
+            std::path::Path::new("/theme.css"),
+            &["DP-1".to_string(), "HDMI-A-1".to_string()],
+        );
+        let modules: serde_json::Value = serde_json::from_str(&config).expect("json");
+
+        assert_eq!(
+            modules["custom/workspaces"]["exec"],
+            "hyprspaces waybar --enable-waybar --theme-css /theme.css"
+        );
+        assert_eq!(
+            modules["custom/workspaces#HDMI-A-1"]["exec"],
+            "hyprspaces waybar --enable-waybar --theme-css /theme.css --monitor HDMI-A-1"
+        );
+        assert_eq!(modules.as_object().expect("object").len(), 3);
+    }
+
+    #[test]
+    fn install_renders_bindings_section_from_config() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = dir.path().join("hyprspaces");
+        let hypr_dir = dir.path().join("hypr");
+        let config_path = dir.path().join("paired.json");
+        fs::write(
+            &config_path,
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","workspace_count":2,"bindings":{"modifier":"SUPER CTRL","commands":["cycle"]}}"#,
+        )
+        .expect("config");
+
+        install(&base_dir, "hyprspaces", &hypr_dir, &config_path, None).expect("install");
+
+        let bindings = fs::read_to_string(base_dir.join("bindings.conf")).expect("read");
+        assert_eq!(
+            bindings,
+            "# hyprspaces keybindings\nbindd = SUPER CTRL, mouse_up, Paired prev, exec, hyprspaces paired cycle prev\nbindd = SUPER CTRL, mouse_down, Paired next, exec, hyprspaces paired cycle next"
+        );
+    }
+
+    #[test]
+    fn sync_bindings_follows_the_workspace_count() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = dir.path().join("hyprspaces");
+        let config_path = dir.path().join("paired.json");
+        let write_config = |count: u32| {
+            fs::write(
+                &config_path,
+                format!(
+                    r#"{{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","workspace_count":{count},"bindings":{{"commands":["switch"]}}}}"#
+                ),
+            )
+            .expect("config");
+        };
+        write_config(2);
+        assert!(!sync_bindings(&base_dir, "hyprspaces", &config_path).expect("sync"));
+
+        install(
+            &base_dir,
+            "hyprspaces",
+            &dir.path().join("hypr"),
+            &config_path,
+            None,
+        )
+        .expect("install");
+        assert!(!sync_bindings(&base_dir, "hyprspaces", &config_path).expect("sync"));
+        write_config(3);
+        assert!(sync_bindings(&base_dir, "hyprspaces", &config_path).expect("sync"));
+
+        let bindings = fs::read_to_string(base_dir.join("bindings.conf")).expect("read");
+        assert!(bindings.ends_with(
+            "bindd = SUPER, code:12, Paired workspace 3, exec, hyprspaces paired switch 3"
+        ));
     }
 
     #[test]
     fn renders_autostart_with_bin_path() {
-        let autostart = render_autostart("hyprspaces");
+        let autostart = render_autostart("hyprspaces", false);
 
-        assert!(autostart.contains("exec-once = hyprspaces daemon"));
+        assert!(autostart.ends_with("exec-once = hyprspaces daemon"));
+    }
+
+    #[test]
+    fn renders_autostart_with_restore_on_start() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let config_path = dir.path().join("paired.json");
+        fs::write(
+            &config_path,
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","restore_on_start":true}"#,
+        )
+        .expect("config");
+
+        let artifacts =
+            generated_artifacts(dir.path(), "hyprspaces", &config_path).expect("artifacts");
+        let autostart = artifacts
+            .iter()
+            .find(|artifact| artifact.path.ends_with("autostart.conf"))
+            .expect("autostart");
+
+        assert!(restore_on_start(&config_path).expect("read"));
+        assert!(
+            autostart
+                .contents
+                .ends_with("exec-once = hyprspaces daemon --restore-on-start")
+        );
+        assert!(
+            render_xdg_autostart("hyprspaces", true)
+                .contains("Exec=hyprspaces daemon --restore-on-start\n")
+        );
     }
 
     #[test]
     fn renders_workspace_rules() {
-        let rules = render_workspace_rules("DP-1", "HDMI-A-1", 2);
+        let rules = render_workspace_rules("DP-1", "HDMI-A-1", 2, 1);
 
         assert!(rules.contains("workspace = 1, monitor:DP-1, persistent:true, default:true"));
         assert!(rules.contains("workspace = 2, monitor:DP-1, persistent:true"));
         assert!(rules.contains("workspace = 3, monitor:HDMI-A-1, persistent:true, default:true"));
         assert!(rules.contains("workspace = 4, monitor:HDMI-A-1, persistent:true"));
+
+        let rules = render_workspace_rules("DP-1", "HDMI-A-1", 2, 0);
+        assert!(rules.contains("workspace = 0, monitor:DP-1, persistent:true, default:true"));
+        assert!(rules.contains("workspace = 2, monitor:HDMI-A-1, persistent:true, default:true"));
+        assert!(rules.ends_with("workspace = 3, monitor:HDMI-A-1, persistent:true"));
+    }
+
+    #[test]
+    fn syncs_workspace_rules_from_resolved_config() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let config = crate::config::Config::builder()
+            .primary_monitor("eDP-1")
+            .secondary_monitor("DP-3")
+            .workspace_count(2)
+            .build()
+            .expect("config");
+
+        assert!(sync_workspace_rules(dir.path(), &config).expect("sync"));
+        let rules = fs::read_to_string(workspace_rules_path(dir.path())).expect("rules");
+        assert_eq!(rules, render_workspace_rules("eDP-1", "DP-3", 2, 1));
+        assert!(!sync_workspace_rules(dir.path(), &config).expect("unchanged"));
+    }
+
+    #[test]
+    fn binds_slot_keys_to_workspaces_from_the_base() {
+        let bindings = render_bindings("hyprspaces", 2, 11, &BindingsConfig::default());
+
+        assert!(bindings.contains(
+            "bindd = SUPER, code:10, Paired workspace 11, exec, hyprspaces paired switch 11"
+        ));
+        assert!(bindings.contains("hyprspaces paired move-window 12"));
     }
 
     #[test]
@@ -466,36 +1709,6 @@ mod tests {
         assert!(updated.contains("line2"));
     }
 
-    #[test]
-    fn derives_migration_targets_for_secondary_workspaces() {
-        let clients = vec![
-            ClientInfo {
-                address: "0x123".to_string(),
-                workspace: WorkspaceRef { id: 12, name: None },
-                class: None,
-                title: None,
-                initial_class: None,
-                initial_title: None,
-                app_id: None,
-                pid: None,
-            },
-            ClientInfo {
-                address: "0x456".to_string(),
-                workspace: WorkspaceRef { id: 1, name: None },
-                class: None,
-                title: None,
-                initial_class: None,
-                initial_title: None,
-                app_id: None,
-                pid: None,
-            },
-        ];
-
-        let targets = migration_targets(&clients, 10);
-
-        assert_eq!(targets, vec![("0x123".to_string(), 2)]);
-    }
-
     #[test]
     fn ensures_config_with_auto_detected_monitors() {
         let dir = tempfile::tempdir().expect("tempdir");
@@ -505,11 +1718,15 @@ mod tests {
                 name: "DP-1".to_string(),
                 x: 0,
                 id: 1,
+                mirror_of: None,
+                ..Default::default()
             },
             MonitorInfo {
                 name: "HDMI-A-1".to_string(),
                 x: 1920,
                 id: 2,
+                mirror_of: None,
+                ..Default::default()
             },
         ];
 
@@ -537,6 +1754,8 @@ mod tests {
             name: "DP-1".to_string(),
             x: 0,
             id: 1,
+            mirror_of: None,
+            ..Default::default()
         }];
 
         install(
@@ -577,6 +1796,115 @@ mod tests {
         assert!(hyprland.contains("BEGIN HYPRSPACES"));
     }
 
+    #[test]
+    fn installs_and_removes_xdg_autostart_entry() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let autostart_dir = dir.path().join("autostart");
+
+        let path =
+            install_xdg_autostart(&autostart_dir, "/usr/bin/hyprspaces", false).expect("install");
+        let entry = fs::read_to_string(&path).expect("read");
+
+        assert_eq!(path, autostart_dir.join("hyprspaces-daemon.desktop"));
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("Exec=/usr/bin/hyprspaces daemon\n"));
+        assert!(entry.contains("OnlyShowIn=Hyprland;\n"));
+        assert!(uninstall_xdg_autostart(&autostart_dir).expect("uninstall"));
+        assert!(!path.exists());
+        assert!(!uninstall_xdg_autostart(&autostart_dir).expect("uninstall again"));
+    }
+
+    #[test]
+    fn uninstall_restores_hypr_configs_byte_for_byte() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = dir.path().join("hyprspaces");
+        let hypr_dir = dir.path().join("hypr");
+        fs::create_dir_all(&hypr_dir).expect("hypr dir");
+        let originals = [
+            ("bindings.conf", "bind = SUPER, Q, killactive"),
+            ("autostart.conf", "exec-once = waybar\n\n\n"),
+            (
+                "hyprland.conf",
+                "# BEGIN HYPRSPACES is a fine comment\nsource = extra.conf\n",
+            ),
+        ];
+        for (file, contents) in originals {
+            fs::write(hypr_dir.join(file), contents).expect("write");
+        }
+        let config_path = dir.path().join("paired.json");
+
+        install(&base_dir, "hyprspaces", &hypr_dir, &config_path, None).expect("install");
+        install(&base_dir, "hyprspaces", &hypr_dir, &config_path, None).expect("reinstall");
+        let manifest = InstallManifest::load(&base_dir).expect("manifest");
+        assert_eq!(manifest.edits.len(), 3);
+        assert_eq!(manifest.files.len(), 3);
+        let hyprland = hypr_dir.join("hyprland.conf");
+        let mut edited = fs::read_to_string(&hyprland).expect("read");
+        edited.push_str("monitor = DP-1, preferred, auto, 1\n");
+        fs::write(&hyprland, edited).expect("user edit");
+
+        uninstall(&base_dir, &hypr_dir).expect("uninstall");
+
+        for (file, contents) in &originals[..2] {
+            assert_eq!(
+                fs::read_to_string(hypr_dir.join(file)).expect("read"),
+                *contents
+            );
+        }
+        assert_eq!(
+            fs::read_to_string(&hyprland).expect("read"),
+            "# BEGIN HYPRSPACES is a fine comment\nsource = extra.conf\nmonitor = DP-1, preferred, auto, 1\n"
+        );
+        assert!(!manifest_path(&base_dir).exists());
+    }
+
+    #[test]
+    fn rollback_restores_latest_backup_set() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base_dir = dir.path().join("hyprspaces");
+        let hypr_dir = dir.path().join("hypr");
+        fs::create_dir_all(&hypr_dir).expect("hypr dir");
+        fs::write(hypr_dir.join("hyprland.conf"), "base").expect("hyprland");
+        let config_path = dir.path().join("paired.json");
+
+        install(&base_dir, "hyprspaces", &hypr_dir, &config_path, None).expect("install");
+        install(&base_dir, "hyprspaces", &hypr_dir, &config_path, None).expect("no-op install");
+        let installed = fs::read_to_string(hypr_dir.join("hyprland.conf")).expect("read");
+        uninstall(&base_dir, &hypr_dir).expect("uninstall");
+        assert_eq!(
+            fs::read_to_string(hypr_dir.join("hyprland.conf")).expect("read"),
+            "base"
+        );
+
+        let restored = rollback(&base_dir).expect("rollback uninstall");
+        assert!(restored.contains(&hypr_dir.join("hyprland.conf")));
+        assert_eq!(
+            fs::read_to_string(hypr_dir.join("hyprland.conf")).expect("read"),
+            installed
+        );
+        assert!(base_dir.join("bindings.conf").exists());
+
+        rollback(&base_dir).expect("rollback install");
+        assert_eq!(
+            fs::read_to_string(hypr_dir.join("hyprland.conf")).expect("read"),
+            "base"
+        );
+        assert!(!base_dir.join("bindings.conf").exists());
+        assert!(matches!(rollback(&base_dir), Err(SetupError::NoBackups)));
+    }
+
+    #[test]
+    fn writes_atomically_without_leaving_temp_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("hyprland.conf");
+        fs::write(&path, "old").expect("write");
+
+        write_atomic(&path, "new").expect("atomic write");
+
+        assert_eq!(fs::read_to_string(&path).expect("read"), "new");
+        assert_eq!(fs::read_dir(dir.path()).expect("dir").count(), 1);
+    }
+
     #[test]
     fn uninstall_removes_fragments_and_blocks() {
         let dir = tempfile::tempdir().expect("tempdir");
@@ -0,0 +1,64 @@
+//! SIGTERM/SIGINT handling for the daemon.
+
+use std::io;
+use std::os::fd::{FromRawFd, IntoRawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+static WAKE_FD: AtomicI32 = AtomicI32::new(-1);
+
+extern "C" fn on_signal(_signum: libc::c_int) {
+    REQUESTED.store(true, Ordering::SeqCst);
+    let fd = WAKE_FD.load(Ordering::SeqCst);
+    if fd >= 0 {
+        let byte = 1u8;
+        // SAFETY: write(2) is async-signal-safe and `byte` outlives the call.
+        unsafe { libc::write(fd, (&raw const byte).cast(), 1) };
+    }
+}
+
+/// Returns a stream that becomes readable once SIGTERM or SIGINT arrives.
+pub fn install() -> io::Result<UnixStream> {
+    let (reader, writer) = UnixStream::pair()?;
+    writer.set_nonblocking(true)?;
+    let previous = WAKE_FD.swap(writer.into_raw_fd(), Ordering::SeqCst);
+    if previous >= 0 {
+        // SAFETY: the previous write end came from `into_raw_fd`.
+        drop(unsafe { UnixStream::from_raw_fd(previous) });
+    }
+    let handler = on_signal as extern "C" fn(libc::c_int) as *const () as libc::sighandler_t;
+    for signum in [libc::SIGINT, libc::SIGTERM] {
+        // SAFETY: `on_signal` only touches atomics and calls write(2).
+        if unsafe { libc::signal(signum, handler) } == libc::SIG_ERR {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(reader)
+}
+
+/// Whether SIGTERM or SIGINT arrived since [`install`].
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{install, requested};
+    use std::io::Read;
+    use std::time::Duration;
+
+    #[test]
+    fn sigterm_sets_the_flag_and_wakes_the_stream() {
+        let mut wake = install().expect("install");
+        wake.set_read_timeout(Some(Duration::from_secs(5)))
+            .expect("timeout");
+
+        // SAFETY: the handler only touches atomics and write(2).
+        assert_eq!(unsafe { libc::raise(libc::SIGTERM) }, 0);
+
+        let mut byte = [0u8; 1];
+        assert_eq!(wake.read(&mut byte).expect("wake"), 1);
+        assert!(requested());
+    }
+}
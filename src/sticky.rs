@@ -0,0 +1,253 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::hyprctl::{
+    ClientInfo, Dispatcher, HyprctlBatch, HyprctlError, HyprlandIpc, WorkspaceTarget,
+};
+use crate::layout::Layout;
+
+#[derive(thiserror::Error, Debug)]
+pub enum StickyError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid sticky state: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("no focused window to stick")]
+    NoActiveWindow,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StickyState {
+    #[serde(default)]
+    addresses: BTreeSet<String>,
+}
+
+pub fn state_path(state_dir: &Path) -> PathBuf {
+    state_dir.join("sticky.json")
+}
+
+pub fn load(path: &Path) -> Result<BTreeSet<String>, StickyError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(serde_json::from_str::<StickyState>(&contents)?.addresses),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(BTreeSet::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Flips `address` in the sticky set; returns whether it is now sticky.
+pub fn toggle(path: &Path, address: &str, clients: &[ClientInfo]) -> Result<bool, StickyError> {
+    let mut addresses = load(path)?;
+    addresses.retain(|sticky| clients.iter().any(|client| client.address == *sticky));
+    let sticky = if addresses.remove(address) {
+        false
+    } else {
+        addresses.insert(address.to_string());
+        true
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(
+        path,
+        serde_json::to_string_pretty(&StickyState { addresses })?,
+    )?;
+    Ok(sticky)
+}
+
+pub fn active_window(clients: &[ClientInfo]) -> Option<&ClientInfo> {
+    clients
+        .iter()
+        .find(|client| client.focus_history_id == Some(0))
+}
+
+pub fn is_sticky(client: &ClientInfo, classes: &[String], addresses: &BTreeSet<String>) -> bool {
+    addresses.contains(&client.address)
+        || [&client.class, &client.initial_class]
+            .into_iter()
+            .flatten()
+            .any(|class| classes.contains(class))
+}
+
+/// Moves sticky windows to the workspace their monitor shows after the switch.
+pub fn follow_batch(
+    clients: &[ClientInfo],
+    layout: &Layout<'_>,
+    workspace: u32,
+    classes: &[String],
+    addresses: &BTreeSet<String>,
+) -> String {
+    let mut batch = HyprctlBatch::new();
+
+    for client in clients {
+        if !is_sticky(client, classes, addresses) {
+            continue;
+        }
+        let Some(monitor) = layout.monitor_for(client.workspace.id) else {
+            continue;
+        };
+        let Some(target) = layout.shown_workspace(workspace, monitor) else {
+            continue;
+        };
+        if target != client.workspace.id {
+            batch.push(Dispatcher::MoveToWorkspaceSilent {
+                target: WorkspaceTarget::Id(target),
+                address: &client.address,
+            });
+        }
+    }
+
+    batch.into_argument()
+}
+
+pub fn follow(
+    hyprctl: &dyn HyprlandIpc,
+    config: &Config,
+    addresses: &BTreeSet<String>,
+    workspace: u32,
+) -> Result<bool, HyprctlError> {
+    if config.sticky_classes.is_empty() && addresses.is_empty() {
+        return Ok(false);
+    }
+    let batch = follow_batch(
+        &hyprctl.clients()?,
+        &Layout::for_config(config),
+        workspace,
+        &config.sticky_classes,
+        addresses,
+    );
+    if batch.is_empty() {
+        return Ok(false);
+    }
+    hyprctl.batch(&batch)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{active_window, follow_batch, load, state_path, toggle};
+    use crate::hyprctl::{ClientInfo, WorkspaceRef};
+    use crate::layout::{Layout, LayoutMonitor};
+    use crate::paths::state_dir;
+    use std::collections::BTreeSet;
+
+    fn client(address: &str, workspace: u32, class: &str) -> ClientInfo {
+        ClientInfo {
+            address: address.to_string(),
+            workspace: WorkspaceRef {
+                id: workspace,
+                name: None,
+            },
+            class: Some(class.to_string()),
+            title: None,
+            initial_class: None,
+            initial_title: None,
+            app_id: None,
+            pid: None,
+            focus_history_id: None,
+            floating: false,
+            at: None,
+            size: None,
+            fullscreen: 0,
+            fullscreen_client: 0,
+            pseudo: false,
+        }
+    }
+
+    #[test]
+    fn moves_sticky_windows_to_the_shown_workspace_on_their_monitor() {
+        let clients = [
+            client("0x1", 1, "mpv"),
+            client("0x2", 11, "mpv"),
+            client("0x3", 2, "kitty"),
+            client("0x4", 12, "pavucontrol"),
+            client("0x5", 3, "mpv"),
+        ];
+        let layout = Layout::paired("DP-1", "HDMI-A-1", 10);
+        let addresses = BTreeSet::from(["0x4".to_string()]);
+
+        let batch = follow_batch(&clients, &layout, 13, &["mpv".to_string()], &addresses);
+
+        assert_eq!(
+            batch,
+            "dispatch movetoworkspacesilent 3,address:0x1 ; dispatch movetoworkspacesilent 13,address:0x2 ; dispatch movetoworkspacesilent 13,address:0x4"
+        );
+    }
+
+    #[test]
+    fn follows_the_focused_stacked_view() {
+        let members = [
+            LayoutMonitor::Primary,
+            LayoutMonitor::Secondary,
+            LayoutMonitor::Secondary,
+        ];
+        let layout = Layout::new("DP-1", "HDMI-A-1", 10, &members);
+        let clients = [client("0x1", 11, "mpv"), client("0x2", 1, "mpv")];
+
+        let batch = follow_batch(
+            &clients,
+            &layout,
+            24,
+            &["mpv".to_string()],
+            &BTreeSet::new(),
+        );
+
+        assert_eq!(
+            batch,
+            "dispatch movetoworkspacesilent 24,address:0x1 ; dispatch movetoworkspacesilent 4,address:0x2"
+        );
+    }
+
+    #[test]
+    fn toggles_sticky_addresses_and_prunes_closed_windows() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("sticky.json");
+        let clients = [client("0x1", 1, "mpv"), client("0x2", 2, "mpv")];
+
+        assert!(toggle(&path, "0x1", &clients).expect("stick"));
+        assert!(toggle(&path, "0x2", &clients[1..]).expect("stick"));
+        assert_eq!(
+            load(&path).expect("load"),
+            BTreeSet::from(["0x2".to_string()])
+        );
+        assert!(!toggle(&path, "0x2", &clients).expect("unstick"));
+        assert!(load(&path).expect("load").is_empty());
+    }
+
+    #[test]
+    fn keeps_sticky_sets_separate_per_instance() {
+        let base = tempfile::tempdir().expect("tempdir");
+        let first = state_path(&state_dir(base.path(), Some("first")));
+        let second = state_path(&state_dir(base.path(), Some("second")));
+
+        assert!(toggle(&first, "0x1", &[client("0x1", 1, "mpv")]).expect("stick"));
+        assert!(toggle(&second, "0x9", &[client("0x9", 1, "mpv")]).expect("stick"));
+
+        assert_eq!(
+            load(&first).expect("load"),
+            BTreeSet::from(["0x1".to_string()])
+        );
+        assert_eq!(
+            load(&second).expect("load"),
+            BTreeSet::from(["0x9".to_string()])
+        );
+    }
+
+    #[test]
+    fn finds_the_focused_window() {
+        let mut focused = client("0x2", 2, "kitty");
+        focused.focus_history_id = Some(0);
+        let mut previous = client("0x1", 1, "mpv");
+        previous.focus_history_id = Some(1);
+        let clients = [previous, focused];
+
+        assert_eq!(
+            active_window(&clients).map(|client| client.address.as_str()),
+            Some("0x2")
+        );
+    }
+}
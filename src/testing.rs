@@ -0,0 +1,827 @@
+//! Hyprland test doubles, public with the `test-util` feature.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::rc::Rc;
+
+use serde_json::json;
+
+use crate::hyprctl::{
+    ClientInfo, Compositor, Hyprctl, HyprctlError, HyprctlRunner, HyprlandIpc, MonitorInfo,
+    OptionInfo, WorkspaceInfo,
+};
+
+#[derive(Default)]
+struct Script {
+    payloads: HashMap<String, String>,
+    queued: HashMap<String, VecDeque<Result<String, HyprctlError>>>,
+    expected: Vec<Vec<String>>,
+    calls: Vec<Vec<String>>,
+    compositor: Option<Compositor>,
+}
+
+/// Replays scripted responses; unscripted JSON queries fail, other calls answer `ok`.
+#[derive(Clone, Default)]
+pub struct ScriptedIpc {
+    script: Rc<RefCell<Script>>,
+}
+
+impl ScriptedIpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers every `query` with `payload` until changed.
+    pub fn with_json(self, query: &str, payload: &str) -> Self {
+        self.set_json(query, payload);
+        self
+    }
+
+    pub fn with_compositor(self, compositor: Compositor) -> Self {
+        self.script.borrow_mut().compositor = Some(compositor);
+        self
+    }
+
+    pub fn set_json(&self, query: &str, payload: &str) {
+        self.script
+            .borrow_mut()
+            .payloads
+            .insert(query.to_string(), payload.to_string());
+    }
+
+    /// Answers the next `query` with `response`.
+    pub fn respond(&self, query: &str, response: &str) {
+        self.queue(query, Ok(response.to_string()));
+    }
+
+    /// Fails the next `query` with `error`.
+    pub fn fail(&self, query: &str, error: HyprctlError) {
+        self.queue(query, Err(error));
+    }
+
+    /// Requires a call with exactly these arguments, in order; see [`Self::verify`].
+    pub fn expect(&self, args: &[&str]) {
+        self.script
+            .borrow_mut()
+            .expected
+            .push(args.iter().map(|arg| arg.to_string()).collect());
+    }
+
+    /// Panics unless every expectation was met in order.
+    pub fn verify(&self) {
+        let script = self.script.borrow();
+        let mut calls = script.calls.iter();
+        for expected in &script.expected {
+            assert!(
+                calls.any(|call| call == expected),
+                "expected hyprctl call {expected:?} was not made in order; calls: {:#?}",
+                script.calls
+            );
+        }
+    }
+
+    /// Every `hyprctl` argument list seen so far, oldest first.
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.script.borrow().calls.clone()
+    }
+
+    pub fn called(&self, args: &[&str]) -> bool {
+        self.script
+            .borrow()
+            .calls
+            .iter()
+            .any(|call| call.iter().map(String::as_str).eq(args.iter().copied()))
+    }
+
+    pub fn clear_calls(&self) {
+        self.script.borrow_mut().calls.clear();
+    }
+
+    fn queue(&self, query: &str, response: Result<String, HyprctlError>) {
+        self.script
+            .borrow_mut()
+            .queued
+            .entry(query.to_string())
+            .or_default()
+            .push_back(response);
+    }
+
+    fn hyprctl(&self) -> Hyprctl<ScriptedIpc> {
+        Hyprctl::new(self.clone())
+    }
+}
+
+fn query_key(args: &[String]) -> (&str, bool) {
+    match args.first().map(String::as_str) {
+        // `swaymsg -t <type>` answers in JSON too.
+        Some("-j" | "-t") => (args.get(1).map_or("", String::as_str), true),
+        Some("--batch") => ("batch", false),
+        Some(command) => (command, false),
+        None => ("", false),
+    }
+}
+
+impl HyprctlRunner for ScriptedIpc {
+    fn run(&self, args: &[String]) -> Result<String, HyprctlError> {
+        let mut script = self.script.borrow_mut();
+        script.calls.push(args.to_vec());
+        let (query, json) = query_key(args);
+        if let Some(response) = script
+            .queued
+            .get_mut(query)
+            .and_then(|queue| queue.pop_front())
+        {
+            return response;
+        }
+        match script.payloads.get(query) {
+            Some(payload) => Ok(payload.clone()),
+            None if json => Err(HyprctlError::CommandFailed {
+                command: format!("hyprctl {}", args.join(" ")),
+                status: 1,
+                stderr: format!("no scripted response for '{query}'"),
+            }),
+            None => Ok("ok".to_string()),
+        }
+    }
+}
+
+impl HyprlandIpc for ScriptedIpc {
+    fn batch(&self, batch: &str) -> Result<String, HyprctlError> {
+        self.hyprctl().batch(batch)
+    }
+
+    fn active_workspace_id(&self) -> Result<u32, HyprctlError> {
+        self.hyprctl().active_workspace_id()
+    }
+
+    fn dispatch(&self, dispatcher: &str, argument: &str) -> Result<String, HyprctlError> {
+        self.hyprctl().dispatch(dispatcher, argument)
+    }
+
+    fn reload(&self) -> Result<String, HyprctlError> {
+        self.hyprctl().reload()
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, HyprctlError> {
+        self.hyprctl().monitors()
+    }
+
+    fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, HyprctlError> {
+        self.hyprctl().workspaces()
+    }
+
+    fn clients(&self) -> Result<Vec<ClientInfo>, HyprctlError> {
+        self.hyprctl().clients()
+    }
+
+    fn get_option(&self, name: &str) -> Result<OptionInfo, HyprctlError> {
+        self.hyprctl().get_option(name)
+    }
+
+    fn keyword(&self, name: &str, value: &str) -> Result<String, HyprctlError> {
+        self.hyprctl().keyword(name, value)
+    }
+
+    fn compositor(&self) -> Compositor {
+        self.script
+            .borrow()
+            .compositor
+            .unwrap_or(Compositor::Hyprland)
+    }
+}
+
+struct FakeMonitor {
+    name: String,
+    x: i32,
+    width: u32,
+    height: u32,
+    shown: u32,
+}
+
+struct FakeClient {
+    address: String,
+    class: String,
+    workspace: u32,
+}
+
+#[derive(Default)]
+struct World {
+    monitors: Vec<FakeMonitor>,
+    /// Workspace id to the monitor it lives on.
+    workspaces: BTreeMap<u32, String>,
+    clients: Vec<FakeClient>,
+    /// Window addresses, most recently focused first.
+    focus_history: Vec<String>,
+    focused: usize,
+    options: BTreeMap<String, i64>,
+    commands: Vec<String>,
+}
+
+impl World {
+    fn monitor_index(&self, name: &str) -> Result<usize, String> {
+        self.monitors
+            .iter()
+            .position(|monitor| monitor.name == name)
+            .ok_or_else(|| format!("no such monitor '{name}'"))
+    }
+
+    fn free_workspace(&self) -> u32 {
+        (1..)
+            .find(|id| !self.workspaces.contains_key(id))
+            .unwrap_or(1)
+    }
+
+    fn focused_name(&self) -> String {
+        self.monitors
+            .get(self.focused)
+            .map(|monitor| monitor.name.clone())
+            .unwrap_or_default()
+    }
+
+    fn add_monitor(&mut self, name: &str, width: u32, height: u32) {
+        let x = self
+            .monitors
+            .iter()
+            .map(|monitor| monitor.x + monitor.width as i32)
+            .max()
+            .unwrap_or(0);
+        let shown = self.free_workspace();
+        self.workspaces.insert(shown, name.to_string());
+        self.monitors.push(FakeMonitor {
+            name: name.to_string(),
+            x,
+            width,
+            height,
+            shown,
+        });
+    }
+
+    /// Like Hyprland, hands the monitor's workspaces to a remaining monitor.
+    fn remove_monitor(&mut self, name: &str) -> Result<(), String> {
+        let index = self.monitor_index(name)?;
+        if self.monitors.len() == 1 {
+            return Err("can't disconnect the last monitor".to_string());
+        }
+        self.monitors.remove(index);
+        let fallback = self.monitors[0].name.clone();
+        for monitor in self.workspaces.values_mut() {
+            if monitor == name {
+                *monitor = fallback.clone();
+            }
+        }
+        if self.focused >= index {
+            self.focused = self.focused.saturating_sub(1);
+        }
+        self.prune();
+        Ok(())
+    }
+
+    fn ensure_workspace(&mut self, id: u32) {
+        if !self.workspaces.contains_key(&id) {
+            let monitor = self.focused_name();
+            self.workspaces.insert(id, monitor);
+        }
+    }
+
+    fn show(&mut self, id: u32) -> Result<(), String> {
+        self.ensure_workspace(id);
+        let index = self.monitor_index(&self.workspaces[&id].clone())?;
+        self.monitors[index].shown = id;
+        self.focused = index;
+        let last_focused = self.focus_history.iter().find(|address| {
+            self.clients
+                .iter()
+                .any(|client| client.address == **address && client.workspace == id)
+        });
+        if let Some(address) = last_focused.cloned() {
+            self.focus_window(&address);
+        }
+        self.prune();
+        Ok(())
+    }
+
+    fn focus_window(&mut self, address: &str) {
+        self.focus_history.retain(|candidate| candidate != address);
+        self.focus_history.insert(0, address.to_string());
+    }
+
+    fn move_workspace(&mut self, id: u32, monitor: &str) -> Result<(), String> {
+        let target = self.monitor_index(monitor)?;
+        let Some(previous) = self.workspaces.get(&id).cloned() else {
+            return Err(format!("no such workspace {id}"));
+        };
+        let source = self.monitor_index(&previous)?;
+        self.workspaces.insert(id, monitor.to_string());
+        self.monitors[target].shown = id;
+        if source != target && self.monitors[source].shown == id {
+            let replacement = self
+                .workspaces
+                .iter()
+                .find(|(_, owner)| **owner == previous)
+                .map(|(id, _)| *id)
+                .unwrap_or_else(|| self.free_workspace());
+            self.workspaces.insert(replacement, previous);
+            self.monitors[source].shown = replacement;
+        }
+        self.prune();
+        Ok(())
+    }
+
+    fn client_mut(&mut self, address: &str) -> Result<&mut FakeClient, String> {
+        self.clients
+            .iter_mut()
+            .find(|client| client.address == address)
+            .ok_or_else(|| format!("no such window '{address}'"))
+    }
+
+    fn move_client(&mut self, argument: &str, follow: bool) -> Result<(), String> {
+        let (target, window) = argument.split_once(',').unwrap_or((argument, ""));
+        let id = parse_workspace(target)?;
+        let address = match window.strip_prefix("address:") {
+            Some(address) => address.to_string(),
+            None if window.is_empty() => self
+                .focus_history
+                .first()
+                .cloned()
+                .ok_or("no focused window")?,
+            None => return Err(format!("unsupported window selector '{window}'")),
+        };
+        self.ensure_workspace(id);
+        self.client_mut(&address)?.workspace = id;
+        if follow {
+            self.show(id)?;
+        }
+        self.prune();
+        Ok(())
+    }
+
+    /// Drops workspaces that are neither shown nor hold a window.
+    fn prune(&mut self) {
+        let monitors = &self.monitors;
+        let clients = &self.clients;
+        self.workspaces.retain(|id, _| {
+            monitors.iter().any(|monitor| monitor.shown == *id)
+                || clients.iter().any(|client| client.workspace == *id)
+        });
+    }
+
+    fn dispatch(&mut self, dispatcher: &str, argument: &str) -> Result<(), String> {
+        match dispatcher {
+            "workspace" => self.show(parse_workspace(argument)?),
+            "focusmonitor" => {
+                self.focused = self.monitor_index(argument)?;
+                Ok(())
+            }
+            "moveworkspacetomonitor" => {
+                let (workspace, monitor) = argument
+                    .split_once(' ')
+                    .ok_or_else(|| format!("bad moveworkspacetomonitor argument '{argument}'"))?;
+                self.move_workspace(parse_workspace(workspace)?, monitor)
+            }
+            "movetoworkspacesilent" => self.move_client(argument, false),
+            "movetoworkspace" => self.move_client(argument, true),
+            "focuswindow" => {
+                let address = argument.strip_prefix("address:").unwrap_or(argument);
+                let workspace = self.client_mut(address)?.workspace;
+                self.show(workspace)?;
+                self.focus_window(address);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn run(&mut self, args: &[String]) -> Result<String, String> {
+        let arg = |index: usize| args.get(index).map_or("", String::as_str);
+        match arg(0) {
+            "-j" => self.query(arg(1), arg(2)),
+            "--batch" => {
+                let mut responses = Vec::new();
+                for command in arg(1).split(';').map(str::trim) {
+                    let mut parts = command.splitn(3, ' ');
+                    let (verb, name) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+                    let value = parts.next().unwrap_or("");
+                    let applied = match verb {
+                        "dispatch" => self.dispatch(name, value),
+                        "keyword" => {
+                            self.keyword(name, value);
+                            Ok(())
+                        }
+                        "" => continue,
+                        _ => Err(format!("unsupported batch command '{command}'")),
+                    };
+                    match applied {
+                        Ok(()) => {
+                            self.commands.push(command.to_string());
+                            responses.push("ok".to_string());
+                        }
+                        Err(err) => responses.push(err),
+                    }
+                }
+                Ok(responses.join("\n\n"))
+            }
+            "dispatch" => {
+                self.dispatch(arg(1), arg(2))?;
+                self.commands
+                    .push(format!("dispatch {} {}", arg(1), arg(2)));
+                Ok("ok".to_string())
+            }
+            "keyword" => {
+                self.keyword(arg(1), arg(2));
+                self.commands.push(format!("keyword {} {}", arg(1), arg(2)));
+                Ok("ok".to_string())
+            }
+            "reload" => {
+                self.commands.push("reload".to_string());
+                Ok("ok".to_string())
+            }
+            other => Err(format!("unsupported hyprctl command '{other}'")),
+        }
+    }
+
+    fn keyword(&mut self, name: &str, value: &str) {
+        if let Ok(value) = value.trim().parse() {
+            self.options.insert(name.to_string(), value);
+        }
+    }
+
+    fn query(&self, query: &str, argument: &str) -> Result<String, String> {
+        let workspace_ref = |id: u32| json!({"id": id, "name": id.to_string()});
+        let payload = match query {
+            "monitors" => json!(
+                self.monitors
+                    .iter()
+                    .enumerate()
+                    .map(|(index, monitor)| json!({
+                        "id": index,
+                        "name": monitor.name,
+                        "x": monitor.x,
+                        "y": 0,
+                        "width": monitor.width,
+                        "height": monitor.height,
+                        "scale": 1.0,
+                        "focused": index == self.focused,
+                        "activeWorkspace": workspace_ref(monitor.shown),
+                    }))
+                    .collect::<Vec<_>>()
+            ),
+            "workspaces" => json!(
+                self.workspaces
+                    .iter()
+                    .map(|(id, monitor)| json!({
+                        "id": id,
+                        "name": id.to_string(),
+                        "monitor": monitor,
+                        "windows": self.clients.iter().filter(|client| client.workspace == *id).count(),
+                    }))
+                    .collect::<Vec<_>>()
+            ),
+            "clients" => json!(
+                self.clients
+                    .iter()
+                    .map(|client| json!({
+                        "address": client.address,
+                        "class": client.class,
+                        "title": client.class,
+                        "workspace": workspace_ref(client.workspace),
+                        "focusHistoryID": self
+                            .focus_history
+                            .iter()
+                            .position(|address| *address == client.address),
+                    }))
+                    .collect::<Vec<_>>()
+            ),
+            "activeworkspace" => {
+                let monitor = self.monitors.get(self.focused).ok_or("no monitors")?;
+                workspace_ref(monitor.shown)
+            }
+            "getoption" => {
+                let value = self.options.get(argument);
+                json!({"option": argument, "int": value.copied().unwrap_or(0), "set": value.is_some()})
+            }
+            _ => return Err(format!("unsupported query '{query}'")),
+        };
+        Ok(payload.to_string())
+    }
+}
+
+fn parse_workspace(argument: &str) -> Result<u32, String> {
+    argument
+        .strip_prefix("name:")
+        .unwrap_or(argument)
+        .parse()
+        .map_err(|_| format!("unsupported workspace '{argument}'"))
+}
+
+/// A [`HyprlandIpc`] backed by a model of a running session; unknown dispatchers are no-ops.
+#[derive(Clone, Default)]
+pub struct FakeHyprland {
+    world: Rc<RefCell<World>>,
+}
+
+impl FakeHyprland {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_monitor(self, name: &str, width: u32, height: u32) -> Self {
+        self.connect_monitor(name, width, height);
+        self
+    }
+
+    pub fn with_window(self, address: &str, class: &str, workspace: u32) -> Self {
+        self.open_window(address, class, workspace);
+        self
+    }
+
+    pub fn with_option(self, name: &str, value: i64) -> Self {
+        self.world
+            .borrow_mut()
+            .options
+            .insert(name.to_string(), value);
+        self
+    }
+
+    pub fn connect_monitor(&self, name: &str, width: u32, height: u32) {
+        self.world.borrow_mut().add_monitor(name, width, height);
+    }
+
+    /// Unplugs `name`; panics if it isn't connected or is the last monitor.
+    pub fn disconnect_monitor(&self, name: &str) {
+        if let Err(err) = self.world.borrow_mut().remove_monitor(name) {
+            panic!("{err}");
+        }
+    }
+
+    /// Opens a window on `workspace` and focuses it.
+    pub fn open_window(&self, address: &str, class: &str, workspace: u32) {
+        let mut world = self.world.borrow_mut();
+        world.ensure_workspace(workspace);
+        world.clients.push(FakeClient {
+            address: address.to_string(),
+            class: class.to_string(),
+            workspace,
+        });
+        world.focus_window(address);
+    }
+
+    pub fn focused_monitor(&self) -> String {
+        self.world.borrow().focused_name()
+    }
+
+    /// The workspace `monitor` shows.
+    pub fn shown(&self, monitor: &str) -> Option<u32> {
+        let world = self.world.borrow();
+        world
+            .monitors
+            .iter()
+            .find(|candidate| candidate.name == monitor)
+            .map(|monitor| monitor.shown)
+    }
+
+    pub fn workspace_monitor(&self, workspace: u32) -> Option<String> {
+        self.world.borrow().workspaces.get(&workspace).cloned()
+    }
+
+    pub fn focused_window(&self) -> Option<String> {
+        self.world.borrow().focus_history.first().cloned()
+    }
+
+    pub fn window_workspace(&self, address: &str) -> Option<u32> {
+        let world = self.world.borrow();
+        world
+            .clients
+            .iter()
+            .find(|client| client.address == address)
+            .map(|client| client.workspace)
+    }
+
+    /// Every dispatch and keyword applied so far, one per batch entry.
+    pub fn commands(&self) -> Vec<String> {
+        self.world.borrow().commands.clone()
+    }
+
+    fn hyprctl(&self) -> Hyprctl<FakeHyprland> {
+        Hyprctl::new(self.clone())
+    }
+}
+
+impl HyprctlRunner for FakeHyprland {
+    fn run(&self, args: &[String]) -> Result<String, HyprctlError> {
+        self.world
+            .borrow_mut()
+            .run(args)
+            .map_err(|stderr| HyprctlError::CommandFailed {
+                command: format!("hyprctl {}", args.join(" ")),
+                status: 1,
+                stderr,
+            })
+    }
+}
+
+impl HyprlandIpc for FakeHyprland {
+    fn batch(&self, batch: &str) -> Result<String, HyprctlError> {
+        self.hyprctl().batch(batch)
+    }
+
+    fn active_workspace_id(&self) -> Result<u32, HyprctlError> {
+        self.hyprctl().active_workspace_id()
+    }
+
+    fn dispatch(&self, dispatcher: &str, argument: &str) -> Result<String, HyprctlError> {
+        self.hyprctl().dispatch(dispatcher, argument)
+    }
+
+    fn reload(&self) -> Result<String, HyprctlError> {
+        self.hyprctl().reload()
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, HyprctlError> {
+        self.hyprctl().monitors()
+    }
+
+    fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, HyprctlError> {
+        self.hyprctl().workspaces()
+    }
+
+    fn clients(&self) -> Result<Vec<ClientInfo>, HyprctlError> {
+        self.hyprctl().clients()
+    }
+
+    fn get_option(&self, name: &str) -> Result<OptionInfo, HyprctlError> {
+        self.hyprctl().get_option(name)
+    }
+
+    fn keyword(&self, name: &str, value: &str) -> Result<String, HyprctlError> {
+        self.hyprctl().keyword(name, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FakeHyprland, ScriptedIpc};
+    use crate::hyprctl::{HyprctlError, HyprlandIpc};
+    use std::io;
+
+    #[test]
+    fn answers_queries_from_payloads_and_queues() {
+        let ipc = ScriptedIpc::new()
+            .with_json("activeworkspace", r#"{"id":3}"#)
+            .with_json("workspaces", "[]");
+        ipc.respond("activeworkspace", r#"{"id":7}"#);
+
+        assert_eq!(ipc.active_workspace_id().expect("queued"), 7);
+        assert_eq!(ipc.active_workspace_id().expect("payload"), 3);
+        assert!(ipc.workspaces().expect("workspaces").is_empty());
+        assert_eq!(ipc.batch("dispatch workspace 1").expect("batch"), "ok");
+        assert!(ipc.called(&["--batch", "dispatch workspace 1"]));
+    }
+
+    #[test]
+    fn injects_failures_once() {
+        let ipc = ScriptedIpc::new().with_json("monitors", "[]");
+        ipc.fail(
+            "monitors",
+            HyprctlError::Io(io::Error::new(io::ErrorKind::ConnectionRefused, "down")),
+        );
+
+        assert!(matches!(ipc.monitors(), Err(HyprctlError::Io(_))));
+        assert!(ipc.monitors().expect("recovered").is_empty());
+    }
+
+    #[test]
+    fn fails_unscripted_json_queries() {
+        let ipc = ScriptedIpc::new();
+
+        let err = ipc.clients().expect_err("unscripted");
+
+        assert!(
+            err.to_string()
+                .contains("no scripted response for 'clients'")
+        );
+        assert_eq!(
+            ipc.calls(),
+            vec![vec!["-j".to_string(), "clients".to_string()]]
+        );
+    }
+
+    #[test]
+    fn verifies_expectations_in_order() {
+        let ipc = ScriptedIpc::new();
+        ipc.expect(&["dispatch", "workspace", "1"]);
+        ipc.expect(&["reload"]);
+
+        ipc.dispatch("workspace", "1").expect("dispatch");
+        ipc.keyword("general:gaps_in", "4").expect("keyword");
+        ipc.reload().expect("reload");
+
+        ipc.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "was not made in order")]
+    fn verify_panics_on_missing_calls() {
+        let ipc = ScriptedIpc::new();
+        ipc.expect(&["reload"]);
+        ipc.expect(&["dispatch", "workspace", "1"]);
+
+        ipc.dispatch("workspace", "1").expect("dispatch");
+        ipc.reload().expect("reload");
+
+        ipc.verify();
+    }
+
+    fn fake() -> FakeHyprland {
+        FakeHyprland::new()
+            .with_monitor("DP-1", 2560, 1440)
+            .with_monitor("HDMI-A-1", 1920, 1080)
+    }
+
+    #[test]
+    fn fake_reports_a_consistent_session() {
+        let hyprland = fake().with_window("0x1", "kitty", 4);
+
+        let monitors = hyprland.monitors().expect("monitors");
+        let workspaces = hyprland.workspaces().expect("workspaces");
+        let clients = hyprland.clients().expect("clients");
+
+        assert_eq!(
+            monitors
+                .iter()
+                .map(|monitor| (monitor.name.as_str(), monitor.x, monitor.active_workspace))
+                .collect::<Vec<_>>(),
+            vec![("DP-1", 0, Some(1)), ("HDMI-A-1", 2560, Some(2))]
+        );
+        assert!(monitors[0].focused);
+        assert_eq!(
+            workspaces
+                .iter()
+                .map(|workspace| (
+                    workspace.id,
+                    workspace.windows,
+                    workspace.monitor.as_deref()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, 0, Some("DP-1")),
+                (2, 0, Some("HDMI-A-1")),
+                (4, 1, Some("DP-1"))
+            ]
+        );
+        assert_eq!(clients[0].workspace.id, 4);
+        assert_eq!(hyprland.active_workspace_id().expect("active"), 1);
+    }
+
+    #[test]
+    fn fake_switches_workspaces_and_drops_empty_ones() {
+        let hyprland = fake().with_window("0x1", "kitty", 1);
+
+        hyprland.dispatch("workspace", "3").expect("switch");
+        hyprland.dispatch("workspace", "name:2").expect("switch");
+
+        assert_eq!(hyprland.shown("DP-1"), Some(3));
+        assert_eq!(hyprland.focused_monitor(), "HDMI-A-1");
+        assert_eq!(hyprland.workspace_monitor(1).as_deref(), Some("DP-1"));
+        hyprland.dispatch("workspace", "1").expect("switch");
+        assert_eq!(hyprland.workspace_monitor(3), None);
+    }
+
+    #[test]
+    fn fake_moves_workspaces_and_windows() {
+        let hyprland = fake().with_window("0x1", "kitty", 1);
+
+        hyprland
+            .batch("dispatch moveworkspacetomonitor 1 HDMI-A-1 ; dispatch movetoworkspacesilent 5,address:0x1")
+            .expect("batch");
+
+        assert_eq!(hyprland.shown("HDMI-A-1"), Some(1));
+        assert_eq!(hyprland.shown("DP-1"), Some(3));
+        assert_eq!(hyprland.window_workspace("0x1"), Some(5));
+        assert_eq!(hyprland.workspace_monitor(5).as_deref(), Some("DP-1"));
+        assert_eq!(hyprland.workspace_monitor(1).as_deref(), Some("HDMI-A-1"));
+    }
+
+    #[test]
+    fn fake_batches_skip_failing_entries_but_dispatch_fails() {
+        let hyprland = fake();
+
+        let output = hyprland
+            .batch("dispatch moveworkspacetomonitor 9 DP-1 ; dispatch workspace 4")
+            .expect("batch");
+
+        assert_eq!(output, "no such workspace 9\n\nok");
+        assert_eq!(hyprland.commands(), vec!["dispatch workspace 4"]);
+        assert!(hyprland.dispatch("focusmonitor", "DP-9").is_err());
+    }
+
+    #[test]
+    fn fake_hands_workspaces_over_on_disconnect() {
+        let hyprland = fake().with_window("0x1", "kitty", 2);
+
+        hyprland.disconnect_monitor("HDMI-A-1");
+        hyprland.connect_monitor("HDMI-A-1", 1920, 1080);
+
+        assert_eq!(hyprland.workspace_monitor(2).as_deref(), Some("DP-1"));
+        assert_eq!(hyprland.shown("HDMI-A-1"), Some(3));
+    }
+}
@@ -0,0 +1,327 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::hyprctl::{ClientInfo, HyprctlError, HyprlandIpc};
+use crate::layout::Layout;
+
+const TITLE_WIDTH: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SlotView {
+    pub workspace_id: u32,
+    pub titles: Vec<String>,
+}
+
+/// One slot's workspace on every layout member, primary first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PairView {
+    pub slot: u32,
+    pub members: Vec<SlotView>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Overview {
+    pub monitors: Vec<String>,
+    pub active_slot: Option<u32>,
+    pub pairs: Vec<PairView>,
+}
+
+impl Overview {
+    pub fn build(config: &Config, clients: &[ClientInfo], active_workspace: Option<u32>) -> Self {
+        let layout = Layout::for_config(config);
+        let view = |workspace_id: u32| SlotView {
+            workspace_id,
+            titles: clients
+                .iter()
+                .filter(|client| client.workspace.id == workspace_id)
+                .map(client_label)
+                .collect(),
+        };
+        let pairs = (1..=layout.offset())
+            .map(|slot| PairView {
+                slot,
+                members: (0..layout.member_count())
+                    .map(|member| view(layout.workspace(slot, member)))
+                    .collect(),
+            })
+            .collect();
+        Self {
+            monitors: layout.monitors().map(str::to_string).collect(),
+            active_slot: active_workspace
+                .filter(|workspace| layout.member_of(*workspace).is_some())
+                .map(|workspace| layout.slot(workspace)),
+            pairs,
+        }
+    }
+}
+
+pub fn overview(hyprctl: &dyn HyprlandIpc, config: &Config) -> Result<Overview, HyprctlError> {
+    let clients = hyprctl.clients()?;
+    let active = hyprctl.active_workspace_id().ok();
+    Ok(Overview::build(config, &clients, active))
+}
+
+fn client_label(client: &ClientInfo) -> String {
+    client
+        .title
+        .as_deref()
+        .filter(|title| !title.is_empty())
+        .or(client.class.as_deref())
+        .unwrap_or(&client.address)
+        .to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TuiAction {
+    Up,
+    Down,
+    Select(u32),
+    Switch,
+    MoveWindow,
+    SaveSession,
+    Quit,
+}
+
+pub fn parse_keys(input: &[u8]) -> Vec<TuiAction> {
+    let mut actions = Vec::new();
+    let mut idx = 0;
+    while idx < input.len() {
+        if input[idx..].starts_with(b"\x1b[A") {
+            actions.push(TuiAction::Up);
+            idx += 3;
+            continue;
+        }
+        if input[idx..].starts_with(b"\x1b[B") {
+            actions.push(TuiAction::Down);
+            idx += 3;
+            continue;
+        }
+        let action = match input[idx] {
+            b'k' => Some(TuiAction::Up),
+            b'j' => Some(TuiAction::Down),
+            b'\r' | b'\n' => Some(TuiAction::Switch),
+            b'm' => Some(TuiAction::MoveWindow),
+            b's' => Some(TuiAction::SaveSession),
+            b'q' | 0x03 => Some(TuiAction::Quit),
+            digit @ b'1'..=b'9' => Some(TuiAction::Select(u32::from(digit - b'0'))),
+            b'0' => Some(TuiAction::Select(10)),
+            _ => None,
+        };
+        actions.extend(action);
+        idx += 1;
+    }
+    actions
+}
+
+pub fn move_selection(selected: u32, action: TuiAction, count: u32) -> u32 {
+    if count == 0 {
+        return selected;
+    }
+    match action {
+        TuiAction::Up if selected > 1 => selected - 1,
+        TuiAction::Down if selected < count => selected + 1,
+        TuiAction::Select(slot) if slot >= 1 && slot <= count => slot,
+        _ => selected,
+    }
+}
+
+pub fn render_frame(overview: &Overview, selected: u32, status: &str) -> String {
+    let header = overview
+        .monitors
+        .iter()
+        .enumerate()
+        .map(|(member, monitor)| match member {
+            0 => "primary".to_string(),
+            1 => "secondary".to_string(),
+            _ => monitor.clone(),
+        })
+        .collect::<Vec<_>>();
+    let mut lines = vec![
+        format!("hyprspaces pairs ({})", overview.monitors.join(" | ")),
+        String::new(),
+        format!("     slot  {}", columns(&header)),
+    ];
+    for pair in &overview.pairs {
+        let cursor = if pair.slot == selected { '>' } else { ' ' };
+        let active = if overview.active_slot == Some(pair.slot) {
+            '*'
+        } else {
+            ' '
+        };
+        let cells = pair.members.iter().map(slot_cell).collect::<Vec<_>>();
+        lines.push(format!(
+            "  {cursor} {active}{:>3}   {}",
+            pair.slot,
+            columns(&cells)
+        ));
+    }
+    lines.push(String::new());
+    lines.push(
+        "j/k select  1-0 jump  enter switch  m move window  s save session  q quit".to_string(),
+    );
+    if !status.is_empty() {
+        lines.push(status.to_string());
+    }
+    lines.join("\r\n")
+}
+
+/// Pads every cell but the last to a fixed column width.
+fn columns(cells: &[String]) -> String {
+    let width = TITLE_WIDTH + 4;
+    let mut line = String::new();
+    for (index, cell) in cells.iter().enumerate() {
+        if index + 1 == cells.len() {
+            line.push_str(cell);
+        } else {
+            line.push_str(&format!("{cell:<width$}  "));
+        }
+    }
+    line
+}
+
+fn slot_cell(slot: &SlotView) -> String {
+    let titles = truncate(&slot.titles.join(", "), TITLE_WIDTH);
+    format!("[{}] {titles}", slot.titles.len())
+}
+
+fn truncate(value: &str, width: usize) -> String {
+    if value.chars().count() <= width {
+        return value.to_string();
+    }
+    let mut truncated = value.chars().take(width - 1).collect::<String>();
+    truncated.push('~');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Overview, TuiAction, move_selection, parse_keys, render_frame};
+    use crate::config::Config;
+    use crate::hyprctl::{ClientInfo, WorkspaceRef};
+    use crate::layout::LayoutMonitor;
+
+    fn config() -> Config {
+        Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(3)
+            .build()
+            .expect("config")
+    }
+
+    fn client(address: &str, workspace: u32, title: &str) -> ClientInfo {
+        ClientInfo {
+            address: address.to_string(),
+            workspace: WorkspaceRef {
+                id: workspace,
+                name: None,
+            },
+            class: Some("kitty".to_string()),
+            title: Some(title.to_string()),
+            initial_class: None,
+            initial_title: None,
+            app_id: None,
+            pid: None,
+            focus_history_id: None,
+            floating: false,
+            at: None,
+            size: None,
+            fullscreen: 0,
+            fullscreen_client: 0,
+            pseudo: false,
+        }
+    }
+
+    #[test]
+    fn builds_pairs_with_titles_and_active_slot() {
+        let clients = vec![
+            client("0x1", 1, "editor"),
+            client("0x2", 5, "browser"),
+            client("0x3", 5, ""),
+        ];
+
+        let overview = Overview::build(&config(), &clients, Some(5));
+
+        assert_eq!(overview.active_slot, Some(2));
+        assert_eq!(overview.pairs.len(), 3);
+        assert_eq!(overview.pairs[0].members[0].titles, vec!["editor"]);
+        assert_eq!(overview.pairs[1].members[1].workspace_id, 5);
+        assert_eq!(
+            overview.pairs[1].members[1].titles,
+            vec!["browser", "kitty"]
+        );
+    }
+
+    #[test]
+    fn follows_the_configured_base_and_layout_members() {
+        let config = Config::builder()
+            .primary_monitor("DP-1")
+            .secondary_monitor("HDMI-A-1")
+            .workspace_count(3)
+            .workspace_base(11)
+            .layout(vec![
+                LayoutMonitor::Primary,
+                LayoutMonitor::Secondary,
+                LayoutMonitor::Named("DP-3".to_string()),
+            ])
+            .build()
+            .expect("config");
+
+        let overview = Overview::build(&config, &[client("0x1", 18, "chat")], Some(19));
+
+        assert_eq!(overview.monitors, vec!["DP-1", "HDMI-A-1", "DP-3"]);
+        assert_eq!(overview.active_slot, Some(3));
+        let ids = overview.pairs[1]
+            .members
+            .iter()
+            .map(|view| view.workspace_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![12, 15, 18]);
+        assert_eq!(overview.pairs[1].members[2].titles, vec!["chat"]);
+        assert_eq!(Overview::build(&config, &[], Some(10)).active_slot, None);
+        assert_eq!(Overview::build(&config, &[], Some(20)).active_slot, None);
+
+        let frame = render_frame(&overview, 2, "");
+        assert!(frame.starts_with("hyprspaces pairs (DP-1 | HDMI-A-1 | DP-3)"));
+        assert!(frame.contains("[1] chat"));
+    }
+
+    #[test]
+    fn parses_keys_and_arrow_sequences() {
+        assert_eq!(
+            parse_keys(b"j\x1b[Ak3\rmsqx"),
+            vec![
+                TuiAction::Down,
+                TuiAction::Up,
+                TuiAction::Up,
+                TuiAction::Select(3),
+                TuiAction::Switch,
+                TuiAction::MoveWindow,
+                TuiAction::SaveSession,
+                TuiAction::Quit,
+            ]
+        );
+    }
+
+    #[test]
+    fn clamps_selection_to_slot_range() {
+        assert_eq!(move_selection(1, TuiAction::Up, 3), 1);
+        assert_eq!(move_selection(3, TuiAction::Down, 3), 3);
+        assert_eq!(move_selection(2, TuiAction::Down, 3), 3);
+        assert_eq!(move_selection(2, TuiAction::Select(9), 3), 2);
+        assert_eq!(move_selection(2, TuiAction::Select(1), 3), 1);
+    }
+
+    #[test]
+    fn renders_selected_and_active_markers() {
+        let overview = Overview::build(&config(), &[client("0x1", 4, "notes")], Some(1));
+
+        let frame = render_frame(&overview, 1, "switched to 1");
+        let lines = frame.split("\r\n").collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "hyprspaces pairs (DP-1 | HDMI-A-1)");
+        assert!(lines[3].starts_with("  > *  1   [0] "));
+        assert!(lines[3].ends_with("[1] notes"));
+        assert_eq!(lines.last().copied(), Some("switched to 1"));
+    }
+}
@@ -0,0 +1,704 @@
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, DebounceMode, MAX_DEBOUNCE_MS, MonitorMatch, strip_json_comments};
+use crate::hyprctl::{HyprlandOptions, MonitorInfo, WORKSPACE_BACK_AND_FORTH, is_batch_safe};
+use crate::layout::LayoutMonitor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{label}: {}", self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinnedWorkspace {
+    pub workspace_id: u32,
+    pub monitor: String,
+    pub source: PathBuf,
+    pub line: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawOffsets {
+    #[serde(default)]
+    paired_offset: Option<u32>,
+    #[serde(default)]
+    workspace_count: Option<u32>,
+}
+
+pub fn validate_config(
+    contents: &str,
+    monitors: Result<&[MonitorInfo], String>,
+    pinned: &[PinnedWorkspace],
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut config = match Config::from_json(contents) {
+        Ok(config) => config,
+        Err(err) => {
+            diagnostics.push(Diagnostic::error(err.to_string()));
+            return diagnostics;
+        }
+    };
+    if let Ok(monitors) = monitors {
+        config.resolve_monitors(monitors);
+    }
+
+    if let Ok(raw) = serde_json::from_str::<RawOffsets>(&strip_json_comments(contents))
+        && let (Some(offset), Some(count)) = (raw.paired_offset, raw.workspace_count)
+    {
+        if offset < count {
+            diagnostics.push(Diagnostic::error(format!(
+                "paired_offset ({offset}) is smaller than workspace_count ({count}); secondary workspaces would overlap the primary range"
+            )));
+        } else if offset > count {
+            diagnostics.push(Diagnostic::warning(format!(
+                "paired_offset ({offset}) is ignored because workspace_count ({count}) is set"
+            )));
+        }
+    }
+
+    let same_monitor = if config.primary_monitor.is_empty() {
+        config.primary_match.is_some()
+            && config.primary_match != Some(MonitorMatch::Auto)
+            && config.primary_match == config.secondary_match
+    } else {
+        config.primary_monitor == config.secondary_monitor
+    };
+    if same_monitor {
+        diagnostics.push(Diagnostic::warning(format!(
+            "primary_monitor and secondary_monitor are both '{}'",
+            config.primary_monitor
+        )));
+    }
+
+    match monitors {
+        Ok(monitors) => {
+            let available = monitors
+                .iter()
+                .map(|monitor| monitor.name.as_str())
+                .collect::<Vec<_>>();
+            for (field, name, selector) in [
+                (
+                    "primary_monitor",
+                    &config.primary_monitor,
+                    &config.primary_match,
+                ),
+                (
+                    "secondary_monitor",
+                    &config.secondary_monitor,
+                    &config.secondary_match,
+                ),
+            ] {
+                if let Some(selector) = selector.as_ref().filter(|_| name.is_empty()) {
+                    diagnostics.push(Diagnostic::error(format!(
+                        "{field} {selector} matches no connected monitor (available: {})",
+                        available.join(", ")
+                    )));
+                } else if !available.contains(&name.as_str()) {
+                    diagnostics.push(Diagnostic::error(format!(
+                        "{field} '{name}' is not connected (available: {})",
+                        available.join(", ")
+                    )));
+                } else if let Some(monitor) = monitors
+                    .iter()
+                    .find(|monitor| monitor.name == *name && monitor.is_mirror())
+                {
+                    diagnostics.push(Diagnostic::warning(format!(
+                        "{field} '{name}' mirrors {}; pairing treats it as disconnected",
+                        monitor.mirror_of.as_deref().unwrap_or_default()
+                    )));
+                }
+            }
+            for member in &config.layout {
+                if let LayoutMonitor::Named(name) = member
+                    && !available.contains(&name.as_str())
+                {
+                    diagnostics.push(Diagnostic::warning(format!(
+                        "layout monitor '{name}' is not connected"
+                    )));
+                }
+            }
+        }
+        Err(err) => {
+            diagnostics.push(Diagnostic::warning(format!(
+                "could not query monitors, skipping monitor checks: {err}"
+            )));
+        }
+    }
+
+    diagnostics.extend(offline_diagnostics(&config));
+    diagnostics.extend(pinned_diagnostics(&config, pinned));
+    diagnostics
+}
+
+pub fn config_errors(config: &Config) -> Vec<String> {
+    offline_diagnostics(config)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .map(|diagnostic| diagnostic.message)
+        .collect()
+}
+
+/// Monitor names that could not be written into a hyprctl batch intact.
+pub fn monitor_name_errors(config: &Config) -> Vec<String> {
+    let mut names = vec![
+        ("primary_monitor", config.primary_monitor.as_str()),
+        ("secondary_monitor", config.secondary_monitor.as_str()),
+    ];
+    for member in &config.layout {
+        if let LayoutMonitor::Named(name) = member {
+            names.push(("layout monitor", name));
+        }
+    }
+    for profile in &config.profiles {
+        names.push(("profile primary_monitor", &profile.primary_monitor));
+        names.push(("profile secondary_monitor", &profile.secondary_monitor));
+        names.extend(
+            profile
+                .monitors
+                .iter()
+                .map(|name| ("profile monitor", name.as_str())),
+        );
+    }
+    names
+        .into_iter()
+        .filter(|(_, name)| !is_batch_safe(name) || name.contains(char::is_whitespace))
+        .map(|(field, name)| {
+            format!(
+                "{field} '{}' is not a valid monitor name (no spaces, ';' or ',')",
+                name.escape_debug()
+            )
+        })
+        .collect()
+}
+
+fn offline_diagnostics(config: &Config) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    diagnostics.extend(
+        monitor_name_errors(config)
+            .into_iter()
+            .map(Diagnostic::error),
+    );
+    if config.workspace_count == 0 {
+        diagnostics.push(Diagnostic::error("workspace_count must be at least 1"));
+    }
+    if config.retry.attempts == 0 {
+        diagnostics.push(Diagnostic::error("retry.attempts must be at least 1"));
+    }
+    if !(1..=MAX_DEBOUNCE_MS).contains(&config.debounce.rebalance_ms) {
+        diagnostics.push(Diagnostic::error(format!(
+            "debounce.rebalance_ms must be between 1 and {MAX_DEBOUNCE_MS}"
+        )));
+    }
+    if config.debounce.focus_ms > MAX_DEBOUNCE_MS {
+        diagnostics.push(Diagnostic::error(format!(
+            "debounce.focus_ms must be at most {MAX_DEBOUNCE_MS}"
+        )));
+    }
+    if config.debounce.mode == DebounceMode::Adaptive
+        && !(config.debounce.rebalance_ms..=MAX_DEBOUNCE_MS)
+            .contains(&config.debounce.max_rebalance_ms)
+    {
+        diagnostics.push(Diagnostic::error(format!(
+            "debounce.max_rebalance_ms must be between debounce.rebalance_ms and {MAX_DEBOUNCE_MS}"
+        )));
+    }
+    if config.layout.len() == 1 {
+        diagnostics.push(Diagnostic::error(
+            "layout needs at least two entries (or none for the default pair)",
+        ));
+    }
+    diagnostics.extend(schedule_diagnostics(config));
+    diagnostics.extend(idle_inhibit_diagnostics(config));
+    diagnostics.extend(name_diagnostics(config));
+    diagnostics
+}
+
+fn schedule_diagnostics(config: &Config) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (idx, schedule) in config.schedules.iter().enumerate() {
+        if schedule.slot == 0 || schedule.slot > config.workspace_count {
+            diagnostics.push(Diagnostic::error(format!(
+                "schedule '{}' targets slot {}, outside 1-{}",
+                schedule.name, schedule.slot, config.workspace_count
+            )));
+        }
+        if config.schedules[..idx]
+            .iter()
+            .any(|other| other.name == schedule.name)
+        {
+            diagnostics.push(Diagnostic::error(format!(
+                "schedule name '{}' is used more than once",
+                schedule.name
+            )));
+        }
+    }
+    diagnostics
+}
+
+fn idle_inhibit_diagnostics(config: &Config) -> Vec<Diagnostic> {
+    config
+        .idle_inhibit_slots
+        .iter()
+        .filter(|slot| **slot == 0 || **slot > config.workspace_count)
+        .map(|slot| {
+            Diagnostic::error(format!(
+                "idle_inhibit_slots contains slot {slot}, outside 1-{}",
+                config.workspace_count
+            ))
+        })
+        .collect()
+}
+
+fn name_diagnostics(config: &Config) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (slot, name) in &config.names {
+        if *slot == 0 || *slot > config.workspace_count {
+            diagnostics.push(Diagnostic::error(format!(
+                "name '{name}' is set for slot {slot}, outside 1-{}",
+                config.workspace_count
+            )));
+        }
+        if name.is_empty() || name.parse::<u32>().is_ok() {
+            diagnostics.push(Diagnostic::error(format!(
+                "slot {slot} name '{name}' must be non-empty and not a number"
+            )));
+        }
+        if config.slot_for_name(name) != Some(*slot) {
+            diagnostics.push(Diagnostic::error(format!(
+                "name '{name}' is used for more than one slot"
+            )));
+        }
+    }
+    diagnostics
+}
+
+fn pinned_diagnostics(config: &Config, pinned: &[PinnedWorkspace]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let offset = config.paired_offset;
+    for (idx, rule) in pinned.iter().enumerate() {
+        let index = rule.workspace_id.checked_sub(config.workspace_base);
+        let expected = match index.map(|index| index / offset) {
+            Some(0) => &config.primary_monitor,
+            Some(1) => &config.secondary_monitor,
+            _ => continue,
+        };
+        if !expected.is_empty() && &rule.monitor != expected {
+            diagnostics.push(Diagnostic::error(format!(
+                "workspace {} is pinned to '{}' at {}:{}, but hyprspaces expects '{}'",
+                rule.workspace_id,
+                rule.monitor,
+                rule.source.display(),
+                rule.line,
+                expected
+            )));
+        }
+        if let Some(previous) = pinned[..idx]
+            .iter()
+            .find(|other| other.workspace_id == rule.workspace_id && other.monitor != rule.monitor)
+        {
+            diagnostics.push(Diagnostic::warning(format!(
+                "workspace {} is pinned to both '{}' ({}:{}) and '{}' ({}:{})",
+                rule.workspace_id,
+                previous.monitor,
+                previous.source.display(),
+                previous.line,
+                rule.monitor,
+                rule.source.display(),
+                rule.line
+            )));
+        }
+    }
+    diagnostics
+}
+
+pub fn option_diagnostics(options: &HyprlandOptions) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    if options.workspace_back_and_forth {
+        diagnostics.push(Diagnostic::warning(format!(
+            "{WORKSPACE_BACK_AND_FORTH} is enabled; switching to the active pair toggles each monitor back to its own previous workspace"
+        )));
+    }
+    diagnostics
+}
+
+pub fn parse_pinned_workspaces(contents: &str, source: &Path) -> Vec<PinnedWorkspace> {
+    contents
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let (key, value) = line.split_once('=')?;
+            if key.trim() != "workspace" {
+                return None;
+            }
+            let mut fields = value.split(',').map(str::trim);
+            let workspace_id = fields.next()?.parse().ok()?;
+            let monitor = fields.find_map(|field| field.strip_prefix("monitor:"))?;
+            Some(PinnedWorkspace {
+                workspace_id,
+                monitor: monitor.trim().to_string(),
+                source: source.to_path_buf(),
+                line: idx + 1,
+            })
+        })
+        .collect()
+}
+
+pub fn pinned_workspaces_in_dir(dir: &Path) -> Vec<PinnedWorkspace> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "conf"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+        .iter()
+        .filter_map(|path| {
+            fs::read_to_string(path)
+                .ok()
+                .map(|contents| parse_pinned_workspaces(&contents, path))
+        })
+        .flatten()
+        .collect()
+}
+
+pub fn count_severity(diagnostics: &[Diagnostic], severity: Severity) -> usize {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == severity)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        PinnedWorkspace, Severity, count_severity, option_diagnostics, parse_pinned_workspaces,
+        pinned_workspaces_in_dir, validate_config,
+    };
+    use crate::hyprctl::{HyprlandOptions, MonitorInfo};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn monitors() -> Vec<MonitorInfo> {
+        vec![
+            MonitorInfo {
+                name: "DP-1".to_string(),
+                x: 0,
+                id: 1,
+                mirror_of: None,
+                ..Default::default()
+            },
+            MonitorInfo {
+                name: "HDMI-A-1".to_string(),
+                x: 1920,
+                id: 2,
+                mirror_of: None,
+                ..Default::default()
+            },
+        ]
+    }
+
+    const VALID: &str = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1"}"#;
+
+    #[test]
+    fn accepts_valid_config() {
+        let monitors = monitors();
+
+        let diagnostics = validate_config(VALID, Ok(&monitors), &[]);
+
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let monitors = monitors();
+
+        let diagnostics = validate_config(
+            r#"{"primary_monitor":"DP-1","secondary_monitor":""}"#,
+            Ok(&monitors),
+            &[],
+        );
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert!(diagnostics[0].message.contains("secondary_monitor"));
+    }
+
+    #[test]
+    fn reports_disconnected_monitors() {
+        let monitors = monitors();
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"DP-2"}"#;
+
+        let diagnostics = validate_config(input, Ok(&monitors), &[]);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Error), 1);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: secondary_monitor 'DP-2' is not connected (available: DP-1, HDMI-A-1)"
+        );
+    }
+
+    #[test]
+    fn resolves_monitor_matches_against_connected_monitors() {
+        let mut monitors = monitors();
+        monitors[1].description = Some("LG Electronics LG ULTRAGEAR 0x0001".to_string());
+        let input = r#"{"primary_monitor":{"serial":"ABC123"},"secondary_monitor":{"description":"LG ULTRAGEAR"}}"#;
+
+        let diagnostics = validate_config(input, Ok(&monitors), &[]);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Error), 1);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: primary_monitor serial 'ABC123' matches no connected monitor (available: DP-1, HDMI-A-1)"
+        );
+    }
+
+    #[test]
+    fn warns_about_mirrored_monitors() {
+        let mut monitors = monitors();
+        monitors[1].mirror_of = Some("1".to_string());
+
+        let diagnostics = validate_config(VALID, Ok(&monitors), &[]);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Warning), 1);
+        assert!(
+            diagnostics
+                .iter()
+                .any(|diagnostic| diagnostic.to_string()
+                    == "warning: secondary_monitor 'HDMI-A-1' mirrors 1; pairing treats it as disconnected")
+        );
+    }
+
+    #[test]
+    fn warns_when_monitors_unavailable() {
+        let diagnostics = validate_config(VALID, Err("no socket".to_string()), &[]);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Warning), 1);
+        assert_eq!(count_severity(&diagnostics, Severity::Error), 0);
+    }
+
+    #[test]
+    fn rejects_offset_smaller_than_workspace_count() {
+        let monitors = monitors();
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","paired_offset":8,"workspace_count":10}"#;
+
+        let diagnostics = validate_config(input, Ok(&monitors), &[]);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Error), 1);
+        assert!(diagnostics[0].message.contains("paired_offset (8)"));
+    }
+
+    #[test]
+    fn checks_layout_entries() {
+        let monitors = monitors();
+        let input =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","layout":["primary"]}"#;
+
+        let diagnostics = validate_config(input, Ok(&monitors), &[]);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Error), 1);
+        assert!(
+            diagnostics[0]
+                .message
+                .starts_with("layout needs at least two entries")
+        );
+
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","layout":["primary","secondary","DP-3"]}"#;
+
+        let diagnostics = validate_config(input, Ok(&monitors), &[]);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Warning), 1);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "warning: layout monitor 'DP-3' is not connected"
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_debounce_windows() {
+        let monitors = monitors();
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","debounce":{"rebalance_ms":0,"focus_ms":20000}}"#;
+
+        let diagnostics = validate_config(input, Ok(&monitors), &[]);
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "error: debounce.rebalance_ms must be between 1 and 10000",
+                "error: debounce.focus_ms must be at most 10000",
+            ]
+        );
+
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","debounce":{"mode":"adaptive","rebalance_ms":500,"max_rebalance_ms":300}}"#;
+        let diagnostics = validate_config(input, Ok(&monitors), &[]);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: debounce.max_rebalance_ms must be between debounce.rebalance_ms and 10000"
+        );
+    }
+
+    #[test]
+    fn rejects_zero_retry_attempts() {
+        let monitors = monitors();
+        let input =
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","retry":{"attempts":0}}"#;
+
+        let diagnostics = validate_config(input, Ok(&monitors), &[]);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Error), 1);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: retry.attempts must be at least 1"
+        );
+    }
+
+    #[test]
+    fn reports_invalid_schedules() {
+        let monitors = monitors();
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","workspace_count":5,
+            "schedules":[{"name":"work","at":"09:00","slot":7},{"name":"work","at":"10:00","slot":2}]}"#;
+
+        let diagnostics = validate_config(input, Ok(&monitors), &[]);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Error), 2);
+        assert!(diagnostics[0].message.contains("slot 7, outside 1-5"));
+        assert!(diagnostics[1].message.contains("used more than once"));
+    }
+
+    #[test]
+    fn reports_invalid_slot_names() {
+        let monitors = monitors();
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","workspace_count":5,
+            "names":{"1":"code","2":"code","3":"7","9":"mail"}}"#;
+
+        let diagnostics = validate_config(input, Ok(&monitors), &[]);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Error), 3);
+        assert!(diagnostics[0].message.contains("more than one slot"));
+        assert!(diagnostics[1].message.contains("slot 3 name '7'"));
+        assert!(diagnostics[2].message.contains("slot 9, outside 1-5"));
+    }
+
+    #[test]
+    fn reports_idle_inhibit_slots_out_of_range() {
+        let monitors = monitors();
+        let input = r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","workspace_count":5,
+            "idle_inhibit_slots":[5,9]}"#;
+
+        let diagnostics = validate_config(input, Ok(&monitors), &[]);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Error), 1);
+        assert!(diagnostics[0].message.contains("slot 9, outside 1-5"));
+    }
+
+    #[test]
+    fn reports_conflicting_pinned_workspaces() {
+        let monitors = monitors();
+        let pinned = vec![
+            PinnedWorkspace {
+                workspace_id: 3,
+                monitor: "HDMI-A-1".to_string(),
+                source: PathBuf::from("hyprland.conf"),
+                line: 4,
+            },
+            PinnedWorkspace {
+                workspace_id: 3,
+                monitor: "DP-1".to_string(),
+                source: PathBuf::from("rules.conf"),
+                line: 1,
+            },
+        ];
+
+        let diagnostics = validate_config(VALID, Ok(&monitors), &pinned);
+
+        assert_eq!(count_severity(&diagnostics, Severity::Error), 1);
+        assert_eq!(count_severity(&diagnostics, Severity::Warning), 1);
+        assert!(diagnostics[0].message.contains("hyprland.conf:4"));
+    }
+
+    #[test]
+    fn warns_about_workspace_back_and_forth() {
+        let enabled = HyprlandOptions {
+            workspace_back_and_forth: true,
+        };
+
+        assert!(option_diagnostics(&HyprlandOptions::default()).is_empty());
+        let diagnostics = option_diagnostics(&enabled);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn parses_pinned_workspace_rules() {
+        let contents = "# comment\nworkspace = 3, monitor:DP-1, persistent:true\nworkspace = name:web, monitor:DP-1\nbind = SUPER, 1, workspace, 1\n";
+
+        let pinned = parse_pinned_workspaces(contents, Path::new("hyprland.conf"));
+
+        assert_eq!(
+            pinned,
+            vec![PinnedWorkspace {
+                workspace_id: 3,
+                monitor: "DP-1".to_string(),
+                source: PathBuf::from("hyprland.conf"),
+                line: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn collects_pinned_workspaces_from_conf_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(dir.path().join("a.conf"), "workspace = 1, monitor:DP-1\n").expect("write");
+        fs::write(
+            dir.path().join("notes.txt"),
+            "workspace = 2, monitor:DP-1\n",
+        )
+        .expect("write");
+
+        let pinned = pinned_workspaces_in_dir(dir.path());
+
+        assert_eq!(pinned.len(), 1);
+        assert_eq!(pinned[0].workspace_id, 1);
+    }
+}
@@ -0,0 +1,107 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReport {
+    pub version: &'static str,
+    pub git_hash: Option<&'static str>,
+    pub build_date: Option<&'static str>,
+    pub features: Vec<&'static str>,
+}
+
+impl VersionReport {
+    pub fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_hash: option_env!("HYPRSPACES_GIT_HASH"),
+            build_date: option_env!("HYPRSPACES_BUILD_DATE"),
+            features: enabled_features(),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!("hyprspaces {}", self.version);
+        if let Some(hash) = self.git_hash {
+            summary.push_str(&format!(" ({hash})"));
+        }
+        if let Some(date) = self.build_date {
+            summary.push_str(&format!(" built {date}"));
+        }
+        if !self.features.is_empty() {
+            summary.push_str(&format!(" [{}]", self.features.join(", ")));
+        }
+        summary
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "version": self.version,
+            "git_hash": self.git_hash,
+            "build_date": self.build_date,
+            "features": self.features,
+        })
+        .to_string()
+    }
+}
+
+pub fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "cli-full") {
+        features.push("cli-full");
+    }
+    if cfg!(feature = "native-ipc") {
+        features.push("native-ipc");
+    }
+    if cfg!(feature = "async-daemon") {
+        features.push("async-daemon");
+    }
+    if cfg!(feature = "metrics") {
+        features.push("metrics");
+    }
+    if cfg!(feature = "sway-compat") {
+        features.push("sway-compat");
+    }
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VersionReport;
+
+    fn report() -> VersionReport {
+        VersionReport {
+            version: "1.2.3",
+            git_hash: Some("abc123"),
+            build_date: Some("2026-01-02"),
+            features: vec!["native-ipc"],
+        }
+    }
+
+    #[test]
+    fn summarizes_build_metadata() {
+        assert_eq!(
+            report().summary(),
+            "hyprspaces 1.2.3 (abc123) built 2026-01-02 [native-ipc]"
+        );
+
+        let bare = VersionReport {
+            git_hash: None,
+            build_date: None,
+            features: Vec::new(),
+            ..report()
+        };
+        assert_eq!(bare.summary(), "hyprspaces 1.2.3");
+    }
+
+    #[test]
+    fn serializes_report_as_json() {
+        let json: serde_json::Value = serde_json::from_str(&report().to_json()).expect("json");
+
+        assert_eq!(json["version"], "1.2.3");
+        assert_eq!(json["git_hash"], "abc123");
+        assert_eq!(json["build_date"], "2026-01-02");
+        assert_eq!(json["features"], serde_json::json!(["native-ipc"]));
+    }
+
+    #[test]
+    fn current_reports_crate_version() {
+        assert_eq!(VersionReport::current().version, env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -1,6 +1,11 @@
-use crate::hyprctl::{HyprlandIpc, HyprctlError, WorkspaceInfo};
+use crate::config::{Config, ThemeSource, WaybarConfig};
+pub use crate::daemon::{BarMode, BarState};
+use crate::hyprctl::{ClientInfo, HyprctlError, HyprlandIpc, MonitorInfo, WorkspaceInfo};
+use crate::layout::Layout;
 use crate::paired::normalize_workspace;
-use std::path::Path;
+use crate::setup::render_template;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ThemeColors {
@@ -18,12 +23,86 @@ impl ThemeColors {
     }
 }
 
+impl BarMode {
+    fn glyph(self) -> Option<&'static str> {
+        match self {
+            BarMode::Active => None,
+            BarMode::Passthrough => Some("\u{f0379}"),
+            BarMode::Disconnected => Some("\u{f0318}"),
+        }
+    }
+
+    fn tooltip(self) -> Option<&'static str> {
+        match self {
+            BarMode::Active => None,
+            BarMode::Passthrough => Some(
+                "hyprspaces is in single-monitor passthrough: workspaces are not paired until a second display connects",
+            ),
+            BarMode::Disconnected => {
+                Some("hyprspaces lost contact with Hyprland and is waiting for it to respond")
+            }
+        }
+    }
+}
+
+impl BarState {
+    pub fn render(
+        &self,
+        offset: u32,
+        base: u32,
+        names: &BTreeMap<u32, String>,
+        format: &WaybarConfig,
+        colors: &ThemeColors,
+    ) -> String {
+        let display = render_pairs(
+            self.active_workspace,
+            &self.workspaces,
+            offset,
+            base,
+            names,
+            format,
+            colors,
+        );
+        render_mode_json(&display, self.mode)
+    }
+
+    /// The state as seen from a bar on `monitor`.
+    pub fn for_monitor(&self, layout: &Layout<'_>, monitor: &str) -> BarState {
+        BarState {
+            active_workspace: self
+                .visible
+                .get(monitor)
+                .copied()
+                .unwrap_or(self.active_workspace),
+            workspaces: self
+                .workspaces
+                .iter()
+                .filter(|workspace| layout.monitor_for(workspace.id) == Some(monitor))
+                .cloned()
+                .collect(),
+            mode: self.mode,
+            visible: self.visible.clone(),
+        }
+    }
+}
+
+pub fn visible_workspaces(monitors: &[MonitorInfo]) -> BTreeMap<String, u32> {
+    monitors
+        .iter()
+        .filter_map(|monitor| Some((monitor.name.clone(), monitor.active_workspace?)))
+        .collect()
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum WaybarError {
     #[error("failed to read theme css")]
     Io(#[from] std::io::Error),
     #[error("missing foreground color in theme css")]
     MissingForeground,
+    #[error("no foreground color found in {0}")]
+    MissingThemeColor(PathBuf),
+    #[error("invalid color '{0}', expected #rrggbb")]
+    InvalidColor(String),
     #[error("hyprctl failed")]
     Hyprctl(#[from] HyprctlError),
 }
@@ -34,6 +113,66 @@ pub fn load_theme_colors(path: &Path) -> Result<ThemeColors, WaybarError> {
     ThemeColors::from_foreground(&foreground).ok_or(WaybarError::MissingForeground)
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThemePaths {
+    pub css: PathBuf,
+    pub wal: PathBuf,
+    pub matugen: PathBuf,
+}
+
+impl ThemePaths {
+    pub fn new(css: PathBuf, cache_dir: &Path) -> Self {
+        Self {
+            css,
+            wal: cache_dir.join("wal").join("colors.json"),
+            matugen: cache_dir.join("matugen").join("colors.json"),
+        }
+    }
+}
+
+pub fn resolve_theme_colors(
+    source: ThemeSource,
+    color: Option<&str>,
+    paths: &ThemePaths,
+) -> Result<ThemeColors, WaybarError> {
+    if let Some(color) = color {
+        return ThemeColors::from_foreground(color)
+            .ok_or_else(|| WaybarError::InvalidColor(color.to_string()));
+    }
+    match source {
+        ThemeSource::Css => load_theme_colors(&paths.css),
+        ThemeSource::Wal => load_json_theme(&paths.wal, parse_wal_foreground),
+        ThemeSource::Matugen => load_json_theme(&paths.matugen, parse_matugen_foreground),
+        ThemeSource::Auto => load_json_theme(&paths.wal, parse_wal_foreground)
+            .or_else(|_| load_json_theme(&paths.matugen, parse_matugen_foreground))
+            .or_else(|_| load_theme_colors(&paths.css)),
+    }
+}
+
+fn load_json_theme(
+    path: &Path,
+    parse: fn(&serde_json::Value) -> Option<String>,
+) -> Result<ThemeColors, WaybarError> {
+    let contents = std::fs::read_to_string(path)?;
+    serde_json::from_str(&contents)
+        .ok()
+        .as_ref()
+        .and_then(parse)
+        .and_then(|hex| ThemeColors::from_foreground(&hex))
+        .ok_or_else(|| WaybarError::MissingThemeColor(path.to_path_buf()))
+}
+
+pub fn parse_wal_foreground(colors: &serde_json::Value) -> Option<String> {
+    normalize_hex(colors.pointer("/special/foreground")?.as_str()?)
+}
+
+pub fn parse_matugen_foreground(colors: &serde_json::Value) -> Option<String> {
+    let on_surface = colors
+        .pointer("/colors/on_surface/dark")
+        .or_else(|| colors.pointer("/colors/dark/on_surface"))?;
+    normalize_hex(on_surface.as_str()?)
+}
+
 pub fn parse_foreground(css: &str) -> Option<String> {
     let needle = "@define-color foreground";
     let line = css
@@ -46,50 +185,328 @@ pub fn parse_foreground(css: &str) -> Option<String> {
     normalize_hex(hex)
 }
 
-pub fn occupied_workspaces(workspaces: &[WorkspaceInfo], offset: u32) -> Vec<u32> {
+/// Slots with windows on either side of the pair.
+pub fn occupied_workspaces(workspaces: &[WorkspaceInfo], offset: u32, base: u32) -> Vec<u32> {
+    let paired = base..base + offset * 2;
     let mut ids: Vec<u32> = workspaces
         .iter()
-        .filter(|workspace| workspace.windows > 0)
-        .map(|workspace| {
-            if workspace.id > offset {
-                workspace.id - offset
-            } else {
-                workspace.id
-            }
-        })
+        .filter(|workspace| workspace.windows > 0 && paired.contains(&workspace.id))
+        .map(|workspace| normalize_workspace(workspace.id, offset, base))
         .collect();
     ids.sort_unstable();
     ids.dedup();
     ids
 }
 
-pub fn render_display(active_workspace: u32, occupied: &[u32], colors: &ThemeColors) -> String {
-    let mut output = String::new();
-    let glyph = "\u{f14fb}";
-    for i in 1..=5 {
-        let is_active = i == active_workspace;
-        let is_occupied = occupied.contains(&i);
-        if is_active {
-            output.push_str(&format!(
-                "<span foreground='{}'>{}</span>",
-                colors.bright, glyph
-            ));
-        } else if is_occupied {
-            output.push_str(&format!("<span foreground='{}'>{}</span>", colors.mid, i));
-        } else {
-            output.push_str(&format!("<span foreground='{}'>{}</span>", colors.dim, i));
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotStatus {
+    Active,
+    Occupied,
+    Empty,
+}
+
+impl SlotStatus {
+    pub fn class(self) -> &'static str {
+        match self {
+            SlotStatus::Active => "active",
+            SlotStatus::Occupied => "occupied",
+            SlotStatus::Empty => "empty",
         }
-        if i < 5 {
-            output.push(' ');
+    }
+
+    fn color(self, colors: &ThemeColors) -> &str {
+        match self {
+            SlotStatus::Active => &colors.bright,
+            SlotStatus::Occupied => &colors.mid,
+            SlotStatus::Empty => &colors.dim,
         }
     }
-    output
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotView {
+    pub slot: u32,
+    pub workspace: u32,
+    pub status: SlotStatus,
+    pub label: String,
+}
+
+/// Slots 1-5 with their labels, escaped for the target markup.
+pub fn slot_views(
+    active_workspace: u32,
+    occupied: &[u32],
+    base: u32,
+    names: &BTreeMap<u32, String>,
+    format: &WaybarConfig,
+    escape: impl Fn(&str) -> String,
+) -> Vec<SlotView> {
+    let glyph = "\u{f14fb}";
+    (1..=5)
+        .map(|i| {
+            let status = if i == active_workspace {
+                SlotStatus::Active
+            } else if occupied.contains(&i) {
+                SlotStatus::Occupied
+            } else {
+                SlotStatus::Empty
+            };
+            let template = match status {
+                SlotStatus::Active => &format.active_format,
+                SlotStatus::Occupied => &format.occupied_format,
+                SlotStatus::Empty => &format.empty_format,
+            };
+            let id = (base + i - 1).to_string();
+            let label = match (template, names.get(&i)) {
+                (Some(template), name) => {
+                    let name = name.map_or_else(|| id.clone(), |name| escape(name));
+                    let icon = format
+                        .icons
+                        .get(&i)
+                        .map_or_else(|| glyph.to_string(), |icon| escape(icon));
+                    render_template(template, &[("id", &id), ("name", &name), ("icon", &icon)])
+                }
+                (None, Some(name)) => escape(name),
+                (None, None) if status == SlotStatus::Active => glyph.to_string(),
+                (None, None) => id,
+            };
+            SlotView {
+                slot: i,
+                workspace: base + i - 1,
+                status,
+                label,
+            }
+        })
+        .collect()
+}
+
+/// Renders slots 1-5 as Pango markup for Waybar.
+pub fn render_display(
+    active_workspace: u32,
+    occupied: &[u32],
+    base: u32,
+    names: &BTreeMap<u32, String>,
+    format: &WaybarConfig,
+    colors: &ThemeColors,
+) -> String {
+    let slots = slot_views(
+        active_workspace,
+        occupied,
+        base,
+        names,
+        format,
+        escape_markup,
+    );
+    render_markup(&slots, colors)
+}
+
+fn render_markup(slots: &[SlotView], colors: &ThemeColors) -> String {
+    slots
+        .iter()
+        .map(|view| {
+            format!(
+                "<span foreground='{}'>{}</span>",
+                view.status.color(colors),
+                view.label
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// An output format for `hyprspaces bar`.
+pub trait RenderBackend {
+    fn escape(&self, text: &str) -> String;
+    fn render(&self, slots: &[SlotView], mode: BarMode, colors: &ThemeColors) -> String;
+
+    /// Shown while waiting for the daemon, if the format has a way to say so.
+    fn starting(&self) -> Option<String> {
+        None
+    }
+
+    fn safe_mode(&self, _error: &str) -> Option<String> {
+        None
+    }
+}
+
+pub struct WaybarJson;
+
+impl RenderBackend for WaybarJson {
+    fn escape(&self, text: &str) -> String {
+        escape_markup(text)
+    }
+
+    fn render(&self, slots: &[SlotView], mode: BarMode, colors: &ThemeColors) -> String {
+        render_mode_json(&render_markup(slots, colors), mode)
+    }
+
+    fn starting(&self) -> Option<String> {
+        Some(render_starting())
+    }
+
+    fn safe_mode(&self, error: &str) -> Option<String> {
+        Some(render_safe_mode(error))
+    }
+}
+
+/// JSON for an eww `deflisten`.
+pub struct Eww;
+
+impl RenderBackend for Eww {
+    fn escape(&self, text: &str) -> String {
+        text.to_string()
+    }
+
+    fn render(&self, slots: &[SlotView], mode: BarMode, colors: &ThemeColors) -> String {
+        let yuck = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+        let labels = slots
+            .iter()
+            .map(|view| {
+                format!(
+                    "(label :class \"slot {}\" :style \"color: {};\" :text \"{}\")",
+                    view.status.class(),
+                    view.status.color(colors),
+                    yuck(&view.label)
+                )
+            })
+            .collect::<Vec<_>>();
+        let glyph = mode
+            .glyph()
+            .map(|glyph| format!(" (label :class \"mode\" :text \"{glyph}\")"))
+            .unwrap_or_default();
+        serde_json::json!({
+            "class": mode.class(),
+            "slots": slots
+                .iter()
+                .map(|view| serde_json::json!({
+                    "slot": view.slot,
+                    "workspace": view.workspace,
+                    "status": view.status.class(),
+                    "label": view.label,
+                    "color": view.status.color(colors),
+                }))
+                .collect::<Vec<_>>(),
+            "literal": format!(
+                "(box :class \"hyprspaces {}\" :space-evenly false{glyph} {})",
+                mode.class(),
+                labels.join(" ")
+            ),
+        })
+        .to_string()
+    }
+}
+
+pub struct Polybar;
+
+impl RenderBackend for Polybar {
+    fn escape(&self, text: &str) -> String {
+        text.replace('%', "%%")
+    }
+
+    fn render(&self, slots: &[SlotView], mode: BarMode, colors: &ThemeColors) -> String {
+        let slots = slots
+            .iter()
+            .map(|view| format!("%{{F{}}}{}%{{F-}}", view.status.color(colors), view.label))
+            .collect::<Vec<_>>()
+            .join(" ");
+        with_mode_glyph(slots, mode)
+    }
+}
+
+pub struct Plain;
+
+impl RenderBackend for Plain {
+    fn escape(&self, text: &str) -> String {
+        text.to_string()
+    }
+
+    fn render(&self, slots: &[SlotView], mode: BarMode, colors: &ThemeColors) -> String {
+        let slots = slots
+            .iter()
+            .map(|view| {
+                let (r, g, b) = rgb(view.status.color(colors)).unwrap_or((255, 255, 255));
+                let bold = if view.status == SlotStatus::Active {
+                    "1;"
+                } else {
+                    ""
+                };
+                format!("\x1b[{bold}38;2;{r};{g};{b}m{}\x1b[0m", view.label)
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        with_mode_glyph(slots, mode)
+    }
+}
+
+fn with_mode_glyph(text: String, mode: BarMode) -> String {
+    match mode.glyph() {
+        Some(glyph) => format!("{glyph} {text}"),
+        None => text,
+    }
+}
+
+pub fn render_with(
+    backend: &dyn RenderBackend,
+    state: &BarState,
+    config: &Config,
+    colors: &ThemeColors,
+) -> String {
+    let offset = config.paired_offset;
+    let base = config.workspace_base;
+    let slots = slot_views(
+        normalize_workspace(state.active_workspace, offset, base),
+        &occupied_workspaces(&state.workspaces, offset, base),
+        base,
+        &config.names,
+        &config.waybar,
+        |text| backend.escape(text),
+    );
+    backend.render(&slots, state.mode, colors)
+}
+
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&apos;")
+}
+
+pub fn render_starting() -> String {
+    serde_json::json!({
+        "text": "starting\u{2026}",
+        "class": "starting",
+        "tooltip": "waiting for the hyprspaces daemon",
+        "markup": true
+    })
+    .to_string()
+}
+
+pub fn render_safe_mode(error: &str) -> String {
+    serde_json::json!({
+        "text": "config error",
+        "class": "error",
+        "tooltip": format!("hyprspaces daemon is in safe mode: {error}"),
+        "markup": false
+    })
+    .to_string()
 }
 
 pub fn render_json(text: &str) -> String {
+    render_mode_json(text, BarMode::Active)
+}
+
+pub fn render_mode_json(text: &str, mode: BarMode) -> String {
+    let (Some(glyph), Some(tooltip)) = (mode.glyph(), mode.tooltip()) else {
+        return serde_json::json!({
+            "text": text,
+            "class": mode.class(),
+            "markup": true
+        })
+        .to_string();
+    };
     serde_json::json!({
-        "text": text,
-        "class": "workspaces",
+        "text": format!("{glyph} {text}"),
+        "class": mode.class(),
+        "tooltip": tooltip,
         "markup": true
     })
     .to_string()
@@ -99,22 +516,228 @@ pub fn render_state(
     active_workspace: u32,
     workspaces: &[WorkspaceInfo],
     offset: u32,
+    base: u32,
+    names: &BTreeMap<u32, String>,
     colors: &ThemeColors,
 ) -> String {
-    let active_normalized = normalize_workspace(active_workspace, offset);
-    let occupied = occupied_workspaces(workspaces, offset);
-    let display = render_display(active_normalized, &occupied, colors);
-    render_json(&display)
+    render_json(&render_pairs(
+        active_workspace,
+        workspaces,
+        offset,
+        base,
+        names,
+        &WaybarConfig::default(),
+        colors,
+    ))
+}
+
+fn render_pairs(
+    active_workspace: u32,
+    workspaces: &[WorkspaceInfo],
+    offset: u32,
+    base: u32,
+    names: &BTreeMap<u32, String>,
+    format: &WaybarConfig,
+    colors: &ThemeColors,
+) -> String {
+    let active_normalized = normalize_workspace(active_workspace, offset, base);
+    let occupied = occupied_workspaces(workspaces, offset, base);
+    render_display(active_normalized, &occupied, base, names, format, colors)
+}
+
+pub const DEFAULT_FOREGROUND: &str = "#c0caf5";
+pub const STATE_FILE: &str = "waybar.state";
+
+pub fn state_path(base_dir: &Path) -> PathBuf {
+    base_dir.join(STATE_FILE)
+}
+
+pub fn render_bar(
+    state: &BarState,
+    config: &Config,
+    colors: &ThemeColors,
+    clients: Option<&[ClientInfo]>,
+) -> String {
+    let json = state.render(
+        config.paired_offset,
+        config.workspace_base,
+        &config.names,
+        &config.waybar,
+        colors,
+    );
+    match clients {
+        Some(clients) => attach_tooltip(&json, &render_window_tooltip(clients, config)),
+        None => json,
+    }
+}
+
+pub fn write_state(path: &Path, line: &str) -> std::io::Result<()> {
+    let temp = path.with_extension("state.tmp");
+    std::fs::write(&temp, format!("{line}\n"))?;
+    std::fs::rename(&temp, path)
+}
+
+pub fn signal_waybar(signal: u8) -> std::io::Result<()> {
+    std::process::Command::new("pkill")
+        .args([format!("-RTMIN+{signal}").as_str(), "-x", "waybar"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|_| ())
+}
+
+pub struct StateFollower {
+    path: PathBuf,
+    last: Option<String>,
+}
+
+impl StateFollower {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, last: None }
+    }
+
+    pub fn poll(&mut self) -> Option<String> {
+        let contents = std::fs::read_to_string(&self.path).ok()?;
+        let line = contents.trim_end();
+        if line.is_empty() || self.last.as_deref() == Some(line) {
+            return None;
+        }
+        self.last = Some(line.to_string());
+        self.last.clone()
+    }
+}
+
+pub fn render_window_tooltip(clients: &[ClientInfo], config: &Config) -> String {
+    let offset = config.paired_offset;
+    let base = config.workspace_base;
+    let mut lines = Vec::new();
+    for slot in 1..=config.workspace_count {
+        let titles = |workspace_id: u32| {
+            clients
+                .iter()
+                .filter(|client| client.workspace.id == workspace_id)
+                .map(|client| {
+                    let title = client
+                        .title
+                        .as_deref()
+                        .filter(|title| !title.is_empty())
+                        .or(client.class.as_deref())
+                        .unwrap_or(&client.address);
+                    escape_markup(title)
+                })
+                .collect::<Vec<_>>()
+        };
+        let primary = titles(base + slot - 1);
+        let secondary = titles(base + slot - 1 + offset);
+        let count = primary.len() + secondary.len();
+        if count == 0 {
+            continue;
+        }
+        let label = match config.slot_name(slot) {
+            Some(name) => format!("{slot} {}", escape_markup(name)),
+            None => slot.to_string(),
+        };
+        let noun = if count == 1 { "window" } else { "windows" };
+        lines.push(format!("{label}: {count} {noun}"));
+        for (monitor, titles) in [
+            (&config.primary_monitor, primary),
+            (&config.secondary_monitor, secondary),
+        ] {
+            if !titles.is_empty() {
+                lines.push(format!(
+                    "  {}: {}",
+                    escape_markup(monitor),
+                    titles.join(", ")
+                ));
+            }
+        }
+    }
+    if lines.is_empty() {
+        "no windows".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+pub fn attach_tooltip(json: &str, tooltip: &str) -> String {
+    let Ok(mut value) = serde_json::from_str::<serde_json::Value>(json) else {
+        return json.to_string();
+    };
+    let tooltip = match value.get("tooltip").and_then(serde_json::Value::as_str) {
+        Some(existing) => format!("{existing}\n\n{tooltip}"),
+        None => tooltip.to_string(),
+    };
+    value["tooltip"] = tooltip.into();
+    value.to_string()
+}
+
+pub fn render_hyprland_workspaces(state: &BarState, config: &Config) -> String {
+    let offset = config.paired_offset;
+    let base = config.workspace_base;
+    let active_slot = config.slot_of(state.active_workspace);
+    let active_primary = config.slot_workspace(active_slot);
+    let secondary_start = base + offset;
+    let mut ids = state
+        .workspaces
+        .iter()
+        .map(|workspace| workspace.id)
+        .filter(|id| (base..base + offset * 2).contains(id))
+        .chain([active_primary, active_primary + offset])
+        .collect::<Vec<_>>();
+    ids.sort_unstable_by_key(|id| (*id >= secondary_start, *id));
+    ids.dedup();
+    let workspaces = ids
+        .into_iter()
+        .map(|id| {
+            let slot = config.slot_of(id);
+            let active = slot == active_slot;
+            let monitor = if id >= secondary_start {
+                &config.secondary_monitor
+            } else {
+                &config.primary_monitor
+            };
+            let class = if active {
+                format!("workspace-button w{slot} workspace-active wa{slot}")
+            } else {
+                format!("workspace-button w{slot}")
+            };
+            serde_json::json!({
+                "id": id,
+                "name": config.slot_name(slot).map_or_else(|| slot.to_string(), str::to_string),
+                "active": active,
+                "monitor": monitor,
+                "class": class,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::Value::Array(workspaces).to_string()
+}
+
+pub fn bar_state_from_hyprctl(hyprctl: &dyn HyprlandIpc) -> Result<BarState, WaybarError> {
+    Ok(BarState {
+        active_workspace: hyprctl.active_workspace_id()?,
+        workspaces: hyprctl.workspaces()?,
+        mode: BarMode::Active,
+        visible: BTreeMap::new(),
+    })
 }
 
 pub fn state_from_hyprctl(
     hyprctl: &dyn HyprlandIpc,
     offset: u32,
+    base: u32,
+    names: &BTreeMap<u32, String>,
     colors: &ThemeColors,
 ) -> Result<String, WaybarError> {
-    let active_workspace = hyprctl.active_workspace_id()?;
-    let workspaces = hyprctl.workspaces()?;
-    Ok(render_state(active_workspace, &workspaces, offset, colors))
+    let state = bar_state_from_hyprctl(hyprctl)?;
+    Ok(render_state(
+        state.active_workspace,
+        &state.workspaces,
+        offset,
+        base,
+        names,
+        colors,
+    ))
 }
 
 pub fn should_update(line: &str) -> bool {
@@ -141,11 +764,16 @@ fn normalize_hex(hex: &str) -> Option<String> {
     Some(value.to_lowercase())
 }
 
-fn dim_color(hex: &str, factor: u8) -> Option<String> {
+fn rgb(hex: &str) -> Option<(u8, u8, u8)> {
     let normalized = normalize_hex(hex)?;
     let r = u8::from_str_radix(&normalized[1..3], 16).ok()?;
     let g = u8::from_str_radix(&normalized[3..5], 16).ok()?;
     let b = u8::from_str_radix(&normalized[5..7], 16).ok()?;
+    Some((r, g, b))
+}
+
+fn dim_color(hex: &str, factor: u8) -> Option<String> {
+    let (r, g, b) = rgb(hex)?;
     Some(format!(
         "#{:02x}{:02x}{:02x}",
         (u16::from(r) * u16::from(factor) / 100) as u8,
@@ -157,14 +785,19 @@ fn dim_color(hex: &str, factor: u8) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::{
-        ThemeColors, load_theme_colors, occupied_workspaces, parse_foreground, render_display,
-        render_state, should_update, state_from_hyprctl,
+        BarMode, BarState, Eww, Plain, Polybar, RenderBackend, SlotStatus, StateFollower,
+        ThemeColors, ThemePaths, WaybarJson, attach_tooltip, load_theme_colors,
+        occupied_workspaces, parse_foreground, render_bar, render_display,
+        render_hyprland_workspaces, render_json, render_mode_json, render_safe_mode,
+        render_starting, render_state, render_window_tooltip, render_with, resolve_theme_colors,
+        should_update, slot_views, state_from_hyprctl, state_path, visible_workspaces, write_state,
     };
-    use crate::hyprctl::{Hyprctl, HyprctlRunner, WorkspaceInfo};
-    use std::cell::RefCell;
-    use std::collections::VecDeque;
+    use crate::config::{Config, ThemeSource, WaybarConfig};
+    use crate::hyprctl::{ClientInfo, MonitorInfo, WorkspaceInfo};
+    use crate::layout::Layout;
+    use crate::testing::ScriptedIpc;
+    use std::collections::BTreeMap;
     use std::fs;
-    use std::rc::Rc;
 
     #[test]
     fn parses_foreground_color() {
@@ -196,19 +829,137 @@ mod tests {
             },
         ];
 
-        assert_eq!(occupied_workspaces(&workspaces, 10), vec![1, 2]);
+        assert_eq!(occupied_workspaces(&workspaces, 10, 1), vec![1, 2]);
     }
 
     #[test]
     fn renders_display_with_active_and_occupied() {
         let colors = ThemeColors::from_foreground("#ffffff").expect("colors");
-        let output = render_display(2, &[1, 3], &colors);
+        let output = render_display(
+            2,
+            &[1, 3],
+            1,
+            &BTreeMap::new(),
+            &WaybarConfig::default(),
+            &colors,
+        );
 
         assert!(output.contains("\u{f14fb}"));
         assert!(output.contains("1"));
         assert!(output.contains("3"));
     }
 
+    #[test]
+    fn renders_display_with_slot_names() {
+        let colors = ThemeColors::from_foreground("#ffffff").expect("colors");
+        let names = BTreeMap::from([(1, "code".to_string()), (3, "r&d".to_string())]);
+        let output = render_display(1, &[3], 1, &names, &WaybarConfig::default(), &colors);
+
+        assert!(output.starts_with("<span foreground='#ffffff'>code</span>"));
+        assert!(output.contains(">r&amp;d</span>"));
+        assert!(!output.contains("\u{f14fb}"));
+    }
+
+    #[test]
+    fn renders_display_with_format_templates_and_icons() {
+        let colors = ThemeColors::from_foreground("#ffffff").expect("colors");
+        let names = BTreeMap::from([(2, "web".to_string())]);
+        let format: WaybarConfig = serde_json::from_str(
+            r#"{
+                "active_format": "<b>{icon} {name}</b>",
+                "occupied_format": "{id}:{icon}",
+                "empty_format": "{id}",
+                "icons": {"2": "\uf269", "3": "<>"}
+            }"#,
+        )
+        .expect("format");
+
+        let output = render_display(2, &[3], 1, &names, &format, &colors);
+
+        assert!(output.starts_with("<span foreground='#666666'>1</span>"));
+        assert!(output.contains("<span foreground='#ffffff'><b>\u{f269} web</b></span>"));
+        assert!(output.contains("<span foreground='#a5a5a5'>3:&lt;&gt;</span>"));
+        assert!(output.ends_with("<span foreground='#666666'>5</span>"));
+    }
+
+    #[test]
+    fn slot_views_carry_status_and_escaped_labels() {
+        let names = BTreeMap::from([(4, "a<b".to_string())]);
+        let slots = slot_views(2, &[3, 4], 11, &names, &WaybarConfig::default(), |text| {
+            text.replace('<', "&lt;")
+        });
+
+        let statuses = slots.iter().map(|view| view.status).collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                SlotStatus::Empty,
+                SlotStatus::Active,
+                SlotStatus::Occupied,
+                SlotStatus::Occupied,
+                SlotStatus::Empty,
+            ]
+        );
+        let labels = slots
+            .iter()
+            .map(|view| view.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec!["11", "\u{f14fb}", "13", "a&lt;b", "15"]);
+        assert_eq!(slots[2].workspace, 13);
+    }
+
+    #[test]
+    fn renders_the_same_state_for_every_backend() {
+        let colors = ThemeColors::from_foreground("#ffffff").expect("colors");
+        let config = Config::from_json(
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","names":{"1":"50% \"x\""}}"#,
+        )
+        .expect("config");
+        let mut state = BarState {
+            active_workspace: 12,
+            workspaces: vec![WorkspaceInfo {
+                id: 3,
+                windows: 1,
+                name: None,
+                monitor: None,
+            }],
+            mode: BarMode::Active,
+            visible: BTreeMap::new(),
+        };
+
+        assert_eq!(
+            render_with(&WaybarJson, &state, &config, &colors),
+            render_bar(&state, &config, &colors, None)
+        );
+
+        let polybar = render_with(&Polybar, &state, &config, &colors);
+        assert!(
+            polybar.starts_with(
+                "%{F#666666}50%% \"x\"%{F-} %{F#ffffff}\u{f14fb}%{F-} %{F#a5a5a5}3%{F-}"
+            )
+        );
+
+        let plain = render_with(&Plain, &state, &config, &colors);
+        assert!(plain.contains("\x1b[1;38;2;255;255;255m\u{f14fb}\x1b[0m"));
+        assert!(plain.contains("\x1b[38;2;165;165;165m3\x1b[0m"));
+
+        let eww: serde_json::Value =
+            serde_json::from_str(&render_with(&Eww, &state, &config, &colors)).expect("json");
+        assert_eq!(eww["class"], "workspaces");
+        assert_eq!(eww["slots"][0]["label"], "50% \"x\"");
+        assert_eq!(eww["slots"][1]["status"], "active");
+        assert_eq!(eww["slots"][2]["color"], "#a5a5a5");
+        let literal = eww["literal"].as_str().expect("literal");
+        assert!(literal.starts_with(
+            "(box :class \"hyprspaces workspaces\" :space-evenly false (label :class \"slot empty\" :style \"color: #666666;\" :text \"50% \\\"x\\\"\")"
+        ));
+
+        state.mode = BarMode::Passthrough;
+        assert!(render_with(&Polybar, &state, &config, &colors).starts_with("\u{f0379} %{F"));
+        assert!(Polybar.starting().is_none());
+        assert_eq!(WaybarJson.starting(), Some(render_starting()));
+    }
+
     #[test]
     fn renders_state_json() {
         let colors = ThemeColors::from_foreground("#ffffff").expect("colors");
@@ -219,12 +970,210 @@ mod tests {
             monitor: None,
         }];
 
-        let json = render_state(1, &workspaces, 10, &colors);
+        let json = render_state(1, &workspaces, 10, 1, &BTreeMap::new(), &colors);
 
         assert!(json.contains("\"markup\":true"));
         assert!(json.contains("\"class\":\"workspaces\""));
     }
 
+    #[test]
+    fn filters_state_to_one_monitor() {
+        let workspace = |id, windows| WorkspaceInfo {
+            id,
+            windows,
+            name: None,
+            monitor: None,
+        };
+        let state = BarState {
+            active_workspace: 3,
+            workspaces: vec![workspace(1, 1), workspace(3, 0), workspace(14, 2)],
+            mode: BarMode::Active,
+            visible: BTreeMap::from([("HDMI-A-1".to_string(), 14)]),
+        };
+        let layout = Layout::paired("DP-1", "HDMI-A-1", 10);
+
+        let secondary = state.for_monitor(&layout, "HDMI-A-1");
+        let primary = state.for_monitor(&layout, "DP-1");
+
+        assert_eq!(secondary.active_workspace, 14);
+        assert_eq!(occupied_workspaces(&secondary.workspaces, 10, 1), vec![4]);
+        assert_eq!(primary.active_workspace, 3);
+        assert_eq!(occupied_workspaces(&primary.workspaces, 10, 1), vec![1]);
+    }
+
+    #[test]
+    fn reads_visible_workspaces_from_monitors() {
+        let monitors: Vec<MonitorInfo> = serde_json::from_str(
+            r#"[
+                {"name":"DP-1","x":0,"id":0,"activeWorkspace":{"id":2,"name":"2"}},
+                {"name":"HDMI-A-1","x":1920,"id":1,"activeWorkspace":{"id":-98,"name":"special"}},
+                {"name":"DP-2","x":3840,"id":2}
+            ]"#,
+        )
+        .expect("monitors");
+
+        assert_eq!(
+            visible_workspaces(&monitors),
+            BTreeMap::from([("DP-1".to_string(), 2)])
+        );
+    }
+
+    #[test]
+    fn renders_daemon_modes_with_distinct_class_and_glyph() {
+        let colors = ThemeColors::from_foreground("#ffffff").expect("colors");
+        let state: BarState =
+            serde_json::from_str(r#"{"active_workspace":1,"workspaces":[]}"#).expect("state");
+        assert_eq!(state.mode, BarMode::Active);
+
+        let active: serde_json::Value = serde_json::from_str(&state.render(
+            10,
+            1,
+            &BTreeMap::new(),
+            &WaybarConfig::default(),
+            &colors,
+        ))
+        .expect("json");
+        assert_eq!(active["class"], "workspaces");
+        assert!(active.get("tooltip").is_none());
+
+        for (mode, class, glyph) in [
+            (BarMode::Passthrough, "passthrough", "\u{f0379} "),
+            (BarMode::Disconnected, "disconnected", "\u{f0318} "),
+        ] {
+            let state = BarState {
+                mode,
+                ..state.clone()
+            };
+            let json: serde_json::Value = serde_json::from_str(&state.render(
+                10,
+                1,
+                &BTreeMap::new(),
+                &WaybarConfig::default(),
+                &colors,
+            ))
+            .expect("json");
+            assert_eq!(json["class"], class);
+            assert!(json["text"].as_str().expect("text").starts_with(glyph));
+            assert!(json["tooltip"].is_string());
+        }
+    }
+
+    #[test]
+    fn renders_hyprland_workspaces_compat_schema() {
+        let config = crate::config::Config::from_json(
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","names":{"2":"web"}}"#,
+        )
+        .expect("config");
+        let state = BarState {
+            active_workspace: 12,
+            workspaces: vec![
+                WorkspaceInfo {
+                    id: 1,
+                    windows: 1,
+                    name: None,
+                    monitor: None,
+                },
+                WorkspaceInfo {
+                    id: 25,
+                    windows: 1,
+                    name: None,
+                    monitor: None,
+                },
+            ],
+            mode: BarMode::Active,
+            visible: BTreeMap::new(),
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_hyprland_workspaces(&state, &config)).expect("json");
+
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"id": 1, "name": "1", "active": false, "monitor": "DP-1", "class": "workspace-button w1"},
+                {"id": 2, "name": "web", "active": true, "monitor": "DP-1", "class": "workspace-button w2 workspace-active wa2"},
+                {"id": 12, "name": "web", "active": true, "monitor": "HDMI-A-1", "class": "workspace-button w2 workspace-active wa2"},
+            ])
+        );
+    }
+
+    #[test]
+    fn renders_window_tooltip_per_pair() {
+        let config = Config::from_json(
+            r#"{"primary_monitor":"DP-1","secondary_monitor":"HDMI-A-1","workspace_count":3,"names":{"2":"web"}}"#,
+        )
+        .expect("config");
+        let clients: Vec<ClientInfo> = serde_json::from_str(
+            r#"[
+                {"address":"0x1","workspace":{"id":1},"class":"kitty","title":"nvim <main>"},
+                {"address":"0x2","workspace":{"id":2},"class":"firefox","title":""},
+                {"address":"0x3","workspace":{"id":5},"class":"slack","title":"Slack"},
+                {"address":"0x4","workspace":{"id":4},"class":"kitty","title":"htop"}
+            ]"#,
+        )
+        .expect("clients");
+
+        assert_eq!(
+            render_window_tooltip(&clients, &config),
+            "1: 2 windows\n  DP-1: nvim &lt;main&gt;\n  HDMI-A-1: htop\n2 web: 2 windows\n  DP-1: firefox\n  HDMI-A-1: Slack"
+        );
+        assert_eq!(render_window_tooltip(&[], &config), "no windows");
+    }
+
+    #[test]
+    fn attaches_tooltip_after_mode_tooltip() {
+        let plain = attach_tooltip(&render_json("1"), "no windows");
+        let json: serde_json::Value = serde_json::from_str(&plain).expect("json");
+        assert_eq!(json["tooltip"], "no windows");
+        assert_eq!(json["text"], "1");
+
+        let passthrough =
+            attach_tooltip(&render_mode_json("1", BarMode::Passthrough), "1: 1 window");
+        let json: serde_json::Value = serde_json::from_str(&passthrough).expect("json");
+        assert!(
+            json["tooltip"]
+                .as_str()
+                .expect("tooltip")
+                .ends_with("\n\n1: 1 window")
+        );
+    }
+
+    #[test]
+    fn follows_state_file_changes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = state_path(dir.path());
+        let mut follower = StateFollower::new(path.clone());
+
+        assert_eq!(follower.poll(), None);
+        write_state(&path, r#"{"text":"1"}"#).expect("write");
+        assert_eq!(follower.poll().as_deref(), Some(r#"{"text":"1"}"#));
+        assert_eq!(follower.poll(), None);
+        write_state(&path, r#"{"text":"2"}"#).expect("write");
+        assert_eq!(follower.poll().as_deref(), Some(r#"{"text":"2"}"#));
+        assert!(!dir.path().join("waybar.state.tmp").exists());
+    }
+
+    #[test]
+    fn renders_starting_state_json() {
+        let json: serde_json::Value = serde_json::from_str(&render_starting()).expect("json");
+
+        assert_eq!(json["class"], "starting");
+        assert_eq!(json["text"], "starting\u{2026}");
+    }
+
+    #[test]
+    fn renders_safe_mode_json() {
+        let json: serde_json::Value =
+            serde_json::from_str(&render_safe_mode("missing required field: primary_monitor"))
+                .expect("json");
+
+        assert_eq!(json["class"], "error");
+        assert_eq!(
+            json["tooltip"],
+            "hyprspaces daemon is in safe mode: missing required field: primary_monitor"
+        );
+    }
+
     #[test]
     fn updates_on_waybar_events() {
         assert!(should_update("workspace>>2"));
@@ -246,42 +1195,62 @@ mod tests {
         assert_eq!(colors, expected);
     }
 
-    #[derive(Clone)]
-    struct SequenceRunner {
-        responses: Rc<RefCell<VecDeque<String>>>,
-        calls: Rc<RefCell<Vec<Vec<String>>>>,
-    }
+    #[test]
+    fn resolves_theme_colors_from_generated_palettes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let paths = ThemePaths::new(dir.path().join("style.css"), dir.path());
+        fs::write(&paths.css, "@define-color foreground #112233;").expect("css");
+        fs::create_dir_all(paths.wal.parent().expect("wal dir")).expect("wal dir");
+        fs::create_dir_all(paths.matugen.parent().expect("matugen dir")).expect("matugen dir");
+        let colors = |hex| ThemeColors::from_foreground(hex).expect("colors");
 
-    impl SequenceRunner {
-        fn new(responses: Vec<String>) -> Self {
-            Self {
-                responses: Rc::new(RefCell::new(responses.into())),
-                calls: Rc::new(RefCell::new(Vec::new())),
-            }
-        }
-    }
+        assert_eq!(
+            resolve_theme_colors(ThemeSource::Auto, None, &paths).expect("auto css"),
+            colors("#112233")
+        );
+        assert!(resolve_theme_colors(ThemeSource::Wal, None, &paths).is_err());
 
-    impl HyprctlRunner for SequenceRunner {
-        fn run(&self, args: &[String]) -> Result<String, crate::hyprctl::HyprctlError> {
-            self.calls.borrow_mut().push(args.to_vec());
-            let response = self.responses.borrow_mut().pop_front().unwrap_or_default();
-            Ok(response)
-        }
+        fs::write(
+            &paths.matugen,
+            r##"{"colors":{"on_surface":{"dark":"#445566","light":"#000000"}}}"##,
+        )
+        .expect("matugen");
+        assert_eq!(
+            resolve_theme_colors(ThemeSource::Matugen, None, &paths).expect("matugen"),
+            colors("#445566")
+        );
+        assert_eq!(
+            resolve_theme_colors(ThemeSource::Auto, None, &paths).expect("auto matugen"),
+            colors("#445566")
+        );
+
+        fs::write(&paths.wal, r##"{"special":{"foreground":"#778899"}}"##).expect("wal");
+        assert_eq!(
+            resolve_theme_colors(ThemeSource::Auto, None, &paths).expect("auto wal"),
+            colors("#778899")
+        );
+        assert_eq!(
+            resolve_theme_colors(ThemeSource::Css, Some("#ABCDEF"), &paths).expect("override"),
+            colors("#abcdef")
+        );
+        let err = resolve_theme_colors(ThemeSource::Css, Some("red"), &paths).expect_err("bad");
+        assert_eq!(err.to_string(), "invalid color 'red', expected #rrggbb");
     }
 
     #[test]
     fn renders_state_from_hyprctl() {
         let colors = ThemeColors::from_foreground("#ffffff").expect("colors");
-        let runner = SequenceRunner::new(vec![
-            r#"{"id":12}"#.to_string(),
-            r#"[{"id":1,"windows":1},{"id":12,"windows":2}]"#.to_string(),
-        ]);
-        let hyprctl = Hyprctl::new(runner.clone());
+        let hyprctl = ScriptedIpc::new()
+            .with_json("activeworkspace", r#"{"id":12}"#)
+            .with_json(
+                "workspaces",
+                r#"[{"id":1,"windows":1},{"id":12,"windows":2}]"#,
+            );
 
-        let json = state_from_hyprctl(&hyprctl, 10, &colors).expect("state");
+        let json = state_from_hyprctl(&hyprctl, 10, 1, &BTreeMap::new(), &colors).expect("state");
 
         assert!(json.contains("\"class\":\"workspaces\""));
-        let calls = runner.calls.borrow();
+        let calls = hyprctl.calls();
         assert_eq!(
             calls[0],
             vec!["-j".to_string(), "activeworkspace".to_string()]
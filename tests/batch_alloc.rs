@@ -0,0 +1,79 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use hyprspaces::hyprctl::{BatchCache, SwitchStyle};
+use hyprspaces::layout;
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations_during(run: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    run();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn repeated_focus_switches_do_not_allocate() {
+    let mut cache = BatchCache::default();
+    cache.paired_switch(
+        &layout::Layout::paired("DP-1", "HDMI-A-1", 10),
+        10,
+        "DP-1",
+        SwitchStyle::Full,
+    );
+
+    let allocations = allocations_during(|| {
+        for workspace in 1..=100 {
+            let batch = cache.paired_switch(
+                &layout::Layout::paired("DP-1", "HDMI-A-1", 10),
+                workspace,
+                "HDMI-A-1",
+                SwitchStyle::Full,
+            );
+            assert!(batch.starts_with("dispatch focusmonitor DP-1"));
+        }
+    });
+
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn repeated_rebalances_do_not_allocate() {
+    let mut cache = BatchCache::default();
+    cache.rebalance(&layout::Layout::paired("DP-1", "HDMI-A-1", 10));
+
+    let allocations = allocations_during(|| {
+        for _ in 0..100 {
+            assert!(
+                !cache
+                    .rebalance(&layout::Layout::paired("DP-1", "HDMI-A-1", 10))
+                    .is_empty()
+            );
+        }
+    });
+
+    assert_eq!(allocations, 0);
+}
@@ -11,3 +11,16 @@ fn prints_help() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("hyprspaces"));
 }
+
+#[test]
+fn prints_version_json() {
+    let output = Command::new(env!("CARGO_BIN_EXE_hyprspaces"))
+        .args(["--version", "--json"])
+        .output()
+        .expect("run");
+
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(report["version"], env!("CARGO_PKG_VERSION"));
+    assert!(report["features"].is_array());
+}
@@ -1,17 +1,126 @@
 use clap::{CommandFactory, Parser};
 
+use std::path::PathBuf;
+use std::time::Duration;
+
+use hyprspaces::cli::{
+    Cli, Command, ConfigCommand, CursorArg, CycleOrderArg, DaemonCommand, HoldActionArg,
+    IpcBackend, LogCommand, PairedCommand, PairedSideArg, ScheduleCommand, SendTargetArg,
+};
+#[cfg(feature = "cli-full")]
 use hyprspaces::cli::{
-    Cli, Command, PairedCommand, SessionCommand, SessionRestoreMode, SetupCommand,
+    QueryCommand, QueryFormat, RulesCommand, SessionCommand, SessionRestoreMode, SetupCommand,
+    WaybarCompat,
 };
+use hyprspaces::paired::WorkspaceArg;
+#[cfg(feature = "cli-full")]
+use hyprspaces::setup::{BarPosition, ModulesSection};
 
 #[test]
 fn parses_paired_switch() {
     let cli = Cli::try_parse_from(["hyprspaces", "paired", "switch", "3"]).expect("parse");
 
-    match cli.command {
+    match cli.command.expect("command") {
+        Command::Paired {
+            command:
+                PairedCommand::Switch {
+                    workspace,
+                    raw,
+                    cursor,
+                },
+        } => {
+            assert_eq!(workspace, WorkspaceArg::Id(3));
+            assert!(!raw);
+            assert_eq!(cursor, None);
+        }
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+fn parses_send_with_paired_flags() {
+    let cli = Cli::try_parse_from(["hyprspaces", "send", "switch", "--raw", "13"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Send { args } => assert_eq!(args, vec!["switch", "--raw", "13"]),
+        _ => panic!("unexpected command"),
+    }
+    assert!(Cli::try_parse_from(["hyprspaces", "send"]).is_err());
+}
+
+#[test]
+fn parses_paired_switch_by_name() {
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "switch", "code"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::Switch { workspace, .. },
+        } => assert_eq!(workspace, WorkspaceArg::Name("code".to_string())),
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+fn parses_paired_switch_raw() {
+    let cli =
+        Cli::try_parse_from(["hyprspaces", "paired", "switch", "--raw", "13"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::Switch { workspace, raw, .. },
+        } => {
+            assert_eq!(workspace, WorkspaceArg::Id(13));
+            assert!(raw);
+        }
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+fn parses_paired_cycle_occupied_only() {
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "cycle", "next", "--occupied-only"])
+        .expect("parse");
+
+    match cli.command.expect("command") {
         Command::Paired {
-            command: PairedCommand::Switch { workspace },
-        } => assert_eq!(workspace, 3),
+            command: PairedCommand::Cycle { occupied_only, .. },
+        } => assert!(occupied_only),
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+fn parses_paired_cycle_order() {
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "cycle", "prev", "--order", "mru"])
+        .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::Cycle { order, .. },
+        } => assert_eq!(order, CycleOrderArg::Mru),
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+fn parses_cursor_override_on_switch_and_cycle() {
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "switch", "3", "--cursor", "follow"])
+        .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::Switch { cursor, .. },
+        } => assert_eq!(cursor, Some(CursorArg::Follow)),
+        _ => panic!("unexpected command"),
+    }
+
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "cycle", "next", "--cursor", "stay"])
+        .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::Cycle { cursor, .. },
+        } => assert_eq!(cursor, Some(CursorArg::Stay)),
         _ => panic!("unexpected command"),
     }
 }
@@ -20,7 +129,7 @@ fn parses_paired_switch() {
 fn parses_paired_grab_rogue() {
     let cli = Cli::try_parse_from(["hyprspaces", "paired", "grab-rogue"]).expect("parse");
 
-    match cli.command {
+    match cli.command.expect("command") {
         Command::Paired {
             command: PairedCommand::GrabRogue,
         } => {}
@@ -29,17 +138,569 @@ fn parses_paired_grab_rogue() {
 }
 
 #[test]
+fn parses_paired_send_to() {
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "send-to", "other"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::SendTo { target },
+        } => assert_eq!(target, SendTargetArg::Other),
+        _ => panic!("unexpected command"),
+    }
+    assert!(Cli::try_parse_from(["hyprspaces", "paired", "send-to", "left"]).is_err());
+}
+
+#[test]
+fn parses_paired_urgent() {
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "urgent"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::Urgent,
+        } => {}
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+fn parses_paired_hold() {
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "hold"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::Hold { action, monitor },
+        } => {
+            assert_eq!(action, HoldActionArg::Toggle);
+            assert_eq!(monitor, PairedSideArg::Secondary);
+        }
+        _ => panic!("unexpected command"),
+    }
+
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "hold", "on", "--monitor", "primary"])
+        .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::Hold { action, monitor },
+        } => {
+            assert_eq!(action, HoldActionArg::On);
+            assert_eq!(monitor, PairedSideArg::Primary);
+        }
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+fn parses_paired_stick() {
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "stick"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::Stick { address: None },
+        } => {}
+        _ => panic!("unexpected command"),
+    }
+
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "stick", "--address", "0x55aa"])
+        .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::Stick { address },
+        } => assert_eq!(address.as_deref(), Some("0x55aa")),
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+fn parses_paired_clone() {
+    let cli = Cli::try_parse_from(["hyprspaces", "paired", "clone", "2", "5"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Paired {
+            command: PairedCommand::Clone { from, to },
+        } => {
+            assert_eq!(from, WorkspaceArg::Id(2));
+            assert_eq!(to, WorkspaceArg::Id(5));
+        }
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
 fn parses_setup_migrate_windows() {
     let cli = Cli::try_parse_from(["hyprspaces", "setup", "migrate-windows"]).expect("parse");
 
-    match cli.command {
+    match cli.command.expect("command") {
+        Command::Setup {
+            command:
+                SetupCommand::MigrateWindows {
+                    preview: false,
+                    yes: false,
+                    only,
+                    except,
+                },
+        } => assert!(only.is_empty() && except.is_empty()),
+        _ => panic!("unexpected command"),
+    }
+
+    let cli = Cli::try_parse_from([
+        "hyprspaces",
+        "setup",
+        "migrate-windows",
+        "--preview",
+        "--only",
+        "kitty",
+        "--only",
+        "firefox",
+        "--except",
+        "mpv",
+    ])
+    .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Setup {
+            command:
+                SetupCommand::MigrateWindows {
+                    preview: true,
+                    yes: false,
+                    only,
+                    except,
+                },
+        } => {
+            assert_eq!(only, vec!["kitty", "firefox"]);
+            assert_eq!(except, vec!["mpv"]);
+        }
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_setup_migrate_windows_yes() {
+    let cli =
+        Cli::try_parse_from(["hyprspaces", "setup", "migrate-windows", "--yes"]).expect("parse");
+
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Setup {
+            command: SetupCommand::MigrateWindows { yes: true, .. },
+        }
+    ));
+    assert!(
+        Cli::try_parse_from([
+            "hyprspaces",
+            "setup",
+            "migrate-windows",
+            "--yes",
+            "--preview"
+        ])
+        .is_err()
+    );
+}
+
+#[test]
+fn parses_daemon_with_and_without_subcommand() {
+    let cli = Cli::try_parse_from(["hyprspaces", "daemon"]).expect("parse");
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Daemon {
+            replace: false,
+            no_initial_rebalance: false,
+            trace_decisions: false,
+            restore_on_start: false,
+            rebalance_debounce: None,
+            focus_debounce: None,
+            record: None,
+            replay: None,
+            command: None,
+            ..
+        }
+    ));
+
+    let cli = Cli::try_parse_from(["hyprspaces", "daemon", "--replace"]).expect("parse");
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Daemon {
+            replace: true,
+            command: None,
+            ..
+        }
+    ));
+
+    let cli = Cli::try_parse_from(["hyprspaces", "daemon", "reload"]).expect("parse");
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Daemon {
+            command: Some(DaemonCommand::Reload),
+            ..
+        }
+    ));
+    assert!(Cli::try_parse_from(["hyprspaces", "daemon", "--replace", "reload"]).is_err());
+
+    let cli =
+        Cli::try_parse_from(["hyprspaces", "daemon", "--no-initial-rebalance"]).expect("parse");
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Daemon {
+            no_initial_rebalance: true,
+            command: None,
+            ..
+        }
+    ));
+
+    let cli = Cli::try_parse_from(["hyprspaces", "daemon", "--trace-decisions"]).expect("parse");
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Daemon {
+            trace_decisions: true,
+            command: None,
+            ..
+        }
+    ));
+
+    let cli = Cli::try_parse_from(["hyprspaces", "daemon", "--restore-on-start"]).expect("parse");
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Daemon {
+            restore_on_start: true,
+            command: None,
+            ..
+        }
+    ));
+
+    let cli = Cli::try_parse_from([
+        "hyprspaces",
+        "daemon",
+        "--rebalance-debounce",
+        "500",
+        "--focus-debounce",
+        "0",
+    ])
+    .expect("parse");
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Daemon {
+            rebalance_debounce: Some(500),
+            focus_debounce: Some(0),
+            ..
+        }
+    ));
+    assert!(Cli::try_parse_from(["hyprspaces", "daemon", "--rebalance-debounce", "0"]).is_err());
+    assert!(Cli::try_parse_from(["hyprspaces", "daemon", "--focus-debounce", "10001"]).is_err());
+}
+
+#[test]
+fn parses_daemon_record_and_replay() {
+    let cli =
+        Cli::try_parse_from(["hyprspaces", "daemon", "--record", "events.log"]).expect("parse");
+    match cli.command.expect("command") {
+        Command::Daemon { record, replay, .. } => {
+            assert_eq!(record, Some(PathBuf::from("events.log")));
+            assert_eq!(replay, None);
+        }
+        _ => panic!("unexpected command"),
+    }
+
+    let cli = Cli::try_parse_from([
+        "hyprspaces",
+        "daemon",
+        "--replay",
+        "events.log",
+        "--speed",
+        "4",
+    ])
+    .expect("parse");
+    match cli.command.expect("command") {
+        Command::Daemon { replay, speed, .. } => {
+            assert_eq!(replay, Some(PathBuf::from("events.log")));
+            assert_eq!(speed, 4.0);
+        }
+        _ => panic!("unexpected command"),
+    }
+
+    assert!(Cli::try_parse_from(["hyprspaces", "daemon", "--speed", "2"]).is_err());
+    assert!(
+        Cli::try_parse_from(["hyprspaces", "daemon", "--replay", "a", "--speed", "0"]).is_err()
+    );
+    assert!(
+        Cli::try_parse_from(["hyprspaces", "daemon", "--replay", "a", "--record", "b"]).is_err()
+    );
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_query_windows_format() {
+    let cli = Cli::try_parse_from(["hyprspaces", "query", "windows", "--format", "rofi-script"])
+        .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Query {
+            command: QueryCommand::Windows { format },
+        } => assert_eq!(format, QueryFormat::RofiScript),
+        _ => panic!("unexpected command"),
+    }
+    assert!(Cli::try_parse_from(["hyprspaces", "query", "windows", "--format", "dmenu"]).is_err());
+}
+
+#[test]
+fn parses_socket_ipc_backend() {
+    let cli = Cli::try_parse_from(["hyprspaces", "--ipc", "socket", "status"]).expect("parse");
+
+    assert!(matches!(cli.ipc, IpcBackend::Socket));
+}
+
+#[test]
+fn parses_version_json() {
+    let cli = Cli::try_parse_from(["hyprspaces", "--version", "--json"]).expect("parse");
+
+    assert!(cli.version);
+    assert!(cli.json);
+    assert!(cli.command.is_none());
+    assert!(Cli::try_parse_from(["hyprspaces", "--json", "status"]).is_err());
+}
+
+#[test]
+fn parses_keyword_dry_run() {
+    let cli = Cli::try_parse_from([
+        "hyprspaces",
+        "keyword",
+        "general:gaps_out",
+        "12",
+        "--dry-run",
+    ])
+    .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Keyword {
+            name,
+            value,
+            dry_run,
+        } => {
+            assert_eq!(name, "general:gaps_out");
+            assert_eq!(value, "12");
+            assert!(dry_run);
+        }
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_waybar_daemon_timeout() {
+    let cli = Cli::try_parse_from(["hyprspaces", "waybar", "--enable-waybar"]).expect("parse");
+    match cli.command.expect("command") {
+        Command::Waybar(args) => assert_eq!(args.daemon_timeout, Duration::from_secs(30)),
+        _ => panic!("unexpected command"),
+    }
+
+    let cli = Cli::try_parse_from([
+        "hyprspaces",
+        "waybar",
+        "--enable-waybar",
+        "--daemon-timeout",
+        "0",
+    ])
+    .expect("parse");
+    match cli.command.expect("command") {
+        Command::Waybar(args) => assert!(args.daemon_timeout.is_zero()),
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_waybar_compat_format() {
+    let cli = Cli::try_parse_from([
+        "hyprspaces",
+        "waybar",
+        "--enable-waybar",
+        "--compat",
+        "hyprland-workspaces",
+    ])
+    .expect("parse");
+    match cli.command.expect("command") {
+        Command::Waybar(args) => {
+            assert_eq!(args.compat, Some(WaybarCompat::HyprlandWorkspaces));
+        }
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_setup_install_autostart_xdg() {
+    let cli =
+        Cli::try_parse_from(["hyprspaces", "setup", "install", "--autostart-xdg"]).expect("parse");
+
+    match cli.command.expect("command") {
         Command::Setup {
-            command: SetupCommand::MigrateWindows,
+            command: SetupCommand::Install(args),
+        } => {
+            assert!(args.autostart_xdg);
+            assert!(!args.waybar);
+        }
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_setup_install_waybar_placement() {
+    let cli = Cli::try_parse_from([
+        "hyprspaces",
+        "setup",
+        "install",
+        "--waybar",
+        "--waybar-bar",
+        "bottom",
+        "--waybar-output",
+        "DP-1",
+        "--waybar-modules",
+        "center",
+        "--waybar-index",
+        "2",
+    ])
+    .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Setup {
+            command: SetupCommand::Install(args),
+        } => {
+            assert_eq!(args.waybar_bar, Some(BarPosition::Bottom));
+            assert_eq!(args.waybar_output.as_deref(), Some("DP-1"));
+            assert_eq!(args.waybar_modules, Some(ModulesSection::Center));
+            assert_eq!(args.waybar_index, Some(2));
+        }
+        _ => panic!("unexpected command"),
+    }
+    assert!(
+        Cli::try_parse_from(["hyprspaces", "setup", "install", "--waybar-index", "0"]).is_err()
+    );
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_waybar_follow_file_flag() {
+    let cli = Cli::try_parse_from(["hyprspaces", "waybar", "--enable-waybar", "--follow-file"])
+        .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Waybar(args) => assert!(args.follow_file),
+        _ => panic!("unexpected command"),
+    }
+    assert!(
+        Cli::try_parse_from([
+            "hyprspaces",
+            "waybar",
+            "--follow-file",
+            "--compat",
+            "hyprland-workspaces"
+        ])
+        .is_err()
+    );
+}
+
+#[test]
+fn parses_capabilities_json_flag() {
+    let cli = Cli::try_parse_from(["hyprspaces", "capabilities", "--json"]).expect("parse");
+
+    assert!(matches!(
+        cli.command,
+        Some(Command::Capabilities { json: true })
+    ));
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_setup_rollback() {
+    let cli = Cli::try_parse_from(["hyprspaces", "setup", "rollback"]).expect("parse");
+
+    assert!(matches!(
+        cli.command,
+        Some(Command::Setup {
+            command: SetupCommand::Rollback
+        })
+    ));
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_doctor_fix_flags() {
+    let cli = Cli::try_parse_from(["hyprspaces", "doctor", "--fix", "--yes"]).expect("parse");
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Doctor {
+            fix: true,
+            yes: true
+        }
+    ));
+    assert!(Cli::try_parse_from(["hyprspaces", "doctor", "--yes"]).is_err());
+}
+
+#[test]
+fn parses_config_validate() {
+    let cli = Cli::try_parse_from(["hyprspaces", "config", "validate"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Config {
+            command: ConfigCommand::Validate,
         } => {}
         _ => panic!("unexpected command"),
     }
 }
 
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_config_init_force() {
+    let cli = Cli::try_parse_from(["hyprspaces", "config", "init", "--force"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Config {
+            command: ConfigCommand::Init { force },
+        } => assert!(force),
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_config_set() {
+    let cli = Cli::try_parse_from(["hyprspaces", "config", "set", "workspace_count", "12"])
+        .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Config {
+            command: ConfigCommand::Set { key, value },
+        } => {
+            assert_eq!(key, "workspace_count");
+            assert_eq!(value, "12");
+        }
+        _ => panic!("unexpected command"),
+    }
+    assert!(Cli::try_parse_from(["hyprspaces", "config", "set", "workspace_count"]).is_err());
+}
+
+#[test]
+fn parses_log_show_since() {
+    let cli = Cli::try_parse_from(["hyprspaces", "log", "show", "--since", "10m"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Log {
+            command: LogCommand::Show { since },
+        } => assert_eq!(since, Some(Duration::from_secs(600))),
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+fn rejects_invalid_log_since() {
+    let cli = Cli::try_parse_from(["hyprspaces", "log", "show", "--since", "soon"]);
+
+    assert!(cli.is_err());
+}
+
 #[test]
 fn parses_completions_bash() {
     let cli = Cli::try_parse_from(["hyprspaces", "completions", "bash"]);
@@ -62,18 +723,23 @@ fn parses_status_command() {
 }
 
 #[test]
+#[cfg(feature = "cli-full")]
 fn parses_session_save() {
     let cli = Cli::try_parse_from(["hyprspaces", "session", "save"]).expect("parse");
 
-    match cli.command {
+    match cli.command.expect("command") {
         Command::Session {
-            command: SessionCommand::Save { path },
-        } => assert!(path.is_none()),
+            command: SessionCommand::Save { path, name },
+        } => {
+            assert!(path.is_none());
+            assert!(name.is_none());
+        }
         _ => panic!("unexpected command"),
     }
 }
 
 #[test]
+#[cfg(feature = "cli-full")]
 fn parses_session_restore_mode() {
     let cli = Cli::try_parse_from([
         "hyprspaces",
@@ -81,16 +747,222 @@ fn parses_session_restore_mode() {
         "restore",
         "--mode",
         "cold",
+        "--allow-degraded",
     ])
     .expect("parse");
 
-    match cli.command {
+    match cli.command.expect("command") {
         Command::Session {
-            command: SessionCommand::Restore { mode, path },
+            command:
+                SessionCommand::Restore {
+                    mode,
+                    path,
+                    allow_degraded,
+                    ..
+                },
         } => {
             assert_eq!(mode, SessionRestoreMode::Cold);
             assert!(path.is_none());
+            assert!(allow_degraded);
+        }
+        _ => panic!("unexpected command"),
+    }
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_named_session_commands() {
+    let cli =
+        Cli::try_parse_from(["hyprspaces", "session", "restore", "--name", "work"]).expect("parse");
+    match cli.command.expect("command") {
+        Command::Session {
+            command: SessionCommand::Restore { name, path, .. },
+        } => {
+            assert_eq!(name.as_deref(), Some("work"));
+            assert!(path.is_none());
+        }
+        _ => panic!("unexpected command"),
+    }
+
+    let cli =
+        Cli::try_parse_from(["hyprspaces", "session", "delete", "--name", "work"]).expect("parse");
+    match cli.command.expect("command") {
+        Command::Session {
+            command: SessionCommand::Delete { name },
+        } => assert_eq!(name, "work"),
+        _ => panic!("unexpected command"),
+    }
+
+    let cli = Cli::try_parse_from([
+        "hyprspaces",
+        "session",
+        "diff",
+        "--name",
+        "work",
+        "--against",
+        "home",
+    ])
+    .expect("parse");
+    match cli.command.expect("command") {
+        Command::Session {
+            command:
+                SessionCommand::Diff {
+                    name,
+                    against,
+                    mode,
+                    ..
+                },
+        } => {
+            assert_eq!(name.as_deref(), Some("work"));
+            assert_eq!(against, "home");
+            assert_eq!(mode, SessionRestoreMode::Auto);
         }
         _ => panic!("unexpected command"),
     }
+
+    let cli = Cli::try_parse_from(["hyprspaces", "session", "list"]).expect("parse");
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Session {
+            command: SessionCommand::List
+        }
+    ));
+
+    assert!(
+        Cli::try_parse_from([
+            "hyprspaces",
+            "session",
+            "save",
+            "--name",
+            "work",
+            "--path",
+            "/tmp/work.json",
+        ])
+        .is_err()
+    );
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_rules_sync() {
+    let cli = Cli::try_parse_from(["hyprspaces", "rules", "sync"]).expect("parse");
+
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Rules {
+            command: RulesCommand::Sync
+        }
+    ));
+}
+
+#[test]
+fn parses_schedule_commands() {
+    let cli = Cli::try_parse_from(["hyprspaces", "schedule", "disable", "work"]).expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Schedule {
+            command: ScheduleCommand::Disable { name },
+        } => assert_eq!(name, "work"),
+        _ => panic!("unexpected command"),
+    }
+    assert!(Cli::try_parse_from(["hyprspaces", "schedule", "list"]).is_ok());
+}
+
+#[test]
+fn parses_rebalance_modes() {
+    let cli = Cli::try_parse_from(["hyprspaces", "rebalance", "--preserve"]).expect("parse");
+
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Rebalance {
+            strict: false,
+            preserve: true
+        }
+    ));
+    assert!(Cli::try_parse_from(["hyprspaces", "rebalance", "--strict", "--preserve"]).is_err());
+}
+
+#[test]
+fn parses_reconcile_modes() {
+    let cli = Cli::try_parse_from(["hyprspaces", "reconcile", "--fix"]).expect("parse");
+
+    assert!(matches!(
+        cli.command.expect("command"),
+        Command::Reconcile {
+            fix: true,
+            dry_run: false
+        }
+    ));
+    assert!(Cli::try_parse_from(["hyprspaces", "reconcile", "--fix", "--dry-run"]).is_err());
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_bar_format() {
+    use hyprspaces::cli::BarFormat;
+
+    let cli = Cli::try_parse_from(["hyprspaces", "bar"]).expect("parse");
+    match cli.command.expect("command") {
+        Command::Bar(args) => {
+            assert_eq!(args.format, BarFormat::WaybarJson);
+            assert_eq!(args.daemon_timeout, Duration::from_secs(30));
+        }
+        _ => panic!("unexpected command"),
+    }
+
+    for (name, format) in [
+        ("eww", BarFormat::Eww),
+        ("polybar", BarFormat::Polybar),
+        ("plain", BarFormat::Plain),
+    ] {
+        let cli = Cli::try_parse_from(["hyprspaces", "bar", "--format", name, "--monitor", "DP-1"])
+            .expect("parse");
+        match cli.command.expect("command") {
+            Command::Bar(args) => {
+                assert_eq!(args.format, format);
+                assert_eq!(args.monitor.as_deref(), Some("DP-1"));
+            }
+            _ => panic!("unexpected command"),
+        }
+    }
+    assert!(Cli::try_parse_from(["hyprspaces", "bar", "--format", "lemonbar"]).is_err());
+}
+
+#[test]
+#[cfg(feature = "cli-full")]
+fn parses_waybar_monitor() {
+    let cli = Cli::try_parse_from([
+        "hyprspaces",
+        "waybar",
+        "--enable-waybar",
+        "--monitor",
+        "DP-1",
+    ])
+    .expect("parse");
+
+    match cli.command.expect("command") {
+        Command::Waybar(args) => assert_eq!(args.monitor.as_deref(), Some("DP-1")),
+        _ => panic!("unexpected command"),
+    }
+    assert!(
+        Cli::try_parse_from([
+            "hyprspaces",
+            "waybar",
+            "--enable-waybar",
+            "--follow-file",
+            "--monitor",
+            "DP-1"
+        ])
+        .is_err()
+    );
+    assert!(
+        Cli::try_parse_from([
+            "hyprspaces",
+            "setup",
+            "install",
+            "--waybar",
+            "--waybar-per-output"
+        ])
+        .is_ok()
+    );
 }
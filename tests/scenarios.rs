@@ -0,0 +1,85 @@
+#![cfg(feature = "test-util")]
+
+use hyprspaces::Engine;
+use hyprspaces::config::Config;
+use hyprspaces::paired::CycleDirection;
+use hyprspaces::testing::FakeHyprland;
+
+fn desk() -> FakeHyprland {
+    FakeHyprland::new()
+        .with_monitor("DP-1", 2560, 1440)
+        .with_monitor("HDMI-A-1", 1920, 1080)
+}
+
+fn engine(hyprland: &FakeHyprland) -> Engine<FakeHyprland> {
+    let config = Config::builder()
+        .primary_monitor("DP-1")
+        .secondary_monitor("HDMI-A-1")
+        .build()
+        .expect("config");
+    Engine::new(config, hyprland.clone())
+}
+
+#[test]
+fn switching_shows_the_pair_on_both_monitors() {
+    let hyprland = desk();
+    let engine = engine(&hyprland);
+    engine.rebalance().expect("rebalance");
+
+    engine.switch(3).expect("switch");
+
+    assert_eq!(hyprland.shown("DP-1"), Some(3));
+    assert_eq!(hyprland.shown("HDMI-A-1"), Some(13));
+    assert_eq!(hyprland.focused_monitor(), "DP-1");
+    assert_eq!(engine.active_slot().expect("slot"), 3);
+
+    engine.cycle(CycleDirection::Next).expect("cycle");
+
+    assert_eq!(hyprland.shown("DP-1"), Some(4));
+    assert_eq!(hyprland.shown("HDMI-A-1"), Some(14));
+}
+
+#[test]
+fn moving_a_window_follows_it_to_its_pair() {
+    let hyprland = desk().with_window("0x1", "kitty", 1);
+    let engine = engine(&hyprland);
+
+    engine.move_window(5).expect("move window");
+
+    assert_eq!(hyprland.window_workspace("0x1"), Some(5));
+    assert_eq!(hyprland.shown("DP-1"), Some(5));
+    assert_eq!(hyprland.shown("HDMI-A-1"), Some(15));
+    assert_eq!(hyprland.focused_window().as_deref(), Some("0x1"));
+}
+
+#[test]
+fn rebalance_returns_workspaces_after_a_hotplug() {
+    let hyprland = desk();
+    let engine = engine(&hyprland);
+    engine.switch(2).expect("switch");
+    hyprland.open_window("0x1", "firefox", 12);
+
+    hyprland.disconnect_monitor("HDMI-A-1");
+    assert_eq!(hyprland.workspace_monitor(12).as_deref(), Some("DP-1"));
+
+    hyprland.connect_monitor("HDMI-A-1", 1920, 1080);
+    engine.rebalance().expect("rebalance");
+
+    assert_eq!(hyprland.workspace_monitor(12).as_deref(), Some("HDMI-A-1"));
+    assert_eq!(hyprland.window_workspace("0x1"), Some(12));
+}
+
+#[test]
+fn focusing_a_window_shows_its_pair() {
+    let hyprland = desk()
+        .with_window("0x1", "kitty", 1)
+        .with_window("0x2", "firefox", 17);
+    let engine = engine(&hyprland);
+    engine.rebalance().expect("rebalance");
+
+    assert_eq!(engine.focus_window("0x2").expect("focus"), 17);
+
+    assert_eq!(hyprland.shown("DP-1"), Some(7));
+    assert_eq!(hyprland.shown("HDMI-A-1"), Some(17));
+    assert_eq!(hyprland.focused_window().as_deref(), Some("0x2"));
+}
@@ -1,8 +1,19 @@
+#![cfg(feature = "cli-full")]
+
+use std::collections::BTreeMap;
+use std::fs;
 use std::path::Path;
+use std::time::{Duration, SystemTime};
 
-use hyprspaces::config::Config;
+use hyprspaces::config::{Config, OutOfRangePolicy, RebalanceMode};
 use hyprspaces::hyprctl::{ClientInfo, MonitorInfo, WorkspaceInfo, WorkspaceRef};
-use hyprspaces::session::{restore_batch, session_path, RestoreMode, SessionSnapshot};
+use hyprspaces::paths::sessions_dir;
+use hyprspaces::session::{
+    FullscreenState, RestoreMode, SESSION_VERSION, SessionError, SessionSnapshot, SnapshotClient,
+    SnapshotFocus, SnapshotMonitor, SnapshotWorkspace, WindowGeometry, collapse_to_primary,
+    diff_snapshots, missing_monitors, newest_snapshot, parse_snapshot, render_diff, restore_batch,
+    session_path, shutdown_path, unmatched_clients,
+};
 
 fn test_config() -> Config {
     Config {
@@ -11,6 +22,35 @@ fn test_config() -> Config {
         paired_offset: 10,
         workspace_count: 10,
         wrap_cycling: true,
+        cycle_skip_empty: false,
+        out_of_range: OutOfRangePolicy::Normalize,
+        profiles: Vec::new(),
+        rebalance_on_start: true,
+        hotplug_settle_ms: 0,
+        schedules: Vec::new(),
+        rebalance_mode: RebalanceMode::Strict,
+        idle_inhibit_slots: Vec::new(),
+        launch: BTreeMap::new(),
+        names: BTreeMap::new(),
+        theme_source: Default::default(),
+        waybar: Default::default(),
+        drag_focus_delay_ms: 0,
+        metrics_address: None,
+        retry: Default::default(),
+        fast_switch: false,
+        enforce_range: false,
+        jump_to_urgent: false,
+        cursor_follows_focus: false,
+        instant_rebalance: false,
+        debounce: Default::default(),
+        layout: Vec::new(),
+        sticky_classes: Vec::new(),
+        reconcile: Default::default(),
+        primary_match: None,
+        secondary_match: None,
+        auto_primary: Default::default(),
+        workspace_base: 1,
+        restore: Default::default(),
     }
 }
 
@@ -18,21 +58,63 @@ fn test_config() -> Config {
 fn session_path_defaults_to_latest() {
     let base = Path::new("/tmp/hyprspaces");
 
-    let path = session_path(base, None);
+    let path = session_path(&sessions_dir(base, None), None);
 
     assert_eq!(path, base.join("sessions").join("latest.json"));
 }
 
+#[test]
+fn session_path_is_scoped_to_the_instance() {
+    let base = Path::new("/tmp/hyprspaces");
+
+    let path = session_path(&sessions_dir(base, Some("abc_123")), None);
+
+    assert_eq!(
+        path,
+        base.join("sessions").join("abc_123").join("latest.json")
+    );
+}
+
 #[test]
 fn session_path_uses_override() {
     let base = Path::new("/tmp/hyprspaces");
     let override_path = Path::new("/tmp/custom/session.json");
 
-    let path = session_path(base, Some(override_path));
+    let path = session_path(&sessions_dir(base, None), Some(override_path));
 
     assert_eq!(path, override_path);
 }
 
+#[test]
+fn newest_snapshot_spans_instance_directories() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let root = sessions_dir(dir.path(), None);
+    assert_eq!(newest_snapshot(&root), None);
+
+    let write = |path: &Path, age: u64| {
+        fs::create_dir_all(path.parent().expect("parent")).expect("dir");
+        fs::write(path, "{}").expect("write");
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .expect("open")
+            .set_modified(SystemTime::now() - Duration::from_secs(age))
+            .expect("mtime");
+    };
+    let manual = session_path(&root, None);
+    let previous = shutdown_path(&sessions_dir(dir.path(), Some("old_1")));
+    write(&manual, 60);
+    write(&previous, 10);
+    write(
+        &session_path(&sessions_dir(dir.path(), Some("older_0")), None),
+        3600,
+    );
+    // A named slot called "latest" is never picked up for cold restores.
+    write(&root.join("named").join("latest.json"), 0);
+
+    assert_eq!(newest_snapshot(&root), Some(previous));
+}
+
 #[test]
 fn snapshot_computes_paired_slot_and_focus() {
     let config = test_config();
@@ -40,6 +122,8 @@ fn snapshot_computes_paired_slot_and_focus() {
         name: "HDMI-A-1".to_string(),
         x: 0,
         id: 1,
+        mirror_of: None,
+        ..Default::default()
     }];
     let workspaces = vec![WorkspaceInfo {
         id: 13,
@@ -59,6 +143,13 @@ fn snapshot_computes_paired_slot_and_focus() {
         initial_title: None,
         app_id: None,
         pid: Some(4242),
+        focus_history_id: None,
+        floating: false,
+        at: None,
+        size: None,
+        fullscreen: 0,
+        fullscreen_client: 0,
+        pseudo: false,
     }];
 
     let snapshot = SessionSnapshot::from_state(
@@ -97,16 +188,16 @@ fn snapshot_skips_pairing_for_special_workspace() {
         initial_title: None,
         app_id: None,
         pid: Some(4242),
+        focus_history_id: None,
+        floating: false,
+        at: None,
+        size: None,
+        fullscreen: 0,
+        fullscreen_client: 0,
+        pseudo: false,
     }];
 
-    let snapshot = SessionSnapshot::from_state(
-        &config,
-        None,
-        0,
-        monitors,
-        workspaces,
-        clients,
-    );
+    let snapshot = SessionSnapshot::from_state(&config, None, 0, monitors, workspaces, clients);
 
     assert_eq!(snapshot.clients[0].paired_slot, 0);
     assert_eq!(
@@ -141,6 +232,9 @@ fn restore_same_session_moves_mismatched_clients() {
             workspace_id: 2,
             workspace_name: None,
             paired_slot: 2,
+            floating: None,
+            fullscreen: None,
+            pseudo: false,
         }],
     };
     let current_clients = vec![ClientInfo {
@@ -152,6 +246,13 @@ fn restore_same_session_moves_mismatched_clients() {
         initial_title: None,
         app_id: None,
         pid: None,
+        focus_history_id: None,
+        floating: false,
+        at: None,
+        size: None,
+        fullscreen: 0,
+        fullscreen_client: 0,
+        pseudo: false,
     }];
 
     let batch = restore_batch(
@@ -194,6 +295,9 @@ fn restore_same_session_uses_special_workspace_name() {
             workspace_id: 0,
             workspace_name: Some("special:term".to_string()),
             paired_slot: 0,
+            floating: None,
+            fullscreen: None,
+            pseudo: false,
         }],
     };
     let current_clients = vec![ClientInfo {
@@ -208,6 +312,13 @@ fn restore_same_session_uses_special_workspace_name() {
         initial_title: None,
         app_id: None,
         pid: None,
+        focus_history_id: None,
+        floating: false,
+        at: None,
+        size: None,
+        fullscreen: 0,
+        fullscreen_client: 0,
+        pseudo: false,
     }];
 
     let batch = restore_batch(
@@ -250,6 +361,9 @@ fn restore_cold_matches_by_app_id() {
             workspace_id: 4,
             workspace_name: None,
             paired_slot: 4,
+            floating: None,
+            fullscreen: None,
+            pseudo: false,
         }],
     };
     let current_clients = vec![ClientInfo {
@@ -261,6 +375,13 @@ fn restore_cold_matches_by_app_id() {
         initial_title: None,
         app_id: Some("org.gnome.Nautilus".to_string()),
         pid: None,
+        focus_history_id: None,
+        floating: false,
+        at: None,
+        size: None,
+        fullscreen: 0,
+        fullscreen_client: 0,
+        pseudo: false,
     }];
 
     let batch = restore_batch(
@@ -277,6 +398,150 @@ fn restore_cold_matches_by_app_id() {
     );
 }
 
+fn diff_client(address: &str, app_id: &str, workspace_id: u32) -> SnapshotClient {
+    SnapshotClient {
+        address: address.to_string(),
+        class: None,
+        title: None,
+        initial_class: None,
+        initial_title: None,
+        app_id: Some(app_id.to_string()),
+        pid: None,
+        workspace_id,
+        workspace_name: None,
+        paired_slot: workspace_id,
+        floating: None,
+        fullscreen: None,
+        pseudo: false,
+    }
+}
+
+fn diff_snapshot(signature: &str, monitor: &str, clients: Vec<SnapshotClient>) -> SessionSnapshot {
+    SessionSnapshot {
+        version: 1,
+        created_at: 0,
+        signature: Some(signature.to_string()),
+        paired_offset: 10,
+        workspace_count: 10,
+        focus: SnapshotFocus {
+            monitor: None,
+            workspace_id: 1,
+        },
+        monitors: Vec::new(),
+        workspaces: vec![SnapshotWorkspace {
+            id: 13,
+            name: None,
+            monitor: Some(monitor.to_string()),
+            windows: 0,
+        }],
+        clients,
+    }
+}
+
+#[test]
+fn diff_reports_moves_arrivals_departures_and_the_restore_plan() {
+    let config = test_config();
+    let snapshot = diff_snapshot(
+        "old",
+        "HDMI-A-1",
+        vec![
+            diff_client("0xabc", "org.gnome.Nautilus", 4),
+            diff_client("0x111", "firefox", 2),
+        ],
+    );
+    let current = diff_snapshot(
+        "new",
+        "DP-1",
+        vec![
+            diff_client("0xdef", "org.gnome.Nautilus", 1),
+            diff_client("0x222", "kitty", 1),
+        ],
+    );
+
+    let diff = diff_snapshots(&snapshot, &current, RestoreMode::Auto, &config);
+
+    assert_eq!(
+        render_diff(&diff),
+        "moved:\n  org.gnome.Nautilus (0xdef): 4 → 1\n\
+         appeared:\n  kitty (0x222) on 1\n\
+         vanished:\n  firefox (0x111) on 2\n\
+         monitors:\n  pair 3: workspace 13 HDMI-A-1 → DP-1\n\
+         restore would run:\n  dispatch movetoworkspacesilent 4,address:0xdef"
+    );
+}
+
+#[test]
+fn diff_pairs_windows_by_address_within_the_same_session() {
+    let config = test_config();
+    let snapshot = diff_snapshot("sig", "HDMI-A-1", vec![diff_client("0xabc", "kitty", 2)]);
+    let current = diff_snapshot("sig", "HDMI-A-1", vec![diff_client("0xabc", "kitty", 2)]);
+
+    let diff = diff_snapshots(&snapshot, &current, RestoreMode::Auto, &config);
+    assert!(diff.is_empty());
+    assert_eq!(render_diff(&diff), "no differences");
+
+    let renamed = diff_snapshot("sig", "HDMI-A-1", vec![diff_client("0xfff", "kitty", 2)]);
+    let diff = diff_snapshots(&snapshot, &renamed, RestoreMode::Same, &config);
+    assert_eq!(diff.appeared.len(), 1);
+    assert_eq!(diff.vanished.len(), 1);
+    assert!(diff.restore.is_empty());
+}
+
+#[test]
+fn unmatched_clients_lists_snapshot_windows_without_a_match() {
+    let client = |address: &str, class: &str| hyprspaces::session::SnapshotClient {
+        address: address.to_string(),
+        class: Some(class.to_string()),
+        title: None,
+        initial_class: Some(class.to_string()),
+        initial_title: None,
+        app_id: None,
+        pid: None,
+        workspace_id: 2,
+        workspace_name: None,
+        paired_slot: 2,
+        floating: None,
+        fullscreen: None,
+        pseudo: false,
+    };
+    let snapshot = SessionSnapshot {
+        version: 1,
+        created_at: 0,
+        signature: None,
+        paired_offset: 10,
+        workspace_count: 10,
+        focus: hyprspaces::session::SnapshotFocus {
+            monitor: None,
+            workspace_id: 1,
+        },
+        monitors: Vec::new(),
+        workspaces: Vec::new(),
+        clients: vec![client("0x1", "kitty"), client("0x2", "firefox")],
+    };
+    let current_clients = vec![ClientInfo {
+        address: "0x9".to_string(),
+        workspace: WorkspaceRef { id: 1, name: None },
+        class: Some("firefox".to_string()),
+        title: None,
+        initial_class: Some("firefox".to_string()),
+        initial_title: None,
+        app_id: None,
+        pid: None,
+        focus_history_id: None,
+        floating: false,
+        at: None,
+        size: None,
+        fullscreen: 0,
+        fullscreen_client: 0,
+        pseudo: false,
+    }];
+
+    let unmatched = unmatched_clients(&snapshot, &current_clients);
+
+    assert_eq!(unmatched.len(), 1);
+    assert_eq!(unmatched[0].address, "0x1");
+}
+
 #[test]
 fn restore_cold_skips_special_fallback() {
     let config = test_config();
@@ -306,6 +571,13 @@ fn restore_cold_skips_special_fallback() {
         initial_title: None,
         app_id: None,
         pid: None,
+        focus_history_id: None,
+        floating: false,
+        at: None,
+        size: None,
+        fullscreen: 0,
+        fullscreen_client: 0,
+        pseudo: false,
     }];
 
     let batch = restore_batch(
@@ -345,6 +617,9 @@ fn restore_cold_moves_special_workspace_by_name() {
             workspace_id: 0,
             workspace_name: Some("special:term".to_string()),
             paired_slot: 0,
+            floating: None,
+            fullscreen: None,
+            pseudo: false,
         }],
     };
     let current_clients = vec![ClientInfo {
@@ -359,6 +634,13 @@ fn restore_cold_moves_special_workspace_by_name() {
         initial_title: None,
         app_id: Some("org.example.Term".to_string()),
         pid: None,
+        focus_history_id: None,
+        floating: false,
+        at: None,
+        size: None,
+        fullscreen: 0,
+        fullscreen_client: 0,
+        pseudo: false,
     }];
 
     let batch = restore_batch(
@@ -401,6 +683,9 @@ fn restore_auto_uses_same_when_signature_matches() {
             workspace_id: 2,
             workspace_name: None,
             paired_slot: 2,
+            floating: None,
+            fullscreen: None,
+            pseudo: false,
         }],
     };
     let current_clients = vec![ClientInfo {
@@ -412,6 +697,13 @@ fn restore_auto_uses_same_when_signature_matches() {
         initial_title: None,
         app_id: None,
         pid: None,
+        focus_history_id: None,
+        floating: false,
+        at: None,
+        size: None,
+        fullscreen: 0,
+        fullscreen_client: 0,
+        pseudo: false,
     }];
 
     let batch = restore_batch(
@@ -454,6 +746,9 @@ fn restore_auto_uses_cold_when_signature_differs() {
             workspace_id: 4,
             workspace_name: None,
             paired_slot: 4,
+            floating: None,
+            fullscreen: None,
+            pseudo: false,
         }],
     };
     let current_clients = vec![ClientInfo {
@@ -465,6 +760,13 @@ fn restore_auto_uses_cold_when_signature_differs() {
         initial_title: None,
         app_id: Some("org.gnome.Nautilus".to_string()),
         pid: None,
+        focus_history_id: None,
+        floating: false,
+        at: None,
+        size: None,
+        fullscreen: 0,
+        fullscreen_client: 0,
+        pseudo: false,
     }];
 
     let batch = restore_batch(
@@ -480,3 +782,373 @@ fn restore_auto_uses_cold_when_signature_differs() {
         "dispatch movetoworkspacesilent 4,address:0xdef"
     );
 }
+
+#[test]
+fn parse_snapshot_upgrades_v1_files() {
+    let snapshot = parse_snapshot(
+        r#"{
+            "version": 1,
+            "created_at": 5,
+            "paired_offset": 10,
+            "workspace_count": 10,
+            "focus": {"workspace_id": 1},
+            "monitors": [{"id": 0, "name": "DP-1"}],
+            "workspaces": [],
+            "clients": []
+        }"#,
+    )
+    .expect("v1 snapshot");
+
+    assert_eq!(snapshot.version, SESSION_VERSION);
+    assert_eq!(
+        snapshot.monitors,
+        [SnapshotMonitor {
+            id: 0,
+            name: "DP-1".to_string(),
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+        }]
+    );
+}
+
+#[test]
+fn parse_snapshot_rejects_unknown_versions() {
+    let newer = format!(r#"{{"version": {}}}"#, SESSION_VERSION + 1);
+    for contents in [newer.as_str(), r#"{"version": 0}"#, r#"{"clients": []}"#] {
+        let err = parse_snapshot(contents).expect_err("unsupported");
+        assert!(matches!(err, SessionError::UnsupportedVersion { .. }));
+    }
+    assert_eq!(
+        parse_snapshot(&newer).expect_err("newer").to_string(),
+        format!(
+            "unsupported session snapshot version {} (this build reads 1 to {SESSION_VERSION})",
+            SESSION_VERSION + 1
+        )
+    );
+}
+
+/// Fixed-seed xorshift64 that varies the snapshot fixtures below.
+struct FixtureSeed(u64);
+
+impl FixtureSeed {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u32 {
+        (self.next() % bound) as u32
+    }
+
+    fn text(&mut self) -> Option<String> {
+        const PARTS: [&str; 6] = [
+            "kitty",
+            "Firefox",
+            "org.gnome.Nautilus",
+            "é \"quoted\"",
+            "",
+            "~/src",
+        ];
+        (self.below(4) != 0).then(|| PARTS[self.below(PARTS.len() as u64) as usize].to_string())
+    }
+}
+
+fn fixture_snapshot(rng: &mut FixtureSeed) -> SessionSnapshot {
+    let monitors = (0..rng.below(4))
+        .map(|id| SnapshotMonitor {
+            id: id as i32,
+            name: format!("DP-{id}"),
+            x: rng.below(7680) as i32 - 1920,
+            y: rng.below(2160) as i32,
+            width: rng.below(7680),
+            height: rng.below(4320),
+        })
+        .collect();
+    let workspaces = (0..rng.below(6))
+        .map(|id| SnapshotWorkspace {
+            id: id + 1,
+            name: rng.text(),
+            monitor: rng.text(),
+            windows: rng.below(10),
+        })
+        .collect();
+    let clients = (0..rng.below(8))
+        .map(|idx| {
+            let special = rng.below(5) == 0;
+            SnapshotClient {
+                address: format!("0x{:x}", rng.next()),
+                class: rng.text(),
+                title: rng.text(),
+                initial_class: rng.text(),
+                initial_title: rng.text(),
+                app_id: rng.text(),
+                pid: (idx % 2 == 0).then(|| rng.below(100_000) as i32),
+                workspace_id: rng.below(20) + 1,
+                workspace_name: special.then(|| "special:scratch".to_string()),
+                paired_slot: rng.below(10) + 1,
+                floating: (rng.below(3) == 0).then(|| WindowGeometry {
+                    x: rng.below(3840) as i32 - 1920,
+                    y: rng.below(2160) as i32,
+                    width: rng.below(1920) as i32 + 1,
+                    height: rng.below(1080) as i32 + 1,
+                }),
+                fullscreen: (rng.below(4) == 0).then(|| FullscreenState {
+                    internal: rng.below(4) as u8,
+                    client: rng.below(4) as u8,
+                }),
+                pseudo: rng.below(2) == 0,
+            }
+        })
+        .collect();
+    SessionSnapshot {
+        version: SESSION_VERSION,
+        created_at: rng.next() >> 32,
+        signature: rng.text(),
+        paired_offset: 10,
+        workspace_count: rng.below(10) + 1,
+        focus: SnapshotFocus {
+            monitor: rng.text(),
+            workspace_id: rng.below(20) + 1,
+        },
+        monitors,
+        workspaces,
+        clients,
+    }
+}
+
+#[test]
+fn fixed_seed_snapshot_fixtures_round_trip_and_upgrade_from_v1() {
+    let mut rng = FixtureSeed(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..256 {
+        let snapshot = fixture_snapshot(&mut rng);
+        let json = serde_json::to_string(&snapshot).expect("serialize");
+        assert_eq!(parse_snapshot(&json).expect("round trip"), snapshot);
+
+        let mut v1: serde_json::Value = serde_json::from_str(&json).expect("value");
+        v1["version"] = 1.into();
+        for monitor in v1["monitors"].as_array_mut().expect("monitors") {
+            let monitor = monitor.as_object_mut().expect("monitor");
+            for field in ["x", "y", "width", "height"] {
+                monitor.remove(field);
+            }
+        }
+        let upgraded = parse_snapshot(&v1.to_string()).expect("upgrade");
+        let expected = SessionSnapshot {
+            monitors: snapshot
+                .monitors
+                .iter()
+                .map(|monitor| SnapshotMonitor {
+                    id: monitor.id,
+                    name: monitor.name.clone(),
+                    x: 0,
+                    y: 0,
+                    width: 0,
+                    height: 0,
+                })
+                .collect(),
+            ..snapshot
+        };
+        assert_eq!(upgraded, expected);
+    }
+}
+
+fn floating_client(address: &str, floating: bool, at: [i32; 2], size: [i32; 2]) -> ClientInfo {
+    ClientInfo {
+        address: address.to_string(),
+        workspace: WorkspaceRef { id: 3, name: None },
+        class: Some("qalculate-gtk".to_string()),
+        title: None,
+        initial_class: Some("qalculate-gtk".to_string()),
+        initial_title: None,
+        app_id: None,
+        pid: None,
+        focus_history_id: None,
+        floating,
+        at: Some(at),
+        size: Some(size),
+        fullscreen: 0,
+        fullscreen_client: 0,
+        pseudo: false,
+    }
+}
+
+#[test]
+fn snapshot_records_geometry_only_for_floating_windows() {
+    let config = test_config();
+    let clients = vec![
+        floating_client("0x1", true, [100, 200], [400, 300]),
+        floating_client("0x2", false, [0, 0], [1280, 1440]),
+    ];
+
+    let snapshot = SessionSnapshot::from_state(&config, None, 3, Vec::new(), Vec::new(), clients);
+
+    assert_eq!(
+        snapshot.clients[0].floating,
+        Some(WindowGeometry {
+            x: 100,
+            y: 200,
+            width: 400,
+            height: 300,
+        })
+    );
+    assert_eq!(snapshot.clients[1].floating, None);
+}
+
+#[test]
+fn restore_floats_and_places_windows_saved_as_floating() {
+    let config = test_config();
+    let saved = vec![
+        floating_client("0x1", true, [100, 200], [400, 300]),
+        floating_client("0x2", false, [0, 0], [1280, 1440]),
+    ];
+    let snapshot = SessionSnapshot::from_state(
+        &config,
+        Some("sig".to_string()),
+        3,
+        Vec::new(),
+        Vec::new(),
+        saved,
+    );
+
+    let tiled_now = vec![floating_client("0x1", false, [0, 0], [1280, 1440])];
+    let batch = restore_batch(
+        &snapshot,
+        RestoreMode::Same,
+        Some("sig"),
+        &tiled_now,
+        &config,
+    );
+    assert_eq!(
+        batch.to_argument(),
+        "dispatch setfloating address:0x1 ; \
+         dispatch movewindowpixel exact 100 200,address:0x1 ; \
+         dispatch resizewindowpixel exact 400 300,address:0x1"
+    );
+
+    let moved_only = vec![
+        floating_client("0x1", true, [10, 20], [400, 300]),
+        floating_client("0x2", true, [5, 5], [200, 200]),
+    ];
+    let batch = restore_batch(
+        &snapshot,
+        RestoreMode::Same,
+        Some("sig"),
+        &moved_only,
+        &config,
+    );
+    assert_eq!(
+        batch.to_argument(),
+        "dispatch movewindowpixel exact 100 200,address:0x1"
+    );
+
+    let calculator = vec![floating_client("0x1", true, [100, 200], [400, 300])];
+    let snapshot =
+        SessionSnapshot::from_state(&config, None, 3, Vec::new(), Vec::new(), calculator);
+    let reopened = vec![floating_client("0x9", true, [10, 20], [640, 480])];
+    let batch = restore_batch(&snapshot, RestoreMode::Cold, None, &reopened, &config);
+    assert_eq!(
+        batch.to_argument(),
+        "dispatch movewindowpixel exact 100 200,address:0x9 ; \
+         dispatch resizewindowpixel exact 400 300,address:0x9"
+    );
+}
+
+#[test]
+fn restore_brings_back_fullscreen_and_pseudo_windows_then_refocuses() {
+    let config = test_config();
+    let mut browser = floating_client("0x1", false, [0, 0], [2560, 1440]);
+    browser.workspace = WorkspaceRef { id: 12, name: None };
+    browser.fullscreen = 2;
+    browser.fullscreen_client = 2;
+    let mut editor = floating_client("0x2", false, [0, 0], [1280, 1440]);
+    editor.pseudo = true;
+    let snapshot = SessionSnapshot::from_state(
+        &config,
+        Some("sig".to_string()),
+        3,
+        Vec::new(),
+        Vec::new(),
+        vec![browser, editor],
+    );
+    assert_eq!(
+        snapshot.clients[0].fullscreen,
+        Some(FullscreenState {
+            internal: 2,
+            client: 2,
+        })
+    );
+    assert!(snapshot.clients[1].pseudo);
+
+    let mut browser = floating_client("0x1", false, [0, 0], [2560, 1440]);
+    browser.workspace = WorkspaceRef { id: 12, name: None };
+    let mut editor = floating_client("0x2", false, [0, 0], [1280, 1440]);
+    editor.focus_history_id = Some(0);
+    let current = vec![browser, editor];
+
+    let batch = restore_batch(&snapshot, RestoreMode::Same, Some("sig"), &current, &config);
+
+    assert_eq!(
+        batch.to_argument(),
+        "dispatch focuswindow address:0x1 ; \
+         dispatch fullscreenstate 2 2 ; \
+         dispatch pseudo address:0x2 ; \
+         dispatch focuswindow address:0x2"
+    );
+}
+
+#[test]
+fn missing_monitors_lists_saved_outputs_that_are_gone() {
+    let mut snapshot = diff_snapshot("sig", "DP-1", Vec::new());
+    snapshot.monitors = ["DP-1", "HDMI-A-1"]
+        .into_iter()
+        .enumerate()
+        .map(|(id, name)| SnapshotMonitor {
+            id: id as i32,
+            name: name.to_string(),
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+        })
+        .collect();
+    let laptop = vec![MonitorInfo {
+        name: "DP-1".to_string(),
+        ..MonitorInfo::default()
+    }];
+
+    assert_eq!(missing_monitors(&snapshot, &laptop), ["HDMI-A-1"]);
+}
+
+#[test]
+fn collapse_maps_every_window_onto_its_pair_primary_workspace() {
+    let config = test_config();
+    let mut special = diff_client("0x4", "kitty", 99);
+    special.workspace_name = Some("special:term".to_string());
+    let mut snapshot = diff_snapshot(
+        "sig",
+        "DP-1",
+        vec![
+            diff_client("0x1", "firefox", 13),
+            diff_client("0x2", "code", 2),
+            diff_client("0x3", "mpv", 20),
+            special,
+        ],
+    );
+
+    collapse_to_primary(&mut snapshot, &config);
+
+    let placed: Vec<_> = snapshot
+        .clients
+        .iter()
+        .map(|client| (client.workspace_id, client.paired_slot))
+        .collect();
+    assert_eq!(placed, [(3, 3), (2, 2), (10, 10), (99, 99)]);
+    assert_eq!(
+        snapshot.clients[3].workspace_name.as_deref(),
+        Some("special:term")
+    );
+}